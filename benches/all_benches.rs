@@ -15,7 +15,7 @@ use narangcia_cryptic::{
         password::Argon2PasswordManager,
         token::{TokenService, jwt::JwtTokenService},
         user::{
-            User,
+            User, UserId,
             persistence::{InMemoryUserRepo, UserRepository},
         },
     },
@@ -66,6 +66,37 @@ fn bench_argon2_verify(c: &mut Criterion) {
     });
 }
 
+/// Benchmark for hashing a password with an Argon2 hasher reusing pooled memory.
+/// Compare with `argon2_hash` to see the cost of allocating Argon2 memory per call.
+fn bench_argon2_hash_pooled(c: &mut Criterion) {
+    let hasher = Argon2Hasher::new().with_buffer_pool(1);
+    let password = b"benchmark_password_12345";
+    let salt = generate_secure_salt().unwrap();
+
+    c.bench_function("argon2_hash_pooled", |b| {
+        b.iter(|| {
+            let hash = hasher.hash(black_box(password), Some(&salt));
+            black_box(hash)
+        })
+    });
+}
+
+/// Benchmark for verifying a password hash with an Argon2 hasher reusing pooled memory.
+/// Compare with `argon2_verify` to see the cost of allocating Argon2 memory per call.
+fn bench_argon2_verify_pooled(c: &mut Criterion) {
+    let hasher = Argon2Hasher::new().with_buffer_pool(1);
+    let password = b"benchmark_password_12345";
+    let salt = generate_secure_salt().unwrap();
+    let hash = hasher.hash(password, Some(&salt)).unwrap();
+
+    c.bench_function("argon2_verify_pooled", |b| {
+        b.iter(|| {
+            let result = hasher.verify(black_box(password), black_box(&hash));
+            black_box(result)
+        })
+    });
+}
+
 // --- JWT Token Benchmarks ---
 /// Benchmark for generating a JWT token pair (access and refresh tokens).
 /// Uses a static user ID and secret key for repeatability.
@@ -182,7 +213,8 @@ fn bench_in_memory_repo_add_user(c: &mut Criterion) {
     group.finish();
 }
 
-/// Benchmarks for in-memory repository operations: get by ID, get by identifier, and update user.
+/// Benchmarks for in-memory repository operations: get by ID, get by identifier, get credentials
+/// by identifier, and update user.
 /// Pre-populates the repository with 1000 users and measures the time for each operation.
 fn bench_in_memory_repo_operations(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
@@ -214,7 +246,7 @@ fn bench_in_memory_repo_operations(c: &mut Criterion) {
 
     group.bench_function("get_user_by_id", |b| {
         b.to_async(&rt).iter(|| async {
-            let user_id = format!("bench_user_{}", black_box(500));
+            let user_id = UserId::from(format!("bench_user_{}", black_box(500)));
             let result = repo.get_user_by_id(&user_id).await;
             black_box(result)
         })
@@ -228,6 +260,15 @@ fn bench_in_memory_repo_operations(c: &mut Criterion) {
         })
     });
 
+    // Compare with `get_user_by_identifier`: the credentials-only lookup used by logins
+    group.bench_function("get_credentials_by_identifier", |b| {
+        b.to_async(&rt).iter(|| async {
+            let identifier = format!("bench_username_{}", black_box(500));
+            let result = repo.get_credentials_by_identifier(&identifier).await;
+            black_box(result)
+        })
+    });
+
     group.bench_function("update_user", |b| {
         b.to_async(&rt).iter(|| async {
             let mut user = users[500].clone();
@@ -431,7 +472,9 @@ criterion_group!(
     hash_benches,
     bench_generate_secure_salt,
     bench_argon2_hash,
-    bench_argon2_verify
+    bench_argon2_verify,
+    bench_argon2_hash_pooled,
+    bench_argon2_verify_pooled
 );
 
 criterion_group!(
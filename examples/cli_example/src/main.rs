@@ -237,8 +237,8 @@ async fn signup_user(
                 "✅ User '{}' successfully registered!",
                 user.credentials
                     .as_ref()
-                    .map(|c| &c.identifier)
-                    .unwrap_or(&user.id)
+                    .map(|c| c.identifier.as_str())
+                    .unwrap_or(user.id.as_str())
             );
         }
         Err(e) => {
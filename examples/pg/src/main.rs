@@ -116,7 +116,7 @@ async fn main() {
                 return;
             }
             let user = User {
-                id: args[3].clone(),
+                id: args[3].clone().into(),
                 credentials: narangcia_cryptic::core::credentials::Credentials {
                     user_id: args[3].clone(),
                     identifier: args[4].clone(),
@@ -133,7 +133,7 @@ async fn main() {
                 eprintln!("Usage: get_user_by_id <id>");
                 return;
             }
-            match repo.get_user_by_id(&args[3]).await {
+            match repo.get_user_by_id(&args[3].clone().into()).await {
                 Some(u) => println!("User: id={} identifier={}", u.id, u.credentials.identifier),
                 None => println!("User not found"),
             }
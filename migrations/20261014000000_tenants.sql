@@ -0,0 +1,10 @@
+-- Multi-tenant support: identifiers are only unique within a tenant.
+ALTER TABLE cryptic_users ADD COLUMN tenant_id VARCHAR(255);
+
+ALTER TABLE cryptic_credentials ADD COLUMN tenant_id VARCHAR(255);
+ALTER TABLE cryptic_credentials DROP CONSTRAINT cryptic_credentials_identifier_key;
+-- Two partial indexes rather than UNIQUE NULLS NOT DISTINCT, which needs PostgreSQL 15
+CREATE UNIQUE INDEX cryptic_credentials_tenant_identifier_key
+  ON cryptic_credentials (tenant_id, identifier) WHERE tenant_id IS NOT NULL;
+CREATE UNIQUE INDEX cryptic_credentials_identifier_key
+  ON cryptic_credentials (identifier) WHERE tenant_id IS NULL;
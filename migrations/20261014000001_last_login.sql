@@ -0,0 +1,2 @@
+-- Track the last successful login of each user.
+ALTER TABLE cryptic_users ADD COLUMN last_login_at TIMESTAMP;
@@ -0,0 +1,2 @@
+-- Record the scopes granted at the last authorization of each linked OAuth account.
+ALTER TABLE cryptic_oauth_accounts ADD COLUMN granted_scopes TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,2 @@
+-- Soft-disable users without deleting them.
+ALTER TABLE cryptic_users ADD COLUMN status VARCHAR(16) NOT NULL DEFAULT 'active';
@@ -0,0 +1,2 @@
+-- Per-user Argon2 targets (e.g., `m=19456,t=2,p=1`); NULL uses the service-wide target.
+ALTER TABLE cryptic_users ADD COLUMN hash_target VARCHAR(64);
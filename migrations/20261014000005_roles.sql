@@ -0,0 +1,2 @@
+-- Roles granted to each user (e.g., `admin`).
+ALTER TABLE cryptic_users ADD COLUMN roles TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,2 @@
+-- Version of each user, bumped by every update for optimistic concurrency control.
+ALTER TABLE cryptic_users ADD COLUMN version BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,14 @@
+-- API keys authenticating as a user, stored as keyed HMAC-SHA256 tags.
+CREATE TABLE cryptic_api_keys
+(
+  id VARCHAR(64) PRIMARY KEY,
+  user_id UUID NOT NULL,
+  label VARCHAR(255) NOT NULL,
+  secret_hash VARCHAR(255) NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  last_used_at TIMESTAMP,
+  FOREIGN KEY (user_id) REFERENCES cryptic_users(id) ON DELETE CASCADE
+);
+
+-- Index for listing the keys of a user
+CREATE INDEX idx_api_keys_user ON cryptic_api_keys(user_id);
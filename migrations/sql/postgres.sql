@@ -4,35 +4,50 @@
 -- This schema defines the core tables for user authentication and credential management in the Cryptic system.
 --
 -- Tables:
---   - cryptic_users: Stores user identities (UUID primary key) with timestamps.
+--   - cryptic_users: Stores user identities (UUID primary key) with timestamps and optional tenant.
 --   - cryptic_credentials: Stores user credentials, including unique identifier and password hash.
 --   - cryptic_oauth_accounts: Stores OAuth account linkings to users.
+--   - cryptic_api_keys: Stores API keys authenticating as users.
 --
 -- Relationships:
 --   - Each credential is linked to a user via user_id (foreign key).
 --   - Each OAuth account is linked to a user via user_id (foreign key).
---   - Deleting a user cascades to delete their credentials and OAuth accounts.
+--   - Each API key is linked to a user via user_id (foreign key).
+--   - Deleting a user cascades to delete their credentials, OAuth accounts and API keys.
 --
 -- Notes:
---   - Identifiers (e.g., email, username) must be unique.
---   - Passwords are stored as secure hashes, not plaintext.
+--   - Identifiers (e.g., email, username) must be unique within a tenant.
+--   - Passwords are stored as secure hashes, not plaintext; API keys as keyed HMAC-SHA256 tags.
 --   - OAuth accounts are identified by provider and provider_user_id combination.
 --
 CREATE TABLE cryptic_users
 (
   id UUID PRIMARY KEY,
   created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
-  updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+  updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  tenant_id VARCHAR(255),
+  last_login_at TIMESTAMP,
+  status VARCHAR(16) NOT NULL DEFAULT 'active',
+  hash_target VARCHAR(64),
+  roles TEXT[] NOT NULL DEFAULT '{}',
+  version BIGINT NOT NULL DEFAULT 0
 );
 
 CREATE TABLE cryptic_credentials
 (
   user_id UUID PRIMARY KEY,
-  identifier VARCHAR(255) UNIQUE NOT NULL,
+  identifier VARCHAR(255) NOT NULL,
   password_hash VARCHAR(255) NOT NULL,
+  tenant_id VARCHAR(255),
   FOREIGN KEY (user_id) REFERENCES cryptic_users(id) ON DELETE CASCADE
 );
 
+-- Identifiers are unique within a tenant, and among users without a tenant
+CREATE UNIQUE INDEX cryptic_credentials_tenant_identifier_key
+  ON cryptic_credentials (tenant_id, identifier) WHERE tenant_id IS NOT NULL;
+CREATE UNIQUE INDEX cryptic_credentials_identifier_key
+  ON cryptic_credentials (identifier) WHERE tenant_id IS NULL;
+
 CREATE TABLE cryptic_oauth_accounts
 (
   user_id UUID NOT NULL,
@@ -45,6 +60,7 @@ CREATE TABLE cryptic_oauth_accounts
   locale VARCHAR(10),
   updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
   raw_data JSONB,
+  granted_scopes TEXT[] NOT NULL DEFAULT '{}',
   PRIMARY KEY (user_id, provider),
   UNIQUE (provider, provider_user_id),
   FOREIGN KEY (user_id) REFERENCES cryptic_users(id) ON DELETE CASCADE
@@ -55,3 +71,17 @@ CREATE INDEX idx_oauth_provider_user ON cryptic_oauth_accounts(provider, provide
 
 -- Index for faster OAuth lookups by email
 CREATE INDEX idx_oauth_email ON cryptic_oauth_accounts(email) WHERE email IS NOT NULL;
+
+CREATE TABLE cryptic_api_keys
+(
+  id VARCHAR(64) PRIMARY KEY,
+  user_id UUID NOT NULL,
+  label VARCHAR(255) NOT NULL,
+  secret_hash VARCHAR(255) NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  last_used_at TIMESTAMP,
+  FOREIGN KEY (user_id) REFERENCES cryptic_users(id) ON DELETE CASCADE
+);
+
+-- Index for listing the keys of a user
+CREATE INDEX idx_api_keys_user ON cryptic_api_keys(user_id);
@@ -90,6 +90,40 @@ pub struct AuthService {
     /// The token manager responsible for generating and validating authentication tokens.
     pub token_manager: Box<dyn crate::core::token::TokenService + Send + Sync>,
     pub oauth2_manager: Box<dyn crate::core::oauth::OAuth2Service + Send + Sync>,
+    /// The store recording consumed single-use tokens (magic links, reset and verification tokens).
+    pub one_time_tokens: Box<dyn crate::core::token::one_time::OneTimeTokenStore + Send + Sync>,
+    /// The store holding pending email one-time passwords.
+    pub email_otps: Box<dyn crate::core::otp::OtpStore + Send + Sync>,
+    /// The store holding OAuth2 accounts waiting for their link to be confirmed.
+    pub pending_links: Box<dyn crate::core::oauth::link::PendingLinkStore + Send + Sync>,
+    /// The store tracking issued sessions and their revocation.
+    pub sessions: Box<dyn crate::core::token::session::SessionStore + Send + Sync>,
+    /// The store holding API keys.
+    pub api_keys: Box<dyn crate::core::api_key::ApiKeyStore + Send + Sync>,
+    /// The store counting hits against the per-user and per-IP rate limits.
+    pub rate_limits: Box<dyn crate::core::rate_limit::RateLimitStore + Send + Sync>,
+    /// The cache of recent access token validations, if enabled.
+    pub validation_cache: Option<crate::core::token::cache::ValidationCache>,
+    /// The verifier of the challenge (CAPTCHA) tokens of signups and logins, if enabled.
+    pub challenge_verifier:
+        Option<Box<dyn crate::core::challenge::ChallengeVerifier + Send + Sync>>,
+    /// The denylist of disposable email domains checked on signups, if enabled.
+    pub disposable_email_filter: Option<crate::core::policy::DisposableEmailFilter>,
+    /// The resolver canonicalizing submitted login identifiers before lookups.
+    pub identifier_resolver: Box<dyn crate::core::credentials::IdentifierResolver + Send + Sync>,
+    /// The generator proposing identifiers for OAuth2 users without an email.
+    pub username_generator: Box<dyn crate::core::credentials::UsernameGenerator + Send + Sync>,
+    /// The enricher computing custom access token claims for loaded users.
+    pub claims_enricher: Box<dyn crate::core::token::enricher::ClaimsEnricher + Send + Sync>,
+    /// The transformer rewriting the claims of validated access tokens.
+    pub claims_transformer:
+        Box<dyn crate::core::token::transformer::ClaimsTransformer + Send + Sync>,
+    /// The log receiving security audit events.
+    pub audit_log: Box<dyn crate::core::audit::AuditLog + Send + Sync>,
+    /// The notifier telling users about security events on their account.
+    pub notifier: Box<dyn crate::core::notify::Notifier + Send + Sync>,
+    /// The key of the user pseudonyms written to logs, derived from `vars.secret_key`.
+    pub log_ids: crate::core::user::LogIdKey,
 }
 
 impl Default for AuthService {
@@ -111,7 +145,127 @@ impl Default for AuthService {
                 vars.token_expiration,
                 vars.refresh_token_expiration,
             )),
+            #[cfg(feature = "oauth")]
             oauth2_manager: Box::new(crate::core::oauth::manager::OAuth2Manager::default()),
+            #[cfg(not(feature = "oauth"))]
+            oauth2_manager: Box::new(crate::core::oauth::DisabledOAuth2Service),
+            one_time_tokens: Box::new(
+                crate::core::token::one_time::InMemoryOneTimeTokenStore::new(),
+            ),
+            email_otps: Box::new(crate::core::otp::InMemoryOtpStore::new()),
+            pending_links: Box::new(crate::core::oauth::link::InMemoryPendingLinkStore::new()),
+            sessions: Box::new(crate::core::token::session::InMemorySessionStore::new()),
+            validation_cache: None,
+            challenge_verifier: None,
+            disposable_email_filter: None,
+            identifier_resolver: Box::new(crate::core::credentials::IdentityIdentifierResolver),
+            username_generator: Box::new(crate::core::credentials::ProviderUsernameGenerator),
+            claims_enricher: Box::new(crate::core::token::enricher::NoopClaimsEnricher),
+            claims_transformer: Box::new(
+                crate::core::token::transformer::IdentityClaimsTransformer,
+            ),
+            api_keys: Box::new(crate::core::api_key::InMemoryApiKeyStore::new()),
+            rate_limits: Box::new(crate::core::rate_limit::InMemoryRateLimitStore::new()),
+            audit_log: Box::new(crate::core::audit::StdoutAuditLog),
+            notifier: Box::new(crate::core::notify::NoopNotifier),
+            log_ids: crate::core::user::LogIdKey::derive(vars.secret_key.as_bytes()),
+        }
+    }
+}
+
+/// How long [`AuthService::bootstrap`] keeps other bootstraps out.
+pub const BOOTSTRAP_LEASE: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// The single-use token identifier consumed by [`AuthService::bootstrap`].
+const BOOTSTRAP_MARKER: &str = "cryptic:bootstrap";
+
+/// Builds the default password manager: Argon2 with `vars.argon2_params` and
+/// `vars.password_hashing_timeout_ms`.
+pub(crate) fn default_password_manager(
+    vars: &crate::core::vars::AuthServiceVariables,
+) -> Result<Box<dyn crate::core::password::SecurePasswordManager + Send + Sync>, AuthError> {
+    #[cfg_attr(not(feature = "tokio"), allow(unused_mut))]
+    let mut manager =
+        crate::core::password::Argon2PasswordManager::with_params(vars.argon2_params)?
+            .with_malformed_hash_behavior(vars.malformed_hash_behavior);
+    #[cfg(feature = "tokio")]
+    if let Some(timeout_ms) = vars.password_hashing_timeout_ms {
+        manager = manager.with_timeout(std::time::Duration::from_millis(timeout_ms));
+    }
+    #[cfg(not(feature = "tokio"))]
+    if vars.password_hashing_timeout_ms.is_some() {
+        return Err(AuthError::ConfigError(
+            "password_hashing_timeout_ms requires the tokio dependency, enabled by the postgres and axum features"
+                .to_string(),
+        ));
+    }
+    Ok(Box::new(manager))
+}
+
+/// Builds the default token service: JWTs signed with `vars.secret_key`, with the configured
+/// lifetimes, audiences and compression.
+pub(crate) fn default_token_service(
+    vars: &crate::core::vars::AuthServiceVariables,
+) -> Result<Box<dyn crate::core::token::TokenService + Send + Sync>, AuthError> {
+    let mut manager = crate::core::token::jwt::JwtTokenService::try_new(
+        &vars.secret_key,
+        vars.token_expiration,
+        vars.refresh_token_expiration,
+    )?
+    .with_audiences(vars.token_audiences.clone());
+    if let Some(threshold) = vars.token_compression_threshold {
+        #[cfg(feature = "jwt-compression")]
+        {
+            manager = manager.with_compression(threshold as usize);
+        }
+        #[cfg(not(feature = "jwt-compression"))]
+        return Err(AuthError::ConfigError(format!(
+            "token_compression_threshold ({threshold}) requires the `jwt-compression` feature"
+        )));
+    }
+    if let Some(strategy) = vars.refresh_strategy {
+        manager = manager.with_refresh_strategy(strategy);
+    }
+    Ok(Box::new(manager))
+}
+
+/// Rehashes the user's password with `password_manager` and the user's
+/// [`hash_target`](User::hash_target), and persists it in `users`.
+///
+/// Failures are logged and otherwise ignored so that a successful login is never rejected
+/// because the hash upgrade could not be stored.
+pub(crate) async fn rehash_password(
+    password_manager: &(dyn crate::core::password::SecurePasswordManager + Send + Sync),
+    users: &(dyn crate::core::user::persistence::UserRepository + Send + Sync),
+    log_ids: &crate::core::user::LogIdKey,
+    user: &mut User,
+    password: &str,
+) {
+    let new_hash = match password_manager
+        .hash_password_for(password, user.hash_target.as_ref())
+        .await
+    {
+        Ok(hash) => hash,
+        Err(e) => {
+            log::warn!(
+                "Failed to rehash password for user {}: {e}",
+                log_ids.log_id(&user.id)
+            );
+            return;
+        }
+    };
+    let Some(credentials) = user.credentials.as_mut() else {
+        return;
+    };
+    let previous_hash = std::mem::replace(&mut credentials.password_hash, new_hash);
+    user.updated_at = chrono::Utc::now().naive_utc();
+    if let Err(e) = users.update_user(user).await {
+        log::warn!(
+            "Failed to store rehashed password for user {}: {e}",
+            log_ids.log_id(&user.id)
+        );
+        if let Some(credentials) = user.credentials.as_mut() {
+            credentials.password_hash = previous_hash;
         }
     }
 }
@@ -121,13 +275,23 @@ impl AuthService {
     ///
     /// # Arguments
     /// * `vars` - Shared configuration and variables for the authentication service.
-    /// * `password_manager` - Optional custom password manager. If `None`, uses Argon2 by default.
+    /// * `password_manager` - Optional custom password manager. If `None`, uses Argon2 with `vars.argon2_params`
+    ///   and `vars.password_hashing_timeout_ms`.
     /// * `persistent_users_manager` - Optional custom user repository. If `None`, uses in-memory repository by default.
-    /// * `token_manager` - Optional custom token service. If `None`, uses JWT token service by default.
-    /// * `oauth2_manager` - Optional custom OAuth2 service. If `None`, uses default OAuth2 manager.
+    /// * `token_manager` - Optional custom token service. If `None`, uses JWT token service by default,
+    ///   which requires `vars.secret_key` to be at least [`MIN_HMAC_SECRET_LEN`](crate::core::token::jwt::MIN_HMAC_SECRET_LEN) bytes long.
+    /// * `oauth2_manager` - Optional custom OAuth2 service. If `None`, uses an OAuth2 manager built from `vars.oauth_configs`,
+    ///   signing `state` parameters with `vars.oauth_state_secret` if set. Without the `oauth` feature, the
+    ///   default service has no providers and every OAuth2 call fails with [`AuthError::ConfigError`].
     ///
     /// # Returns
     /// Returns an [`AuthService`] instance on success, or an [`AuthError`] if construction fails.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::ConfigError`] if the default JWT token service would be built
+    /// from a secret that is too short, if the OAuth2 state secret is too short, if the
+    /// Argon2 parameters are invalid, or if `vars.password_hashing_timeout_ms` is set without
+    /// the `tokio` dependency.
     pub fn new(
         vars: Arc<crate::core::vars::AuthServiceVariables>,
         password_manager: Option<
@@ -141,7 +305,7 @@ impl AuthService {
     ) -> Result<Self, AuthError> {
         let pwd_manager = match password_manager {
             Some(manager) => manager,
-            None => Box::new(crate::core::password::Argon2PasswordManager::default()),
+            None => default_password_manager(&vars)?,
         };
         let pum = match persistent_users_manager {
             Some(manager) => manager,
@@ -149,469 +313,3869 @@ impl AuthService {
         };
         let tk_manager = match token_manager {
             Some(manager) => manager,
-            None => Box::new(crate::core::token::jwt::JwtTokenService::new(
-                &vars.secret_key,
-                vars.token_expiration,
-                vars.refresh_token_expiration,
-            )),
+            None => default_token_service(&vars)?,
         };
         let oauth_manager = match oauth2_manager {
             Some(manager) => manager,
-            None => Box::new(crate::core::oauth::manager::OAuth2Manager::default()),
+            #[cfg(not(feature = "oauth"))]
+            None => Box::new(crate::core::oauth::DisabledOAuth2Service),
+            #[cfg(feature = "oauth")]
+            None => {
+                let mut manager =
+                    crate::core::oauth::manager::OAuth2Manager::new(vars.oauth_configs.clone());
+                if let Some(secret) = &vars.oauth_state_secret {
+                    let ttl = vars
+                        .oauth_state_ttl
+                        .unwrap_or(crate::core::oauth::state::DEFAULT_STATE_TTL);
+                    manager =
+                        manager.with_signed_state(crate::core::oauth::state::SignedState::new(
+                            secret.as_bytes(),
+                            std::time::Duration::from_secs(ttl),
+                        )?);
+                }
+                if let Some(breaker) = vars.oauth_circuit_breaker {
+                    manager = manager.with_circuit_breaker(breaker);
+                }
+                Box::new(manager)
+            }
         };
 
+        let log_ids = crate::core::user::LogIdKey::derive(vars.secret_key.as_bytes());
         Ok(AuthService {
             vars,
             password_manager: pwd_manager,
             persistent_users_manager: pum,
             token_manager: tk_manager,
             oauth2_manager: oauth_manager,
+            one_time_tokens: Box::new(
+                crate::core::token::one_time::InMemoryOneTimeTokenStore::new(),
+            ),
+            email_otps: Box::new(crate::core::otp::InMemoryOtpStore::new()),
+            pending_links: Box::new(crate::core::oauth::link::InMemoryPendingLinkStore::new()),
+            sessions: Box::new(crate::core::token::session::InMemorySessionStore::new()),
+            validation_cache: None,
+            challenge_verifier: None,
+            disposable_email_filter: None,
+            identifier_resolver: Box::new(crate::core::credentials::IdentityIdentifierResolver),
+            username_generator: Box::new(crate::core::credentials::ProviderUsernameGenerator),
+            claims_enricher: Box::new(crate::core::token::enricher::NoopClaimsEnricher),
+            claims_transformer: Box::new(
+                crate::core::token::transformer::IdentityClaimsTransformer,
+            ),
+            api_keys: Box::new(crate::core::api_key::InMemoryApiKeyStore::new()),
+            rate_limits: Box::new(crate::core::rate_limit::InMemoryRateLimitStore::new()),
+            audit_log: Box::new(crate::core::audit::StdoutAuditLog),
+            notifier: Box::new(crate::core::notify::NoopNotifier),
+            log_ids,
         })
     }
 
-    /// Authenticates a user using the specified login method.
+    /// Replaces the store used to record consumed single-use tokens.
     ///
-    /// Supports both credentials-based and OAuth2-based login flows.
+    /// The default is an in-memory store, which is not shared between instances.
     ///
     /// # Arguments
-    /// * `method` - The authentication method to use for login. See [`LoginMethod`].
+    /// * `store` - The single-use token store to use.
     ///
     /// # Returns
-    /// Returns a tuple `(User, TokenPair)` if login is successful, or an [`AuthError`] if authentication fails.
+    /// The updated [`AuthService`].
+    pub fn with_one_time_token_store(
+        mut self,
+        store: Box<dyn crate::core::token::one_time::OneTimeTokenStore + Send + Sync>,
+    ) -> Self {
+        self.one_time_tokens = store;
+        self
+    }
+
+    /// Consumes a single-use token, failing if it was already used.
+    ///
+    /// Flows honoring single-use tokens (magic links, password resets, email verification)
+    /// must call this after validating the token and before acting on it. Consumption is
+    /// atomic, so concurrent submissions of the same token succeed exactly once.
+    ///
+    /// # Arguments
+    /// * `jti` - The unique identifier of the token.
+    /// * `expires_at` - The token's expiration (UNIX timestamp, seconds).
     ///
     /// # Errors
-    /// Returns [`AuthError::InvalidCredentials`] if credentials are invalid, or other variants for OAuth2 failures.
-    pub async fn login(
+    /// Returns [`AuthError::InvalidToken`] if the token was already consumed, or
+    /// [`AuthError::ServiceUnavailable`] if the store is unavailable.
+    pub async fn consume_one_time_token(
         &self,
-        method: LoginMethod,
-    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
-        match method {
-            LoginMethod::Credentials {
-                identifier,
-                password,
-            } => {
-                // Find user by identifier
-                let stored_user = self
-                    .persistent_users_manager
-                    .get_user_by_identifier(&identifier)
-                    .await
-                    .ok_or(AuthError::InvalidCredentials)?;
-
-                // Verify the password using the password manager from the service
-                let credentials = stored_user
-                    .credentials
-                    .as_ref()
-                    .ok_or(AuthError::InvalidCredentials)?;
-
-                let is_valid = self
-                    .password_manager
-                    .verify_password(&password, &credentials.password_hash)
-                    .await
-                    .map_err(|e| {
-                        AuthError::PasswordVerificationError(format!(
-                            "Password verification failed: {e}"
-                        ))
-                    })?;
-
-                if !is_valid {
-                    return Err(AuthError::InvalidCredentials);
-                }
-
-                // Generate tokens
-                let tokens = self.get_tokens(stored_user.id.clone()).await?;
-                Ok((stored_user, tokens))
-            }
-            LoginMethod::OAuth2 {
-                provider,
-                code,
-                state,
-            } => {
-                // Exchange code for token
-                let oauth_token = self
-                    .exchange_oauth2_code_for_token(provider, &code, &state)
-                    .await?;
-
-                // Fetch user info from OAuth provider
-                let oauth_user_info = self.fetch_oauth2_user_info(&oauth_token).await?;
-
-                // Try to find existing user by OAuth provider and user ID
-                let existing_user = self
-                    .persistent_users_manager
-                    .get_user_by_oauth_id(provider, &oauth_user_info.provider_user_id)
-                    .await;
-
-                let user = if let Some(mut user) = existing_user {
-                    // Update OAuth account info
-                    user.oauth_accounts.insert(provider, oauth_user_info);
-                    user.updated_at = chrono::Utc::now().naive_utc();
-                    self.persistent_users_manager.update_user(&user).await?;
-                    user
-                } else {
-                    // Check if user exists by email (if provided)
-                    let existing_user_by_email = if let Some(ref email) = oauth_user_info.email {
-                        self.persistent_users_manager
-                            .get_user_by_identifier(email)
-                            .await
-                    } else {
-                        None
-                    };
-
-                    if let Some(mut user) = existing_user_by_email {
-                        // Link OAuth account to existing user
-                        user.oauth_accounts.insert(provider, oauth_user_info);
-                        user.updated_at = chrono::Utc::now().naive_utc();
-                        self.persistent_users_manager.update_user(&user).await?;
-                        user
-                    } else {
-                        // Create new user
-                        let mut new_user = User {
-                            id: uuid::Uuid::new_v4().to_string(),
-                            ..User::default()
-                        };
-                        new_user.oauth_accounts.insert(provider, oauth_user_info);
-                        new_user.created_at = chrono::Utc::now().naive_utc();
-                        new_user.updated_at = new_user.created_at;
-
-                        self.persistent_users_manager
-                            .add_user(new_user.clone())
-                            .await?;
-                        new_user
-                    }
-                };
-
-                // Generate tokens for the user
-                let tokens = self.get_tokens(user.id.clone()).await?;
-                Ok((user, tokens))
-            }
+        jti: &str,
+        expires_at: usize,
+    ) -> Result<(), AuthError> {
+        if self.one_time_tokens.consume(jti, expires_at).await? {
+            Ok(())
+        } else {
+            Err(AuthError::InvalidToken(
+                "Token was already used".to_string(),
+            ))
         }
     }
 
-    /// Registers a new user using the specified signup method.
+    /// Validates an access token and consumes it, so it authorizes a single request.
     ///
-    /// Supports both credentials-based and OAuth2-based registration flows.
+    /// High-value operations (e.g., a payout or an account deletion) can demand that the
+    /// access token presented for them is never replayed, even within its lifetime. The token
+    /// is validated as by [`AuthService::validate_access_token`], then its `jti` is recorded in
+    /// the single-use token store until the token expires. Normal validations of the token are
+    /// unaffected: only calls to this method reject its reuse.
     ///
     /// # Arguments
-    /// * `method` - The registration method to use for signup. See [`SignupMethod`].
+    /// * `token` - The access token to consume.
     ///
     /// # Returns
-    /// Returns a tuple `(User, TokenPair)` if signup is successful, or an [`AuthError`] if registration fails.
+    /// The token claims, the first time the token is consumed.
     ///
     /// # Errors
-    /// Returns [`AuthError::SignupError`] or other variants for OAuth2 failures.
-    pub async fn signup(
+    /// Returns the errors of [`AuthService::validate_access_token`],
+    /// [`AuthError::InvalidToken`] if the token has no `jti` claim,
+    /// [`AuthError::TokenReuseDetected`] if the token was already consumed, or
+    /// [`AuthError::ServiceUnavailable`] if the store is unavailable.
+    pub async fn consume_one_time_access(
         &self,
-        method: SignupMethod,
-    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
-        match method {
-            SignupMethod::Credentials {
-                identifier,
-                password,
-            } => {
-                // Create user with credentials
-                let user = User::with_plain_password(
-                    self.password_manager.as_ref(),
-                    uuid::Uuid::new_v4().to_string(),
-                    identifier,
-                    crate::core::credentials::PlainPassword::new(password),
-                )
-                .await?;
+        token: &str,
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        self.consume_one_time_access_in_tenant(token, None).await
+    }
 
-                // Register the user
-                self.persistent_users_manager
-                    .add_user(user.clone())
-                    .await
-                    .map_err(|e| AuthError::SignupError(format!("signup: {e}")))?;
+    /// Validates an access token issued for `tenant_id` and consumes it.
+    async fn consume_one_time_access_in_tenant(
+        &self,
+        token: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        let claims = self
+            .validate_access_token_in_tenant(token, tenant_id)
+            .await?;
+        let jti = claims
+            .get_token_id()
+            .ok_or_else(|| AuthError::InvalidToken("Token has no jti claim".to_string()))?;
+        if !self
+            .one_time_tokens
+            .consume(jti, claims.get_expiration())
+            .await?
+        {
+            log::warn!(
+                "Replayed one-time access token of {}",
+                self.log_id(&crate::core::user::UserId::from(claims.get_subject()))
+            );
+            return Err(AuthError::TokenReuseDetected);
+        }
+        Ok(claims)
+    }
 
-                // Generate tokens
-                let tokens = self.get_tokens(user.id.clone()).await?;
-                Ok((user, tokens))
-            }
-            SignupMethod::OAuth2 {
-                provider,
-                code,
-                state,
-            } => {
-                // Exchange code for token
-                let oauth_token = self
-                    .exchange_oauth2_code_for_token(provider, &code, &state)
-                    .await?;
+    /// Returns the pseudonym of a user ID for log lines.
+    ///
+    /// Log it instead of the user's ID or identifier (e.g., an email address) to keep logs
+    /// correlatable without personal data. The key is derived from `vars.secret_key`, so
+    /// pseudonyms stay stable across restarts and instances sharing the secret.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user ID.
+    pub fn log_id(&self, user_id: &crate::core::user::UserId) -> String {
+        self.log_ids.log_id(user_id)
+    }
 
-                // Fetch user info from OAuth provider
-                let oauth_user_info = self.fetch_oauth2_user_info(&oauth_token).await?;
+    /// Returns a signer of expiring URLs (e.g., download or confirmation links) using the
+    /// service's secret key.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::ConfigError`] if the secret key is too short.
+    pub fn signed_urls(&self) -> Result<crate::core::signed_url::SignedUrl, AuthError> {
+        crate::core::signed_url::SignedUrl::new(self.vars.secret_key.as_bytes())
+    }
 
-                // Try to find existing user by OAuth provider and user ID
-                let existing_user = self
-                    .persistent_users_manager
-                    .get_user_by_oauth_id(provider, &oauth_user_info.provider_user_id)
-                    .await;
-
-                let user = if let Some(mut user) = existing_user {
-                    // Update OAuth account info
-                    user.oauth_accounts.insert(provider, oauth_user_info);
-                    user.updated_at = chrono::Utc::now().naive_utc();
-                    self.persistent_users_manager.update_user(&user).await?;
-                    user
-                } else {
-                    // Check if user exists by email (if provided)
-                    let existing_user_by_email = if let Some(ref email) = oauth_user_info.email {
-                        self.persistent_users_manager
-                            .get_user_by_identifier(email)
-                            .await
-                    } else {
-                        None
-                    };
-
-                    if let Some(mut user) = existing_user_by_email {
-                        // Link OAuth account to existing user
-                        user.oauth_accounts.insert(provider, oauth_user_info);
-                        user.updated_at = chrono::Utc::now().naive_utc();
-                        self.persistent_users_manager.update_user(&user).await?;
-                        user
-                    } else {
-                        // Create new user
-                        let mut new_user = User {
-                            id: uuid::Uuid::new_v4().to_string(),
-                            ..User::default()
-                        };
-                        new_user.oauth_accounts.insert(provider, oauth_user_info);
-                        new_user.created_at = chrono::Utc::now().naive_utc();
-                        new_user.updated_at = new_user.created_at;
-
-                        self.persistent_users_manager
-                            .add_user(new_user.clone())
-                            .await?;
-                        new_user
-                    }
-                };
+    /// Replaces the store used to hold pending email one-time passwords.
+    ///
+    /// The default is an in-memory store, which is not shared between instances.
+    ///
+    /// # Arguments
+    /// * `store` - The one-time password store to use.
+    ///
+    /// # Returns
+    /// The updated [`AuthService`].
+    pub fn with_otp_store(
+        mut self,
+        store: Box<dyn crate::core::otp::OtpStore + Send + Sync>,
+    ) -> Self {
+        self.email_otps = store;
+        self
+    }
 
-                // Generate tokens for the user
-                let tokens = self.get_tokens(user.id.clone()).await?;
-                Ok((user, tokens))
-            }
-        }
+    /// Replaces the store used to track sessions and their revocation.
+    ///
+    /// The default is an in-memory store, which is not shared between instances.
+    ///
+    /// # Arguments
+    /// * `store` - The session store to use.
+    ///
+    /// # Returns
+    /// The updated [`AuthService`].
+    pub fn with_session_store(
+        mut self,
+        store: Box<dyn crate::core::token::session::SessionStore + Send + Sync>,
+    ) -> Self {
+        self.sessions = store;
+        self
     }
 
-    /// Generates a new token pair (access and refresh tokens) for a given user ID.
+    /// Replaces the store used to hold API keys.
+    ///
+    /// The default is an in-memory store, which is not shared between instances and loses
+    /// keys on restart.
     ///
     /// # Arguments
-    /// * `id` - The user ID for which to generate tokens.
+    /// * `store` - The API key store to use.
     ///
     /// # Returns
-    /// Returns a [`TokenPair`] containing access and refresh tokens, or an [`AuthError`] if generation fails.
-    pub async fn get_tokens(&self, id: String) -> Result<crate::core::token::TokenPair, AuthError> {
-        self.token_manager.generate_token_pair(&id).await
+    /// The updated [`AuthService`].
+    pub fn with_api_key_store(
+        mut self,
+        store: Box<dyn crate::core::api_key::ApiKeyStore + Send + Sync>,
+    ) -> Self {
+        self.api_keys = store;
+        self
     }
 
-    /// Validates an access token and returns the associated claims.
+    /// Replaces the store used to count hits against the rate limits.
+    ///
+    /// The default is an in-memory store, which is not shared between instances.
     ///
     /// # Arguments
-    /// * `token` - The access token to validate.
+    /// * `store` - The rate limit store to use.
     ///
     /// # Returns
-    /// Returns the token claims if valid, or an [`AuthError`] if validation fails.
-    pub async fn validate_access_token(
-        &self,
-        token: &str,
-    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
-        self.token_manager.validate_access_token(token).await
+    /// The updated [`AuthService`].
+    pub fn with_rate_limit_store(
+        mut self,
+        store: Box<dyn crate::core::rate_limit::RateLimitStore + Send + Sync>,
+    ) -> Self {
+        self.rate_limits = store;
+        self
     }
 
-    /// Refreshes an access token using a valid refresh token.
+    /// Enables caching of access token validations.
+    ///
+    /// Tokens whose `jti` passed validation within the cache TTL skip the revocation and user
+    /// status checks, which bounds store reads under high request rates. Entries of a user are
+    /// dropped when [`AuthService::revoke_all_for_user`] or [`AuthService::set_user_status`] is
+    /// called on this instance. Disabled by default.
     ///
     /// # Arguments
-    /// * `refresh_token` - The refresh token to use for generating a new access token.
+    /// * `cache` - The validation cache to use.
     ///
     /// # Returns
-    /// Returns a new [`TokenPair`] if the refresh token is valid, or an [`AuthError`] if refresh fails.
-    pub async fn refresh_access_token(
-        &self,
-        refresh_token: &str,
-    ) -> Result<crate::core::token::TokenPair, AuthError> {
-        self.token_manager.refresh_access_token(refresh_token).await
+    /// The updated [`AuthService`].
+    pub fn with_validation_cache(
+        mut self,
+        cache: crate::core::token::cache::ValidationCache,
+    ) -> Self {
+        self.validation_cache = Some(cache);
+        self
     }
 
-    /// Validates a token and extracts the user ID (subject) from it.
+    /// Requires a solved challenge (e.g., a CAPTCHA) on signups and logins made through
+    /// [`AuthService::signup_with_challenge`] and [`AuthService::login_with_challenge`], or
+    /// their [`TenantAuthService`] counterparts.
+    ///
+    /// The check is opt-in per call: [`AuthService::signup`], [`AuthService::login`] and the
+    /// tenant `signup` and `login` never check challenges, so trusted callers (e.g.,
+    /// administration tools) are unaffected. Endpoints reachable by untrusted clients must call
+    /// the `_with_challenge` methods for the verifier to protect them. Disabled by default.
     ///
     /// # Arguments
-    /// * `token` - The token to validate and extract the user ID from.
+    /// * `verifier` - The verifier of challenge tokens.
     ///
     /// # Returns
-    /// Returns the user ID as a `String` if the token is valid, or an [`AuthError`] if validation fails.
-    pub async fn get_user_id_from_token(&self, token: &str) -> Result<String, AuthError> {
-        let claims = self.validate_access_token(token).await?;
-        Ok(claims.get_subject().to_string())
+    /// The updated [`AuthService`].
+    pub fn with_challenge_verifier(
+        mut self,
+        verifier: Box<dyn crate::core::challenge::ChallengeVerifier + Send + Sync>,
+    ) -> Self {
+        self.challenge_verifier = Some(verifier);
+        self
     }
 
-    /// Checks if a token is expired by attempting to validate it.
+    /// Checks the challenge token of a request, if a challenge verifier is set.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::ChallengeFailed`] if the token is missing or rejected, or the
+    /// other errors of the verifier.
+    async fn verify_challenge(&self, challenge_token: Option<&str>) -> Result<(), AuthError> {
+        let Some(verifier) = &self.challenge_verifier else {
+            return Ok(());
+        };
+        match challenge_token.filter(|token| !token.is_empty()) {
+            Some(token) => verifier.verify(token).await,
+            None => Err(AuthError::ChallengeFailed(
+                "missing challenge token".to_string(),
+            )),
+        }
+    }
+
+    /// Rejects signups with email addresses at disposable domains.
+    ///
+    /// The filter is checked on the identifier of credentials signups and profile completions,
+    /// and on the email of OAuth2 accounts about to create a user or be linked to one by email,
+    /// which fail with [`AuthError::DisposableEmailNotAllowed`]. Existing users and OAuth2
+    /// accounts that are already linked are unaffected. Disabled by default.
     ///
     /// # Arguments
-    /// * `token` - The token to check for expiration.
+    /// * `filter` - The denylist of disposable domains.
     ///
     /// # Returns
-    /// Returns `true` if the token is expired or invalid, `false` otherwise.
-    pub async fn is_token_expired(&self, token: &str) -> bool {
-        self.validate_access_token(token).await.is_err()
+    /// The updated [`AuthService`].
+    pub fn with_disposable_email_filter(
+        mut self,
+        filter: crate::core::policy::DisposableEmailFilter,
+    ) -> Self {
+        self.disposable_email_filter = Some(filter);
+        self
     }
 
-    /// Validates a token and retrieves the associated user from the repository.
+    /// Checks `email` against the disposable email filter, if enabled.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::DisposableEmailNotAllowed`] if its domain is denied.
+    fn ensure_not_disposable(&self, email: &str) -> Result<(), AuthError> {
+        match &self.disposable_email_filter {
+            Some(filter) => filter.check(email),
+            None => Ok(()),
+        }
+    }
+
+    /// Replaces the resolver canonicalizing login identifiers.
+    ///
+    /// The resolver runs on the identifier of every credentials login and signup and of email
+    /// one-time password requests and logins, before the repository is queried. The default
+    /// uses identifiers as submitted.
     ///
     /// # Arguments
-    /// * `token` - The token to validate and extract the user from.
+    /// * `resolver` - The identifier resolver to use.
     ///
     /// # Returns
-    /// Returns the [`User`] if the token is valid and the user exists, or an [`AuthError`] otherwise.
-    pub async fn get_user_from_token(&self, token: &str) -> Result<User, AuthError> {
-        let user_id = self.get_user_id_from_token(token).await?;
-        self.persistent_users_manager
-            .get_user_by_id(&user_id)
-            .await
-            .ok_or(AuthError::UserNotFound)
+    /// The updated [`AuthService`].
+    pub fn with_identifier_resolver(
+        mut self,
+        resolver: Box<dyn crate::core::credentials::IdentifierResolver + Send + Sync>,
+    ) -> Self {
+        self.identifier_resolver = resolver;
+        self
     }
 
-    // OAuth2 Methods
+    /// Replaces the generator proposing identifiers for OAuth2 users without an email.
+    ///
+    /// The default is [`ProviderUsernameGenerator`](crate::core::credentials::ProviderUsernameGenerator).
+    ///
+    /// # Arguments
+    /// * `generator` - The username generator to use.
+    ///
+    /// # Returns
+    /// The updated [`AuthService`].
+    pub fn with_username_generator(
+        mut self,
+        generator: Box<dyn crate::core::credentials::UsernameGenerator + Send + Sync>,
+    ) -> Self {
+        self.username_generator = generator;
+        self
+    }
 
-    /// Generates an OAuth2 authorization URL for the specified provider.
+    /// Replaces the enricher computing custom access token claims.
+    ///
+    /// The enricher runs whenever tokens are issued for a user the service has loaded: logins,
+    /// signups, OAuth2 logins, email one-time password logins and link confirmations. Tokens
+    /// issued from a bare user ID ([`AuthService::get_tokens`] and its variants) are not
+    /// enriched, and [`AuthService::refresh_access_token_enriched`] runs it again on refresh.
+    /// The default adds no claims.
     ///
     /// # Arguments
-    /// * `provider` - The OAuth2 provider to generate the URL for.
-    /// * `state` - A state parameter for CSRF protection.
-    /// * `scopes` - Optional additional scopes beyond the default ones.
+    /// * `enricher` - The claims enricher to use.
     ///
     /// # Returns
-    /// Returns the authorization URL as a `String` if successful, or an [`AuthError`] if generation fails.
-    pub async fn generate_oauth2_auth_url(
-        &self,
-        provider: crate::core::oauth::store::OAuth2Provider,
-        state: &str,
-        scopes: Option<Vec<String>>,
-    ) -> Result<String, AuthError> {
-        self.oauth2_manager
-            .generate_auth_url(provider, state, scopes)
-            .await
+    /// The updated [`AuthService`].
+    pub fn with_claims_enricher(
+        mut self,
+        enricher: Box<dyn crate::core::token::enricher::ClaimsEnricher + Send + Sync>,
+    ) -> Self {
+        self.claims_enricher = enricher;
+        self
     }
 
-    /// Exchanges an OAuth2 authorization code for an access token.
+    /// Replaces the transformer rewriting the claims of validated access tokens.
+    ///
+    /// It runs in [`AuthService::validate_access_token`] and the validations built on it, once
+    /// the token's signature, expiration, session and user have been checked, e.g. to map
+    /// internal role names to external ones. The default returns the claims unchanged.
     ///
     /// # Arguments
-    /// * `provider` - The OAuth2 provider.
-    /// * `code` - The authorization code received from the provider.
-    /// * `state` - The state parameter for verification.
+    /// * `transformer` - The claims transformer to use.
     ///
     /// # Returns
-    /// Returns an [`OAuth2Token`] if successful, or an [`AuthError`] if the token exchange fails.
-    pub async fn exchange_oauth2_code_for_token(
-        &self,
-        provider: crate::core::oauth::store::OAuth2Provider,
-        code: &str,
-        state: &str,
-    ) -> Result<crate::core::oauth::store::OAuth2Token, AuthError> {
-        self.oauth2_manager
-            .exchange_code_for_token(provider, code, state)
-            .await
+    /// The updated [`AuthService`].
+    pub fn with_claims_transformer(
+        mut self,
+        transformer: Box<dyn crate::core::token::transformer::ClaimsTransformer + Send + Sync>,
+    ) -> Self {
+        self.claims_transformer = transformer;
+        self
     }
 
-    /// Fetches user information from an OAuth2 provider using an access token.
+    /// Replaces the log receiving security audit events (logins, lockouts, rate limiting,
+    /// refresh token reuse, password and status changes, session and API key revocations).
+    ///
+    /// The default is [`StdoutAuditLog`](crate::core::audit::StdoutAuditLog).
     ///
     /// # Arguments
-    /// * `token` - The OAuth2 token to use for fetching user info.
+    /// * `audit_log` - The audit log to use.
     ///
     /// # Returns
-    /// Returns [`OAuth2UserInfo`] if successful, or an [`AuthError`] if fetching user info fails.
-    pub async fn fetch_oauth2_user_info(
-        &self,
-        token: &crate::core::oauth::store::OAuth2Token,
-    ) -> Result<crate::core::oauth::store::OAuth2UserInfo, AuthError> {
-        self.oauth2_manager.fetch_user_info(token).await
+    /// The updated [`AuthService`].
+    pub fn with_audit_log(
+        mut self,
+        audit_log: Box<dyn crate::core::audit::AuditLog + Send + Sync>,
+    ) -> Self {
+        self.audit_log = audit_log;
+        self
     }
 
-    /// Refreshes an OAuth2 access token using a refresh token.
+    /// Replaces the notifier telling users about password changes and logins.
+    ///
+    /// The default is [`NoopNotifier`](crate::core::notify::NoopNotifier), which sends nothing.
     ///
     /// # Arguments
-    /// * `token` - The OAuth2 token containing the refresh token.
+    /// * `notifier` - The notifier to use.
     ///
     /// # Returns
-    /// Returns a new [`OAuth2Token`] if successful, or an [`AuthError`] if refreshing the token fails.
-    pub async fn refresh_oauth2_token(
-        &self,
-        token: &crate::core::oauth::store::OAuth2Token,
-    ) -> Result<crate::core::oauth::store::OAuth2Token, AuthError> {
-        self.oauth2_manager.refresh_token(token).await
+    /// The updated [`AuthService`].
+    pub fn with_notifier(
+        mut self,
+        notifier: Box<dyn crate::core::notify::Notifier + Send + Sync>,
+    ) -> Self {
+        self.notifier = notifier;
+        self
     }
 
-    /// Links an OAuth account to an existing user.
+    /// Replaces the store used to hold OAuth2 accounts waiting for their link to be confirmed.
+    ///
+    /// The default is an in-memory store, which is not shared between instances.
     ///
     /// # Arguments
-    /// * `user_id` - The ID of the existing user.
-    /// * `provider` - The OAuth2 provider.
-    /// * `code` - The authorization code from the OAuth provider.
-    /// * `state` - The state parameter for CSRF protection.
+    /// * `store` - The pending link store to use.
     ///
     /// # Returns
-    /// Returns the updated [`User`] on success.
+    /// The updated [`AuthService`].
+    pub fn with_pending_link_store(
+        mut self,
+        store: Box<dyn crate::core::oauth::link::PendingLinkStore + Send + Sync>,
+    ) -> Self {
+        self.pending_links = store;
+        self
+    }
+
+    /// Removes the expired entries of the session, one-time token, email OTP and rate limit
+    /// stores.
+    ///
+    /// In-memory stores only prune expired entries when accessed, so a long-running server
+    /// should call this periodically, e.g. through [`AuthService::spawn_maintenance`].
+    /// Persistent backends rely on their own expiration (e.g. TTLs) and prune nothing.
+    ///
+    /// # Returns
+    /// The total number of entries removed.
     ///
     /// # Errors
-    /// Returns [`AuthError::UserNotFound`] if the user doesn't exist, or other variants for OAuth2 failures.
-    pub async fn link_oauth_account(
+    /// Returns the error of the first store that is unavailable.
+    pub async fn prune_expired(&self) -> Result<usize, AuthError> {
+        Ok(self.sessions.prune_expired().await?
+            + self.one_time_tokens.prune_expired().await?
+            + self.email_otps.prune_expired().await?
+            + self.rate_limits.prune_expired().await?)
+    }
+
+    /// Spawns a background task calling [`AuthService::prune_expired`] every `interval`.
+    ///
+    /// Failures are logged and retried at the next tick. The task runs until the returned
+    /// handle is aborted or the runtime shuts down.
+    ///
+    /// # Arguments
+    /// * `interval` - The time between two prunings. The first one happens immediately.
+    ///
+    /// # Returns
+    /// The handle of the spawned task.
+    #[cfg(feature = "tokio")]
+    pub fn spawn_maintenance(
+        self: std::sync::Arc<Self>,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                match self.prune_expired().await {
+                    Ok(0) => {}
+                    Ok(pruned) => log::debug!("Pruned {pruned} expired store entries"),
+                    Err(e) => log::warn!("Failed to prune expired store entries: {e}"),
+                }
+            }
+        })
+    }
+
+    /// Completes an OAuth2 account link after an [`AuthError::AccountLinkRequiresVerification`].
+    ///
+    /// The user proves ownership of the account with either their password or a valid access
+    /// token issued to them. On success, the pending provider account is linked and the user
+    /// is logged in. Attempts are counted per user against
+    /// [`AuthServiceVariables::link_confirmation_rate_limit`](crate::core::vars::AuthServiceVariables::link_confirmation_rate_limit),
+    /// and recorded in the audit log like logins.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user ID returned in the challenge.
+    /// * `provider` - The provider returned in the challenge.
+    /// * `password_or_token` - The user's password, or an access token issued to them.
+    ///
+    /// # Returns
+    /// The updated user and a new token pair.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidCredentials`] if the proof is wrong, [`AuthError::LoginError`]
+    /// if the user is locked out after too many attempts, [`AuthError::InvalidInput`] if no
+    /// link is pending (or it expired), [`AuthError::UserNotFound`] if the user no longer
+    /// exists, [`AuthError::AccountDisabled`] if the user is not active,
+    /// [`AuthError::PasswordVerificationError`] if the password could not be verified, or
+    /// [`AuthError::TooManyLinkedAccounts`] if the user already links the maximum number of
+    /// providers.
+    pub async fn confirm_link(
+        &self,
+        user_id: &str,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        password_or_token: &str,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        let result = self
+            .authenticate_link(user_id, provider, password_or_token)
+            .await;
+        self.report_login(
+            None,
+            crate::core::token::claims::amr::oauth(provider),
+            &result,
+        )
+        .await;
+        result
+    }
+
+    /// Completes an OAuth2 account link as [`Self::confirm_link`] does, without auditing.
+    async fn authenticate_link(
+        &self,
+        user_id: &str,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        password_or_token: &str,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        let oauth_user_info = self
+            .pending_links
+            .get(user_id, provider)
+            .await?
+            .ok_or_else(|| AuthError::InvalidInput("No pending account link".to_string()))?;
+        let mut user = self
+            .persistent_users_manager
+            .find_user_by_id(&user_id.into())
+            .await?
+            .ok_or(AuthError::UserNotFound)?;
+
+        // Every attempt counts, so that the proof cannot be guessed
+        let limit = self
+            .vars
+            .link_confirmation_rate_limit
+            .unwrap_or(crate::core::rate_limit::DEFAULT_LINK_CONFIRMATION_RATE_LIMIT);
+        if self
+            .rate_limits
+            .hit(
+                &crate::core::rate_limit::link_confirmation_key(&self.log_id(&user.id)),
+                limit,
+            )
+            .await?
+            .is_some()
+        {
+            self.audit_log
+                .record(crate::core::audit::AuditEvent::LockedOut {
+                    user_id: self.log_id(&user.id),
+                });
+            return Err(AuthError::LoginError(
+                "Too many failed attempts, try again later".to_string(),
+            ));
+        }
+
+        let password_matches = match &user.credentials {
+            Some(credentials) if !credentials.password_hash.is_empty() => self
+                .password_manager
+                .verify_password(password_or_token, &credentials.password_hash)
+                .await
+                .map_err(|e| match e {
+                    AuthError::HashingTimeout => e,
+                    e => AuthError::PasswordVerificationError(format!(
+                        "Password verification failed: {e}"
+                    )),
+                })?,
+            _ => false,
+        };
+        let proven = password_matches
+            || self
+                .validate_access_token_of_any_tenant(password_or_token)
+                .await
+                .is_ok_and(|claims| claims.get_subject() == user_id);
+        if !proven {
+            return Err(AuthError::InvalidCredentials);
+        }
+        Self::ensure_active(&user)?;
+        self.ensure_can_link(&user, provider)?;
+
+        self.pending_links.remove(user_id, provider).await?;
+        user.oauth_accounts.insert(provider, oauth_user_info);
+        user.updated_at = chrono::Utc::now().naive_utc();
+        self.touch_last_login(&mut user);
+        self.persistent_users_manager.update_user(&user).await?;
+
+        let mut amr = vec![crate::core::token::claims::amr::oauth(provider)];
+        if password_matches {
+            amr.push(crate::core::token::claims::amr::PASSWORD.to_string());
+        }
+        let tokens = self.issue_tokens(&user, amr).await?;
+        Ok((user, tokens))
+    }
+
+    /// Generates an email one-time password for the user without a tenant with the given
+    /// identifier.
+    ///
+    /// The code is made of [`OTP_CODE_LENGTH`](crate::core::otp::OTP_CODE_LENGTH) digits and
+    /// replaces any pending code. Only its HMAC-SHA256 tag, keyed by a key derived from
+    /// `vars.secret_key`, is stored. Delivering the code to the user is the caller's
+    /// responsibility.
+    ///
+    /// Unknown identifiers and users who are not active get no code, but the same success:
+    /// callers answer the request identically either way, so that it does not reveal which
+    /// accounts exist.
+    ///
+    /// # Arguments
+    /// * `identifier` - The user's identifier (usually their email address).
+    ///
+    /// # Returns
+    /// The plain code, to be sent to the user, or `None` if there is no active user to send it
+    /// to.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::RateLimited`] if the user reached the per-user rate limit,
+    /// [`AuthError::LoginError`] if the user is locked out after too many wrong codes, or an
+    /// error if the user or the code cannot be read or stored.
+    pub async fn request_email_otp(&self, identifier: &str) -> Result<Option<String>, AuthError> {
+        self.request_email_otp_in_tenant(identifier, None).await
+    }
+
+    /// Generates an email one-time password for the user of the tenant `tenant_id` with the
+    /// given identifier. See [`Self::request_email_otp`].
+    async fn request_email_otp_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<String>, AuthError> {
+        let identifier = self.identifier_resolver.resolve(identifier)?;
+        let Some(user) = self
+            .persistent_users_manager
+            .find_user_by_identifier_in_tenant(&identifier, tenant_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+        if !user.status.is_active() {
+            return Ok(None);
+        }
+        self.check_user_rate_limit(user.id.as_str()).await?;
+
+        if let Some(pending) = self.email_otps.get(user.id.as_str()).await?
+            && pending.attempts >= self.email_otp_max_attempts()
+        {
+            return Err(AuthError::LoginError(
+                "Too many failed attempts, try again later".to_string(),
+            ));
+        }
+
+        let code = crate::core::rand::secure_random_digits(crate::core::otp::OTP_CODE_LENGTH);
+        // Binding the user ID keeps a record copied to another user's key from verifying
+        let code_hash = self.keyed_tag(
+            crate::core::kdf::EMAIL_OTP_LABEL,
+            format!("{}:{code}", user.id).as_bytes(),
+        );
+        let ttl = self
+            .vars
+            .email_otp_ttl
+            .unwrap_or(crate::core::otp::DEFAULT_OTP_TTL);
+        let expires_at = (chrono::Utc::now().timestamp().max(0) as u64).saturating_add(ttl);
+        self.email_otps
+            .put(
+                user.id.as_str(),
+                crate::core::otp::OtpRecord {
+                    code_hash,
+                    expires_at: expires_at as usize,
+                    attempts: 0,
+                },
+            )
+            .await?;
+        Ok(Some(code))
+    }
+
+    /// Returns the base64-encoded HMAC-SHA256 tag of `message`, keyed by the key derived from
+    /// `vars.secret_key` for `label`.
+    fn keyed_tag(&self, label: &str, message: &[u8]) -> String {
+        use base64::Engine;
+        let key = crate::core::kdf::derive_key(self.vars.secret_key.as_bytes(), label);
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(crate::core::kdf::hmac_sha256(key.as_slice(), message))
+    }
+
+    /// Returns whether `stored` is the tag returned by [`Self::keyed_tag`] for `label` and
+    /// `message`, comparing the tags in constant time.
+    fn keyed_tag_matches(&self, label: &str, message: &[u8], stored: &str) -> bool {
+        use base64::Engine;
+        let key = crate::core::kdf::derive_key(self.vars.secret_key.as_bytes(), label);
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(stored)
+            .is_ok_and(|tag| crate::core::kdf::verify_hmac_sha256(key.as_slice(), message, &tag))
+    }
+
+    /// Logs a user without a tenant in with an email one-time password obtained from
+    /// [`Self::request_email_otp`].
+    ///
+    /// A code can be used once. Every submission counts as an attempt; once the attempt limit
+    /// is reached, the user is locked out until the code expires.
+    ///
+    /// # Arguments
+    /// * `identifier` - The user's identifier (usually their email address).
+    /// * `code` - The code submitted by the user.
+    ///
+    /// # Returns
+    /// The user and a new token pair.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidCredentials`] if the user, the pending code or the code
+    /// itself is invalid or expired, [`AuthError::LoginError`] if the user is locked out, or
+    /// [`AuthError::AccountDisabled`] if the user is not active.
+    pub async fn login_with_email_otp(
+        &self,
+        identifier: &str,
+        code: &str,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.login_with_email_otp_in_tenant(identifier, code, None)
+            .await
+    }
+
+    /// Logs a user of the tenant `tenant_id` in with an email one-time password. See
+    /// [`Self::login_with_email_otp`].
+    async fn login_with_email_otp_in_tenant(
+        &self,
+        identifier: &str,
+        code: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        let result = self
+            .authenticate_with_email_otp(identifier, code, tenant_id)
+            .await;
+        self.report_login(
+            Some(identifier.to_string()),
+            crate::core::token::claims::amr::ONE_TIME_PASSWORD.to_string(),
+            &result,
+        )
+        .await;
+        result
+    }
+
+    /// Logs a user in with an email one-time password, without auditing the outcome.
+    async fn authenticate_with_email_otp(
+        &self,
+        identifier: &str,
+        code: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        let identifier = self.identifier_resolver.resolve(identifier)?;
+        let mut user = self
+            .persistent_users_manager
+            .find_user_by_identifier_in_tenant(&identifier, tenant_id)
+            .await?
+            .ok_or(AuthError::InvalidCredentials)?;
+
+        let pending = self
+            .email_otps
+            .record_attempt(user.id.as_str())
+            .await?
+            .ok_or(AuthError::InvalidCredentials)?;
+        if pending.attempts > self.email_otp_max_attempts() {
+            return Err(AuthError::LoginError(
+                "Too many failed attempts, try again later".to_string(),
+            ));
+        }
+
+        if !self.keyed_tag_matches(
+            crate::core::kdf::EMAIL_OTP_LABEL,
+            format!("{}:{code}", user.id).as_bytes(),
+            &pending.code_hash,
+        ) {
+            if pending.attempts == self.email_otp_max_attempts() {
+                self.audit_log
+                    .record(crate::core::audit::AuditEvent::LockedOut {
+                        user_id: self.log_id(&user.id),
+                    });
+            }
+            return Err(AuthError::InvalidCredentials);
+        }
+        self.email_otps.remove(user.id.as_str()).await?;
+        Self::ensure_active(&user)?;
+
+        self.record_login(&mut user).await;
+        let tokens = self
+            .issue_tokens(
+                &user,
+                vec![crate::core::token::claims::amr::ONE_TIME_PASSWORD.to_string()],
+            )
+            .await?;
+        Ok((user, tokens))
+    }
+
+    /// Generates a password reset token for the user without a tenant with the given
+    /// identifier, and notifies the user through
+    /// [`Notifier::notify_reset_requested`](crate::core::notify::Notifier::notify_reset_requested).
+    ///
+    /// The caller sends the token to the user (e.g., in a reset link), who submits it back to
+    /// [`Self::reset_password`]. Only a keyed tag of the token is stored, in the one-time
+    /// password store, until it expires after
+    /// [`AuthServiceVariables::password_reset_ttl`](crate::core::vars::AuthServiceVariables::password_reset_ttl).
+    /// Requesting a new token replaces the pending one. Unknown and inactive users get no token
+    /// without an error, so callers can answer the same way whether the account exists or not.
+    ///
+    /// # Arguments
+    /// * `identifier` - The user's identifier (usually their email address).
+    ///
+    /// # Returns
+    /// The token, to be sent to the user, or `None` if there is no active user to send it to.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::RateLimited`] if the user reached the per-user rate limit,
+    /// [`AuthError::LoginError`] if the user is locked out after too many wrong tokens, or an
+    /// error if the user or the token cannot be read or stored.
+    pub async fn request_password_reset(
+        &self,
+        identifier: &str,
+    ) -> Result<Option<String>, AuthError> {
+        self.request_password_reset_in_tenant(identifier, None)
+            .await
+    }
+
+    /// Generates a password reset token for the user of the tenant `tenant_id` with the given
+    /// identifier. See [`Self::request_password_reset`].
+    async fn request_password_reset_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<String>, AuthError> {
+        let identifier = self.identifier_resolver.resolve(identifier)?;
+        let Some(user) = self
+            .persistent_users_manager
+            .find_user_by_identifier_in_tenant(&identifier, tenant_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+        if !user.status.is_active() {
+            return Ok(None);
+        }
+        self.check_user_rate_limit(user.id.as_str()).await?;
+
+        let key = crate::core::otp::password_reset_key(user.id.as_str());
+        if let Some(pending) = self.email_otps.get(&key).await?
+            && pending.attempts >= self.email_otp_max_attempts()
+        {
+            return Err(AuthError::LoginError(
+                "Too many failed attempts, try again later".to_string(),
+            ));
+        }
+
+        let token = crate::core::rand::secure_random_string(32);
+        let token_hash = self.keyed_tag(
+            crate::core::kdf::PASSWORD_RESET_LABEL,
+            format!("{}:{token}", user.id).as_bytes(),
+        );
+        let ttl = self
+            .vars
+            .password_reset_ttl
+            .unwrap_or(crate::core::otp::DEFAULT_PASSWORD_RESET_TTL);
+        let expires_at = (chrono::Utc::now().timestamp().max(0) as u64).saturating_add(ttl);
+        self.email_otps
+            .put(
+                &key,
+                crate::core::otp::OtpRecord {
+                    code_hash: token_hash,
+                    expires_at: expires_at as usize,
+                    attempts: 0,
+                },
+            )
+            .await?;
+
+        if let Err(e) = self.notifier.notify_reset_requested(&user).await {
+            log::warn!(
+                "Failed to notify user {} of a password reset request: {e}",
+                self.log_id(&user.id)
+            );
+        }
+        Ok(Some(token))
+    }
+
+    /// Sets a new password for the user without a tenant with the given identifier, with a
+    /// token obtained from [`Self::request_password_reset`].
+    ///
+    /// A token can be used once. Every submission counts as an attempt against
+    /// [`AuthServiceVariables::email_otp_max_attempts`](crate::core::vars::AuthServiceVariables::email_otp_max_attempts).
+    /// The password is stored as by [`Self::set_password`], so every session of the user is
+    /// revoked.
+    ///
+    /// # Arguments
+    /// * `identifier` - The user's identifier (usually their email address).
+    /// * `token` - The token submitted by the user.
+    /// * `new_password` - The plaintext password to set.
+    ///
+    /// # Returns
+    /// Returns `Ok(())` once the new credentials are persisted and the sessions revoked.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidToken`] if the user, the pending token or the token itself
+    /// is invalid or expired, [`AuthError::LoginError`] if the user is locked out,
+    /// [`AuthError::AccountDisabled`] if the user is not active, or the errors of
+    /// [`Self::set_password`].
+    pub async fn reset_password(
+        &self,
+        identifier: &str,
+        token: &str,
+        new_password: &str,
+    ) -> Result<(), AuthError> {
+        self.reset_password_in_tenant(identifier, token, new_password, None)
+            .await
+    }
+
+    /// Sets a new password for the user of the tenant `tenant_id` with a reset token. See
+    /// [`Self::reset_password`].
+    async fn reset_password_in_tenant(
+        &self,
+        identifier: &str,
+        token: &str,
+        new_password: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<(), AuthError> {
+        let invalid =
+            || AuthError::InvalidToken("Invalid or expired password reset token".to_string());
+        let identifier = self.identifier_resolver.resolve(identifier)?;
+        let user = self
+            .persistent_users_manager
+            .find_user_by_identifier_in_tenant(&identifier, tenant_id)
+            .await?
+            .ok_or_else(invalid)?;
+
+        let key = crate::core::otp::password_reset_key(user.id.as_str());
+        let pending = self
+            .email_otps
+            .record_attempt(&key)
+            .await?
+            .ok_or_else(invalid)?;
+        if pending.attempts > self.email_otp_max_attempts() {
+            return Err(AuthError::LoginError(
+                "Too many failed attempts, try again later".to_string(),
+            ));
+        }
+        if !self.keyed_tag_matches(
+            crate::core::kdf::PASSWORD_RESET_LABEL,
+            format!("{}:{token}", user.id).as_bytes(),
+            &pending.code_hash,
+        ) {
+            return Err(invalid());
+        }
+        Self::ensure_active(&user)?;
+
+        // The token stays usable if the new password is rejected, e.g. by the policy
+        self.store_password(user.id.as_str(), new_password, true, None)
+            .await?;
+        self.email_otps.remove(&key).await
+    }
+
+    /// Records a request from the IP address `ip` against the per-IP rate limit
+    /// ([`AuthServiceVariables::ip_rate_limit`](crate::core::vars::AuthServiceVariables::ip_rate_limit)).
+    ///
+    /// The service does not know the client's address, so callers (e.g., web handlers) apply
+    /// this limit before calling unauthenticated operations. It is counted separately from the
+    /// per-user limit, which the service applies itself once the user is known.
+    ///
+    /// # Arguments
+    /// * `ip` - The client's IP address.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::RateLimited`] if the address reached the limit, or
+    /// [`AuthError::ServiceUnavailable`] if the store is unavailable.
+    pub async fn check_ip_rate_limit(&self, ip: &str) -> Result<(), AuthError> {
+        self.check_rate_limit(
+            &crate::core::rate_limit::ip_key(ip),
+            self.vars.ip_rate_limit,
+        )
+        .await
+    }
+
+    /// Records an email sent to `user_id` against the per-user rate limit.
+    async fn check_user_rate_limit(&self, user_id: &str) -> Result<(), AuthError> {
+        self.check_rate_limit(
+            &crate::core::rate_limit::user_key(&self.log_id(&user_id.into())),
+            self.vars.user_rate_limit,
+        )
+        .await
+    }
+
+    /// Records a hit for `key` under `limit`, if the limit is enabled.
+    async fn check_rate_limit(
+        &self,
+        key: &str,
+        limit: Option<crate::core::rate_limit::RateLimit>,
+    ) -> Result<(), AuthError> {
+        let Some(limit) = limit else {
+            return Ok(());
+        };
+        match self.rate_limits.hit(key, limit).await? {
+            Some(retry_after_secs) => {
+                self.audit_log
+                    .record(crate::core::audit::AuditEvent::RateLimited {
+                        key: key.to_string(),
+                    });
+                Err(AuthError::RateLimited { retry_after_secs })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Records the outcome of a login attempt in the audit log, and notifies the user of
+    /// successful ones.
+    async fn report_login<T>(
+        &self,
+        identifier: Option<String>,
+        method: String,
+        result: &Result<(User, T), AuthError>,
+    ) {
+        let event = match result {
+            Ok((user, _)) => {
+                if let Err(e) = self.notifier.notify_new_login(user, &method).await {
+                    log::warn!(
+                        "Failed to notify user {} of a new login: {e}",
+                        self.log_id(&user.id)
+                    );
+                }
+                crate::core::audit::AuditEvent::LoginSucceeded {
+                    user_id: self.log_id(&user.id),
+                    method,
+                }
+            }
+            Err(e) => crate::core::audit::AuditEvent::LoginFailed {
+                identifier,
+                method,
+                reason: Self::audit_reason(e).to_string(),
+            },
+        };
+        self.audit_log.record(event);
+    }
+
+    /// Returns the reason recorded in the audit log for a rejected login.
+    ///
+    /// Reasons are stable codes, so that error messages (which may echo user input) never
+    /// reach the audit log.
+    fn audit_reason(error: &AuthError) -> &'static str {
+        match error {
+            AuthError::InvalidCredentials => "invalid_credentials",
+            AuthError::AccountDisabled => "account_disabled",
+            AuthError::LoginError(_) => "locked_out",
+            AuthError::RateLimited { .. } => "rate_limited",
+            AuthError::OAuthEmailNotVerified => "email_not_verified",
+            AuthError::DisposableEmailNotAllowed => "disposable_email",
+            AuthError::OAuthStateMismatch => "state_mismatch",
+            AuthError::AccountLinkRequiresVerification { .. } => "link_requires_verification",
+            AuthError::StorageUnavailable(_) => "storage_unavailable",
+            _ => "error",
+        }
+    }
+
+    /// Returns the number of verification attempts allowed per email one-time password.
+    fn email_otp_max_attempts(&self) -> u32 {
+        self.vars
+            .email_otp_max_attempts
+            .unwrap_or(crate::core::otp::DEFAULT_OTP_MAX_ATTEMPTS)
+    }
+
+    /// Checks that the service is correctly configured.
+    ///
+    /// Validates every OAuth2 provider configuration so that deployments can fail fast
+    /// at startup instead of on the first login attempt.
+    ///
+    /// # Returns
+    /// Returns `Ok(())` if the service is healthy.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::ConfigError`] listing every misconfigured OAuth2 provider.
+    pub async fn health_check(&self) -> Result<(), AuthError> {
+        self.oauth2_manager.validate_configs().map_err(|problems| {
+            let details = problems
+                .iter()
+                .map(|(provider, error)| format!("{provider}: {error}"))
+                .collect::<Vec<_>>()
+                .join("; ");
+            AuthError::ConfigError(format!("Invalid OAuth2 configuration: {details}"))
+        })
+    }
+
+    /// Checks the whole configuration for coherence, e.g. before going live.
+    ///
+    /// Combines [`AuthServiceVariables::validate`](crate::core::vars::AuthServiceVariables::validate)
+    /// with the validation of every configured OAuth2 provider. Unlike
+    /// [`AuthService::health_check`], every issue is reported at once.
+    ///
+    /// # Returns
+    /// Returns `Ok(())` if no issue was found.
+    ///
+    /// # Errors
+    /// Returns every [`ConfigIssue`](crate::core::vars::ConfigIssue) found.
+    pub fn validate_configuration(&self) -> Result<(), Vec<crate::core::vars::ConfigIssue>> {
+        let mut issues = self.vars.validate();
+        if let Err(problems) = self.oauth2_manager.validate_configs() {
+            issues.extend(problems.into_iter().map(|(provider, error)| {
+                crate::core::vars::ConfigIssue::new(
+                    format!("oauth_configs.{}", provider.as_str()),
+                    error.to_string(),
+                )
+            }));
+        }
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+
+    /// Checks a new password against the configured [`PasswordPolicy`](crate::core::policy::PasswordPolicy), if any.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidInput`] if the password does not satisfy the rules of the
+    /// policy, or [`AuthError::WeakPassword`] if it is too predictable.
+    fn enforce_password_policy(&self, password: &str) -> Result<(), AuthError> {
+        match &self.vars.password_policy {
+            Some(policy) => policy.validate_password(password),
+            None => Ok(()),
+        }
+    }
+
+    /// Sets `last_login_at` to now, unless last login tracking is disabled.
+    ///
+    /// Only updates the in-memory user; callers are responsible for persisting it.
+    fn touch_last_login(&self, user: &mut User) {
+        if !self.vars.disable_last_login_tracking {
+            user.last_login_at = Some(chrono::Utc::now().naive_utc());
+        }
+    }
+
+    /// Records a successful login by persisting `last_login_at`, unless tracking is disabled.
+    ///
+    /// Failures are logged and otherwise ignored so that a login is never rejected because
+    /// the timestamp could not be stored.
+    async fn record_login(&self, user: &mut User) {
+        if self.vars.disable_last_login_tracking {
+            return;
+        }
+        let previous = user.last_login_at;
+        self.touch_last_login(user);
+        if let Err(e) = self.persistent_users_manager.update_user(user).await {
+            log::warn!(
+                "Failed to record last login for user {}: {e}",
+                self.log_id(&user.id)
+            );
+            user.last_login_at = previous;
+        }
+    }
+
+    /// Rehashes the user's password with the current password manager and the user's
+    /// [`hash_target`](User::hash_target), and persists it.
+    ///
+    /// Failures are logged and otherwise ignored so that a successful login is never
+    /// rejected because the hash upgrade could not be stored.
+    async fn rehash_password(&self, user: &mut User, password: &str) {
+        rehash_password(
+            self.password_manager.as_ref(),
+            self.persistent_users_manager.as_ref(),
+            &self.log_ids,
+            user,
+            password,
+        )
+        .await;
+    }
+
+    /// Authenticates a user using the specified login method.
+    ///
+    /// Supports both credentials-based and OAuth2-based login flows. Only users without a
+    /// tenant are considered; use [`AuthService::for_tenant`] for tenant-scoped logins.
+    ///
+    /// # Arguments
+    /// * `method` - The authentication method to use for login. See [`LoginMethod`].
+    ///
+    /// # Returns
+    /// Returns a tuple `(User, TokenPair)` if login is successful, or an [`AuthError`] if authentication fails.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidCredentials`] if credentials are invalid,
+    /// [`AuthError::AccountDisabled`] if the user is suspended or deleted, or other variants for
+    /// OAuth2 failures.
+    pub async fn login(
+        &self,
+        method: LoginMethod,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.login_in_tenant(method, None, false).await
+    }
+
+    /// Authenticates a user as with [`AuthService::login`], keeping them signed in ("remember
+    /// me").
+    ///
+    /// The refresh token of a credentials login gets the extended lifetime of
+    /// [`AuthServiceVariables::remember_me_refresh_token_expiration`](crate::core::vars::AuthServiceVariables::remember_me_refresh_token_expiration),
+    /// which its successors keep. OAuth2 logins, and services without an extended lifetime,
+    /// get the default refresh token lifetime.
+    ///
+    /// # Arguments
+    /// * `method` - The authentication method to use for login. See [`LoginMethod`].
+    ///
+    /// # Returns
+    /// Returns a tuple `(User, TokenPair)` if login is successful.
+    ///
+    /// # Errors
+    /// Returns the errors of [`AuthService::login`].
+    pub async fn login_remember_me(
+        &self,
+        method: LoginMethod,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.login_in_tenant(method, None, true).await
+    }
+
+    /// Authenticates a user as with [`AuthService::login`], issuing an access token without a
+    /// refresh token.
+    ///
+    /// Suits stateless API clients that log in again once the access token expires rather
+    /// than refreshing it. The login is audited, and the user notified, as with
+    /// [`AuthService::login`].
+    ///
+    /// # Arguments
+    /// * `method` - The authentication method to use for login. See [`LoginMethod`].
+    ///
+    /// # Returns
+    /// Returns a tuple `(User, String)` with the user and their access token.
+    ///
+    /// # Errors
+    /// Returns the errors of [`AuthService::login`].
+    pub async fn login_access_token_only(
+        &self,
+        method: LoginMethod,
+    ) -> Result<(User, String), AuthError> {
+        self.login_access_token_only_in_tenant(method, None).await
+    }
+
+    /// Registers a new user using the specified signup method.
+    ///
+    /// Supports both credentials-based and OAuth2-based registration flows. The user is
+    /// created without a tenant; use [`AuthService::for_tenant`] for tenant-scoped signups.
+    ///
+    /// # Arguments
+    /// * `method` - The registration method to use for signup. See [`SignupMethod`].
+    ///
+    /// # Returns
+    /// Returns a tuple `(User, TokenPair)` if signup is successful, or an [`AuthError`] if registration fails.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::DisposableEmailNotAllowed`] if the email address is at a domain
+    /// denied by the [disposable email filter](AuthService::with_disposable_email_filter),
+    /// [`AuthError::SignupError`] or other variants for OAuth2 failures.
+    pub async fn signup(
+        &self,
+        method: SignupMethod,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.signup_in_tenant(method, None).await
+    }
+
+    /// Registers a new user as with [`AuthService::signup`], after verifying the challenge
+    /// token sent with the request.
+    ///
+    /// The challenge is verified before the identifier is looked up and the password hashed.
+    /// Without a [challenge verifier](AuthService::with_challenge_verifier), the token is
+    /// ignored.
+    ///
+    /// # Arguments
+    /// * `method` - The registration method to use for signup. See [`SignupMethod`].
+    /// * `challenge_token` - The challenge token solved by the client, if it sent one.
+    ///
+    /// # Returns
+    /// Returns a tuple `(User, TokenPair)` if signup is successful.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::ChallengeFailed`] if the token is missing or rejected, the other
+    /// errors of the verifier, or the errors of [`AuthService::signup`].
+    pub async fn signup_with_challenge(
+        &self,
+        method: SignupMethod,
+        challenge_token: Option<&str>,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.signup_with_challenge_in_tenant(method, challenge_token, None)
+            .await
+    }
+
+    /// Registers a new user in a tenant after verifying the challenge token of the request.
+    /// See [`AuthService::signup_with_challenge`].
+    async fn signup_with_challenge_in_tenant(
+        &self,
+        method: SignupMethod,
+        challenge_token: Option<&str>,
+        tenant_id: Option<&str>,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.verify_challenge(challenge_token).await?;
+        self.signup_in_tenant(method, tenant_id).await
+    }
+
+    /// Authenticates a user as with [`AuthService::login`], after verifying the challenge
+    /// token sent with the request.
+    ///
+    /// The challenge is verified before the identifier is looked up and the password verified.
+    /// Without a [challenge verifier](AuthService::with_challenge_verifier), the token is
+    /// ignored.
+    ///
+    /// # Arguments
+    /// * `method` - The authentication method to use for login. See [`LoginMethod`].
+    /// * `challenge_token` - The challenge token solved by the client, if it sent one.
+    ///
+    /// # Returns
+    /// Returns a tuple `(User, TokenPair)` if login is successful.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::ChallengeFailed`] if the token is missing or rejected, the other
+    /// errors of the verifier, or the errors of [`AuthService::login`].
+    pub async fn login_with_challenge(
+        &self,
+        method: LoginMethod,
+        challenge_token: Option<&str>,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.login_with_challenge_in_tenant(method, challenge_token, None, false)
+            .await
+    }
+
+    /// Authenticates a user in a tenant after verifying the challenge token of the request,
+    /// keeping them signed in if `remember_me` is set. See [`AuthService::login_with_challenge`]
+    /// and [`AuthService::login_remember_me`].
+    pub(crate) async fn login_with_challenge_in_tenant(
+        &self,
+        method: LoginMethod,
+        challenge_token: Option<&str>,
+        tenant_id: Option<&str>,
+        remember_me: bool,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.verify_challenge(challenge_token).await?;
+        self.login_in_tenant(method, tenant_id, remember_me).await
+    }
+
+    /// Logs in or registers the user of an OAuth2 identity the application authenticated
+    /// itself, e.g. with its own OAuth2 client or a provider SDK.
+    ///
+    /// Runs the end of the OAuth2 login flow of [`AuthService::login`] without contacting the
+    /// provider: the account is matched, linked or created, and tokens are issued with the
+    /// `oauth:<provider>` authentication method. The caller is responsible for having
+    /// validated the identity; `info` must come from the provider, never from the client.
+    ///
+    /// # Arguments
+    /// * `info` - The user info fetched from the provider.
+    ///
+    /// # Returns
+    /// Returns a tuple `(User, TokenPair)` for the matched or created user.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidCredentials`] if the provider account is linked to a user of
+    /// a tenant, [`AuthError::AccountDisabled`] if the matched user is suspended or deleted,
+    /// [`AuthError::OAuthEmailNotVerified`] if the provider requires a verified email and
+    /// `info` does not report one, [`AuthError::AccountLinkRequiresVerification`] if the link
+    /// to an existing password account must be confirmed first, or an error if the repository
+    /// or the token service fails.
+    pub async fn login_from_oauth_userinfo(
+        &self,
+        info: crate::core::oauth::store::OAuth2UserInfo,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.login_from_oauth_userinfo_in_tenant(info, None).await
+    }
+
+    /// Logs in or registers the user of an OAuth2 identity in the given tenant, recording the
+    /// outcome in the audit log.
+    async fn login_from_oauth_userinfo_in_tenant(
+        &self,
+        info: crate::core::oauth::store::OAuth2UserInfo,
+        tenant_id: Option<&str>,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        let amr = crate::core::token::claims::amr::oauth(info.provider);
+        let result = self.oauth2_user_flow(info, tenant_id).await;
+        self.report_login(None, amr, &result).await;
+        result
+    }
+
+    /// Checks whether an identifier is free for a new signup, e.g. for a live "username
+    /// available?" check on a signup form.
+    ///
+    /// The identifier is canonicalized by the service's identifier resolver first, so
+    /// `Alice@Example.com` is reported as taken once `alice@example.com` signed up if the
+    /// resolver normalizes case. Only users without a tenant are considered; use
+    /// [`TenantAuthService::is_identifier_available`] for tenant-scoped checks.
+    ///
+    /// Each check counts against the client's
+    /// [`AuthServiceVariables::availability_rate_limit`](crate::core::vars::AuthServiceVariables::availability_rate_limit),
+    /// so one client cannot enumerate users by probing identifiers. A `true` result is not a
+    /// reservation: a concurrent signup may still take the identifier first.
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier to check (e.g., a username or email address).
+    /// * `client` - The key of the caller the check is counted against, usually the client's
+    ///   IP address.
+    ///
+    /// # Returns
+    /// `true` if no user has this identifier, `false` otherwise.
+    ///
+    /// # Errors
+    /// Returns the errors of the identifier resolver, [`AuthError::RateLimited`] if the checks
+    /// reached the limit, or an error if the repository could not be queried.
+    pub async fn is_identifier_available(
+        &self,
+        identifier: &str,
+        client: &str,
+    ) -> Result<bool, AuthError> {
+        self.is_identifier_available_in_tenant(identifier, client, None)
+            .await
+    }
+
+    /// Checks whether an identifier is free within a tenant.
+    async fn is_identifier_available_in_tenant(
+        &self,
+        identifier: &str,
+        client: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<bool, AuthError> {
+        self.check_rate_limit(
+            &crate::core::rate_limit::availability_key(client),
+            Some(
+                self.vars
+                    .availability_rate_limit
+                    .unwrap_or(crate::core::rate_limit::DEFAULT_AVAILABILITY_RATE_LIMIT),
+            ),
+        )
+        .await?;
+        let identifier = self.identifier_resolver.resolve(identifier)?;
+        Ok(self
+            .persistent_users_manager
+            .get_credentials_by_identifier_in_tenant(&identifier, tenant_id)
+            .await?
+            .is_none())
+    }
+
+    /// Validates and hashes a password without storing anything.
+    ///
+    /// Staged registrations (e.g., confirming an email before creating the account) can run
+    /// the identifier resolution, the password policy and the expensive hashing up front, or
+    /// on a background queue, and persist the result later with
+    /// `User::new(credentials.user_id.clone(), credentials)`. The identifier is not checked
+    /// for availability: the repository rejects duplicates when the user is added.
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier of the future user (e.g., an email address).
+    /// * `password` - The plain password to hash.
+    ///
+    /// # Returns
+    /// Credentials for a newly generated user ID, holding the resolved identifier and the hash.
+    ///
+    /// # Errors
+    /// Returns the errors of the identifier resolver, [`AuthError::InvalidInput`] if the
+    /// password does not satisfy the policy, or an error if hashing fails.
+    pub async fn prepare_credentials(
+        &self,
+        identifier: &str,
+        password: &str,
+    ) -> Result<crate::core::credentials::Credentials, AuthError> {
+        let identifier = self.identifier_resolver.resolve(identifier)?;
+        self.enforce_password_policy(password)?;
+        crate::core::credentials::Credentials::from_plain_password(
+            self.password_manager.as_ref(),
+            uuid::Uuid::new_v4().to_string(),
+            identifier,
+            crate::core::credentials::PlainPassword::new(password.to_string()),
+        )
+        .await
+    }
+
+    /// Creates the initial administrator, for first-run seeding.
+    ///
+    /// The user gets the [`ADMIN_ROLE`](crate::core::user::ADMIN_ROLE) role and no tenant.
+    /// Calling it again is harmless: once any user has the role, nothing is written. Concurrent
+    /// calls are serialized by a marker consumed in the single-use token store for
+    /// [`BOOTSTRAP_LEASE`] (shared between instances when the store is), so only one of them
+    /// creates an administrator. The marker is released if the bootstrap fails, or expires if
+    /// the store cannot release it.
+    ///
+    /// # Arguments
+    /// * `admin_identifier` - The identifier of the administrator (e.g., an email address).
+    /// * `admin_password` - The plain password of the administrator.
+    ///
+    /// # Returns
+    /// The created administrator.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::AlreadyBootstrapped`] if a user already has the administrator role
+    /// or another bootstrap is running, [`AuthError::UserAlreadyExists`] if a user without a
+    /// tenant has `admin_identifier`, [`AuthError::NotImplemented`] if the repository cannot
+    /// stream its users, the errors of the identifier resolver and the password policy, or an
+    /// error if the users cannot be read or hashing or storing the user fails.
+    pub async fn bootstrap(
+        &self,
+        admin_identifier: &str,
+        admin_password: &str,
+    ) -> Result<User, AuthError> {
+        let identifier = self.identifier_resolver.resolve(admin_identifier)?;
+        self.enforce_password_policy(admin_password)?;
+        let lease_until = (chrono::Utc::now().timestamp().max(0) as u64)
+            .saturating_add(BOOTSTRAP_LEASE.as_secs()) as usize;
+        if !self
+            .one_time_tokens
+            .consume(BOOTSTRAP_MARKER, lease_until)
+            .await?
+        {
+            return Err(AuthError::AlreadyBootstrapped);
+        }
+        let result = self.bootstrap_leased(identifier, admin_password).await;
+        if result.is_err()
+            && let Err(e) = self.one_time_tokens.release(BOOTSTRAP_MARKER).await
+        {
+            log::warn!("Failed to release the bootstrap marker: {e}");
+        }
+        result
+    }
+
+    /// Creates the initial administrator once the bootstrap marker is held. See
+    /// [`Self::bootstrap`].
+    async fn bootstrap_leased(
+        &self,
+        identifier: String,
+        admin_password: &str,
+    ) -> Result<User, AuthError> {
+        use futures_util::TryStreamExt;
+
+        // Checked under the marker, so that no other bootstrap creates an administrator meanwhile
+        let mut users = self.persistent_users_manager.stream_users();
+        while let Some(user) = users.try_next().await? {
+            if user.has_role(crate::core::user::ADMIN_ROLE) {
+                return Err(AuthError::AlreadyBootstrapped);
+            }
+        }
+
+        let reservation = self
+            .persistent_users_manager
+            .reserve_identifier(&identifier, None)
+            .await?;
+        let mut user = User::with_plain_password(
+            self.password_manager.as_ref(),
+            uuid::Uuid::new_v4().to_string(),
+            identifier,
+            crate::core::credentials::PlainPassword::new(admin_password.to_string()),
+        )
+        .await?;
+        user.roles.push(crate::core::user::ADMIN_ROLE.to_string());
+        // The user, its credentials and its role are stored together or not at all
+        let user = self
+            .persistent_users_manager
+            .transaction(move |tx| Box::pin(async move { tx.add_user(user).await }))
+            .await?;
+        reservation.commit();
+        log::info!("Bootstrapped administrator {}", self.log_id(&user.id));
+        Ok(user)
+    }
+
+    /// Returns a view of this service scoped to the given tenant.
+    ///
+    /// Logins, signups and token validation performed through the view only see users of
+    /// that tenant, so the same identifier can be registered in several tenants.
+    ///
+    /// # Arguments
+    /// * `tenant_id` - The tenant to scope operations to.
+    ///
+    /// # Returns
+    /// A [`TenantAuthService`] borrowing this service.
+    pub fn for_tenant(&self, tenant_id: impl Into<String>) -> TenantAuthService<'_> {
+        TenantAuthService {
+            service: self,
+            tenant_id: tenant_id.into(),
+        }
+    }
+
+    /// Authenticates a user of the given tenant (or of no tenant when `tenant_id` is `None`),
+    /// recording the outcome in the audit log.
+    async fn login_in_tenant(
+        &self,
+        method: LoginMethod,
+        tenant_id: Option<&str>,
+        remember_me: bool,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        let (identifier, amr) = Self::login_audit_identity(&method);
+        let result = self
+            .authenticate_in_tenant(method, tenant_id, remember_me)
+            .await;
+        self.report_login(identifier, amr, &result).await;
+        result
+    }
+
+    /// Authenticates a user of the given tenant as [`Self::login_in_tenant`] does, issuing an
+    /// access token only.
+    async fn login_access_token_only_in_tenant(
+        &self,
+        method: LoginMethod,
+        tenant_id: Option<&str>,
+    ) -> Result<(User, String), AuthError> {
+        let (identifier, amr) = Self::login_audit_identity(&method);
+        let result = async {
+            let (user, amr) = self.authenticate_user_in_tenant(method, tenant_id).await?;
+            let access_token = self.issue_access_token(&user, amr).await?;
+            Ok((user, access_token))
+        }
+        .await;
+        self.report_login(identifier, amr, &result).await;
+        result
+    }
+
+    /// Returns the identifier and the authentication method recorded in the audit log for a
+    /// login with `method`.
+    fn login_audit_identity(method: &LoginMethod) -> (Option<String>, String) {
+        match method {
+            LoginMethod::Credentials { identifier, .. } => (
+                Some(identifier.clone()),
+                crate::core::token::claims::amr::PASSWORD.to_string(),
+            ),
+            LoginMethod::OAuth2 { provider, .. } => {
+                (None, crate::core::token::claims::amr::oauth(*provider))
+            }
+        }
+    }
+
+    /// Authenticates a user of the given tenant, without auditing. Credentials logins with
+    /// `remember_me` set get the extended refresh token lifetime.
+    async fn authenticate_in_tenant(
+        &self,
+        method: LoginMethod,
+        tenant_id: Option<&str>,
+        remember_me: bool,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        let refresh_expiration = (remember_me && matches!(method, LoginMethod::Credentials { .. }))
+            .then_some(self.vars.remember_me_refresh_token_expiration)
+            .flatten();
+        let (user, amr) = self.authenticate_user_in_tenant(method, tenant_id).await?;
+        let tokens = self
+            .issue_tokens_with_refresh_expiration(&user, amr, refresh_expiration)
+            .await?;
+        Ok((user, tokens))
+    }
+
+    /// Authenticates a user of the given tenant without issuing tokens or auditing.
+    ///
+    /// Returns the user and the authentication methods to embed in their tokens.
+    async fn authenticate_user_in_tenant(
+        &self,
+        method: LoginMethod,
+        tenant_id: Option<&str>,
+    ) -> Result<(User, Vec<String>), AuthError> {
+        match method {
+            LoginMethod::Credentials {
+                identifier,
+                password,
+            } => {
+                let identifier = self.identifier_resolver.resolve(&identifier)?;
+
+                // Load only the credentials; the full user is fetched once the password matches
+                let credentials = self
+                    .persistent_users_manager
+                    .get_credentials_by_identifier_in_tenant(&identifier, tenant_id)
+                    .await?
+                    .ok_or(AuthError::InvalidCredentials)?;
+                // Identifiers completed after an OAuth2 signup have no password yet
+                if credentials.password_hash.is_empty() {
+                    return Err(AuthError::InvalidCredentials);
+                }
+                if !self.password_manager.identify(&credentials.password_hash) {
+                    self.audit_log
+                        .record(crate::core::audit::AuditEvent::MalformedPasswordHash {
+                            user_id: self.log_id(&credentials.user_id),
+                        });
+                }
+
+                // Verify the password using the password manager from the service
+                let is_valid = self
+                    .password_manager
+                    .verify_password(&password, &credentials.password_hash)
+                    .await
+                    .map_err(|e| match e {
+                        AuthError::HashingTimeout => e,
+                        e => AuthError::PasswordVerificationError(format!(
+                            "Password verification failed: {e}"
+                        )),
+                    })?;
+
+                if !is_valid {
+                    return Err(AuthError::InvalidCredentials);
+                }
+
+                let mut stored_user = self
+                    .persistent_users_manager
+                    .find_user_by_id(&credentials.user_id)
+                    .await?
+                    .ok_or(AuthError::InvalidCredentials)?;
+                Self::ensure_active(&stored_user)?;
+
+                // Opportunistically upgrade legacy or outdated hashes, toward the user's target
+                if self
+                    .password_manager
+                    .needs_rehash_for(&credentials.password_hash, stored_user.hash_target.as_ref())
+                {
+                    self.rehash_password(&mut stored_user, &password).await;
+                }
+
+                self.record_login(&mut stored_user).await;
+
+                Ok((
+                    stored_user,
+                    vec![crate::core::token::claims::amr::PASSWORD.to_string()],
+                ))
+            }
+            LoginMethod::OAuth2 {
+                provider,
+                code,
+                state,
+            } => {
+                let user = self
+                    .oauth2_authenticate(provider, &code, &state, tenant_id)
+                    .await?;
+                Ok((user, vec![crate::core::token::claims::amr::oauth(provider)]))
+            }
+        }
+    }
+
+    /// Registers a user in the given tenant (or with no tenant when `tenant_id` is `None`).
+    async fn signup_in_tenant(
+        &self,
+        method: SignupMethod,
+        tenant_id: Option<&str>,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        match method {
+            SignupMethod::Credentials {
+                identifier,
+                password,
+            } => {
+                let identifier = self.identifier_resolver.resolve(&identifier)?;
+                self.ensure_not_disposable(&identifier)?;
+                self.enforce_password_policy(&password)?;
+
+                // Claim the identifier so concurrent signups cannot race past the hashing step
+                let reservation = self
+                    .persistent_users_manager
+                    .reserve_identifier(&identifier, tenant_id)
+                    .await
+                    .map_err(|e| AuthError::SignupError(format!("signup: {e}")))?;
+
+                // Create user with credentials
+                let user = User::with_plain_password(
+                    self.password_manager.as_ref(),
+                    uuid::Uuid::new_v4().to_string(),
+                    identifier,
+                    crate::core::credentials::PlainPassword::new(password),
+                )
+                .await?
+                .with_tenant(tenant_id.map(str::to_string));
+
+                // Register the user, its credentials and roles in a transaction, so that a
+                // failed signup stores nothing
+                let user = self
+                    .persistent_users_manager
+                    .transaction(move |tx| Box::pin(async move { tx.add_user(user).await }))
+                    .await
+                    .map_err(|e| AuthError::SignupError(format!("signup: {e}")))?;
+                reservation.commit();
+
+                // Generate tokens
+                let tokens = self
+                    .issue_tokens(
+                        &user,
+                        vec![crate::core::token::claims::amr::PASSWORD.to_string()],
+                    )
+                    .await?;
+                Ok((user, tokens))
+            }
+            SignupMethod::OAuth2 {
+                provider,
+                code,
+                state,
+            } => self.oauth2_flow(provider, &code, &state, tenant_id).await,
+        }
+    }
+
+    /// Completes an OAuth2 authorization code flow, logging in or creating the matching user.
+    ///
+    /// The provider account is matched by provider user ID first, then by email against the
+    /// credentials of the tenant. When no user matches, a new user is created in the tenant.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidCredentials`] if the provider account is linked to a user of
+    /// another tenant, [`AuthError::OAuthEmailNotVerified`] if the provider requires a verified
+    /// email and does not report one, [`AuthError::DisposableEmailNotAllowed`] if an unlinked
+    /// account's email is at a disposable domain, [`AuthError::TooManyLinkedAccounts`] if the
+    /// matched user already links the maximum number of providers, or other variants for OAuth2
+    /// and storage failures.
+    async fn oauth2_flow(
+        &self,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        code: &str,
+        state: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        let user = self
+            .oauth2_authenticate(provider, code, state, tenant_id)
+            .await?;
+        let tokens = self
+            .issue_tokens(
+                &user,
+                vec![crate::core::token::claims::amr::oauth(provider)],
+            )
+            .await?;
+        Ok((user, tokens))
+    }
+
+    /// Runs the OAuth2 flow of [`AuthService::oauth2_flow`] without issuing tokens.
+    async fn oauth2_authenticate(
+        &self,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        code: &str,
+        state: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<User, AuthError> {
+        // Exchange code for token
+        let oauth_token = self
+            .exchange_oauth2_code_for_token(provider, code, state)
+            .await?;
+
+        // Fetch user info from OAuth provider
+        let oauth_user_info = self.fetch_oauth2_user_info(&oauth_token).await?;
+        self.oauth2_user(oauth_user_info, tenant_id).await
+    }
+
+    /// Logs in or creates the user matching OAuth2 user info, as in [`AuthService::oauth2_flow`].
+    async fn oauth2_user_flow(
+        &self,
+        oauth_user_info: crate::core::oauth::store::OAuth2UserInfo,
+        tenant_id: Option<&str>,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        let provider = oauth_user_info.provider;
+        let user = self.oauth2_user(oauth_user_info, tenant_id).await?;
+        let tokens = self
+            .issue_tokens(
+                &user,
+                vec![crate::core::token::claims::amr::oauth(provider)],
+            )
+            .await?;
+        Ok((user, tokens))
+    }
+
+    /// Finds, links or creates the user matching OAuth2 user info, without issuing tokens.
+    async fn oauth2_user(
+        &self,
+        oauth_user_info: crate::core::oauth::store::OAuth2UserInfo,
+        tenant_id: Option<&str>,
+    ) -> Result<User, AuthError> {
+        let provider = oauth_user_info.provider;
+        if self.oauth2_manager.requires_verified_email(provider)
+            && oauth_user_info.verified_email != Some(true)
+        {
+            return Err(AuthError::OAuthEmailNotVerified);
+        }
+
+        // Try to find existing user by OAuth provider and user ID
+        let existing_user = self
+            .persistent_users_manager
+            .find_user_by_oauth_id(provider, &oauth_user_info.provider_user_id)
+            .await?;
+
+        let user = if let Some(mut user) = existing_user {
+            if user.tenant_id.as_deref() != tenant_id {
+                return Err(AuthError::InvalidCredentials);
+            }
+            Self::ensure_active(&user)?;
+            // Update OAuth account info, best-effort like the last login it is stored with
+            user.oauth_accounts.insert(provider, oauth_user_info);
+            user.updated_at = chrono::Utc::now().naive_utc();
+            if self.vars.disable_last_login_tracking {
+                if let Err(e) = self.persistent_users_manager.update_user(&user).await {
+                    log::warn!(
+                        "Failed to refresh OAuth2 account of user {}: {e}",
+                        self.log_id(&user.id)
+                    );
+                }
+            } else {
+                self.record_login(&mut user).await;
+            }
+            user
+        } else {
+            // Check if user exists by email (if provided)
+            let existing_user_by_email = if let Some(ref email) = oauth_user_info.email {
+                self.ensure_not_disposable(email)?;
+                self.persistent_users_manager
+                    .find_user_by_identifier_in_tenant(email, tenant_id)
+                    .await?
+            } else {
+                None
+            };
+
+            if let Some(mut user) = existing_user_by_email {
+                Self::ensure_active(&user)?;
+                self.ensure_can_link(&user, provider)?;
+                if self.vars.require_oauth_link_verification
+                    && user
+                        .credentials
+                        .as_ref()
+                        .is_some_and(crate::core::credentials::Credentials::has_password)
+                {
+                    // Park the account until the owner proves they control the password account
+                    let expires_at = chrono::Utc::now().timestamp().max(0) as u64
+                        + crate::core::oauth::link::DEFAULT_PENDING_LINK_TTL;
+                    self.pending_links
+                        .put(user.id.as_str(), oauth_user_info, expires_at as usize)
+                        .await?;
+                    return Err(AuthError::AccountLinkRequiresVerification {
+                        user_id: user.id,
+                        provider,
+                    });
+                }
+
+                // Link OAuth account to existing user
+                user.oauth_accounts.insert(provider, oauth_user_info);
+                user.updated_at = chrono::Utc::now().naive_utc();
+                self.touch_last_login(&mut user);
+                self.persistent_users_manager.update_user(&user).await?;
+                user
+            } else {
+                // Create new user
+                let mut new_user = User {
+                    id: crate::core::user::UserId::generate(),
+                    tenant_id: tenant_id.map(str::to_string),
+                    ..User::default()
+                };
+                new_user.oauth_accounts.insert(provider, oauth_user_info);
+                new_user.created_at = chrono::Utc::now().naive_utc();
+                new_user.updated_at = new_user.created_at;
+                self.touch_last_login(&mut new_user);
+
+                // The user and its OAuth account are stored together or not at all
+                self.persistent_users_manager
+                    .transaction(move |tx| Box::pin(async move { tx.add_user(new_user).await }))
+                    .await?
+            }
+        };
+        Ok(user)
+    }
+
+    /// Computes the custom access token claims of a loaded user: the claims of the enricher,
+    /// the user's [`roles`](User::roles) if the enricher sets none, plus the
+    /// `linked_providers` claim when
+    /// [`AuthServiceVariables::linked_providers_claim`](crate::core::vars::AuthServiceVariables::linked_providers_claim)
+    /// is set.
+    async fn custom_claims(
+        &self,
+        user: &User,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, AuthError> {
+        let mut claims = self.claims_enricher.enrich(user).await?;
+        if !user.roles.is_empty() && !claims.contains_key("roles") {
+            claims.insert("roles".to_string(), serde_json::json!(user.roles));
+        }
+        if self.vars.linked_providers_claim {
+            let providers = crate::core::oauth::store::OAuth2Provider::all()
+                .iter()
+                .filter(|provider| user.has_oauth_account(**provider))
+                .map(|provider| serde_json::Value::String(provider.as_str().to_string()))
+                .collect();
+            claims.insert(
+                "linked_providers".to_string(),
+                serde_json::Value::Array(providers),
+            );
+        }
+        Ok(claims)
+    }
+
+    /// Generates a token pair for the given user who just authenticated with the methods
+    /// `amr`, embedding its tenant, enriched claims and the authentication time.
+    async fn issue_tokens(
+        &self,
+        user: &User,
+        amr: Vec<String>,
+    ) -> Result<crate::core::token::TokenPair, AuthError> {
+        self.issue_tokens_with_refresh_expiration(user, amr, None)
+            .await
+    }
+
+    /// Like [`Self::issue_tokens`], with a refresh token lifetime overriding the default.
+    async fn issue_tokens_with_refresh_expiration(
+        &self,
+        user: &User,
+        amr: Vec<String>,
+        refresh_expiration: Option<u64>,
+    ) -> Result<crate::core::token::TokenPair, AuthError> {
+        let options = crate::core::token::TokenOptions {
+            tenant_id: user.tenant_id.clone(),
+            custom_claims: self.custom_claims(user).await?,
+            auth_time: Some(chrono::Utc::now().timestamp().max(0) as usize),
+            amr,
+            refresh_expiration,
+            ..Default::default()
+        };
+        self.issue_session_tokens(user.id.as_str(), options).await
+    }
+
+    /// Starts a new session for `user_id`, recording it in the session store.
+    ///
+    /// The session ID of `options` is replaced by the new session.
+    async fn issue_session_tokens(
+        &self,
+        user_id: &str,
+        options: crate::core::token::TokenOptions,
+    ) -> Result<crate::core::token::TokenPair, AuthError> {
+        self.issue_session_tokens_with(user_id, options, async |options| {
+            self.token_manager
+                .generate_token_pair_with(user_id, options)
+                .await
+        })
+        .await
+    }
+
+    /// Starts a new session for `user_id` as [`Self::issue_session_tokens`] does, issuing its
+    /// tokens with `issue`.
+    async fn issue_session_tokens_with<T>(
+        &self,
+        user_id: &str,
+        mut options: crate::core::token::TokenOptions,
+        issue: impl AsyncFnOnce(&crate::core::token::TokenOptions) -> Result<T, AuthError>,
+    ) -> Result<T, AuthError> {
+        let session_id = uuid::Uuid::new_v4().to_string();
+        options.session_id = Some(session_id.clone());
+        let issued = issue(&options).await?;
+        self.sessions
+            .record(
+                user_id,
+                &session_id,
+                self.session_expiration(options.refresh_expiration),
+            )
+            .await?;
+        Ok(issued)
+    }
+
+    /// Returns the token options of a user ID, embedding the tenant of the user if it exists.
+    ///
+    /// # Errors
+    /// Returns an error if the repository could not be queried.
+    async fn user_token_options(
+        &self,
+        id: &crate::core::user::UserId,
+    ) -> Result<crate::core::token::TokenOptions, AuthError> {
+        let user = self.persistent_users_manager.find_user_by_id(id).await?;
+        Ok(crate::core::token::TokenOptions {
+            tenant_id: user.and_then(|user| user.tenant_id),
+            ..Default::default()
+        })
+    }
+
+    /// Generates an access token, without a refresh token, for the given user who just
+    /// authenticated with the methods `amr`, embedding the same values as
+    /// [`Self::issue_tokens`].
+    async fn issue_access_token(&self, user: &User, amr: Vec<String>) -> Result<String, AuthError> {
+        let options = crate::core::token::TokenOptions {
+            tenant_id: user.tenant_id.clone(),
+            custom_claims: self.custom_claims(user).await?,
+            auth_time: Some(chrono::Utc::now().timestamp().max(0) as usize),
+            amr,
+            ..Default::default()
+        };
+        self.issue_session_access_token(user.id.as_str(), options)
+            .await
+    }
+
+    /// Starts a new session for `user_id` as [`Self::issue_session_tokens`] does, issuing an
+    /// access token only. The session ends when the access token expires.
+    async fn issue_session_access_token(
+        &self,
+        user_id: &str,
+        mut options: crate::core::token::TokenOptions,
+    ) -> Result<String, AuthError> {
+        let session_id = uuid::Uuid::new_v4().to_string();
+        options.session_id = Some(session_id.clone());
+        let access_token = self
+            .token_manager
+            .generate_access_token_with(user_id, &options)
+            .await?;
+        let expires_at = (chrono::Utc::now().timestamp().max(0) as u64)
+            .saturating_add(self.vars.token_expiration) as usize;
+        self.sessions
+            .record(user_id, &session_id, expires_at)
+            .await?;
+        Ok(access_token)
+    }
+
+    /// Returns the expiration of a session started or refreshed now, whose refresh token lives
+    /// `refresh_expiration` seconds (default: the configured refresh token lifetime).
+    fn session_expiration(&self, refresh_expiration: Option<u64>) -> usize {
+        (chrono::Utc::now().timestamp().max(0) as u64)
+            .saturating_add(refresh_expiration.unwrap_or(self.vars.refresh_token_expiration))
+            as usize
+    }
+
+    /// Fails if the token described by `claims` belongs to a revoked session.
+    async fn ensure_not_revoked(
+        &self,
+        claims: &(dyn crate::core::token::claims::Claims + Send + Sync),
+    ) -> Result<(), AuthError> {
+        if self
+            .sessions
+            .is_revoked(
+                claims.get_subject(),
+                claims.get_session_id(),
+                claims.get_issued_at(),
+            )
+            .await?
+        {
+            return Err(AuthError::InvalidToken(
+                "Token has been revoked".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Consumes a refresh token if the token service rotates refresh tokens, rejecting reuse.
+    ///
+    /// Refreshes call this once the successor tokens are generated, so that a refresh failing
+    /// for another reason does not burn the token. Tokens without a `jti` claim cannot be
+    /// tracked and are let through. A reuse revokes the token's session, as the token may have
+    /// been stolen, or every session of the user if the token has none or the session store
+    /// cannot revoke a single session.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::TokenReuseDetected`] if the token was already consumed (outside the
+    /// grace window of [`RefreshStrategy::RotateWithGrace`](crate::core::token::refresh::RefreshStrategy::RotateWithGrace)),
+    /// or [`AuthError::ServiceUnavailable`] if the store is unavailable.
+    async fn consume_refresh_token(
+        &self,
+        claims: &(dyn crate::core::token::claims::Claims + Send + Sync),
+    ) -> Result<(), AuthError> {
+        use crate::core::token::refresh::RefreshStrategy;
+
+        let grace = match self.token_manager.refresh_strategy() {
+            Some(RefreshStrategy::Rotate) => None,
+            Some(RefreshStrategy::RotateWithGrace(window)) => Some(window),
+            Some(RefreshStrategy::Reuse) | None => return Ok(()),
+        };
+        let Some(jti) = claims.get_token_id() else {
+            return Ok(());
+        };
+        if self
+            .one_time_tokens
+            .consume(jti, claims.get_expiration())
+            .await?
+        {
+            return Ok(());
+        }
+        if let Some(window) = grace
+            && let Some(consumed_at) = self.one_time_tokens.consumed_at(jti).await?
+        {
+            let now = chrono::Utc::now().timestamp().max(0) as usize;
+            if now.saturating_sub(consumed_at) as u64 <= window.as_secs() {
+                return Ok(());
+            }
+        }
+
+        log::warn!(
+            "Reused refresh token of {}",
+            self.log_id(&crate::core::user::UserId::from(claims.get_subject()))
+        );
+        self.audit_log
+            .record(crate::core::audit::AuditEvent::RefreshTokenReused {
+                user_id: Some(self.log_id(&claims.get_subject().into())),
+            });
+        let user_id = claims.get_subject();
+        let revoked = match claims.get_session_id() {
+            Some(session_id) => {
+                self.sessions
+                    .revoke_session(
+                        user_id,
+                        session_id,
+                        self.session_expiration(claims.get_refresh_expiration()),
+                    )
+                    .await
+            }
+            None => Err(AuthError::NotImplemented(
+                "Token has no session".to_string(),
+            )),
+        };
+        match revoked {
+            Err(AuthError::NotImplemented(_)) => {
+                self.sessions.revoke_all_for_user(user_id).await?;
+            }
+            result => {
+                result?;
+            }
+        }
+        self.invalidate_cached_validations(user_id);
+        Err(AuthError::TokenReuseDetected)
+    }
+
+    /// Records a [`RefreshTokenReused`](crate::core::audit::AuditEvent::RefreshTokenReused)
+    /// event if the token service rejected a refresh as a reuse, and passes `result` on.
+    fn report_refresh_reuse(
+        &self,
+        result: Result<crate::core::token::TokenPair, AuthError>,
+        user_id: Option<&str>,
+    ) -> Result<crate::core::token::TokenPair, AuthError> {
+        if let Err(AuthError::TokenReuseDetected) = &result {
+            self.audit_log
+                .record(crate::core::audit::AuditEvent::RefreshTokenReused {
+                    user_id: user_id.map(|user_id| self.log_id(&user_id.into())),
+                });
+        }
+        result
+    }
+
+    /// Checks that linking `provider` keeps `user` within
+    /// [`AuthServiceVariables::max_linked_providers`](crate::core::vars::AuthServiceVariables::max_linked_providers).
+    ///
+    /// # Errors
+    /// Returns [`AuthError::TooManyLinkedAccounts`] if the user already links the maximum
+    /// number of other providers.
+    fn ensure_can_link(
+        &self,
+        user: &User,
+        provider: crate::core::oauth::store::OAuth2Provider,
+    ) -> Result<(), AuthError> {
+        if let Some(max) = self.vars.max_linked_providers
+            && !user.has_oauth_account(provider)
+            && user.oauth_accounts.len() >= max as usize
+        {
+            return Err(AuthError::TooManyLinkedAccounts { max });
+        }
+        Ok(())
+    }
+
+    /// Fails with [`AuthError::AccountDisabled`] if `user` is suspended or deleted.
+    fn ensure_active(user: &User) -> Result<(), AuthError> {
+        if user.status.is_active() {
+            Ok(())
+        } else {
+            Err(AuthError::AccountDisabled)
+        }
+    }
+
+    /// Fails if user statuses are verified on validation and the subject of `claims` is
+    /// missing or not active.
+    ///
+    /// # Returns
+    /// The user read from the repository if statuses are verified, `None` otherwise.
+    async fn ensure_subject_active(
+        &self,
+        claims: &(dyn crate::core::token::claims::Claims + Send + Sync),
+    ) -> Result<Option<User>, AuthError> {
+        if !self.vars.verify_user_status_on_validation {
+            return Ok(None);
+        }
+        let user = self
+            .persistent_users_manager
+            .find_user_by_id(&claims.get_subject().into())
+            .await?
+            .ok_or_else(|| AuthError::InvalidToken("Token user no longer exists".to_string()))?;
+        Self::ensure_active(&user)?;
+        Ok(Some(user))
+    }
+
+    /// Drops the cached validations of the tokens of `user_id`.
+    fn invalidate_cached_validations(&self, user_id: &str) {
+        if let Some(cache) = &self.validation_cache {
+            cache.invalidate_user(user_id);
+        }
+    }
+
+    /// Sets the status of a user, e.g. to suspend or reactivate their account.
+    ///
+    /// Suspended and deleted users can no longer log in. Their existing tokens stay valid
+    /// unless [`AuthServiceVariables::verify_user_status_on_validation`](crate::core::vars::AuthServiceVariables::verify_user_status_on_validation)
+    /// is set or their sessions are revoked with [`AuthService::revoke_all_for_user`].
+    ///
+    /// # Arguments
+    /// * `user_id` - The user whose status to set.
+    /// * `status` - The new status.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::UserNotFound`] if the user does not exist, or an error if the
+    /// repository update fails.
+    pub async fn set_user_status(
+        &self,
+        user_id: impl Into<crate::core::user::UserId>,
+        status: crate::core::user::UserStatus,
+    ) -> Result<(), AuthError> {
+        let mut user = self
+            .persistent_users_manager
+            .find_user_by_id(&user_id.into())
+            .await?
+            .ok_or(AuthError::UserNotFound)?;
+        user.status = status;
+        user.updated_at = chrono::Utc::now().naive_utc();
+        self.persistent_users_manager.update_user(&user).await?;
+        self.invalidate_cached_validations(user.id.as_str());
+        self.audit_log
+            .record(crate::core::audit::AuditEvent::UserStatusChanged {
+                user_id: self.log_id(&user.id),
+                status: status.as_str().to_string(),
+            });
+        Ok(())
+    }
+
+    /// Revokes every session of a user ("sign out everywhere").
+    ///
+    /// Access and refresh tokens of the revoked sessions are rejected from now on, as are the
+    /// user's tokens issued without a session. Calling it again is harmless and reports no
+    /// new sessions.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user whose sessions to revoke.
+    ///
+    /// # Returns
+    /// The number of live sessions revoked by this call.
+    ///
+    /// # Errors
+    /// Returns an error if the session store is unavailable.
+    pub async fn revoke_all_for_user(&self, user_id: &str) -> Result<u32, AuthError> {
+        let revoked = self.sessions.revoke_all_for_user(user_id).await?;
+        self.invalidate_cached_validations(user_id);
+        self.audit_log
+            .record(crate::core::audit::AuditEvent::SessionsRevoked {
+                user_id: self.log_id(&user_id.into()),
+            });
+        Ok(revoked)
+    }
+
+    /// Creates an API key authenticating as `user_id`.
+    ///
+    /// Only an HMAC-SHA256 tag of the key, keyed by a key derived from `vars.secret_key`, is
+    /// stored: the returned key cannot be retrieved again and must be shown to the user now.
+    /// Keys carry 256 bits of entropy, so the tag needs no slow password hash to resist
+    /// guessing, and verifying a key costs a single HMAC.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user the key authenticates as.
+    /// * `label` - A label to recognize the key in [`AuthService::list_api_keys`].
+    ///
+    /// # Returns
+    /// The API key and the description of its record.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::UserNotFound`] if the user does not exist, or an error if storing
+    /// the key fails.
+    pub async fn create_api_key(
+        &self,
+        user_id: &str,
+        label: &str,
+    ) -> Result<(String, crate::core::api_key::ApiKeyInfo), AuthError> {
+        let user = self
+            .persistent_users_manager
+            .find_user_by_id(&user_id.into())
+            .await?
+            .ok_or(AuthError::UserNotFound)?;
+
+        let key_id = uuid::Uuid::new_v4().simple().to_string();
+        let secret =
+            crate::core::rand::secure_random_string(crate::core::api_key::API_KEY_SECRET_BYTES);
+        let record = crate::core::api_key::ApiKeyRecord {
+            id: key_id.clone(),
+            user_id: user.id.to_string(),
+            label: label.to_string(),
+            secret_hash: self.keyed_tag(
+                crate::core::kdf::API_KEY_LABEL,
+                crate::core::api_key::format_api_key(&key_id, &secret).as_bytes(),
+            ),
+            created_at: chrono::Utc::now().naive_utc(),
+            last_used_at: None,
+        };
+        let info = crate::core::api_key::ApiKeyInfo::from(&record);
+        self.api_keys.insert(record).await?;
+        self.audit_log
+            .record(crate::core::audit::AuditEvent::ApiKeyCreated {
+                user_id: self.log_id(&user.id),
+                key_id: key_id.clone(),
+            });
+        Ok((crate::core::api_key::format_api_key(&key_id, &secret), info))
+    }
+
+    /// Authenticates a request made with an API key, recording the key's use.
+    ///
+    /// # Arguments
+    /// * `api_key` - The API key presented by the client.
+    ///
+    /// # Returns
+    /// The user the key authenticates as.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidCredentials`] if the key is malformed, unknown, revoked or
+    /// wrong, or its user no longer exists, [`AuthError::AccountDisabled`] if the user is not
+    /// active, and [`AuthError::StorageUnavailable`] if the user could not be read.
+    pub async fn verify_api_key(&self, api_key: &str) -> Result<User, AuthError> {
+        let (key_id, _) =
+            crate::core::api_key::parse_api_key(api_key).ok_or(AuthError::InvalidCredentials)?;
+        let record = self
+            .api_keys
+            .get(key_id)
+            .await?
+            .ok_or(AuthError::InvalidCredentials)?;
+        if !self.keyed_tag_matches(
+            crate::core::kdf::API_KEY_LABEL,
+            api_key.as_bytes(),
+            &record.secret_hash,
+        ) {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        let user = self
+            .persistent_users_manager
+            .find_user_by_id(&record.user_id.as_str().into())
+            .await?
+            .ok_or(AuthError::InvalidCredentials)?;
+        Self::ensure_active(&user)?;
+        self.api_keys
+            .touch(key_id, chrono::Utc::now().naive_utc())
+            .await?;
+        Ok(user)
+    }
+
+    /// Lists the API keys of a user, oldest first. Secrets are never returned.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user whose keys to list.
+    ///
+    /// # Returns
+    /// The labels, creation and last use timestamps of the user's keys.
+    ///
+    /// # Errors
+    /// Returns an error if the API key store is unavailable.
+    pub async fn list_api_keys(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<crate::core::api_key::ApiKeyInfo>, AuthError> {
+        Ok(self
+            .api_keys
+            .list_for_user(user_id)
+            .await?
+            .iter()
+            .map(crate::core::api_key::ApiKeyInfo::from)
+            .collect())
+    }
+
+    /// Revokes an API key of a user. The key stops authenticating immediately.
+    ///
+    /// # Arguments
+    /// * `user_id` - The owner of the key.
+    /// * `key_id` - The ID of the key, as returned by [`AuthService::list_api_keys`].
+    ///
+    /// # Errors
+    /// Returns [`AuthError::ApiKeyNotFound`] if the user has no such key, or an error if the
+    /// API key store is unavailable.
+    pub async fn revoke_api_key(&self, user_id: &str, key_id: &str) -> Result<(), AuthError> {
+        if self.api_keys.remove(user_id, key_id).await? {
+            self.audit_log
+                .record(crate::core::audit::AuditEvent::ApiKeyRevoked {
+                    user_id: self.log_id(&user_id.into()),
+                    key_id: key_id.to_string(),
+                });
+            Ok(())
+        } else {
+            Err(AuthError::ApiKeyNotFound)
+        }
+    }
+
+    /// Generates a new token pair (access and refresh tokens) for a given user ID.
+    ///
+    /// The tokens are issued for the tenant of the user, if any.
+    ///
+    /// # Arguments
+    /// * `id` - The user ID for which to generate tokens.
+    ///
+    /// # Returns
+    /// Returns a [`TokenPair`] containing access and refresh tokens, or an [`AuthError`] if generation fails.
+    pub async fn get_tokens(
+        &self,
+        id: impl Into<crate::core::user::UserId>,
+    ) -> Result<crate::core::token::TokenPair, AuthError> {
+        let id = id.into();
+        let options = self.user_token_options(&id).await?;
+        self.issue_session_tokens(id.as_str(), options).await
+    }
+
+    /// Generates an access token for a given user ID, without a refresh token.
+    ///
+    /// Suits stateless API clients that authenticate again once the access token expires
+    /// rather than refreshing it. Like [`AuthService::get_tokens`], the token starts a new
+    /// session, which ends when the token expires. The token is issued for the tenant of the
+    /// user, if any.
+    ///
+    /// # Arguments
+    /// * `id` - The user ID for which to generate the token.
+    ///
+    /// # Returns
+    /// Returns the access token, or an [`AuthError`] if generation fails.
+    pub async fn get_access_token_only(
+        &self,
+        id: impl Into<crate::core::user::UserId>,
+    ) -> Result<String, AuthError> {
+        let id = id.into();
+        let options = self.user_token_options(&id).await?;
+        self.issue_session_access_token(id.as_str(), options).await
+    }
+
+    /// Generates a new token pair for a given user ID, also returning the access token claims.
+    ///
+    /// Like [`AuthService::get_tokens`], the tokens start a new session. They are issued for
+    /// the tenant of the user, if any. The claims give the expiration, issued-at timestamp,
+    /// session ID and `jti` of the access token without decoding it again.
+    ///
+    /// # Arguments
+    /// * `id` - The user ID for which to generate tokens.
+    ///
+    /// # Returns
+    /// Returns the [`TokenPair`] and the
+    /// [`AccessTokenClaims`](crate::core::token::claims::AccessTokenClaims) of its access
+    /// token, or an [`AuthError`] if generation fails.
+    pub async fn generate_token_pair_with_claims_out(
+        &self,
+        id: impl Into<crate::core::user::UserId>,
+    ) -> Result<
+        (
+            crate::core::token::TokenPair,
+            crate::core::token::claims::AccessTokenClaims,
+        ),
+        AuthError,
+    > {
+        let id = id.into();
+        let options = self.user_token_options(&id).await?;
+        self.issue_session_tokens_with(id.as_str(), options, async |options| {
+            self.token_manager
+                .generate_token_pair_with_claims_out(id.as_str(), options)
+                .await
+        })
+        .await
+    }
+
+    /// Generates a new token pair for a given user ID, issued for a specific audience.
+    ///
+    /// Use it to mint tokens for one of several services: each service's [`AuthService`] is
+    /// configured with its own audience (see
+    /// [`AuthServiceVariables::token_audiences`](crate::core::vars::AuthServiceVariables::token_audiences))
+    /// and rejects tokens issued for the others. The tokens are issued for the tenant of the
+    /// user, if any.
+    ///
+    /// # Arguments
+    /// * `id` - The user ID for which to generate tokens.
+    /// * `audience` - The audience (consuming service) embedded in the `aud` claim.
+    ///
+    /// # Returns
+    /// Returns a [`TokenPair`] containing access and refresh tokens, or an [`AuthError`] if generation fails.
+    pub async fn generate_token_pair_for_audience(
+        &self,
+        id: impl Into<crate::core::user::UserId>,
+        audience: &str,
+    ) -> Result<crate::core::token::TokenPair, AuthError> {
+        let id = id.into();
+        let options = crate::core::token::TokenOptions {
+            audience: Some(audience.to_string()),
+            ..self.user_token_options(&id).await?
+        };
+        self.issue_session_tokens(id.as_str(), options).await
+    }
+
+    /// Generates a new token pair for a given user ID, bound to a client fingerprint.
+    ///
+    /// The fingerprint is embedded in the `cnf` claim of both tokens and kept when they are
+    /// refreshed. Services receiving bound tokens must check them with
+    /// [`AuthService::validate_access_token_bound`], so that a stolen token is useless from
+    /// another client. The fingerprint is stored in the token as is: pass a hash (e.g., of the
+    /// client's TLS certificate) rather than a raw device identifier. The tokens are issued for
+    /// the tenant of the user, if any.
+    ///
+    /// # Arguments
+    /// * `id` - The user ID for which to generate tokens.
+    /// * `fingerprint` - The fingerprint of the client the tokens are issued to.
+    ///
+    /// # Returns
+    /// Returns a [`TokenPair`] containing access and refresh tokens, or an [`AuthError`] if generation fails.
+    pub async fn generate_token_pair_bound(
+        &self,
+        id: impl Into<crate::core::user::UserId>,
+        fingerprint: &str,
+    ) -> Result<crate::core::token::TokenPair, AuthError> {
+        let id = id.into();
+        let options = crate::core::token::TokenOptions {
+            fingerprint: Some(fingerprint.to_string()),
+            ..self.user_token_options(&id).await?
+        };
+        self.issue_session_tokens(id.as_str(), options).await
+    }
+
+    /// Validates an access token and returns the associated claims.
+    ///
+    /// The claims are those returned by the
+    /// [`claims_transformer`](AuthService::with_claims_transformer), applied once every check
+    /// passed.
+    ///
+    /// # Arguments
+    /// * `token` - The access token to validate.
+    ///
+    /// # Returns
+    /// Returns the token claims if valid, or an [`AuthError`] if validation fails, including
+    /// [`AuthError::InvalidToken`] if the token's session was revoked or the token was issued
+    /// for a tenant (validate those with [`TenantAuthService::validate_access_token`]). When
+    /// [`AuthServiceVariables::verify_user_status_on_validation`](crate::core::vars::AuthServiceVariables::verify_user_status_on_validation)
+    /// is set, also fails with [`AuthError::AccountDisabled`] if the user is not active.
+    pub async fn validate_access_token(
+        &self,
+        token: &str,
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        self.validate_access_token_in_tenant(token, None).await
+    }
+
+    /// Validates an access token issued for `tenant_id`, or for no tenant if `None`.
+    async fn validate_access_token_in_tenant(
+        &self,
+        token: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        let claims = self.validate_access_token_of_any_tenant(token).await?;
+        Self::ensure_token_tenant(claims.as_ref(), tenant_id)?;
+        Ok(claims)
+    }
+
+    /// Validates an access token issued for any tenant, or for none.
+    ///
+    /// Used by operations acting on the token's own user, wherever it belongs.
+    async fn validate_access_token_of_any_tenant(
+        &self,
+        token: &str,
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        Ok(self.validate_access_token_with_user(token).await?.0)
+    }
+
+    /// Fails if the token described by `claims` was not issued for `tenant_id`.
+    fn ensure_token_tenant(
+        claims: &(dyn crate::core::token::claims::Claims + Send + Sync),
+        tenant_id: Option<&str>,
+    ) -> Result<(), AuthError> {
+        if claims.get_tenant_id() != tenant_id {
+            return Err(AuthError::InvalidToken(
+                "Token was issued for another tenant".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Validates several access tokens concurrently, e.g. to warm a gateway's cache.
+    ///
+    /// Each token is validated as by [`AuthService::validate_access_token`]. At most
+    /// [`AuthServiceVariables::validation_concurrency`](crate::core::vars::AuthServiceVariables::validation_concurrency)
+    /// validations run at once, so a large batch does not flood the session store and the
+    /// repository.
+    ///
+    /// # Arguments
+    /// * `tokens` - The access tokens to validate.
+    ///
+    /// # Returns
+    /// The outcome of each token's validation, in the order of `tokens`.
+    pub async fn validate_many(
+        &self,
+        tokens: &[String],
+    ) -> Vec<Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError>> {
+        use futures_util::StreamExt;
+
+        let concurrency = self
+            .vars
+            .validation_concurrency
+            .unwrap_or(crate::core::token::DEFAULT_VALIDATION_CONCURRENCY)
+            .max(1) as usize;
+        futures_util::stream::iter(tokens)
+            .map(|token| self.validate_access_token(token))
+            .buffered(concurrency)
+            .collect()
+            .await
+    }
+
+    /// Validates an access token and returns its user together with its claims.
+    ///
+    /// The token is validated once, as with [`AuthService::validate_access_token`], and the
+    /// user is read from the repository at most once: the user read while checking its status
+    /// (or cached with the validation) is reused. This is the usual entry point of
+    /// authentication middleware, replacing a [`AuthService::get_user_id_from_token`] then
+    /// `get_user_by_id` sequence.
+    ///
+    /// # Arguments
+    /// * `token` - The access token to validate.
+    ///
+    /// # Returns
+    /// The token's user and its claims (e.g., to read the `jti` or custom claims).
+    ///
+    /// # Errors
+    /// Returns [`AuthError::UserNotFound`] if the token's user does not exist, or the errors of
+    /// [`AuthService::validate_access_token`].
+    pub async fn authenticate_request(
+        &self,
+        token: &str,
+    ) -> Result<
+        (
+            User,
+            Box<dyn crate::core::token::claims::Claims + Send + Sync>,
+        ),
+        AuthError,
+    > {
+        self.authenticate_request_in_tenant(token, None).await
+    }
+
+    /// Authenticates a request of a user of the given tenant, as
+    /// [`AuthService::authenticate_request`] does.
+    async fn authenticate_request_in_tenant(
+        &self,
+        token: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<
+        (
+            User,
+            Box<dyn crate::core::token::claims::Claims + Send + Sync>,
+        ),
+        AuthError,
+    > {
+        let (claims, user) = self.validate_access_token_with_user(token).await?;
+        Self::ensure_token_tenant(claims.as_ref(), tenant_id)?;
+        let user = match user {
+            Some(user) => user,
+            None => self
+                .persistent_users_manager
+                .find_user_by_id(&claims.get_subject().into())
+                .await?
+                .ok_or(AuthError::UserNotFound)?,
+        };
+        Ok((user, claims))
+    }
+
+    /// Validates an access token, returning the user read while validating it, if any.
+    async fn validate_access_token_with_user(
+        &self,
+        token: &str,
+    ) -> Result<
+        (
+            Box<dyn crate::core::token::claims::Claims + Send + Sync>,
+            Option<User>,
+        ),
+        AuthError,
+    > {
+        let claims = self.token_manager.validate_access_token(token).await?;
+        let cache = self.validation_cache.as_ref().zip(claims.get_token_id());
+        if let Some((cache, jti)) = cache
+            && let Some(cached) = cache.get(jti)
+        {
+            let user = cached.user;
+            return Ok((self.claims_transformer.transform(claims).await?, user));
+        }
+
+        self.ensure_not_revoked(claims.as_ref()).await?;
+        let user = self.ensure_subject_active(claims.as_ref()).await?;
+        if let Some((cache, jti)) = cache {
+            cache.insert(jti, claims.get_subject(), user.clone());
+        }
+        Ok((self.claims_transformer.transform(claims).await?, user))
+    }
+
+    /// Validates an access token and returns the roles of its user.
+    ///
+    /// # Arguments
+    /// * `token` - The access token to validate.
+    /// * `source` - Where to read the roles from, or `None` for
+    ///   [`AuthServiceVariables::role_source`](crate::core::vars::AuthServiceVariables::role_source).
+    ///   [`RoleSource::Token`](crate::core::user::RoleSource::Token) reads the token's `roles`
+    ///   claim; [`RoleSource::Repository`](crate::core::user::RoleSource::Repository) reads the
+    ///   user's current roles, bypassing the validation cache.
+    ///
+    /// # Returns
+    /// The roles of the token's user.
+    ///
+    /// # Errors
+    /// Returns the errors of [`AuthService::validate_access_token`], and when reading from the
+    /// repository, [`AuthError::InvalidToken`] if the token's user no longer exists or
+    /// [`AuthError::AccountDisabled`] if they are not active.
+    pub async fn user_roles(
+        &self,
+        token: &str,
+        source: Option<crate::core::user::RoleSource>,
+    ) -> Result<Vec<String>, AuthError> {
+        self.user_roles_in_tenant(token, source, None).await
+    }
+
+    /// Validates an access token issued for `tenant_id` and returns the roles of its user.
+    async fn user_roles_in_tenant(
+        &self,
+        token: &str,
+        source: Option<crate::core::user::RoleSource>,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<String>, AuthError> {
+        let claims = self
+            .validate_access_token_in_tenant(token, tenant_id)
+            .await?;
+        match source.unwrap_or(self.vars.role_source) {
+            crate::core::user::RoleSource::Token => Ok(Self::token_roles(claims.as_ref())),
+            crate::core::user::RoleSource::Repository => {
+                let user = self
+                    .persistent_users_manager
+                    .find_user_by_id(&claims.get_subject().into())
+                    .await?
+                    .ok_or_else(|| {
+                        AuthError::InvalidToken("Token user no longer exists".to_string())
+                    })?;
+                Self::ensure_active(&user)?;
+                Ok(user.roles)
+            }
+        }
+    }
+
+    /// Validates an access token and checks whether its user was granted a role.
+    ///
+    /// # Arguments
+    /// * `token` - The access token to validate.
+    /// * `role` - The role to look for (e.g., [`ADMIN_ROLE`](crate::core::user::ADMIN_ROLE)).
+    /// * `source` - Where to read the roles from, as for [`AuthService::user_roles`].
+    ///
+    /// # Returns
+    /// True if the user has the role, false otherwise.
+    ///
+    /// # Errors
+    /// Returns the errors of [`AuthService::user_roles`].
+    pub async fn user_has_role(
+        &self,
+        token: &str,
+        role: &str,
+        source: Option<crate::core::user::RoleSource>,
+    ) -> Result<bool, AuthError> {
+        Ok(self
+            .user_roles(token, source)
+            .await?
+            .iter()
+            .any(|granted| granted == role))
+    }
+
+    /// Returns the roles carried by validated claims, in their `roles` claim.
+    fn token_roles(claims: &(dyn crate::core::token::claims::Claims + Send + Sync)) -> Vec<String> {
+        if !claims.get_roles().is_empty() {
+            return claims.get_roles().to_vec();
+        }
+        claims
+            .get_custom_claims()
+            .and_then(|custom| custom.get("roles"))
+            .and_then(serde_json::Value::as_array)
+            .map(|roles| {
+                roles
+                    .iter()
+                    .filter_map(serde_json::Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Validates an access token and checks that the user authenticated recently.
+    ///
+    /// Sensitive operations (changing an email, deleting an account) can require a fresh
+    /// login: when this check fails, the application asks the user to authenticate again and
+    /// retries with the new token. Refreshed tokens keep the time of the original login, so
+    /// refreshing does not pass the check.
+    ///
+    /// # Arguments
+    /// * `token` - The access token to validate.
+    /// * `max_age` - The maximum time elapsed since the user authenticated.
+    ///
+    /// # Returns
+    /// The token claims if the token is valid and its `auth_time` is recent enough.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::StepUpRequired`] if the token has no `auth_time` or it is older
+    /// than `max_age`, or the errors of [`AuthService::validate_access_token`].
+    pub async fn require_recent_auth(
+        &self,
+        token: &str,
+        max_age: std::time::Duration,
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        self.require_recent_auth_in_tenant(token, max_age, None)
+            .await
+    }
+
+    /// Validates an access token issued for `tenant_id` and checks that the user
+    /// authenticated recently.
+    async fn require_recent_auth_in_tenant(
+        &self,
+        token: &str,
+        max_age: std::time::Duration,
+        tenant_id: Option<&str>,
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        let claims = self
+            .validate_access_token_in_tenant(token, tenant_id)
+            .await?;
+        let auth_time = claims.get_auth_time().ok_or_else(|| {
+            AuthError::StepUpRequired("token does not record an authentication time".to_string())
+        })?;
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        if now.saturating_sub(auth_time as u64) > max_age.as_secs() {
+            return Err(AuthError::StepUpRequired(format!(
+                "authentication is older than {}s",
+                max_age.as_secs()
+            )));
+        }
+        Ok(claims)
+    }
+
+    /// Validates an access token and checks that the user authenticated with every method of
+    /// `required` (e.g., `&["otp"]` for a one-time password).
+    ///
+    /// # Arguments
+    /// * `token` - The access token to validate.
+    /// * `required` - The `amr` values the token must carry; see
+    ///   [`amr`](crate::core::token::claims::amr) for those recorded by the login flows.
+    ///
+    /// # Returns
+    /// The token claims if the token is valid and carries every required method.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::StepUpRequired`] naming the missing methods, or the errors of
+    /// [`AuthService::validate_access_token`].
+    pub async fn require_amr(
+        &self,
+        token: &str,
+        required: &[&str],
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        self.require_amr_in_tenant(token, required, None).await
+    }
+
+    /// Validates an access token issued for `tenant_id` and checks that the user
+    /// authenticated with every method of `required`.
+    async fn require_amr_in_tenant(
+        &self,
+        token: &str,
+        required: &[&str],
+        tenant_id: Option<&str>,
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        let claims = self
+            .validate_access_token_in_tenant(token, tenant_id)
+            .await?;
+        let missing: Vec<&str> = required
+            .iter()
+            .copied()
+            .filter(|method| !claims.get_amr().iter().any(|amr| amr == method))
+            .collect();
+        if !missing.is_empty() {
+            return Err(AuthError::StepUpRequired(format!(
+                "missing authentication methods: {}",
+                missing.join(", ")
+            )));
+        }
+        Ok(claims)
+    }
+
+    /// Validates an access token bound to a client fingerprint, as issued by
+    /// [`AuthService::generate_token_pair_bound`].
+    ///
+    /// # Arguments
+    /// * `token` - The access token to validate.
+    /// * `presented_fingerprint` - The fingerprint of the client presenting the token.
+    ///
+    /// # Returns
+    /// Returns the token claims if the token is valid and bound to the presenting client.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::TokenBindingMismatch`] if the token is unbound or bound to another
+    /// fingerprint, or the errors of [`AuthService::validate_access_token`].
+    pub async fn validate_access_token_bound(
+        &self,
+        token: &str,
+        presented_fingerprint: &str,
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        let claims = self.validate_access_token(token).await?;
+        let bound = claims.get_fingerprint().is_some_and(|fingerprint| {
+            crate::core::token::claims::fingerprint_matches(fingerprint, presented_fingerprint)
+        });
+        if !bound {
+            return Err(AuthError::TokenBindingMismatch);
+        }
+        Ok(claims)
+    }
+
+    /// Refreshes an access token using a valid refresh token.
+    ///
+    /// If the token service rotates refresh tokens with reuse detection (see
+    /// [`TokenService::refresh_strategy`](crate::core::token::TokenService::refresh_strategy)),
+    /// the refresh token is consumed in the single-use token store, and presenting it again
+    /// is rejected, audited and revokes the session of the token.
+    ///
+    /// # Arguments
+    /// * `refresh_token` - The refresh token to use for generating a new access token.
+    ///
+    /// # Returns
+    /// Returns a new [`TokenPair`] if the refresh token is valid, or an [`AuthError`] if refresh fails,
+    /// including [`AuthError::InvalidToken`] if the token's session was revoked,
+    /// [`AuthError::TokenReuseDetected`] if the token was already used, or
+    /// [`AuthError::AccountDisabled`] if user statuses are verified and the user is not active.
+    pub async fn refresh_access_token(
+        &self,
+        refresh_token: &str,
+    ) -> Result<crate::core::token::TokenPair, AuthError> {
+        // Token services unable to validate refresh tokens alone cannot be checked for revocation
+        let claims = match self
+            .token_manager
+            .validate_refresh_token(refresh_token)
+            .await
+        {
+            Ok(claims) => Some(claims),
+            Err(AuthError::NotImplemented(_)) => None,
+            Err(e) => return Err(e),
+        };
+        if let Some(claims) = &claims {
+            self.ensure_not_revoked(claims.as_ref()).await?;
+            self.ensure_subject_active(claims.as_ref()).await?;
+        }
+
+        let tokens = self.report_refresh_reuse(
+            self.token_manager.refresh_access_token(refresh_token).await,
+            claims.as_ref().map(|c| c.get_subject()),
+        )?;
+        if let Some(claims) = &claims {
+            self.consume_refresh_token(claims.as_ref()).await?;
+        }
+        if let Some(claims) = claims
+            && let Some(session_id) = claims.get_session_id()
+        {
+            self.sessions
+                .record(
+                    claims.get_subject(),
+                    session_id,
+                    self.session_expiration(claims.get_refresh_expiration()),
+                )
+                .await?;
+        }
+        Ok(tokens)
+    }
+
+    /// Refreshes an access token, re-populating it from the current state of the user.
+    ///
+    /// [`AuthService::refresh_access_token`] leaves refreshes to the token service, which only
+    /// knows the refresh token: custom claims added by the
+    /// [`ClaimsEnricher`](crate::core::token::enricher::ClaimsEnricher) are lost. This method
+    /// reads the user instead, runs the enricher again and takes the tenant from the user, so
+    /// the new access token carries current roles and tenant. The session, audience and client
+    /// binding of the refresh token are kept.
+    ///
+    /// # Arguments
+    /// * `refresh_token` - The refresh token to use for generating a new access token.
+    ///
+    /// # Returns
+    /// Returns a new [`TokenPair`] if the refresh token is valid.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::NotImplemented`] if the token service cannot validate refresh
+    /// tokens, [`AuthError::InvalidToken`] if the token's session was revoked or its user no
+    /// longer exists, [`AuthError::AccountDisabled`] if the user is not active, or the errors
+    /// of the enricher and the token service.
+    pub async fn refresh_access_token_enriched(
+        &self,
+        refresh_token: &str,
+    ) -> Result<crate::core::token::TokenPair, AuthError> {
+        let claims = self
+            .token_manager
+            .validate_refresh_token(refresh_token)
+            .await?;
+        self.ensure_not_revoked(claims.as_ref()).await?;
+        let user = self
+            .persistent_users_manager
+            .find_user_by_id(&claims.get_subject().into())
+            .await?
+            .ok_or_else(|| AuthError::InvalidToken("Token subject no longer exists".to_string()))?;
+        Self::ensure_active(&user)?;
+
+        let options = crate::core::token::TokenOptions {
+            tenant_id: user.tenant_id.clone(),
+            session_id: claims.get_session_id().map(str::to_string),
+            audience: claims.get_audience().map(str::to_string),
+            fingerprint: claims.get_fingerprint().map(str::to_string),
+            custom_claims: self.custom_claims(&user).await?,
+            auth_time: claims.get_auth_time(),
+            amr: claims.get_amr().to_vec(),
+            refresh_expiration: claims.get_refresh_expiration(),
+        };
+        let tokens = self.report_refresh_reuse(
+            self.token_manager
+                .refresh_access_token_with(refresh_token, user.id.as_str(), &options)
+                .await,
+            Some(user.id.as_str()),
+        )?;
+        self.consume_refresh_token(claims.as_ref()).await?;
+        if let Some(session_id) = &options.session_id {
+            self.sessions
+                .record(
+                    user.id.as_str(),
+                    session_id,
+                    self.session_expiration(options.refresh_expiration),
+                )
+                .await?;
+        }
+        Ok(tokens)
+    }
+
+    /// Refreshes an access token, checking that the access token it replaces belongs to the
+    /// same user.
+    ///
+    /// Clients rotating tokens present the refresh token along with their previous access
+    /// token. Requiring both to carry the same subject rejects requests mixing tokens of
+    /// different users, e.g. a stolen refresh token replayed next to the attacker's own
+    /// access token. The old access token may have expired, but must be authentic.
+    ///
+    /// # Arguments
+    /// * `refresh_token` - The refresh token to use for generating a new access token.
+    /// * `old_access_token` - The access token issued with the refresh token or a previous rotation.
+    ///
+    /// # Returns
+    /// Returns a new [`TokenPair`] if both tokens are valid and belong to the same user.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::SubjectMismatch`] if the tokens belong to different users,
+    /// [`AuthError::NotImplemented`] if the token service cannot validate both tokens, or the
+    /// errors of [`AuthService::refresh_access_token`].
+    pub async fn refresh_access_token_checked(
+        &self,
+        refresh_token: &str,
+        old_access_token: &str,
+    ) -> Result<crate::core::token::TokenPair, AuthError> {
+        let refresh_claims = self
+            .token_manager
+            .validate_refresh_token(refresh_token)
+            .await?;
+        let access_claims = self
+            .token_manager
+            .validate_expired_access_token(old_access_token)
+            .await?;
+        if refresh_claims.get_subject() != access_claims.get_subject() {
+            log::warn!("Refresh rejected: refresh and access token subjects differ");
+            return Err(AuthError::SubjectMismatch);
+        }
+        self.refresh_access_token(refresh_token).await
+    }
+
+    /// Introspects a token, in the style of an RFC 7662 introspection endpoint.
+    ///
+    /// Unlike [`AuthService::validate_access_token`], this never fails: invalid or expired
+    /// tokens are reported as inactive.
+    ///
+    /// # Arguments
+    /// * `token` - The token to introspect.
+    ///
+    /// # Returns
+    /// Returns an [`IntrospectionResult`](crate::core::token::IntrospectionResult) describing the token.
+    pub async fn introspect(&self, token: &str) -> crate::core::token::IntrospectionResult {
+        match self.validate_access_token(token).await {
+            Ok(claims) => crate::core::token::IntrospectionResult::from_claims(claims.as_ref()),
+            Err(e) => {
+                log::debug!("Token introspection reported an inactive token: {e}");
+                crate::core::token::IntrospectionResult::inactive()
+            }
+        }
+    }
+
+    /// Validates a refresh token without consuming it or issuing new tokens.
+    ///
+    /// This is useful to check whether a session is still active. Unlike
+    /// [`AuthService::refresh_access_token`], no new token pair is generated.
+    ///
+    /// # Arguments
+    /// * `refresh_token` - The refresh token to validate.
+    ///
+    /// # Returns
+    /// Returns the refresh token claims (subject and expiration) if valid, or an [`AuthError`]
+    /// if the token is invalid, expired, or not a refresh token.
+    pub async fn validate_refresh_token(
+        &self,
+        refresh_token: &str,
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        self.token_manager
+            .validate_refresh_token(refresh_token)
+            .await
+    }
+
+    /// Validates a token and extracts the user ID (subject) from it.
+    ///
+    /// # Arguments
+    /// * `token` - The token to validate and extract the user ID from.
+    ///
+    /// # Returns
+    /// Returns the [`UserId`](crate::core::user::UserId) if the token is valid, or an [`AuthError`] if validation fails.
+    pub async fn get_user_id_from_token(
+        &self,
+        token: &str,
+    ) -> Result<crate::core::user::UserId, AuthError> {
+        let claims = self.validate_access_token_of_any_tenant(token).await?;
+        Ok(claims.get_subject().into())
+    }
+
+    /// Checks if a token is expired by attempting to validate it.
+    ///
+    /// # Arguments
+    /// * `token` - The token to check for expiration.
+    ///
+    /// # Returns
+    /// Returns `true` if the token is expired or invalid, `false` otherwise.
+    pub async fn is_token_expired(&self, token: &str) -> bool {
+        self.validate_access_token_of_any_tenant(token)
+            .await
+            .is_err()
+    }
+
+    /// Validates a token and retrieves the associated user from the repository.
+    ///
+    /// # Arguments
+    /// * `token` - The token to validate and extract the user from.
+    ///
+    /// # Returns
+    /// Returns the [`User`] if the token is valid and the user exists, or an [`AuthError`] otherwise.
+    pub async fn get_user_from_token(&self, token: &str) -> Result<User, AuthError> {
+        Ok(self.authenticate_request(token).await?.0)
+    }
+
+    // OAuth2 Methods
+
+    /// Generates an OAuth2 authorization URL for the specified provider.
+    ///
+    /// # Arguments
+    /// * `provider` - The OAuth2 provider to generate the URL for.
+    /// * `state` - A state parameter for CSRF protection.
+    /// * `scopes` - Optional additional scopes beyond the default ones.
+    ///
+    /// # Returns
+    /// Returns the authorization URL as a `String` if successful, or an [`AuthError`] if generation fails.
+    pub async fn generate_oauth2_auth_url(
+        &self,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        state: &str,
+        scopes: Option<Vec<String>>,
+    ) -> Result<String, AuthError> {
+        self.oauth2_manager
+            .generate_auth_url(provider, state, scopes)
+            .await
+    }
+
+    /// Exchanges an OAuth2 authorization code for an access token.
+    ///
+    /// # Arguments
+    /// * `provider` - The OAuth2 provider.
+    /// * `code` - The authorization code received from the provider.
+    /// * `state` - The state parameter for verification.
+    ///
+    /// # Returns
+    /// Returns an [`OAuth2Token`] if successful, or an [`AuthError`] if the token exchange fails.
+    pub async fn exchange_oauth2_code_for_token(
+        &self,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        code: &str,
+        state: &str,
+    ) -> Result<crate::core::oauth::store::OAuth2Token, AuthError> {
+        self.oauth2_manager
+            .exchange_code_for_token(provider, code, state)
+            .await
+    }
+
+    /// Fetches user information from an OAuth2 provider using an access token.
+    ///
+    /// The scopes granted on the token are recorded in
+    /// [`OAuth2UserInfo::granted_scopes`](crate::core::oauth::store::OAuth2UserInfo::granted_scopes),
+    /// so that they are stored with the linked account.
+    ///
+    /// # Arguments
+    /// * `token` - The OAuth2 token to use for fetching user info.
+    ///
+    /// # Returns
+    /// Returns [`OAuth2UserInfo`] if successful, or an [`AuthError`] if fetching user info fails.
+    pub async fn fetch_oauth2_user_info(
+        &self,
+        token: &crate::core::oauth::store::OAuth2Token,
+    ) -> Result<crate::core::oauth::store::OAuth2UserInfo, AuthError> {
+        let mut user_info = self.oauth2_manager.fetch_user_info(token).await?;
+        if user_info.granted_scopes.is_empty() {
+            user_info.granted_scopes = token.granted_scopes();
+        }
+        Ok(user_info)
+    }
+
+    /// Refreshes an OAuth2 access token using a refresh token.
+    ///
+    /// # Arguments
+    /// * `token` - The OAuth2 token containing the refresh token.
+    ///
+    /// # Returns
+    /// Returns a new [`OAuth2Token`] if successful, or an [`AuthError`] if refreshing the token fails.
+    pub async fn refresh_oauth2_token(
+        &self,
+        token: &crate::core::oauth::store::OAuth2Token,
+    ) -> Result<crate::core::oauth::store::OAuth2Token, AuthError> {
+        self.oauth2_manager.refresh_token(token).await
+    }
+
+    /// Returns an OAuth2 token usable for a provider request, refreshing it first if it is
+    /// about to expire.
+    ///
+    /// A token is refreshed once it expires within
+    /// [`AuthServiceVariables::oauth_token_expiry_margin`](crate::core::vars::AuthServiceVariables::oauth_token_expiry_margin)
+    /// seconds, so requests never carry a token that expired in transit or on a provider
+    /// whose clock runs ahead. Callers should store the returned token when it differs.
+    ///
+    /// # Arguments
+    /// * `token` - The OAuth2 token to use.
+    ///
+    /// # Returns
+    /// The token itself if it is still valid beyond the margin, or a refreshed token.
+    ///
+    /// # Errors
+    /// Returns the errors of [`AuthService::refresh_oauth2_token`] if a refresh is needed and
+    /// fails (e.g., the token has no refresh token).
+    pub async fn fresh_oauth2_token(
+        &self,
+        token: &crate::core::oauth::store::OAuth2Token,
+    ) -> Result<crate::core::oauth::store::OAuth2Token, AuthError> {
+        let margin = self
+            .vars
+            .oauth_token_expiry_margin
+            .unwrap_or(crate::core::oauth::store::DEFAULT_OAUTH_EXPIRY_MARGIN);
+        if token.is_expired_with_margin(margin) {
+            self.refresh_oauth2_token(token).await
+        } else {
+            Ok(token.clone())
+        }
+    }
+
+    /// Lists every supported OAuth2 provider with its display name and default scopes.
+    ///
+    /// Only providers configured in the OAuth2 manager are marked as available, so a login
+    /// UI can render exactly the buttons that will work.
+    ///
+    /// # Returns
+    /// Returns one [`ProviderInfo`](crate::core::oauth::store::ProviderInfo) per supported provider.
+    pub fn available_oauth_providers(&self) -> Vec<crate::core::oauth::store::ProviderInfo> {
+        crate::core::oauth::store::OAuth2Provider::all()
+            .iter()
+            .map(|&provider| crate::core::oauth::store::ProviderInfo {
+                provider,
+                display_name: provider.display_name().to_string(),
+                default_scopes: match self.oauth2_manager.get_config(provider) {
+                    Some(config) => config.default_scopes_for(provider),
+                    None => provider
+                        .default_scopes()
+                        .into_iter()
+                        .map(str::to_string)
+                        .collect(),
+                },
+                available: self.oauth2_manager.is_provider_configured(provider),
+            })
+            .collect()
+    }
+
+    /// Links an OAuth account to an existing user.
+    ///
+    /// A provider identity can only be linked to one user: linking it again to the same user
+    /// refreshes the stored account information, while linking it to another user fails.
+    ///
+    /// # Arguments
+    /// * `user_id` - The ID of the existing user.
+    /// * `provider` - The OAuth2 provider.
+    /// * `code` - The authorization code from the OAuth provider.
+    /// * `state` - The state parameter for CSRF protection.
+    ///
+    /// # Returns
+    /// Returns the updated [`User`] on success.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::UserNotFound`] if the user doesn't exist,
+    /// [`AuthError::OAuthAccountAlreadyLinked`] if the provider identity is linked to another
+    /// user, [`AuthError::TooManyLinkedAccounts`] if the user already links the maximum number
+    /// of providers, or other variants for OAuth2 failures.
+    pub async fn link_oauth_account(
+        &self,
+        user_id: &str,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        code: &str,
+        state: &str,
+    ) -> Result<User, AuthError> {
+        // Get the existing user
+        let mut user = self
+            .persistent_users_manager
+            .find_user_by_id(&user_id.into())
+            .await?
+            .ok_or(AuthError::UserNotFound)?;
+
+        // Exchange code for token
+        let oauth_token = self
+            .exchange_oauth2_code_for_token(provider, code, state)
+            .await?;
+
+        // Fetch user info from OAuth provider
+        let oauth_user_info = self.fetch_oauth2_user_info(&oauth_token).await?;
+
+        if let Some(owner) = self
+            .persistent_users_manager
+            .find_user_by_oauth_id(provider, &oauth_user_info.provider_user_id)
+            .await?
+            && owner.id != user.id
+        {
+            return Err(AuthError::OAuthAccountAlreadyLinked {
+                other_user_id: owner.id,
+            });
+        }
+
+        // Link the OAuth account to the user
+        self.ensure_can_link(&user, provider)?;
+        user = user.link_oauth_account(oauth_user_info);
+
+        // Update the user in storage
+        self.persistent_users_manager.update_user(&user).await?;
+
+        Ok(user)
+    }
+
+    /// Unlinks an OAuth account from a user.
+    ///
+    /// Unlinking the only provider of a user without a password would lock them out, so it is
+    /// refused unless `force` is set (e.g., by an administrator closing the account).
+    ///
+    /// # Arguments
+    /// * `user_id` - The ID of the user.
+    /// * `provider` - The OAuth2 provider to unlink.
+    /// * `force` - Whether to unlink the provider even if it is the user's last login method.
+    ///
+    /// # Returns
+    /// Returns the updated [`User`] on success.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::UserNotFound`] if the user doesn't exist,
+    /// [`AuthError::CannotRemoveLastCredential`] if the provider is the user's last login
+    /// method and `force` is not set, or other variants for update failures.
+    pub async fn unlink_oauth_account(
+        &self,
+        user_id: &str,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        force: bool,
+    ) -> Result<User, AuthError> {
+        // Get the existing user
+        let mut user = self
+            .persistent_users_manager
+            .find_user_by_id(&user_id.into())
+            .await?
+            .ok_or(AuthError::UserNotFound)?;
+
+        // Keep at least one way to log in
+        if !force
+            && !user
+                .credentials
+                .as_ref()
+                .is_some_and(crate::core::credentials::Credentials::has_password)
+            && user.has_oauth_account(provider)
+            && user.oauth_accounts.len() == 1
+        {
+            return Err(AuthError::CannotRemoveLastCredential);
+        }
+
+        // Unlink the OAuth account
+        user.unlink_oauth_account(provider);
+
+        // Update the user in storage
+        self.persistent_users_manager.update_user(&user).await?;
+
+        Ok(user)
+    }
+
+    /// Hashes and stores a password for an existing user.
+    ///
+    /// This lets users created through OAuth2 add password credentials later. The password
+    /// is checked against the configured password policy before being hashed. When the user
+    /// has no credentials yet, the login identifier is the email of the first linked OAuth2
+    /// account that has one. Without any email, it is a unique username proposed by the
+    /// service's [`UsernameGenerator`](crate::core::credentials::UsernameGenerator) (e.g.,
+    /// `github_482913`), or the user ID if no OAuth2 account is linked. An identifier set with
+    /// [`AuthService::complete_profile`] is kept.
+    ///
+    /// When an existing password is replaced (e.g. on a password reset), every session of the
+    /// user is revoked, so tokens obtained with the old password stop working.
+    ///
+    /// # Arguments
+    /// * `user_id` - The ID of the existing user.
+    /// * `new_password` - The plaintext password to set.
+    /// * `overwrite` - Whether to replace existing password credentials.
+    ///
+    /// # Returns
+    /// Returns `Ok(())` once the new credentials are persisted.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::UserNotFound`] if the user doesn't exist, [`AuthError::InvalidInput`]
+    /// if the password violates the policy or the user already has a password and `overwrite`
+    /// is `false`, [`AuthError::UserAlreadyExists`] if the derived identifier belongs to another
+    /// user of the same tenant, or other variants for hashing and update failures.
+    pub async fn set_password(
         &self,
         user_id: &str,
-        provider: crate::core::oauth::store::OAuth2Provider,
-        code: &str,
-        state: &str,
+        new_password: &str,
+        overwrite: bool,
+    ) -> Result<(), AuthError> {
+        self.store_password(user_id, new_password, overwrite, None)
+            .await
+    }
+
+    /// Sets the login identifier of a user created without one (see
+    /// [`User::needs_identifier`]), e.g. a Discord user who signed up without sharing an email.
+    ///
+    /// The identifier goes through the identifier resolver and must be free within the user's
+    /// tenant. No password is set: the user keeps logging in through OAuth2 until
+    /// [`AuthService::set_password`] adds one, which keeps this identifier.
+    ///
+    /// # Arguments
+    /// * `user_id` - The ID of the existing user.
+    /// * `identifier` - The login identifier to set (e.g., a username or email address).
+    ///
+    /// # Returns
+    /// The updated user.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::UserNotFound`] if the user doesn't exist, [`AuthError::InvalidInput`]
+    /// if the user already has an identifier, the errors of the identifier resolver,
+    /// [`AuthError::DisposableEmailNotAllowed`] if the identifier is a disposable email address,
+    /// [`AuthError::UserAlreadyExists`] if another user of the same tenant has the identifier,
+    /// or other variants for update failures.
+    pub async fn complete_profile(
+        &self,
+        user_id: &str,
+        identifier: &str,
     ) -> Result<User, AuthError> {
-        // Get the existing user
         let mut user = self
             .persistent_users_manager
-            .get_user_by_id(user_id)
-            .await
+            .find_user_by_id(&user_id.into())
+            .await?
             .ok_or(AuthError::UserNotFound)?;
+        if !user.needs_identifier() {
+            return Err(AuthError::InvalidInput(
+                "User already has an identifier".to_string(),
+            ));
+        }
 
-        // Exchange code for token
-        let oauth_token = self
-            .exchange_oauth2_code_for_token(provider, code, state)
+        let identifier = self.identifier_resolver.resolve(identifier)?;
+        self.ensure_not_disposable(&identifier)?;
+        let tenant_id = user.tenant_id.clone();
+        let reservation = self
+            .persistent_users_manager
+            .reserve_identifier(&identifier, tenant_id.as_deref())
             .await?;
+        if self
+            .persistent_users_manager
+            .get_credentials_by_identifier_in_tenant(&identifier, tenant_id.as_deref())
+            .await?
+            .is_some()
+        {
+            return Err(AuthError::UserAlreadyExists);
+        }
 
-        // Fetch user info from OAuth provider
-        let oauth_user_info = self.fetch_oauth2_user_info(&oauth_token).await?;
-
-        // Link the OAuth account to the user
-        user = user.link_oauth_account(oauth_user_info);
-
-        // Update the user in storage
+        user.credentials = Some(crate::core::credentials::Credentials::without_password(
+            user.id.to_string(),
+            identifier,
+        ));
+        user.updated_at = chrono::Utc::now().naive_utc();
         self.persistent_users_manager.update_user(&user).await?;
-
+        reservation.commit();
         Ok(user)
     }
 
-    /// Unlinks an OAuth account from a user.
+    /// Changes the password of the user authenticated by `access_token`.
+    ///
+    /// The current password must be given again. Once the new password is stored, the other
+    /// sessions of the user are revoked. The session of `access_token` is revoked as well,
+    /// unless [`AuthServiceVariables::keep_session_on_password_change`](crate::core::vars::AuthServiceVariables::keep_session_on_password_change)
+    /// is set. If the session store does not implement
+    /// [`SessionStore::revoke_all_for_user_except`](crate::core::token::session::SessionStore::revoke_all_for_user_except),
+    /// every session is revoked instead.
     ///
     /// # Arguments
-    /// * `user_id` - The ID of the user.
-    /// * `provider` - The OAuth2 provider to unlink.
+    /// * `access_token` - An access token of the user.
+    /// * `current_password` - The user's current password.
+    /// * `new_password` - The plaintext password to set.
     ///
     /// # Returns
-    /// Returns the updated [`User`] on success.
+    /// Returns `Ok(())` once the new credentials are persisted and the sessions revoked.
     ///
     /// # Errors
-    /// Returns [`AuthError::UserNotFound`] if the user doesn't exist, or other variants for update failures.
-    pub async fn unlink_oauth_account(
+    /// Returns the errors of [`AuthService::validate_access_token`],
+    /// [`AuthError::InvalidCredentials`] if the user has no password or `current_password` is
+    /// wrong, [`AuthError::InvalidInput`] if the new password violates the policy, or other
+    /// variants for hashing, update and session store failures.
+    pub async fn change_password(
+        &self,
+        access_token: &str,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<(), AuthError> {
+        let claims = self
+            .validate_access_token_of_any_tenant(access_token)
+            .await?;
+        let user = self
+            .persistent_users_manager
+            .find_user_by_id(&claims.get_subject().into())
+            .await?
+            .ok_or(AuthError::UserNotFound)?;
+        let credentials = user
+            .credentials
+            .as_ref()
+            .filter(|credentials| credentials.has_password())
+            .ok_or(AuthError::InvalidCredentials)?;
+
+        let is_valid = self
+            .password_manager
+            .verify_password(current_password, &credentials.password_hash)
+            .await
+            .map_err(|e| match e {
+                AuthError::HashingTimeout => e,
+                e => AuthError::PasswordVerificationError(format!(
+                    "Password verification failed: {e}"
+                )),
+            })?;
+        if !is_valid {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        let keep_session = claims
+            .get_session_id()
+            .filter(|_| self.vars.keep_session_on_password_change);
+        self.store_password(user.id.as_str(), new_password, true, keep_session)
+            .await
+    }
+
+    /// Hashes and stores a password for an existing user, revoking their sessions (except
+    /// `keep_session`) if existing credentials are replaced.
+    async fn store_password(
         &self,
         user_id: &str,
-        provider: crate::core::oauth::store::OAuth2Provider,
-    ) -> Result<User, AuthError> {
-        // Get the existing user
+        new_password: &str,
+        overwrite: bool,
+        keep_session: Option<&str>,
+    ) -> Result<(), AuthError> {
         let mut user = self
             .persistent_users_manager
-            .get_user_by_id(user_id)
-            .await
+            .find_user_by_id(&user_id.into())
+            .await?
             .ok_or(AuthError::UserNotFound)?;
 
-        // Unlink the OAuth account
-        user.unlink_oauth_account(provider);
+        let has_password = user
+            .credentials
+            .as_ref()
+            .is_some_and(crate::core::credentials::Credentials::has_password);
+        if has_password && !overwrite {
+            return Err(AuthError::InvalidInput(
+                "User already has password credentials".to_string(),
+            ));
+        }
 
-        // Update the user in storage
+        self.enforce_password_policy(new_password)?;
+
+        let mut reservation = None;
+        let identifier = match &user.credentials {
+            Some(credentials) => credentials.identifier.clone(),
+            None => {
+                let linked = || {
+                    crate::core::oauth::store::OAuth2Provider::all()
+                        .iter()
+                        .filter_map(|provider| user.oauth_accounts.get(provider))
+                };
+                match linked().find_map(|info| info.email.clone()) {
+                    Some(email) => email,
+                    None => match linked().next() {
+                        Some(info) => {
+                            let (username, guard) = self
+                                .reserve_generated_username(info, user.tenant_id.as_deref())
+                                .await?;
+                            reservation = Some(guard);
+                            username
+                        }
+                        None => user.id.to_string(),
+                    },
+                }
+            }
+        };
+
+        if let Some(existing) = self
+            .persistent_users_manager
+            .find_user_by_identifier_in_tenant(&identifier, user.tenant_id.as_deref())
+            .await?
+            && existing.id != user.id
+        {
+            return Err(AuthError::UserAlreadyExists);
+        }
+
+        let password_hash = self
+            .password_manager
+            .hash_password_for(new_password, user.hash_target.as_ref())
+            .await
+            .map_err(|e| match e {
+                AuthError::HashingTimeout => e,
+                e => AuthError::HashingError(format!("Couldn't hash : {e}")),
+            })?;
+        let credentials = crate::core::credentials::Credentials::new(
+            user.id.to_string(),
+            identifier,
+            password_hash,
+        );
+
+        user.credentials = Some(credentials);
+        let replaced = has_password;
+        user.updated_at = chrono::Utc::now().naive_utc();
         self.persistent_users_manager.update_user(&user).await?;
+        if let Some(reservation) = reservation {
+            reservation.commit();
+        }
+        self.audit_log
+            .record(crate::core::audit::AuditEvent::PasswordChanged {
+                user_id: self.log_id(&user.id),
+            });
+        if let Err(e) = self.notifier.notify_password_changed(&user).await {
+            log::warn!(
+                "Failed to notify user {} of a password change: {e}",
+                self.log_id(&user.id)
+            );
+        }
 
-        Ok(user)
+        if replaced {
+            match keep_session {
+                Some(session_id) => {
+                    match self
+                        .sessions
+                        .revoke_all_for_user_except(user.id.as_str(), session_id)
+                        .await
+                    {
+                        Err(AuthError::NotImplemented(_)) => {
+                            log::warn!(
+                                "Session store cannot keep a session; revoking every session of user {}",
+                                self.log_id(&user.id)
+                            );
+                            self.sessions.revoke_all_for_user(user.id.as_str()).await?;
+                        }
+                        result => {
+                            result?;
+                        }
+                    }
+                }
+                None => {
+                    self.sessions.revoke_all_for_user(user.id.as_str()).await?;
+                }
+            }
+            self.invalidate_cached_validations(user.id.as_str());
+        }
+        Ok(())
+    }
+
+    /// Generates an identifier for the OAuth2 user `info` that no other user of the tenant
+    /// has, and claims it until the returned guard ends.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::UserAlreadyExists`] if every candidate is taken, or the errors of
+    /// the repository.
+    async fn reserve_generated_username(
+        &self,
+        info: &crate::core::oauth::store::OAuth2UserInfo,
+        tenant_id: Option<&str>,
+    ) -> Result<(String, crate::core::user::persistence::ReservationGuard), AuthError> {
+        for attempt in 0..crate::core::credentials::username::MAX_USERNAME_ATTEMPTS {
+            let candidate = self
+                .username_generator
+                .generate(info.provider, info, attempt);
+            let guard = match self
+                .persistent_users_manager
+                .reserve_identifier(&candidate, tenant_id)
+                .await
+            {
+                Ok(guard) => guard,
+                Err(AuthError::UserAlreadyExists) => continue,
+                Err(e) => return Err(e),
+            };
+            if self
+                .persistent_users_manager
+                .find_user_by_identifier_in_tenant(&candidate, tenant_id)
+                .await?
+                .is_none()
+            {
+                return Ok((candidate, guard));
+            }
+        }
+        Err(AuthError::UserAlreadyExists)
+    }
+
+    /// Lists the users whose stored password hash is not up to date, without changing anything.
+    ///
+    /// A hash needs a rehash when the password manager identifies it but reports outdated
+    /// algorithm or parameters against the [`hash_target`](User::hash_target) of its user, or
+    /// the service-wide parameters if the user has none
+    /// ([`SecurePasswordManager::needs_rehash_for`]), as logins do; such hashes are still
+    /// upgraded lazily on the user's next login. Hashes the manager does not identify at all
+    /// are reported separately. Useful to estimate the scope of a migration.
+    ///
+    /// Users are read in batches with [`UserRepository::stream_users`].
+    ///
+    /// [`SecurePasswordManager::needs_rehash_for`]: crate::core::password::SecurePasswordManager::needs_rehash_for
+    /// [`UserRepository::stream_users`]: crate::core::user::persistence::UserRepository::stream_users
+    ///
+    /// # Returns
+    /// Returns a [`MigrationReport`](crate::core::password::MigrationReport) over every user
+    /// with password credentials.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::NotImplemented`] if the user repository cannot stream users, or
+    /// other variants if it could not be read.
+    pub async fn scan_for_rehash(
+        &self,
+    ) -> Result<crate::core::password::MigrationReport, AuthError> {
+        use futures_util::StreamExt;
+
+        let mut report = crate::core::password::MigrationReport::default();
+        let mut users = self.persistent_users_manager.stream_users();
+        while let Some(user) = users.next().await {
+            let user = user?;
+            let Some(password_hash) = user
+                .credentials
+                .as_ref()
+                .map(|credentials| credentials.password_hash.as_str())
+                .filter(|hash| !hash.is_empty())
+            else {
+                continue;
+            };
+            report.scanned += 1;
+            if !self.password_manager.identify(password_hash) {
+                report.unrecognized.push(user.id);
+            } else if self
+                .password_manager
+                .needs_rehash_for(password_hash, user.hash_target.as_ref())
+            {
+                report.needs_rehash.push(user.id);
+            }
+        }
+        Ok(report)
     }
 
     /// Retrieves all OAuth providers linked to a user.
@@ -630,8 +4194,8 @@ impl AuthService {
     ) -> Result<Vec<crate::core::oauth::store::OAuth2Provider>, AuthError> {
         let user = self
             .persistent_users_manager
-            .get_user_by_id(user_id)
-            .await
+            .find_user_by_id(&user_id.into())
+            .await?
             .ok_or(AuthError::UserNotFound)?;
 
         Ok(user.oauth_accounts.keys().copied().collect())
@@ -648,11 +4212,335 @@ impl AuthService {
         &self,
         provider: crate::core::oauth::store::OAuth2Provider,
     ) -> Result<String, AuthError> {
-        // Since oauth2_manager is a Box<dyn OAuth2Service>, we need to downcast it to OAuth2Manager
-        // to access the get_redirect_frontend_uri method. For now, we'll need to modify the trait.
-        // Let's assume we add this method to the OAuth2Service trait.
         self.oauth2_manager
             .get_redirect_frontend_uri(provider)
             .await
     }
+
+    /// Returns the configuration of an OAuth2 provider, as held by the OAuth2 service.
+    ///
+    /// # Arguments
+    /// * `provider` - The OAuth2 provider whose configuration to return.
+    ///
+    /// # Returns
+    /// The configuration, or `None` if the provider is not configured or the OAuth2 service
+    /// does not expose configurations.
+    pub fn oauth2_config(
+        &self,
+        provider: crate::core::oauth::store::OAuth2Provider,
+    ) -> Option<&crate::core::oauth::store::OAuth2Config> {
+        self.oauth2_manager.get_config(provider)
+    }
+
+    /// Returns every OAuth2 provider configured in the OAuth2 service.
+    pub fn configured_oauth2_providers(&self) -> Vec<crate::core::oauth::store::OAuth2Provider> {
+        self.oauth2_manager.list_configured_providers()
+    }
+}
+
+/// A view of an [`AuthService`] scoped to a single tenant.
+///
+/// Created with [`AuthService::for_tenant`]. Users created through this view belong to the
+/// tenant, lookups only match users of the tenant, and issued tokens carry the tenant claim.
+pub struct TenantAuthService<'a> {
+    /// The underlying authentication service.
+    service: &'a AuthService,
+    /// The tenant operations are scoped to.
+    tenant_id: String,
+}
+
+impl TenantAuthService<'_> {
+    /// Returns the tenant this view is scoped to.
+    pub fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+
+    /// Authenticates a user of this tenant. See [`AuthService::login`].
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidCredentials`] if no user of this tenant matches the credentials,
+    /// or other variants for OAuth2 failures.
+    pub async fn login(
+        &self,
+        method: LoginMethod,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.service
+            .login_in_tenant(method, Some(&self.tenant_id), false)
+            .await
+    }
+
+    /// Authenticates a user of this tenant, keeping them signed in ("remember me"). See
+    /// [`AuthService::login_remember_me`].
+    ///
+    /// # Errors
+    /// Returns the errors of [`TenantAuthService::login`].
+    pub async fn login_remember_me(
+        &self,
+        method: LoginMethod,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.service
+            .login_in_tenant(method, Some(&self.tenant_id), true)
+            .await
+    }
+
+    /// Authenticates a user of this tenant after verifying the challenge token of the request.
+    /// See [`AuthService::login_with_challenge`].
+    ///
+    /// # Errors
+    /// Returns the errors of [`AuthService::login_with_challenge`].
+    pub async fn login_with_challenge(
+        &self,
+        method: LoginMethod,
+        challenge_token: Option<&str>,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.service
+            .login_with_challenge_in_tenant(method, challenge_token, Some(&self.tenant_id), false)
+            .await
+    }
+
+    /// Authenticates a user of this tenant, issuing an access token only. See
+    /// [`AuthService::login_access_token_only`].
+    ///
+    /// # Errors
+    /// Returns the errors of [`TenantAuthService::login`].
+    pub async fn login_access_token_only(
+        &self,
+        method: LoginMethod,
+    ) -> Result<(User, String), AuthError> {
+        self.service
+            .login_access_token_only_in_tenant(method, Some(&self.tenant_id))
+            .await
+    }
+
+    /// Generates an email one-time password for a user of this tenant. See
+    /// [`AuthService::request_email_otp`].
+    ///
+    /// # Errors
+    /// Returns the errors of [`AuthService::request_email_otp`].
+    pub async fn request_email_otp(&self, identifier: &str) -> Result<Option<String>, AuthError> {
+        self.service
+            .request_email_otp_in_tenant(identifier, Some(&self.tenant_id))
+            .await
+    }
+
+    /// Generates a password reset token for a user of this tenant. See
+    /// [`AuthService::request_password_reset`].
+    ///
+    /// # Errors
+    /// Returns the errors of [`AuthService::request_password_reset`].
+    pub async fn request_password_reset(
+        &self,
+        identifier: &str,
+    ) -> Result<Option<String>, AuthError> {
+        self.service
+            .request_password_reset_in_tenant(identifier, Some(&self.tenant_id))
+            .await
+    }
+
+    /// Sets a new password for a user of this tenant with a reset token. See
+    /// [`AuthService::reset_password`].
+    ///
+    /// # Errors
+    /// Returns the errors of [`AuthService::reset_password`].
+    pub async fn reset_password(
+        &self,
+        identifier: &str,
+        token: &str,
+        new_password: &str,
+    ) -> Result<(), AuthError> {
+        self.service
+            .reset_password_in_tenant(identifier, token, new_password, Some(&self.tenant_id))
+            .await
+    }
+
+    /// Logs a user of this tenant in with an email one-time password. See
+    /// [`AuthService::login_with_email_otp`].
+    ///
+    /// # Errors
+    /// Returns the errors of [`AuthService::login_with_email_otp`].
+    pub async fn login_with_email_otp(
+        &self,
+        identifier: &str,
+        code: &str,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.service
+            .login_with_email_otp_in_tenant(identifier, code, Some(&self.tenant_id))
+            .await
+    }
+
+    /// Logs in or registers the user of an OAuth2 identity in this tenant. See
+    /// [`AuthService::login_from_oauth_userinfo`].
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidCredentials`] if the provider account is linked to a user of
+    /// another tenant, or the other errors of [`AuthService::login_from_oauth_userinfo`].
+    pub async fn login_from_oauth_userinfo(
+        &self,
+        info: crate::core::oauth::store::OAuth2UserInfo,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.service
+            .login_from_oauth_userinfo_in_tenant(info, Some(&self.tenant_id))
+            .await
+    }
+
+    /// Registers a new user in this tenant. See [`AuthService::signup`].
+    ///
+    /// # Errors
+    /// Returns [`AuthError::SignupError`] if the identifier is already used in this tenant,
+    /// or other variants for OAuth2 failures.
+    pub async fn signup(
+        &self,
+        method: SignupMethod,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.service
+            .signup_in_tenant(method, Some(&self.tenant_id))
+            .await
+    }
+
+    /// Registers a new user in this tenant after verifying the challenge token of the request.
+    /// See [`AuthService::signup_with_challenge`].
+    ///
+    /// # Errors
+    /// Returns the errors of [`AuthService::signup_with_challenge`].
+    pub async fn signup_with_challenge(
+        &self,
+        method: SignupMethod,
+        challenge_token: Option<&str>,
+    ) -> Result<(User, crate::core::token::TokenPair), AuthError> {
+        self.service
+            .signup_with_challenge_in_tenant(method, challenge_token, Some(&self.tenant_id))
+            .await
+    }
+
+    /// Checks whether an identifier is free in this tenant. See
+    /// [`AuthService::is_identifier_available`].
+    ///
+    /// # Errors
+    /// Returns the errors of the identifier resolver, [`AuthError::RateLimited`] if the checks
+    /// reached the limit, or an error if the repository could not be queried.
+    pub async fn is_identifier_available(
+        &self,
+        identifier: &str,
+        client: &str,
+    ) -> Result<bool, AuthError> {
+        self.service
+            .is_identifier_available_in_tenant(identifier, client, Some(&self.tenant_id))
+            .await
+    }
+
+    /// Validates an access token issued for this tenant.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidToken`] if the token was issued for another tenant (or for no
+    /// tenant), or other token validation errors.
+    pub async fn validate_access_token(
+        &self,
+        token: &str,
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        self.service
+            .validate_access_token_in_tenant(token, Some(&self.tenant_id))
+            .await
+    }
+
+    /// Validates an access token issued for this tenant and consumes it. See
+    /// [`AuthService::consume_one_time_access`].
+    ///
+    /// # Errors
+    /// Returns the errors of [`TenantAuthService::validate_access_token`], or those of
+    /// [`AuthService::consume_one_time_access`] when consuming the token.
+    pub async fn consume_one_time_access(
+        &self,
+        token: &str,
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        self.service
+            .consume_one_time_access_in_tenant(token, Some(&self.tenant_id))
+            .await
+    }
+
+    /// Validates an access token issued for this tenant and returns the roles of its user. See
+    /// [`AuthService::user_roles`].
+    ///
+    /// # Errors
+    /// Returns the errors of [`TenantAuthService::validate_access_token`], or those of
+    /// [`AuthService::user_roles`] when reading from the repository.
+    pub async fn user_roles(
+        &self,
+        token: &str,
+        source: Option<crate::core::user::RoleSource>,
+    ) -> Result<Vec<String>, AuthError> {
+        self.service
+            .user_roles_in_tenant(token, source, Some(&self.tenant_id))
+            .await
+    }
+
+    /// Validates an access token issued for this tenant and checks whether its user was
+    /// granted a role. See [`AuthService::user_has_role`].
+    ///
+    /// # Errors
+    /// Returns the errors of [`TenantAuthService::user_roles`].
+    pub async fn user_has_role(
+        &self,
+        token: &str,
+        role: &str,
+        source: Option<crate::core::user::RoleSource>,
+    ) -> Result<bool, AuthError> {
+        Ok(self
+            .user_roles(token, source)
+            .await?
+            .iter()
+            .any(|granted| granted == role))
+    }
+
+    /// Validates an access token issued for this tenant and checks that the user
+    /// authenticated recently. See [`AuthService::require_recent_auth`].
+    ///
+    /// # Errors
+    /// Returns [`AuthError::StepUpRequired`] if the authentication is too old, or the errors
+    /// of [`TenantAuthService::validate_access_token`].
+    pub async fn require_recent_auth(
+        &self,
+        token: &str,
+        max_age: std::time::Duration,
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        self.service
+            .require_recent_auth_in_tenant(token, max_age, Some(&self.tenant_id))
+            .await
+    }
+
+    /// Validates an access token issued for this tenant and checks that the user
+    /// authenticated with every method of `required`. See [`AuthService::require_amr`].
+    ///
+    /// # Errors
+    /// Returns [`AuthError::StepUpRequired`] naming the missing methods, or the errors of
+    /// [`TenantAuthService::validate_access_token`].
+    pub async fn require_amr(
+        &self,
+        token: &str,
+        required: &[&str],
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        self.service
+            .require_amr_in_tenant(token, required, Some(&self.tenant_id))
+            .await
+    }
+
+    /// Validates an access token issued for this tenant and returns its user. See
+    /// [`AuthService::authenticate_request`].
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidToken`] if the token was issued for another tenant (or for no
+    /// tenant), or the errors of [`AuthService::authenticate_request`].
+    pub async fn authenticate_request(
+        &self,
+        token: &str,
+    ) -> Result<
+        (
+            User,
+            Box<dyn crate::core::token::claims::Claims + Send + Sync>,
+        ),
+        AuthError,
+    > {
+        self.service
+            .authenticate_request_in_tenant(token, Some(&self.tenant_id))
+            .await
+    }
 }
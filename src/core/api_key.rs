@@ -0,0 +1,184 @@
+//! API key storage.
+//!
+//! API keys let scripts and services authenticate as a user without a login flow. A key is
+//! shown once, when it is created, and has the form `ck_<key_id>.<secret>`: the key ID locates
+//! the record, and only a keyed HMAC-SHA256 tag of the key is stored, so a leaked store does not
+//! reveal usable keys. Each record also tracks when its key was last used.
+//!
+//! This module provides the [`ApiKeyStore`] trait and an in-memory default implementation;
+//! `PgApiKeyStore` (with the `postgres` feature) persists keys in PostgreSQL.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::NaiveDateTime;
+
+use crate::error::AuthError;
+
+/// Prefix of every API key, making keys recognizable (e.g., by secret scanners).
+pub const API_KEY_PREFIX: &str = "ck_";
+
+/// Number of random bytes in the secret part of an API key.
+pub const API_KEY_SECRET_BYTES: usize = 32;
+
+/// A stored API key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKeyRecord {
+    /// The public identifier of the key, embedded in the key itself.
+    pub id: String,
+    /// The user the key authenticates as.
+    pub user_id: String,
+    /// A label chosen by the user to recognize the key (e.g., `ci-deploy`).
+    pub label: String,
+    /// The HMAC-SHA256 tag of the key, base64-encoded.
+    pub secret_hash: String,
+    /// When the key was created.
+    pub created_at: NaiveDateTime,
+    /// When the key last authenticated a request, if ever.
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+/// The description of an API key returned to its owner, without any secret.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKeyInfo {
+    /// The public identifier of the key, used to revoke it.
+    pub id: String,
+    /// The label of the key.
+    pub label: String,
+    /// When the key was created.
+    pub created_at: NaiveDateTime,
+    /// When the key last authenticated a request, if ever.
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+impl From<&ApiKeyRecord> for ApiKeyInfo {
+    fn from(record: &ApiKeyRecord) -> Self {
+        Self {
+            id: record.id.clone(),
+            label: record.label.clone(),
+            created_at: record.created_at,
+            last_used_at: record.last_used_at,
+        }
+    }
+}
+
+/// Formats the API key shown to the user from its ID and secret.
+pub(crate) fn format_api_key(key_id: &str, secret: &str) -> String {
+    format!("{API_KEY_PREFIX}{key_id}.{secret}")
+}
+
+/// Splits an API key into its ID and secret, or returns `None` if it is malformed.
+pub(crate) fn parse_api_key(key: &str) -> Option<(&str, &str)> {
+    key.strip_prefix(API_KEY_PREFIX)?
+        .split_once('.')
+        .filter(|(key_id, secret)| !key_id.is_empty() && !secret.is_empty())
+}
+
+/// Stores API key records, keyed by key ID.
+#[async_trait::async_trait]
+pub trait ApiKeyStore: Send + Sync {
+    /// Stores a new API key record.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn insert(&self, record: ApiKeyRecord) -> Result<(), AuthError>;
+
+    /// Returns the record of the key `key_id`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn get(&self, key_id: &str) -> Result<Option<ApiKeyRecord>, AuthError>;
+
+    /// Returns the records of every key of `user_id`, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn list_for_user(&self, user_id: &str) -> Result<Vec<ApiKeyRecord>, AuthError>;
+
+    /// Removes the key `key_id` if it belongs to `user_id`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if the key was removed.
+    /// * `Ok(false)` if there is no such key for this user.
+    /// * `Err(AuthError)` if the store is unavailable.
+    async fn remove(&self, user_id: &str, key_id: &str) -> Result<bool, AuthError>;
+
+    /// Records that the key `key_id` was used at `used_at`. Unknown keys are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn touch(&self, key_id: &str, used_at: NaiveDateTime) -> Result<(), AuthError>;
+}
+
+/// In-memory implementation of [`ApiKeyStore`].
+///
+/// Suitable for single-instance deployments and tests; keys are lost on restart, and
+/// multi-instance deployments need a shared store.
+#[derive(Debug, Default)]
+pub struct InMemoryApiKeyStore {
+    /// Records mapped by key ID.
+    records: Mutex<HashMap<String, ApiKeyRecord>>,
+}
+
+impl InMemoryApiKeyStore {
+    /// Creates a new, empty in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Locks the records.
+    fn records(
+        &self,
+    ) -> Result<std::sync::MutexGuard<'_, HashMap<String, ApiKeyRecord>>, AuthError> {
+        self.records
+            .lock()
+            .map_err(|e| AuthError::ServiceUnavailable(e.to_string()))
+    }
+}
+
+#[async_trait::async_trait]
+impl ApiKeyStore for InMemoryApiKeyStore {
+    async fn insert(&self, record: ApiKeyRecord) -> Result<(), AuthError> {
+        self.records()?.insert(record.id.clone(), record);
+        Ok(())
+    }
+
+    async fn get(&self, key_id: &str) -> Result<Option<ApiKeyRecord>, AuthError> {
+        Ok(self.records()?.get(key_id).cloned())
+    }
+
+    async fn list_for_user(&self, user_id: &str) -> Result<Vec<ApiKeyRecord>, AuthError> {
+        let mut records: Vec<ApiKeyRecord> = self
+            .records()?
+            .values()
+            .filter(|record| record.user_id == user_id)
+            .cloned()
+            .collect();
+        records.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+        Ok(records)
+    }
+
+    async fn remove(&self, user_id: &str, key_id: &str) -> Result<bool, AuthError> {
+        let mut records = self.records()?;
+        if records
+            .get(key_id)
+            .is_some_and(|record| record.user_id == user_id)
+        {
+            records.remove(key_id);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    async fn touch(&self, key_id: &str, used_at: NaiveDateTime) -> Result<(), AuthError> {
+        if let Some(record) = self.records()?.get_mut(key_id) {
+            record.last_used_at = Some(used_at);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+//! Security audit events.
+//!
+//! General logging is meant for debugging and can be verbose (the OAuth2 manager logs tokens
+//! at `debug` level). Security-relevant decisions get a separate channel: `AuthService` reports
+//! them as [`AuditEvent`]s to an [`AuditLog`], which operators can forward to a SIEM.
+//!
+//! Events never carry secrets (passwords, codes, tokens or keys), only identifiers of the
+//! users, keys and limits involved. Users are identified by the pseudonym of their ID
+//! (`AuthService::log_id`), so audit trails correlate events of a user without storing their
+//! ID. The default [`StdoutAuditLog`] writes one JSON object per line to standard output.
+
+use serde::Serialize;
+
+/// A security-relevant decision taken by the service.
+///
+/// Serialized with an `event` tag in `snake_case` (e.g., `{"event":"login_failed",...}`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// A user logged in.
+    LoginSucceeded {
+        /// The pseudonym of the user who logged in.
+        user_id: String,
+        /// The authentication method, as recorded in the `amr` claim (e.g., `pwd`).
+        method: String,
+    },
+    /// A login attempt was rejected.
+    LoginFailed {
+        /// The submitted identifier, for identifier-based logins.
+        identifier: Option<String>,
+        /// The authentication method attempted (e.g., `pwd`, `otp`, `oauth:github`).
+        method: String,
+        /// Why the attempt was rejected (e.g., `invalid_credentials`, `account_disabled`).
+        reason: String,
+    },
+    /// A user exhausted the attempts of their email one-time password or of an account link
+    /// confirmation.
+    LockedOut {
+        /// The pseudonym of the locked-out user.
+        user_id: String,
+    },
+    /// A request was refused by a rate limit.
+    RateLimited {
+        /// The limited key (e.g., `user:<log_id>` or `ip:<address>`).
+        key: String,
+    },
+    /// A consumed single-use refresh token was presented again.
+    RefreshTokenReused {
+        /// The pseudonym of the owner of the token, when the token service can tell.
+        user_id: Option<String>,
+    },
+    /// A login attempt ran into a stored password hash the password manager does not
+    /// recognize, e.g. a corrupted row.
+    MalformedPasswordHash {
+        /// The pseudonym of the user whose stored hash is malformed.
+        user_id: String,
+    },
+    /// A user's password was set or changed.
+    PasswordChanged {
+        /// The pseudonym of the user whose password changed.
+        user_id: String,
+    },
+    /// Every session of a user was revoked.
+    SessionsRevoked {
+        /// The pseudonym of the user whose sessions were revoked.
+        user_id: String,
+    },
+    /// A user's status changed (e.g., the account was suspended).
+    UserStatusChanged {
+        /// The pseudonym of the user whose status changed.
+        user_id: String,
+        /// The new status (e.g., `suspended`).
+        status: String,
+    },
+    /// An API key was created.
+    ApiKeyCreated {
+        /// The pseudonym of the owner of the key.
+        user_id: String,
+        /// The public identifier of the key.
+        key_id: String,
+    },
+    /// An API key was revoked.
+    ApiKeyRevoked {
+        /// The pseudonym of the owner of the key.
+        user_id: String,
+        /// The public identifier of the key.
+        key_id: String,
+    },
+}
+
+impl AuditEvent {
+    /// Returns the `snake_case` name of the event, as serialized in its `event` tag.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AuditEvent::LoginSucceeded { .. } => "login_succeeded",
+            AuditEvent::LoginFailed { .. } => "login_failed",
+            AuditEvent::LockedOut { .. } => "locked_out",
+            AuditEvent::RateLimited { .. } => "rate_limited",
+            AuditEvent::RefreshTokenReused { .. } => "refresh_token_reused",
+            AuditEvent::MalformedPasswordHash { .. } => "malformed_password_hash",
+            AuditEvent::PasswordChanged { .. } => "password_changed",
+            AuditEvent::SessionsRevoked { .. } => "sessions_revoked",
+            AuditEvent::UserStatusChanged { .. } => "user_status_changed",
+            AuditEvent::ApiKeyCreated { .. } => "api_key_created",
+            AuditEvent::ApiKeyRevoked { .. } => "api_key_revoked",
+        }
+    }
+}
+
+/// Receives the audit events of an `AuthService`.
+///
+/// Recording is synchronous and infallible: implementations forwarding events to a remote
+/// system should buffer them rather than block or fail the operation being audited.
+pub trait AuditLog: Send + Sync {
+    /// Records `event`.
+    fn record(&self, event: AuditEvent);
+}
+
+/// The default [`AuditLog`], writing each event to standard output as a JSON line with a
+/// `timestamp` (RFC 3339, UTC).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdoutAuditLog;
+
+impl AuditLog for StdoutAuditLog {
+    fn record(&self, event: AuditEvent) {
+        let mut line = match serde_json::to_value(&event) {
+            Ok(serde_json::Value::Object(line)) => line,
+            _ => serde_json::Map::new(),
+        };
+        line.insert(
+            "timestamp".to_string(),
+            chrono::Utc::now().to_rfc3339().into(),
+        );
+        println!("{}", serde_json::Value::Object(line));
+    }
+}
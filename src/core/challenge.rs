@@ -0,0 +1,29 @@
+//! Bot challenges (CAPTCHAs) on signup and login.
+//!
+//! Scripted signups and credential stuffing can be slowed down by requiring clients to solve a
+//! challenge (e.g., hCaptcha, reCAPTCHA or Cloudflare Turnstile) whose token is sent along with
+//! the request. `AuthService::signup_with_challenge` and `AuthService::login_with_challenge`
+//! hand that token to a [`ChallengeVerifier`] before any identifier lookup or password hashing,
+//! so rejected requests cost almost nothing.
+//!
+//! No verifier is shipped: each challenge provider has its own verification API, called with
+//! the site's secret. Implementations typically post the token to that API and check the
+//! response.
+
+use crate::error::AuthError;
+
+/// Verifies the challenge tokens solved by clients.
+#[async_trait::async_trait]
+pub trait ChallengeVerifier: Send + Sync {
+    /// Verifies a challenge token sent by a client.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token produced by the client-side challenge widget.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::ChallengeFailed`] if the token is invalid, expired or already
+    /// used, or another `AuthError` if the challenge provider cannot be reached.
+    async fn verify(&self, token: &str) -> Result<(), AuthError>;
+}
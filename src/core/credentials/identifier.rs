@@ -0,0 +1,38 @@
+//! Login identifier resolution.
+//!
+//! Users type identifiers in many forms: `+33 6 12 34 56 78` and `0612345678` can name the same
+//! phone number, and a directory may expect a distinguished name where users type a login. An
+//! [`IdentifierResolver`] maps the identifier submitted by the user to the canonical form
+//! stored in the repository. `AuthService` runs it before every identifier lookup (logins,
+//! signups and email one-time passwords), so an identifier is canonicalized in one place.
+
+use crate::error::AuthError;
+
+/// Maps submitted login identifiers to the identifiers stored in the user repository.
+///
+/// Resolution must be deterministic: the identifier stored at signup is the one resolved from
+/// the submitted identifier, and later logins only find the user if they resolve to the same
+/// value.
+pub trait IdentifierResolver: Send + Sync {
+    /// Returns the canonical identifier for `raw`.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The identifier as submitted by the user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AuthError`] (typically [`AuthError::InvalidCredentials`]) if `raw` cannot
+    /// be a valid identifier. The error is returned to the caller of the login or signup.
+    fn resolve(&self, raw: &str) -> Result<String, AuthError>;
+}
+
+/// The default [`IdentifierResolver`], using submitted identifiers as they are.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IdentityIdentifierResolver;
+
+impl IdentifierResolver for IdentityIdentifierResolver {
+    fn resolve(&self, raw: &str) -> Result<String, AuthError> {
+        Ok(raw.to_string())
+    }
+}
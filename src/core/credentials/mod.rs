@@ -7,6 +7,7 @@
 //!
 //! - Defines the [`Credentials`] struct, which encapsulates a user's authentication information.
 //! - Provides methods for creating credentials from plaintext passwords or precomputed hashes.
+//! - Defines the [`StoredCredentials`] struct, the minimal data loaded to verify a login.
 //! - Supports password verification using pluggable password managers.
 //!
 //! ## Features
@@ -17,7 +18,9 @@
 //!
 //! ## Modules
 //!
+//! - [`identifier`]: Contains the [`IdentifierResolver`] trait canonicalizing login identifiers.
 //! - [`plain_password`]: Contains the [`PlainPassword`] type for handling plaintext passwords.
+//! - [`username`]: Contains the [`UsernameGenerator`] trait naming OAuth2 users without an email.
 //!
 //! ## Example
 //!
@@ -39,9 +42,13 @@
 //! # }
 //! ```
 
+pub mod identifier;
 pub mod plain_password;
+pub mod username;
 
+pub use identifier::{IdentifierResolver, IdentityIdentifierResolver};
 pub use plain_password::PlainPassword;
+pub use username::{ProviderUsernameGenerator, UsernameGenerator};
 
 /// Represents a user's credentials, including identifiers and hashed password.
 ///
@@ -82,6 +89,31 @@ impl Credentials {
         }
     }
 
+    /// Creates credentials holding a login identifier but no password, e.g. for an OAuth2 user
+    /// who picked a username after signing up.
+    ///
+    /// Password logins with such credentials fail with
+    /// [`AuthError::InvalidCredentials`](crate::error::AuthError::InvalidCredentials) until a
+    /// password is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - Unique identifier for the user (e.g., UUID).
+    /// * `identifier` - User's login identifier (e.g., email or username).
+    pub fn without_password(user_id: String, identifier: String) -> Self {
+        Self {
+            user_id,
+            identifier,
+            password_hash: String::new(),
+        }
+    }
+
+    /// Returns whether these credentials hold a password hash, unlike those created by
+    /// [`Credentials::without_password`].
+    pub fn has_password(&self) -> bool {
+        !self.password_hash.is_empty()
+    }
+
     /// Creates credentials by hashing a plaintext password using the provided password manager.
     ///
     /// # Arguments
@@ -93,7 +125,8 @@ impl Credentials {
     ///
     /// # Errors
     ///
-    /// Returns [`AuthError::HashingError`] if password hashing fails.
+    /// Returns [`AuthError::HashingError`] if password hashing fails, or
+    /// [`AuthError::HashingTimeout`] if it exceeds the password manager's timeout.
     ///
     /// # Returns
     ///
@@ -107,7 +140,10 @@ impl Credentials {
         let password_hash = manager
             .hash_password(plain_password.as_str())
             .await
-            .map_err(|e| crate::error::AuthError::HashingError(format!("Couldn't hash : {e}")))?;
+            .map_err(|e| match e {
+                crate::error::AuthError::HashingTimeout => e,
+                e => crate::error::AuthError::HashingError(format!("Couldn't hash : {e}")),
+            })?;
 
         Ok(Self {
             user_id,
@@ -125,7 +161,8 @@ impl Credentials {
     ///
     /// # Errors
     ///
-    /// Returns [`AuthError::VerificationError`] if verification fails.
+    /// Returns [`AuthError::VerificationError`] if verification fails, or
+    /// [`AuthError::HashingTimeout`] if it exceeds the password manager's timeout.
     ///
     /// # Returns
     ///
@@ -138,8 +175,26 @@ impl Credentials {
         manager
             .verify_password(plain_password.as_str(), &self.password_hash)
             .await
-            .map_err(|e| {
-                crate::error::AuthError::VerificationError(format!("Couldn't verify : {e}"))
+            .map_err(|e| match e {
+                crate::error::AuthError::HashingTimeout => e,
+                e => crate::error::AuthError::VerificationError(format!("Couldn't verify : {e}")),
             })
     }
 }
+
+/// The part of a user's credentials needed to verify a password.
+///
+/// Returned by [`UserRepository::get_credentials_by_identifier`](crate::core::user::persistence::UserRepository::get_credentials_by_identifier)
+/// so that logins only load the full user once the password has been verified.
+///
+/// # Fields
+///
+/// - `user_id`: The id of the user owning the credentials.
+/// - `password_hash`: Securely hashed password.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StoredCredentials {
+    /// The id of the user owning the credentials
+    pub user_id: crate::core::user::UserId,
+    /// Hashed password
+    pub password_hash: String,
+}
@@ -0,0 +1,39 @@
+//! Username generation for OAuth2 accounts.
+//!
+//! Users created through an OAuth2 provider have no login identifier until they set a
+//! password. Their email is used as identifier when the provider reports one; otherwise a
+//! [`UsernameGenerator`] proposes one. `AuthService` claims each candidate with
+//! [`UserRepository::reserve_identifier`](crate::core::user::persistence::UserRepository::reserve_identifier)
+//! and retries with the next attempt number when it is taken, so concurrent assignments
+//! never produce the same identifier.
+
+use crate::core::oauth::store::{OAuth2Provider, OAuth2UserInfo};
+
+/// Number of candidates tried before giving up on generating a unique username.
+pub const MAX_USERNAME_ATTEMPTS: u32 = 8;
+
+/// Proposes login identifiers for OAuth2 users without a usable email.
+pub trait UsernameGenerator: Send + Sync {
+    /// Returns a candidate username.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - The provider the user signed up with.
+    /// * `info` - The user's information at the provider.
+    /// * `attempt` - The number of candidates already rejected as taken, starting at 0.
+    ///   Implementations should widen their search space as it grows.
+    fn generate(&self, provider: OAuth2Provider, info: &OAuth2UserInfo, attempt: u32) -> String;
+}
+
+/// The default [`UsernameGenerator`], producing `<provider>_<digits>` (e.g., `github_482913`).
+///
+/// The random suffix has 6 digits, plus one per rejected candidate.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProviderUsernameGenerator;
+
+impl UsernameGenerator for ProviderUsernameGenerator {
+    fn generate(&self, provider: OAuth2Provider, _info: &OAuth2UserInfo, attempt: u32) -> String {
+        let digits = crate::core::rand::secure_random_digits(6 + attempt as usize);
+        format!("{provider}_{digits}")
+    }
+}
@@ -0,0 +1,106 @@
+//! Double-submit cookie CSRF protection.
+//!
+//! With the double-submit pattern, the server sets a random value in a cookie and embeds a
+//! matching value in the form (or OAuth2 `state`, or a request header). A cross-site request
+//! carries the cookie but cannot read it, so it cannot produce the matching form value.
+//!
+//! [`generate_csrf_pair`] returns the two values. The form value is the cookie token masked
+//! with a fresh random pad, so it differs on every page rendering and cannot be recovered
+//! through compression side channels (BREACH). [`verify_csrf`] unmasks it and compares it to
+//! the cookie in constant time.
+//!
+//! ```rust
+//! use narangcia_cryptic::core::csrf::{generate_csrf_pair, verify_csrf};
+//!
+//! let (cookie_value, form_value) = generate_csrf_pair();
+//! assert!(verify_csrf(&cookie_value, &form_value));
+//! ```
+//!
+//! The cookie should be set with `SameSite=Lax` (or `Strict`) and `Secure`; it does not need
+//! to be readable by the server-rendered page.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+use crate::core::rand::secure_random_bytes;
+
+/// Number of random bytes in a CSRF token.
+const CSRF_TOKEN_BYTES: usize = 32;
+
+/// Generates a CSRF cookie value and the matching form value.
+///
+/// Both values are URL-safe base64 strings. A new pair should be generated per session (or
+/// per form); the form value can also be regenerated for the same cookie with
+/// [`mask_csrf_token`].
+///
+/// # Returns
+///
+/// A `(cookie_value, form_value)` pair.
+pub fn generate_csrf_pair() -> (String, String) {
+    let token = secure_random_bytes(CSRF_TOKEN_BYTES);
+    let cookie_value = URL_SAFE_NO_PAD.encode(&token);
+    let form_value = mask(&token);
+    (cookie_value, form_value)
+}
+
+/// Returns a new form value matching `cookie_value`, e.g. to render another form for the
+/// same CSRF cookie.
+///
+/// # Arguments
+///
+/// * `cookie_value` - A cookie value returned by [`generate_csrf_pair`].
+///
+/// # Returns
+///
+/// The masked form value, or `None` if `cookie_value` is not a CSRF token.
+pub fn mask_csrf_token(cookie_value: &str) -> Option<String> {
+    let token = decode_token(cookie_value)?;
+    Some(mask(&token))
+}
+
+/// Checks that a submitted form value matches the CSRF cookie, in constant time.
+///
+/// # Arguments
+///
+/// * `cookie_value` - The value of the CSRF cookie sent with the request.
+/// * `form_value` - The value submitted in the form, state parameter or header.
+///
+/// # Returns
+///
+/// `true` if both values come from the same pair, `false` if either is missing, malformed or
+/// tampered with.
+pub fn verify_csrf(cookie_value: &str, form_value: &str) -> bool {
+    let Some(token) = decode_token(cookie_value) else {
+        return false;
+    };
+    let Some(masked) = URL_SAFE_NO_PAD
+        .decode(form_value)
+        .ok()
+        .filter(|masked| masked.len() == 2 * CSRF_TOKEN_BYTES)
+    else {
+        return false;
+    };
+
+    let (pad, cipher) = masked.split_at(CSRF_TOKEN_BYTES);
+    pad.iter()
+        .zip(cipher)
+        .zip(&token)
+        .fold(0u8, |diff, ((p, c), t)| diff | (p ^ c ^ t))
+        == 0
+}
+
+/// Decodes a cookie value into its token bytes.
+fn decode_token(cookie_value: &str) -> Option<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(cookie_value)
+        .ok()
+        .filter(|token| token.len() == CSRF_TOKEN_BYTES)
+}
+
+/// Encodes `token` XORed with a fresh random pad, prefixed with the pad.
+fn mask(token: &[u8]) -> String {
+    let pad = secure_random_bytes(token.len());
+    let mut masked = pad.clone();
+    masked.extend(pad.iter().zip(token).map(|(p, t)| p ^ t));
+    URL_SAFE_NO_PAD.encode(masked)
+}
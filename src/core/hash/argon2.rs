@@ -15,20 +15,227 @@
 //! let hash = hasher.hash(password, Some(&salt)).unwrap();
 //! assert!(hasher.verify(password, &hash).unwrap());
 //! ```
+use std::sync::Mutex;
+
+use zeroize::Zeroizing;
+
 use argon2::{
-    Argon2,
+    Algorithm, Argon2, Block, KeyId, Params, ParamsBuilder, Version,
     password_hash::{
-        Error as PasswordHashError, PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+        Error as PasswordHashError, Output, ParamsString, PasswordHash, PasswordHasher,
+        PasswordVerifier, SaltString,
     },
 };
 
+/// Cost parameters for the Argon2id algorithm.
+///
+/// The defaults match the recommendations of the [`argon2`] crate (19 MiB of memory,
+/// 2 iterations, 1 degree of parallelism).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Argon2Params {
+    /// Memory cost in kibibytes.
+    pub memory_kib: u32,
+    /// Number of iterations (time cost).
+    pub iterations: u32,
+    /// Degree of parallelism (number of lanes).
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Params {
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// Lowest memory cost (in kibibytes) chosen by [`Argon2Params::calibrate`].
+const CALIBRATION_MIN_MEMORY_KIB: u32 = 1024;
+
+/// Highest memory cost (in kibibytes) chosen by [`Argon2Params::calibrate`] (1 GiB).
+const CALIBRATION_MAX_MEMORY_KIB: u32 = 1024 * 1024;
+
+/// Highest iteration count chosen by [`Argon2Params::calibrate`].
+const CALIBRATION_MAX_ITERATIONS: u32 = 32;
+
+/// Maximum number of benchmark rounds run by [`Argon2Params::calibrate`].
+const CALIBRATION_ROUNDS: usize = 6;
+
+impl std::fmt::Display for Argon2Params {
+    /// Formats the parameters like the parameter section of a PHC hash (`m=19456,t=2,p=1`).
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "m={},t={},p={}",
+            self.memory_kib, self.iterations, self.parallelism
+        )
+    }
+}
+
+impl std::str::FromStr for Argon2Params {
+    type Err = crate::error::AuthError;
+
+    /// Parses parameters formatted by [`Argon2Params`]'s `Display` implementation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            || crate::error::AuthError::InvalidInput(format!("Invalid Argon2 parameters: {s}"));
+        let (mut memory_kib, mut iterations, mut parallelism) = (None, None, None);
+        for pair in s.split(',') {
+            let (key, value) = pair.trim().split_once('=').ok_or_else(invalid)?;
+            let value: u32 = value.parse().map_err(|_| invalid())?;
+            match key {
+                "m" => memory_kib = Some(value),
+                "t" => iterations = Some(value),
+                "p" => parallelism = Some(value),
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(Self {
+            memory_kib: memory_kib.ok_or_else(invalid)?,
+            iterations: iterations.ok_or_else(invalid)?,
+            parallelism: parallelism.ok_or_else(invalid)?,
+        })
+    }
+}
+
+impl Argon2Params {
+    /// Benchmarks Argon2id on the current machine and returns parameters hashing a password in
+    /// roughly `target` (e.g. 250ms).
+    ///
+    /// The memory cost is scaled first, keeping the default iteration count; iterations are only
+    /// adjusted once the memory cost reaches its bounds (1 MiB to 1 GiB). Parallelism is kept at 1.
+    ///
+    /// Calibration takes several times `target` and is sensitive to the machine's load: run it
+    /// once at startup or offline and store the result, never per request. Build with
+    /// optimizations, as unoptimized Argon2 is several times slower.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The desired duration of a single password hash.
+    ///
+    /// # Returns
+    ///
+    /// The calibrated parameters.
+    pub fn calibrate(target: std::time::Duration) -> Argon2Params {
+        let mut params = Argon2Params {
+            memory_kib: 16 * 1024,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: 1,
+        };
+        let target = target.as_secs_f64();
+
+        for _ in 0..CALIBRATION_ROUNDS {
+            let elapsed = params.benchmark().as_secs_f64().max(1e-6);
+            let ratio = target / elapsed;
+            if (0.9..=1.1).contains(&ratio) {
+                break;
+            }
+
+            let work = f64::from(params.memory_kib) * f64::from(params.iterations) * ratio;
+            let memory_kib = (work / f64::from(Params::DEFAULT_T_COST)).clamp(
+                f64::from(CALIBRATION_MIN_MEMORY_KIB),
+                f64::from(CALIBRATION_MAX_MEMORY_KIB),
+            );
+            let iterations = (work / memory_kib)
+                .round()
+                .clamp(1.0, f64::from(CALIBRATION_MAX_ITERATIONS));
+            params.memory_kib = memory_kib as u32;
+            params.iterations = iterations as u32;
+        }
+        params
+    }
+
+    /// Returns the fastest of two timed Argon2id hashes with these parameters.
+    fn benchmark(&self) -> std::time::Duration {
+        let Ok(params) = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(32))
+        else {
+            return std::time::Duration::ZERO;
+        };
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+        let mut output = [0u8; 32];
+        (0..2)
+            .map(|_| {
+                let start = std::time::Instant::now();
+                let _ = argon2.hash_password_into(
+                    b"cryptic-calibration",
+                    b"calibration-salt",
+                    &mut output,
+                );
+                start.elapsed()
+            })
+            .min()
+            .unwrap_or_default()
+    }
+}
+
+/// A pool of reusable Argon2 memory buffers.
+///
+/// Argon2 needs `memory_kib` KiB of working memory per hash; allocating (and page-faulting)
+/// it on every call dominates allocator activity on busy login servers. Buffers are handed out
+/// to one hash at a time and returned afterwards, so concurrent hashes never share memory.
+/// Their content is fully overwritten by the next hash using them.
+#[derive(Debug)]
+struct BlockPool {
+    /// Idle buffers, each holding the block count of the hasher's parameters.
+    buffers: Mutex<Vec<Vec<Block>>>,
+    /// Maximum number of idle buffers kept; extra buffers are freed.
+    max_idle: usize,
+}
+
+impl Clone for BlockPool {
+    /// Returns an empty pool with the same idle buffer limit.
+    fn clone(&self) -> Self {
+        Self {
+            buffers: Mutex::new(Vec::with_capacity(self.max_idle)),
+            max_idle: self.max_idle,
+        }
+    }
+}
+
+impl BlockPool {
+    /// Runs `f` with a buffer of `block_count` blocks, reusing an idle buffer if possible.
+    fn with_blocks<R>(&self, block_count: usize, f: impl FnOnce(&mut [Block]) -> R) -> R {
+        let mut blocks = self
+            .buffers
+            .lock()
+            .ok()
+            .and_then(|mut buffers| buffers.pop())
+            .filter(|blocks| blocks.len() == block_count)
+            .unwrap_or_else(|| vec![Block::default(); block_count]);
+
+        let result = f(&mut blocks);
+
+        if let Ok(mut buffers) = self.buffers.lock()
+            && buffers.len() < self.max_idle
+        {
+            buffers.push(blocks);
+        }
+        result
+    }
+}
+
 /// A wrapper for the Argon2 password hashing algorithm.
 ///
-/// Provides methods to hash and verify passwords or arbitrary data using Argon2.
-#[derive(Default)]
+/// Provides methods to hash and verify passwords or arbitrary data using Argon2. Clones share
+/// the parameters and secret key, but not the buffer pool.
+#[derive(Clone)]
 pub struct Argon2Hasher {
     /// The underlying Argon2 hasher instance.
     hasher: Argon2<'static>,
+    /// Reusable working memory, if enabled with [`Argon2Hasher::with_buffer_pool`].
+    pool: Option<BlockPool>,
+    /// The server-held Argon2 secret key, if set with [`Argon2Hasher::with_secret`].
+    secret: Option<Zeroizing<Vec<u8>>>,
+    /// The length (in bytes) of generated salts.
+    salt_len: usize,
+}
+
+impl Default for Argon2Hasher {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Argon2Hasher {
@@ -43,12 +250,215 @@ impl Argon2Hasher {
     pub fn new() -> Self {
         Self {
             hasher: Argon2::default(),
+            pool: None,
+            secret: None,
+            salt_len: crate::core::hash::salt::DEFAULT_SALT_LEN,
         }
     }
 
+    /// Creates a new [`Argon2Hasher`] using Argon2id with the given cost parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The Argon2 cost parameters to use.
+    ///
+    /// # Returns
+    ///
+    /// Returns the hasher, or a [`PasswordHashError`] if the parameters are out of range.
+    pub fn with_params(params: Argon2Params) -> Result<Self, PasswordHashError> {
+        let params = Params::new(
+            params.memory_kib,
+            params.iterations,
+            params.parallelism,
+            None,
+        )?;
+        Ok(Self {
+            hasher: Argon2::new(Algorithm::Argon2id, Version::V0x13, params),
+            pool: None,
+            secret: None,
+            salt_len: crate::core::hash::salt::DEFAULT_SALT_LEN,
+        })
+    }
+
+    /// Enables reuse of Argon2 working memory across hashes and verifications.
+    ///
+    /// Up to `max_idle` buffers of `memory_kib` KiB each are kept between calls, trading
+    /// resident memory for fewer allocations. Size it to the expected number of concurrent
+    /// hashes. Results are identical to those of a hasher without a pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_idle` - The maximum number of idle buffers to keep.
+    ///
+    /// # Returns
+    ///
+    /// The updated hasher.
+    pub fn with_buffer_pool(mut self, max_idle: usize) -> Self {
+        self.pool = Some(BlockPool {
+            buffers: Mutex::new(Vec::with_capacity(max_idle)),
+            max_idle,
+        });
+        self
+    }
+
+    /// Sets the Argon2 secret key (keyed hashing) used for every hash and verification.
+    ///
+    /// Unlike an application-level pepper applied to the password beforehand, the key is an
+    /// input of the Argon2 function itself. It is not stored in the hash string, so hashes
+    /// produced with a key only verify with the same key: a database leak alone is not enough
+    /// to run offline guesses. Losing or changing the key invalidates every such hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The secret key, held by the server outside of the user store.
+    ///
+    /// # Returns
+    ///
+    /// The updated hasher.
+    pub fn with_secret(mut self, key: Vec<u8>) -> Self {
+        self.secret = Some(Zeroizing::new(key));
+        self
+    }
+
+    /// Sets the length of the salts generated for new hashes (16 bytes by default).
+    ///
+    /// The salt is encoded in each hash string, so hashes made with any salt length keep
+    /// verifying after a change.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The salt length in bytes, between
+    ///   [`MIN_SALT_LEN`](crate::core::hash::salt::MIN_SALT_LEN) and
+    ///   [`MAX_SALT_LEN`](crate::core::hash::salt::MAX_SALT_LEN).
+    ///
+    /// # Returns
+    ///
+    /// The updated hasher, or [`PasswordHashError::SaltInvalid`] if `len` is out of range.
+    pub fn with_salt_len(mut self, len: usize) -> Result<Self, PasswordHashError> {
+        crate::core::hash::salt::validate_salt_len(len)?;
+        self.salt_len = len;
+        Ok(self)
+    }
+
+    /// Tags new hashes with the ID of the secret key, in the `keyid` parameter of the PHC
+    /// string.
+    ///
+    /// The ID lets a verifier holding several keys (e.g., while rotating the secret set with
+    /// [`Argon2Hasher::with_secret`]) pick the key of each hash without trying them all. It is
+    /// not an input of the hash: hashes verify whether or not the verifier sets the same ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_id` - The key ID, up to 8 bytes.
+    ///
+    /// # Returns
+    ///
+    /// The updated hasher, or a [`PasswordHashError`] if the ID is too long.
+    pub fn with_key_id(mut self, key_id: &[u8]) -> Result<Self, PasswordHashError> {
+        let params = self.hasher.params();
+        let mut builder = ParamsBuilder::new();
+        builder
+            .m_cost(params.m_cost())
+            .t_cost(params.t_cost())
+            .p_cost(params.p_cost())
+            .keyid(KeyId::new(key_id)?);
+        if let Some(output_len) = params.output_len() {
+            builder.output_len(output_len);
+        }
+        self.hasher = Argon2::new(Algorithm::Argon2id, Version::V0x13, builder.build()?);
+        Ok(self)
+    }
+
+    /// Returns the key ID tagging new hashes, if set with [`Argon2Hasher::with_key_id`].
+    pub fn key_id(&self) -> Option<&[u8]> {
+        Some(self.hasher.params().keyid()).filter(|key_id| !key_id.is_empty())
+    }
+
+    /// Returns the key ID tagging a hash string, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash_str` - The hash string to inspect.
+    ///
+    /// # Returns
+    ///
+    /// The key ID, or `None` if the hash has none or cannot be parsed.
+    pub fn key_id_of(hash_str: &str) -> Option<Vec<u8>> {
+        let hash = PasswordHash::new(hash_str).ok()?;
+        let params = Params::try_from(&hash).ok()?;
+        Some(params.keyid().to_vec()).filter(|key_id| !key_id.is_empty())
+    }
+
+    /// Returns the cost parameters of this hasher.
+    pub fn params(&self) -> Argon2Params {
+        let params = self.hasher.params();
+        Argon2Params {
+            memory_kib: params.m_cost(),
+            iterations: params.t_cost(),
+            parallelism: params.p_cost(),
+        }
+    }
+
+    /// Returns a hasher with the same secret key, key ID and salt length, but other cost
+    /// parameters.
+    ///
+    /// Used to hash a user's password with the parameters targeted for that user. The buffer
+    /// pool is not carried over.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The Argon2 cost parameters of the new hasher.
+    ///
+    /// # Returns
+    ///
+    /// Returns the hasher, or a [`PasswordHashError`] if the parameters are out of range.
+    pub fn with_target(&self, params: Argon2Params) -> Result<Self, PasswordHashError> {
+        let mut hasher = Self::with_params(params)?;
+        if let Some(key_id) = self.key_id() {
+            hasher = hasher.with_key_id(key_id)?;
+        }
+        hasher.secret = self.secret.clone();
+        hasher.salt_len = self.salt_len;
+        Ok(hasher)
+    }
+
+    /// Returns the Argon2 context to hash with, including the secret key if one is set.
+    fn context(&self) -> Result<Argon2<'_>, PasswordHashError> {
+        match &self.secret {
+            Some(secret) => Argon2::new_with_secret(
+                secret,
+                Algorithm::Argon2id,
+                Version::V0x13,
+                self.hasher.params().clone(),
+            )
+            .map_err(PasswordHashError::from),
+            None => Ok(self.hasher.clone()),
+        }
+    }
+
+    /// Computes the raw Argon2 output of `data` and `salt` with this hasher, using `pool`
+    /// for the working memory.
+    fn hash_output_pooled(
+        &self,
+        pool: &BlockPool,
+        data: &[u8],
+        salt: &[u8],
+        output_len: usize,
+    ) -> Result<Output, PasswordHashError> {
+        let context = self.context()?;
+        let block_count = context.params().block_count();
+        Output::init_with(output_len, |out| {
+            pool.with_blocks(block_count, |blocks| {
+                context.hash_password_into_with_memory(data, salt, out, blocks)
+            })
+            .map_err(PasswordHashError::from)
+        })
+    }
+
     /// Hashes arbitrary data (such as a password) using Argon2 and a salt.
     ///
-    /// If a salt is not provided, a secure random salt will be generated.
+    /// If a salt is not provided, a secure random salt of the configured length (see
+    /// [`Argon2Hasher::with_salt_len`]) will be generated.
     ///
     /// # Arguments
     ///
@@ -73,9 +483,28 @@ impl Argon2Hasher {
     ) -> Result<String, PasswordHashError> {
         let salt = match salt {
             Some(s) => s.clone(),
-            None => crate::core::hash::salt::generate_secure_salt()?,
+            None => crate::core::hash::salt::generate_secure_salt_len(self.salt_len)?,
+        };
+        let Some(pool) = &self.pool else {
+            let hash = self.context()?.hash_password(data, &salt)?;
+            return Ok(hash.to_string());
+        };
+
+        let salt = salt.as_salt();
+        let mut salt_bytes = [0u8; 64];
+        let salt_bytes = salt.decode_b64(&mut salt_bytes)?;
+        let params = self.hasher.params();
+        let output_len = params.output_len().unwrap_or(Params::DEFAULT_OUTPUT_LEN);
+        let output = self.hash_output_pooled(pool, data, salt_bytes, output_len)?;
+
+        // Both constructors use Argon2id v19
+        let hash = PasswordHash {
+            algorithm: Algorithm::Argon2id.ident(),
+            version: Some(Version::V0x13.into()),
+            params: ParamsString::try_from(params)?,
+            salt: Some(salt),
+            hash: Some(output),
         };
-        let hash = self.hasher.hash_password(data, &salt)?;
         Ok(hash.to_string())
     }
 
@@ -100,10 +529,82 @@ impl Argon2Hasher {
     /// ```
     pub fn verify(&self, data: &[u8], hash_str: &str) -> Result<bool, PasswordHashError> {
         let parsed_hash = PasswordHash::new(hash_str)?;
-        match self.hasher.verify_password(data, &parsed_hash) {
+        if let Some(pool) = &self.pool
+            && let (Some(salt), Some(expected)) = (parsed_hash.salt, parsed_hash.hash)
+            && self.uses_own_params(&parsed_hash)
+        {
+            let mut salt_bytes = [0u8; 64];
+            let salt_bytes = salt.decode_b64(&mut salt_bytes)?;
+            let output = self.hash_output_pooled(pool, data, salt_bytes, expected.len())?;
+            // `Output` equality is constant-time
+            return Ok(output == expected);
+        }
+
+        match self.context()?.verify_password(data, &parsed_hash) {
             Ok(()) => Ok(true),
             Err(PasswordHashError::Password) => Ok(false),
             Err(e) => Err(e),
         }
     }
+
+    /// Returns whether `hash` was produced with this hasher's algorithm and cost parameters,
+    /// so that it can be recomputed with pooled memory.
+    fn uses_own_params(&self, hash: &PasswordHash<'_>) -> bool {
+        let own = self.hasher.params();
+        hash.algorithm == Algorithm::Argon2id.ident()
+            && hash.version == Some(Version::V0x13.into())
+            && Params::try_from(hash).is_ok_and(|params| {
+                params.m_cost() == own.m_cost()
+                    && params.t_cost() == own.t_cost()
+                    && params.p_cost() == own.p_cost()
+                    && params.keyid().is_empty()
+                    && params.data().is_empty()
+            })
+    }
+
+    /// Returns whether `hash_str` is an Argon2 PHC string.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash_str` - The hash string to inspect.
+    pub fn identify(&self, hash_str: &str) -> bool {
+        PasswordHash::new(hash_str)
+            .map(|hash| hash.algorithm.as_str().starts_with("argon2"))
+            .unwrap_or(false)
+    }
+
+    /// Returns whether `hash_str` was produced with a different algorithm, version or
+    /// cost parameters than this hasher's, and should therefore be recomputed.
+    ///
+    /// Unparseable hashes are reported as needing a rehash.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash_str` - The hash string to inspect.
+    pub fn needs_rehash(&self, hash_str: &str) -> bool {
+        self.needs_rehash_for(hash_str, self.params())
+    }
+
+    /// Returns whether `hash_str` was produced with a different algorithm, version or
+    /// cost parameters than `target`, and should therefore be recomputed.
+    ///
+    /// Unparseable hashes are reported as needing a rehash.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash_str` - The hash string to inspect.
+    /// * `target` - The cost parameters the hash should have been produced with.
+    pub fn needs_rehash_for(&self, hash_str: &str, target: Argon2Params) -> bool {
+        let Ok(hash) = PasswordHash::new(hash_str) else {
+            return true;
+        };
+        let Ok(params) = Params::try_from(&hash) else {
+            return true;
+        };
+        hash.algorithm != Algorithm::Argon2id.ident()
+            || hash.version != Some(Version::V0x13.into())
+            || params.m_cost() != target.memory_kib
+            || params.t_cost() != target.iterations
+            || params.p_cost() != target.parallelism
+    }
 }
@@ -1,5 +1,5 @@
-pub use argon2::Argon2Hasher;
-pub use salt::generate_secure_salt;
+pub use argon2::{Argon2Hasher, Argon2Params};
+pub use salt::{generate_secure_salt, generate_secure_salt_len};
 
 /// Hashing utilities for the `cryptic` authentication library.
 ///
@@ -12,7 +12,9 @@ pub use salt::generate_secure_salt;
 ///
 /// # Re-exports
 /// - [`Argon2Hasher`]: Main struct for hashing and verifying passwords using Argon2.
+/// - [`Argon2Params`]: Cost parameters for the Argon2 hasher.
 /// - [`generate_secure_salt`]: Function to generate a cryptographically secure random salt.
+/// - [`generate_secure_salt_len`]: Same, with a given salt length.
 /// Argon2 password hashing implementation.
 pub mod argon2;
 /// Salt generation utilities.
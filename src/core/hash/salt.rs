@@ -12,13 +12,40 @@
 //! println!("Salt: {}", salt.as_str());
 //! ```
 
+use crate::core::rand::try_fill_secure_random;
+use argon2::password_hash::errors::InvalidValue;
 use argon2::password_hash::{Error as PasswordHashError, SaltString};
-use rand::{TryRngCore, rngs::OsRng};
+
+/// Default salt length (in bytes), as recommended for password hashing.
+pub const DEFAULT_SALT_LEN: usize = 16;
+
+/// Minimum salt length (in bytes) accepted by Argon2.
+pub const MIN_SALT_LEN: usize = argon2::MIN_SALT_LEN;
+
+/// Maximum salt length (in bytes) that fits in a PHC hash string.
+pub const MAX_SALT_LEN: usize = 48;
+
+/// Checks that `len` is a salt length Argon2 and the PHC format accept.
+///
+/// # Errors
+///
+/// Returns [`PasswordHashError::SaltInvalid`] if `len` is below [`MIN_SALT_LEN`] or above
+/// [`MAX_SALT_LEN`].
+pub fn validate_salt_len(len: usize) -> Result<(), PasswordHashError> {
+    if len < MIN_SALT_LEN {
+        return Err(PasswordHashError::SaltInvalid(InvalidValue::TooShort));
+    }
+    if len > MAX_SALT_LEN {
+        return Err(PasswordHashError::SaltInvalid(InvalidValue::TooLong));
+    }
+    Ok(())
+}
 
 /// Generates a cryptographically secure random salt for password hashing.
 ///
-/// This function uses the operating system's secure random number generator to fill a 16-byte array,
-/// then encodes it as a base64 salt string compatible with Argon2 password hashing.
+/// This function uses the operating system's secure random number generator to fill a
+/// [`DEFAULT_SALT_LEN`]-byte array, then encodes it as a base64 salt string compatible with
+/// Argon2 password hashing.
 ///
 /// # Errors
 ///
@@ -32,11 +59,32 @@ use rand::{TryRngCore, rngs::OsRng};
 /// println!("Salt: {}", salt.as_str());
 /// ```
 pub fn generate_secure_salt() -> Result<SaltString, PasswordHashError> {
-    let mut bytes = [0u8; 16];
+    generate_secure_salt_len(DEFAULT_SALT_LEN)
+}
+
+/// Generates a cryptographically secure random salt of `len` bytes for password hashing.
+///
+/// # Arguments
+///
+/// * `len` - The salt length in bytes, between [`MIN_SALT_LEN`] and [`MAX_SALT_LEN`].
+///
+/// # Errors
+///
+/// Returns [`PasswordHashError::SaltInvalid`] if `len` is out of range, or another
+/// [`PasswordHashError`] if the random number generator fails.
+///
+/// # Example
+///
+/// ```rust
+/// use cryptic::core::hash::salt::generate_secure_salt_len;
+/// let salt = generate_secure_salt_len(32).expect("Failed to generate salt");
+/// ```
+pub fn generate_secure_salt_len(len: usize) -> Result<SaltString, PasswordHashError> {
+    validate_salt_len(len)?;
+    let mut bytes = [0u8; MAX_SALT_LEN];
+    let bytes = &mut bytes[..len];
 
-    OsRng
-        .try_fill_bytes(&mut bytes)
-        .map_err(|_| PasswordHashError::Password)?;
+    try_fill_secure_random(bytes).map_err(|_| PasswordHashError::Password)?;
 
-    SaltString::encode_b64(&bytes)
+    SaltString::encode_b64(bytes)
 }
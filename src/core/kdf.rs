@@ -0,0 +1,91 @@
+//! Derivation of purpose-specific keys from the service secret.
+//!
+//! Features keying a MAC (email one-time passwords, password reset tokens, API keys, log
+//! identifiers) must not use
+//! `secret_key` directly: a tag computed under the JWT signing key for one purpose could then
+//! be replayed for another. [`derive_key`] expands the secret with HKDF-SHA256 into one
+//! independent key per label, and [`hmac_sha256`] and [`verify_hmac_sha256`] compute and
+//! check tags under such a key.
+//!
+//! # Example
+//!
+//! ```rust
+//! use narangcia_cryptic::core::kdf::{derive_key, hmac_sha256, verify_hmac_sha256};
+//!
+//! let key = derive_key(b"service secret", "example");
+//! let tag = hmac_sha256(key.as_slice(), b"message");
+//! assert!(verify_hmac_sha256(key.as_slice(), b"message", &tag));
+//! assert!(!verify_hmac_sha256(key.as_slice(), b"other message", &tag));
+//! ```
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+/// Label of the key hashing email one-time passwords.
+pub const EMAIL_OTP_LABEL: &str = "cryptic email-otp";
+
+/// Label of the key hashing API keys.
+pub const API_KEY_LABEL: &str = "cryptic api-key";
+
+/// Label of the key hashing password reset tokens.
+pub const PASSWORD_RESET_LABEL: &str = "cryptic password-reset";
+
+/// Label of the key of user ID pseudonyms in log lines.
+pub const LOG_ID_LABEL: &str = "cryptic log-id";
+
+/// Length in bytes of derived keys and HMAC-SHA256 tags.
+pub const KEY_LEN: usize = 32;
+
+/// Derives the key for `label` from `secret` with HKDF-SHA256.
+///
+/// Different labels yield independent keys, and the secret cannot be recovered from them.
+///
+/// # Arguments
+///
+/// * `secret` - The input keying material, usually the service's `secret_key`.
+/// * `label` - The purpose of the key, passed as HKDF `info`.
+///
+/// # Returns
+///
+/// The derived key, zeroized on drop.
+pub fn derive_key(secret: &[u8], label: &str) -> Zeroizing<[u8; KEY_LEN]> {
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    hkdf::Hkdf::<Sha256>::new(None, secret)
+        .expand(label.as_bytes(), key.as_mut_slice())
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+/// Computes the HMAC-SHA256 tag of `message` under `key`.
+///
+/// # Arguments
+///
+/// * `key` - The MAC key, usually returned by [`derive_key`].
+/// * `message` - The message to authenticate.
+///
+/// # Returns
+///
+/// The tag.
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; KEY_LEN] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+/// Checks the HMAC-SHA256 tag of `message` under `key`, in constant time.
+///
+/// # Arguments
+///
+/// * `key` - The MAC key the tag was computed with.
+/// * `message` - The authenticated message.
+/// * `tag` - The tag to check.
+///
+/// # Returns
+///
+/// Whether `tag` is the tag of `message` under `key`.
+pub fn verify_hmac_sha256(key: &[u8], message: &[u8], tag: &[u8]) -> bool {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.verify_slice(tag).is_ok()
+}
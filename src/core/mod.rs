@@ -1,8 +1,18 @@
+pub mod api_key;
+pub mod audit;
+pub mod challenge;
 pub mod credentials;
+pub mod csrf;
 pub mod hash;
+pub mod kdf;
+pub mod notify;
 pub mod oauth;
+pub mod otp;
 pub mod password;
 pub mod policy;
+pub mod rand;
+pub mod rate_limit;
+pub mod signed_url;
 pub mod token;
 pub mod user;
 pub mod vars;
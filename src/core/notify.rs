@@ -0,0 +1,76 @@
+//! User notifications on security events.
+//!
+//! Users should hear about changes to their account they may not have made themselves: a new
+//! password, a login, a password reset request. `AuthService` decides *when* to notify and
+//! calls a [`Notifier`]; *how* the user is reached (email, push, SMS) is left to the
+//! application. The default [`NoopNotifier`] sends nothing.
+//!
+//! Notifications are awaited in the flow that triggered them, so implementations talking to
+//! slow services should enqueue the message rather than deliver it inline. Failures are
+//! logged and never fail the flow.
+
+use crate::core::user::User;
+use crate::error::AuthError;
+
+/// Notifies users of security events on their account.
+#[async_trait::async_trait]
+pub trait Notifier: Send + Sync {
+    /// Called once a new password is stored for `user` (set, changed or reset).
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user whose password changed, with their new credentials.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AuthError`] if the notification cannot be sent. The error is logged.
+    async fn notify_password_changed(&self, user: &User) -> Result<(), AuthError>;
+
+    /// Called after every successful login of `user`.
+    ///
+    /// Implementations decide whether the login warrants a message (e.g., only for a device
+    /// or location they have not seen before).
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user who logged in.
+    /// * `method` - The authentication method, as recorded in the `amr` claim (e.g., `pwd`,
+    ///   `otp`, `oauth:github`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AuthError`] if the notification cannot be sent. The error is logged.
+    async fn notify_new_login(&self, user: &User, method: &str) -> Result<(), AuthError>;
+
+    /// Called when a password reset is requested for `user`, by
+    /// [`AuthService::request_password_reset`](crate::AuthService::request_password_reset)
+    /// once the reset token is stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user a reset was requested for.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AuthError`] if the notification cannot be sent.
+    async fn notify_reset_requested(&self, user: &User) -> Result<(), AuthError>;
+}
+
+/// The default [`Notifier`], sending nothing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopNotifier;
+
+#[async_trait::async_trait]
+impl Notifier for NoopNotifier {
+    async fn notify_password_changed(&self, _user: &User) -> Result<(), AuthError> {
+        Ok(())
+    }
+
+    async fn notify_new_login(&self, _user: &User, _method: &str) -> Result<(), AuthError> {
+        Ok(())
+    }
+
+    async fn notify_reset_requested(&self, _user: &User) -> Result<(), AuthError> {
+        Ok(())
+    }
+}
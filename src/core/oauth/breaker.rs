@@ -0,0 +1,143 @@
+//! Per-provider circuit breaking of OAuth2 provider calls.
+//!
+//! When a provider is down, every login through it waits for a request to fail, and the
+//! retries of users pile up on the provider as it recovers. A [`CircuitBreaker`] counts the
+//! consecutive failures of each provider: once [`CircuitBreakerConfig::failure_threshold`] is
+//! reached, the circuit opens and calls fail fast with [`AuthError::OAuthNetwork`] for
+//! [`CircuitBreakerConfig::cooldown_secs`] seconds, without contacting the provider.
+//!
+//! After the cooldown, calls go through again. A success closes the circuit; a failure opens
+//! it for another cooldown right away.
+//!
+//! Only failures telling that the provider is unreachable or malfunctioning count: network
+//! errors, unparsable responses and server errors. Standard OAuth2 errors (e.g., an
+//! `invalid_grant` for a reused code) prove that the provider is up and reset the count.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::store::OAuth2Provider;
+use crate::error::AuthError;
+
+/// Default cooldown (in seconds) of a circuit breaker configured without one.
+pub const DEFAULT_BREAKER_COOLDOWN: u64 = 30;
+
+/// When the circuit of a provider opens, and for how long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// The number of consecutive failures opening the circuit.
+    pub failure_threshold: u32,
+    /// How long (in seconds) an open circuit fails calls before letting them through again.
+    pub cooldown_secs: u64,
+}
+
+impl CircuitBreakerConfig {
+    /// Creates a configuration opening circuits after `failure_threshold` consecutive failures
+    /// for `cooldown_secs` seconds.
+    pub fn new(failure_threshold: u32, cooldown_secs: u64) -> Self {
+        Self {
+            failure_threshold,
+            cooldown_secs,
+        }
+    }
+}
+
+/// The failure count of a provider.
+#[derive(Debug, Default)]
+struct Circuit {
+    /// The number of failures since the last success.
+    consecutive_failures: u32,
+    /// Until when calls fail fast, if the circuit is open.
+    open_until: Option<Instant>,
+}
+
+/// Tracks the failures of each provider and fails calls to providers whose circuit is open.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    /// The thresholds of the breaker.
+    config: CircuitBreakerConfig,
+    /// The circuits of the providers called so far.
+    circuits: Mutex<HashMap<OAuth2Provider, Circuit>>,
+}
+
+impl CircuitBreaker {
+    /// Creates a breaker with every circuit closed.
+    ///
+    /// # Arguments
+    /// * `config` - When circuits open, and for how long.
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            circuits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns whether calls to `provider` currently fail fast.
+    pub fn is_open(&self, provider: OAuth2Provider) -> bool {
+        self.check(provider).is_err()
+    }
+
+    /// Checks that `provider` may be called.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::OAuthNetwork`] if the circuit of `provider` is open.
+    pub fn check(&self, provider: OAuth2Provider) -> Result<(), AuthError> {
+        let circuits = self.circuits.lock().map_err(poisoned)?;
+        match circuits
+            .get(&provider)
+            .and_then(|circuit| circuit.open_until)
+        {
+            Some(open_until) if Instant::now() < open_until => Err(AuthError::OAuthNetwork(
+                format!("{provider} is unavailable, retry later"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Records the outcome of a call to `provider`, counting errors that indicate an outage.
+    pub fn record<T>(&self, provider: OAuth2Provider, outcome: &Result<T, AuthError>) {
+        match outcome {
+            Err(
+                AuthError::OAuthNetwork(_)
+                | AuthError::OAuthTokenExchange(_)
+                | AuthError::OAuthInvalidResponse(_),
+            ) => self.record_failure(provider),
+            _ => self.record_success(provider),
+        }
+    }
+
+    /// Records a successful call to `provider`, closing its circuit.
+    pub fn record_success(&self, provider: OAuth2Provider) {
+        if let Ok(mut circuits) = self.circuits.lock() {
+            circuits.remove(&provider);
+        }
+    }
+
+    /// Records a failed call to `provider`, opening its circuit once the threshold is reached.
+    pub fn record_failure(&self, provider: OAuth2Provider) {
+        let Ok(mut circuits) = self.circuits.lock() else {
+            return;
+        };
+        let circuit = circuits.entry(provider).or_default();
+        circuit.consecutive_failures = circuit.consecutive_failures.saturating_add(1);
+        if circuit.consecutive_failures >= self.config.failure_threshold {
+            if circuit
+                .open_until
+                .is_none_or(|open_until| open_until <= Instant::now())
+            {
+                log::warn!(
+                    "Opening the circuit of {provider} after {} consecutive failures",
+                    circuit.consecutive_failures
+                );
+            }
+            circuit.open_until =
+                Some(Instant::now() + Duration::from_secs(self.config.cooldown_secs));
+        }
+    }
+}
+
+/// Maps a poisoned lock to [`AuthError::ServiceUnavailable`].
+fn poisoned<E: std::fmt::Display>(e: E) -> AuthError {
+    AuthError::ServiceUnavailable(e.to_string())
+}
@@ -0,0 +1,260 @@
+//! GitHub App installation tokens.
+//!
+//! A GitHub App authenticates as itself with a short-lived JWT signed by its private key, and
+//! exchanges that JWT for an installation access token to call the API on behalf of an
+//! installation. This flow is server-to-server and unrelated to the user OAuth2 flows of
+//! [`OAuth2Manager`](super::manager::OAuth2Manager): no user is involved and no user info is
+//! fetched.
+//!
+//! [`GitHubAppTokenService`] signs the app JWT, requests installation tokens from the GitHub
+//! API, and caches them until shortly before they expire.
+//!
+//! ```rust,ignore
+//! let service = GitHubAppTokenService::new("123456", &std::fs::read("app.pem")?)?;
+//! let token = service.installation_token(42).await?;
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use log::debug;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::AuthError;
+
+/// The default base URL of the GitHub REST API.
+pub const GITHUB_API_BASE: &str = "https://api.github.com";
+
+/// How long before its expiration a cached installation token stops being used.
+const REFRESH_MARGIN_SECONDS: i64 = 60;
+
+/// Lifetime of the app JWT. GitHub rejects JWTs valid for more than ten minutes.
+const APP_JWT_LIFETIME_SECONDS: i64 = 540;
+
+/// How far in the past the app JWT is issued, to tolerate clock drift with GitHub.
+const APP_JWT_BACKDATE_SECONDS: i64 = 60;
+
+/// Claims of the JWT a GitHub App authenticates with.
+#[derive(Debug, Serialize)]
+struct AppClaims {
+    /// Issued at (as a UNIX timestamp).
+    iat: i64,
+    /// Expiration time (as a UNIX timestamp).
+    exp: i64,
+    /// The app ID (or client ID) of the GitHub App.
+    iss: String,
+}
+
+/// The body of a successful installation token response.
+#[derive(Deserialize)]
+struct InstallationTokenResponse {
+    /// The installation access token.
+    token: String,
+    /// When the token expires.
+    expires_at: DateTime<Utc>,
+}
+
+/// An installation access token of a GitHub App.
+///
+/// Its `Debug` output redacts the token.
+#[derive(Clone, PartialEq, Eq)]
+pub struct InstallationToken {
+    /// The installation the token grants access to.
+    pub installation_id: u64,
+    /// The token, sent as a bearer token to the GitHub API.
+    pub token: String,
+    /// When GitHub stops accepting the token.
+    pub expires_at: DateTime<Utc>,
+}
+
+impl std::fmt::Debug for InstallationToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InstallationToken")
+            .field("installation_id", &self.installation_id)
+            .field("token", &"[REDACTED]")
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+/// Issues and caches installation access tokens for a GitHub App.
+///
+/// Tokens are cached per installation and reused until a minute before they
+/// expire, so callers can request a token for every API call.
+pub struct GitHubAppTokenService {
+    /// The app ID (or client ID) of the GitHub App.
+    app_id: String,
+    /// The private key of the GitHub App.
+    encoding_key: EncodingKey,
+    /// The base URL of the GitHub API, without a trailing slash.
+    api_base: String,
+    /// The HTTP client used for API requests.
+    http_client: Client,
+    /// Cached installation tokens mapped by installation ID.
+    cache: Mutex<HashMap<u64, InstallationToken>>,
+}
+
+impl std::fmt::Debug for GitHubAppTokenService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GitHubAppTokenService")
+            .field("app_id", &self.app_id)
+            .field("api_base", &self.api_base)
+            .finish_non_exhaustive()
+    }
+}
+
+impl GitHubAppTokenService {
+    /// Creates a service for the GitHub App `app_id`, using the public GitHub API.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The app ID (or client ID) of the GitHub App.
+    /// * `private_key_pem` - The PEM-encoded RSA private key generated for the app.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::ConfigError`] if the private key cannot be parsed or the HTTP
+    /// client cannot be built.
+    pub fn new(app_id: impl Into<String>, private_key_pem: &[u8]) -> Result<Self, AuthError> {
+        let app_id = app_id.into();
+        let encoding_key = EncodingKey::from_rsa_pem(private_key_pem)
+            .map_err(|e| AuthError::ConfigError(format!("Invalid GitHub App private key: {e}")))?;
+        let http_client = Client::builder()
+            .user_agent(format!("cryptic-github-app-{app_id}"))
+            .build()
+            .map_err(|e| AuthError::ConfigError(format!("Failed to create HTTP client: {e}")))?;
+
+        Ok(Self {
+            app_id,
+            encoding_key,
+            api_base: GITHUB_API_BASE.to_string(),
+            http_client,
+            cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Sets the base URL of the GitHub API, for GitHub Enterprise Server or tests.
+    ///
+    /// # Arguments
+    ///
+    /// * `api_base` - The API base URL (e.g., `https://github.example.com/api/v3`).
+    pub fn with_api_base(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = api_base.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Signs a JWT authenticating as the GitHub App.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The JWT, valid for nine minutes.
+    /// * `Err(AuthError)` - If signing failed.
+    pub fn app_jwt(&self) -> Result<String, AuthError> {
+        let now = Utc::now().timestamp();
+        let claims = AppClaims {
+            iat: now - APP_JWT_BACKDATE_SECONDS,
+            exp: now + APP_JWT_LIFETIME_SECONDS,
+            iss: self.app_id.clone(),
+        };
+        jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &self.encoding_key)
+            .map_err(|e| AuthError::TokenGeneration(format!("Failed to sign GitHub App JWT: {e}")))
+    }
+
+    /// Returns an installation access token, from the cache if it is not about to expire.
+    ///
+    /// # Arguments
+    ///
+    /// * `installation_id` - The ID of the app installation.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(InstallationToken)` - A token valid for at least another minute.
+    /// * `Err(AuthError)` - If the token could not be obtained from GitHub.
+    ///
+    /// # Errors
+    ///
+    /// * [`AuthError::OAuthNetwork`] - If the GitHub API could not be reached.
+    /// * [`AuthError::OAuthProvider`] - If GitHub rejected the request.
+    /// * [`AuthError::OAuthInvalidResponse`] - If the response could not be parsed.
+    pub async fn installation_token(
+        &self,
+        installation_id: u64,
+    ) -> Result<InstallationToken, AuthError> {
+        if let Some(token) = self.cached_token(installation_id) {
+            return Ok(token);
+        }
+
+        let token = self.request_installation_token(installation_id).await?;
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(installation_id, token.clone());
+        }
+        Ok(token)
+    }
+
+    /// Drops the cached token of `installation_id`, e.g. after GitHub rejected it.
+    ///
+    /// # Arguments
+    ///
+    /// * `installation_id` - The ID of the app installation.
+    pub fn invalidate(&self, installation_id: u64) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.remove(&installation_id);
+        }
+    }
+
+    /// Returns the cached token of `installation_id`, if it is not about to expire.
+    fn cached_token(&self, installation_id: u64) -> Option<InstallationToken> {
+        let cache = self.cache.lock().ok()?;
+        cache
+            .get(&installation_id)
+            .filter(|token| {
+                token.expires_at - Duration::seconds(REFRESH_MARGIN_SECONDS) > Utc::now()
+            })
+            .cloned()
+    }
+
+    /// Exchanges a fresh app JWT for an installation token.
+    async fn request_installation_token(
+        &self,
+        installation_id: u64,
+    ) -> Result<InstallationToken, AuthError> {
+        let url = format!(
+            "{}/app/installations/{installation_id}/access_tokens",
+            self.api_base
+        );
+        debug!("Requesting GitHub installation token for installation {installation_id}");
+
+        let response = self
+            .http_client
+            .post(&url)
+            .bearer_auth(self.app_jwt()?)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .send()
+            .await
+            .map_err(|e| {
+                AuthError::OAuthNetwork(format!("Failed to request installation token: {e}"))
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AuthError::OAuthProvider(format!(
+                "GitHub rejected the installation token request with status {status}: {body}"
+            )));
+        }
+
+        let body: InstallationTokenResponse = response.json().await.map_err(|e| {
+            AuthError::OAuthInvalidResponse(format!("Invalid installation token response: {e}"))
+        })?;
+
+        Ok(InstallationToken {
+            installation_id,
+            token: body.token,
+            expires_at: body.expires_at,
+        })
+    }
+}
@@ -0,0 +1,120 @@
+//! Pending OAuth2 account links.
+//!
+//! When an OAuth2 login matches an existing password account by email, linking the provider
+//! silently would let anyone controlling that email at the provider take over the account.
+//! Instead, the provider's user info is parked as a pending link until the account owner
+//! proves ownership (see `AuthService::confirm_link`). This module provides the
+//! [`PendingLinkStore`] trait and an in-memory default implementation.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use super::store::{OAuth2Provider, OAuth2UserInfo};
+use crate::error::AuthError;
+
+/// Default lifetime (in seconds) of a pending account link.
+pub const DEFAULT_PENDING_LINK_TTL: u64 = 10 * 60;
+
+/// Stores OAuth2 accounts waiting for their link to a user to be confirmed.
+#[async_trait::async_trait]
+pub trait PendingLinkStore: Send + Sync {
+    /// Stores `user_info` as pending for `user_id` until `expires_at`, replacing any pending
+    /// link for the same provider.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - The ID of the user the account would be linked to.
+    /// * `user_info` - The provider's user info; its `provider` field identifies the link.
+    /// * `expires_at` - The link's expiration (UNIX timestamp, seconds).
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn put(
+        &self,
+        user_id: &str,
+        user_info: OAuth2UserInfo,
+        expires_at: usize,
+    ) -> Result<(), AuthError>;
+
+    /// Returns the unexpired pending link of `user_id` with `provider`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn get(
+        &self,
+        user_id: &str,
+        provider: OAuth2Provider,
+    ) -> Result<Option<OAuth2UserInfo>, AuthError>;
+
+    /// Removes the pending link of `user_id` with `provider`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn remove(&self, user_id: &str, provider: OAuth2Provider) -> Result<(), AuthError>;
+}
+
+/// Pending links mapped by user ID and provider, with their expiration timestamp.
+type PendingLinks = HashMap<(String, OAuth2Provider), (OAuth2UserInfo, usize)>;
+
+/// In-memory implementation of [`PendingLinkStore`].
+///
+/// Expired links are pruned lazily. Suitable for single-instance deployments and tests;
+/// multi-instance deployments need a shared store.
+#[derive(Debug, Default)]
+pub struct InMemoryPendingLinkStore {
+    /// Pending links mapped by user ID and provider, with their expiration timestamp.
+    links: Mutex<PendingLinks>,
+}
+
+impl InMemoryPendingLinkStore {
+    /// Creates a new, empty in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Locks the links, pruning the expired ones.
+    fn live_links(&self) -> Result<std::sync::MutexGuard<'_, PendingLinks>, AuthError> {
+        let now = chrono::Utc::now().timestamp().max(0) as usize;
+        let mut links = self
+            .links
+            .lock()
+            .map_err(|e| AuthError::ServiceUnavailable(e.to_string()))?;
+        links.retain(|_, (_, expires_at)| *expires_at > now);
+        Ok(links)
+    }
+}
+
+#[async_trait::async_trait]
+impl PendingLinkStore for InMemoryPendingLinkStore {
+    async fn put(
+        &self,
+        user_id: &str,
+        user_info: OAuth2UserInfo,
+        expires_at: usize,
+    ) -> Result<(), AuthError> {
+        self.live_links()?.insert(
+            (user_id.to_string(), user_info.provider),
+            (user_info, expires_at),
+        );
+        Ok(())
+    }
+
+    async fn get(
+        &self,
+        user_id: &str,
+        provider: OAuth2Provider,
+    ) -> Result<Option<OAuth2UserInfo>, AuthError> {
+        Ok(self
+            .live_links()?
+            .get(&(user_id.to_string(), provider))
+            .map(|(user_info, _)| user_info.clone()))
+    }
+
+    async fn remove(&self, user_id: &str, provider: OAuth2Provider) -> Result<(), AuthError> {
+        self.live_links()?.remove(&(user_id.to_string(), provider));
+        Ok(())
+    }
+}
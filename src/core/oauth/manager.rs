@@ -22,6 +22,7 @@
 //! - HTTP requests for OAuth2 endpoints
 //! - Parsing provider-specific user info responses
 //! - Token exchange and refresh
+//! - Optional per-provider circuit breaking of provider calls
 //!
 //! ## Example Usage
 //!
@@ -37,7 +38,8 @@
 use async_trait::async_trait;
 use oauth2::{
     AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, EmptyExtraTokenFields,
-    EndpointSet, RedirectUrl, RefreshToken, Scope, TokenResponse, TokenUrl, basic::BasicClient,
+    EndpointSet, RedirectUrl, RefreshToken, RequestTokenError, Scope, StandardErrorResponse,
+    TokenResponse, TokenUrl, basic::BasicClient, basic::BasicErrorResponseType,
     basic::BasicTokenType,
 };
 use reqwest::Client;
@@ -45,6 +47,8 @@ use serde_json::Value;
 use std::collections::HashMap;
 
 use super::OAuth2Service;
+use super::breaker::{CircuitBreaker, CircuitBreakerConfig};
+use super::state::SignedState;
 use super::store::{OAuth2Config, OAuth2Provider, OAuth2Token, OAuth2UserInfo};
 use crate::AuthError;
 
@@ -55,7 +59,7 @@ use log::{debug, info};
 /// This alias simplifies the usage of the [`oauth2::Client`] type with all required generic parameters for standard OAuth2 flows.
 /// It is used internally to manage provider-specific OAuth2 clients, ensuring type safety and consistency across supported providers.
 type ConfiguredBasicClient = oauth2::Client<
-    oauth2::StandardErrorResponse<oauth2::basic::BasicErrorResponseType>,
+    StandardErrorResponse<BasicErrorResponseType>,
     oauth2::StandardTokenResponse<EmptyExtraTokenFields, BasicTokenType>,
     oauth2::basic::BasicTokenIntrospectionResponse,
     oauth2::StandardRevocableToken,
@@ -93,6 +97,14 @@ type ConfiguredBasicClient = oauth2::Client<
 pub struct OAuth2Manager {
     /// Map of OAuth2 providers to their configuration.
     configs: HashMap<OAuth2Provider, OAuth2Config>,
+    /// Token endpoints replacing the default endpoint of a provider.
+    token_urls: HashMap<OAuth2Provider, String>,
+    /// User info endpoints replacing the default endpoint of a provider.
+    user_info_urls: HashMap<OAuth2Provider, String>,
+    /// The signer of stateless `state` parameters, if enabled.
+    signed_state: Option<SignedState>,
+    /// The circuit breaker of provider calls, if enabled.
+    breaker: Option<CircuitBreaker>,
 }
 
 impl OAuth2Manager {
@@ -109,7 +121,164 @@ impl OAuth2Manager {
             configs.len()
         );
 
-        Self { configs }
+        Self {
+            configs,
+            token_urls: HashMap::new(),
+            user_info_urls: HashMap::new(),
+            signed_state: None,
+            breaker: None,
+        }
+    }
+
+    /// Replaces the token endpoint of `provider`, e.g. to go through a proxy or a test server.
+    ///
+    /// # Arguments
+    /// * `provider` - The OAuth2 provider whose token endpoint to replace.
+    /// * `token_url` - The token endpoint URL to use instead of the provider's.
+    ///
+    /// # Returns
+    /// The updated [`OAuth2Manager`].
+    pub fn with_token_url(
+        mut self,
+        provider: OAuth2Provider,
+        token_url: impl Into<String>,
+    ) -> Self {
+        self.token_urls.insert(provider, token_url.into());
+        self
+    }
+
+    /// Replaces the user info endpoint of `provider`, e.g. to go through a proxy or a test
+    /// server.
+    ///
+    /// # Arguments
+    /// * `provider` - The OAuth2 provider whose user info endpoint to replace.
+    /// * `user_info_url` - The user info endpoint URL to use instead of the provider's.
+    ///
+    /// # Returns
+    /// The updated [`OAuth2Manager`].
+    pub fn with_user_info_url(
+        mut self,
+        provider: OAuth2Provider,
+        user_info_url: impl Into<String>,
+    ) -> Self {
+        self.user_info_urls.insert(provider, user_info_url.into());
+        self
+    }
+
+    /// Replaces caller-managed `state` parameters with signed, time-limited ones.
+    ///
+    /// [`OAuth2Service::generate_auth_url`] then sends a state signed for the provider that
+    /// embeds the caller's state as nonce, and [`OAuth2Service::exchange_code_for_token`]
+    /// verifies it before contacting the provider, so no server-side storage of issued states
+    /// is needed. Without it, the caller is responsible for tracking and checking states.
+    /// Signed states are not tied to the browser: to prevent login CSRF, the caller still
+    /// checks that the nonce matches one stored for the browser (see the
+    /// [`state`](super::state) module).
+    ///
+    /// # Arguments
+    /// * `signed_state` - The signer to use.
+    ///
+    /// # Returns
+    /// The updated [`OAuth2Manager`].
+    pub fn with_signed_state(mut self, signed_state: SignedState) -> Self {
+        self.signed_state = Some(signed_state);
+        self
+    }
+
+    /// Fails calls to a provider fast after consecutive failures, instead of waiting for each
+    /// call to an unavailable provider to fail (see [`CircuitBreaker`]).
+    ///
+    /// Token exchanges, token refreshes and user info requests are guarded and counted.
+    ///
+    /// # Arguments
+    /// * `config` - When circuits open, and for how long.
+    ///
+    /// # Returns
+    /// The updated [`OAuth2Manager`].
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.breaker = Some(CircuitBreaker::new(config));
+        self
+    }
+
+    /// Returns the circuit breaker of provider calls, if enabled.
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.breaker.as_ref()
+    }
+
+    /// Fails fast if the circuit of `provider` is open.
+    fn check_circuit(&self, provider: OAuth2Provider) -> Result<(), AuthError> {
+        match &self.breaker {
+            Some(breaker) => breaker.check(provider),
+            None => Ok(()),
+        }
+    }
+
+    /// Records the outcome of a call to `provider` in the circuit breaker, and returns it.
+    fn record_outcome<T>(
+        &self,
+        provider: OAuth2Provider,
+        outcome: Result<T, AuthError>,
+    ) -> Result<T, AuthError> {
+        if let Some(breaker) = &self.breaker {
+            breaker.record(provider, &outcome);
+        }
+        outcome
+    }
+
+    /// Returns the token endpoint used for `provider`.
+    fn token_url(&self, provider: OAuth2Provider, config: &OAuth2Config) -> String {
+        self.token_urls
+            .get(&provider)
+            .cloned()
+            .unwrap_or_else(|| config.token_url(provider).to_string())
+    }
+
+    /// Returns the user info endpoint used for `provider`.
+    fn user_info_url(&self, provider: OAuth2Provider, config: &OAuth2Config) -> String {
+        self.user_info_urls
+            .get(&provider)
+            .cloned()
+            .unwrap_or_else(|| config.user_info_url(provider).to_string())
+    }
+
+    /// Converts a failed token request into an [`AuthError`].
+    ///
+    /// Standard OAuth2 error bodies (`error`, `error_description`, `error_uri`) become
+    /// [`AuthError::OAuthProviderError`], including those sent with a success status (as
+    /// GitHub does), which the OAuth2 client fails to parse as tokens. Non-JSON responses (e.g.,
+    /// the HTML page of a rate limiter) become [`AuthError::OAuthNetwork`], quoting the start
+    /// of the body when the client kept it. Other failures become
+    /// [`AuthError::OAuthTokenExchange`] prefixed with `context`.
+    fn token_request_error<RE: std::error::Error + 'static>(
+        error: RequestTokenError<RE, StandardErrorResponse<BasicErrorResponseType>>,
+        context: &str,
+    ) -> AuthError {
+        let response = match &error {
+            RequestTokenError::ServerResponse(response) => Some(response.clone()),
+            RequestTokenError::Parse(_, body) => {
+                if let Some(description) = non_json_response(None, body) {
+                    return AuthError::OAuthNetwork(format!("{context}: {description}"));
+                }
+                serde_json::from_slice(body).ok()
+            }
+            // Raised by the OAuth2 client for success responses that are not JSON
+            RequestTokenError::Other(message)
+                if message.starts_with("unexpected response Content-Type") =>
+            {
+                return AuthError::OAuthNetwork(format!(
+                    "{context}: provider returned a non-JSON response ({message})"
+                ));
+            }
+            _ => None,
+        };
+        match response {
+            Some(response) => AuthError::OAuthProviderError {
+                code: response.error().as_ref().to_string(),
+                description: response.error_description().cloned(),
+                uri: response.error_uri().cloned(),
+            },
+            None => AuthError::OAuthTokenExchange(format!("{context}: {error}")),
+        }
     }
 
     /// Returns a configured HTTP client for the given provider with the appropriate User-Agent.
@@ -161,7 +330,7 @@ impl OAuth2Manager {
             AuthError::ConfigError(format!("Invalid auth URL: {e}"))
         })?;
 
-        let token_url = TokenUrl::new(config.token_url(provider).to_string()).map_err(|e| {
+        let token_url = TokenUrl::new(self.token_url(provider, config)).map_err(|e| {
             debug!("Invalid token URL for provider {provider:?}: {e}");
             AuthError::ConfigError(format!("Invalid token URL: {e}"))
         })?;
@@ -176,7 +345,7 @@ impl OAuth2Manager {
         debug!("Client ID: {}", config.client_id);
         debug!("Redirect URI: {}", config.redirect_callback_uri);
         debug!("Auth URL: {}", config.auth_url(provider));
-        debug!("Token URL: {}", config.token_url(provider));
+        debug!("Token URL: {}", self.token_url(provider, config));
         let client = BasicClient::new(ClientId::new(config.client_id.clone()))
             .set_client_secret(ClientSecret::new(config.client_secret.clone()))
             .set_auth_uri(auth_url)
@@ -186,10 +355,49 @@ impl OAuth2Manager {
         Ok(client)
     }
 
+    /// Returns the value at `path` in a user info response.
+    ///
+    /// The path is a field name, or a dot-separated path for nested fields (e.g. `user.id`).
+    fn lookup_path<'a>(response_body: &'a Value, path: &str) -> Option<&'a Value> {
+        path.split('.')
+            .try_fold(response_body, |value, key| value.get(key))
+    }
+
+    /// Returns the first non-empty user ID found among `candidates` in a user info response.
+    ///
+    /// Candidates are field names, or dot-separated paths for nested fields (e.g. `user.id`).
+    /// String and integer values are accepted, since providers change representations across
+    /// API versions.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::OAuthInvalidResponse`] listing the candidates and the raw response
+    /// if none of them holds an ID.
+    fn extract_provider_user_id(
+        provider: OAuth2Provider,
+        response_body: &Value,
+        candidates: &[&str],
+    ) -> Result<String, AuthError> {
+        candidates
+            .iter()
+            .find_map(|path| match Self::lookup_path(response_body, path)? {
+                Value::String(id) if !id.is_empty() => Some(id.clone()),
+                Value::Number(id) => Some(id.to_string()),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                AuthError::OAuthInvalidResponse(format!(
+                    "Missing user ID in {provider} user info (tried {}): {response_body}",
+                    candidates.join(", ")
+                ))
+            })
+    }
+
     /// Parses user information from the provider's user info response.
     ///
     /// This method extracts and normalizes user profile data from the JSON response returned by the provider's user info endpoint.
     /// The parsing logic is provider-specific and handles differences in field names and formats.
+    /// Fields absent from the response are then looked up through the provider's
+    /// [`OAuth2Config::field_map`], if any.
     ///
     /// # Arguments
     /// * `provider` - The OAuth2 provider whose response is being parsed.
@@ -208,10 +416,18 @@ impl OAuth2Manager {
         response_body: Value,
     ) -> Result<OAuth2UserInfo, AuthError> {
         let now = chrono::Utc::now().naive_utc();
+        let field_map = self.configs.get(&provider).map(|config| &config.field_map);
+        let id_candidates = |builtin: &[&'static str]| -> Vec<&str> {
+            builtin
+                .iter()
+                .copied()
+                .chain(field_map.and_then(|map| map.get("id")).map(String::as_str))
+                .collect()
+        };
 
         debug!("Parsing user info for provider: {provider:?}");
 
-        match provider {
+        let mut user_info = match provider {
             OAuth2Provider::Google => {
                 debug!("Google user info response: {response_body:?}");
                 let email = response_body["email"].as_str().map(|s| s.to_string());
@@ -219,10 +435,11 @@ impl OAuth2Manager {
                 let avatar_url = response_body["picture"].as_str().map(|s| s.to_string());
                 let verified_email = response_body["verified_email"].as_bool();
                 let locale = response_body["locale"].as_str().map(|s| s.to_string());
-                let provider_user_id = response_body["id"]
-                    .as_str()
-                    .ok_or_else(|| AuthError::OAuthInvalidResponse("Missing user ID".to_string()))?
-                    .to_string();
+                let provider_user_id = Self::extract_provider_user_id(
+                    provider,
+                    &response_body,
+                    &id_candidates(&["id", "sub"]),
+                )?;
 
                 Ok(OAuth2UserInfo {
                     user_id: String::new(), // Will be set when linking to cryptic user
@@ -235,16 +452,18 @@ impl OAuth2Manager {
                     locale,
                     updated_at: now,
                     raw_data: Some(response_body),
+                    granted_scopes: Vec::new(),
                 })
             }
             OAuth2Provider::GitHub => {
                 debug!("GitHub user info response: {response_body:?}");
                 let name = response_body["name"].as_str().map(|s| s.to_string());
                 let avatar_url = response_body["avatar_url"].as_str().map(|s| s.to_string());
-                let provider_user_id = response_body["id"]
-                    .as_u64()
-                    .ok_or_else(|| AuthError::OAuthInvalidResponse("Missing user ID".to_string()))?
-                    .to_string();
+                let provider_user_id = Self::extract_provider_user_id(
+                    provider,
+                    &response_body,
+                    &id_candidates(&["id", "node_id"]),
+                )?;
 
                 // GitHub requires a separate API call for email
                 let email = if let Some(email_str) = response_body["email"].as_str() {
@@ -268,6 +487,7 @@ impl OAuth2Manager {
                     locale: None,
                     updated_at: now,
                     raw_data: Some(response_body),
+                    granted_scopes: Vec::new(),
                 })
             }
             OAuth2Provider::Discord => {
@@ -275,10 +495,11 @@ impl OAuth2Manager {
                 let email = response_body["email"].as_str().map(|s| s.to_string());
                 let name = response_body["username"].as_str().map(|s| s.to_string());
                 let avatar = response_body["avatar"].as_str();
-                let provider_user_id = response_body["id"]
-                    .as_str()
-                    .ok_or_else(|| AuthError::OAuthInvalidResponse("Missing user ID".to_string()))?
-                    .to_string();
+                let provider_user_id = Self::extract_provider_user_id(
+                    provider,
+                    &response_body,
+                    &id_candidates(&["id", "user.id"]),
+                )?;
 
                 let avatar_url = avatar.map(|avatar_hash| {
                     format!(
@@ -300,6 +521,7 @@ impl OAuth2Manager {
                     locale,
                     updated_at: now,
                     raw_data: Some(response_body),
+                    granted_scopes: Vec::new(),
                 })
             }
             OAuth2Provider::Microsoft => {
@@ -309,10 +531,11 @@ impl OAuth2Manager {
                     .or_else(|| response_body["userPrincipalName"].as_str())
                     .map(|s| s.to_string());
                 let name = response_body["displayName"].as_str().map(|s| s.to_string());
-                let provider_user_id = response_body["id"]
-                    .as_str()
-                    .ok_or_else(|| AuthError::OAuthInvalidResponse("Missing user ID".to_string()))?
-                    .to_string();
+                let provider_user_id = Self::extract_provider_user_id(
+                    provider,
+                    &response_body,
+                    &id_candidates(&["id", "oid", "sub"]),
+                )?;
 
                 Ok(OAuth2UserInfo {
                     user_id: String::new(), // Will be set when linking to cryptic user
@@ -325,9 +548,33 @@ impl OAuth2Manager {
                     locale: None,
                     updated_at: now,
                     raw_data: Some(response_body),
+                    granted_scopes: Vec::new(),
                 })
             }
+        }?;
+
+        if let Some(field_map) = field_map.filter(|map| !map.is_empty())
+            && let Some(raw_data) = &user_info.raw_data
+        {
+            let mapped = |field: &str| {
+                field_map
+                    .get(field)
+                    .and_then(|path| Self::lookup_path(raw_data, path))
+                    .and_then(Value::as_str)
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string)
+            };
+            if user_info.email.is_none() {
+                user_info.email = mapped("email");
+            }
+            if user_info.name.is_none() {
+                user_info.name = mapped("name");
+            }
+            if user_info.avatar_url.is_none() {
+                user_info.avatar_url = mapped("avatar");
+            }
         }
+        Ok(user_info)
     }
 }
 
@@ -338,7 +585,7 @@ impl OAuth2Service for OAuth2Manager {
     /// # Arguments
     ///
     /// * `provider` - The OAuth2 provider.
-    /// * `state` - CSRF state parameter.
+    /// * `state` - CSRF state parameter, embedded in a signed state if signed states are enabled.
     /// * `scopes` - Optional additional scopes to request.
     ///
     /// # Returns
@@ -358,11 +605,7 @@ impl OAuth2Service for OAuth2Manager {
 
         let config = self.configs.get(&provider).unwrap();
         // Dans ton code Rust, assure-toi de dédupliquer les scopes
-        let mut all_scopes = provider
-            .default_scopes()
-            .into_iter()
-            .map(|s| s.to_string())
-            .collect::<Vec<_>>();
+        let mut all_scopes = config.default_scopes_for(provider);
 
         if let Some(additional_scopes) = scopes {
             all_scopes.extend(additional_scopes);
@@ -374,7 +617,11 @@ impl OAuth2Service for OAuth2Manager {
         all_scopes.dedup();
 
         debug!("Final scopes for auth URL: {all_scopes:?}");
-        let mut auth_request = client.authorize_url(|| CsrfToken::new(state.to_string()));
+        let state = match &self.signed_state {
+            Some(signed_state) => signed_state.sign(provider, state)?,
+            None => state.to_string(),
+        };
+        let mut auth_request = client.authorize_url(|| CsrfToken::new(state));
 
         for scope in all_scopes {
             auth_request = auth_request.add_scope(Scope::new(scope));
@@ -391,22 +638,29 @@ impl OAuth2Service for OAuth2Manager {
     ///
     /// * `provider` - The OAuth2 provider.
     /// * `code` - The authorization code received from the provider.
-    /// * `_state` - The CSRF state parameter (unused).
+    /// * `state` - The CSRF state parameter, verified if signed states are enabled.
     ///
     /// # Returns
     ///
-    /// Returns an [`OAuth2Token`] on success, or [`AuthError`] on failure.
+    /// Returns an [`OAuth2Token`] on success, [`AuthError::OAuthStateMismatch`] if signed
+    /// states are enabled and `state` is invalid or expired, [`AuthError::OAuthProviderError`]
+    /// if the provider rejected the code with a standard OAuth2 error, or another
+    /// [`AuthError`] on failure.
     async fn exchange_code_for_token(
         &self,
         provider: OAuth2Provider,
         code: &str,
-        _state: &str,
+        state: &str,
     ) -> Result<OAuth2Token, AuthError> {
         info!("Exchanging code for token for provider: {:?}", provider);
+        if let Some(signed_state) = &self.signed_state {
+            signed_state.verify(provider, state)?;
+        }
         debug!("Authorization code: {}", code);
         let client = self.get_client(provider)?;
         let http_client = self.get_http_client(provider)?;
 
+        self.check_circuit(provider)?;
         let token_result = client
             .exchange_code(AuthorizationCode::new(code.to_string()))
             .request_async(&http_client) // Utilise le client HTTP configuré avec le bon User-Agent
@@ -414,8 +668,9 @@ impl OAuth2Service for OAuth2Manager {
             .map_err(|e| {
                 debug!("Token exchange failed for provider {provider:?}: {e}");
                 debug!("Full error details: {e:?}");
-                AuthError::OAuthTokenExchange(format!("Token exchange failed: {e}"))
-            })?;
+                Self::token_request_error(e, "Token exchange failed")
+            });
+        let token_result = self.record_outcome(provider, token_result)?;
 
         let access_token = token_result.access_token().secret().clone();
         let refresh_token = token_result.refresh_token().map(|rt| rt.secret().clone());
@@ -453,7 +708,9 @@ impl OAuth2Service for OAuth2Manager {
     ///
     /// # Returns
     ///
-    /// Returns [`OAuth2UserInfo`] on success, or [`AuthError`] on failure.
+    /// Returns [`OAuth2UserInfo`] on success, [`AuthError::OAuthNetwork`] quoting the start of
+    /// the body if the provider answered with a non-JSON page (e.g., during an outage), or
+    /// another [`AuthError`] on failure.
     async fn fetch_user_info(&self, token: &OAuth2Token) -> Result<OAuth2UserInfo, AuthError> {
         info!("Fetching user info for provider: {:?}", token.provider);
         debug!("Access token: {}", token.access_token);
@@ -465,10 +722,11 @@ impl OAuth2Service for OAuth2Manager {
             ))
         })?;
 
-        let user_info_url = config.user_info_url(token.provider);
+        let user_info_url = self.user_info_url(token.provider, config);
         debug!("User info URL: {}", user_info_url);
         let http_client = self.get_http_client(token.provider)?;
 
+        self.check_circuit(token.provider)?;
         let response = http_client
             .get(user_info_url)
             .bearer_auth(&token.access_token)
@@ -481,26 +739,52 @@ impl OAuth2Service for OAuth2Manager {
                     token.provider, e
                 );
                 AuthError::OAuthNetwork(format!("Failed to fetch user info: {e}"))
-            })?;
+            });
+        let response = self.record_outcome(token.provider, response)?;
 
-        if !response.status().is_success() {
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response.bytes().await.map_err(|e| {
+            AuthError::OAuthNetwork(format!("Failed to read the user info response: {e}"))
+        });
+        let body = self.record_outcome(token.provider, body)?;
+
+        // Error pages of proxies and maintenance modes are HTML, not OAuth2 errors
+        if let Some(description) = non_json_response(content_type.as_deref(), &body) {
             debug!(
-                "User info request failed with status: {}",
-                response.status()
+                "Non-JSON user info response for provider {:?} with status {status}",
+                token.provider
             );
+            let error = Err(AuthError::OAuthNetwork(format!(
+                "User info request failed with status {status}: {description}"
+            )));
+            return self.record_outcome(token.provider, error);
+        }
+
+        if !status.is_success() {
+            debug!("User info request failed with status: {}", status);
+            if let Some(breaker) = &self.breaker
+                && status.is_server_error()
+            {
+                breaker.record_failure(token.provider);
+            }
             return Err(AuthError::OAuthUserInfo(format!(
-                "User info request failed with status: {}",
-                response.status()
+                "User info request failed with status: {status}"
             )));
         }
 
-        let response_body: Value = response.json().await.map_err(|e| {
+        let response_body = serde_json::from_slice::<Value>(&body).map_err(|e| {
             debug!(
                 "Invalid JSON response for provider {:?}: {}",
                 token.provider, e
             );
             AuthError::OAuthInvalidResponse(format!("Invalid JSON response: {e}"))
-        })?;
+        });
+        let response_body = self.record_outcome(token.provider, response_body)?;
 
         self.parse_user_info(token.provider, response_body).await
     }
@@ -513,7 +797,9 @@ impl OAuth2Service for OAuth2Manager {
     ///
     /// # Returns
     ///
-    /// Returns a new [`OAuth2Token`] on success, or [`AuthError`] on failure.
+    /// Returns a new [`OAuth2Token`] on success, [`AuthError::OAuthProviderError`] if the
+    /// provider rejected the refresh token with a standard OAuth2 error (typically
+    /// `invalid_grant`), or another [`AuthError`] on failure.
     async fn refresh_token(&self, token: &OAuth2Token) -> Result<OAuth2Token, AuthError> {
         info!("Refreshing token for provider: {:?}", token.provider);
         debug!("Current refresh token: {:?}", token.refresh_token);
@@ -528,6 +814,7 @@ impl OAuth2Service for OAuth2Manager {
             AuthError::OAuthTokenExchange("No refresh token available".to_string())
         })?;
 
+        self.check_circuit(token.provider)?;
         let token_result = client
             .exchange_refresh_token(&RefreshToken::new(refresh_token.clone()))
             .request_async(&http_client) // Utilise le client HTTP configuré avec le bon User-Agent
@@ -537,8 +824,9 @@ impl OAuth2Service for OAuth2Manager {
                     "Token refresh failed for provider {:?}: {}",
                     token.provider, e
                 );
-                AuthError::OAuthTokenExchange(format!("Token refresh failed: {e}"))
-            })?;
+                Self::token_request_error(e, "Token refresh failed")
+            });
+        let token_result = self.record_outcome(token.provider, token_result)?;
 
         let access_token = token_result.access_token().secret().clone();
         let new_refresh_token = token_result
@@ -583,9 +871,72 @@ impl OAuth2Service for OAuth2Manager {
     ) -> Result<String, AuthError> {
         self.get_redirect_frontend_uri(provider)
     }
+
+    fn get_config(&self, provider: OAuth2Provider) -> Option<&OAuth2Config> {
+        self.configs.get(&provider)
+    }
+
+    fn validate_configs(&self) -> Result<(), Vec<(OAuth2Provider, AuthError)>> {
+        self.validate_configs()
+    }
 }
 
 impl OAuth2Manager {
+    /// Validates every configured provider by building its OAuth2 and HTTP clients
+    /// and parsing its URLs.
+    ///
+    /// # Returns
+    /// Returns `Ok(())` if every configuration is usable, or every misconfigured provider
+    /// paired with an [`AuthError::ConfigError`] describing the problem.
+    ///
+    /// # Example
+    /// ```rust
+    /// if let Err(problems) = manager.validate_configs() {
+    ///     for (provider, error) in problems {
+    ///         eprintln!("{provider}: {error}");
+    ///     }
+    /// }
+    /// ```
+    pub fn validate_configs(&self) -> Result<(), Vec<(OAuth2Provider, AuthError)>> {
+        let problems: Vec<(OAuth2Provider, AuthError)> = OAuth2Provider::all()
+            .iter()
+            .filter(|provider| self.configs.contains_key(provider))
+            .filter_map(|&provider| {
+                self.validate_config(provider)
+                    .err()
+                    .map(|error| (provider, error))
+            })
+            .collect();
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Validates a single provider configuration.
+    fn validate_config(&self, provider: OAuth2Provider) -> Result<(), AuthError> {
+        let config = self.configs.get(&provider).ok_or_else(|| {
+            AuthError::ConfigError(format!("No config found for provider: {provider:?}"))
+        })?;
+
+        if config.client_id.trim().is_empty() {
+            return Err(AuthError::ConfigError("Missing client ID".to_string()));
+        }
+        if config.client_secret.trim().is_empty() {
+            return Err(AuthError::ConfigError("Missing client secret".to_string()));
+        }
+
+        self.get_client(provider)?;
+        self.get_http_client(provider)?;
+
+        oauth2::url::Url::parse(&config.redirect_frontend_uri)
+            .map_err(|e| AuthError::ConfigError(format!("Invalid frontend redirect URL: {e}")))?;
+
+        Ok(())
+    }
+
     /// Gets the frontend redirect URI for the given provider.
     ///
     /// This URI is typically used to redirect users back to the frontend application after completing the OAuth2 flow.
@@ -629,6 +980,41 @@ impl OAuth2Manager {
     }
 }
 
+/// The number of characters of a non-JSON response body quoted in errors.
+const BODY_SNIPPET_LEN: usize = 200;
+
+/// Describes a provider response that is not JSON, such as the HTML page of a rate limiter or
+/// a maintenance mode, quoting the start of its body.
+///
+/// A response is not JSON if its `Content-Type` is neither JSON nor missing, or if its body
+/// starts like markup.
+///
+/// # Arguments
+/// * `content_type` - The `Content-Type` header of the response, if known.
+/// * `body` - The response body.
+///
+/// # Returns
+/// The description, or `None` if the response looks like JSON.
+fn non_json_response(content_type: Option<&str>, body: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(body);
+    let text = text.trim();
+    let declared_json =
+        content_type.is_none_or(|content_type| content_type.to_ascii_lowercase().contains("json"));
+    if declared_json && !text.starts_with('<') {
+        return None;
+    }
+
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut snippet: String = collapsed.chars().take(BODY_SNIPPET_LEN).collect();
+    if collapsed.chars().count() > BODY_SNIPPET_LEN {
+        snippet.push('…');
+    }
+    Some(format!(
+        "provider returned a non-JSON response ({}): {snippet}",
+        content_type.unwrap_or("no Content-Type")
+    ))
+}
+
 /// Provides a default empty [`OAuth2Manager`] with no provider configurations.
 ///
 /// This implementation is useful for testing or initializing the manager before loading provider configs.
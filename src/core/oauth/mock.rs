@@ -0,0 +1,267 @@
+//! In-memory OAuth2 service for testing.
+//!
+//! This module provides [`MockOAuth2Service`], an [`OAuth2Service`] implementation with
+//! programmable responses, so that code depending on OAuth2 (login handlers, account linking)
+//! can be tested without contacting real providers. It is only available with the
+//! `test-util` feature.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use narangcia_cryptic::core::oauth::mock::{MockOAuth2Operation, MockOAuth2Service};
+//!
+//! let mock = MockOAuth2Service::new()
+//!     .with_user(OAuth2Provider::GitHub, "valid-code", user_info)
+//!     .with_error(MockOAuth2Operation::RefreshToken, AuthError::OAuthProvider("revoked".into()));
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use super::OAuth2Service;
+use super::store::{OAuth2Provider, OAuth2Token, OAuth2UserInfo};
+use crate::AuthError;
+
+/// The operations of [`MockOAuth2Service`] that can be made to fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MockOAuth2Operation {
+    /// [`OAuth2Service::generate_auth_url`].
+    GenerateAuthUrl,
+    /// [`OAuth2Service::exchange_code_for_token`].
+    ExchangeCode,
+    /// [`OAuth2Service::fetch_user_info`].
+    FetchUserInfo,
+    /// [`OAuth2Service::refresh_token`].
+    RefreshToken,
+}
+
+/// An [`OAuth2Service`] returning preset responses instead of contacting providers.
+///
+/// - Authorization codes are registered with [`MockOAuth2Service::with_user`]; exchanging an
+///   unknown code fails with [`AuthError::OAuthTokenExchange`].
+/// - Issued tokens expire after the configured lifetime (one hour by default).
+/// - Errors injected with [`MockOAuth2Service::with_error`] are returned by the next call of the
+///   corresponding operation only.
+pub struct MockOAuth2Service {
+    /// User info returned for each `(provider, code)` pair.
+    users: Mutex<HashMap<(OAuth2Provider, String), OAuth2UserInfo>>,
+    /// User info associated with each issued access token.
+    issued: Mutex<HashMap<String, OAuth2UserInfo>>,
+    /// Errors returned by the next call of an operation.
+    errors: Mutex<HashMap<MockOAuth2Operation, AuthError>>,
+    /// Lifetime of issued tokens, in seconds (negative for already expired tokens).
+    token_lifetime_secs: i64,
+    /// Frontend redirect URI returned for every provider.
+    redirect_frontend_uri: String,
+    /// Providers whose logins require a verified email.
+    verified_email_required: HashSet<OAuth2Provider>,
+    /// Scope reported as granted on issued tokens.
+    granted_scope: Option<String>,
+}
+
+impl Default for MockOAuth2Service {
+    fn default() -> Self {
+        Self {
+            users: Mutex::new(HashMap::new()),
+            issued: Mutex::new(HashMap::new()),
+            errors: Mutex::new(HashMap::new()),
+            token_lifetime_secs: 3600,
+            redirect_frontend_uri: "http://localhost/auth/callback".to_string(),
+            verified_email_required: HashSet::new(),
+            granted_scope: None,
+        }
+    }
+}
+
+impl MockOAuth2Service {
+    /// Creates a mock with no registered codes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the user info returned when `code` is exchanged for `provider`.
+    ///
+    /// # Arguments
+    /// * `provider` - The provider the code belongs to.
+    /// * `code` - The authorization code.
+    /// * `user_info` - The user info returned for tokens obtained with the code.
+    pub fn with_user(
+        self,
+        provider: OAuth2Provider,
+        code: impl Into<String>,
+        mut user_info: OAuth2UserInfo,
+    ) -> Self {
+        user_info.provider = provider;
+        if let Ok(mut users) = self.users.lock() {
+            users.insert((provider, code.into()), user_info);
+        }
+        self
+    }
+
+    /// Sets the lifetime of issued tokens, in seconds. Negative values issue expired tokens.
+    pub fn with_token_lifetime(mut self, secs: i64) -> Self {
+        self.token_lifetime_secs = secs;
+        self
+    }
+
+    /// Sets the frontend redirect URI returned for every provider.
+    pub fn with_redirect_frontend_uri(mut self, uri: impl Into<String>) -> Self {
+        self.redirect_frontend_uri = uri.into();
+        self
+    }
+
+    /// Requires a verified email for logins through `provider`.
+    pub fn with_required_verified_email(mut self, provider: OAuth2Provider) -> Self {
+        self.verified_email_required.insert(provider);
+        self
+    }
+
+    /// Sets the scope reported as granted on issued tokens (none by default).
+    pub fn with_granted_scope(mut self, scope: impl Into<String>) -> Self {
+        self.granted_scope = Some(scope.into());
+        self
+    }
+
+    /// Makes the next call of `operation` fail with `error`.
+    pub fn with_error(self, operation: MockOAuth2Operation, error: AuthError) -> Self {
+        self.fail_next(operation, error);
+        self
+    }
+
+    /// Makes the next call of `operation` fail with `error`, on an already shared mock.
+    pub fn fail_next(&self, operation: MockOAuth2Operation, error: AuthError) {
+        if let Ok(mut errors) = self.errors.lock() {
+            errors.insert(operation, error);
+        }
+    }
+
+    /// Returns the injected error for `operation`, if any, removing it.
+    fn take_error(&self, operation: MockOAuth2Operation) -> Result<(), AuthError> {
+        let mut errors = self
+            .errors
+            .lock()
+            .map_err(|e| AuthError::OAuthOther(e.to_string()))?;
+        match errors.remove(&operation) {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Issues a token for `user_info` and remembers the association.
+    fn issue_token(
+        &self,
+        provider: OAuth2Provider,
+        user_info: OAuth2UserInfo,
+    ) -> Result<OAuth2Token, AuthError> {
+        let now = chrono::Utc::now().naive_utc();
+        let access_token = format!(
+            "mock-access-{}",
+            crate::core::rand::secure_random_string(16)
+        );
+        let refresh_token = format!(
+            "mock-refresh-{}",
+            crate::core::rand::secure_random_string(16)
+        );
+        self.issued
+            .lock()
+            .map_err(|e| AuthError::OAuthOther(e.to_string()))?
+            .insert(access_token.clone(), user_info.clone());
+        self.issued
+            .lock()
+            .map_err(|e| AuthError::OAuthOther(e.to_string()))?
+            .insert(refresh_token.clone(), user_info);
+
+        Ok(OAuth2Token {
+            access_token,
+            refresh_token: Some(refresh_token),
+            expires_at: Some(now + chrono::Duration::seconds(self.token_lifetime_secs)),
+            token_type: "Bearer".to_string(),
+            scope: self.granted_scope.clone(),
+            provider,
+            created_at: now,
+        })
+    }
+
+    /// Returns the user info associated with an issued token.
+    fn issued_user(&self, token: &str) -> Result<OAuth2UserInfo, AuthError> {
+        self.issued
+            .lock()
+            .map_err(|e| AuthError::OAuthOther(e.to_string()))?
+            .get(token)
+            .cloned()
+            .ok_or_else(|| AuthError::OAuthUserInfo("Unknown mock token".to_string()))
+    }
+}
+
+#[async_trait]
+impl OAuth2Service for MockOAuth2Service {
+    async fn generate_auth_url(
+        &self,
+        provider: OAuth2Provider,
+        state: &str,
+        scopes: Option<Vec<String>>,
+    ) -> Result<String, AuthError> {
+        self.take_error(MockOAuth2Operation::GenerateAuthUrl)?;
+        let scopes = scopes.unwrap_or_default().join(" ");
+        Ok(format!(
+            "https://mock.oauth.invalid/{provider}/authorize?state={state}&scope={scopes}"
+        ))
+    }
+
+    async fn exchange_code_for_token(
+        &self,
+        provider: OAuth2Provider,
+        code: &str,
+        _state: &str,
+    ) -> Result<OAuth2Token, AuthError> {
+        self.take_error(MockOAuth2Operation::ExchangeCode)?;
+        let user_info = self
+            .users
+            .lock()
+            .map_err(|e| AuthError::OAuthOther(e.to_string()))?
+            .get(&(provider, code.to_string()))
+            .cloned()
+            .ok_or_else(|| {
+                AuthError::OAuthTokenExchange(format!("Unknown mock code for {provider}"))
+            })?;
+        self.issue_token(provider, user_info)
+    }
+
+    async fn fetch_user_info(&self, token: &OAuth2Token) -> Result<OAuth2UserInfo, AuthError> {
+        self.take_error(MockOAuth2Operation::FetchUserInfo)?;
+        // The provider enforces the exact expiration, without the client's safety margin
+        if token.is_expired_with_margin(0) {
+            return Err(AuthError::OAuthUserInfo("Mock token expired".to_string()));
+        }
+        let mut user_info = self.issued_user(&token.access_token)?;
+        user_info.updated_at = chrono::Utc::now().naive_utc();
+        Ok(user_info)
+    }
+
+    async fn refresh_token(&self, token: &OAuth2Token) -> Result<OAuth2Token, AuthError> {
+        self.take_error(MockOAuth2Operation::RefreshToken)?;
+        let refresh_token = token
+            .refresh_token
+            .as_deref()
+            .ok_or_else(|| AuthError::OAuthTokenExchange("Missing refresh token".to_string()))?;
+        let user_info = self.issued_user(refresh_token)?;
+        self.issue_token(token.provider, user_info)
+    }
+
+    async fn get_redirect_frontend_uri(
+        &self,
+        _provider: OAuth2Provider,
+    ) -> Result<String, AuthError> {
+        Ok(self.redirect_frontend_uri.clone())
+    }
+
+    fn is_provider_configured(&self, _provider: OAuth2Provider) -> bool {
+        true
+    }
+
+    fn requires_verified_email(&self, provider: OAuth2Provider) -> bool {
+        self.verified_email_required.contains(&provider)
+    }
+}
@@ -6,7 +6,14 @@
 //!
 //! # Modules
 //!
-//! - `manager`: Contains the logic for managing OAuth2 operations and provider-specific details.
+//! - `breaker`: Fails calls to unavailable providers fast, with a circuit breaker per provider.
+//! - `github_app`: Issues installation tokens for GitHub Apps (requires the `github-app` feature).
+//! - `link`: Stores OAuth2 accounts waiting for the account owner to confirm their link.
+//! - `manager`: Contains the logic for managing OAuth2 operations and provider-specific details
+//!   (requires the `oauth` feature, enabled by default).
+//! - `mock`: An in-memory [`OAuth2Service`] with programmable responses (requires the `test-util` feature).
+//! - `scope`: Lists the scopes of each provider as typed enums.
+//! - `state`: Signs and verifies stateless OAuth2 `state` parameters.
 //! - `store`: Defines types and storage mechanisms for OAuth2 tokens, user info, and providers.
 //!
 //! # Traits
@@ -39,6 +46,8 @@ pub trait OAuth2Service {
     /// * `provider` - The OAuth2 provider for which to generate the authorization URL.
     /// * `state` - A unique state string for CSRF protection.
     /// * `scopes` - Optional list of additional scopes to request beyond the provider's defaults.
+    ///   Typed scopes of the [`scope`] module convert with `.into()` (e.g.,
+    ///   `GitHubScope::ReadOrg.into()`); raw strings are accepted for any other scope.
     ///
     /// # Returns
     ///
@@ -112,7 +121,8 @@ pub trait OAuth2Service {
     /// Gets the redirect_frontend_uri for the given OAuth2 provider.
     ///
     /// This method returns the frontend redirect URI that should be used to redirect
-    /// users back to the frontend application after OAuth2 completion.
+    /// users back to the frontend application after OAuth2 completion. The default
+    /// implementation reads it from [`OAuth2Service::get_config`].
     ///
     /// # Arguments
     ///
@@ -125,11 +135,156 @@ pub trait OAuth2Service {
     async fn get_redirect_frontend_uri(
         &self,
         provider: store::OAuth2Provider,
-    ) -> Result<String, crate::AuthError>;
+    ) -> Result<String, crate::AuthError> {
+        self.get_config(provider)
+            .map(|config| config.redirect_frontend_uri.clone())
+            .ok_or_else(|| {
+                crate::AuthError::ConfigError(format!("No config found for provider: {provider:?}"))
+            })
+    }
+
+    /// Returns the configuration of the given provider, if this service holds one.
+    ///
+    /// Lets callers inspect provider settings (client ID, redirect URIs, scopes) through a
+    /// `dyn OAuth2Service` without knowing the concrete service. The default implementation
+    /// holds no configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - The OAuth2 provider whose configuration to return.
+    fn get_config(&self, provider: store::OAuth2Provider) -> Option<&store::OAuth2Config> {
+        let _ = provider;
+        None
+    }
+
+    /// Returns every provider configured in this service, in declaration order.
+    ///
+    /// The default implementation filters [`OAuth2Provider::all`](store::OAuth2Provider::all)
+    /// with [`OAuth2Service::is_provider_configured`].
+    fn list_configured_providers(&self) -> Vec<store::OAuth2Provider> {
+        store::OAuth2Provider::all()
+            .iter()
+            .copied()
+            .filter(|provider| self.is_provider_configured(*provider))
+            .collect()
+    }
+
+    /// Returns whether the given provider is configured in this service.
+    ///
+    /// The default implementation reports the providers [`OAuth2Service::get_config`] has a
+    /// configuration for.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - The OAuth2 provider to check.
+    fn is_provider_configured(&self, provider: store::OAuth2Provider) -> bool {
+        self.get_config(provider).is_some()
+    }
+
+    /// Returns whether logins through the given provider require a verified email.
+    ///
+    /// When it does, logins whose user info does not report the email as verified are
+    /// rejected. The default implementation reads
+    /// [`OAuth2Config::require_verified_email`](store::OAuth2Config::require_verified_email)
+    /// from [`OAuth2Service::get_config`], and requires no verification without a configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - The OAuth2 provider to check.
+    fn requires_verified_email(&self, provider: store::OAuth2Provider) -> bool {
+        self.get_config(provider)
+            .is_some_and(|config| config.require_verified_email)
+    }
+
+    /// Validates every configured provider without contacting it.
+    ///
+    /// Intended to be called at startup so that misconfigurations surface before the
+    /// first login attempt. The default implementation reports no problems.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If every provider configuration is usable.
+    /// * `Err(Vec<(OAuth2Provider, AuthError)>)` - Every misconfigured provider with its error.
+    fn validate_configs(&self) -> Result<(), Vec<(store::OAuth2Provider, crate::AuthError)>> {
+        Ok(())
+    }
 }
 
+/// An [`OAuth2Service`] without providers, used by [`AuthService`](crate::AuthService) when the
+/// `oauth` feature is disabled.
+///
+/// Every provider call fails with [`AuthError::ConfigError`](crate::AuthError::ConfigError).
+#[cfg(not(feature = "oauth"))]
+pub(crate) struct DisabledOAuth2Service;
+
+#[cfg(not(feature = "oauth"))]
+impl DisabledOAuth2Service {
+    fn disabled(provider: store::OAuth2Provider) -> crate::AuthError {
+        crate::AuthError::ConfigError(format!(
+            "OAuth2 support is disabled, enable the `oauth` feature to use provider: {provider:?}"
+        ))
+    }
+}
+
+#[cfg(not(feature = "oauth"))]
+#[async_trait]
+impl OAuth2Service for DisabledOAuth2Service {
+    async fn generate_auth_url(
+        &self,
+        provider: store::OAuth2Provider,
+        _state: &str,
+        _scopes: Option<Vec<String>>,
+    ) -> Result<String, crate::AuthError> {
+        Err(Self::disabled(provider))
+    }
+
+    async fn exchange_code_for_token(
+        &self,
+        provider: store::OAuth2Provider,
+        _code: &str,
+        _state: &str,
+    ) -> Result<store::OAuth2Token, crate::AuthError> {
+        Err(Self::disabled(provider))
+    }
+
+    async fn fetch_user_info(
+        &self,
+        token: &store::OAuth2Token,
+    ) -> Result<store::OAuth2UserInfo, crate::AuthError> {
+        Err(Self::disabled(token.provider))
+    }
+
+    async fn refresh_token(
+        &self,
+        token: &store::OAuth2Token,
+    ) -> Result<store::OAuth2Token, crate::AuthError> {
+        Err(Self::disabled(token.provider))
+    }
+}
+
+/// OAuth2 breaker module: per-provider circuit breaking of provider calls.
+pub mod breaker;
+
+/// GitHub App module: issues and caches installation access tokens.
+#[cfg(feature = "github-app")]
+pub mod github_app;
+
+/// OAuth2 link module: stores pending account links awaiting confirmation.
+pub mod link;
+
 /// OAuth2 manager module: contains logic for managing provider-specific operations.
+#[cfg(feature = "oauth")]
 pub mod manager;
 
+/// OAuth2 mock module: an in-memory service with programmable responses for tests.
+#[cfg(feature = "test-util")]
+pub mod mock;
+
+/// OAuth2 scope module: typed scopes of each supported provider.
+pub mod scope;
+
+/// OAuth2 state module: signs and verifies stateless, time-limited state parameters.
+pub mod state;
+
 /// OAuth2 store module: defines types and storage for tokens, user info, and providers.
 pub mod store;
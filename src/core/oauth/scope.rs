@@ -0,0 +1,160 @@
+//! Typed OAuth2 scopes for the supported providers.
+//!
+//! Scopes are plain strings on the wire, and a typo (`user_email` instead of `user:email`)
+//! only shows up as a missing permission at runtime. This module provides one enum per
+//! provider listing its commonly requested scopes. Each converts to the scope string with
+//! `to_str()` or `String::from`, so callers can request extra scopes type-safely:
+//!
+//! ```rust
+//! use narangcia_cryptic::core::oauth::scope::GitHubScope;
+//!
+//! let scopes: Vec<String> = vec![GitHubScope::ReadOrg.into(), GitHubScope::PublicRepo.into()];
+//! assert_eq!(scopes, ["read:org", "public_repo"]);
+//! ```
+//!
+//! Scopes missing from the enums can still be passed as raw strings to
+//! [`OAuth2Service::generate_auth_url`](crate::core::oauth::OAuth2Service::generate_auth_url)
+//! and [`OAuth2Config::additional_scopes`](crate::core::oauth::store::OAuth2Config::additional_scopes).
+
+/// Implements `Display` and the conversion to `String` of a scope enum through its `to_str`.
+macro_rules! scope_conversions {
+    ($scope:ty) => {
+        impl std::fmt::Display for $scope {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.to_str())
+            }
+        }
+
+        impl From<$scope> for String {
+            fn from(scope: $scope) -> Self {
+                scope.to_str().to_string()
+            }
+        }
+    };
+}
+
+/// Scopes of the Google OAuth2 provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GoogleScope {
+    /// Authenticates the user with OpenID Connect (`openid`).
+    OpenId,
+    /// Reads the user's primary email address (`email`).
+    Email,
+    /// Reads the user's basic profile (`profile`).
+    Profile,
+    /// Reads the user's calendars.
+    CalendarReadonly,
+    /// Reads the user's Drive files.
+    DriveReadonly,
+}
+
+impl GoogleScope {
+    /// Returns the scope string sent to Google.
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Self::OpenId => "openid",
+            Self::Email => "email",
+            Self::Profile => "profile",
+            Self::CalendarReadonly => "https://www.googleapis.com/auth/calendar.readonly",
+            Self::DriveReadonly => "https://www.googleapis.com/auth/drive.readonly",
+        }
+    }
+}
+
+scope_conversions!(GoogleScope);
+
+/// Scopes of the GitHub OAuth2 provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GitHubScope {
+    /// Reads the user's email addresses (`user:email`).
+    UserEmail,
+    /// Reads the user's profile data (`read:user`).
+    ReadUser,
+    /// Reads and writes the user's profile data (`user`).
+    User,
+    /// Reads and writes public repositories (`public_repo`).
+    PublicRepo,
+    /// Reads and writes public and private repositories (`repo`).
+    Repo,
+    /// Reads organization and team membership (`read:org`).
+    ReadOrg,
+    /// Writes gists (`gist`).
+    Gist,
+}
+
+impl GitHubScope {
+    /// Returns the scope string sent to GitHub.
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Self::UserEmail => "user:email",
+            Self::ReadUser => "read:user",
+            Self::User => "user",
+            Self::PublicRepo => "public_repo",
+            Self::Repo => "repo",
+            Self::ReadOrg => "read:org",
+            Self::Gist => "gist",
+        }
+    }
+}
+
+scope_conversions!(GitHubScope);
+
+/// Scopes of the Discord OAuth2 provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiscordScope {
+    /// Reads the user's profile, without their email (`identify`).
+    Identify,
+    /// Reads the user's email address (`email`).
+    Email,
+    /// Lists the user's guilds (`guilds`).
+    Guilds,
+    /// Adds the user to guilds (`guilds.join`).
+    GuildsJoin,
+    /// Reads the user's linked third-party accounts (`connections`).
+    Connections,
+}
+
+impl DiscordScope {
+    /// Returns the scope string sent to Discord.
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Self::Identify => "identify",
+            Self::Email => "email",
+            Self::Guilds => "guilds",
+            Self::GuildsJoin => "guilds.join",
+            Self::Connections => "connections",
+        }
+    }
+}
+
+scope_conversions!(DiscordScope);
+
+/// Scopes of the Microsoft identity platform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MicrosoftScope {
+    /// Authenticates the user with OpenID Connect (`openid`).
+    OpenId,
+    /// Reads the user's primary email address (`email`).
+    Email,
+    /// Reads the user's basic profile (`profile`).
+    Profile,
+    /// Issues refresh tokens (`offline_access`).
+    OfflineAccess,
+    /// Reads the user's profile through Microsoft Graph (`User.Read`).
+    UserRead,
+}
+
+impl MicrosoftScope {
+    /// Returns the scope string sent to Microsoft.
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Self::OpenId => "openid",
+            Self::Email => "email",
+            Self::Profile => "profile",
+            Self::OfflineAccess => "offline_access",
+            Self::UserRead => "User.Read",
+        }
+    }
+}
+
+scope_conversions!(MicrosoftScope);
@@ -0,0 +1,157 @@
+//! Stateless, signed OAuth2 `state` parameters.
+//!
+//! The `state` parameter protects the OAuth2 callback against CSRF only if the callback
+//! accepts the state issued to the browser that started the flow. By default, tracking issued
+//! states is left to the caller (e.g., in a session or a store). Horizontally scaled servers
+//! without shared storage can use [`SignedState`] instead: the state carries its own nonce,
+//! issue time and an HMAC-SHA256 over them and the provider, so any instance sharing the secret
+//! verifies it without a lookup.
+//!
+//! A signed state only proves that this service issued it recently for the provider. It is not
+//! tied to a browser: an attacker can request a valid state and send the victim a callback link
+//! carrying it and the attacker's authorization code (login CSRF). To bind the flow to the
+//! browser, pass a random nonce that is also stored in a cookie (or the session) of the browser
+//! when signing, and check that the nonce returned by [`SignedState::verify`] matches it on the
+//! callback.
+//!
+//! A signed state has the form `<nonce>.<issued_at>.<signature>`, where the nonce is the state
+//! given by the caller (or a random one) and the signature is URL-safe base64.
+
+use std::time::Duration;
+
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+
+use super::store::OAuth2Provider;
+use crate::error::AuthError;
+
+/// Default lifetime (in seconds) of a signed state: the time the user has to authorize the
+/// application at the provider.
+pub const DEFAULT_STATE_TTL: u64 = 10 * 60;
+
+/// Number of random bytes of the nonces generated for empty caller states.
+const STATE_NONCE_BYTES: usize = 16;
+
+/// Signs and verifies time-limited OAuth2 `state` parameters.
+///
+/// Enabled on an [`OAuth2Manager`](super::manager::OAuth2Manager) with
+/// [`with_signed_state`](super::manager::OAuth2Manager::with_signed_state), or through
+/// [`AuthServiceVariables::oauth_state_secret`](crate::core::vars::AuthServiceVariables::oauth_state_secret).
+#[derive(Clone)]
+pub struct SignedState {
+    /// The key signing states.
+    encoding_key: EncodingKey,
+    /// The key verifying states.
+    decoding_key: DecodingKey,
+    /// How long a state stays valid after being issued.
+    ttl: Duration,
+}
+
+impl std::fmt::Debug for SignedState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignedState")
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SignedState {
+    /// Creates a signer from an HMAC secret.
+    ///
+    /// # Arguments
+    /// * `secret` - The HMAC secret, shared by every instance verifying the states.
+    /// * `ttl` - How long a state stays valid after being issued.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::ConfigError`] if the secret is shorter than
+    /// [`MIN_HMAC_SECRET_LEN`](crate::core::token::jwt::MIN_HMAC_SECRET_LEN) bytes.
+    pub fn new(secret: &[u8], ttl: Duration) -> Result<Self, AuthError> {
+        let min_len = crate::core::token::jwt::MIN_HMAC_SECRET_LEN;
+        if secret.len() < min_len {
+            return Err(AuthError::ConfigError(format!(
+                "OAuth state secret must be at least {min_len} bytes long"
+            )));
+        }
+        Ok(Self {
+            encoding_key: EncodingKey::from_secret(secret),
+            decoding_key: DecodingKey::from_secret(secret),
+            ttl,
+        })
+    }
+
+    /// Returns a signed state for `provider`, embedding `nonce`.
+    ///
+    /// # Arguments
+    /// * `provider` - The provider the state is issued for.
+    /// * `nonce` - The caller's state value. An empty value is replaced with a random nonce.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::OAuthOther`] if signing fails.
+    pub fn sign(&self, provider: OAuth2Provider, nonce: &str) -> Result<String, AuthError> {
+        let nonce = if nonce.is_empty() {
+            crate::core::rand::secure_random_string(STATE_NONCE_BYTES)
+        } else {
+            nonce.to_string()
+        };
+        let issued_at = chrono::Utc::now().timestamp().max(0);
+        let signature = jsonwebtoken::crypto::sign(
+            Self::message(provider, &nonce, issued_at).as_bytes(),
+            &self.encoding_key,
+            Algorithm::HS256,
+        )
+        .map_err(|e| AuthError::OAuthOther(format!("Failed to sign OAuth state: {e}")))?;
+        Ok(format!("{nonce}.{issued_at}.{signature}"))
+    }
+
+    /// Checks that `state` was signed for `provider` and has not expired.
+    ///
+    /// The check does not tie the state to the browser presenting it: compare the returned
+    /// nonce with the one stored for the browser when the state was signed.
+    ///
+    /// # Arguments
+    /// * `provider` - The provider redirecting back with the state.
+    /// * `state` - The state received on the callback.
+    ///
+    /// # Returns
+    /// The nonce embedded in the state.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::OAuthStateMismatch`] if the state is malformed, tampered with,
+    /// issued for another provider, issued in the future or expired.
+    pub fn verify<'s>(
+        &self,
+        provider: OAuth2Provider,
+        state: &'s str,
+    ) -> Result<&'s str, AuthError> {
+        let mut parts = state.rsplitn(3, '.');
+        let (Some(signature), Some(issued_at), Some(nonce)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(AuthError::OAuthStateMismatch);
+        };
+        let issued_at: i64 = issued_at
+            .parse()
+            .map_err(|_| AuthError::OAuthStateMismatch)?;
+
+        let valid = jsonwebtoken::crypto::verify(
+            signature,
+            Self::message(provider, nonce, issued_at).as_bytes(),
+            &self.decoding_key,
+            Algorithm::HS256,
+        )
+        .unwrap_or(false);
+        if !valid {
+            return Err(AuthError::OAuthStateMismatch);
+        }
+
+        let age = chrono::Utc::now().timestamp() - issued_at;
+        if age < 0 || age as u64 >= self.ttl.as_secs() {
+            return Err(AuthError::OAuthStateMismatch);
+        }
+        Ok(nonce)
+    }
+
+    /// Returns the signed message for a state.
+    fn message(provider: OAuth2Provider, nonce: &str, issued_at: i64) -> String {
+        format!("{provider}\n{nonce}\n{issued_at}")
+    }
+}
@@ -25,7 +25,10 @@
 //!     client_secret: "your-client-secret".to_string(),
 //!     redirect_uri: "https://api.myapp.com/oauth/google/callback".to_string(),
 //!     redirect_frontend_uri: "https://myapp.com/auth/callback".to_string(),
+//!     default_scopes: None,
 //!     additional_scopes: vec!["profile".to_string()],
+//!     require_verified_email: true,
+//!     field_map: HashMap::new(),
 //! };
 //! ```
 //!
@@ -55,6 +58,9 @@
 
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::scope::{DiscordScope, GitHubScope, GoogleScope, MicrosoftScope};
 
 /// Supported OAuth2 providers for authentication.
 ///
@@ -74,6 +80,36 @@ pub enum OAuth2Provider {
 }
 
 impl OAuth2Provider {
+    /// Returns every supported provider, in declaration order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// assert_eq!(OAuth2Provider::all().len(), 4);
+    /// ```
+    pub fn all() -> &'static [OAuth2Provider] {
+        &[Self::Google, Self::GitHub, Self::Discord, Self::Microsoft]
+    }
+
+    /// Returns the stable lowercase identifier of the provider.
+    ///
+    /// This is the same representation used by serde, [`std::fmt::Display`] and
+    /// [`std::str::FromStr`], and is suitable for persisting the provider.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// assert_eq!(OAuth2Provider::GitHub.as_str(), "github");
+    /// ```
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Google => "google",
+            Self::GitHub => "github",
+            Self::Discord => "discord",
+            Self::Microsoft => "microsoft",
+        }
+    }
+
     /// Returns the display name of the provider as a human-readable string.
     ///
     /// # Examples
@@ -93,6 +129,10 @@ impl OAuth2Provider {
 
     /// Returns the default OAuth2 scopes required for the provider.
     ///
+    /// They are always requested by the [`OAuth2Manager`](crate::core::oauth::manager::OAuth2Manager),
+    /// and are listed as typed scopes in the [`scope`](crate::core::oauth::scope) module (e.g.,
+    /// [`GoogleScope::Email`]).
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -101,14 +141,80 @@ impl OAuth2Provider {
     /// ```
     pub fn default_scopes(&self) -> Vec<&'static str> {
         match self {
-            Self::Google => vec!["openid", "email", "profile"],
-            Self::GitHub => vec!["user:email"],
-            Self::Discord => vec!["identify", "email"],
-            Self::Microsoft => vec!["openid", "email", "profile"],
+            Self::Google => [
+                GoogleScope::OpenId,
+                GoogleScope::Email,
+                GoogleScope::Profile,
+            ]
+            .iter()
+            .map(GoogleScope::to_str)
+            .collect(),
+            Self::GitHub => vec![GitHubScope::UserEmail.to_str()],
+            Self::Discord => vec![
+                DiscordScope::Identify.to_str(),
+                DiscordScope::Email.to_str(),
+            ],
+            Self::Microsoft => [
+                MicrosoftScope::OpenId,
+                MicrosoftScope::Email,
+                MicrosoftScope::Profile,
+            ]
+            .iter()
+            .map(MicrosoftScope::to_str)
+            .collect(),
         }
     }
 }
 
+impl std::fmt::Display for OAuth2Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for OAuth2Provider {
+    type Err = crate::error::AuthError;
+
+    /// Parses a provider from its identifier, ignoring ASCII case.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::InvalidInput`](crate::error::AuthError::InvalidInput) for unknown providers.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|provider| provider.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                crate::error::AuthError::InvalidInput(format!("Unsupported OAuth2 provider: {s}"))
+            })
+    }
+}
+
+/// Describes an OAuth2 provider for building login UIs.
+///
+/// Returned by [`AuthService::available_oauth_providers`](crate::AuthService::available_oauth_providers),
+/// so a frontend can render exactly the login buttons that will work.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderInfo {
+    /// The OAuth2 provider.
+    pub provider: OAuth2Provider,
+    /// The human-readable name of the provider.
+    pub display_name: String,
+    /// The default scopes requested from the provider.
+    pub default_scopes: Vec<String>,
+    /// Whether the provider is configured and can be used to log in.
+    pub available: bool,
+}
+
+/// Default safety margin (in seconds) before the expiration of an OAuth2 access token within
+/// which it is already treated as expired (see [`OAuth2Token::is_expired_with_margin`] and
+/// [`AuthServiceVariables::oauth_token_expiry_margin`](crate::core::vars::AuthServiceVariables::oauth_token_expiry_margin)).
+///
+/// [`OAuth2Token::expires_at`] is computed from `expires_in` when the token is received, so
+/// clock skew and request latency can make a token look valid slightly past its real expiry.
+pub const DEFAULT_OAUTH_EXPIRY_MARGIN: u64 = 30;
+
 /// Represents an OAuth2 token, including access and refresh tokens, expiration, and provider info.
 ///
 /// This struct holds all relevant information about an OAuth2 token issued by a provider,
@@ -132,20 +238,35 @@ pub struct OAuth2Token {
 }
 
 impl OAuth2Token {
-    /// Checks if the token is expired.
+    /// Checks if the token is expired, or expires within [`DEFAULT_OAUTH_EXPIRY_MARGIN`] seconds.
     ///
-    /// Returns `true` if the current time is past the expiration time, or `false` otherwise.
-    /// If `expires_at` is `None`, returns `false`.
+    /// The margin is fixed: deployments configuring
+    /// [`AuthServiceVariables::oauth_token_expiry_margin`](crate::core::vars::AuthServiceVariables::oauth_token_expiry_margin)
+    /// must call [`OAuth2Token::is_expired_with_margin`] with it instead, as
+    /// `AuthService::fresh_oauth2_token` does. If `expires_at` is `None`, returns `false`.
+    #[deprecated(
+        since = "0.3.0",
+        note = "ignores the configured margin; use `is_expired_with_margin` with `AuthServiceVariables::oauth_token_expiry_margin`"
+    )]
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_with_margin(DEFAULT_OAUTH_EXPIRY_MARGIN)
+    }
+
+    /// Checks if the token is expired, or expires within `margin_secs` seconds.
     ///
-    /// # Examples
+    /// Treating tokens as expired slightly early makes callers refresh them rather than send
+    /// a token the provider considers expired.
     ///
-    /// ```rust
-    /// let token = OAuth2Token { /* ... */ };
-    /// let expired = token.is_expired();
-    /// ```
-    pub fn is_expired(&self) -> bool {
+    /// # Arguments
+    ///
+    /// * `margin_secs` - The safety margin before the expiration, `0` for the exact expiration.
+    ///
+    /// Returns `true` if the current time plus the margin is past the expiration time, or
+    /// `false` otherwise. If `expires_at` is `None`, returns `false`.
+    pub fn is_expired_with_margin(&self, margin_secs: u64) -> bool {
+        let margin = chrono::Duration::seconds(i64::try_from(margin_secs).unwrap_or(i64::MAX));
         self.expires_at
-            .map(|exp| chrono::Utc::now().naive_utc() > exp)
+            .map(|exp| chrono::Utc::now().naive_utc() > exp - margin)
             .unwrap_or(false)
     }
 
@@ -173,6 +294,27 @@ impl OAuth2Token {
             })
             .unwrap_or(false)
     }
+
+    /// Returns the scopes granted by the provider, as listed in [`OAuth2Token::scope`].
+    ///
+    /// Providers separate scopes with spaces (RFC 6749) or, like GitHub, with commas; both
+    /// are accepted. Returns an empty list when the provider did not report scopes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let token = OAuth2Token { scope: Some("read:user,user:email".to_string()), /* ... */ };
+    /// assert_eq!(token.granted_scopes(), vec!["read:user", "user:email"]);
+    /// ```
+    pub fn granted_scopes(&self) -> Vec<String> {
+        self.scope
+            .as_deref()
+            .unwrap_or_default()
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|scope| !scope.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
 }
 
 /// Represents user information returned by an OAuth2 provider.
@@ -201,6 +343,9 @@ pub struct OAuth2UserInfo {
     /// The raw user info data as returned by the provider, if available.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub raw_data: Option<serde_json::Value>,
+    /// The scopes granted by the user at the last authorization, if reported by the provider.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub granted_scopes: Vec<String>,
 }
 
 /// Configuration for OAuth2 authentication with a provider.
@@ -224,11 +369,43 @@ pub struct OAuth2Config {
     /// with authentication tokens included in the URL fragment (e.g., `#access_token=...&refresh_token=...`).
     /// This should point to a frontend page that can handle token extraction from the URL fragment.
     pub redirect_frontend_uri: String,
+    /// Scopes replacing [`OAuth2Provider::default_scopes`], e.g. to request only `openid email`
+    /// from Google. `None` keeps the built-in defaults; [`Self::additional_scopes`] are
+    /// requested either way.
+    pub default_scopes: Option<Vec<String>>,
     /// Additional scopes to request during authentication.
     pub additional_scopes: Vec<String>,
+    /// Whether logins through this provider require the provider to report the account's
+    /// email as verified. Accounts without a verified flag (e.g., GitHub, Microsoft) are then
+    /// treated as unverified.
+    pub require_verified_email: bool,
+    /// Overrides locating user info fields in nonstandard provider responses.
+    ///
+    /// Maps `id`, `email`, `name` or `avatar` to the response field holding it, or to a
+    /// dot-separated path for nested fields (e.g. `profile.mail`). An override is only
+    /// consulted when the provider's built-in field is absent, so an empty map keeps the
+    /// built-in mapping.
+    pub field_map: HashMap<String, String>,
 }
 
 impl OAuth2Config {
+    /// Returns the default scopes requested from the given provider: the configured override,
+    /// or the provider's built-in defaults.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - The OAuth2 provider this configuration is for.
+    pub fn default_scopes_for(&self, provider: OAuth2Provider) -> Vec<String> {
+        match &self.default_scopes {
+            Some(scopes) => scopes.clone(),
+            None => provider
+                .default_scopes()
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
     /// Returns the authorization URL for the given provider.
     ///
     /// # Arguments
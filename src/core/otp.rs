@@ -0,0 +1,167 @@
+//! Email one-time password (OTP) storage.
+//!
+//! Passwordless login by email code works in two steps: a short numeric code is generated and
+//! sent to the user, then the user submits it back. Only a keyed tag of the code is stored,
+//! together with its expiration and the number of verification attempts, so that a leaked store
+//! does not reveal live codes and brute-forcing the small code space is bounded.
+//!
+//! Password reset tokens are stored the same way, under a key of their own (see
+//! `AuthService::request_password_reset`).
+//!
+//! This module provides the [`OtpStore`] trait and an in-memory default implementation.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::error::AuthError;
+
+/// Number of digits of generated email OTP codes.
+pub const OTP_CODE_LENGTH: usize = 6;
+
+/// Default lifetime (in seconds) of an email OTP code.
+pub const DEFAULT_OTP_TTL: u64 = 5 * 60;
+
+/// Default number of verification attempts allowed per email OTP code.
+pub const DEFAULT_OTP_MAX_ATTEMPTS: u32 = 5;
+
+/// Default lifetime (in seconds) of a password reset token.
+pub const DEFAULT_PASSWORD_RESET_TTL: u64 = 30 * 60;
+
+/// The [`OtpStore`] key of the pending password reset of a user, apart from their email OTP.
+pub(crate) fn password_reset_key(user_id: &str) -> String {
+    format!("reset:{user_id}")
+}
+
+/// A pending one-time password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OtpRecord {
+    /// The keyed HMAC-SHA256 tag of the code, base64-encoded.
+    pub code_hash: String,
+    /// The code's expiration (UNIX timestamp, seconds).
+    pub expires_at: usize,
+    /// The number of verification attempts made so far.
+    pub attempts: u32,
+}
+
+impl OtpRecord {
+    /// Returns whether the code has expired at `now` (UNIX timestamp, seconds).
+    pub fn is_expired(&self, now: usize) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// Stores pending one-time passwords, keyed by user ID.
+///
+/// Implementations must make [`OtpStore::record_attempt`] atomic, so that concurrent
+/// submissions cannot exceed the attempt limit.
+#[async_trait::async_trait]
+pub trait OtpStore: Send + Sync {
+    /// Stores `record` under `key`, replacing any pending code.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn put(&self, key: &str, record: OtpRecord) -> Result<(), AuthError>;
+
+    /// Returns the unexpired record stored under `key`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn get(&self, key: &str) -> Result<Option<OtpRecord>, AuthError>;
+
+    /// Increments the attempt counter of the unexpired record stored under `key`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(OtpRecord))` with the updated counter if a live record exists.
+    /// * `Ok(None)` if there is no pending code, or it has expired.
+    /// * `Err(AuthError)` if the store is unavailable.
+    async fn record_attempt(&self, key: &str) -> Result<Option<OtpRecord>, AuthError>;
+
+    /// Removes the record stored under `key`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn remove(&self, key: &str) -> Result<(), AuthError>;
+
+    /// Removes the records that expired, and returns how many were removed.
+    ///
+    /// Long-running in-memory stores call this periodically (see
+    /// `AuthService::spawn_maintenance`) so they do not grow without bound. Persistent
+    /// backends should rely on native expiration (e.g. TTLs) instead; the default
+    /// implementation does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn prune_expired(&self) -> Result<usize, AuthError> {
+        Ok(0)
+    }
+}
+
+/// In-memory implementation of [`OtpStore`].
+///
+/// Expired records are pruned lazily, or by [`OtpStore::prune_expired`]. Suitable for
+/// single-instance deployments and tests; multi-instance deployments need a shared store.
+#[derive(Debug, Default)]
+pub struct InMemoryOtpStore {
+    /// Pending codes mapped by key.
+    records: Mutex<HashMap<String, OtpRecord>>,
+}
+
+impl InMemoryOtpStore {
+    /// Creates a new, empty in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Locks the records, pruning the expired ones.
+    fn live_records(
+        &self,
+    ) -> Result<std::sync::MutexGuard<'_, HashMap<String, OtpRecord>>, AuthError> {
+        let now = chrono::Utc::now().timestamp().max(0) as usize;
+        let mut records = self
+            .records
+            .lock()
+            .map_err(|e| AuthError::ServiceUnavailable(e.to_string()))?;
+        records.retain(|_, record| !record.is_expired(now));
+        Ok(records)
+    }
+}
+
+#[async_trait::async_trait]
+impl OtpStore for InMemoryOtpStore {
+    async fn put(&self, key: &str, record: OtpRecord) -> Result<(), AuthError> {
+        self.live_records()?.insert(key.to_string(), record);
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<OtpRecord>, AuthError> {
+        Ok(self.live_records()?.get(key).cloned())
+    }
+
+    async fn record_attempt(&self, key: &str) -> Result<Option<OtpRecord>, AuthError> {
+        Ok(self.live_records()?.get_mut(key).map(|record| {
+            record.attempts = record.attempts.saturating_add(1);
+            record.clone()
+        }))
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), AuthError> {
+        self.live_records()?.remove(key);
+        Ok(())
+    }
+
+    async fn prune_expired(&self) -> Result<usize, AuthError> {
+        let now = chrono::Utc::now().timestamp().max(0) as usize;
+        let mut records = self
+            .records
+            .lock()
+            .map_err(|e| AuthError::ServiceUnavailable(e.to_string()))?;
+        let before = records.len();
+        records.retain(|_, record| !record.is_expired(now));
+        Ok(before - records.len())
+    }
+}
@@ -16,18 +16,278 @@
 //! # });
 //! ```
 
-use crate::core::hash::Argon2Hasher;
+use std::sync::Arc;
+#[cfg(feature = "tokio")]
+use std::time::Duration;
+
+use zeroize::Zeroizing;
+
+use crate::core::hash::{Argon2Hasher, Argon2Params};
 use crate::core::password::manager::SecurePasswordManager;
 use crate::error::AuthError;
 
+/// How [`Argon2PasswordManager::verify_password`] treats an empty password or hash.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EmptyInputBehavior {
+    /// Report the password as not matching (`Ok(false)`).
+    #[default]
+    Mismatch,
+    /// Fail with [`AuthError::InvalidPassword`], so that callers sending empty credentials
+    /// (usually a client bug) can be told apart from wrong passwords.
+    Reject,
+}
+
+/// How [`Argon2PasswordManager::verify_password`] treats a stored hash that is not an Argon2
+/// PHC string, such as a truncated or otherwise corrupted database row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MalformedHashBehavior {
+    /// Report the password as not matching (`Ok(false)`) and log a warning, so a single
+    /// corrupt row fails its own logins closed without surfacing as a server error.
+    #[default]
+    Mismatch,
+    /// Fail with [`AuthError::VerificationError`].
+    Reject,
+}
+
 /// A password manager that uses the Argon2 algorithm for hashing and verifying passwords.
 ///
 /// This struct wraps an [`Argon2Hasher`] and implements the [`SecurePasswordManager`] trait,
 /// providing asynchronous methods for password hashing and verification.
 #[derive(Default)]
 pub struct Argon2PasswordManager {
-    /// The Argon2 hasher instance used for password operations.
-    hasher: Argon2Hasher,
+    /// The Argon2 hasher instance used for password operations, shared with blocking tasks.
+    hasher: Arc<Argon2Hasher>,
+    /// How empty passwords or hashes are treated on verification.
+    empty_input: EmptyInputBehavior,
+    /// How malformed hashes are treated on verification.
+    malformed_hash: MalformedHashBehavior,
+    /// The maximum duration of a hash or verification, if set with
+    /// [`Argon2PasswordManager::with_timeout`].
+    #[cfg(feature = "tokio")]
+    timeout: Option<Duration>,
+}
+
+impl Argon2PasswordManager {
+    /// Creates a new [`Argon2PasswordManager`] using the given Argon2 cost parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The Argon2 cost parameters to use for hashing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::ConfigError`] if the parameters are rejected by Argon2.
+    pub fn with_params(params: Argon2Params) -> Result<Self, AuthError> {
+        let hasher = Argon2Hasher::with_params(params)
+            .map_err(|e| AuthError::ConfigError(format!("Invalid Argon2 parameters: {e}")))?;
+        Ok(Self {
+            hasher: Arc::new(hasher),
+            empty_input: EmptyInputBehavior::default(),
+            malformed_hash: MalformedHashBehavior::default(),
+            #[cfg(feature = "tokio")]
+            timeout: None,
+        })
+    }
+
+    /// Sets how an empty password or hash is treated on verification.
+    ///
+    /// The default, [`EmptyInputBehavior::Mismatch`], reports a non-matching password.
+    ///
+    /// # Arguments
+    ///
+    /// * `behavior` - The behavior to apply.
+    ///
+    /// # Returns
+    ///
+    /// The updated password manager.
+    pub fn with_empty_input_behavior(mut self, behavior: EmptyInputBehavior) -> Self {
+        self.empty_input = behavior;
+        self
+    }
+
+    /// Sets how a stored hash that is not an Argon2 PHC string is treated on verification.
+    ///
+    /// The default, [`MalformedHashBehavior::Mismatch`], reports a non-matching password.
+    ///
+    /// # Arguments
+    ///
+    /// * `behavior` - The behavior to apply.
+    ///
+    /// # Returns
+    ///
+    /// The updated password manager.
+    pub fn with_malformed_hash_behavior(mut self, behavior: MalformedHashBehavior) -> Self {
+        self.malformed_hash = behavior;
+        self
+    }
+
+    /// Hashes and verifies passwords with an Argon2 secret key.
+    ///
+    /// See [`Argon2Hasher::with_secret`].
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The server-held secret key.
+    ///
+    /// # Returns
+    ///
+    /// The updated password manager.
+    pub fn with_secret(mut self, key: Vec<u8>) -> Self {
+        let hasher = Arc::unwrap_or_clone(self.hasher).with_secret(key);
+        self.hasher = Arc::new(hasher);
+        self
+    }
+
+    /// Tags new hashes with the ID of the secret key.
+    ///
+    /// See [`Argon2Hasher::with_key_id`].
+    ///
+    /// # Arguments
+    ///
+    /// * `key_id` - The key ID, up to 8 bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::ConfigError`] if the ID is too long.
+    pub fn with_key_id(mut self, key_id: &[u8]) -> Result<Self, AuthError> {
+        let hasher = Arc::unwrap_or_clone(self.hasher)
+            .with_key_id(key_id)
+            .map_err(|e| AuthError::ConfigError(format!("Invalid key ID: {e}")))?;
+        self.hasher = Arc::new(hasher);
+        Ok(self)
+    }
+
+    /// Returns the key ID tagging new hashes, if set with
+    /// [`Argon2PasswordManager::with_key_id`].
+    pub fn key_id(&self) -> Option<&[u8]> {
+        self.hasher.key_id()
+    }
+
+    /// Reuses Argon2 working memory across password operations.
+    ///
+    /// See [`Argon2Hasher::with_buffer_pool`].
+    ///
+    /// # Arguments
+    ///
+    /// * `max_idle` - The maximum number of idle buffers to keep.
+    ///
+    /// # Returns
+    ///
+    /// The updated password manager.
+    pub fn with_buffer_pool(mut self, max_idle: usize) -> Self {
+        let hasher = Arc::unwrap_or_clone(self.hasher).with_buffer_pool(max_idle);
+        self.hasher = Arc::new(hasher);
+        self
+    }
+
+    /// Generates salts of `len` bytes for new hashes.
+    ///
+    /// See [`Argon2Hasher::with_salt_len`].
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The salt length in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::ConfigError`] if `len` is out of the range Argon2 accepts.
+    pub fn with_salt_len(mut self, len: usize) -> Result<Self, AuthError> {
+        let hasher = Arc::unwrap_or_clone(self.hasher)
+            .with_salt_len(len)
+            .map_err(|e| AuthError::ConfigError(format!("Invalid salt length: {e}")))?;
+        self.hasher = Arc::new(hasher);
+        Ok(self)
+    }
+
+    /// Fails hashes and verifications taking longer than `timeout`.
+    ///
+    /// A safety valve against Argon2 parameters too costly for the hardware: requests fail
+    /// fast with [`AuthError::HashingTimeout`] instead of hanging. The work runs on a tokio
+    /// blocking thread, which keeps running until the hash completes even after the timeout
+    /// fired. Only available with the `tokio` dependency (enabled by the `postgres` and `axum`
+    /// features); password operations outside a tokio runtime then fail with
+    /// [`AuthError::ConfigError`] rather than running without the timeout.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The maximum duration of a password operation.
+    ///
+    /// # Returns
+    ///
+    /// The updated password manager.
+    #[cfg(feature = "tokio")]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Runs `operation` on the hasher, within the configured timeout if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::HashingTimeout`] if the timeout elapsed,
+    /// [`AuthError::HashingError`] if the blocking task panicked, or
+    /// [`AuthError::ConfigError`] if a timeout is set but no tokio runtime is running.
+    async fn run<T>(
+        &self,
+        operation: impl FnOnce(&Argon2Hasher) -> T + Send + 'static,
+    ) -> Result<T, AuthError>
+    where
+        T: Send + 'static,
+    {
+        self.run_on(Arc::clone(&self.hasher), operation).await
+    }
+
+    /// Runs `operation` on `hasher`, within the configured timeout if any.
+    ///
+    /// # Errors
+    ///
+    /// See [`Argon2PasswordManager::run`].
+    async fn run_on<T>(
+        &self,
+        hasher: Arc<Argon2Hasher>,
+        operation: impl FnOnce(&Argon2Hasher) -> T + Send + 'static,
+    ) -> Result<T, AuthError>
+    where
+        T: Send + 'static,
+    {
+        #[cfg(feature = "tokio")]
+        if let Some(timeout) = self.timeout {
+            if tokio::runtime::Handle::try_current().is_err() {
+                return Err(AuthError::ConfigError(
+                    "The password hashing timeout requires a running tokio runtime".to_string(),
+                ));
+            }
+            let task = tokio::task::spawn_blocking(move || operation(&hasher));
+            return match tokio::time::timeout(timeout, task).await {
+                Ok(Ok(value)) => Ok(value),
+                Ok(Err(e)) => Err(AuthError::HashingError(format!("Hashing task failed: {e}"))),
+                Err(_) => {
+                    log::warn!("Password operation exceeded the {timeout:?} timeout");
+                    Err(AuthError::HashingTimeout)
+                }
+            };
+        }
+        Ok(operation(&hasher))
+    }
+
+    /// Returns the hasher for a user's target parameters: this manager's own hasher when
+    /// `target` is `None` or equal to its parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::ConfigError`] if the target parameters are rejected by Argon2.
+    fn hasher_for(&self, target: Option<&Argon2Params>) -> Result<Arc<Argon2Hasher>, AuthError> {
+        match target {
+            Some(target) if *target != self.hasher.params() => {
+                let hasher = self.hasher.with_target(*target).map_err(|e| {
+                    AuthError::ConfigError(format!("Invalid target Argon2 parameters: {e}"))
+                })?;
+                Ok(Arc::new(hasher))
+            }
+            _ => Ok(Arc::clone(&self.hasher)),
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -45,16 +305,33 @@ impl SecurePasswordManager for Argon2PasswordManager {
     ///
     /// # Errors
     ///
-    /// Returns [`AuthError::InvalidPassword`] if the password is empty, or [`AuthError::HashingError`] if hashing fails.
+    /// Returns [`AuthError::InvalidPassword`] if the password is empty, [`AuthError::HashingError`] if hashing fails,
+    /// or [`AuthError::HashingTimeout`] if it exceeds the configured timeout.
     async fn hash_password(&self, password: &str) -> Result<String, AuthError> {
+        self.hash_password_for(password, None).await
+    }
+
+    /// Hashes a password using the Argon2 algorithm and the user's target parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Argon2PasswordManager::hash_password`], or
+    /// [`AuthError::ConfigError`] if the target parameters are rejected by Argon2.
+    async fn hash_password_for(
+        &self,
+        password: &str,
+        target: Option<&Argon2Params>,
+    ) -> Result<String, AuthError> {
         if password.is_empty() {
             return Err(AuthError::InvalidPassword(
                 "Password cannot be empty".to_string(),
             ));
         }
+        let hasher = self.hasher_for(target)?;
+        let password = Zeroizing::new(password.as_bytes().to_vec());
         let hash = self
-            .hasher
-            .hash(password.as_bytes(), None)
+            .run_on(hasher, move |hasher| hasher.hash(&password, None))
+            .await?
             .map_err(|e| AuthError::HashingError(format!("Hashing error: {e}")))?;
         Ok(hash)
     }
@@ -69,24 +346,66 @@ impl SecurePasswordManager for Argon2PasswordManager {
     /// # Returns
     ///
     /// * `Ok(true)` if the password matches the hash.
-    /// * `Ok(false)` if the password or hash is empty, or if verification fails.
+    /// * `Ok(false)` if the password does not match, if the password or hash is empty with
+    ///   [`EmptyInputBehavior::Mismatch`], or if the hash is malformed with
+    ///   [`MalformedHashBehavior::Mismatch`].
     /// * `Err(AuthError)` if verification encounters an error.
     ///
     /// # Errors
     ///
-    /// Returns [`AuthError::VerificationError`] if verification fails due to an internal error.
+    /// Returns [`AuthError::InvalidPassword`] if the password or hash is empty with
+    /// [`EmptyInputBehavior::Reject`], [`AuthError::VerificationError`] if the hash is
+    /// malformed with [`MalformedHashBehavior::Reject`] or verification fails due to an
+    /// internal error, or [`AuthError::HashingTimeout`] if it exceeds the configured timeout.
     async fn verify_password(
         &self,
         password: &str,
         hashed_password: &str,
     ) -> Result<bool, AuthError> {
         if password.is_empty() || hashed_password.is_empty() {
-            return Ok(false);
+            return match self.empty_input {
+                EmptyInputBehavior::Mismatch => Ok(false),
+                EmptyInputBehavior::Reject => Err(AuthError::InvalidPassword(
+                    "Password and hash cannot be empty".to_string(),
+                )),
+            };
+        }
+        if !self.hasher.identify(hashed_password) {
+            return match self.malformed_hash {
+                MalformedHashBehavior::Mismatch => {
+                    log::warn!("Stored password hash is not an Argon2 PHC string");
+                    Ok(false)
+                }
+                MalformedHashBehavior::Reject => Err(AuthError::VerificationError(
+                    "Stored password hash is not an Argon2 PHC string".to_string(),
+                )),
+            };
         }
+        let password = Zeroizing::new(password.as_bytes().to_vec());
+        let hashed_password = hashed_password.to_string();
         let valid = self
-            .hasher
-            .verify(password.as_bytes(), hashed_password)
+            .run(move |hasher| hasher.verify(&password, &hashed_password))
+            .await?
             .map_err(|e| AuthError::VerificationError(format!("Verification error: {e}")))?;
         Ok(valid)
     }
+
+    /// Returns whether the hash is an Argon2 PHC string.
+    fn identify(&self, hashed_password: &str) -> bool {
+        self.hasher.identify(hashed_password)
+    }
+
+    /// Returns whether the hash uses different Argon2 parameters than this manager.
+    fn needs_rehash(&self, hashed_password: &str) -> bool {
+        self.hasher.needs_rehash(hashed_password)
+    }
+
+    /// Returns whether the hash uses different Argon2 parameters than the user's target, or
+    /// than this manager if the user has none.
+    fn needs_rehash_for(&self, hashed_password: &str, target: Option<&Argon2Params>) -> bool {
+        match target {
+            Some(target) => self.hasher.needs_rehash_for(hashed_password, *target),
+            None => self.hasher.needs_rehash(hashed_password),
+        }
+    }
 }
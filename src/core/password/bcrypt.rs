@@ -0,0 +1,79 @@
+//! Bcrypt password manager implementation.
+//!
+//! This module provides an implementation of the [`SecurePasswordManager`] trait using bcrypt.
+//! It is primarily intended for verifying legacy hashes while migrating users to Argon2, typically
+//! as a fallback inside a [`CompositePasswordManager`](crate::core::password::CompositePasswordManager).
+
+use crate::core::password::manager::SecurePasswordManager;
+use crate::error::AuthError;
+
+/// Hash prefixes produced by the different bcrypt revisions.
+const BCRYPT_PREFIXES: &[&str] = &["$2a$", "$2b$", "$2x$", "$2y$"];
+
+/// A password manager that uses the bcrypt algorithm for hashing and verifying passwords.
+pub struct BcryptPasswordManager {
+    /// The bcrypt cost factor used when hashing.
+    cost: u32,
+}
+
+impl Default for BcryptPasswordManager {
+    fn default() -> Self {
+        Self {
+            cost: bcrypt::DEFAULT_COST,
+        }
+    }
+}
+
+impl BcryptPasswordManager {
+    /// Creates a new [`BcryptPasswordManager`] using the given cost factor.
+    ///
+    /// # Arguments
+    ///
+    /// * `cost` - The bcrypt cost factor (between 4 and 31).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::ConfigError`] if the cost is outside the range supported by bcrypt.
+    pub fn with_cost(cost: u32) -> Result<Self, AuthError> {
+        if !(4..=31).contains(&cost) {
+            return Err(AuthError::ConfigError(format!(
+                "Invalid bcrypt cost {cost}: must be between 4 and 31"
+            )));
+        }
+        Ok(Self { cost })
+    }
+}
+
+#[async_trait::async_trait]
+impl SecurePasswordManager for BcryptPasswordManager {
+    /// Hashes a password using bcrypt.
+    async fn hash_password(&self, password: &str) -> Result<String, AuthError> {
+        bcrypt::hash(password, self.cost)
+            .map_err(|e| AuthError::HashingError(format!("Hashing error: {e}")))
+    }
+
+    /// Verifies a password against a bcrypt hash.
+    async fn verify_password(
+        &self,
+        password: &str,
+        hashed_password: &str,
+    ) -> Result<bool, AuthError> {
+        bcrypt::verify(password, hashed_password)
+            .map_err(|e| AuthError::VerificationError(format!("Verification error: {e}")))
+    }
+
+    /// Returns whether the hash uses one of the bcrypt `$2?$` prefixes.
+    fn identify(&self, hashed_password: &str) -> bool {
+        BCRYPT_PREFIXES
+            .iter()
+            .any(|prefix| hashed_password.starts_with(prefix))
+    }
+
+    /// Returns whether the hash was produced with a different cost than this manager's.
+    fn needs_rehash(&self, hashed_password: &str) -> bool {
+        hashed_password
+            .parse::<bcrypt::HashParts>()
+            .map(|parts| parts.get_cost() != self.cost)
+            .unwrap_or(true)
+    }
+}
@@ -0,0 +1,103 @@
+//! Composite password manager for algorithm migrations.
+//!
+//! This module provides [`CompositePasswordManager`], which hashes new passwords with a primary
+//! manager while still verifying hashes produced by legacy managers (e.g., bcrypt). Combined with
+//! [`SecurePasswordManager::needs_rehash`], it lets callers transparently upgrade stored hashes
+//! on the next successful login.
+
+use crate::core::hash::Argon2Params;
+use crate::core::password::manager::SecurePasswordManager;
+use crate::error::AuthError;
+
+/// A password manager that delegates to a primary manager and a list of legacy fallbacks.
+///
+/// - Hashing always uses the primary manager.
+/// - Verification uses the first manager (primary first, then legacy managers in order)
+///   whose [`SecurePasswordManager::identify`] accepts the stored hash.
+/// - A hash needs rehashing when the primary manager does not identify it, or when the
+///   primary manager reports that its parameters are outdated.
+pub struct CompositePasswordManager {
+    /// The manager used for hashing and for verifying current hashes.
+    primary: Box<dyn SecurePasswordManager + Send + Sync>,
+
+    /// Managers used only to verify hashes produced by older algorithms.
+    legacy: Vec<Box<dyn SecurePasswordManager + Send + Sync>>,
+}
+
+impl CompositePasswordManager {
+    /// Creates a new [`CompositePasswordManager`].
+    ///
+    /// # Arguments
+    ///
+    /// * `primary` - The manager used to hash new passwords.
+    /// * `legacy` - Managers used to verify hashes produced by older algorithms, tried in order.
+    pub fn new(
+        primary: Box<dyn SecurePasswordManager + Send + Sync>,
+        legacy: Vec<Box<dyn SecurePasswordManager + Send + Sync>>,
+    ) -> Self {
+        Self { primary, legacy }
+    }
+
+    /// Returns the manager able to verify the given hash, if any.
+    fn manager_for(
+        &self,
+        hashed_password: &str,
+    ) -> Option<&(dyn SecurePasswordManager + Send + Sync)> {
+        std::iter::once(&self.primary)
+            .chain(self.legacy.iter())
+            .find(|manager| manager.identify(hashed_password))
+            .map(|manager| manager.as_ref())
+    }
+}
+
+#[async_trait::async_trait]
+impl SecurePasswordManager for CompositePasswordManager {
+    /// Hashes a password using the primary manager.
+    async fn hash_password(&self, password: &str) -> Result<String, AuthError> {
+        self.primary.hash_password(password).await
+    }
+
+    /// Verifies a password using the manager that identifies the stored hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::VerificationError`] if no configured manager recognizes the hash format.
+    async fn verify_password(
+        &self,
+        password: &str,
+        hashed_password: &str,
+    ) -> Result<bool, AuthError> {
+        match self.manager_for(hashed_password) {
+            Some(manager) => manager.verify_password(password, hashed_password).await,
+            None => Err(AuthError::VerificationError(
+                "Unrecognized password hash format".to_string(),
+            )),
+        }
+    }
+
+    /// Returns whether any of the configured managers identifies the hash.
+    fn identify(&self, hashed_password: &str) -> bool {
+        self.manager_for(hashed_password).is_some()
+    }
+
+    /// Returns whether the hash should be recomputed with the primary manager.
+    fn needs_rehash(&self, hashed_password: &str) -> bool {
+        !self.primary.identify(hashed_password) || self.primary.needs_rehash(hashed_password)
+    }
+
+    /// Hashes a password using the primary manager and the user's target parameters.
+    async fn hash_password_for(
+        &self,
+        password: &str,
+        target: Option<&Argon2Params>,
+    ) -> Result<String, AuthError> {
+        self.primary.hash_password_for(password, target).await
+    }
+
+    /// Returns whether the hash should be recomputed with the primary manager and the user's
+    /// target parameters.
+    fn needs_rehash_for(&self, hashed_password: &str, target: Option<&Argon2Params>) -> bool {
+        !self.primary.identify(hashed_password)
+            || self.primary.needs_rehash_for(hashed_password, target)
+    }
+}
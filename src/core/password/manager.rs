@@ -25,6 +25,7 @@
 //! }
 //! ```
 
+use crate::core::hash::Argon2Params;
 use crate::error::AuthError;
 
 #[async_trait::async_trait]
@@ -76,4 +77,70 @@ pub trait SecurePasswordManager {
         password: &str,
         hashed_password: &str,
     ) -> Result<bool, AuthError>;
+
+    /// Returns whether the hash was produced by the algorithm this manager implements.
+    ///
+    /// Used to dispatch verification when several algorithms coexist (e.g., during a
+    /// bcrypt to Argon2 migration). The default implementation accepts every hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `hashed_password` - The stored password hash to inspect.
+    fn identify(&self, hashed_password: &str) -> bool {
+        let _ = hashed_password;
+        true
+    }
+
+    /// Returns whether the hash should be recomputed with the current algorithm and parameters.
+    ///
+    /// Callers typically rehash the password after a successful login when this returns `true`.
+    /// The default implementation never requests a rehash.
+    ///
+    /// # Arguments
+    ///
+    /// * `hashed_password` - The stored password hash to inspect.
+    fn needs_rehash(&self, hashed_password: &str) -> bool {
+        let _ = hashed_password;
+        false
+    }
+
+    /// Hashes a plaintext password with the cost parameters targeted for its user.
+    ///
+    /// Lets deployments hash segments of users with different costs (e.g., keeping a lower
+    /// cost for grandfathered accounts). `None` uses the manager's own parameters. The default
+    /// implementation ignores `target` and calls [`SecurePasswordManager::hash_password`].
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - The plaintext password to hash.
+    /// * `target` - The user's target parameters, if any (see
+    ///   [`User::hash_target`](crate::core::user::User::hash_target)).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` containing the hashed password if successful.
+    /// * `Err(AuthError)` if hashing fails.
+    async fn hash_password_for(
+        &self,
+        password: &str,
+        target: Option<&Argon2Params>,
+    ) -> Result<String, AuthError> {
+        let _ = target;
+        self.hash_password(password).await
+    }
+
+    /// Returns whether the hash should be recomputed to match the cost parameters targeted for
+    /// its user.
+    ///
+    /// `None` compares against the manager's own parameters. The default implementation
+    /// ignores `target` and calls [`SecurePasswordManager::needs_rehash`].
+    ///
+    /// # Arguments
+    ///
+    /// * `hashed_password` - The stored password hash to inspect.
+    /// * `target` - The user's target parameters, if any.
+    fn needs_rehash_for(&self, hashed_password: &str, target: Option<&Argon2Params>) -> bool {
+        let _ = target;
+        self.needs_rehash(hashed_password)
+    }
 }
@@ -0,0 +1,33 @@
+//! Password hash migration reporting.
+//!
+//! Stored hashes are upgraded lazily, on the next successful login of each user. This module
+//! provides [`MigrationReport`], returned by
+//! [`AuthService::scan_for_rehash`](crate::AuthService::scan_for_rehash), which describes how
+//! many stored hashes are still waiting for that upgrade.
+
+use crate::core::user::UserId;
+
+/// The state of stored password hashes relative to the current password manager.
+///
+/// # Fields
+///
+/// - `scanned`: The number of stored password hashes inspected.
+/// - `needs_rehash`: Users whose hash verifies but uses an outdated algorithm or parameters.
+/// - `unrecognized`: Users whose hash no configured algorithm can verify.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// The number of stored password hashes inspected.
+    pub scanned: usize,
+    /// Users whose hash will be upgraded on their next login.
+    pub needs_rehash: Vec<UserId>,
+    /// Users whose hash is not identified by the password manager, so they cannot log in
+    /// with their password until it is reset.
+    pub unrecognized: Vec<UserId>,
+}
+
+impl MigrationReport {
+    /// Returns whether every stored hash is up to date.
+    pub fn is_complete(&self) -> bool {
+        self.needs_rehash.is_empty() && self.unrecognized.is_empty()
+    }
+}
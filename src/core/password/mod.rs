@@ -6,11 +6,19 @@
 //! # Modules
 //!
 //! - [`argon2`]: Contains the Argon2 password hashing implementation and configuration.
+//! - [`bcrypt`]: Contains the bcrypt password manager used for legacy hashes (requires the `bcrypt` feature).
+//! - [`composite`]: Combines a primary manager with legacy fallbacks for hash migrations.
 //! - [`manager`]: Defines the `SecurePasswordManager` trait and related password management logic.
+//! - [`migration`]: Reports on stored hashes awaiting an upgrade to the current algorithm.
+//! - [`rotation`]: Verifies hashes produced with previous Argon2 secret keys during a rotation.
 //!
 //! # Re-exports
 //!
 //! - [`Argon2PasswordManager`]: A concrete password manager using Argon2 for hashing and verification.
+//! - [`BcryptPasswordManager`]: A password manager using bcrypt (requires the `bcrypt` feature).
+//! - [`CompositePasswordManager`]: A password manager dispatching verification across algorithms.
+//! - [`KeyRotatingPasswordManager`]: A password manager dispatching verification across secret keys.
+//! - [`MigrationReport`]: The result of scanning stored hashes for outdated ones.
 //! - [`SecurePasswordManager`]: The main trait for password management operations.
 //!
 //! # Example
@@ -30,10 +38,28 @@
 //! This module is designed to make it easy to follow best practices for password security.
 
 pub mod argon2;
+#[cfg(feature = "bcrypt")]
+pub mod bcrypt;
+pub mod composite;
 pub mod manager;
+pub mod migration;
+pub mod rotation;
 
 /// Re-export of the Argon2-based password manager implementation.
-pub use argon2::Argon2PasswordManager;
+pub use argon2::{Argon2PasswordManager, EmptyInputBehavior, MalformedHashBehavior};
+
+/// Re-export of the bcrypt-based password manager implementation.
+#[cfg(feature = "bcrypt")]
+pub use bcrypt::BcryptPasswordManager;
+
+/// Re-export of the composite password manager used for hash migrations.
+pub use composite::CompositePasswordManager;
+
+/// Re-export of the password manager used for secret key rotations.
+pub use rotation::KeyRotatingPasswordManager;
+
+/// Re-export of the hash migration report.
+pub use migration::MigrationReport;
 
 /// Re-export of the main password management trait.
 pub use manager::SecurePasswordManager;
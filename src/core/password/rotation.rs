@@ -0,0 +1,201 @@
+//! Password manager for Argon2 secret key rotations.
+//!
+//! Hashes produced with an Argon2 secret key (see
+//! [`Argon2PasswordManager::with_secret`]) only verify with that key, so rotating it must keep
+//! the old key around until every user logged in again. This module provides
+//! [`KeyRotatingPasswordManager`], which hashes new passwords with the new key while still
+//! verifying hashes produced with the old ones, and reports those hashes as needing a rehash.
+//! It is the secret key analog of the
+//! [`CompositePasswordManager`](crate::core::password::CompositePasswordManager) used for
+//! algorithm migrations.
+//!
+//! Hashes are matched to their key through the `keyid` parameter of their PHC string (see
+//! [`Argon2PasswordManager::with_key_id`]), so each key must have a distinct ID. Hashes without
+//! a `keyid` belong to the manager without one, typically the key used before the first
+//! rotation.
+
+use chrono::{DateTime, Utc};
+
+use crate::core::hash::{Argon2Hasher, Argon2Params};
+use crate::core::password::argon2::Argon2PasswordManager;
+use crate::core::password::manager::SecurePasswordManager;
+use crate::error::AuthError;
+
+/// A previous secret key, still accepted for verification.
+struct LegacyKey {
+    /// The manager holding the previous key.
+    manager: Argon2PasswordManager,
+    /// When hashes of this key stop verifying, if the grace window is limited.
+    retire_at: Option<DateTime<Utc>>,
+}
+
+/// A password manager hashing with a primary Argon2 secret key and verifying with previous ones.
+///
+/// - Hashing always uses the primary manager.
+/// - Verification uses the manager whose key ID matches the `keyid` of the stored hash,
+///   unless that key's grace window is over.
+/// - A hash needs rehashing when it was produced with a previous key, or when the primary
+///   manager reports that its parameters are outdated.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let manager = KeyRotatingPasswordManager::new(
+///     Argon2PasswordManager::default().with_secret(new_key).with_key_id(b"2025")?,
+/// )
+/// .with_legacy_until(
+///     Argon2PasswordManager::default().with_secret(old_key),
+///     Utc::now() + chrono::Duration::days(90),
+/// )?;
+/// ```
+pub struct KeyRotatingPasswordManager {
+    /// The manager holding the current key.
+    primary: Argon2PasswordManager,
+    /// The managers holding previous keys, used only to verify hashes.
+    legacy: Vec<LegacyKey>,
+}
+
+impl KeyRotatingPasswordManager {
+    /// Creates a new [`KeyRotatingPasswordManager`] without previous keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `primary` - The manager holding the current key, used to hash new passwords.
+    pub fn new(primary: Argon2PasswordManager) -> Self {
+        Self {
+            primary,
+            legacy: Vec::new(),
+        }
+    }
+
+    /// Keeps verifying hashes produced with a previous key, with no time limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `manager` - The manager holding the previous key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::ConfigError`] if another configured key has the same key ID.
+    pub fn with_legacy(self, manager: Argon2PasswordManager) -> Result<Self, AuthError> {
+        self.push_legacy(manager, None)
+    }
+
+    /// Keeps verifying hashes produced with a previous key until `retire_at`.
+    ///
+    /// Users who did not log in during the grace window can no longer log in with their
+    /// password, which must then be reset.
+    ///
+    /// # Arguments
+    ///
+    /// * `manager` - The manager holding the previous key.
+    /// * `retire_at` - When hashes of the previous key stop verifying.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::ConfigError`] if another configured key has the same key ID.
+    pub fn with_legacy_until(
+        self,
+        manager: Argon2PasswordManager,
+        retire_at: DateTime<Utc>,
+    ) -> Result<Self, AuthError> {
+        self.push_legacy(manager, Some(retire_at))
+    }
+
+    /// Adds a previous key, rejecting key IDs that would make hashes ambiguous.
+    fn push_legacy(
+        mut self,
+        manager: Argon2PasswordManager,
+        retire_at: Option<DateTime<Utc>>,
+    ) -> Result<Self, AuthError> {
+        let key_id = manager.key_id();
+        let taken = std::iter::once(&self.primary)
+            .chain(self.legacy.iter().map(|legacy| &legacy.manager))
+            .any(|other| other.key_id() == key_id);
+        if taken {
+            return Err(AuthError::ConfigError(format!(
+                "Argon2 key ID {:?} is used by several keys",
+                key_id.map(String::from_utf8_lossy)
+            )));
+        }
+        self.legacy.push(LegacyKey { manager, retire_at });
+        Ok(self)
+    }
+
+    /// Returns the manager able to verify the given hash, if its key is still accepted.
+    fn manager_for(&self, hashed_password: &str) -> Option<&Argon2PasswordManager> {
+        if !self.primary.identify(hashed_password) {
+            return None;
+        }
+        let key_id = Argon2Hasher::key_id_of(hashed_password);
+        if key_id.as_deref() == self.primary.key_id() {
+            return Some(&self.primary);
+        }
+        let now = Utc::now();
+        self.legacy
+            .iter()
+            .find(|legacy| {
+                legacy.manager.key_id() == key_id.as_deref()
+                    && legacy.retire_at.is_none_or(|retire_at| now < retire_at)
+            })
+            .map(|legacy| &legacy.manager)
+    }
+
+    /// Returns whether the hash was produced with a previous key.
+    fn uses_legacy_key(&self, hashed_password: &str) -> bool {
+        Argon2Hasher::key_id_of(hashed_password).as_deref() != self.primary.key_id()
+    }
+}
+
+#[async_trait::async_trait]
+impl SecurePasswordManager for KeyRotatingPasswordManager {
+    /// Hashes a password using the current key.
+    async fn hash_password(&self, password: &str) -> Result<String, AuthError> {
+        self.primary.hash_password(password).await
+    }
+
+    /// Verifies a password using the key that produced the stored hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::VerificationError`] if the hash is not an Argon2 hash, or if its
+    /// key is unknown or retired.
+    async fn verify_password(
+        &self,
+        password: &str,
+        hashed_password: &str,
+    ) -> Result<bool, AuthError> {
+        match self.manager_for(hashed_password) {
+            Some(manager) => manager.verify_password(password, hashed_password).await,
+            None => Err(AuthError::VerificationError(
+                "No active key verifies this password hash".to_string(),
+            )),
+        }
+    }
+
+    /// Returns whether the hash was produced with a key still accepted.
+    fn identify(&self, hashed_password: &str) -> bool {
+        self.manager_for(hashed_password).is_some()
+    }
+
+    /// Returns whether the hash should be recomputed with the current key.
+    fn needs_rehash(&self, hashed_password: &str) -> bool {
+        self.uses_legacy_key(hashed_password) || self.primary.needs_rehash(hashed_password)
+    }
+
+    /// Hashes a password using the current key and the user's target parameters.
+    async fn hash_password_for(
+        &self,
+        password: &str,
+        target: Option<&Argon2Params>,
+    ) -> Result<String, AuthError> {
+        self.primary.hash_password_for(password, target).await
+    }
+
+    /// Returns whether the hash should be recomputed with the current key and the user's
+    /// target parameters.
+    fn needs_rehash_for(&self, hashed_password: &str, target: Option<&Argon2Params>) -> bool {
+        self.uses_legacy_key(hashed_password)
+            || self.primary.needs_rehash_for(hashed_password, target)
+    }
+}
@@ -0,0 +1,100 @@
+//! Disposable email domain filtering.
+//!
+//! Throwaway email providers let a single person open any number of accounts. A
+//! [`DisposableEmailFilter`] holds a denylist of such domains, maintained by the application
+//! (public lists exist, e.g. the `disposable-email-domains` project), and rejects email
+//! addresses at these domains or their subdomains. `AuthService::with_disposable_email_filter`
+//! checks it on credentials signups, profile completions and OAuth2 accounts creating or
+//! linking a user by email.
+
+use std::collections::HashSet;
+
+use crate::error::AuthError;
+
+/// A denylist of disposable email domains.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DisposableEmailFilter {
+    /// The denied domains, lowercase and without trailing dots.
+    domains: HashSet<String>,
+}
+
+impl DisposableEmailFilter {
+    /// Creates a filter denying `domains` and their subdomains.
+    ///
+    /// # Arguments
+    /// * `domains` - The denied domains (e.g., `mailinator.com`), matched ignoring ASCII case.
+    pub fn new<I, S>(domains: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            domains: domains
+                .into_iter()
+                .filter_map(|domain| normalize(domain.as_ref()))
+                .collect(),
+        }
+    }
+
+    /// Creates a filter from a list holding one domain per line, as published by denylist
+    /// projects. Blank lines and lines starting with `#` are ignored.
+    ///
+    /// # Arguments
+    /// * `list` - The contents of the list.
+    pub fn from_list(list: &str) -> Self {
+        Self::new(
+            list.lines()
+                .map(str::trim)
+                .filter(|line| !line.starts_with('#')),
+        )
+    }
+
+    /// Returns the number of denied domains.
+    pub fn len(&self) -> usize {
+        self.domains.len()
+    }
+
+    /// Returns whether the filter denies no domain.
+    pub fn is_empty(&self) -> bool {
+        self.domains.is_empty()
+    }
+
+    /// Returns whether `email` is at a denied domain or one of its subdomains.
+    ///
+    /// Identifiers without an `@` (e.g., usernames) are never disposable.
+    pub fn is_disposable(&self, email: &str) -> bool {
+        let Some(domain) = email
+            .rsplit_once('@')
+            .and_then(|(_, domain)| normalize(domain))
+        else {
+            return false;
+        };
+        let mut suffix = domain.as_str();
+        loop {
+            if self.domains.contains(suffix) {
+                return true;
+            }
+            match suffix.split_once('.') {
+                Some((_, parent)) => suffix = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// Checks that `email` is not at a denied domain.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::DisposableEmailNotAllowed`] if it is.
+    pub fn check(&self, email: &str) -> Result<(), AuthError> {
+        if self.is_disposable(email) {
+            return Err(AuthError::DisposableEmailNotAllowed);
+        }
+        Ok(())
+    }
+}
+
+/// Lowercases a domain and strips its trailing dot, returning `None` if nothing is left.
+fn normalize(domain: &str) -> Option<String> {
+    let domain = domain.trim().trim_end_matches('.').to_ascii_lowercase();
+    (!domain.is_empty()).then_some(domain)
+}
@@ -12,9 +12,22 @@
 //! let policy = PasswordPolicy::default();
 //! assert!(policy.validate_password("Str0ng!Passw0rd").is_ok());
 //! ```
+//!
+//! Rules can be complemented with a minimum strength score, estimated by the [`strength`]
+//! submodule, to reject predictable passwords that satisfy them. The [`disposable`] submodule
+//! filters email addresses of throwaway providers.
 
 use crate::error::AuthError;
 
+/// Submodule for disposable email domain filtering.
+pub mod disposable;
+
+/// Submodule for password strength estimation.
+pub mod strength;
+
+/// Re-export of the disposable email domain filter.
+pub use disposable::DisposableEmailFilter;
+
 /// Represents the requirements for a strong password.
 ///
 /// This struct allows you to configure password complexity rules such as minimum length,
@@ -26,12 +39,15 @@ use crate::error::AuthError;
 /// - `require_lowercase`: If `true`, at least one lowercase letter is required.
 /// - `require_digit`: If `true`, at least one digit is required.
 /// - `require_special_char`: If `true`, at least one non-alphanumeric character is required.
+/// - `min_strength_score`: If set, the minimum [`strength::estimate_strength`] score (0 to 4).
+#[derive(Debug, Clone)]
 pub struct PasswordPolicy {
     pub min_length: usize,
     pub require_uppercase: bool,
     pub require_lowercase: bool,
     pub require_digit: bool,
     pub require_special_char: bool,
+    pub min_strength_score: Option<u8>,
 }
 
 impl Default for PasswordPolicy {
@@ -39,6 +55,7 @@ impl Default for PasswordPolicy {
     ///
     /// - Minimum length: 12
     /// - Requires uppercase, lowercase, digit, and special character
+    /// - No minimum strength score
     fn default() -> Self {
         PasswordPolicy {
             min_length: 12,
@@ -46,6 +63,7 @@ impl Default for PasswordPolicy {
             require_lowercase: true,
             require_digit: true,
             require_special_char: true,
+            min_strength_score: None,
         }
     }
 }
@@ -58,7 +76,9 @@ impl PasswordPolicy {
     ///
     /// # Returns
     /// * `Ok(())` if the password satisfies all requirements.
-    /// * `Err(AuthError)` with a descriptive message if any requirement is not met.
+    /// * `Err(AuthError::InvalidInput)` with a descriptive message if a rule is not met.
+    /// * `Err(AuthError::WeakPassword)` with feedback if the password scores below
+    ///   `min_strength_score`.
     ///
     /// # Example
     /// ```rust
@@ -95,6 +115,15 @@ impl PasswordPolicy {
                 "Password must contain at least one special character.".to_string(),
             ));
         }
+        if let Some(min_score) = self.min_strength_score {
+            let estimate = strength::estimate_strength(password);
+            if estimate.score < min_score {
+                return Err(AuthError::WeakPassword {
+                    score: estimate.score,
+                    feedback: estimate.feedback,
+                });
+            }
+        }
         Ok(())
     }
 }
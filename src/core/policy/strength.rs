@@ -0,0 +1,59 @@
+//! Password strength estimation.
+//!
+//! Composition rules accept predictable passwords such as `P@ssw0rd1`, which is a common
+//! password with textbook substitutions. [`estimate_strength`] estimates how many guesses an
+//! attacker needs instead. With the `zxcvbn` feature, it runs
+//! [zxcvbn](https://github.com/dropbox/zxcvbn), matching the password against its dictionaries
+//! of common passwords, names and words, and its keyboard, sequence, repeat and date patterns.
+//! Without it, a built-in estimator in the same spirit matches a much smaller word list, so
+//! enable the feature wherever strength scores gate signups.
+//!
+//! The result is a score on the zxcvbn scale, from 0 (too guessable) to 4 (very unguessable),
+//! with feedback for the user. [`PasswordPolicy::min_strength_score`](super::PasswordPolicy::min_strength_score)
+//! rejects passwords scoring below a threshold.
+
+#[cfg(not(feature = "zxcvbn"))]
+mod heuristic;
+
+/// The estimated strength of a password.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrengthEstimate {
+    /// The score, from 0 (too guessable) to 4 (very unguessable).
+    pub score: u8,
+    /// The base-10 logarithm of the estimated number of guesses.
+    pub guesses_log10: f64,
+    /// Suggestions for the user, empty for strong passwords.
+    pub feedback: Vec<String>,
+}
+
+/// Estimates the strength of a password.
+///
+/// # Arguments
+/// * `password` - The password to estimate.
+///
+/// # Returns
+/// The score, the estimated guesses and feedback for the user.
+pub fn estimate_strength(password: &str) -> StrengthEstimate {
+    #[cfg(feature = "zxcvbn")]
+    {
+        let entropy = zxcvbn::zxcvbn(password, &[]);
+        let feedback = entropy
+            .feedback()
+            .map(|feedback| {
+                feedback
+                    .warning()
+                    .map(|warning| warning.to_string())
+                    .into_iter()
+                    .chain(feedback.suggestions().iter().map(ToString::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        StrengthEstimate {
+            score: entropy.score().into(),
+            guesses_log10: entropy.guesses_log10(),
+            feedback,
+        }
+    }
+    #[cfg(not(feature = "zxcvbn"))]
+    heuristic::estimate_strength(password)
+}
@@ -0,0 +1,353 @@
+//! The built-in strength estimator, used when the `zxcvbn` feature is disabled.
+//!
+//! It matches a short list of common passwords and words (undoing `@`-for-`a` style
+//! substitutions), years, keyboard rows, sequences and repeats. Its dictionary is far smaller
+//! than zxcvbn's, so it overrates passwords built on less common words and names.
+
+use super::StrengthEstimate;
+
+/// Common passwords and words, matched after case folding and substitution reversal.
+const COMMON_WORDS: &[&str] = &[
+    "password",
+    "passwd",
+    "qwerty",
+    "letmein",
+    "welcome",
+    "admin",
+    "administrator",
+    "login",
+    "dragon",
+    "monkey",
+    "master",
+    "shadow",
+    "iloveyou",
+    "sunshine",
+    "princess",
+    "football",
+    "baseball",
+    "soccer",
+    "hockey",
+    "superman",
+    "batman",
+    "whatever",
+    "freedom",
+    "starwars",
+    "pokemon",
+    "computer",
+    "internet",
+    "secret",
+    "hello",
+    "charlie",
+    "michael",
+    "jennifer",
+    "jordan",
+    "hunter",
+    "ranger",
+    "buster",
+    "thomas",
+    "robert",
+    "daniel",
+    "andrew",
+    "joshua",
+    "matthew",
+    "jessica",
+    "ashley",
+    "summer",
+    "winter",
+    "spring",
+    "autumn",
+    "flower",
+    "cookie",
+    "chocolate",
+    "banana",
+    "orange",
+    "purple",
+    "mustang",
+    "ferrari",
+    "harley",
+    "killer",
+    "pepper",
+    "ginger",
+    "tigger",
+    "maggie",
+    "access",
+    "changeme",
+    "default",
+    "guest",
+    "root",
+    "test",
+    "temp",
+    "user",
+    "love",
+    "money",
+    "family",
+    "friend",
+    "angel",
+    "blessed",
+    "cheese",
+    "coffee",
+    "london",
+    "paris",
+    "berlin",
+    "secure",
+    "security",
+    "company",
+    "office",
+    "google",
+    "facebook",
+];
+
+/// Keyboard rows, in which runs of adjacent keys are easy to guess.
+const KEYBOARD_ROWS: &[&str] = &[
+    "qwertyuiop",
+    "asdfghjkl",
+    "zxcvbnm",
+    "1234567890",
+    "azertyuiop",
+];
+
+/// Common substitutions, mapped back to the letter they replace.
+const SUBSTITUTIONS: &[(char, char)] = &[
+    ('@', 'a'),
+    ('4', 'a'),
+    ('8', 'b'),
+    ('(', 'c'),
+    ('3', 'e'),
+    ('6', 'g'),
+    ('1', 'i'),
+    ('!', 'i'),
+    ('|', 'l'),
+    ('0', 'o'),
+    ('$', 's'),
+    ('5', 's'),
+    ('7', 't'),
+    ('+', 't'),
+    ('2', 'z'),
+];
+
+/// Minimum length of a keyboard run, sequence or repeat treated as a pattern.
+const MIN_PATTERN_LEN: usize = 3;
+
+/// A predictable part of a password.
+struct Pattern {
+    /// The index of the first character of the part.
+    start: usize,
+    /// The number of characters of the part.
+    len: usize,
+    /// The base-10 logarithm of the guesses needed to find the part.
+    guesses_log10: f64,
+}
+
+/// Finds a pattern in a lowercase password, outside the patterns already found.
+type PatternFinder = fn(&[char], &[Pattern]) -> Option<Pattern>;
+
+/// Estimates the strength of a password with the built-in patterns.
+pub(super) fn estimate_strength(password: &str) -> StrengthEstimate {
+    let chars: Vec<char> = password.chars().collect();
+    let folded: Vec<char> = chars
+        .iter()
+        .map(|c| {
+            let c = c.to_ascii_lowercase();
+            SUBSTITUTIONS
+                .iter()
+                .find(|(substitute, _)| *substitute == c)
+                .map_or(c, |(_, letter)| *letter)
+        })
+        .collect();
+    let lowercase: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+
+    let mut feedback = Vec::new();
+    let mut patterns = Vec::new();
+    if let Some(pattern) = common_word(&folded) {
+        feedback.push("This is similar to a commonly used password.".to_string());
+        let part = &chars[pattern.start..pattern.start + pattern.len];
+        let mut guesses_log10 = pattern.guesses_log10;
+        if part.iter().any(char::is_ascii_uppercase) {
+            guesses_log10 += 0.3;
+        }
+        if part.iter().any(|c| !c.is_ascii_alphabetic()) {
+            guesses_log10 += 0.6;
+            feedback.push(
+                "Predictable substitutions like '@' instead of 'a' don't help very much."
+                    .to_string(),
+            );
+        }
+        patterns.push(Pattern {
+            guesses_log10,
+            ..pattern
+        });
+    }
+    let finders: [(PatternFinder, &str); 4] = [
+        (year, "Years are easy to guess."),
+        (keyboard_run, "Straight rows of keys are easy to guess."),
+        (sequence, "Sequences like abc or 6543 are easy to guess."),
+        (repeat, "Repeats like \"aaa\" are easy to guess."),
+    ];
+    for (find, message) in finders {
+        if let Some(pattern) = find(&lowercase, &patterns) {
+            feedback.push(message.to_string());
+            patterns.push(pattern);
+        }
+    }
+
+    let remaining: Vec<char> = (0..chars.len())
+        .filter(|index| !is_covered(&patterns, *index))
+        .map(|index| chars[index])
+        .collect();
+    let guesses_log10 = patterns
+        .iter()
+        .map(|pattern| pattern.guesses_log10)
+        .sum::<f64>()
+        + remaining.len() as f64 * charset_size(&remaining).log10();
+
+    let score = match guesses_log10 {
+        g if g < 3.0 => 0,
+        g if g < 6.0 => 1,
+        g if g < 8.0 => 2,
+        g if g < 10.0 => 3,
+        _ => 4,
+    };
+    if score < 3 {
+        feedback.push("Add another word or two. Uncommon words are better.".to_string());
+    }
+    StrengthEstimate {
+        score,
+        guesses_log10,
+        feedback,
+    }
+}
+
+/// Finds the longest common word in the folded password.
+fn common_word(folded: &[char]) -> Option<Pattern> {
+    let folded: String = folded.iter().collect();
+    COMMON_WORDS
+        .iter()
+        .enumerate()
+        .filter_map(|(rank, word)| {
+            // Indices are in characters, matching the other patterns
+            let byte_start = folded.find(word)?;
+            Some(Pattern {
+                start: folded[..byte_start].chars().count(),
+                len: word.chars().count(),
+                guesses_log10: ((rank + 1) as f64).log10() + 1.0,
+            })
+        })
+        .max_by_key(|pattern| pattern.len)
+}
+
+/// Finds a year between 1900 and 2099, outside `taken` patterns.
+fn year(lowercase: &[char], taken: &[Pattern]) -> Option<Pattern> {
+    (0..lowercase.len().saturating_sub(3))
+        .find(|&start| {
+            let digits: String = lowercase[start..start + 4].iter().collect();
+            (digits.starts_with("19") || digits.starts_with("20"))
+                && digits.chars().all(|c| c.is_ascii_digit())
+                && (start..start + 4).all(|index| !is_covered(taken, index))
+        })
+        .map(|start| Pattern {
+            start,
+            len: 4,
+            guesses_log10: 200f64.log10(),
+        })
+}
+
+/// Finds the longest run of adjacent keys of a keyboard row, outside `taken` patterns.
+fn keyboard_run(lowercase: &[char], taken: &[Pattern]) -> Option<Pattern> {
+    longest_run(lowercase, taken, |previous, next| {
+        KEYBOARD_ROWS.iter().any(|row| {
+            let row: Vec<char> = row.chars().collect();
+            row.windows(2)
+                .any(|pair| pair[0] == previous && pair[1] == next)
+        })
+    })
+    .map(|(start, len)| Pattern {
+        start,
+        len,
+        guesses_log10: (KEYBOARD_ROWS.len() as f64 * 10.0 * len as f64).log10(),
+    })
+}
+
+/// Finds the longest ascending or descending sequence of letters or digits, outside `taken`
+/// patterns.
+fn sequence(lowercase: &[char], taken: &[Pattern]) -> Option<Pattern> {
+    let step = |previous: char, next: char, delta: i32| {
+        previous.is_ascii_alphanumeric()
+            && next.is_ascii_alphanumeric()
+            && next as i32 - previous as i32 == delta
+    };
+    [1, -1]
+        .into_iter()
+        .filter_map(|delta| longest_run(lowercase, taken, |p, n| step(p, n, delta)))
+        .max_by_key(|(_, len)| *len)
+        .map(|(start, len)| Pattern {
+            start,
+            len,
+            guesses_log10: (36.0 * 2.0 * len as f64).log10(),
+        })
+}
+
+/// Finds the longest repetition of a single character, outside `taken` patterns.
+fn repeat(lowercase: &[char], taken: &[Pattern]) -> Option<Pattern> {
+    longest_run(lowercase, taken, |previous, next| previous == next).map(|(start, len)| Pattern {
+        start,
+        len,
+        guesses_log10: (charset_size(&lowercase[start..=start]) * len as f64).log10(),
+    })
+}
+
+/// Finds the longest run of at least [`MIN_PATTERN_LEN`] characters in which each pair of
+/// consecutive characters satisfies `adjacent`, outside `taken` patterns.
+fn longest_run(
+    chars: &[char],
+    taken: &[Pattern],
+    adjacent: impl Fn(char, char) -> bool,
+) -> Option<(usize, usize)> {
+    let free = |index: usize| !is_covered(taken, index);
+    let mut best: Option<(usize, usize)> = None;
+    let mut start = 0;
+    for index in 1..=chars.len() {
+        let continues = index < chars.len()
+            && free(index)
+            && free(index - 1)
+            && adjacent(chars[index - 1], chars[index]);
+        if !continues {
+            let len = index - start;
+            if len >= MIN_PATTERN_LEN && free(start) && best.is_none_or(|(_, best)| len > best) {
+                best = Some((start, len));
+            }
+            start = index;
+        }
+    }
+    best
+}
+
+/// Returns whether the character at `index` belongs to one of `patterns`.
+fn is_covered(patterns: &[Pattern], index: usize) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| (pattern.start..pattern.start + pattern.len).contains(&index))
+}
+
+/// Returns the number of characters an attacker brute-forcing `chars` must try per position.
+fn charset_size(chars: &[char]) -> f64 {
+    let mut size: f64 = 0.0;
+    if chars.iter().any(char::is_ascii_lowercase) {
+        size += 26.0;
+    }
+    if chars.iter().any(char::is_ascii_uppercase) {
+        size += 26.0;
+    }
+    if chars.iter().any(char::is_ascii_digit) {
+        size += 10.0;
+    }
+    if chars
+        .iter()
+        .any(|c| c.is_ascii() && !c.is_ascii_alphanumeric())
+    {
+        size += 33.0;
+    }
+    if chars.iter().any(|c| !c.is_ascii()) {
+        size += 100.0;
+    }
+    size.max(1.0)
+}
@@ -0,0 +1,92 @@
+//! Secure random generation utilities.
+//!
+//! This module centralizes access to the cryptographically secure random number generator
+//! so that every feature needing high-entropy values (reset tokens, magic links, backup codes,
+//! API keys, OAuth2 state, salts) draws from the same, audited source.
+//!
+//! All values come from the operating system's CSPRNG ([`OsRng`]).
+//!
+//! # Example
+//!
+//! ```rust
+//! use narangcia_cryptic::core::rand::{secure_random_bytes, secure_random_string};
+//!
+//! let key = secure_random_bytes(32);
+//! assert_eq!(key.len(), 32);
+//!
+//! let token = secure_random_string(32);
+//! assert_eq!(token.len(), 43);
+//! ```
+
+use ::rand::{TryRngCore, rngs::OsRng};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+/// Fills `dest` with bytes from the operating system's CSPRNG.
+///
+/// # Arguments
+///
+/// * `dest` - The buffer to fill.
+///
+/// # Errors
+///
+/// Returns the underlying [`OsError`](::rand::rand_core::OsError) if the OS random source is unavailable.
+pub fn try_fill_secure_random(dest: &mut [u8]) -> Result<(), ::rand::rand_core::OsError> {
+    OsRng.try_fill_bytes(dest)
+}
+
+/// Returns `n` bytes from the operating system's CSPRNG.
+///
+/// # Arguments
+///
+/// * `n` - The number of random bytes to generate.
+///
+/// # Panics
+///
+/// Panics if the OS random source is unavailable, as no secure value can be produced.
+pub fn secure_random_bytes(n: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; n];
+    try_fill_secure_random(&mut bytes).expect("operating system random source is unavailable");
+    bytes
+}
+
+/// Returns a URL-safe, unpadded base64 string encoding `bytes` random bytes.
+///
+/// The result only contains `A-Z`, `a-z`, `0-9`, `-` and `_`, and is `ceil(bytes * 4 / 3)`
+/// characters long. Use at least 32 bytes for secrets such as tokens or API keys.
+///
+/// # Arguments
+///
+/// * `bytes` - The number of random bytes (entropy) to encode.
+///
+/// # Panics
+///
+/// Panics if the OS random source is unavailable, as no secure value can be produced.
+pub fn secure_random_string(bytes: usize) -> String {
+    URL_SAFE_NO_PAD.encode(secure_random_bytes(bytes))
+}
+
+/// Returns a string of `len` uniformly distributed decimal digits.
+///
+/// Suitable for short codes read by humans, such as email one-time passwords. Their low
+/// entropy must be compensated by a short lifetime and an attempt limit.
+///
+/// # Arguments
+///
+/// * `len` - The number of digits to generate.
+///
+/// # Panics
+///
+/// Panics if the OS random source is unavailable, as no secure value can be produced.
+pub fn secure_random_digits(len: usize) -> String {
+    let mut digits = String::with_capacity(len);
+    while digits.len() < len {
+        for byte in secure_random_bytes(len - digits.len()) {
+            // Rejection sampling: 250 is the largest multiple of 10 below 256.
+            if byte < 250 {
+                digits.push(char::from(b'0' + byte % 10));
+            }
+        }
+    }
+    digits
+}
@@ -0,0 +1,168 @@
+//! Fixed-window rate limiting.
+//!
+//! Independent limits protect the service. The per-IP limit throttles clients by network
+//! address, and callers apply it with `AuthService::check_ip_rate_limit` before any lookup.
+//! The per-user limit is keyed by the pseudonym (`AuthService::log_id`) of the resolved user
+//! ID. The service applies it to operations that reach a user's inbox (e.g., email one-time
+//! passwords) after the user has been looked up, so one account cannot be flooded with emails
+//! by an attacker rotating through many addresses.
+//! A third limit throttles `AuthService::is_identifier_available` per client, so that one
+//! client cannot enumerate users by probing identifiers. It is enabled by default
+//! ([`DEFAULT_AVAILABILITY_RATE_LIMIT`]). A fourth limit caps the password or token proofs
+//! submitted to `AuthService::confirm_link` per user, locking out guessing once it is reached
+//! ([`DEFAULT_LINK_CONFIRMATION_RATE_LIMIT`]).
+//!
+//! Each limit allows [`RateLimit::max_requests`] hits per key within a fixed window of
+//! [`RateLimit::window_secs`] seconds. This module provides the [`RateLimitStore`] trait
+//! counting hits and an in-memory default implementation.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::error::AuthError;
+
+/// Default window (in seconds) of a rate limit configured without one.
+pub const DEFAULT_RATE_LIMIT_WINDOW: u64 = 3600;
+
+/// Default limit on identifier availability checks per client: 30 per minute.
+pub const DEFAULT_AVAILABILITY_RATE_LIMIT: RateLimit = RateLimit {
+    max_requests: 30,
+    window_secs: 60,
+};
+
+/// Default limit on account link confirmations per user: 5 per 15 minutes.
+pub const DEFAULT_LINK_CONFIRMATION_RATE_LIMIT: RateLimit = RateLimit {
+    max_requests: 5,
+    window_secs: 900,
+};
+
+/// A number of hits allowed per key within a fixed window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The number of hits allowed per window.
+    pub max_requests: u32,
+    /// The length of the window, in seconds.
+    pub window_secs: u64,
+}
+
+impl RateLimit {
+    /// Creates a limit of `max_requests` hits per `window_secs` seconds.
+    pub fn new(max_requests: u32, window_secs: u64) -> Self {
+        Self {
+            max_requests,
+            window_secs,
+        }
+    }
+}
+
+/// The key of a per-user limit counter, from the pseudonym of the user ID.
+pub(crate) fn user_key(log_id: &str) -> String {
+    format!("user:{log_id}")
+}
+
+/// The key of a per-IP limit counter.
+pub(crate) fn ip_key(ip: &str) -> String {
+    format!("ip:{ip}")
+}
+
+/// The key of the identifier availability limit counter of a client.
+pub(crate) fn availability_key(client: &str) -> String {
+    format!("availability:{client}")
+}
+
+/// The key of the account link confirmation limit counter of a user, from the pseudonym of
+/// the user ID.
+pub(crate) fn link_confirmation_key(log_id: &str) -> String {
+    format!("link:{log_id}")
+}
+
+/// Counts hits per key within fixed windows.
+///
+/// Implementations must make [`RateLimitStore::hit`] atomic, so that concurrent requests
+/// cannot exceed the limit.
+#[async_trait::async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Records a hit for `key` under `limit`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(None)` if the hit is allowed.
+    /// * `Ok(Some(retry_after))` if the limit is reached, with the number of seconds until
+    ///   the window resets. Rejected hits are not counted.
+    /// * `Err(AuthError)` if the store is unavailable.
+    async fn hit(&self, key: &str, limit: RateLimit) -> Result<Option<u64>, AuthError>;
+
+    /// Removes the windows that expired, and returns how many were removed.
+    ///
+    /// Long-running in-memory stores call this periodically (see
+    /// `AuthService::spawn_maintenance`) so they do not grow without bound. Persistent
+    /// backends should rely on native expiration (e.g. TTLs) instead; the default
+    /// implementation does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn prune_expired(&self) -> Result<usize, AuthError> {
+        Ok(0)
+    }
+}
+
+/// A counter of hits in the current window.
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    /// The end of the window (UNIX timestamp, seconds).
+    resets_at: u64,
+    /// The number of hits counted in the window.
+    hits: u32,
+}
+
+/// In-memory implementation of [`RateLimitStore`].
+///
+/// Expired windows are pruned lazily, or by [`RateLimitStore::prune_expired`]. Suitable for
+/// single-instance deployments and tests;
+/// multi-instance deployments need a shared store.
+#[derive(Debug, Default)]
+pub struct InMemoryRateLimitStore {
+    /// Current windows mapped by key.
+    windows: Mutex<HashMap<String, Window>>,
+}
+
+impl InMemoryRateLimitStore {
+    /// Creates a new, empty in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimitStore for InMemoryRateLimitStore {
+    async fn hit(&self, key: &str, limit: RateLimit) -> Result<Option<u64>, AuthError> {
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        let mut windows = self
+            .windows
+            .lock()
+            .map_err(|e| AuthError::ServiceUnavailable(e.to_string()))?;
+        windows.retain(|_, window| window.resets_at > now);
+
+        let window = windows.entry(key.to_string()).or_insert(Window {
+            resets_at: now.saturating_add(limit.window_secs.max(1)),
+            hits: 0,
+        });
+        if window.hits >= limit.max_requests {
+            return Ok(Some(window.resets_at - now));
+        }
+        window.hits += 1;
+        Ok(None)
+    }
+
+    async fn prune_expired(&self) -> Result<usize, AuthError> {
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        let mut windows = self
+            .windows
+            .lock()
+            .map_err(|e| AuthError::ServiceUnavailable(e.to_string()))?;
+        let before = windows.len();
+        windows.retain(|_, window| window.resets_at > now);
+        Ok(before - windows.len())
+    }
+}
@@ -0,0 +1,193 @@
+//! Signed, expiring URLs.
+//!
+//! Download links, unsubscribe links and confirmation links must not be forgeable or usable
+//! forever, but do not need a token store. A [`SignedUrl`] appends an expiration and an
+//! HMAC-SHA256 signature to a URL's query, covering its scheme, host, path and every query
+//! parameter, so any instance sharing the secret verifies the link without a lookup:
+//!
+//! ```text
+//! https://files.example.com/export?file=report.csv&expires=1767225600&signature=<base64url>
+//! ```
+//!
+//! Signatures are compared in constant time. Signing uses a label distinct from the other
+//! uses of the secret, so a signed URL can never be replayed as a token or a signed state.
+
+use std::time::Duration;
+
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use url::Url;
+
+use crate::error::AuthError;
+
+/// The query parameter holding the expiration (UNIX timestamp, seconds) of a signed URL.
+pub const EXPIRES_PARAM: &str = "expires";
+
+/// The query parameter holding the signature of a signed URL. It is always the last one.
+pub const SIGNATURE_PARAM: &str = "signature";
+
+/// The label separating signed URL signatures from the other uses of the secret.
+const SIGNATURE_LABEL: &str = "narangcia-cryptic signed url";
+
+/// The query parameters of a URL whose signature and expiration were verified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedParams {
+    /// The signed query parameters, in order, without `expires` and `signature`.
+    pub params: Vec<(String, String)>,
+    /// When the URL expires (UNIX timestamp, seconds).
+    pub expires_at: i64,
+}
+
+impl VerifiedParams {
+    /// Returns the value of the first query parameter named `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Signs URLs and verifies signed URLs.
+///
+/// [`AuthService::signed_urls`](crate::AuthService::signed_urls) returns one signing with the
+/// service's secret key.
+#[derive(Clone)]
+pub struct SignedUrl {
+    /// The key signing URLs.
+    encoding_key: EncodingKey,
+    /// The key verifying URLs.
+    decoding_key: DecodingKey,
+}
+
+impl std::fmt::Debug for SignedUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignedUrl").finish_non_exhaustive()
+    }
+}
+
+impl SignedUrl {
+    /// Creates a signer from an HMAC secret.
+    ///
+    /// # Arguments
+    /// * `secret` - The HMAC secret, shared by every instance verifying the URLs.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::ConfigError`] if the secret is shorter than
+    /// [`MIN_HMAC_SECRET_LEN`](crate::core::token::jwt::MIN_HMAC_SECRET_LEN) bytes.
+    pub fn new(secret: &[u8]) -> Result<Self, AuthError> {
+        let min_len = crate::core::token::jwt::MIN_HMAC_SECRET_LEN;
+        if secret.len() < min_len {
+            return Err(AuthError::ConfigError(format!(
+                "Signed URL secret must be at least {min_len} bytes long"
+            )));
+        }
+        Ok(Self {
+            encoding_key: EncodingKey::from_secret(secret),
+            decoding_key: DecodingKey::from_secret(secret),
+        })
+    }
+
+    /// Returns `base` with `params`, an expiration and a signature appended to its query.
+    ///
+    /// # Arguments
+    /// * `base` - The absolute URL to sign. Query parameters it already has are signed too.
+    /// * `params` - The query parameters to add, e.g. `[("file", "report.csv")]`.
+    /// * `ttl` - How long the URL stays valid.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidInput`] if `base` is not an absolute URL or a parameter is
+    /// named `expires` or `signature`, or [`AuthError::TokenGeneration`] if signing fails.
+    pub fn sign_url(
+        &self,
+        base: &str,
+        params: &[(&str, &str)],
+        ttl: Duration,
+    ) -> Result<String, AuthError> {
+        let mut url =
+            Url::parse(base).map_err(|e| AuthError::InvalidInput(format!("Invalid URL: {e}")))?;
+        let existing: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        let reserved = |name: &str| name == EXPIRES_PARAM || name == SIGNATURE_PARAM;
+        if let Some(name) = existing
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .chain(params.iter().map(|(name, _)| *name))
+            .find(|name| reserved(name))
+        {
+            return Err(AuthError::InvalidInput(format!(
+                "Query parameter {name:?} is reserved for signed URLs"
+            )));
+        }
+
+        let expires_at = chrono::Utc::now().timestamp().max(0) + ttl.as_secs() as i64;
+        // Re-encode the existing query, so it is signed as `verify_url` will re-encode it
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(&existing)
+            .extend_pairs(params)
+            .append_pair(EXPIRES_PARAM, &expires_at.to_string());
+        let signature = jsonwebtoken::crypto::sign(
+            Self::message(&url).as_bytes(),
+            &self.encoding_key,
+            Algorithm::HS256,
+        )
+        .map_err(|e| AuthError::TokenGeneration(format!("Failed to sign URL: {e}")))?;
+        url.query_pairs_mut()
+            .append_pair(SIGNATURE_PARAM, &signature);
+        Ok(url.into())
+    }
+
+    /// Checks the signature and expiration of a URL returned by [`SignedUrl::sign_url`].
+    ///
+    /// # Arguments
+    /// * `url` - The URL to verify, as requested by the client.
+    ///
+    /// # Returns
+    /// The signed query parameters.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidToken`] if the URL is malformed, unsigned or was tampered
+    /// with, or [`AuthError::TokenExpired`] if it expired.
+    pub fn verify_url(&self, url: &str) -> Result<VerifiedParams, AuthError> {
+        let invalid = || AuthError::InvalidToken("Invalid URL signature".to_string());
+        let mut url = Url::parse(url).map_err(|_| invalid())?;
+        let mut params: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        let signature = match params.pop() {
+            Some((name, signature)) if name == SIGNATURE_PARAM => signature,
+            _ => return Err(invalid()),
+        };
+
+        // Re-encode the signed part of the query as `sign_url` did
+        url.query_pairs_mut().clear().extend_pairs(&params);
+        let valid = jsonwebtoken::crypto::verify(
+            &signature,
+            Self::message(&url).as_bytes(),
+            &self.decoding_key,
+            Algorithm::HS256,
+        )
+        .unwrap_or(false);
+        if !valid {
+            return Err(invalid());
+        }
+
+        let expires_at = match params.pop() {
+            Some((name, expires_at)) if name == EXPIRES_PARAM => {
+                expires_at.parse::<i64>().map_err(|_| invalid())?
+            }
+            _ => return Err(invalid()),
+        };
+        if chrono::Utc::now().timestamp() >= expires_at {
+            return Err(AuthError::TokenExpired);
+        }
+        Ok(VerifiedParams { params, expires_at })
+    }
+
+    /// Returns the signed message for a URL without its signature.
+    fn message(url: &Url) -> String {
+        format!(
+            "{SIGNATURE_LABEL}\n{}\n{}\n{}",
+            url.origin().ascii_serialization(),
+            url.path(),
+            url.query().unwrap_or_default()
+        )
+    }
+}
@@ -0,0 +1,129 @@
+//! Short-lived caching of access token validations.
+//!
+//! Checking a signature is cheap, but the checks `AuthService` runs on top of it (session
+//! revocation, user status) read stores on every request. A [`ValidationCache`] remembers, for
+//! a few seconds, that a token (identified by its `jti` claim) passed those checks, along with
+//! the user read while checking it. Signatures and expirations are still verified on every
+//! validation; only the store reads are skipped.
+//!
+//! Entries of a user are dropped when their sessions are revoked or their status changes
+//! through `AuthService`, so revocation takes effect immediately on this instance. Other
+//! instances (and changes made directly in the stores) are only seen once entries expire,
+//! which bounds the delay by the cache TTL.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::core::user::User;
+
+/// Number of entries above which expired entries are pruned on insertion.
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// A cached successful validation.
+#[derive(Debug, Clone)]
+pub struct CachedValidation {
+    /// The subject (user ID) of the validated token.
+    pub user_id: String,
+    /// The user read while validating the token, if user statuses are verified.
+    pub user: Option<User>,
+    /// When the entry stops being used.
+    expires_at: Instant,
+}
+
+/// An in-memory cache of successful access token validations, keyed by `jti`.
+///
+/// The cache is best-effort: if its lock is poisoned, lookups miss and insertions are skipped.
+#[derive(Debug)]
+pub struct ValidationCache {
+    /// How long a validation is reused.
+    ttl: Duration,
+    /// Cached validations mapped by token ID.
+    entries: Mutex<HashMap<String, CachedValidation>>,
+}
+
+impl ValidationCache {
+    /// Creates an empty cache reusing validations for `ttl`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ttl` - How long a validation is reused, typically a few seconds. It bounds how long
+    ///   a revocation made elsewhere may go unnoticed.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached validation of the token `jti`, if it has not expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `jti` - The unique identifier of the token.
+    pub fn get(&self, jti: &str) -> Option<CachedValidation> {
+        let mut entries = self.entries.lock().ok()?;
+        let entry = entries.get(jti)?;
+        if entry.expires_at > Instant::now() {
+            return Some(entry.clone());
+        }
+        entries.remove(jti);
+        None
+    }
+
+    /// Records that the token `jti` of `user_id` passed validation.
+    ///
+    /// # Arguments
+    ///
+    /// * `jti` - The unique identifier of the token.
+    /// * `user_id` - The subject of the token.
+    /// * `user` - The user read while validating the token, if any.
+    pub fn insert(&self, jti: &str, user_id: &str, user: Option<User>) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        let now = Instant::now();
+        if entries.len() >= PRUNE_THRESHOLD {
+            entries.retain(|_, entry| entry.expires_at > now);
+        }
+        entries.insert(
+            jti.to_string(),
+            CachedValidation {
+                user_id: user_id.to_string(),
+                user,
+                expires_at: now + self.ttl,
+            },
+        );
+    }
+
+    /// Drops the cached validations of every token of `user_id`.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - The user whose validations to drop.
+    pub fn invalidate_user(&self, user_id: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.retain(|_, entry| entry.user_id != user_id);
+        }
+    }
+
+    /// Drops every cached validation.
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    /// Returns the number of cached validations, including expired ones not yet pruned.
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .map(|entries| entries.len())
+            .unwrap_or(0)
+    }
+
+    /// Returns whether the cache holds no validations.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
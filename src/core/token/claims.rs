@@ -16,13 +16,166 @@ pub trait Claims {
     fn get_subject(&self) -> &str;
     /// Returns the expiration timestamp (as a UNIX timestamp in seconds).
     fn get_expiration(&self) -> usize;
+    /// Returns the tenant the token was issued for, if any.
+    fn get_tenant_id(&self) -> Option<&str> {
+        None
+    }
+    /// Returns the issued-at timestamp (as a UNIX timestamp in seconds), if known.
+    fn get_issued_at(&self) -> Option<usize> {
+        None
+    }
+    /// Returns the token type (e.g., `access` or `refresh`), if known.
+    fn get_token_type(&self) -> Option<&str> {
+        None
+    }
+    /// Returns the space-separated scopes granted to the token, if any.
+    fn get_scope(&self) -> Option<&str> {
+        None
+    }
+    /// Returns the roles granted to the token's subject.
+    fn get_roles(&self) -> &[String] {
+        &[]
+    }
+    /// Returns the session the token belongs to, if any.
+    fn get_session_id(&self) -> Option<&str> {
+        None
+    }
+    /// Returns the audience the token was issued for, if any.
+    fn get_audience(&self) -> Option<&str> {
+        None
+    }
+    /// Returns the issuer of the token (`iss` claim), if any.
+    fn get_issuer(&self) -> Option<&str> {
+        None
+    }
+    /// Returns the client fingerprint the token is bound to, if any.
+    fn get_fingerprint(&self) -> Option<&str> {
+        None
+    }
+    /// Returns the unique identifier of the token (`jti` claim), if any.
+    fn get_token_id(&self) -> Option<&str> {
+        None
+    }
+    /// Returns the custom (non-standard) claims of the token, if any.
+    fn get_custom_claims(&self) -> Option<&serde_json::Map<String, serde_json::Value>> {
+        None
+    }
+    /// Returns the custom claim named `name`, if the token carries it.
+    fn get_custom(&self, name: &str) -> Option<&serde_json::Value> {
+        self.get_custom_claims()?.get(name)
+    }
+    /// Returns when the user authenticated (`auth_time` claim, UNIX timestamp in seconds), if
+    /// known. Refreshes keep the time of the original login.
+    fn get_auth_time(&self) -> Option<usize> {
+        None
+    }
+    /// Returns the methods the user authenticated with (`amr` claim, e.g. `["pwd"]`), if known.
+    fn get_amr(&self) -> &[String] {
+        &[]
+    }
+    /// Returns the lifetime (in seconds) a refresh token was issued with when it overrides the
+    /// default (`rexp` claim), e.g. for "remember me" logins.
+    fn get_refresh_expiration(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Authentication method reference (`amr`) values recorded by the login flows, following
+/// RFC 8176 where it defines one.
+pub mod amr {
+    use crate::core::oauth::store::OAuth2Provider;
+
+    /// Password-based authentication.
+    pub const PASSWORD: &str = "pwd";
+    /// One-time password authentication (e.g., an email code).
+    pub const ONE_TIME_PASSWORD: &str = "otp";
+
+    /// Returns the method of a login through an OAuth2 provider (e.g., `oauth:google`).
+    pub fn oauth(provider: OAuth2Provider) -> String {
+        format!("oauth:{provider}")
+    }
+}
+
+/// Names of the standard access token claims, which custom claims cannot override.
+pub(crate) const RESERVED_CLAIMS: &[&str] = &[
+    "sub",
+    "exp",
+    "iat",
+    "nbf",
+    "iss",
+    "token_type",
+    "tenant_id",
+    "sid",
+    "aud",
+    "cnf",
+    "jti",
+    "auth_time",
+    "amr",
+];
+
+/// Registered claims (RFC 7519, RFC 8693 and OpenID Connect) that validation does not
+/// enforce, rejected under [`UnknownClaimsPolicy::Strict`].
+pub const UNSUPPORTED_REGISTERED_CLAIMS: &[&str] =
+    &["nbf", "act", "may_act", "azp", "acr", "nonce"];
+
+/// How validation treats claims that [`AccessTokenClaims`] does not model, e.g. claims added
+/// by another issuer sharing the key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnknownClaimsPolicy {
+    /// Keeps unknown claims in [`AccessTokenClaims::custom`], readable with
+    /// [`Claims::get_custom`], so tokens of newer issuers remain accepted.
+    #[default]
+    Lenient,
+    /// Rejects tokens carrying one of the [`UNSUPPORTED_REGISTERED_CLAIMS`], whose restrictions
+    /// (e.g., a `nbf` not-before time) would otherwise be silently ignored. Other unknown
+    /// claims are kept as with [`UnknownClaimsPolicy::Lenient`].
+    Strict,
+}
+
+impl UnknownClaimsPolicy {
+    /// Checks the unknown claims of validated access token claims against the policy.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidToken`](crate::error::AuthError::InvalidToken) if the policy
+    /// is strict and the claims carry an unsupported registered claim.
+    pub(crate) fn check(self, claims: &AccessTokenClaims) -> Result<(), crate::error::AuthError> {
+        if self == Self::Lenient {
+            return Ok(());
+        }
+        match UNSUPPORTED_REGISTERED_CLAIMS
+            .iter()
+            .find(|name| claims.custom.contains_key(**name))
+        {
+            Some(name) => Err(crate::error::AuthError::InvalidToken(format!(
+                "Unsupported registered claim: {name}"
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The `cnf` (confirmation) claim of a token bound to a client, modeled on RFC 7800.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfirmationClaim {
+    /// The fingerprint of the client the token is bound to (e.g., a TLS certificate hash).
+    pub fingerprint: String,
+}
+
+/// Compares a bound fingerprint with the one presented by a client in constant time.
+pub(crate) fn fingerprint_matches(bound: &str, presented: &str) -> bool {
+    bound.len() == presented.len()
+        && bound
+            .bytes()
+            .zip(presented.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
 }
 
 /// Claims for access tokens.
 ///
 /// Access tokens are short-lived tokens used to authenticate requests to protected resources.
 /// This struct contains the standard fields required for access token validation and identification.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AccessTokenClaims {
     /// Subject (user ID) to whom the token was issued.
     pub sub: String,
@@ -32,6 +185,64 @@ pub struct AccessTokenClaims {
     pub iat: usize,
     /// Type of the token (should be "access").
     pub token_type: String,
+    /// Tenant the token was issued for, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    /// Session the token belongs to, shared by the tokens of a login and its refreshes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    /// Audience (consuming application) the token was issued for, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    /// Issuer (the authentication server) of the token, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// Client the token is bound to, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cnf: Option<ConfirmationClaim>,
+    /// Unique identifier of the token, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    /// When the user authenticated (UNIX timestamp, seconds), if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_time: Option<usize>,
+    /// Methods the user authenticated with.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub amr: Vec<String>,
+    /// Custom claims, serialized alongside the standard ones. Unknown claims of validated
+    /// tokens end up here too.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub custom: serde_json::Map<String, serde_json::Value>,
+}
+
+impl AccessTokenClaims {
+    /// Copies the values exposed by any [`Claims`] implementation into access token claims.
+    ///
+    /// # Arguments
+    ///
+    /// * `claims` - The claims to copy. A missing issued-at timestamp is set to 0 and a
+    ///   missing token type to `access`.
+    pub fn from_claims(claims: &(dyn Claims + Send + Sync)) -> Self {
+        Self {
+            sub: claims.get_subject().to_string(),
+            exp: claims.get_expiration(),
+            iat: claims.get_issued_at().unwrap_or(0),
+            token_type: claims.get_token_type().unwrap_or("access").to_string(),
+            tenant_id: claims.get_tenant_id().map(str::to_string),
+            sid: claims.get_session_id().map(str::to_string),
+            aud: claims.get_audience().map(str::to_string),
+            iss: claims.get_issuer().map(str::to_string),
+            cnf: claims
+                .get_fingerprint()
+                .map(|fingerprint| ConfirmationClaim {
+                    fingerprint: fingerprint.to_string(),
+                }),
+            jti: claims.get_token_id().map(str::to_string),
+            auth_time: claims.get_auth_time(),
+            amr: claims.get_amr().to_vec(),
+            custom: claims.get_custom_claims().cloned().unwrap_or_default(),
+        }
+    }
 }
 
 impl Claims for AccessTokenClaims {
@@ -44,6 +255,61 @@ impl Claims for AccessTokenClaims {
     fn get_expiration(&self) -> usize {
         self.exp
     }
+
+    /// Returns the tenant the access token was issued for.
+    fn get_tenant_id(&self) -> Option<&str> {
+        self.tenant_id.as_deref()
+    }
+
+    /// Returns the issued-at timestamp of the access token.
+    fn get_issued_at(&self) -> Option<usize> {
+        Some(self.iat)
+    }
+
+    /// Returns the type of the access token.
+    fn get_token_type(&self) -> Option<&str> {
+        Some(&self.token_type)
+    }
+
+    /// Returns the session the access token belongs to.
+    fn get_session_id(&self) -> Option<&str> {
+        self.sid.as_deref()
+    }
+
+    /// Returns the audience the access token was issued for.
+    fn get_audience(&self) -> Option<&str> {
+        self.aud.as_deref()
+    }
+
+    /// Returns the issuer of the access token.
+    fn get_issuer(&self) -> Option<&str> {
+        self.iss.as_deref()
+    }
+
+    /// Returns the client fingerprint the access token is bound to.
+    fn get_fingerprint(&self) -> Option<&str> {
+        self.cnf.as_ref().map(|cnf| cnf.fingerprint.as_str())
+    }
+
+    /// Returns the unique identifier of the access token.
+    fn get_token_id(&self) -> Option<&str> {
+        self.jti.as_deref()
+    }
+
+    /// Returns the custom claims of the access token.
+    fn get_custom_claims(&self) -> Option<&serde_json::Map<String, serde_json::Value>> {
+        Some(&self.custom)
+    }
+
+    /// Returns when the user authenticated.
+    fn get_auth_time(&self) -> Option<usize> {
+        self.auth_time
+    }
+
+    /// Returns the methods the user authenticated with.
+    fn get_amr(&self) -> &[String] {
+        &self.amr
+    }
 }
 
 /// Claims for refresh tokens.
@@ -61,6 +327,34 @@ pub struct RefreshTokenClaims {
     pub iat: usize,
     /// Type of the token (should be "refresh").
     pub token_type: String,
+    /// Tenant the token was issued for, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    /// Session the token belongs to, shared by the tokens of a login and its refreshes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    /// Audience (consuming application) the token was issued for, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    /// Issuer (the authentication server) of the token, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// Client the token is bound to, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cnf: Option<ConfirmationClaim>,
+    /// Unique identifier of the token, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    /// When the user authenticated (UNIX timestamp, seconds), if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_time: Option<usize>,
+    /// Methods the user authenticated with.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub amr: Vec<String>,
+    /// Lifetime (in seconds) the token was issued with, if it overrides the default (e.g., for
+    /// "remember me" logins). Successors are issued with the same lifetime.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rexp: Option<u64>,
 }
 
 impl Claims for RefreshTokenClaims {
@@ -73,4 +367,59 @@ impl Claims for RefreshTokenClaims {
     fn get_expiration(&self) -> usize {
         self.exp
     }
+
+    /// Returns the tenant the refresh token was issued for.
+    fn get_tenant_id(&self) -> Option<&str> {
+        self.tenant_id.as_deref()
+    }
+
+    /// Returns the issued-at timestamp of the refresh token.
+    fn get_issued_at(&self) -> Option<usize> {
+        Some(self.iat)
+    }
+
+    /// Returns the type of the refresh token.
+    fn get_token_type(&self) -> Option<&str> {
+        Some(&self.token_type)
+    }
+
+    /// Returns the session the refresh token belongs to.
+    fn get_session_id(&self) -> Option<&str> {
+        self.sid.as_deref()
+    }
+
+    /// Returns the audience the refresh token was issued for.
+    fn get_audience(&self) -> Option<&str> {
+        self.aud.as_deref()
+    }
+
+    /// Returns the issuer of the refresh token.
+    fn get_issuer(&self) -> Option<&str> {
+        self.iss.as_deref()
+    }
+
+    /// Returns the client fingerprint the refresh token is bound to.
+    fn get_fingerprint(&self) -> Option<&str> {
+        self.cnf.as_ref().map(|cnf| cnf.fingerprint.as_str())
+    }
+
+    /// Returns the unique identifier of the refresh token.
+    fn get_token_id(&self) -> Option<&str> {
+        self.jti.as_deref()
+    }
+
+    /// Returns when the user authenticated.
+    fn get_auth_time(&self) -> Option<usize> {
+        self.auth_time
+    }
+
+    /// Returns the methods the user authenticated with.
+    fn get_amr(&self) -> &[String] {
+        &self.amr
+    }
+
+    /// Returns the overridden lifetime of the refresh token.
+    fn get_refresh_expiration(&self) -> Option<u64> {
+        self.rexp
+    }
 }
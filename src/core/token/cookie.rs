@@ -0,0 +1,194 @@
+//! Cookie representation of token pairs.
+//!
+//! Browser applications usually keep tokens in `HttpOnly` cookies rather than in scripts.
+//! [`TokenPair::to_set_cookie_headers`] renders both tokens as `Set-Cookie` header values with
+//! consistent attributes, and [`TokenPair::from_cookie_header`] reads them back from the
+//! `Cookie` header of later requests.
+//!
+//! ```rust
+//! use narangcia_cryptic::core::token::TokenPair;
+//! use narangcia_cryptic::core::token::cookie::CookieOptions;
+//!
+//! let pair = TokenPair {
+//!     access_token: "access".to_string(),
+//!     refresh_token: "refresh".to_string(),
+//! };
+//! let options = CookieOptions::default();
+//! let headers = pair.to_set_cookie_headers(&options);
+//! assert!(headers[0].starts_with("access_token=access; Path=/; HttpOnly; Secure; SameSite=Lax"));
+//!
+//! let parsed = TokenPair::from_cookie_header("access_token=access; refresh_token=refresh", &options);
+//! assert_eq!(parsed, Some(pair));
+//! ```
+
+use super::TokenPair;
+
+/// The `SameSite` attribute of token cookies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SameSite {
+    /// Cookies are only sent with same-site requests.
+    Strict,
+    /// Cookies are also sent with top-level cross-site navigations (e.g., OAuth2 redirects).
+    #[default]
+    Lax,
+    /// Cookies are sent with every request. Requires `Secure`, which is always set.
+    None,
+}
+
+impl SameSite {
+    /// Returns the attribute value.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None",
+        }
+    }
+}
+
+/// Attributes of the cookies rendered by [`TokenPair::to_set_cookie_headers`].
+///
+/// # Fields
+///
+/// - `access_cookie_name`: The name of the access token cookie.
+/// - `refresh_cookie_name`: The name of the refresh token cookie.
+/// - `path`: The `Path` of the access token cookie.
+/// - `refresh_path`: The `Path` of the refresh token cookie, if it differs from `path`.
+/// - `domain`: The `Domain` of both cookies, if any.
+/// - `secure`: Whether cookies are only sent over HTTPS.
+/// - `http_only`: Whether cookies are hidden from scripts.
+/// - `same_site`: The `SameSite` attribute of both cookies.
+/// - `access_max_age`, `refresh_max_age`: Lifetimes (in seconds) overriding the token expirations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CookieOptions {
+    /// The name of the access token cookie (default: `access_token`).
+    pub access_cookie_name: String,
+    /// The name of the refresh token cookie (default: `refresh_token`).
+    pub refresh_cookie_name: String,
+    /// The `Path` of the access token cookie (default: `/`).
+    pub path: String,
+    /// The `Path` of the refresh token cookie, e.g. the refresh endpoint, so the refresh token
+    /// is not sent with every request. `None` uses [`Self::path`].
+    pub refresh_path: Option<String>,
+    /// The `Domain` of both cookies. `None` restricts them to the issuing host.
+    pub domain: Option<String>,
+    /// Sets `Secure` (default: `true`). Always set with [`SameSite::None`].
+    pub secure: bool,
+    /// Sets `HttpOnly` (default: `true`).
+    pub http_only: bool,
+    /// The `SameSite` attribute (default: [`SameSite::Lax`]).
+    pub same_site: SameSite,
+    /// The `Max-Age` of the access token cookie. `None` derives it from the `exp` claim of the
+    /// token, or renders a session cookie if the token has none.
+    pub access_max_age: Option<u64>,
+    /// The `Max-Age` of the refresh token cookie, derived like [`Self::access_max_age`].
+    /// Opaque refresh tokens carry no expiration, so they need an explicit value to persist.
+    pub refresh_max_age: Option<u64>,
+}
+
+impl Default for CookieOptions {
+    fn default() -> Self {
+        Self {
+            access_cookie_name: "access_token".to_string(),
+            refresh_cookie_name: "refresh_token".to_string(),
+            path: "/".to_string(),
+            refresh_path: None,
+            domain: None,
+            secure: true,
+            http_only: true,
+            same_site: SameSite::default(),
+            access_max_age: None,
+            refresh_max_age: None,
+        }
+    }
+}
+
+impl CookieOptions {
+    /// Renders one `Set-Cookie` header value.
+    fn render(&self, name: &str, value: &str, path: &str, max_age: Option<u64>) -> String {
+        let mut cookie = format!("{name}={value}; Path={path}");
+        if let Some(domain) = &self.domain {
+            cookie.push_str(&format!("; Domain={domain}"));
+        }
+        if let Some(max_age) = max_age {
+            cookie.push_str(&format!("; Max-Age={max_age}"));
+        }
+        if self.http_only {
+            cookie.push_str("; HttpOnly");
+        }
+        if self.secure || self.same_site == SameSite::None {
+            cookie.push_str("; Secure");
+        }
+        cookie.push_str(&format!("; SameSite={}", self.same_site.as_str()));
+        cookie
+    }
+}
+
+impl TokenPair {
+    /// Renders the tokens as `Set-Cookie` header values.
+    ///
+    /// # Arguments
+    /// * `options` - The names and attributes of the cookies.
+    ///
+    /// # Returns
+    /// The access token cookie, then the refresh token cookie.
+    pub fn to_set_cookie_headers(&self, options: &CookieOptions) -> Vec<String> {
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        let max_age = |token: &str, explicit: Option<u64>| {
+            explicit.or_else(|| token_expiration(token).map(|exp| exp.saturating_sub(now)))
+        };
+        vec![
+            options.render(
+                &options.access_cookie_name,
+                &self.access_token,
+                &options.path,
+                max_age(&self.access_token, options.access_max_age),
+            ),
+            options.render(
+                &options.refresh_cookie_name,
+                &self.refresh_token,
+                options.refresh_path.as_deref().unwrap_or(&options.path),
+                max_age(&self.refresh_token, options.refresh_max_age),
+            ),
+        ]
+    }
+
+    /// Reads the tokens back from the value of a `Cookie` request header.
+    ///
+    /// # Arguments
+    /// * `header` - The `Cookie` header value (e.g., `access_token=...; refresh_token=...`).
+    /// * `options` - The options the cookies were rendered with.
+    ///
+    /// # Returns
+    /// The token pair, or `None` if either cookie is missing or empty.
+    pub fn from_cookie_header(header: &str, options: &CookieOptions) -> Option<TokenPair> {
+        Some(TokenPair {
+            access_token: cookie_value(header, &options.access_cookie_name)?.to_string(),
+            refresh_token: cookie_value(header, &options.refresh_cookie_name)?.to_string(),
+        })
+    }
+}
+
+/// Returns the value of the cookie `name` in a `Cookie` request header, if present and
+/// non-empty.
+///
+/// # Arguments
+/// * `header` - The `Cookie` header value.
+/// * `name` - The cookie name.
+pub fn cookie_value<'h>(header: &'h str, name: &str) -> Option<&'h str> {
+    header
+        .split(';')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.trim_matches('"'))
+        .filter(|value| !value.is_empty())
+}
+
+/// Reads the `exp` claim of a JWT without verifying it, to derive a cookie lifetime.
+fn token_expiration(token: &str) -> Option<u64> {
+    let payload = super::jwt::unverified_payload(token)?;
+    serde_json::from_slice::<serde_json::Value>(&payload)
+        .ok()?
+        .get("exp")?
+        .as_u64()
+}
@@ -0,0 +1,43 @@
+//! Issuance-time claims enrichment.
+//!
+//! Some claims can only be computed when a token is issued, e.g. the current subscription
+//! tier held by a billing service. A [`ClaimsEnricher`] returns such claims for a user, and
+//! `AuthService` merges them into the custom claims of every access token it issues for a
+//! user it has loaded (logins, signups, OAuth2 logins and link confirmations), so callers do
+//! not assemble them manually. Plain refreshes drop them; `AuthService::refresh_access_token_enriched`
+//! computes them again.
+//!
+//! Enriched claims cannot override the standard claims (`sub`, `exp`, `sid`, ...): keys
+//! colliding with them are dropped when the token is generated.
+
+use serde_json::{Map, Value};
+
+use crate::core::user::User;
+use crate::error::AuthError;
+
+/// Computes custom claims for the access tokens of a user.
+#[async_trait::async_trait]
+pub trait ClaimsEnricher: Send + Sync {
+    /// Returns the custom claims to embed in the access token issued for `user`.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user the token is issued for.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AuthError`] if the claims cannot be computed. The error is returned to
+    /// the caller of the login or signup, and no token is issued.
+    async fn enrich(&self, user: &User) -> Result<Map<String, Value>, AuthError>;
+}
+
+/// The default [`ClaimsEnricher`], adding no claims.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopClaimsEnricher;
+
+#[async_trait::async_trait]
+impl ClaimsEnricher for NoopClaimsEnricher {
+    async fn enrich(&self, _user: &User) -> Result<Map<String, Value>, AuthError> {
+        Ok(Map::new())
+    }
+}
@@ -5,8 +5,22 @@
 //!
 //! # Features
 //! - Configurable access and refresh token durations
-//! - Secure token encoding and decoding using HMAC SHA-256
+//! - Secure token encoding and decoding using HMAC SHA-256, or RS256/ES256 key pairs whose
+//!   public keys resource servers can verify with an
+//!   [`OfflineVerifier`](crate::core::token::offline::OfflineVerifier)
 //! - Custom error handling for token operations
+//! - Rejection of (or warnings about) HMAC secrets shorter than [`MIN_HMAC_SECRET_LEN`] bytes
+//! - Configurable `typ` and `kid` header values, with `kid`-based verification key selection
+//! - Configurable `sub` claim format through a [`SubjectFormatter`]
+//! - Per-application `aud` claims, with rejection of tokens issued for other audiences
+//! - An optional `iss` claim, with rejection of tokens issued by another server
+//! - Binding of tokens to a client fingerprint through the `cnf` claim
+//! - A unique `jti` claim in every token
+//! - Lenient (default) or strict handling of unknown claims
+//! - Optional DEFLATE compression of large payloads, flagged by a `zip` header (requires the
+//!   `jwt-compression` feature)
+//! - Export of the verification keys (a JWKS, or the `kid` of the HMAC secret)
+//! - A configurable [`RefreshStrategy`], rotating or reusing refresh tokens
 //!
 //! # Example
 //! ```rust
@@ -14,11 +28,35 @@
 //! let jwt_service = JwtTokenService::new("mysecret", 3600, 86400);
 //! ```
 
-use crate::core::token::claims::{AccessTokenClaims, Claims, RefreshTokenClaims};
-use crate::core::token::{TokenPair, TokenService};
+use crate::core::token::claims::{
+    AccessTokenClaims, Claims, ConfirmationClaim, RESERVED_CLAIMS, RefreshTokenClaims,
+    UnknownClaimsPolicy,
+};
+use crate::core::token::material::{self, VerificationMaterial};
+use crate::core::token::refresh::RefreshStrategy;
+use crate::core::token::subject::{IdentitySubjectFormatter, SubjectFormatter};
+use crate::core::token::{TokenOptions, TokenPair, TokenService};
 use crate::error::AuthError;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use chrono::Utc;
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation, decode, decode_header,
+    encode,
+};
+use std::collections::HashMap;
+
+/// Minimum length (in bytes) of an HMAC secret, matching the 256-bit output of HS256.
+pub const MIN_HMAC_SECRET_LEN: usize = 32;
+
+/// Value of the `zip` header of tokens whose payload is DEFLATE-compressed, as defined for
+/// JWE by RFC 7516.
+pub const ZIP_DEFLATE: &str = "DEF";
+
+/// Maximum size (in bytes) of a decompressed payload. Larger payloads are rejected, so a
+/// small crafted token cannot exhaust memory.
+pub const MAX_INFLATED_PAYLOAD_LEN: usize = 256 * 1024;
 
 /// Service for generating, validating, and refreshing JWT access and refresh tokens.
 ///
@@ -35,11 +73,35 @@ pub struct JwtTokenService {
     access_token_duration: u64,
     /// Duration (in seconds) for which a refresh token is valid.
     refresh_token_duration: u64,
+    /// Value of the `typ` header emitted in every token (`None` omits it).
+    typ: Option<String>,
+    /// Value of the `kid` header emitted in every token (`None` omits it).
+    kid: Option<String>,
+    /// Additional verification keys, selected by the `kid` header of incoming tokens.
+    verification_keys: HashMap<String, DecodingKey>,
+    /// Maps user IDs to and from the `sub` claim.
+    subject_formatter: Box<dyn SubjectFormatter>,
+    /// Audiences accepted in the `aud` claim; the first one is embedded by default.
+    audiences: Vec<String>,
+    /// Issuer embedded in the `iss` claim and required in validated tokens, if any.
+    issuer: Option<String>,
+    /// Payload size (in bytes) above which tokens are compressed, if compression is enabled.
+    #[cfg(feature = "jwt-compression")]
+    compression_threshold: Option<usize>,
+    /// Public key verifying tokens, for key pair algorithms.
+    public_key: Option<Jwk>,
+    /// How unknown claims of validated access tokens are treated.
+    unknown_claims: UnknownClaimsPolicy,
+    /// Whether refreshes rotate or reuse refresh tokens, if configured.
+    refresh_strategy: Option<RefreshStrategy>,
 }
 
 impl JwtTokenService {
     /// Creates a new [`JwtTokenService`] with the given secret and token durations.
     ///
+    /// This constructor is lenient: a secret shorter than [`MIN_HMAC_SECRET_LEN`] bytes only
+    /// logs a warning. Use [`JwtTokenService::try_new`] to reject weak secrets instead.
+    ///
     /// # Arguments
     /// * `secret` - The secret key used for signing and verifying tokens.
     /// * `access_token_duration` - Access token validity duration in seconds.
@@ -50,17 +112,333 @@ impl JwtTokenService {
     /// let service = JwtTokenService::new("mysecret", 3600, 86400);
     /// ```
     pub fn new(secret: &str, access_token_duration: u64, refresh_token_duration: u64) -> Self {
+        if secret.len() < MIN_HMAC_SECRET_LEN {
+            log::warn!(
+                "JWT HMAC secret is only {} bytes long; use at least {MIN_HMAC_SECRET_LEN} bytes, \
+                 short secrets can be brute-forced",
+                secret.len()
+            );
+        }
         let key_bytes = secret.as_bytes();
 
+        Self::with_keys(
+            EncodingKey::from_secret(key_bytes),
+            DecodingKey::from_secret(key_bytes),
+            Algorithm::HS256,
+            access_token_duration,
+            refresh_token_duration,
+        )
+    }
+
+    /// Creates a new [`JwtTokenService`] signing tokens with RS256.
+    ///
+    /// Unlike HMAC secrets, the public key can be published (e.g., in a JWKS) so that resource
+    /// servers verify tokens without being able to issue them.
+    ///
+    /// # Arguments
+    /// * `private_key_pem` - The PEM-encoded RSA private key signing tokens.
+    /// * `public_key_pem` - The PEM-encoded RSA public key verifying tokens.
+    /// * `access_token_duration` - Access token validity duration in seconds.
+    /// * `refresh_token_duration` - Refresh token validity duration in seconds.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::ConfigError`] if a key cannot be parsed.
+    pub fn from_rsa_pem(
+        private_key_pem: &[u8],
+        public_key_pem: &[u8],
+        access_token_duration: u64,
+        refresh_token_duration: u64,
+    ) -> Result<Self, AuthError> {
+        let invalid = |e: jsonwebtoken::errors::Error| {
+            AuthError::ConfigError(format!("Invalid RSA key: {e}"))
+        };
+        let mut service = Self::with_keys(
+            EncodingKey::from_rsa_pem(private_key_pem).map_err(invalid)?,
+            DecodingKey::from_rsa_pem(public_key_pem).map_err(invalid)?,
+            Algorithm::RS256,
+            access_token_duration,
+            refresh_token_duration,
+        );
+        service.public_key = Some(material::public_jwk(public_key_pem, Algorithm::RS256)?);
+        Ok(service)
+    }
+
+    /// Creates a new [`JwtTokenService`] signing tokens with ES256, as with
+    /// [`JwtTokenService::from_rsa_pem`].
+    ///
+    /// # Arguments
+    /// * `private_key_pem` - The PEM-encoded (PKCS#8) P-256 private key signing tokens.
+    /// * `public_key_pem` - The PEM-encoded P-256 public key verifying tokens.
+    /// * `access_token_duration` - Access token validity duration in seconds.
+    /// * `refresh_token_duration` - Refresh token validity duration in seconds.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::ConfigError`] if a key cannot be parsed.
+    pub fn from_ec_pem(
+        private_key_pem: &[u8],
+        public_key_pem: &[u8],
+        access_token_duration: u64,
+        refresh_token_duration: u64,
+    ) -> Result<Self, AuthError> {
+        let invalid =
+            |e: jsonwebtoken::errors::Error| AuthError::ConfigError(format!("Invalid EC key: {e}"));
+        let mut service = Self::with_keys(
+            EncodingKey::from_ec_pem(private_key_pem).map_err(invalid)?,
+            DecodingKey::from_ec_pem(public_key_pem).map_err(invalid)?,
+            Algorithm::ES256,
+            access_token_duration,
+            refresh_token_duration,
+        );
+        service.public_key = Some(material::public_jwk(public_key_pem, Algorithm::ES256)?);
+        Ok(service)
+    }
+
+    /// Creates a service with the given keys and default settings.
+    fn with_keys(
+        encoding_key: EncodingKey,
+        decoding_key: DecodingKey,
+        algorithm: Algorithm,
+        access_token_duration: u64,
+        refresh_token_duration: u64,
+    ) -> Self {
         Self {
-            encoding_key: EncodingKey::from_secret(key_bytes),
-            decoding_key: DecodingKey::from_secret(key_bytes),
-            algorithm: Algorithm::HS256,
+            encoding_key,
+            decoding_key,
+            algorithm,
             access_token_duration,
             refresh_token_duration,
+            typ: Some("JWT".to_string()),
+            kid: None,
+            verification_keys: HashMap::new(),
+            subject_formatter: Box::new(IdentitySubjectFormatter),
+            audiences: Vec::new(),
+            issuer: None,
+            #[cfg(feature = "jwt-compression")]
+            compression_threshold: None,
+            public_key: None,
+            unknown_claims: UnknownClaimsPolicy::default(),
+            refresh_strategy: None,
         }
     }
 
+    /// Sets the `kid` (key ID) header emitted in every token.
+    ///
+    /// Tokens carrying this `kid` are verified with this service's own secret.
+    ///
+    /// # Arguments
+    /// * `kid` - The key ID, typically matching the corresponding JWKS entry.
+    ///
+    /// # Example
+    /// ```rust
+    /// let service = JwtTokenService::new("mysecret", 3600, 86400).with_kid("2024-06");
+    /// ```
+    pub fn with_kid(mut self, kid: impl Into<String>) -> Self {
+        self.kid = Some(kid.into());
+        self
+    }
+
+    /// Sets the `typ` header emitted in every token (defaults to `JWT`).
+    ///
+    /// # Arguments
+    /// * `typ` - The token type, or `None` to omit the header field.
+    pub fn with_typ(mut self, typ: Option<String>) -> Self {
+        self.typ = typ;
+        self
+    }
+
+    /// Registers an additional HMAC secret used to verify tokens whose `kid` header matches.
+    ///
+    /// This allows tokens signed with a previous key to remain valid during key rotation.
+    ///
+    /// # Arguments
+    /// * `kid` - The key ID the secret is registered under.
+    /// * `secret` - The secret used to verify tokens carrying this key ID.
+    pub fn with_verification_key(mut self, kid: impl Into<String>, secret: &str) -> Self {
+        self.verification_keys
+            .insert(kid.into(), DecodingKey::from_secret(secret.as_bytes()));
+        self
+    }
+
+    /// Sets the format of the `sub` claim (defaults to the bare user ID).
+    ///
+    /// The formatter is applied to user IDs when tokens are generated and reversed when they
+    /// are validated, so validated claims (and [`AuthService::get_user_id_from_token`]) still
+    /// yield user IDs. Tokens whose subject cannot be parsed are rejected.
+    ///
+    /// [`AuthService::get_user_id_from_token`]: crate::AuthService::get_user_id_from_token
+    ///
+    /// # Arguments
+    /// * `formatter` - The subject formatter to use.
+    ///
+    /// # Example
+    /// ```rust
+    /// let service = JwtTokenService::new("mysecret", 3600, 86400)
+    ///     .with_subject_formatter(PrefixSubjectFormatter::new("user:"));
+    /// ```
+    pub fn with_subject_formatter(mut self, formatter: impl SubjectFormatter + 'static) -> Self {
+        self.subject_formatter = Box::new(formatter);
+        self
+    }
+
+    /// Adds an audience accepted in the `aud` claim of validated tokens.
+    ///
+    /// Once an audience is configured, tokens without an `aud` claim or issued for another
+    /// audience are rejected. Tokens generated without an explicit
+    /// [`TokenOptions::audience`] are issued for the first configured audience.
+    ///
+    /// # Arguments
+    /// * `audience` - The audience, typically the name of the consuming service.
+    ///
+    /// # Example
+    /// ```rust
+    /// let service = JwtTokenService::new("mysecret", 3600, 86400)
+    ///     .with_audience("billing")
+    ///     .with_audience("reports");
+    /// ```
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audiences.push(audience.into());
+        self
+    }
+
+    /// Adds several audiences accepted in the `aud` claim, as with [`JwtTokenService::with_audience`].
+    ///
+    /// # Arguments
+    /// * `audiences` - The audiences to accept, in order of preference.
+    pub fn with_audiences<I, S>(mut self, audiences: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.audiences.extend(audiences.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets the issuer embedded in the `iss` claim of every token.
+    ///
+    /// Once set, validated tokens must carry this issuer.
+    ///
+    /// # Arguments
+    /// * `issuer` - The issuer, typically the URL of the authentication server.
+    pub fn with_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Compresses the payload of tokens whose JSON claims exceed `threshold` bytes.
+    ///
+    /// Compressed payloads are DEFLATE-compressed before being base64-encoded and signed, and
+    /// the token carries a `zip` header set to [`ZIP_DEFLATE`]. Compression pays off for large
+    /// claim sets (e.g., many custom claims) that would otherwise overflow cookie or header
+    /// size limits. Tokens whose payload does not shrink are left uncompressed.
+    ///
+    /// Compressed tokens are accepted during validation whether or not compression is enabled,
+    /// but only by this crate with the `jwt-compression` feature: other JWT libraries will
+    /// reject them.
+    ///
+    /// # Arguments
+    /// * `threshold` - The size (in bytes) of the JSON payload above which tokens are compressed.
+    ///
+    /// # Example
+    /// ```rust
+    /// let service = JwtTokenService::new("mysecret", 3600, 86400).with_compression(1024);
+    /// ```
+    #[cfg(feature = "jwt-compression")]
+    pub fn with_compression(mut self, threshold: usize) -> Self {
+        self.compression_threshold = Some(threshold);
+        self
+    }
+
+    /// Sets how unknown claims of validated access tokens are treated.
+    ///
+    /// # Arguments
+    /// * `policy` - The policy, [`UnknownClaimsPolicy::Lenient`] by default.
+    pub fn with_unknown_claims(mut self, policy: UnknownClaimsPolicy) -> Self {
+        self.unknown_claims = policy;
+        self
+    }
+
+    /// Sets whether refreshes rotate or reuse refresh tokens.
+    ///
+    /// Without a strategy, every refresh issues a new refresh token but earlier ones stay
+    /// valid until they expire. [`RefreshStrategy::Reuse`] returns the presented refresh token
+    /// instead; the rotating strategies issue a new one and let `AuthService` reject reused
+    /// ones (the service itself is stateless and cannot detect reuse).
+    ///
+    /// # Arguments
+    /// * `strategy` - The refresh strategy.
+    ///
+    /// # Example
+    /// ```rust
+    /// let service = JwtTokenService::new("mysecret", 3600, 86400)
+    ///     .with_refresh_strategy(RefreshStrategy::Reuse);
+    /// ```
+    pub fn with_refresh_strategy(mut self, strategy: RefreshStrategy) -> Self {
+        self.refresh_strategy = Some(strategy);
+        self
+    }
+
+    /// Returns the audience to embed in tokens generated with `options`.
+    fn audience_for(&self, options: &TokenOptions) -> Option<String> {
+        options
+            .audience
+            .clone()
+            .or_else(|| self.audiences.first().cloned())
+    }
+
+    /// Builds the JWT header emitted in every token.
+    fn header(&self) -> Header {
+        let mut header = Header::new(self.algorithm);
+        header.typ = self.typ.clone();
+        header.kid = self.kid.clone();
+        header
+    }
+
+    /// Selects the verification key for a token based on its `kid` header.
+    ///
+    /// Tokens without a `kid`, or carrying this service's own `kid`, use the primary key.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidToken`] if the header cannot be decoded or the `kid` is unknown.
+    fn decoding_key_for(&self, token: &str) -> Result<&DecodingKey, AuthError> {
+        let header = decode_header(token)
+            .map_err(|_| AuthError::InvalidToken("Invalid token format".to_string()))?;
+        match header.kid {
+            None => Ok(&self.decoding_key),
+            Some(kid) if self.kid.as_deref() == Some(kid.as_str()) => Ok(&self.decoding_key),
+            Some(kid) => self
+                .verification_keys
+                .get(&kid)
+                .ok_or_else(|| AuthError::InvalidToken(format!("Unknown key ID: {kid}"))),
+        }
+    }
+
+    /// Creates a new [`JwtTokenService`], rejecting secrets shorter than [`MIN_HMAC_SECRET_LEN`] bytes.
+    ///
+    /// # Arguments
+    /// * `secret` - The secret key used for signing and verifying tokens.
+    /// * `access_token_duration` - Access token validity duration in seconds.
+    /// * `refresh_token_duration` - Refresh token validity duration in seconds.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::ConfigError`] if the secret is too short.
+    pub fn try_new(
+        secret: &str,
+        access_token_duration: u64,
+        refresh_token_duration: u64,
+    ) -> Result<Self, AuthError> {
+        if secret.len() < MIN_HMAC_SECRET_LEN {
+            return Err(AuthError::ConfigError(format!(
+                "JWT HMAC secret must be at least {MIN_HMAC_SECRET_LEN} bytes long, got {}",
+                secret.len()
+            )));
+        }
+        Ok(Self::new(
+            secret,
+            access_token_duration,
+            refresh_token_duration,
+        ))
+    }
+
     /// Returns the current UNIX timestamp in seconds using chrono.
     ///
     /// # Errors
@@ -81,48 +459,162 @@ impl JwtTokenService {
     ///
     /// # Arguments
     /// * `user_id` - The user identifier to embed in the token claims.
+    /// * `options` - Additional values to embed in the token claims.
     ///
     /// # Errors
     /// Returns [`AuthError::TokenGeneration`] if token creation fails.
-    fn generate_access_token(&self, user_id: &str) -> Result<String, AuthError> {
+    fn generate_access_token(
+        &self,
+        user_id: &str,
+        options: &TokenOptions,
+    ) -> Result<String, AuthError> {
+        let claims = self.access_claims(user_id, options)?;
+        self.encode_access_claims(&claims)
+    }
+
+    /// Builds the claims of a new access token for the given user ID, with a formatted subject.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::TokenGeneration`] if the system clock is invalid.
+    fn access_claims(
+        &self,
+        user_id: &str,
+        options: &TokenOptions,
+    ) -> Result<AccessTokenClaims, AuthError> {
         let now = Self::current_timestamp()?;
         let expiration = now + self.access_token_duration as usize;
 
-        let claims = AccessTokenClaims {
-            sub: user_id.to_string(),
+        Ok(AccessTokenClaims {
+            sub: self.subject_formatter.format(user_id),
             exp: expiration,
             iat: now,
             token_type: "access".to_string(),
+            tenant_id: options.tenant_id.clone(),
+            sid: options.session_id.clone(),
+            aud: self.audience_for(options),
+            iss: self.issuer.clone(),
+            cnf: options
+                .fingerprint
+                .clone()
+                .map(|fingerprint| ConfirmationClaim { fingerprint }),
+            jti: Some(uuid::Uuid::new_v4().to_string()),
+            auth_time: options.auth_time,
+            amr: options.amr.clone(),
+            custom: options
+                .custom_claims
+                .iter()
+                .filter(|(name, _)| !RESERVED_CLAIMS.contains(&name.as_str()))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+        })
+    }
+
+    /// Signs access token claims.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::TokenGeneration`] if encoding fails.
+    fn encode_access_claims(&self, claims: &AccessTokenClaims) -> Result<String, AuthError> {
+        self.encode_claims(claims, "access")
+    }
+
+    /// Signs claims, compressing the payload if it exceeds the compression threshold.
+    ///
+    /// # Arguments
+    /// * `claims` - The claims to sign.
+    /// * `token_type` - The kind of token (`access` or `refresh`), for error messages.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::TokenGeneration`] if encoding fails.
+    fn encode_claims<C: serde::Serialize>(
+        &self,
+        claims: &C,
+        token_type: &str,
+    ) -> Result<String, AuthError> {
+        let encoding_error = |e: &dyn std::fmt::Display| {
+            AuthError::TokenGeneration(format!("Failed to encode {token_type} token: {e}"))
         };
+        #[cfg(feature = "jwt-compression")]
+        if let Some(token) = self.encode_compressed(claims, &encoding_error)? {
+            return Ok(token);
+        }
+        encode(&self.header(), claims, &self.encoding_key).map_err(|e| encoding_error(&e))
+    }
 
-        let header = Header::new(self.algorithm);
+    /// Signs claims with a compressed payload, if they exceed the compression threshold and
+    /// shrink when compressed.
+    ///
+    /// # Returns
+    /// The compressed token, or `None` if the claims are to be signed uncompressed.
+    #[cfg(feature = "jwt-compression")]
+    fn encode_compressed<C: serde::Serialize>(
+        &self,
+        claims: &C,
+        encoding_error: &dyn Fn(&dyn std::fmt::Display) -> AuthError,
+    ) -> Result<Option<String>, AuthError> {
+        let Some(threshold) = self.compression_threshold else {
+            return Ok(None);
+        };
+        let payload = serde_json::to_vec(claims).map_err(|e| encoding_error(&e))?;
+        let compressed = (payload.len() > threshold)
+            .then(|| miniz_oxide::deflate::compress_to_vec(&payload, 6))
+            .filter(|compressed| compressed.len() < payload.len());
+        let Some(compressed) = compressed else {
+            return Ok(None);
+        };
 
-        encode(&header, &claims, &self.encoding_key)
-            .map_err(|e| AuthError::TokenGeneration(format!("Failed to encode access token: {e}")))
+        let mut header = serde_json::to_value(self.header()).map_err(|e| encoding_error(&e))?;
+        header["zip"] = ZIP_DEFLATE.into();
+        let header = serde_json::to_vec(&header).map_err(|e| encoding_error(&e))?;
+        let message = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header),
+            URL_SAFE_NO_PAD.encode(compressed)
+        );
+        let signature =
+            jsonwebtoken::crypto::sign(message.as_bytes(), &self.encoding_key, self.algorithm)
+                .map_err(|e| encoding_error(&e))?;
+        Ok(Some(format!("{message}.{signature}")))
     }
 
     /// Generates a signed JWT refresh token for the given user ID.
     ///
     /// # Arguments
     /// * `user_id` - The user identifier to embed in the token claims.
+    /// * `options` - Additional values to embed in the token claims.
     ///
     /// # Errors
     /// Returns [`AuthError::TokenGeneration`] if token creation fails.
-    fn generate_refresh_token(&self, user_id: &str) -> Result<String, AuthError> {
+    fn generate_refresh_token(
+        &self,
+        user_id: &str,
+        options: &TokenOptions,
+    ) -> Result<String, AuthError> {
         let now = Self::current_timestamp()?;
-        let expiration = now + self.refresh_token_duration as usize;
+        let duration = options
+            .refresh_expiration
+            .unwrap_or(self.refresh_token_duration);
+        let expiration = now + duration as usize;
 
         let claims = RefreshTokenClaims {
-            sub: user_id.to_string(),
+            sub: self.subject_formatter.format(user_id),
             exp: expiration,
             iat: now,
             token_type: "refresh".to_string(),
+            tenant_id: options.tenant_id.clone(),
+            sid: options.session_id.clone(),
+            aud: self.audience_for(options),
+            iss: self.issuer.clone(),
+            cnf: options
+                .fingerprint
+                .clone()
+                .map(|fingerprint| ConfirmationClaim { fingerprint }),
+            jti: Some(uuid::Uuid::new_v4().to_string()),
+            auth_time: options.auth_time,
+            amr: options.amr.clone(),
+            rexp: options.refresh_expiration,
         };
 
-        let header = Header::new(self.algorithm);
-
-        encode(&header, &claims, &self.encoding_key)
-            .map_err(|e| AuthError::TokenGeneration(format!("Failed to encode refresh token: {e}")))
+        self.encode_claims(&claims, "refresh")
     }
 
     /// Validates a JWT and deserializes its claims.
@@ -134,25 +626,74 @@ impl JwtTokenService {
     /// * `token` - The JWT string to validate and decode.
     ///
     /// # Errors
-    /// Returns [`AuthError::TokenExpired`], [`AuthError::InvalidToken`], or [`AuthError::TokenValidation`] on failure.
+    /// Returns [`AuthError::TokenExpired`], [`AuthError::InvalidToken`], or [`AuthError::TokenValidation`] on failure,
+    /// including when the token's `kid` header does not match a known key or its audience is
+    /// not accepted.
     fn validate_token<T>(&self, token: &str) -> Result<T, AuthError>
     where
         T: serde::de::DeserializeOwned,
     {
-        let validation = Validation::new(self.algorithm);
+        self.decode_token(token, true)
+    }
+
+    /// Decodes a JWT, verifying its signature and audience, and its expiration if `check_expiry`.
+    ///
+    /// # Arguments
+    /// * `token` - The JWT string to validate and decode.
+    /// * `check_expiry` - Whether expired tokens are rejected.
+    ///
+    /// # Errors
+    /// See [`JwtTokenService::validate_token`].
+    fn decode_token<T>(&self, token: &str, check_expiry: bool) -> Result<T, AuthError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut validation = Validation::new(self.algorithm);
+        validation.validate_exp = check_expiry;
+        let mut required_claims = vec!["exp"];
+        if self.audiences.is_empty() {
+            validation.validate_aud = false;
+        } else {
+            validation.set_audience(&self.audiences);
+            required_claims.push("aud");
+        }
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+            required_claims.push("iss");
+        }
+        validation.set_required_spec_claims(&required_claims);
+        let decoding_key = self.decoding_key_for(token)?;
 
-        decode::<T>(token, &self.decoding_key, &validation)
+        let decoded = match InflatedToken::parse(token)? {
+            Some(inflated) => inflated.decode::<T>(decoding_key, &validation),
+            None => decode::<T>(token, decoding_key, &validation),
+        };
+        decoded
             .map(|token_data| token_data.claims)
-            .map_err(|e| match e.kind() {
-                jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::TokenExpired,
-                jsonwebtoken::errors::ErrorKind::InvalidToken => {
-                    AuthError::InvalidToken("Invalid token format".to_string())
-                }
-                jsonwebtoken::errors::ErrorKind::InvalidSignature => {
-                    AuthError::InvalidToken("Invalid token signature".to_string())
-                }
-                _ => AuthError::TokenValidation(format!("Token validation failed: {e}")),
-            })
+            .map_err(decode_error)
+    }
+
+    /// Validates a JWT as a refresh token, enforcing the `refresh` token type and parsing its subject.
+    ///
+    /// # Arguments
+    /// * `refresh_token` - The JWT refresh token string to validate.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidToken`] if the token is not a refresh token, or other token errors.
+    fn validate_refresh_claims(
+        &self,
+        refresh_token: &str,
+    ) -> Result<RefreshTokenClaims, AuthError> {
+        let mut refresh_claims: RefreshTokenClaims = self.validate_token(refresh_token)?;
+
+        if refresh_claims.token_type != "refresh" {
+            return Err(AuthError::InvalidToken(
+                "Expected refresh token".to_string(),
+            ));
+        }
+
+        refresh_claims.sub = self.subject_formatter.parse(&refresh_claims.sub)?;
+        Ok(refresh_claims)
     }
 }
 
@@ -166,8 +707,25 @@ impl TokenService for JwtTokenService {
     /// # Errors
     /// Returns [`AuthError::TokenGeneration`] if token creation fails.
     async fn generate_token_pair(&self, user_id: &str) -> Result<TokenPair, AuthError> {
-        let access_token = self.generate_access_token(user_id)?;
-        let refresh_token = self.generate_refresh_token(user_id)?;
+        self.generate_token_pair_with(user_id, &TokenOptions::default())
+            .await
+    }
+
+    /// Generates a new access and refresh token pair embedding the given options.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user identifier to embed in the token claims.
+    /// * `options` - Additional values (e.g., tenant) to embed in both tokens.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::TokenGeneration`] if token creation fails.
+    async fn generate_token_pair_with(
+        &self,
+        user_id: &str,
+        options: &TokenOptions,
+    ) -> Result<TokenPair, AuthError> {
+        let access_token = self.generate_access_token(user_id, options)?;
+        let refresh_token = self.generate_refresh_token(user_id, options)?;
 
         Ok(TokenPair {
             access_token,
@@ -175,8 +733,53 @@ impl TokenService for JwtTokenService {
         })
     }
 
+    /// Generates an access token without a refresh token.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user identifier to embed in the token claims.
+    /// * `options` - Additional values (e.g., tenant) to embed in the token.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::TokenGeneration`] if token creation fails.
+    async fn generate_access_token_with(
+        &self,
+        user_id: &str,
+        options: &TokenOptions,
+    ) -> Result<String, AuthError> {
+        self.generate_access_token(user_id, options)
+    }
+
+    /// Generates a new token pair, returning the access token claims without decoding the token.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user identifier to embed in the token claims.
+    /// * `options` - Additional values (e.g., tenant) to embed in both tokens.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::TokenGeneration`] if token creation fails.
+    async fn generate_token_pair_with_claims_out(
+        &self,
+        user_id: &str,
+        options: &TokenOptions,
+    ) -> Result<(TokenPair, AccessTokenClaims), AuthError> {
+        let mut claims = self.access_claims(user_id, options)?;
+        let access_token = self.encode_access_claims(&claims)?;
+        let refresh_token = self.generate_refresh_token(user_id, options)?;
+        claims.sub = user_id.to_string();
+
+        Ok((
+            TokenPair {
+                access_token,
+                refresh_token,
+            },
+            claims,
+        ))
+    }
+
     /// Validates an access token and returns its claims.
     ///
+    /// The returned subject is the user ID, parsed with the configured [`SubjectFormatter`].
+    ///
     /// # Arguments
     /// * `token` - The JWT access token string to validate.
     ///
@@ -186,26 +789,265 @@ impl TokenService for JwtTokenService {
         &self,
         token: &str,
     ) -> Result<Box<dyn Claims + Send + Sync>, AuthError> {
-        let claims: AccessTokenClaims = self.validate_token(token)?;
+        let mut claims: AccessTokenClaims = self.validate_token(token)?;
+        self.unknown_claims.check(&claims)?;
+        claims.sub = self.subject_formatter.parse(&claims.sub)?;
+        Ok(Box::new(claims))
+    }
+
+    /// Validates an access token, accepting it if it has expired, and returns its claims.
+    ///
+    /// # Arguments
+    /// * `token` - The JWT access token string to validate.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidToken`] or [`AuthError::TokenValidation`] on failure.
+    async fn validate_expired_access_token(
+        &self,
+        token: &str,
+    ) -> Result<Box<dyn Claims + Send + Sync>, AuthError> {
+        let mut claims: AccessTokenClaims = self.decode_token(token, false)?;
+        self.unknown_claims.check(&claims)?;
+        claims.sub = self.subject_formatter.parse(&claims.sub)?;
         Ok(Box::new(claims))
     }
 
     /// Validates a refresh token and generates a new token pair if valid.
     ///
+    /// The new tokens keep the tenant, session, audience, client binding and authentication
+    /// time and methods of the refresh token. With [`RefreshStrategy::Reuse`], the returned
+    /// refresh token is the presented one.
+    ///
     /// # Arguments
     /// * `refresh_token` - The JWT refresh token string to validate.
     ///
     /// # Errors
     /// Returns [`AuthError::InvalidToken`] if the token is not a refresh token, or other token errors.
     async fn refresh_access_token(&self, refresh_token: &str) -> Result<TokenPair, AuthError> {
-        let refresh_claims: RefreshTokenClaims = self.validate_token(refresh_token)?;
+        let refresh_claims = self.validate_refresh_claims(refresh_token)?;
+        let options = TokenOptions {
+            tenant_id: refresh_claims.tenant_id,
+            session_id: refresh_claims.sid,
+            audience: refresh_claims.aud,
+            fingerprint: refresh_claims.cnf.map(|cnf| cnf.fingerprint),
+            auth_time: refresh_claims.auth_time,
+            amr: refresh_claims.amr,
+            // Overridden lifetimes are carried over; default ones follow the current configuration
+            refresh_expiration: refresh_claims.rexp,
+            ..Default::default()
+        };
+        if self.refresh_strategy == Some(RefreshStrategy::Reuse) {
+            return Ok(TokenPair {
+                access_token: self.generate_access_token(&refresh_claims.sub, &options)?,
+                refresh_token: refresh_token.to_string(),
+            });
+        }
+        self.generate_token_pair_with(&refresh_claims.sub, &options)
+            .await
+    }
 
-        if refresh_claims.token_type != "refresh" {
-            return Err(AuthError::InvalidToken(
-                "Expected refresh token".to_string(),
-            ));
+    /// Refreshes an access token, issuing the new tokens with `options`.
+    ///
+    /// With [`RefreshStrategy::Reuse`], the presented refresh token is returned along with the
+    /// new access token.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidToken`] if the token is not a refresh token, or other token errors.
+    async fn refresh_access_token_with(
+        &self,
+        refresh_token: &str,
+        user_id: &str,
+        options: &TokenOptions,
+    ) -> Result<TokenPair, AuthError> {
+        self.validate_refresh_claims(refresh_token)?;
+        if self.refresh_strategy == Some(RefreshStrategy::Reuse) {
+            return Ok(TokenPair {
+                access_token: self.generate_access_token(user_id, options)?,
+                refresh_token: refresh_token.to_string(),
+            });
+        }
+        self.generate_token_pair_with(user_id, options).await
+    }
+
+    /// Validates a refresh token and returns its claims without issuing new tokens.
+    ///
+    /// # Arguments
+    /// * `refresh_token` - The JWT refresh token string to validate.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidToken`] if the token is not a refresh token, or other token errors.
+    async fn validate_refresh_token(
+        &self,
+        refresh_token: &str,
+    ) -> Result<Box<dyn Claims + Send + Sync>, AuthError> {
+        let claims = self.validate_refresh_claims(refresh_token)?;
+        Ok(Box::new(claims))
+    }
+
+    /// Returns the public key as a single-key JWKS for RS256 and ES256, or a reference to the
+    /// HMAC secret (its `kid`) for HS256.
+    ///
+    /// Only the signing key is described: verification keys added with
+    /// [`JwtTokenService::with_verification_key`] are not part of the material.
+    fn verification_material(&self) -> Result<VerificationMaterial, AuthError> {
+        match &self.public_key {
+            Some(public_key) => {
+                let mut public_key = public_key.clone();
+                public_key.common.key_id = self.kid.clone();
+                Ok(VerificationMaterial::PublicJwks(JwkSet {
+                    keys: vec![public_key],
+                }))
+            }
+            None => Ok(VerificationMaterial::HmacSecretRef {
+                key_id: self.kid.clone(),
+            }),
+        }
+    }
+
+    fn refresh_strategy(&self) -> Option<RefreshStrategy> {
+        self.refresh_strategy
+    }
+}
+
+/// Maps a JWT decoding failure to the matching [`AuthError`].
+pub(crate) fn decode_error(e: jsonwebtoken::errors::Error) -> AuthError {
+    match e.kind() {
+        jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::TokenExpired,
+        jsonwebtoken::errors::ErrorKind::InvalidToken => {
+            AuthError::InvalidToken("Invalid token format".to_string())
+        }
+        jsonwebtoken::errors::ErrorKind::InvalidSignature => {
+            AuthError::InvalidToken("Invalid token signature".to_string())
+        }
+        jsonwebtoken::errors::ErrorKind::InvalidAudience => {
+            AuthError::InvalidToken("Token was issued for another audience".to_string())
+        }
+        jsonwebtoken::errors::ErrorKind::MissingRequiredClaim(claim) if claim == "aud" => {
+            AuthError::InvalidToken("Token has no audience".to_string())
+        }
+        jsonwebtoken::errors::ErrorKind::InvalidIssuer => {
+            AuthError::InvalidToken("Token was issued by another issuer".to_string())
+        }
+        jsonwebtoken::errors::ErrorKind::MissingRequiredClaim(claim) if claim == "iss" => {
+            AuthError::InvalidToken("Token has no issuer".to_string())
+        }
+        _ => AuthError::TokenValidation(format!("Token validation failed: {e}")),
+    }
+}
+
+/// A token with a DEFLATE-compressed payload, inflated back to a standard JWT.
+pub(crate) struct InflatedToken {
+    /// The algorithm of the `alg` header.
+    algorithm: Algorithm,
+    /// The `<header>.<payload>` segments of the original token, covered by its signature.
+    message: String,
+    /// The signature of the original token.
+    signature: String,
+    /// The token rebuilt with the inflated payload, carrying the original signature.
+    token: String,
+}
+
+impl InflatedToken {
+    /// Inflates `token` if its header has a `zip` parameter.
+    ///
+    /// # Returns
+    /// The inflated token, or `None` if the token is not compressed.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidToken`] if the token is malformed, uses an unsupported
+    /// compression algorithm (any, without the `jwt-compression` feature), or inflates to more
+    /// than [`MAX_INFLATED_PAYLOAD_LEN`] bytes.
+    pub(crate) fn parse(token: &str) -> Result<Option<Self>, AuthError> {
+        let malformed = || AuthError::InvalidToken("Invalid token format".to_string());
+        let mut segments = token.split('.');
+        let (Some(header), Some(payload), Some(signature), None) = (
+            segments.next(),
+            segments.next(),
+            segments.next(),
+            segments.next(),
+        ) else {
+            return Err(malformed());
+        };
+        let raw_header: serde_json::Value = URL_SAFE_NO_PAD
+            .decode(header)
+            .ok()
+            .and_then(|header| serde_json::from_slice(&header).ok())
+            .ok_or_else(malformed)?;
+        match raw_header.get("zip") {
+            None => return Ok(None),
+            Some(zip) if zip == ZIP_DEFLATE => {}
+            Some(zip) => {
+                return Err(AuthError::InvalidToken(format!(
+                    "Unsupported token compression: {zip}"
+                )));
+            }
         }
 
-        self.generate_token_pair(&refresh_claims.sub).await
+        let algorithm = decode_header(token).map_err(|_| malformed())?.alg;
+        let compressed = URL_SAFE_NO_PAD.decode(payload).map_err(|_| malformed())?;
+        let inflated = Self::inflate(&compressed)?;
+        Ok(Some(Self {
+            algorithm,
+            message: format!("{header}.{payload}"),
+            signature: signature.to_string(),
+            token: format!("{header}.{}.{signature}", URL_SAFE_NO_PAD.encode(inflated)),
+        }))
     }
+
+    /// Inflates a DEFLATE-compressed payload of at most [`MAX_INFLATED_PAYLOAD_LEN`] bytes.
+    #[cfg(feature = "jwt-compression")]
+    fn inflate(compressed: &[u8]) -> Result<Vec<u8>, AuthError> {
+        miniz_oxide::inflate::decompress_to_vec_with_limit(compressed, MAX_INFLATED_PAYLOAD_LEN)
+            .map_err(|_| AuthError::InvalidToken("Invalid compressed token payload".to_string()))
+    }
+
+    /// Rejects compressed payloads, which require the `jwt-compression` feature.
+    #[cfg(not(feature = "jwt-compression"))]
+    fn inflate(_compressed: &[u8]) -> Result<Vec<u8>, AuthError> {
+        Err(AuthError::InvalidToken(format!(
+            "Unsupported token compression: {ZIP_DEFLATE} (requires the `jwt-compression` feature)"
+        )))
+    }
+
+    /// Verifies the signature of the compressed token, then decodes and validates its claims.
+    ///
+    /// # Arguments
+    /// * `key` - The key verifying the signature.
+    /// * `validation` - The checks applied to the token, as for [`decode`].
+    pub(crate) fn decode<T>(
+        &self,
+        key: &DecodingKey,
+        validation: &Validation,
+    ) -> jsonwebtoken::errors::Result<TokenData<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        use jsonwebtoken::errors::ErrorKind;
+
+        if !validation.algorithms.contains(&self.algorithm) {
+            return Err(ErrorKind::InvalidAlgorithm.into());
+        }
+        if !jsonwebtoken::crypto::verify(
+            &self.signature,
+            self.message.as_bytes(),
+            key,
+            self.algorithm,
+        )? {
+            return Err(ErrorKind::InvalidSignature.into());
+        }
+
+        // The signature covers the compressed payload, checked above
+        let mut validation = validation.clone();
+        validation.insecure_disable_signature_validation();
+        decode::<T>(&self.token, key, &validation)
+    }
+}
+
+/// Returns the JSON payload of a JWT without verifying it, inflating compressed payloads.
+pub(crate) fn unverified_payload(token: &str) -> Option<Vec<u8>> {
+    let token = match InflatedToken::parse(token).ok()? {
+        Some(inflated) => inflated.token,
+        None => token.to_string(),
+    };
+    URL_SAFE_NO_PAD.decode(token.split('.').nth(1)?).ok()
 }
@@ -0,0 +1,118 @@
+//! Key material resource servers need to verify access tokens.
+//!
+//! Deployment tooling provisioning resource servers should not have to know which
+//! [`TokenService`](super::TokenService) issues the tokens: a key pair service publishes its
+//! public keys, while an HMAC service shares a secret that must never leave the secret store.
+//! [`TokenService::verification_material`](super::TokenService::verification_material)
+//! describes both uniformly as a [`VerificationMaterial`].
+//!
+//! HMAC secrets are never exported, nor is any value computed from them: a fingerprint of the
+//! secret would let anyone holding it test guesses of the secret offline. Only the `kid` of the
+//! secret is, so tooling can check that a resource server was provisioned with the secret of
+//! that ID.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::Algorithm;
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, EllipticCurveKeyParameters,
+    EllipticCurveKeyType, Jwk, JwkSet, KeyAlgorithm, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use simple_asn1::ASN1Block;
+
+use crate::error::AuthError;
+
+/// What verifiers need to check the access tokens of a token service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationMaterial {
+    /// Tokens are signed with a shared HMAC secret, which verifiers must obtain from the secret
+    /// store the issuer reads it from.
+    HmacSecretRef {
+        /// The `kid` header of issued tokens, naming the secret in the store, if any.
+        key_id: Option<String>,
+    },
+    /// Tokens are signed with a private key; the set holds the public keys verifying them, ready
+    /// to be served as a JWKS or loaded into an
+    /// [`OfflineVerifier`](super::offline::OfflineVerifier).
+    PublicJwks(JwkSet),
+}
+
+/// Builds the JWK of a PEM-encoded public key.
+///
+/// RSA keys may be SubjectPublicKeyInfo (`PUBLIC KEY`) or PKCS#1 (`RSA PUBLIC KEY`) documents;
+/// P-256 keys must be SubjectPublicKeyInfo documents.
+///
+/// # Arguments
+/// * `public_key_pem` - The PEM-encoded public key.
+/// * `algorithm` - The algorithm the key verifies, `RS256` or `ES256`.
+///
+/// # Errors
+/// Returns [`AuthError::ConfigError`] if the key cannot be parsed or does not match the
+/// algorithm.
+pub(crate) fn public_jwk(public_key_pem: &[u8], algorithm: Algorithm) -> Result<Jwk, AuthError> {
+    let invalid = |reason: &str| AuthError::ConfigError(format!("Invalid public key: {reason}"));
+    let pem = pem::parse(public_key_pem).map_err(|e| invalid(&e.to_string()))?;
+    let der = if pem.tag() == "RSA PUBLIC KEY" {
+        pem.contents().to_vec()
+    } else {
+        subject_public_key(pem.contents()).ok_or_else(|| invalid("malformed key info"))?
+    };
+
+    let (key_algorithm, parameters) = match algorithm {
+        Algorithm::RS256 => {
+            let (n, e) = rsa_components(&der).ok_or_else(|| invalid("malformed RSA key"))?;
+            let parameters = AlgorithmParameters::RSA(RSAKeyParameters {
+                key_type: RSAKeyType::RSA,
+                n: URL_SAFE_NO_PAD.encode(n),
+                e: URL_SAFE_NO_PAD.encode(e),
+            });
+            (KeyAlgorithm::RS256, parameters)
+        }
+        Algorithm::ES256 => {
+            // An uncompressed point: 0x04 followed by the 32-byte coordinates
+            if der.len() != 65 || der[0] != 0x04 {
+                return Err(invalid("not an uncompressed P-256 point"));
+            }
+            let parameters = AlgorithmParameters::EllipticCurve(EllipticCurveKeyParameters {
+                key_type: EllipticCurveKeyType::EC,
+                curve: EllipticCurve::P256,
+                x: URL_SAFE_NO_PAD.encode(&der[1..33]),
+                y: URL_SAFE_NO_PAD.encode(&der[33..]),
+            });
+            (KeyAlgorithm::ES256, parameters)
+        }
+        other => return Err(invalid(&format!("unsupported algorithm {other:?}"))),
+    };
+    Ok(Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(key_algorithm),
+            ..Default::default()
+        },
+        algorithm: parameters,
+    })
+}
+
+/// Extracts the `subjectPublicKey` bits of a DER-encoded SubjectPublicKeyInfo.
+fn subject_public_key(der: &[u8]) -> Option<Vec<u8>> {
+    match simple_asn1::from_der(der).ok()?.first()? {
+        ASN1Block::Sequence(_, fields) => match fields.get(1)? {
+            ASN1Block::BitString(_, _, bits) => Some(bits.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Extracts the big-endian modulus and exponent of a DER-encoded PKCS#1 RSA public key.
+fn rsa_components(der: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    match simple_asn1::from_der(der).ok()?.first()? {
+        ASN1Block::Sequence(_, fields) => match (fields.first()?, fields.get(1)?) {
+            (ASN1Block::Integer(_, n), ASN1Block::Integer(_, e)) => {
+                Some((n.to_bytes_be().1, e.to_bytes_be().1))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
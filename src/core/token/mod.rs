@@ -8,9 +8,22 @@
 //! # Overview
 //!
 //! - **TokenPair**: Represents a pair of access and refresh tokens.
+//! - **TokenOptions**: Optional values embedded in generated tokens (e.g., tenant).
+//! - **IntrospectionResult**: RFC 7662-style description of a token's state.
 //! - **TokenService**: Trait for generating, validating, and refreshing tokens.
+//! - **cache**: Submodule for caching access token validations.
 //! - **claims**: Submodule for token claims definitions.
+//! - **cookie**: Submodule for rendering token pairs as cookies and reading them back.
+//! - **enricher**: Submodule for computing custom claims at issuance time.
 //! - **jwt**: Submodule for JWT-specific logic.
+//! - **material**: Submodule for describing the keys verifiers need.
+//! - **offline**: Submodule for verifying access tokens against a cached JWKS.
+//! - **one_time**: Submodule for tracking the consumption of single-use tokens.
+//! - **opaque**: Submodule for opaque, single-use refresh tokens.
+//! - **refresh**: Submodule for choosing whether refreshes rotate refresh tokens.
+//! - **session**: Submodule for tracking and revoking sessions.
+//! - **subject**: Submodule for mapping user IDs to and from the `sub` claim.
+//! - **transformer**: Submodule for transforming claims at validation time.
 //!
 //! # Example
 //!
@@ -43,12 +56,107 @@ use crate::error::AuthError;
 ///
 /// - `access_token`: The short-lived token used for authenticating requests.
 /// - `refresh_token`: The long-lived token used to refresh the access token.
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct TokenPair {
     pub access_token: String,
     pub refresh_token: String,
 }
 
+/// The result of introspecting a token, modeled on RFC 7662.
+///
+/// Inactive tokens (invalid, expired, or otherwise rejected) are reported with `active: false`
+/// and every other field empty, so callers never learn why a token was rejected.
+///
+/// # Fields
+///
+/// - `active`: Whether the token is currently valid.
+/// - `sub`: The subject (user ID) of the token.
+/// - `exp`: The expiration timestamp (UNIX timestamp, seconds).
+/// - `iat`: The issued-at timestamp (UNIX timestamp, seconds).
+/// - `token_type`: The token type (e.g., `access` or `refresh`).
+/// - `scope`: The space-separated scopes granted to the token.
+/// - `roles`: The roles granted to the subject.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct IntrospectionResult {
+    pub active: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+}
+
+impl IntrospectionResult {
+    /// Returns the result describing an inactive token.
+    pub fn inactive() -> Self {
+        Self::default()
+    }
+
+    /// Builds the result describing an active token from its claims.
+    ///
+    /// # Arguments
+    ///
+    /// * `claims` - The validated claims of the token.
+    pub fn from_claims(claims: &(dyn crate::core::token::claims::Claims + Send + Sync)) -> Self {
+        Self {
+            active: true,
+            sub: Some(claims.get_subject().to_string()),
+            exp: Some(claims.get_expiration()),
+            iat: claims.get_issued_at(),
+            token_type: claims.get_token_type().map(str::to_string),
+            scope: claims.get_scope().map(str::to_string),
+            roles: claims.get_roles().to_vec(),
+        }
+    }
+}
+
+/// Default number of tokens `AuthService::validate_many` validates at once.
+pub const DEFAULT_VALIDATION_CONCURRENCY: u32 = 8;
+
+/// Optional values to embed in a generated token pair.
+///
+/// Passed to [`TokenService::generate_token_pair_with`]. The default value embeds nothing
+/// beyond the standard claims.
+///
+/// # Fields
+///
+/// - `tenant_id`: The tenant the tokens are issued for, if any.
+/// - `session_id`: The session the tokens belong to, if any.
+/// - `audience`: The application the tokens are issued for, if any.
+/// - `fingerprint`: The client fingerprint the tokens are bound to, if any.
+/// - `custom_claims`: Additional claims to embed in the access token.
+/// - `auth_time`, `amr`: When and how the user authenticated, if known.
+/// - `refresh_expiration`: The lifetime of the refresh token, if it differs from the default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenOptions {
+    /// The tenant the tokens are issued for, if any.
+    pub tenant_id: Option<String>,
+    /// The session the tokens belong to (the `sid` claim), used to revoke them together.
+    pub session_id: Option<String>,
+    /// The application the tokens are issued for (the `aud` claim). `None` lets the token
+    /// service pick its default audience, if it has one.
+    pub audience: Option<String>,
+    /// The client fingerprint the tokens are bound to (the `cnf` claim), if any.
+    pub fingerprint: Option<String>,
+    /// Additional claims embedded in the access token. Keys colliding with the standard
+    /// claims are ignored.
+    pub custom_claims: serde_json::Map<String, serde_json::Value>,
+    /// When the user authenticated (the `auth_time` claim, UNIX timestamp in seconds), if known.
+    pub auth_time: Option<usize>,
+    /// The methods the user authenticated with (the `amr` claim), e.g. `["pwd"]`.
+    pub amr: Vec<String>,
+    /// The lifetime (in seconds) of the refresh token, overriding the token service's default
+    /// (e.g., for "remember me" logins). Kept by refreshes.
+    pub refresh_expiration: Option<u64>,
+}
+
 /// Trait for token service operations.
 ///
 /// This trait abstracts the main operations required for token-based authentication systems.
@@ -84,6 +192,79 @@ pub trait TokenService: Send + Sync {
     /// * `Err(AuthError)` if token generation fails.
     async fn generate_token_pair(&self, user_id: &str) -> Result<TokenPair, AuthError>;
 
+    /// Generates a new token pair for a given user, embedding the given options.
+    ///
+    /// The default implementation ignores the options and delegates to
+    /// [`TokenService::generate_token_pair`].
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - The unique identifier of the user for whom the tokens are generated.
+    /// * `options` - Additional values to embed in the tokens.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TokenPair)` containing the access and refresh tokens if successful.
+    /// * `Err(AuthError)` if token generation fails.
+    async fn generate_token_pair_with(
+        &self,
+        user_id: &str,
+        options: &TokenOptions,
+    ) -> Result<TokenPair, AuthError> {
+        let _ = options;
+        self.generate_token_pair(user_id).await
+    }
+
+    /// Generates an access token for a given user, without a refresh token.
+    ///
+    /// The default implementation generates a token pair with
+    /// [`TokenService::generate_token_pair_with`] and drops its refresh token; implementations
+    /// should override it to skip generating the refresh token.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - The unique identifier of the user for whom the token is generated.
+    /// * `options` - Additional values to embed in the token.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` containing the access token if successful.
+    /// * `Err(AuthError)` if token generation fails.
+    async fn generate_access_token_with(
+        &self,
+        user_id: &str,
+        options: &TokenOptions,
+    ) -> Result<String, AuthError> {
+        let pair = self.generate_token_pair_with(user_id, options).await?;
+        Ok(pair.access_token)
+    }
+
+    /// Generates a new token pair for a given user, also returning the claims of the access token.
+    ///
+    /// Saves callers a decode when they need the expiration or `jti` of the issued token. The
+    /// returned subject is the user ID, as returned by [`TokenService::validate_access_token`].
+    /// The default implementation validates the generated access token to obtain its claims.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - The unique identifier of the user for whom the tokens are generated.
+    /// * `options` - Additional values to embed in the tokens.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((TokenPair, AccessTokenClaims))` with the tokens and the access token claims.
+    /// * `Err(AuthError)` if token generation fails.
+    async fn generate_token_pair_with_claims_out(
+        &self,
+        user_id: &str,
+        options: &TokenOptions,
+    ) -> Result<(TokenPair, crate::core::token::claims::AccessTokenClaims), AuthError> {
+        let pair = self.generate_token_pair_with(user_id, options).await?;
+        let claims = self.validate_access_token(&pair.access_token).await?;
+        let claims = crate::core::token::claims::AccessTokenClaims::from_claims(claims.as_ref());
+        Ok((pair, claims))
+    }
+
     /// Validates an access token and extracts its claims.
     ///
     /// # Arguments
@@ -99,6 +280,30 @@ pub trait TokenService: Send + Sync {
         token: &str,
     ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError>;
 
+    /// Validates an access token like [`TokenService::validate_access_token`], but accepts
+    /// it if it has expired.
+    ///
+    /// Used to identify the owner of an access token presented alongside a refresh token. The
+    /// default implementation reports the operation as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The access token string to validate.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Box<dyn Claims>)` containing the extracted claims if the token is authentic.
+    /// * `Err(AuthError)` if the token is invalid.
+    async fn validate_expired_access_token(
+        &self,
+        token: &str,
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        let _ = token;
+        Err(AuthError::NotImplemented(
+            "expired access token validation is not supported by this token service".to_string(),
+        ))
+    }
+
     /// Refreshes an access token using a refresh token.
     ///
     /// # Arguments
@@ -110,14 +315,150 @@ pub trait TokenService: Send + Sync {
     /// * `Ok(TokenPair)` containing the new access and refresh tokens if successful.
     /// * `Err(AuthError)` if the refresh token is invalid or expired.
     async fn refresh_access_token(&self, refresh_token: &str) -> Result<TokenPair, AuthError>;
+
+    /// Refreshes an access token using a refresh token, issuing the new tokens with the given
+    /// options instead of those of the refresh token.
+    ///
+    /// Like [`TokenService::refresh_access_token`], the refresh token is consumed by services
+    /// tracking their use. The default implementation refreshes with
+    /// [`TokenService::refresh_access_token`] and replaces the access token of the new pair
+    /// with one generated from `options`.
+    ///
+    /// # Arguments
+    ///
+    /// * `refresh_token` - The refresh token string used to obtain a new token pair.
+    /// * `user_id` - The user the refresh token was issued to.
+    /// * `options` - The values to embed in the new tokens.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TokenPair)` containing the new access and refresh tokens if successful.
+    /// * `Err(AuthError)` if the refresh token is invalid, expired or already used.
+    async fn refresh_access_token_with(
+        &self,
+        refresh_token: &str,
+        user_id: &str,
+        options: &TokenOptions,
+    ) -> Result<TokenPair, AuthError> {
+        let mut pair = self.refresh_access_token(refresh_token).await?;
+        pair.access_token = self.generate_access_token_with(user_id, options).await?;
+        Ok(pair)
+    }
+
+    /// Validates a refresh token without consuming or rotating it.
+    ///
+    /// Implementations must verify the signature, the expiration and that the token is
+    /// actually a refresh token. The default implementation reports the operation as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `refresh_token` - The refresh token string to validate.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Box<dyn Claims>)` containing the refresh token claims if the token is valid.
+    /// * `Err(AuthError)` if the token is invalid, expired, or not a refresh token.
+    async fn validate_refresh_token(
+        &self,
+        refresh_token: &str,
+    ) -> Result<Box<dyn crate::core::token::claims::Claims + Send + Sync>, AuthError> {
+        let _ = refresh_token;
+        Err(AuthError::NotImplemented(
+            "refresh token validation is not supported by this token service".to_string(),
+        ))
+    }
+
+    /// Describes what verifiers need to check the access tokens of this service.
+    ///
+    /// Implementations must never return a raw HMAC secret (only a reference to it, see
+    /// [`VerificationMaterial::HmacSecretRef`](crate::core::token::material::VerificationMaterial::HmacSecretRef)). The default implementation reports the
+    /// operation as unsupported.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(VerificationMaterial)` describing the verification keys.
+    /// * `Err(AuthError)` if the service cannot describe them.
+    fn verification_material(
+        &self,
+    ) -> Result<crate::core::token::material::VerificationMaterial, AuthError> {
+        Err(AuthError::NotImplemented(
+            "verification material is not supported by this token service".to_string(),
+        ))
+    }
+
+    /// Returns whether refreshes rotate refresh tokens, and how `AuthService` detects their
+    /// reuse (see [`RefreshStrategy`](crate::core::token::refresh::RefreshStrategy)).
+    ///
+    /// The default implementation returns `None`: reuse is handled by the service itself, if
+    /// at all, and `AuthService` does not track consumed refresh tokens.
+    fn refresh_strategy(&self) -> Option<crate::core::token::refresh::RefreshStrategy> {
+        None
+    }
 }
 
+/// Submodule for caching access token validations.
+///
+/// Contains the [`ValidationCache`](cache::ValidationCache) used by `AuthService`.
+pub mod cache;
+
 /// Submodule for default claims for JWTs.
 ///
 /// Contains traits and types for representing and validating claims in tokens.
 pub mod claims;
 
+/// Submodule for cookie serialization of token pairs.
+///
+/// Contains the [`CookieOptions`](cookie::CookieOptions) used to render `Set-Cookie` headers.
+pub mod cookie;
+
+/// Submodule for issuance-time claims enrichment.
+///
+/// Contains the [`ClaimsEnricher`](enricher::ClaimsEnricher) trait and its no-op default.
+pub mod enricher;
+
 /// Submodule for JWT implementation and utilities.
 ///
 /// Contains logic for encoding, decoding, and verifying JWTs.
 pub mod jwt;
+
+/// Submodule for verification material of token services.
+///
+/// Describes what resource servers need to verify access tokens, without exporting secrets.
+pub mod material;
+
+/// Submodule for offline access token verification.
+///
+/// Contains the [`OfflineVerifier`](offline::OfflineVerifier), checking tokens against the
+/// public keys of a JWKS.
+pub mod offline;
+
+/// Submodule for single-use token tracking.
+///
+/// Contains the [`OneTimeTokenStore`](one_time::OneTimeTokenStore) trait and its in-memory implementation.
+pub mod one_time;
+
+/// Submodule for opaque, single-use refresh tokens.
+///
+/// Contains the [`OpaqueRefreshTokenService`](opaque::OpaqueRefreshTokenService), the
+/// [`RefreshTokenStore`](opaque::RefreshTokenStore) trait and its in-memory implementation.
+pub mod opaque;
+
+/// Submodule for refresh token rotation strategies.
+///
+/// Contains the [`RefreshStrategy`](refresh::RefreshStrategy) enum.
+pub mod refresh;
+
+/// Submodule for session tracking and revocation.
+///
+/// Contains the [`SessionStore`](session::SessionStore) trait and its in-memory implementation.
+pub mod session;
+
+/// Submodule for validation-time claims transformation.
+///
+/// Contains the [`ClaimsTransformer`](transformer::ClaimsTransformer) trait and its identity default.
+pub mod transformer;
+
+/// Submodule for token subject formatting.
+///
+/// Contains the [`SubjectFormatter`](subject::SubjectFormatter) trait and its default implementations.
+pub mod subject;
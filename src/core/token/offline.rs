@@ -0,0 +1,233 @@
+//! Offline verification of access tokens against a cached JWKS.
+//!
+//! Resource servers (e.g., edge services) often cannot call the authentication server or
+//! reach its user repository. When the [`JwtTokenService`](super::jwt::JwtTokenService) signs
+//! with a key pair ([`from_rsa_pem`](super::jwt::JwtTokenService::from_rsa_pem) or
+//! [`from_ec_pem`](super::jwt::JwtTokenService::from_ec_pem)), its public keys can be
+//! published as a JWKS, and an [`OfflineVerifier`] built from a cached copy checks access
+//! tokens with no network or storage access.
+//!
+//! The verifier only checks what the token itself proves: its signature, expiration, issuer,
+//! audience and type. Revocations and user status changes are not visible offline, so
+//! resource servers relying on it should keep access token lifetimes short.
+
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, JwkSet, KeyAlgorithm};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+
+use super::claims::{AccessTokenClaims, UnknownClaimsPolicy};
+use crate::error::AuthError;
+
+/// A public key of the JWKS.
+struct VerificationKey {
+    /// The `kid` of the key, if any.
+    kid: Option<String>,
+    /// The algorithm the key verifies.
+    algorithm: Algorithm,
+    /// The key itself.
+    key: DecodingKey,
+}
+
+/// Verifies RS256 and ES256 access tokens using only the public keys of a JWKS.
+///
+/// # Example
+/// ```rust,ignore
+/// let verifier = OfflineVerifier::from_jwks(&cached_jwks)?
+///     .with_issuer("https://auth.example.com")
+///     .with_audience("billing");
+/// let claims = verifier.validate(&access_token)?;
+/// ```
+pub struct OfflineVerifier {
+    /// The usable keys of the JWKS.
+    keys: Vec<VerificationKey>,
+    /// The issuer required in the `iss` claim, if any.
+    issuer: Option<String>,
+    /// The audiences accepted in the `aud` claim. Empty accepts any audience.
+    audiences: Vec<String>,
+    /// How unknown claims are treated.
+    unknown_claims: UnknownClaimsPolicy,
+}
+
+impl std::fmt::Debug for OfflineVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OfflineVerifier")
+            .field(
+                "kids",
+                &self.keys.iter().map(|key| &key.kid).collect::<Vec<_>>(),
+            )
+            .field("issuer", &self.issuer)
+            .field("audiences", &self.audiences)
+            .finish()
+    }
+}
+
+impl OfflineVerifier {
+    /// Creates a verifier from a JWKS document.
+    ///
+    /// RSA keys verify RS256 tokens and P-256 keys verify ES256 tokens. Other keys (including
+    /// symmetric ones, which must never be published) are ignored.
+    ///
+    /// # Arguments
+    /// * `jwks` - The JSON Web Key Set, as served by the authentication server.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::ConfigError`] if the document cannot be parsed or contains no
+    /// usable key.
+    pub fn from_jwks(jwks: &str) -> Result<Self, AuthError> {
+        let jwks: JwkSet = serde_json::from_str(jwks)
+            .map_err(|e| AuthError::ConfigError(format!("Invalid JWKS: {e}")))?;
+
+        let mut keys = Vec::new();
+        for jwk in &jwks.keys {
+            let algorithm = match (&jwk.algorithm, &jwk.common.key_algorithm) {
+                (AlgorithmParameters::RSA(_), None | Some(KeyAlgorithm::RS256)) => Algorithm::RS256,
+                (AlgorithmParameters::EllipticCurve(params), None | Some(KeyAlgorithm::ES256))
+                    if params.curve == EllipticCurve::P256 =>
+                {
+                    Algorithm::ES256
+                }
+                _ => {
+                    log::debug!(
+                        "Ignoring JWKS key {:?}: unsupported key type or algorithm",
+                        jwk.common.key_id
+                    );
+                    continue;
+                }
+            };
+            let key = DecodingKey::from_jwk(jwk)
+                .map_err(|e| AuthError::ConfigError(format!("Invalid JWKS key: {e}")))?;
+            keys.push(VerificationKey {
+                kid: jwk.common.key_id.clone(),
+                algorithm,
+                key,
+            });
+        }
+
+        if keys.is_empty() {
+            return Err(AuthError::ConfigError(
+                "JWKS contains no RS256 or ES256 key".to_string(),
+            ));
+        }
+        Ok(Self {
+            keys,
+            issuer: None,
+            audiences: Vec::new(),
+            unknown_claims: UnknownClaimsPolicy::default(),
+        })
+    }
+
+    /// Requires tokens to carry `issuer` in their `iss` claim.
+    ///
+    /// # Arguments
+    /// * `issuer` - The issuer configured with
+    ///   [`JwtTokenService::with_issuer`](super::jwt::JwtTokenService::with_issuer).
+    pub fn with_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Adds an audience accepted in the `aud` claim.
+    ///
+    /// Once an audience is configured, tokens without an `aud` claim or issued for another
+    /// audience are rejected.
+    ///
+    /// # Arguments
+    /// * `audience` - The audience, typically the name of this resource server.
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audiences.push(audience.into());
+        self
+    }
+
+    /// Sets how unknown claims are treated, as with
+    /// [`JwtTokenService::with_unknown_claims`](super::jwt::JwtTokenService::with_unknown_claims).
+    ///
+    /// # Arguments
+    /// * `policy` - The policy, [`UnknownClaimsPolicy::Lenient`] by default.
+    pub fn with_unknown_claims(mut self, policy: UnknownClaimsPolicy) -> Self {
+        self.unknown_claims = policy;
+        self
+    }
+
+    /// Validates an access token.
+    ///
+    /// Tokens carrying a `kid` header are verified with the key of that ID; tokens without
+    /// one are tried against every key of their algorithm. Compressed tokens (see
+    /// `JwtTokenService::with_compression`) are inflated first with the `jwt-compression`
+    /// feature, and rejected without it.
+    ///
+    /// # Arguments
+    /// * `token` - The access token to validate.
+    ///
+    /// # Returns
+    /// The claims of the token. The `sub` claim is returned as issued, without the reverse
+    /// mapping of a [`SubjectFormatter`](super::subject::SubjectFormatter).
+    ///
+    /// # Errors
+    /// Returns [`AuthError::TokenExpired`] if the token expired, or [`AuthError::InvalidToken`]
+    /// if it is malformed, signed with an unsupported algorithm or an unknown key, has an
+    /// invalid signature, was issued by another issuer or for another audience, is not an
+    /// access token, or carries a claim rejected by the [`UnknownClaimsPolicy`].
+    pub fn validate(&self, token: &str) -> Result<AccessTokenClaims, AuthError> {
+        let header = decode_header(token)
+            .map_err(|_| AuthError::InvalidToken("Invalid token format".to_string()))?;
+        if !matches!(header.alg, Algorithm::RS256 | Algorithm::ES256) {
+            return Err(AuthError::InvalidToken(format!(
+                "Unsupported token algorithm: {:?}",
+                header.alg
+            )));
+        }
+
+        let validation = self.validation(header.alg);
+        let inflated = super::jwt::InflatedToken::parse(token)?;
+        let mut claims = None;
+        let candidates = self.keys.iter().filter(|key| {
+            key.algorithm == header.alg
+                && (header.kid.is_none() || key.kid.as_deref() == header.kid.as_deref())
+        });
+        for key in candidates {
+            let decoded = match &inflated {
+                Some(inflated) => inflated.decode::<AccessTokenClaims>(&key.key, &validation),
+                None => decode::<AccessTokenClaims>(token, &key.key, &validation),
+            };
+            match decoded {
+                Ok(token_data) => {
+                    claims = Some(token_data.claims);
+                    break;
+                }
+                // Without a `kid`, another key of the set may have signed the token
+                Err(e) if *e.kind() == jsonwebtoken::errors::ErrorKind::InvalidSignature => {
+                    continue;
+                }
+                Err(e) => return Err(super::jwt::decode_error(e)),
+            }
+        }
+        let claims = claims.ok_or_else(|| match &header.kid {
+            Some(kid) if !self.keys.iter().any(|key| key.kid.as_deref() == Some(kid)) => {
+                AuthError::InvalidToken(format!("Unknown key ID: {kid}"))
+            }
+            _ => AuthError::InvalidToken("Invalid token signature".to_string()),
+        })?;
+        if claims.token_type != "access" {
+            return Err(AuthError::InvalidToken("Expected access token".to_string()));
+        }
+        self.unknown_claims.check(&claims)?;
+        Ok(claims)
+    }
+
+    /// Builds the checks applied to tokens signed with `algorithm`.
+    fn validation(&self, algorithm: Algorithm) -> Validation {
+        let mut validation = Validation::new(algorithm);
+        let mut required_claims = vec!["exp"];
+        if self.audiences.is_empty() {
+            validation.validate_aud = false;
+        } else {
+            validation.set_audience(&self.audiences);
+            required_claims.push("aud");
+        }
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+            required_claims.push("iss");
+        }
+        validation.set_required_spec_claims(&required_claims);
+        validation
+    }
+}
@@ -0,0 +1,140 @@
+//! Single-use token tracking.
+//!
+//! Signed tokens such as magic links, password reset links and email verification links are
+//! only single-use if their consumption is recorded. This module provides the
+//! [`OneTimeTokenStore`] trait, which atomically marks a token identifier (`jti`) as consumed
+//! until the token expires, and an in-memory default implementation.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::error::AuthError;
+
+/// Records the consumption of single-use tokens.
+///
+/// Implementations must make [`OneTimeTokenStore::consume`] atomic, so that a token submitted
+/// concurrently several times is honored exactly once.
+#[async_trait::async_trait]
+pub trait OneTimeTokenStore: Send + Sync {
+    /// Marks the token identified by `jti` as consumed until `expires_at`.
+    ///
+    /// # Arguments
+    ///
+    /// * `jti` - The unique identifier of the token.
+    /// * `expires_at` - The token's expiration (UNIX timestamp, seconds). The record may be
+    ///   forgotten after this time, since the token is rejected as expired anyway.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if this call consumed the token.
+    /// * `Ok(false)` if the token was already consumed.
+    /// * `Err(AuthError)` if the store is unavailable.
+    async fn consume(&self, jti: &str, expires_at: usize) -> Result<bool, AuthError>;
+
+    /// Returns when the token identified by `jti` was consumed (UNIX timestamp, seconds), if
+    /// it was and the record was not forgotten yet.
+    ///
+    /// Used to accept refresh tokens again within the grace window of
+    /// [`RefreshStrategy::RotateWithGrace`](crate::core::token::refresh::RefreshStrategy::RotateWithGrace).
+    /// The default implementation does not know, which rejects every reuse.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn consumed_at(&self, jti: &str) -> Result<Option<usize>, AuthError> {
+        let _ = jti;
+        Ok(None)
+    }
+
+    /// Forgets the consumption of the token identified by `jti`, so it can be consumed again.
+    ///
+    /// Used to take back a consumption when the action it guarded failed, as
+    /// `AuthService::bootstrap` does. The default implementation does nothing: the token stays
+    /// consumed until it expires.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn release(&self, jti: &str) -> Result<(), AuthError> {
+        let _ = jti;
+        Ok(())
+    }
+
+    /// Removes the consumption records that expired, and returns how many were removed.
+    ///
+    /// Long-running in-memory stores call this periodically (see
+    /// `AuthService::spawn_maintenance`) so they do not grow without bound. Persistent
+    /// backends should rely on native expiration (e.g. TTLs) instead; the default
+    /// implementation does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn prune_expired(&self) -> Result<usize, AuthError> {
+        Ok(0)
+    }
+}
+
+/// In-memory implementation of [`OneTimeTokenStore`].
+///
+/// Consumed identifiers are kept until their expiration and pruned lazily, or by
+/// [`OneTimeTokenStore::prune_expired`]. Suitable for
+/// single-instance deployments and tests; multi-instance deployments need a shared store.
+#[derive(Debug, Default)]
+pub struct InMemoryOneTimeTokenStore {
+    /// Consumed token identifiers mapped to their expiration and consumption timestamps.
+    consumed: Mutex<HashMap<String, (usize, usize)>>,
+}
+
+impl InMemoryOneTimeTokenStore {
+    /// Creates a new, empty in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl OneTimeTokenStore for InMemoryOneTimeTokenStore {
+    /// Marks the token as consumed, pruning expired records first.
+    async fn consume(&self, jti: &str, expires_at: usize) -> Result<bool, AuthError> {
+        let now = chrono::Utc::now().timestamp().max(0) as usize;
+        let mut consumed = self
+            .consumed
+            .lock()
+            .map_err(|e| AuthError::ServiceUnavailable(e.to_string()))?;
+        consumed.retain(|_, (exp, _)| *exp >= now);
+
+        if consumed.contains_key(jti) {
+            return Ok(false);
+        }
+        consumed.insert(jti.to_string(), (expires_at, now));
+        Ok(true)
+    }
+
+    async fn consumed_at(&self, jti: &str) -> Result<Option<usize>, AuthError> {
+        let consumed = self
+            .consumed
+            .lock()
+            .map_err(|e| AuthError::ServiceUnavailable(e.to_string()))?;
+        Ok(consumed.get(jti).map(|(_, consumed_at)| *consumed_at))
+    }
+
+    async fn release(&self, jti: &str) -> Result<(), AuthError> {
+        self.consumed
+            .lock()
+            .map_err(|e| AuthError::ServiceUnavailable(e.to_string()))?
+            .remove(jti);
+        Ok(())
+    }
+
+    async fn prune_expired(&self) -> Result<usize, AuthError> {
+        let now = chrono::Utc::now().timestamp().max(0) as usize;
+        let mut consumed = self
+            .consumed
+            .lock()
+            .map_err(|e| AuthError::ServiceUnavailable(e.to_string()))?;
+        let before = consumed.len();
+        consumed.retain(|_, (exp, _)| *exp >= now);
+        Ok(before - consumed.len())
+    }
+}
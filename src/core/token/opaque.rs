@@ -0,0 +1,403 @@
+//! Opaque, single-use refresh tokens.
+//!
+//! Instead of a signed JWT, [`OpaqueRefreshTokenService`] issues refresh tokens that are random
+//! strings backed by a [`RefreshTokenStore`] record. Each refresh consumes the token and
+//! issues its successor, so each refresh token works once; access tokens are still issued by
+//! a wrapped [`TokenService`].
+//!
+//! Single use makes concurrent refreshes (two tabs refreshing at once) a race: the first
+//! refresh consumes the token, the second finds it used. [`ConcurrentRefreshPolicy`] decides
+//! what the loser gets:
+//!
+//! - [`ConcurrentRefreshPolicy::Reject`] fails it with [`AuthError::TokenReuseDetected`],
+//!   treating any second use as a possible theft.
+//! - [`ConcurrentRefreshPolicy::Grace`] returns it the same successor pair as the winner, if
+//!   it arrives within the grace window.
+//!
+//! The store marks a record as used with a compare-and-set ([`RefreshTokenStore::mark_used`]),
+//! the equivalent of `UPDATE ... WHERE used_at IS NULL` on a database row, so exactly one
+//! refresh wins whatever the interleaving.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::core::token::claims::{Claims, ConfirmationClaim, RefreshTokenClaims};
+use crate::core::token::material::VerificationMaterial;
+use crate::core::token::{TokenOptions, TokenPair, TokenService};
+use crate::error::AuthError;
+
+/// Number of random bytes in an opaque refresh token.
+const OPAQUE_TOKEN_BYTES: usize = 32;
+
+/// What a refresh presenting an already used refresh token gets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConcurrentRefreshPolicy {
+    /// Fails every refresh after the first with [`AuthError::TokenReuseDetected`].
+    #[default]
+    Reject,
+    /// Returns the successor issued by the first refresh to refreshes arriving within the
+    /// window after it, and fails later ones with [`AuthError::TokenReuseDetected`].
+    Grace(Duration),
+}
+
+/// The consumption of a refresh token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefreshTokenUse {
+    /// When the token was consumed.
+    pub used_at: DateTime<Utc>,
+    /// The token pair issued in exchange.
+    pub successor: TokenPair,
+}
+
+/// A stored opaque refresh token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefreshTokenRecord {
+    /// The user the token was issued to.
+    pub user_id: String,
+    /// The options the token pair was issued with, carried over to its successors.
+    pub options: TokenOptions,
+    /// When the token was issued (UNIX timestamp, seconds).
+    pub issued_at: usize,
+    /// When the token expires (UNIX timestamp, seconds).
+    pub expires_at: usize,
+    /// The consumption of the token, once it has been refreshed.
+    pub used: Option<RefreshTokenUse>,
+}
+
+/// Stores opaque refresh token records, keyed by token.
+///
+/// Persistent implementations should key records by a hash of the token, so that a leaked
+/// store does not reveal usable tokens.
+#[async_trait::async_trait]
+pub trait RefreshTokenStore: Send + Sync {
+    /// Stores the record of a new token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn insert(&self, token: &str, record: RefreshTokenRecord) -> Result<(), AuthError>;
+
+    /// Returns the record of `token`, if it exists and has not expired.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn get(&self, token: &str) -> Result<Option<RefreshTokenRecord>, AuthError>;
+
+    /// Marks `token` as used with `usage`, unless it already is. Must be atomic.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if this call consumed the token.
+    /// * `Ok(false)` if the token was already used, or does not exist.
+    /// * `Err(AuthError)` if the store is unavailable.
+    async fn mark_used(&self, token: &str, usage: RefreshTokenUse) -> Result<bool, AuthError>;
+}
+
+/// In-memory implementation of [`RefreshTokenStore`].
+///
+/// Expired records are pruned lazily. Suitable for single-instance deployments and tests;
+/// multi-instance deployments need a shared store.
+#[derive(Debug, Default)]
+pub struct InMemoryRefreshTokenStore {
+    /// Records mapped by token.
+    records: Mutex<HashMap<String, RefreshTokenRecord>>,
+}
+
+impl InMemoryRefreshTokenStore {
+    /// Creates a new, empty in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Locks the records, pruning the expired ones.
+    fn live_records(
+        &self,
+    ) -> Result<std::sync::MutexGuard<'_, HashMap<String, RefreshTokenRecord>>, AuthError> {
+        let now = Utc::now().timestamp().max(0) as usize;
+        let mut records = self
+            .records
+            .lock()
+            .map_err(|e| AuthError::ServiceUnavailable(e.to_string()))?;
+        records.retain(|_, record| record.expires_at > now);
+        Ok(records)
+    }
+}
+
+#[async_trait::async_trait]
+impl RefreshTokenStore for InMemoryRefreshTokenStore {
+    async fn insert(&self, token: &str, record: RefreshTokenRecord) -> Result<(), AuthError> {
+        self.live_records()?.insert(token.to_string(), record);
+        Ok(())
+    }
+
+    async fn get(&self, token: &str) -> Result<Option<RefreshTokenRecord>, AuthError> {
+        Ok(self.live_records()?.get(token).cloned())
+    }
+
+    async fn mark_used(&self, token: &str, usage: RefreshTokenUse) -> Result<bool, AuthError> {
+        let mut records = self.live_records()?;
+        match records.get_mut(token) {
+            Some(record) if record.used.is_none() => {
+                record.used = Some(usage);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+/// A [`TokenService`] issuing opaque, single-use refresh tokens.
+///
+/// Access tokens are issued and validated by the wrapped service; refresh tokens are random
+/// strings recorded in a [`RefreshTokenStore`] (in-memory by default).
+pub struct OpaqueRefreshTokenService {
+    /// The service issuing and validating access tokens.
+    access_tokens: Box<dyn TokenService + Send + Sync>,
+    /// The store holding refresh token records.
+    store: Box<dyn RefreshTokenStore + Send + Sync>,
+    /// The lifetime (in seconds) of refresh tokens.
+    refresh_token_duration: u64,
+    /// What refreshes presenting a used token get.
+    policy: ConcurrentRefreshPolicy,
+}
+
+impl OpaqueRefreshTokenService {
+    /// Creates a service issuing access tokens with `access_tokens` and opaque refresh tokens
+    /// valid for `refresh_token_duration` seconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `access_tokens` - The service issuing and validating access tokens (e.g., a
+    ///   [`JwtTokenService`](crate::core::token::jwt::JwtTokenService)). Its refresh tokens are
+    ///   discarded.
+    /// * `refresh_token_duration` - The lifetime (in seconds) of refresh tokens.
+    pub fn new(
+        access_tokens: Box<dyn TokenService + Send + Sync>,
+        refresh_token_duration: u64,
+    ) -> Self {
+        Self {
+            access_tokens,
+            store: Box::new(InMemoryRefreshTokenStore::new()),
+            refresh_token_duration,
+            policy: ConcurrentRefreshPolicy::default(),
+        }
+    }
+
+    /// Replaces the store holding refresh token records.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The refresh token store to use.
+    pub fn with_store(mut self, store: Box<dyn RefreshTokenStore + Send + Sync>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Sets what a refresh presenting an already used refresh token gets (rejection by
+    /// default).
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The concurrent refresh policy.
+    pub fn with_concurrent_refresh_policy(mut self, policy: ConcurrentRefreshPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the successor of a used token, if the policy allows returning it at `now`.
+    fn reuse(&self, usage: &RefreshTokenUse, now: DateTime<Utc>) -> Result<TokenPair, AuthError> {
+        match self.policy {
+            ConcurrentRefreshPolicy::Grace(window)
+                if now.signed_duration_since(usage.used_at)
+                    <= chrono::Duration::from_std(window).unwrap_or(chrono::Duration::MAX) =>
+            {
+                Ok(usage.successor.clone())
+            }
+            _ => Err(AuthError::TokenReuseDetected),
+        }
+    }
+
+    /// Issues a token pair without recording its refresh token yet.
+    async fn issue(
+        &self,
+        user_id: &str,
+        options: &TokenOptions,
+    ) -> Result<(TokenPair, RefreshTokenRecord), AuthError> {
+        let access_token = self
+            .access_tokens
+            .generate_token_pair_with(user_id, options)
+            .await?
+            .access_token;
+        let now = Utc::now().timestamp().max(0) as usize;
+        let pair = TokenPair {
+            access_token,
+            refresh_token: crate::core::rand::secure_random_string(OPAQUE_TOKEN_BYTES),
+        };
+        let record = RefreshTokenRecord {
+            user_id: user_id.to_string(),
+            options: options.clone(),
+            issued_at: now,
+            expires_at: now.saturating_add(
+                options
+                    .refresh_expiration
+                    .unwrap_or(self.refresh_token_duration) as usize,
+            ),
+            used: None,
+        };
+        Ok((pair, record))
+    }
+
+    /// Returns the record of a live refresh token.
+    async fn record(&self, refresh_token: &str) -> Result<RefreshTokenRecord, AuthError> {
+        self.store
+            .get(refresh_token)
+            .await?
+            .ok_or_else(|| AuthError::InvalidToken("Unknown or expired refresh token".to_string()))
+    }
+
+    /// Consumes a refresh token and issues its successor with `options`, or with the options
+    /// of the consumed token if `None`.
+    async fn rotate(
+        &self,
+        refresh_token: &str,
+        options: Option<&TokenOptions>,
+    ) -> Result<TokenPair, AuthError> {
+        let record = self.record(refresh_token).await?;
+        if let Some(usage) = &record.used {
+            return self.reuse(usage, Utc::now());
+        }
+
+        // The successor is recorded only once this refresh has won the token
+        let options = options.unwrap_or(&record.options);
+        let (successor, successor_record) = self.issue(&record.user_id, options).await?;
+        let usage = RefreshTokenUse {
+            used_at: Utc::now(),
+            successor: successor.clone(),
+        };
+        if self.store.mark_used(refresh_token, usage).await? {
+            self.store
+                .insert(&successor.refresh_token, successor_record)
+                .await?;
+            return Ok(successor);
+        }
+
+        // Another refresh consumed the token first: its successor is the one to share
+        let winner = self.record(refresh_token).await?;
+        match &winner.used {
+            Some(usage) => self.reuse(usage, Utc::now()),
+            None => Err(AuthError::TokenReuseDetected),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenService for OpaqueRefreshTokenService {
+    async fn generate_token_pair(&self, user_id: &str) -> Result<TokenPair, AuthError> {
+        self.generate_token_pair_with(user_id, &TokenOptions::default())
+            .await
+    }
+
+    async fn generate_token_pair_with(
+        &self,
+        user_id: &str,
+        options: &TokenOptions,
+    ) -> Result<TokenPair, AuthError> {
+        let (pair, record) = self.issue(user_id, options).await?;
+        self.store.insert(&pair.refresh_token, record).await?;
+        Ok(pair)
+    }
+
+    /// Generates an access token only, so that no refresh token is stored.
+    async fn generate_access_token_with(
+        &self,
+        user_id: &str,
+        options: &TokenOptions,
+    ) -> Result<String, AuthError> {
+        self.access_tokens
+            .generate_access_token_with(user_id, options)
+            .await
+    }
+
+    async fn validate_access_token(
+        &self,
+        token: &str,
+    ) -> Result<Box<dyn Claims + Send + Sync>, AuthError> {
+        self.access_tokens.validate_access_token(token).await
+    }
+
+    async fn validate_expired_access_token(
+        &self,
+        token: &str,
+    ) -> Result<Box<dyn Claims + Send + Sync>, AuthError> {
+        self.access_tokens
+            .validate_expired_access_token(token)
+            .await
+    }
+
+    /// Consumes the refresh token and issues its successor, keeping the options of the pair.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::InvalidToken`] if the token is unknown or expired, and
+    /// [`AuthError::TokenReuseDetected`] if it was already used and the policy does not allow
+    /// returning its successor.
+    async fn refresh_access_token(&self, refresh_token: &str) -> Result<TokenPair, AuthError> {
+        self.rotate(refresh_token, None).await
+    }
+
+    /// Consumes the refresh token and issues its successor with `options`, which are carried
+    /// over to later successors.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`OpaqueRefreshTokenService::refresh_access_token`].
+    async fn refresh_access_token_with(
+        &self,
+        refresh_token: &str,
+        user_id: &str,
+        options: &TokenOptions,
+    ) -> Result<TokenPair, AuthError> {
+        let _ = user_id;
+        self.rotate(refresh_token, Some(options)).await
+    }
+
+    /// Returns the claims of a live refresh token. Used tokens are valid only while the policy
+    /// allows returning their successor.
+    async fn validate_refresh_token(
+        &self,
+        refresh_token: &str,
+    ) -> Result<Box<dyn Claims + Send + Sync>, AuthError> {
+        let record = self.record(refresh_token).await?;
+        if let Some(usage) = &record.used {
+            self.reuse(usage, Utc::now())?;
+        }
+        Ok(Box::new(RefreshTokenClaims {
+            sub: record.user_id,
+            exp: record.expires_at,
+            iat: record.issued_at,
+            token_type: "refresh".to_string(),
+            tenant_id: record.options.tenant_id,
+            sid: record.options.session_id,
+            aud: record.options.audience,
+            iss: None,
+            cnf: record
+                .options
+                .fingerprint
+                .map(|fingerprint| ConfirmationClaim { fingerprint }),
+            jti: None,
+            auth_time: record.options.auth_time,
+            amr: record.options.amr,
+            rexp: record.options.refresh_expiration,
+        }))
+    }
+
+    /// Returns the material of the access token service; refresh tokens are never verified
+    /// outside this service.
+    fn verification_material(&self) -> Result<VerificationMaterial, AuthError> {
+        self.access_tokens.verification_material()
+    }
+}
@@ -0,0 +1,52 @@
+//! Refresh token rotation strategies.
+//!
+//! Each refresh of an access token may return the refresh token it was given or a new one.
+//! [`RefreshStrategy`] lets operators choose, trading client simplicity for theft detection:
+//!
+//! - [`RefreshStrategy::Reuse`] returns the presented refresh token unchanged. Clients store it
+//!   once, and concurrent refreshes never race, but a stolen refresh token works for its whole
+//!   lifetime and its theft goes unnoticed.
+//! - [`RefreshStrategy::Rotate`] returns a new refresh token on every refresh and consumes the
+//!   presented one: presenting it again fails with
+//!   [`AuthError::TokenReuseDetected`](crate::error::AuthError::TokenReuseDetected).
+//!   Since the legitimate client and a thief cannot both keep using a refresh token, a theft
+//!   surfaces as soon as both try. Clients refreshing concurrently (e.g., two tabs) trip the detection too.
+//! - [`RefreshStrategy::RotateWithGrace`] rotates as well, but accepts a consumed refresh token
+//!   again within a short window after its first use, so concurrent refreshes and retries of a
+//!   refresh whose response was lost succeed. A thief replaying the token within the window
+//!   goes unnoticed, so the window should stay short (seconds).
+//!
+//! Stateless token services cannot tell whether a refresh token was used before. The
+//! strategy of the token service
+//! ([`TokenService::refresh_strategy`](super::TokenService::refresh_strategy)) is enforced by
+//! `AuthService::refresh_access_token`, which records the `jti` of consumed refresh tokens in
+//! its [`OneTimeTokenStore`](super::one_time::OneTimeTokenStore). Grace windows need a store
+//! implementing [`OneTimeTokenStore::consumed_at`](super::one_time::OneTimeTokenStore::consumed_at).
+
+use std::time::Duration;
+
+/// Default grace window (in seconds) of [`RefreshStrategy::RotateWithGrace`] configured
+/// without one.
+pub const DEFAULT_REFRESH_GRACE_SECS: u64 = 10;
+
+/// Whether refreshes return the presented refresh token or a new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshStrategy {
+    /// Returns a new refresh token on every refresh, and rejects presented refresh tokens that
+    /// were already used with
+    /// [`AuthError::TokenReuseDetected`](crate::error::AuthError::TokenReuseDetected).
+    Rotate,
+    /// Returns the presented refresh token, along with a new access token.
+    Reuse,
+    /// Returns a new refresh token on every refresh, and rejects presented refresh tokens that
+    /// were first used longer than the window ago with
+    /// [`AuthError::TokenReuseDetected`](crate::error::AuthError::TokenReuseDetected).
+    RotateWithGrace(Duration),
+}
+
+impl RefreshStrategy {
+    /// Returns whether refreshes issue a new refresh token.
+    pub fn rotates(&self) -> bool {
+        !matches!(self, RefreshStrategy::Reuse)
+    }
+}
@@ -0,0 +1,287 @@
+//! Session tracking and revocation.
+//!
+//! Signed tokens cannot be recalled once issued, so revoking them ("sign out everywhere")
+//! requires recording which sessions exist and which were revoked. Every token pair issued by
+//! `AuthService` belongs to a session, carried in the `sid` claim and kept across refreshes.
+//! This module provides the [`SessionStore`] trait and an in-memory default implementation.
+//!
+//! Tokens without a session (e.g. issued by a token service ignoring
+//! [`TokenOptions::session_id`](super::TokenOptions::session_id)) are revoked by issue time
+//! instead: revoking all sessions of a user also rejects their session-less tokens issued
+//! up to that moment, like a token version bump.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::error::AuthError;
+
+/// Records the sessions of each user and their revocation.
+///
+/// Revocation must be idempotent: revoking already revoked sessions succeeds and reports
+/// nothing new.
+#[async_trait::async_trait]
+pub trait SessionStore: Send + Sync {
+    /// Records (or extends) the session `session_id` of `user_id` until `expires_at`.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - The user the session belongs to.
+    /// * `session_id` - The session identifier (`sid` claim).
+    /// * `expires_at` - The expiration of the session's longest-lived token (UNIX timestamp,
+    ///   seconds). The session may be forgotten after this time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn record(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        expires_at: usize,
+    ) -> Result<(), AuthError>;
+
+    /// Returns whether a token of `user_id` was revoked.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - The token's subject.
+    /// * `session_id` - The token's session, if any.
+    /// * `issued_at` - The token's issue time (UNIX timestamp, seconds), if known. Used for
+    ///   tokens without a session.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn is_revoked(
+        &self,
+        user_id: &str,
+        session_id: Option<&str>,
+        issued_at: Option<usize>,
+    ) -> Result<bool, AuthError>;
+
+    /// Revokes every session of `user_id`, and their tokens issued without a session so far.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(u32)` with the number of live sessions revoked by this call.
+    /// * `Err(AuthError)` if the store is unavailable.
+    async fn revoke_all_for_user(&self, user_id: &str) -> Result<u32, AuthError>;
+
+    /// Revokes the session `session_id` of `user_id`, e.g. after one of its refresh tokens was
+    /// replayed.
+    ///
+    /// The default implementation cannot single out a session and returns
+    /// `AuthError::NotImplemented` without revoking anything; callers then fall back to
+    /// [`SessionStore::revoke_all_for_user`].
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - The user the session belongs to.
+    /// * `session_id` - The session to revoke.
+    /// * `expires_at` - The latest expiration of the session's tokens (UNIX timestamp,
+    ///   seconds), used if the store does not know the session. The revocation may be
+    ///   forgotten after this time.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(bool)` with whether a live session was revoked by this call.
+    /// * `Err(AuthError::NotImplemented)` if the store cannot revoke a single session.
+    /// * `Err(AuthError)` if the store is unavailable.
+    async fn revoke_session(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        expires_at: usize,
+    ) -> Result<bool, AuthError> {
+        let _ = (user_id, session_id, expires_at);
+        Err(AuthError::NotImplemented(
+            "SessionStore::revoke_session".to_string(),
+        ))
+    }
+
+    /// Revokes every session of `user_id` except `keep_session_id`, and their tokens issued
+    /// without a session so far.
+    ///
+    /// The default implementation cannot single out a session and returns
+    /// `AuthError::NotImplemented` without revoking anything; stores keeping sessions alive on
+    /// password change must override it.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - The user whose sessions to revoke.
+    /// * `keep_session_id` - The session to keep alive.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(u32)` with the number of live sessions revoked by this call.
+    /// * `Err(AuthError::NotImplemented)` if the store cannot keep a single session.
+    /// * `Err(AuthError)` if the store is unavailable.
+    async fn revoke_all_for_user_except(
+        &self,
+        user_id: &str,
+        keep_session_id: &str,
+    ) -> Result<u32, AuthError> {
+        let _ = (user_id, keep_session_id);
+        Err(AuthError::NotImplemented(
+            "SessionStore::revoke_all_for_user_except".to_string(),
+        ))
+    }
+
+    /// Removes the sessions that expired, and returns how many were removed.
+    ///
+    /// Long-running in-memory stores call this periodically (see
+    /// `AuthService::spawn_maintenance`) so they do not grow without bound. Persistent
+    /// backends should rely on native expiration (e.g. TTLs) instead; the default
+    /// implementation does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuthError` if the store is unavailable.
+    async fn prune_expired(&self) -> Result<usize, AuthError> {
+        Ok(0)
+    }
+}
+
+/// The sessions of a user.
+#[derive(Debug, Default)]
+struct UserSessions {
+    /// Live sessions mapped to their expiration timestamp.
+    active: HashMap<String, usize>,
+    /// Revoked sessions mapped to their expiration timestamp.
+    revoked: HashMap<String, usize>,
+    /// Tokens without a session issued up to this timestamp are revoked.
+    revoked_until: Option<usize>,
+}
+
+/// In-memory implementation of [`SessionStore`].
+///
+/// Expired sessions are pruned lazily, or by [`SessionStore::prune_expired`], which also
+/// forgets users left without sessions or revocations. Suitable for single-instance
+/// deployments and tests; multi-instance deployments need a shared store.
+#[derive(Debug, Default)]
+pub struct InMemorySessionStore {
+    /// Sessions mapped by user ID.
+    users: Mutex<HashMap<String, UserSessions>>,
+}
+
+impl InMemorySessionStore {
+    /// Creates a new, empty in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Locks the sessions, pruning the expired ones.
+    fn live_sessions(
+        &self,
+    ) -> Result<std::sync::MutexGuard<'_, HashMap<String, UserSessions>>, AuthError> {
+        let now = chrono::Utc::now().timestamp().max(0) as usize;
+        let mut users = self
+            .users
+            .lock()
+            .map_err(|e| AuthError::ServiceUnavailable(e.to_string()))?;
+        for sessions in users.values_mut() {
+            sessions.active.retain(|_, exp| *exp >= now);
+            sessions.revoked.retain(|_, exp| *exp >= now);
+        }
+        Ok(users)
+    }
+}
+
+#[async_trait::async_trait]
+impl SessionStore for InMemorySessionStore {
+    async fn record(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        expires_at: usize,
+    ) -> Result<(), AuthError> {
+        let mut users = self.live_sessions()?;
+        let sessions = users.entry(user_id.to_string()).or_default();
+        if !sessions.revoked.contains_key(session_id) {
+            sessions.active.insert(session_id.to_string(), expires_at);
+        }
+        Ok(())
+    }
+
+    async fn is_revoked(
+        &self,
+        user_id: &str,
+        session_id: Option<&str>,
+        issued_at: Option<usize>,
+    ) -> Result<bool, AuthError> {
+        let users = self.live_sessions()?;
+        let Some(sessions) = users.get(user_id) else {
+            return Ok(false);
+        };
+        Ok(match session_id {
+            Some(session_id) => sessions.revoked.contains_key(session_id),
+            None => match (sessions.revoked_until, issued_at) {
+                (Some(revoked_until), Some(issued_at)) => issued_at <= revoked_until,
+                (Some(_), None) => true,
+                (None, _) => false,
+            },
+        })
+    }
+
+    async fn revoke_all_for_user(&self, user_id: &str) -> Result<u32, AuthError> {
+        let now = chrono::Utc::now().timestamp().max(0) as usize;
+        let mut users = self.live_sessions()?;
+        let sessions = users.entry(user_id.to_string()).or_default();
+        let revoked = sessions.active.len();
+        sessions.revoked.extend(sessions.active.drain());
+        sessions.revoked_until = Some(now);
+        Ok(u32::try_from(revoked).unwrap_or(u32::MAX))
+    }
+
+    async fn revoke_session(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        expires_at: usize,
+    ) -> Result<bool, AuthError> {
+        let mut users = self.live_sessions()?;
+        let sessions = users.entry(user_id.to_string()).or_default();
+        let active = sessions.active.remove(session_id);
+        let expires_at = active.map_or(expires_at, |exp| exp.max(expires_at));
+        sessions.revoked.insert(session_id.to_string(), expires_at);
+        Ok(active.is_some())
+    }
+
+    async fn revoke_all_for_user_except(
+        &self,
+        user_id: &str,
+        keep_session_id: &str,
+    ) -> Result<u32, AuthError> {
+        let now = chrono::Utc::now().timestamp().max(0) as usize;
+        let mut users = self.live_sessions()?;
+        let sessions = users.entry(user_id.to_string()).or_default();
+        let kept = sessions.active.remove_entry(keep_session_id);
+        let revoked = sessions.active.len();
+        sessions.revoked.extend(sessions.active.drain());
+        sessions.active.extend(kept);
+        sessions.revoked_until = Some(now);
+        Ok(u32::try_from(revoked).unwrap_or(u32::MAX))
+    }
+
+    async fn prune_expired(&self) -> Result<usize, AuthError> {
+        let now = chrono::Utc::now().timestamp().max(0) as usize;
+        let mut users = self
+            .users
+            .lock()
+            .map_err(|e| AuthError::ServiceUnavailable(e.to_string()))?;
+        let mut pruned = 0;
+        for sessions in users.values_mut() {
+            let before = sessions.active.len() + sessions.revoked.len();
+            sessions.active.retain(|_, exp| *exp >= now);
+            sessions.revoked.retain(|_, exp| *exp >= now);
+            pruned += before - sessions.active.len() - sessions.revoked.len();
+        }
+        // Users with a revocation time must be kept, so that their session-less tokens stay revoked
+        users.retain(|_, sessions| {
+            !sessions.active.is_empty()
+                || !sessions.revoked.is_empty()
+                || sessions.revoked_until.is_some()
+        });
+        Ok(pruned)
+    }
+}
@@ -0,0 +1,80 @@
+//! Token subject formatting.
+//!
+//! Tokens identify their user through the `sub` claim, which holds the bare user ID by default.
+//! A [`SubjectFormatter`] maps user IDs to another subject format (e.g., `user:123`) when
+//! tokens are generated, and back to user IDs when they are validated, so that an existing
+//! token contract can be matched without changing how users are stored.
+
+use crate::error::AuthError;
+
+/// Converts between user IDs and the `sub` claim of issued tokens.
+///
+/// [`SubjectFormatter::parse`] must invert [`SubjectFormatter::format`]: parsing a formatted
+/// user ID returns the user ID unchanged.
+pub trait SubjectFormatter: Send + Sync {
+    /// Returns the subject embedded in tokens issued for `user_id`.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - The unique identifier of the user.
+    fn format(&self, user_id: &str) -> String;
+
+    /// Returns the user ID a token subject was formatted from.
+    ///
+    /// # Arguments
+    ///
+    /// * `subject` - The `sub` claim of a validated token.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::InvalidToken`] if the subject does not have the expected format.
+    fn parse(&self, subject: &str) -> Result<String, AuthError>;
+}
+
+/// The default [`SubjectFormatter`], using the user ID as the subject.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IdentitySubjectFormatter;
+
+impl SubjectFormatter for IdentitySubjectFormatter {
+    fn format(&self, user_id: &str) -> String {
+        user_id.to_string()
+    }
+
+    fn parse(&self, subject: &str) -> Result<String, AuthError> {
+        Ok(subject.to_string())
+    }
+}
+
+/// A [`SubjectFormatter`] prepending a fixed prefix to user IDs (e.g., `user:` gives `user:123`).
+#[derive(Debug, Clone, Default)]
+pub struct PrefixSubjectFormatter {
+    /// The prefix prepended to user IDs.
+    prefix: String,
+}
+
+impl PrefixSubjectFormatter {
+    /// Creates a formatter prepending `prefix` to user IDs.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - The prefix, including any separator.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+}
+
+impl SubjectFormatter for PrefixSubjectFormatter {
+    fn format(&self, user_id: &str) -> String {
+        format!("{}{user_id}", self.prefix)
+    }
+
+    fn parse(&self, subject: &str) -> Result<String, AuthError> {
+        subject
+            .strip_prefix(self.prefix.as_str())
+            .filter(|user_id| !user_id.is_empty())
+            .map(str::to_string)
+            .ok_or_else(|| AuthError::InvalidToken("Unexpected token subject format".to_string()))
+    }
+}
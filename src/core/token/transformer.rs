@@ -0,0 +1,46 @@
+//! Validation-time claims transformation.
+//!
+//! Gateways sometimes need the claims of a token in another shape than the one it was issued
+//! with, e.g. internal role names mapped to those of an external system. A
+//! [`ClaimsTransformer`] rewrites the claims of every access token `AuthService` validates,
+//! once its signature, expiration and session have been checked, so handlers only see the
+//! transformed claims.
+//!
+//! Unlike a [`ClaimsEnricher`](super::enricher::ClaimsEnricher), which runs when tokens are
+//! issued, a transformer runs on the verifying side and leaves the token itself unchanged.
+
+use super::claims::Claims;
+use crate::error::AuthError;
+
+/// Rewrites the claims of validated access tokens.
+#[async_trait::async_trait]
+pub trait ClaimsTransformer: Send + Sync {
+    /// Returns the claims handed to the caller of a validation, in place of `claims`.
+    ///
+    /// # Arguments
+    ///
+    /// * `claims` - The claims of a token that passed validation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AuthError`] to reject the token. The error is returned to the caller of
+    /// the validation.
+    async fn transform(
+        &self,
+        claims: Box<dyn Claims + Send + Sync>,
+    ) -> Result<Box<dyn Claims + Send + Sync>, AuthError>;
+}
+
+/// The default [`ClaimsTransformer`], returning the claims unchanged.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IdentityClaimsTransformer;
+
+#[async_trait::async_trait]
+impl ClaimsTransformer for IdentityClaimsTransformer {
+    async fn transform(
+        &self,
+        claims: Box<dyn Claims + Send + Sync>,
+    ) -> Result<Box<dyn Claims + Send + Sync>, AuthError> {
+        Ok(claims)
+    }
+}
@@ -1,7 +1,7 @@
 //! User data structures and persistence traits.
 //!
 //! This module provides the [`User`] struct, which encapsulates user identity and credentials,
-//! as well as methods for user creation with both hashed and plaintext passwords. It also
+//! the [`UserId`] newtype identifying users, as well as methods for user creation with both hashed and plaintext passwords. It also
 //! re-exports the [`persistence`] submodule, which defines traits and types for user persistence
 //! operations (e.g., repositories).
 //!
@@ -33,6 +33,235 @@ use crate::core::credentials::{Credentials, PlainPassword};
 use crate::core::oauth::store::{OAuth2Provider, OAuth2UserInfo};
 use std::collections::HashMap;
 
+/// The unique identifier of a [`User`].
+///
+/// A newtype over `String`, so that a user ID cannot be mixed up with a login identifier
+/// (username, email) or any other string at API boundaries. It serializes as a plain string.
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(transparent)]
+pub struct UserId(String);
+
+impl UserId {
+    /// Creates a user ID from its string form.
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    /// Generates a new random (UUID v4) user ID.
+    pub fn generate() -> Self {
+        Self(uuid::Uuid::new_v4().to_string())
+    }
+
+    /// Returns the ID as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Consumes the ID, returning its string form.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+/// Number of characters (6 bits each) of a [`LogIdKey::log_id`] pseudonym.
+const LOG_ID_LEN: usize = 16;
+
+/// The key of user ID pseudonyms for log lines.
+///
+/// A pseudonym is a truncated HMAC-SHA256 of the ID. It is stable for the lifetime of the key,
+/// so log lines of a user can be correlated, but cannot be reversed into the ID without the
+/// key. `AuthService` derives its key from the server secret (see
+/// [`AuthService::log_id`](crate::AuthService::log_id)), so pseudonyms stay stable across
+/// restarts and instances sharing the secret.
+#[derive(Clone)]
+pub struct LogIdKey(zeroize::Zeroizing<[u8; crate::core::kdf::KEY_LEN]>);
+
+impl LogIdKey {
+    /// Derives the key from `secret` with HKDF, under the
+    /// [`LOG_ID_LABEL`](crate::core::kdf::LOG_ID_LABEL) label.
+    ///
+    /// # Arguments
+    /// * `secret` - The server secret.
+    pub fn derive(secret: &[u8]) -> Self {
+        Self(crate::core::kdf::derive_key(
+            secret,
+            crate::core::kdf::LOG_ID_LABEL,
+        ))
+    }
+
+    /// Generates a random key, whose pseudonyms change on every restart.
+    pub fn random() -> Self {
+        let mut key = zeroize::Zeroizing::new([0u8; crate::core::kdf::KEY_LEN]);
+        key.copy_from_slice(&crate::core::rand::secure_random_bytes(
+            crate::core::kdf::KEY_LEN,
+        ));
+        Self(key)
+    }
+
+    /// Returns the pseudonym of `id` for log lines.
+    ///
+    /// Log it instead of the user's ID or identifier (e.g., an email address) to keep logs
+    /// correlatable without personal data.
+    ///
+    /// # Arguments
+    /// * `id` - The user ID.
+    pub fn log_id(&self, id: &UserId) -> String {
+        use base64::Engine;
+        let tag = crate::core::kdf::hmac_sha256(self.0.as_slice(), id.as_str().as_bytes());
+        let mut pseudonym = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(tag);
+        pseudonym.truncate(LOG_ID_LEN);
+        pseudonym
+    }
+}
+
+impl std::fmt::Debug for LogIdKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("LogIdKey([REDACTED])")
+    }
+}
+
+impl From<String> for UserId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+impl From<&str> for UserId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+impl From<UserId> for String {
+    fn from(id: UserId) -> Self {
+        id.0
+    }
+}
+
+impl AsRef<str> for UserId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for UserId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl PartialEq<str> for UserId {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for UserId {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<UserId> for str {
+    fn eq(&self, other: &UserId) -> bool {
+        self == other.0
+    }
+}
+
+impl PartialEq<UserId> for &str {
+    fn eq(&self, other: &UserId) -> bool {
+        *self == other.0
+    }
+}
+
+/// The lifecycle status of a [`User`].
+///
+/// Only active users can log in. Suspended and deleted users are kept in the repository
+/// (e.g., for audits or reactivation) but every login attempt fails with
+/// [`AuthError::AccountDisabled`](crate::error::AuthError::AccountDisabled).
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum UserStatus {
+    /// The user can log in.
+    #[default]
+    Active,
+    /// The user is temporarily disabled, for instance by an administrator.
+    Suspended,
+    /// The user is soft-deleted.
+    Deleted,
+}
+
+impl UserStatus {
+    /// Returns the stable lowercase identifier of the status, suitable for persisting it.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Suspended => "suspended",
+            Self::Deleted => "deleted",
+        }
+    }
+
+    /// Returns whether the user can log in.
+    pub fn is_active(&self) -> bool {
+        *self == Self::Active
+    }
+}
+
+impl std::fmt::Display for UserStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for UserStatus {
+    type Err = crate::error::AuthError;
+
+    /// Parses a status from its identifier, ignoring ASCII case.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::InvalidInput`](crate::error::AuthError::InvalidInput) for unknown statuses.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Active, Self::Suspended, Self::Deleted]
+            .into_iter()
+            .find(|status| status.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                crate::error::AuthError::InvalidInput(format!("Unknown user status: {s}"))
+            })
+    }
+}
+
+/// The role granted to the administrator created by
+/// [`AuthService::bootstrap`](crate::AuthService::bootstrap).
+pub const ADMIN_ROLE: &str = "admin";
+
+/// Where authorization helpers such as
+/// [`AuthService::user_has_role`](crate::AuthService::user_has_role) read the roles of a
+/// token's user from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RoleSource {
+    /// The `roles` claim of the token. Costs nothing, but a role change only shows once the
+    /// user gets a new access token.
+    #[default]
+    Token,
+    /// The [`roles`](User::roles) currently stored in the repository. Costs one user read per
+    /// check, but a revoked role stops authorizing requests immediately.
+    Repository,
+}
+
 /// Represents a user in the authentication system.
 ///
 /// The `User` struct contains a unique identifier and associated credentials.
@@ -40,7 +269,7 @@ use std::collections::HashMap;
 #[derive(Debug, Clone)]
 pub struct User {
     /// Unique identifier for the user (preferably a UUID).
-    pub id: String,
+    pub id: UserId,
     /// User credentials, including hashed password and identifier.
     pub credentials: Option<Credentials>,
     /// OAuth2 accounts linked to this user
@@ -49,17 +278,49 @@ pub struct User {
     pub created_at: chrono::NaiveDateTime,
     /// Last updated timestamp
     pub updated_at: chrono::NaiveDateTime,
+    /// Tenant the user belongs to, if the service is multi-tenant.
+    ///
+    /// Identifiers are only unique within a tenant.
+    pub tenant_id: Option<String>,
+    /// Timestamp of the last successful login, if any.
+    pub last_login_at: Option<chrono::NaiveDateTime>,
+    /// Whether the user is active, suspended or deleted.
+    pub status: UserStatus,
+    /// The Argon2 parameters this user's password should be hashed with, if they differ from
+    /// the service-wide target.
+    ///
+    /// Logins of users with a target rehash toward it instead of the password manager's
+    /// parameters, so segments of users (e.g., grandfathered accounts at a lower cost) can be
+    /// migrated independently. `None` uses the service-wide target.
+    pub hash_target: Option<crate::core::hash::Argon2Params>,
+    /// The roles granted to the user (e.g., [`ADMIN_ROLE`]).
+    ///
+    /// Access tokens issued for a loaded user carry them in a `roles` claim, unless the
+    /// claims enricher sets that claim itself.
+    pub roles: Vec<String>,
+    /// The version of the stored user, incremented by every repository update.
+    ///
+    /// Used as an optimistic lock by
+    /// [`UserRepository::compare_and_update_user`](crate::core::user::persistence::UserRepository::compare_and_update_user).
+    /// Repositories that do not track versions leave it at 0.
+    pub version: u64,
 }
 
 impl Default for User {
     fn default() -> Self {
         let now = chrono::Utc::now().naive_utc();
         Self {
-            id: String::new(),
+            id: UserId::default(),
             credentials: None,
             oauth_accounts: HashMap::new(),
             created_at: now,
             updated_at: now,
+            tenant_id: None,
+            last_login_at: None,
+            status: UserStatus::Active,
+            hash_target: None,
+            roles: Vec::new(),
+            version: 0,
         }
     }
 }
@@ -73,14 +334,20 @@ impl User {
     ///
     /// # Returns
     /// A new [`User`] instance.
-    pub fn new(id: String, credentials: Credentials) -> Self {
+    pub fn new(id: impl Into<UserId>, credentials: Credentials) -> Self {
         let now = chrono::Utc::now().naive_utc();
         Self {
-            id,
+            id: id.into(),
             credentials: Some(credentials),
             oauth_accounts: HashMap::new(),
             created_at: now,
             updated_at: now,
+            tenant_id: None,
+            last_login_at: None,
+            status: UserStatus::Active,
+            hash_target: None,
+            roles: Vec::new(),
+            version: 0,
         }
     }
 
@@ -114,12 +381,13 @@ impl User {
     /// ```
     pub async fn with_plain_password(
         manager: &(dyn crate::core::password::SecurePasswordManager + Send + Sync),
-        id: String,
+        id: impl Into<UserId>,
         identifier: String,
         plain_password: PlainPassword,
     ) -> Result<Self, crate::error::AuthError> {
+        let id = id.into();
         let credentials =
-            Credentials::from_plain_password(manager, id.clone(), identifier, plain_password)
+            Credentials::from_plain_password(manager, id.to_string(), identifier, plain_password)
                 .await?;
 
         Ok(Self {
@@ -128,9 +396,27 @@ impl User {
             oauth_accounts: HashMap::new(),
             created_at: chrono::Utc::now().naive_utc(),
             updated_at: chrono::Utc::now().naive_utc(),
+            tenant_id: None,
+            last_login_at: None,
+            status: UserStatus::Active,
+            hash_target: None,
+            roles: Vec::new(),
+            version: 0,
         })
     }
 
+    /// Assigns the user to a tenant.
+    ///
+    /// # Arguments
+    /// * `tenant_id` - The tenant the user belongs to, or `None` for no tenant.
+    ///
+    /// # Returns
+    /// The updated user.
+    pub fn with_tenant(mut self, tenant_id: Option<String>) -> Self {
+        self.tenant_id = tenant_id;
+        self
+    }
+
     /// Links an OAuth account to this user.
     ///
     /// # Arguments
@@ -139,7 +425,7 @@ impl User {
     /// # Returns
     /// The updated user with the OAuth account linked.
     pub fn link_oauth_account(mut self, mut oauth_info: OAuth2UserInfo) -> Self {
-        oauth_info.user_id = self.id.clone();
+        oauth_info.user_id = self.id.to_string();
         self.oauth_accounts.insert(oauth_info.provider, oauth_info);
         self.updated_at = chrono::Utc::now().naive_utc();
         self
@@ -171,6 +457,28 @@ impl User {
         self.oauth_accounts.contains_key(&provider)
     }
 
+    /// Checks if the user has no login identifier yet, as users created by an OAuth2 login.
+    ///
+    /// Such users can only log in through their OAuth2 accounts until they pick an identifier
+    /// with [`AuthService::complete_profile`](crate::AuthService::complete_profile).
+    ///
+    /// # Returns
+    /// True if the user has no credentials, false otherwise.
+    pub fn needs_identifier(&self) -> bool {
+        self.credentials.is_none()
+    }
+
+    /// Checks if the user was granted a role.
+    ///
+    /// # Arguments
+    /// * `role` - The role to look for (e.g., [`ADMIN_ROLE`]).
+    ///
+    /// # Returns
+    /// True if the user has the role, false otherwise.
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|granted| granted == role)
+    }
+
     /// Gets the OAuth account info for a specific provider.
     ///
     /// # Arguments
@@ -190,8 +498,9 @@ impl User {
     ///
     /// # Returns
     /// A new [`User`] instance with the OAuth account linked.
-    pub fn from_oauth(id: String, mut oauth_info: OAuth2UserInfo) -> Self {
-        oauth_info.user_id = id.clone();
+    pub fn from_oauth(id: impl Into<UserId>, mut oauth_info: OAuth2UserInfo) -> Self {
+        let id = id.into();
+        oauth_info.user_id = id.to_string();
         let now = chrono::Utc::now().naive_utc();
         let mut oauth_accounts = HashMap::new();
         oauth_accounts.insert(oauth_info.provider, oauth_info);
@@ -202,6 +511,12 @@ impl User {
             oauth_accounts,
             created_at: now,
             updated_at: now,
+            tenant_id: None,
+            last_login_at: None,
+            status: UserStatus::Active,
+            hash_target: None,
+            roles: Vec::new(),
+            version: 0,
         }
     }
 }
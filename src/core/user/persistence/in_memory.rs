@@ -4,11 +4,22 @@
 //! It is intended for use in tests or non-persistent environments where a database is not required.
 
 use async_trait::async_trait;
+use futures_util::stream::{self, StreamExt};
 
-use super::traits::UserRepository;
-use crate::core::user::User;
+use super::reservation::ReservationGuard;
+use super::traits::{UserRepository, UserStream, stored_credentials};
+use super::transaction::{RepositoryTransaction, nested_transaction_error};
+use crate::core::credentials::StoredCredentials;
+use crate::core::user::{User, UserId};
+use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 
+/// Number of users copied per batch by [`InMemoryUserRepo::stream_users`].
+const STREAM_BATCH_SIZE: usize = 100;
+
+/// A reserved `(tenant, identifier)` pair.
+type ReservedIdentifier = (Option<String>, String);
+
 /// Thread-safe, in-memory implementation of the [`UserRepository`] trait.
 ///
 /// Stores users in a shared, mutable vector protected by a mutex.
@@ -17,9 +28,37 @@ use std::sync::{Arc, Mutex};
 pub struct InMemoryUserRepo {
     /// Shared, thread-safe vector of users.
     users: Arc<Mutex<Vec<User>>>,
+    /// Identifiers currently reserved by in-flight signups.
+    reserved: Arc<Mutex<HashSet<ReservedIdentifier>>>,
 }
 
 impl InMemoryUserRepo {
+    /// Replaces a stored user with `user` and the next version, if its version is
+    /// `expected_version` (or unconditionally without one).
+    fn write_user(
+        &self,
+        user: &User,
+        expected_version: Option<u64>,
+    ) -> Result<(), crate::error::AuthError> {
+        let mut users = self
+            .users
+            .lock()
+            .map_err(|e| crate::error::AuthError::ServiceUnavailable(e.to_string()))?;
+        let existing = users
+            .iter_mut()
+            .find(|u| u.id == user.id)
+            .ok_or(crate::error::AuthError::UserNotFound)?;
+        if expected_version.is_some_and(|expected| expected != existing.version) {
+            return Err(crate::error::AuthError::ConcurrentModification);
+        }
+        let version = existing.version.wrapping_add(1);
+        *existing = User {
+            version,
+            ..user.clone()
+        };
+        Ok(())
+    }
+
     /// Creates a new, empty in-memory user repository.
     ///
     /// # Examples
@@ -30,6 +69,7 @@ impl InMemoryUserRepo {
     pub fn new() -> Self {
         InMemoryUserRepo {
             users: Arc::new(Mutex::new(Vec::new())),
+            reserved: Arc::new(Mutex::new(HashSet::new())),
         }
     }
 }
@@ -43,12 +83,23 @@ impl UserRepository for InMemoryUserRepo {
     ///
     /// # Returns
     /// * `Ok(User)` if the user was added successfully.
+    /// * `Err(AuthError::UserAlreadyExists)` if a user of the same tenant has the same identifier.
     /// * `Err(AuthError)` if the repository is unavailable.
     async fn add_user(&self, user: User) -> Result<User, crate::error::AuthError> {
         let mut users = self
             .users
             .lock()
             .map_err(|e| crate::error::AuthError::ServiceUnavailable(e.to_string()))?;
+        if let Some(credentials) = &user.credentials
+            && users.iter().any(|u| {
+                u.tenant_id == user.tenant_id
+                    && u.credentials
+                        .as_ref()
+                        .is_some_and(|creds| creds.identifier == credentials.identifier)
+            })
+        {
+            return Err(crate::error::AuthError::UserAlreadyExists);
+        }
         users.push(user.clone());
         Ok(user.clone())
     }
@@ -60,12 +111,12 @@ impl UserRepository for InMemoryUserRepo {
     ///
     /// # Returns
     /// * `Some(User)` if found, or `None` if not found or on lock error.
-    async fn get_user_by_id(&self, id: &str) -> Option<User> {
+    async fn get_user_by_id(&self, id: &UserId) -> Option<User> {
         let users = self.users.lock().ok()?; // Handle potential poisoning
-        users.iter().find(|u| u.id == id).cloned()
+        users.iter().find(|u| &u.id == id).cloned()
     }
 
-    /// Retrieves a user by their identifier (e.g., username or email).
+    /// Retrieves a user without a tenant by their identifier (e.g., username or email).
     ///
     /// # Arguments
     /// * `identifier` - The user's identifier.
@@ -73,38 +124,230 @@ impl UserRepository for InMemoryUserRepo {
     /// # Returns
     /// * `Some(User)` if found, or `None` if not found or on lock error.
     async fn get_user_by_identifier(&self, identifier: &str) -> Option<User> {
+        self.get_user_by_identifier_in_tenant(identifier, None)
+            .await
+    }
+
+    /// Retrieves a user by their identifier within a tenant.
+    ///
+    /// # Arguments
+    /// * `identifier` - The user's identifier.
+    /// * `tenant_id` - The tenant to search in, or `None` for users without a tenant.
+    ///
+    /// # Returns
+    /// * `Some(User)` if found, or `None` if not found or on lock error.
+    async fn get_user_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Option<User> {
         let users = self.users.lock().ok()?; // Handle potential poisoning
         users
             .iter()
             .find(|u| {
-                u.credentials
-                    .as_ref()
-                    .map(|creds| creds.identifier == identifier)
-                    .unwrap_or(false)
+                u.tenant_id.as_deref() == tenant_id
+                    && u.credentials
+                        .as_ref()
+                        .map(|creds| creds.identifier == identifier)
+                        .unwrap_or(false)
             })
             .cloned()
     }
 
-    /// Updates an existing user in the repository.
+    /// Retrieves the credentials for an identifier, among users without a tenant, without
+    /// cloning the user.
     ///
     /// # Arguments
-    /// * `user` - The user with updated information.
+    /// * `identifier` - The user's identifier.
     ///
     /// # Returns
-    /// * `Ok(())` if the user was updated.
-    /// * `Err(AuthError::UserNotFound)` if the user does not exist.
+    /// * `Ok(Some(StoredCredentials))` if found, or `Ok(None)` if not found.
     /// * `Err(AuthError)` if the repository is unavailable.
-    async fn update_user(&self, user: &User) -> Result<(), crate::error::AuthError> {
-        let mut users = self
+    async fn get_credentials_by_identifier(
+        &self,
+        identifier: &str,
+    ) -> Result<Option<StoredCredentials>, crate::error::AuthError> {
+        self.get_credentials_by_identifier_in_tenant(identifier, None)
+            .await
+    }
+
+    /// Retrieves the credentials for an identifier within a tenant without cloning the user.
+    ///
+    /// # Arguments
+    /// * `identifier` - The user's identifier.
+    /// * `tenant_id` - The tenant to search in, or `None` for users without a tenant.
+    ///
+    /// # Returns
+    /// * `Ok(Some(StoredCredentials))` if found, or `Ok(None)` if not found.
+    /// * `Err(AuthError)` if the repository is unavailable.
+    async fn get_credentials_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<StoredCredentials>, crate::error::AuthError> {
+        let users = self
             .users
             .lock()
             .map_err(|e| crate::error::AuthError::ServiceUnavailable(e.to_string()))?;
-        if let Some(existing) = users.iter_mut().find(|u| u.id == user.id) {
-            *existing = user.clone();
-            Ok(())
-        } else {
-            Err(crate::error::AuthError::UserNotFound)
+        Ok(users
+            .iter()
+            .find(|u| {
+                u.tenant_id.as_deref() == tenant_id
+                    && u.credentials
+                        .as_ref()
+                        .is_some_and(|creds| creds.identifier == identifier)
+            })
+            .and_then(stored_credentials))
+    }
+
+    /// Retrieves the credentials of every user with a password.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<StoredCredentials>)` with the credentials, in insertion order.
+    /// * `Err(AuthError)` if the repository is unavailable.
+    async fn list_credentials(&self) -> Result<Vec<StoredCredentials>, crate::error::AuthError> {
+        let users = self
+            .users
+            .lock()
+            .map_err(|e| crate::error::AuthError::ServiceUnavailable(e.to_string()))?;
+        Ok(users.iter().filter_map(stored_credentials).collect())
+    }
+
+    /// Streams every user of the repository, in insertion order.
+    ///
+    /// Users are copied `STREAM_BATCH_SIZE` at a time, holding the lock only while a batch
+    /// is copied. Users deleted during the stream may shift later users into an already read
+    /// batch, in which case they are skipped.
+    ///
+    /// # Returns
+    /// A stream of users, ending with an `AuthError` if the repository becomes unavailable.
+    fn stream_users(&self) -> UserStream<'_> {
+        let users = Arc::clone(&self.users);
+        stream::unfold(Some(0), move |offset| {
+            let users = Arc::clone(&users);
+            async move {
+                let offset = offset?;
+                let batch: Vec<Result<User, crate::error::AuthError>> = match users.lock() {
+                    Ok(users) => users
+                        .iter()
+                        .skip(offset)
+                        .take(STREAM_BATCH_SIZE)
+                        .cloned()
+                        .map(Ok)
+                        .collect(),
+                    Err(e) => {
+                        let error = crate::error::AuthError::ServiceUnavailable(e.to_string());
+                        return Some((vec![Err(error)], None));
+                    }
+                };
+                if batch.is_empty() {
+                    return None;
+                }
+                let next = offset + batch.len();
+                Some((batch, Some(next)))
+            }
+        })
+        .flat_map(stream::iter)
+        .boxed()
+    }
+
+    /// Atomically reserves an identifier within a tenant.
+    ///
+    /// The reservation fails if a stored user of the tenant already has the identifier, or if
+    /// another signup currently holds it. The reservation is removed when the guard ends.
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier to claim.
+    /// * `tenant_id` - The tenant the identifier belongs to, or `None` for no tenant.
+    ///
+    /// # Returns
+    /// * `Ok(ReservationGuard)` if the identifier was claimed.
+    /// * `Err(AuthError::UserAlreadyExists)` if the identifier is taken or reserved.
+    /// * `Err(AuthError)` if the repository is unavailable.
+    async fn reserve_identifier(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<ReservationGuard, crate::error::AuthError> {
+        // Lock users before reservations, in the same order everywhere, to avoid deadlocks
+        let users = self
+            .users
+            .lock()
+            .map_err(|e| crate::error::AuthError::ServiceUnavailable(e.to_string()))?;
+        let mut reserved = self
+            .reserved
+            .lock()
+            .map_err(|e| crate::error::AuthError::ServiceUnavailable(e.to_string()))?;
+
+        let taken = users.iter().any(|u| {
+            u.tenant_id.as_deref() == tenant_id
+                && u.credentials
+                    .as_ref()
+                    .is_some_and(|creds| creds.identifier == identifier)
+        });
+        let key = (tenant_id.map(str::to_string), identifier.to_string());
+        if taken || !reserved.insert(key.clone()) {
+            return Err(crate::error::AuthError::UserAlreadyExists);
         }
+
+        let reservations = Arc::clone(&self.reserved);
+        Ok(ReservationGuard::new(move |_committed| {
+            if let Ok(mut reserved) = reservations.lock() {
+                reserved.remove(&key);
+            }
+        }))
+    }
+
+    /// Opens a best-effort transaction over the repository.
+    ///
+    /// Writes made through the transaction are applied immediately and undone, in reverse
+    /// order, on rollback. There is no isolation: other callers see the writes before the
+    /// transaction ends, and concurrent changes to the same users may be overwritten by a
+    /// rollback.
+    ///
+    /// # Returns
+    /// * `Ok(Box<dyn RepositoryTransaction>)` - The open transaction.
+    async fn begin_transaction(
+        &self,
+    ) -> Result<Box<dyn RepositoryTransaction + '_>, crate::error::AuthError> {
+        Ok(Box::new(InMemoryTransaction {
+            repo: self,
+            undo: Mutex::new(Vec::new()),
+        }))
+    }
+
+    /// Updates an existing user in the repository, bumping its stored version.
+    ///
+    /// # Arguments
+    /// * `user` - The user with updated information.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the user was updated.
+    /// * `Err(AuthError::UserNotFound)` if the user does not exist.
+    /// * `Err(AuthError)` if the repository is unavailable.
+    async fn update_user(&self, user: &User) -> Result<(), crate::error::AuthError> {
+        self.write_user(user, None)
+    }
+
+    /// Updates an existing user if its stored version is `expected_version`, bumping it.
+    ///
+    /// The comparison and the write happen under the same lock.
+    ///
+    /// # Arguments
+    /// * `user` - The user with updated information.
+    /// * `expected_version` - The version of the user when it was loaded.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the user was updated.
+    /// * `Err(AuthError::ConcurrentModification)` if the user changed since it was loaded.
+    /// * `Err(AuthError::UserNotFound)` if the user does not exist.
+    /// * `Err(AuthError)` if the repository is unavailable.
+    async fn compare_and_update_user(
+        &self,
+        user: &User,
+        expected_version: u64,
+    ) -> Result<(), crate::error::AuthError> {
+        self.write_user(user, Some(expected_version))
     }
 
     /// Deletes a user from the repository by their ID.
@@ -116,13 +359,13 @@ impl UserRepository for InMemoryUserRepo {
     /// * `Ok(())` if the user was deleted.
     /// * `Err(AuthError::UserNotFound)` if the user does not exist.
     /// * `Err(AuthError)` if the repository is unavailable.
-    async fn delete_user(&self, id: &str) -> Result<(), crate::error::AuthError> {
+    async fn delete_user(&self, id: &UserId) -> Result<(), crate::error::AuthError> {
         let mut users = self
             .users
             .lock()
             .map_err(|e| crate::error::AuthError::ServiceUnavailable(e.to_string()))?;
         let len_before = users.len();
-        users.retain(|u| u.id != id);
+        users.retain(|u| &u.id != id);
         if users.len() < len_before {
             Ok(())
         } else {
@@ -155,3 +398,179 @@ impl UserRepository for InMemoryUserRepo {
             .cloned()
     }
 }
+
+/// A write made by an [`InMemoryTransaction`], recorded so that it can be undone.
+enum UndoEntry {
+    /// Removes a user added by the transaction.
+    Remove(UserId),
+    /// Restores the previous state of a user updated by the transaction.
+    Restore(User),
+    /// Re-adds a user deleted by the transaction.
+    Reinsert(User),
+}
+
+/// A best-effort transaction over an [`InMemoryUserRepo`], journaling its writes for rollback.
+struct InMemoryTransaction<'a> {
+    /// The repository the transaction writes to.
+    repo: &'a InMemoryUserRepo,
+    /// The writes made so far, in order.
+    undo: Mutex<Vec<UndoEntry>>,
+}
+
+impl InMemoryTransaction<'_> {
+    /// Records a write to undo on rollback.
+    fn record(&self, entry: UndoEntry) -> Result<(), crate::error::AuthError> {
+        self.undo
+            .lock()
+            .map_err(|e| crate::error::AuthError::ServiceUnavailable(e.to_string()))?
+            .push(entry);
+        Ok(())
+    }
+
+    /// Returns the stored state of the user with the given ID.
+    fn snapshot(&self, id: &UserId) -> Result<Option<User>, crate::error::AuthError> {
+        let users = self
+            .repo
+            .users
+            .lock()
+            .map_err(|e| crate::error::AuthError::ServiceUnavailable(e.to_string()))?;
+        Ok(users.iter().find(|u| &u.id == id).cloned())
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryTransaction<'_> {
+    async fn begin_transaction(
+        &self,
+    ) -> Result<Box<dyn RepositoryTransaction + '_>, crate::error::AuthError> {
+        Err(nested_transaction_error())
+    }
+
+    async fn add_user(&self, user: User) -> Result<User, crate::error::AuthError> {
+        let user = self.repo.add_user(user).await?;
+        self.record(UndoEntry::Remove(user.id.clone()))?;
+        Ok(user)
+    }
+
+    async fn get_user_by_id(&self, id: &UserId) -> Option<User> {
+        self.repo.get_user_by_id(id).await
+    }
+
+    async fn get_user_by_identifier(&self, identifier: &str) -> Option<User> {
+        self.repo.get_user_by_identifier(identifier).await
+    }
+
+    async fn get_user_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Option<User> {
+        self.repo
+            .get_user_by_identifier_in_tenant(identifier, tenant_id)
+            .await
+    }
+
+    async fn get_credentials_by_identifier(
+        &self,
+        identifier: &str,
+    ) -> Result<Option<StoredCredentials>, crate::error::AuthError> {
+        self.repo.get_credentials_by_identifier(identifier).await
+    }
+
+    async fn get_credentials_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<StoredCredentials>, crate::error::AuthError> {
+        self.repo
+            .get_credentials_by_identifier_in_tenant(identifier, tenant_id)
+            .await
+    }
+
+    async fn list_credentials(&self) -> Result<Vec<StoredCredentials>, crate::error::AuthError> {
+        self.repo.list_credentials().await
+    }
+
+    fn stream_users(&self) -> UserStream<'_> {
+        self.repo.stream_users()
+    }
+
+    async fn reserve_identifier(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<ReservationGuard, crate::error::AuthError> {
+        self.repo.reserve_identifier(identifier, tenant_id).await
+    }
+
+    async fn update_user(&self, user: &User) -> Result<(), crate::error::AuthError> {
+        let previous = self
+            .snapshot(&user.id)?
+            .ok_or(crate::error::AuthError::UserNotFound)?;
+        self.repo.update_user(user).await?;
+        self.record(UndoEntry::Restore(previous))
+    }
+
+    async fn compare_and_update_user(
+        &self,
+        user: &User,
+        expected_version: u64,
+    ) -> Result<(), crate::error::AuthError> {
+        let previous = self
+            .snapshot(&user.id)?
+            .ok_or(crate::error::AuthError::UserNotFound)?;
+        self.repo
+            .compare_and_update_user(user, expected_version)
+            .await?;
+        self.record(UndoEntry::Restore(previous))
+    }
+
+    async fn delete_user(&self, id: &UserId) -> Result<(), crate::error::AuthError> {
+        let previous = self
+            .snapshot(id)?
+            .ok_or(crate::error::AuthError::UserNotFound)?;
+        self.repo.delete_user(id).await?;
+        self.record(UndoEntry::Reinsert(previous))
+    }
+
+    async fn get_user_by_oauth_id(
+        &self,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        provider_user_id: &str,
+    ) -> Option<User> {
+        self.repo
+            .get_user_by_oauth_id(provider, provider_user_id)
+            .await
+    }
+}
+
+#[async_trait]
+impl RepositoryTransaction for InMemoryTransaction<'_> {
+    async fn commit(self: Box<Self>) -> Result<(), crate::error::AuthError> {
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), crate::error::AuthError> {
+        let undo = self
+            .undo
+            .into_inner()
+            .map_err(|e| crate::error::AuthError::ServiceUnavailable(e.to_string()))?;
+        let mut users = self
+            .repo
+            .users
+            .lock()
+            .map_err(|e| crate::error::AuthError::ServiceUnavailable(e.to_string()))?;
+        for entry in undo.into_iter().rev() {
+            match entry {
+                UndoEntry::Remove(id) => users.retain(|u| u.id != id),
+                UndoEntry::Restore(previous) => {
+                    if let Some(existing) = users.iter_mut().find(|u| u.id == previous.id) {
+                        *existing = previous;
+                    }
+                }
+                UndoEntry::Reinsert(previous) => users.push(previous),
+            }
+        }
+        Ok(())
+    }
+}
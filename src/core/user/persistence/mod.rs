@@ -5,7 +5,9 @@
 //!
 //! # Modules
 //! - [`in_memory`]: In-memory user repository for testing and ephemeral use.
+//! - [`reservation`]: Identifier reservations used to make signups race-free.
 //! - [`store`]: Persistent user storage implementation.
+//! - [`transaction`]: Repository transactions for multi-step writes.
 //! - [`traits`]: Core traits for user repository abstraction.
 //!
 //! # Re-exports
@@ -17,6 +19,11 @@
 /// Useful for testing and non-persistent scenarios.
 pub mod in_memory;
 
+/// Identifier reservations for race-free signups.
+///
+/// This module provides the [`ReservationGuard`] returned by [`UserRepository::reserve_identifier`].
+pub mod reservation;
+
 /// Persistent user storage implementation.
 ///
 /// This module provides a user repository backed by a persistent data store (e.g., database).
@@ -27,13 +34,24 @@ pub mod store;
 /// This module defines the [`UserRepository`] trait and related abstractions for user data operations.
 pub mod traits;
 
+/// Repository transactions for multi-step writes.
+///
+/// This module provides the [`RepositoryTransaction`] returned by [`UserRepository::begin_transaction`].
+pub mod transaction;
+
 // Re-export the main types and traits for easier access
 
 /// Re-export of the in-memory user repository for convenient access.
 pub use in_memory::InMemoryUserRepo;
 
+/// Re-export of the identifier reservation guard for convenient access.
+pub use reservation::ReservationGuard;
+
 /// Re-export of the persistent user storage type for convenient access.
 pub use store::PersistentUsers;
 
+/// Re-export of the repository transaction types for convenient access.
+pub use transaction::{RepositoryTransaction, TransactionFuture};
+
 /// Re-export of the core user repository trait for convenient access.
-pub use traits::UserRepository;
+pub use traits::{UserRepository, UserStream};
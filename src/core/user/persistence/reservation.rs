@@ -0,0 +1,71 @@
+//! Identifier reservations for race-free signups.
+//!
+//! A check-then-insert signup has a time-of-check to time-of-use window: two concurrent
+//! signups can both observe that an identifier is free, then both hash their password and
+//! insert. Repositories close this window by atomically claiming the identifier up front with
+//! [`UserRepository::reserve_identifier`](super::UserRepository::reserve_identifier) and
+//! returning a [`ReservationGuard`] that is held across the hashing step.
+//!
+//! # SQL backends
+//!
+//! With a unique index on the identifier, a reservation is simply an `INSERT` of a
+//! placeholder row (e.g., into a `cryptic_identifier_reservations` table with a unique
+//! `(tenant_id, identifier)` constraint) that fails with a unique violation when the
+//! identifier is taken. Committing the guard keeps the row (or replaces it with the real
+//! credentials), releasing it deletes the row.
+
+/// Callback invoked once when a reservation ends, with `true` if it was committed.
+type ReleaseFn = Box<dyn FnOnce(bool) + Send + Sync>;
+
+/// A claim on an identifier, held while a user is being created.
+///
+/// The reservation ends when the guard is committed with [`ReservationGuard::commit`] (the
+/// user was stored) or dropped (the signup failed), so an aborted signup never leaves the
+/// identifier claimed.
+#[must_use = "dropping the guard immediately releases the reservation"]
+pub struct ReservationGuard {
+    /// Callback ending the reservation, taken on commit or drop.
+    on_release: Option<ReleaseFn>,
+}
+
+impl ReservationGuard {
+    /// Creates a guard that calls `on_release` exactly once when the reservation ends.
+    ///
+    /// # Arguments
+    ///
+    /// * `on_release` - Called with `true` when the guard is committed, or `false` when dropped.
+    pub fn new(on_release: impl FnOnce(bool) + Send + Sync + 'static) -> Self {
+        Self {
+            on_release: Some(Box::new(on_release)),
+        }
+    }
+
+    /// Creates a guard that holds no reservation, for repositories relying on other mechanisms
+    /// (such as a unique index checked at insert time).
+    pub fn noop() -> Self {
+        Self { on_release: None }
+    }
+
+    /// Ends the reservation after the user was successfully stored.
+    pub fn commit(mut self) {
+        if let Some(on_release) = self.on_release.take() {
+            on_release(true);
+        }
+    }
+}
+
+impl Drop for ReservationGuard {
+    fn drop(&mut self) {
+        if let Some(on_release) = self.on_release.take() {
+            on_release(false);
+        }
+    }
+}
+
+impl std::fmt::Debug for ReservationGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReservationGuard")
+            .field("active", &self.on_release.is_some())
+            .finish()
+    }
+}
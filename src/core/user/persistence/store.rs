@@ -1,5 +1,5 @@
 use super::{in_memory::InMemoryUserRepo, traits::UserRepository};
-use crate::core::user::User;
+use crate::core::user::{User, UserId};
 use async_trait::async_trait;
 
 #[cfg(feature = "postgres")]
@@ -84,7 +84,7 @@ impl UserRepository for PersistentUsers {
     /// # Returns
     ///
     /// `Some(User)` if found, or `None` if no user with the given ID exists.
-    async fn get_user_by_id(&self, id: &str) -> Option<User> {
+    async fn get_user_by_id(&self, id: &UserId) -> Option<User> {
         match self {
             PersistentUsers::InMemory(repo) => repo.get_user_by_id(id).await,
             #[cfg(feature = "postgres")]
@@ -92,6 +92,25 @@ impl UserRepository for PersistentUsers {
         }
     }
 
+    /// Retrieves a user by their unique ID, reporting storage failures.
+    ///
+    /// Delegates to the underlying backend implementation.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the user.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Some(User))` if found, `Ok(None)` if not, or an `AuthError` if the backend could not
+    /// be queried.
+    async fn find_user_by_id(&self, id: &UserId) -> Result<Option<User>, crate::error::AuthError> {
+        match self {
+            PersistentUsers::InMemory(repo) => repo.find_user_by_id(id).await,
+            #[cfg(feature = "postgres")]
+            PersistentUsers::PostgresDatabase(repo) => repo.find_user_by_id(id).await,
+        }
+    }
+
     /// Retrieves a user by a unique identifier (e.g., username or email).
     ///
     /// Delegates to the underlying backend implementation.
@@ -112,6 +131,198 @@ impl UserRepository for PersistentUsers {
         }
     }
 
+    /// Retrieves a user by identifier within a tenant.
+    ///
+    /// Delegates to the underlying backend implementation.
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier (such as username or email).
+    /// * `tenant_id` - The tenant to search in, or `None` for users without a tenant.
+    ///
+    /// # Returns
+    ///
+    /// `Some(User)` if found in the tenant, or `None` otherwise.
+    async fn get_user_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Option<User> {
+        match self {
+            PersistentUsers::InMemory(repo) => {
+                repo.get_user_by_identifier_in_tenant(identifier, tenant_id)
+                    .await
+            }
+            #[cfg(feature = "postgres")]
+            PersistentUsers::PostgresDatabase(repo) => {
+                repo.get_user_by_identifier_in_tenant(identifier, tenant_id)
+                    .await
+            }
+        }
+    }
+
+    /// Retrieves a user by identifier within a tenant, reporting storage failures.
+    ///
+    /// Delegates to the underlying backend implementation.
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier (such as username or email).
+    /// * `tenant_id` - The tenant to search in, or `None` for users without a tenant.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Some(User))` if found in the tenant, `Ok(None)` if not, or an `AuthError` if the
+    /// backend could not be queried.
+    async fn find_user_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<User>, crate::error::AuthError> {
+        match self {
+            PersistentUsers::InMemory(repo) => {
+                repo.find_user_by_identifier_in_tenant(identifier, tenant_id)
+                    .await
+            }
+            #[cfg(feature = "postgres")]
+            PersistentUsers::PostgresDatabase(repo) => {
+                repo.find_user_by_identifier_in_tenant(identifier, tenant_id)
+                    .await
+            }
+        }
+    }
+
+    /// Retrieves the credentials for an identifier.
+    ///
+    /// Delegates to the underlying backend implementation.
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier (such as username or email).
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Some(StoredCredentials))` if found, `Ok(None)` if not, or an `AuthError` if the
+    /// backend could not be queried.
+    async fn get_credentials_by_identifier(
+        &self,
+        identifier: &str,
+    ) -> Result<Option<crate::core::credentials::StoredCredentials>, crate::error::AuthError> {
+        match self {
+            PersistentUsers::InMemory(repo) => repo.get_credentials_by_identifier(identifier).await,
+            #[cfg(feature = "postgres")]
+            PersistentUsers::PostgresDatabase(repo) => {
+                repo.get_credentials_by_identifier(identifier).await
+            }
+        }
+    }
+
+    /// Retrieves the credentials for an identifier within a tenant.
+    ///
+    /// Delegates to the underlying backend implementation.
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier (such as username or email).
+    /// * `tenant_id` - The tenant to search in, or `None` for users without a tenant.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Some(StoredCredentials))` if found in the tenant, `Ok(None)` if not, or an
+    /// `AuthError` if the backend could not be queried.
+    async fn get_credentials_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<crate::core::credentials::StoredCredentials>, crate::error::AuthError> {
+        match self {
+            PersistentUsers::InMemory(repo) => {
+                repo.get_credentials_by_identifier_in_tenant(identifier, tenant_id)
+                    .await
+            }
+            #[cfg(feature = "postgres")]
+            PersistentUsers::PostgresDatabase(repo) => {
+                repo.get_credentials_by_identifier_in_tenant(identifier, tenant_id)
+                    .await
+            }
+        }
+    }
+
+    /// Retrieves the credentials of every user with a password.
+    ///
+    /// Delegates to the underlying backend implementation.
+    ///
+    /// # Returns
+    ///
+    /// The credentials of all users, or an `AuthError` if the backend could not be queried.
+    async fn list_credentials(
+        &self,
+    ) -> Result<Vec<crate::core::credentials::StoredCredentials>, crate::error::AuthError> {
+        match self {
+            PersistentUsers::InMemory(repo) => repo.list_credentials().await,
+            #[cfg(feature = "postgres")]
+            PersistentUsers::PostgresDatabase(repo) => repo.list_credentials().await,
+        }
+    }
+
+    /// Streams every user of the repository.
+    ///
+    /// Delegates to the underlying backend implementation.
+    ///
+    /// # Returns
+    ///
+    /// A stream of users, ending early with an `AuthError` if the backend could not be read.
+    fn stream_users(&self) -> crate::core::user::persistence::UserStream<'_> {
+        match self {
+            PersistentUsers::InMemory(repo) => repo.stream_users(),
+            #[cfg(feature = "postgres")]
+            PersistentUsers::PostgresDatabase(repo) => repo.stream_users(),
+        }
+    }
+
+    /// Atomically claims an identifier within a tenant.
+    ///
+    /// Delegates to the underlying backend implementation.
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier to claim.
+    /// * `tenant_id` - The tenant the identifier belongs to, or `None` for no tenant.
+    ///
+    /// # Returns
+    ///
+    /// A [`ReservationGuard`](crate::core::user::persistence::ReservationGuard) holding the claim,
+    /// or `AuthError::UserAlreadyExists` if the identifier is taken.
+    async fn reserve_identifier(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<crate::core::user::persistence::ReservationGuard, crate::error::AuthError> {
+        match self {
+            PersistentUsers::InMemory(repo) => repo.reserve_identifier(identifier, tenant_id).await,
+            #[cfg(feature = "postgres")]
+            PersistentUsers::PostgresDatabase(repo) => {
+                repo.reserve_identifier(identifier, tenant_id).await
+            }
+        }
+    }
+
+    /// Opens a transaction over the repository.
+    ///
+    /// Delegates to the underlying backend implementation.
+    ///
+    /// # Returns
+    ///
+    /// The open [`RepositoryTransaction`](crate::core::user::persistence::RepositoryTransaction),
+    /// or an `AuthError` if it could not be started.
+    async fn begin_transaction(
+        &self,
+    ) -> Result<
+        Box<dyn crate::core::user::persistence::RepositoryTransaction + '_>,
+        crate::error::AuthError,
+    > {
+        match self {
+            PersistentUsers::InMemory(repo) => repo.begin_transaction().await,
+            #[cfg(feature = "postgres")]
+            PersistentUsers::PostgresDatabase(repo) => repo.begin_transaction().await,
+        }
+    }
+
     /// Updates an existing user in the repository.
     ///
     /// Delegates to the underlying backend implementation.
@@ -130,6 +341,35 @@ impl UserRepository for PersistentUsers {
         }
     }
 
+    /// Updates an existing user only if its stored version is `expected_version`.
+    ///
+    /// Delegates to the underlying backend implementation.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user entity with updated fields.
+    /// * `expected_version` - The version of the user when it was loaded.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the update was successful, `AuthError::ConcurrentModification` if the user
+    /// changed since it was loaded, or another `AuthError` otherwise.
+    async fn compare_and_update_user(
+        &self,
+        user: &User,
+        expected_version: u64,
+    ) -> Result<(), crate::error::AuthError> {
+        match self {
+            PersistentUsers::InMemory(repo) => {
+                repo.compare_and_update_user(user, expected_version).await
+            }
+            #[cfg(feature = "postgres")]
+            PersistentUsers::PostgresDatabase(repo) => {
+                repo.compare_and_update_user(user, expected_version).await
+            }
+        }
+    }
+
     /// Deletes a user from the repository by their unique ID.
     ///
     /// Delegates to the underlying backend implementation.
@@ -140,7 +380,7 @@ impl UserRepository for PersistentUsers {
     /// # Returns
     ///
     /// `Ok(())` if the user was deleted successfully, or an `AuthError` otherwise.
-    async fn delete_user(&self, id: &str) -> Result<(), crate::error::AuthError> {
+    async fn delete_user(&self, id: &UserId) -> Result<(), crate::error::AuthError> {
         match self {
             PersistentUsers::InMemory(repo) => repo.delete_user(id).await,
             #[cfg(feature = "postgres")]
@@ -174,4 +414,33 @@ impl UserRepository for PersistentUsers {
             }
         }
     }
+
+    /// Retrieves a user by their OAuth provider and provider user ID, reporting storage
+    /// failures.
+    ///
+    /// Delegates to the underlying backend implementation.
+    ///
+    /// # Arguments
+    /// * `provider` - The OAuth2 provider.
+    /// * `provider_user_id` - The user ID from the OAuth provider.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Some(User))` if found, `Ok(None)` if not, or an `AuthError` if the backend could not
+    /// be queried.
+    async fn find_user_by_oauth_id(
+        &self,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        provider_user_id: &str,
+    ) -> Result<Option<User>, crate::error::AuthError> {
+        match self {
+            PersistentUsers::InMemory(repo) => {
+                repo.find_user_by_oauth_id(provider, provider_user_id).await
+            }
+            #[cfg(feature = "postgres")]
+            PersistentUsers::PostgresDatabase(repo) => {
+                repo.find_user_by_oauth_id(provider, provider_user_id).await
+            }
+        }
+    }
 }
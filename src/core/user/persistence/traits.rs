@@ -1,6 +1,13 @@
+use crate::core::credentials::StoredCredentials;
+use crate::core::user::persistence::ReservationGuard;
+use crate::core::user::persistence::transaction::{NoopTransaction, RepositoryTransaction};
 /// Traits and abstractions for user persistence operations.
-use crate::core::user::User;
+use crate::core::user::{User, UserId};
 use async_trait::async_trait;
+use futures_util::stream::{self, BoxStream, StreamExt};
+
+/// A stream of users, as returned by [`UserRepository::stream_users`].
+pub type UserStream<'a> = BoxStream<'a, Result<User, crate::error::AuthError>>;
 
 /// An abstraction for user persistence, allowing async CRUD operations on users.
 ///
@@ -11,6 +18,10 @@ use async_trait::async_trait;
 pub trait UserRepository: Send + Sync {
     /// Adds a new user to the repository.
     ///
+    /// The user is stored with its roles, credentials and OAuth accounts atomically:
+    /// implementations writing them separately must do so in a single transaction, so that a
+    /// failed write (e.g., a duplicate identifier) leaves no partial user behind.
+    ///
     /// # Arguments
     /// * `user` - The user entity to be added.
     ///
@@ -27,9 +38,31 @@ pub trait UserRepository: Send + Sync {
     /// # Returns
     /// * `Some(User)` - The user if found.
     /// * `None` - If no user exists with the given id.
-    async fn get_user_by_id(&self, id: &str) -> Option<User>;
+    async fn get_user_by_id(&self, id: &UserId) -> Option<User>;
 
-    /// Retrieves a user by a unique identifier (e.g., username or email).
+    /// Retrieves a user by their unique id, reporting storage failures.
+    ///
+    /// [`UserRepository::get_user_by_id`] cannot tell a missing user from an unreachable store.
+    /// Logins and token validation use this method instead, so that a connection loss surfaces
+    /// as [`AuthError::StorageUnavailable`](crate::error::AuthError::StorageUnavailable) rather
+    /// than as a rejected request. Backends that can fail should override it; the default
+    /// implementation wraps [`UserRepository::get_user_by_id`] and never fails.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the user.
+    ///
+    /// # Returns
+    /// * `Ok(Some(User))` - The user if found.
+    /// * `Ok(None)` - If no user exists with the given id.
+    /// * `Err(AuthError)` - If the repository could not be queried.
+    async fn find_user_by_id(&self, id: &UserId) -> Result<Option<User>, crate::error::AuthError> {
+        Ok(self.get_user_by_id(id).await)
+    }
+
+    /// Retrieves a user without a tenant by identifier (e.g., username or email).
+    ///
+    /// Identifiers are only unique within a tenant, so users of a tenant must never be
+    /// matched: use [`UserRepository::get_user_by_identifier_in_tenant`] to look them up.
     ///
     /// # Arguments
     /// * `identifier` - The unique identifier (such as username or email).
@@ -39,6 +72,178 @@ pub trait UserRepository: Send + Sync {
     /// * `None` - If no user exists with the given identifier.
     async fn get_user_by_identifier(&self, identifier: &str) -> Option<User>;
 
+    /// Retrieves a user by identifier within a tenant.
+    ///
+    /// Identifiers are only unique within a tenant, so multi-tenant repositories must
+    /// override this method. The default implementation filters the result of
+    /// [`UserRepository::get_user_by_identifier`], so it only finds users without a tenant.
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier (such as username or email).
+    /// * `tenant_id` - The tenant to search in, or `None` for users without a tenant.
+    ///
+    /// # Returns
+    /// * `Some(User)` - The user if found in the given tenant.
+    /// * `None` - If no user in the tenant has the given identifier.
+    async fn get_user_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Option<User> {
+        self.get_user_by_identifier(identifier)
+            .await
+            .filter(|user| user.tenant_id.as_deref() == tenant_id)
+    }
+
+    /// Retrieves a user by identifier within a tenant, reporting storage failures.
+    ///
+    /// Like [`UserRepository::find_user_by_id`], logins use this method so that a connection
+    /// loss is not mistaken for an unknown identifier. Backends that can fail should override
+    /// it; the default implementation wraps [`UserRepository::get_user_by_identifier_in_tenant`]
+    /// and never fails.
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier (such as username or email).
+    /// * `tenant_id` - The tenant to search in, or `None` for users without a tenant.
+    ///
+    /// # Returns
+    /// * `Ok(Some(User))` - The user if found in the given tenant.
+    /// * `Ok(None)` - If no user in the tenant has the given identifier.
+    /// * `Err(AuthError)` - If the repository could not be queried.
+    async fn find_user_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<User>, crate::error::AuthError> {
+        Ok(self
+            .get_user_by_identifier_in_tenant(identifier, tenant_id)
+            .await)
+    }
+
+    /// Retrieves only the user id and password hash for an identifier, among users without a
+    /// tenant.
+    ///
+    /// Logins verify the password against these credentials and load the full user (OAuth
+    /// accounts, metadata, roles) only on success. Backends that store credentials separately,
+    /// such as SQL databases, should override this method to avoid assembling the user on every
+    /// attempt. The default implementation extracts the credentials from
+    /// [`UserRepository::get_user_by_identifier`].
+    ///
+    /// # Arguments
+    /// * `identifier` - The unique identifier (such as username or email).
+    ///
+    /// # Returns
+    /// * `Ok(Some(StoredCredentials))` - The credentials if found.
+    /// * `Ok(None)` - If no user has password credentials with the given identifier.
+    /// * `Err(AuthError)` - If the repository could not be queried.
+    async fn get_credentials_by_identifier(
+        &self,
+        identifier: &str,
+    ) -> Result<Option<StoredCredentials>, crate::error::AuthError> {
+        Ok(self
+            .get_user_by_identifier(identifier)
+            .await
+            .and_then(|user| stored_credentials(&user)))
+    }
+
+    /// Retrieves only the user id and password hash for an identifier within a tenant.
+    ///
+    /// See [`UserRepository::get_credentials_by_identifier`]. The default implementation extracts
+    /// the credentials from [`UserRepository::get_user_by_identifier_in_tenant`].
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier (such as username or email).
+    /// * `tenant_id` - The tenant to search in, or `None` for users without a tenant.
+    ///
+    /// # Returns
+    /// * `Ok(Some(StoredCredentials))` - The credentials if found in the given tenant.
+    /// * `Ok(None)` - If no user in the tenant has password credentials with the given identifier.
+    /// * `Err(AuthError)` - If the repository could not be queried.
+    async fn get_credentials_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<StoredCredentials>, crate::error::AuthError> {
+        Ok(self
+            .get_user_by_identifier_in_tenant(identifier, tenant_id)
+            .await
+            .and_then(|user| stored_credentials(&user)))
+    }
+
+    /// Retrieves the user id and password hash of every user with password credentials.
+    ///
+    /// Used for maintenance scans reading only password hashes. The default implementation returns [`AuthError::NotImplemented`](crate::error::AuthError::NotImplemented).
+    ///
+    /// # Returns
+    /// * `Ok(Vec<StoredCredentials>)` - The credentials of all users, in no particular order.
+    /// * `Err(AuthError)` - If the repository cannot list users or could not be queried.
+    async fn list_credentials(&self) -> Result<Vec<StoredCredentials>, crate::error::AuthError> {
+        Err(crate::error::AuthError::NotImplemented(
+            "list_credentials is not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Streams every user of the repository, across tenants, for exports and backups.
+    ///
+    /// Implementations should fetch users in batches rather than loading them all in memory:
+    /// SQL backends should read from a server-side cursor (or keyset pagination) instead of
+    /// a single `SELECT`. Users written while the stream is consumed may or may not be
+    /// yielded. The default implementation yields a single
+    /// [`AuthError::NotImplemented`](crate::error::AuthError::NotImplemented).
+    ///
+    /// # Returns
+    /// A stream of `Ok(User)` items in no particular order, ending after the last user or
+    /// after an `Err(AuthError)` if the repository could not be read.
+    fn stream_users(&self) -> UserStream<'_> {
+        stream::once(async {
+            Err(crate::error::AuthError::NotImplemented(
+                "stream_users is not supported by this repository".to_string(),
+            ))
+        })
+        .boxed()
+    }
+
+    /// Atomically claims an identifier within a tenant until the returned guard is committed or dropped.
+    ///
+    /// Signups hold the reservation across the password hashing step so that two concurrent
+    /// signups for the same identifier cannot both succeed. The default implementation
+    /// returns a no-op guard, which is only safe when [`UserRepository::add_user`] enforces
+    /// identifier uniqueness itself (e.g., through a unique index). See the
+    /// [`reservation`](crate::core::user::persistence::reservation) module for the SQL approach.
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier (such as username or email) to claim.
+    /// * `tenant_id` - The tenant the identifier belongs to, or `None` for no tenant.
+    ///
+    /// # Returns
+    /// * `Ok(ReservationGuard)` - The identifier is claimed until the guard ends.
+    /// * `Err(AuthError::UserAlreadyExists)` - If the identifier is used or already reserved.
+    async fn reserve_identifier(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<ReservationGuard, crate::error::AuthError> {
+        let _ = (identifier, tenant_id);
+        Ok(ReservationGuard::noop())
+    }
+
+    /// Opens a transaction whose writes are committed or rolled back together.
+    ///
+    /// Most callers should use `transaction` on `dyn UserRepository`, which commits or rolls
+    /// back based on the closure's result. The default implementation returns a no-op wrapper
+    /// that applies writes immediately and cannot roll them back. See the
+    /// [`transaction`](crate::core::user::persistence::transaction) module for the guarantees
+    /// of the built-in repositories.
+    ///
+    /// # Returns
+    /// * `Ok(Box<dyn RepositoryTransaction>)` - The open transaction.
+    /// * `Err(AuthError)` - If the transaction could not be started.
+    async fn begin_transaction(
+        &self,
+    ) -> Result<Box<dyn RepositoryTransaction + '_>, crate::error::AuthError> {
+        Ok(Box::new(NoopTransaction::new(self)))
+    }
+
     /// Updates an existing user in the repository.
     ///
     /// # Arguments
@@ -49,6 +254,48 @@ pub trait UserRepository: Send + Sync {
     /// * `Err(AuthError)` - If the update failed (e.g., user not found, DB error).
     async fn update_user(&self, user: &User) -> Result<(), crate::error::AuthError>;
 
+    /// Updates an existing user only if it was not modified since it was loaded.
+    ///
+    /// Two writers loading the same user would otherwise silently overwrite each other's
+    /// changes with [`UserRepository::update_user`]. With this method, the first write wins
+    /// and bumps the stored [`User::version`], and the second is rejected.
+    ///
+    /// Implementations must compare and write atomically. SQL backends should bump a
+    /// `version` column in the update itself and treat an update matching no row as a
+    /// conflict:
+    ///
+    /// ```sql
+    /// UPDATE cryptic_users SET ..., version = version + 1 WHERE id = $1 AND version = $2
+    /// ```
+    ///
+    /// The default implementation compares the version of the stored user before calling
+    /// [`UserRepository::update_user`], which narrows the race without closing it, and
+    /// detects nothing for repositories that do not track versions.
+    ///
+    /// # Arguments
+    /// * `user` - The user entity with updated fields.
+    /// * `expected_version` - The [`User::version`] of the user when it was loaded.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the update was successful.
+    /// * `Err(AuthError::ConcurrentModification)` - If the stored version differs from
+    ///   `expected_version`.
+    /// * `Err(AuthError)` - If the update failed (e.g., user not found, DB error).
+    async fn compare_and_update_user(
+        &self,
+        user: &User,
+        expected_version: u64,
+    ) -> Result<(), crate::error::AuthError> {
+        let stored = self
+            .find_user_by_id(&user.id)
+            .await?
+            .ok_or(crate::error::AuthError::UserNotFound)?;
+        if stored.version != expected_version {
+            return Err(crate::error::AuthError::ConcurrentModification);
+        }
+        self.update_user(user).await
+    }
+
     /// Deletes a user from the repository by their id.
     ///
     /// # Arguments
@@ -57,10 +304,13 @@ pub trait UserRepository: Send + Sync {
     /// # Returns
     /// * `Ok(())` - If the user was successfully deleted.
     /// * `Err(AuthError)` - If the deletion failed (e.g., user not found, DB error).
-    async fn delete_user(&self, id: &str) -> Result<(), crate::error::AuthError>;
+    async fn delete_user(&self, id: &UserId) -> Result<(), crate::error::AuthError>;
 
     /// Retrieves a user by their OAuth provider and provider user ID.
     ///
+    /// A provider account links to a single user across tenants, so the lookup is not
+    /// tenant-scoped: callers must check the [`User::tenant_id`] of the result.
+    ///
     /// # Arguments
     /// * `provider` - The OAuth2 provider.
     /// * `provider_user_id` - The user ID from the OAuth provider.
@@ -73,4 +323,36 @@ pub trait UserRepository: Send + Sync {
         provider: crate::core::oauth::store::OAuth2Provider,
         provider_user_id: &str,
     ) -> Option<User>;
+
+    /// Retrieves a user by their OAuth provider and provider user ID, reporting storage
+    /// failures.
+    ///
+    /// Backends that can fail should override it; the default implementation wraps
+    /// [`UserRepository::get_user_by_oauth_id`] and never fails.
+    ///
+    /// # Arguments
+    /// * `provider` - The OAuth2 provider.
+    /// * `provider_user_id` - The user ID from the OAuth provider.
+    ///
+    /// # Returns
+    /// * `Ok(Some(User))` - The user if found.
+    /// * `Ok(None)` - If no user exists with the given OAuth credentials.
+    /// * `Err(AuthError)` - If the repository could not be queried.
+    async fn find_user_by_oauth_id(
+        &self,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        provider_user_id: &str,
+    ) -> Result<Option<User>, crate::error::AuthError> {
+        Ok(self.get_user_by_oauth_id(provider, provider_user_id).await)
+    }
+}
+
+/// Extracts the login credentials of `user`, if it has a password.
+pub(crate) fn stored_credentials(user: &User) -> Option<StoredCredentials> {
+    user.credentials
+        .as_ref()
+        .map(|credentials| StoredCredentials {
+            user_id: user.id.clone(),
+            password_hash: credentials.password_hash.clone(),
+        })
 }
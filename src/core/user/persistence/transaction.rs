@@ -0,0 +1,244 @@
+//! Repository transactions for multi-step writes.
+//!
+//! Flows touching several entities (create a user, then write side tables) need all of their
+//! writes to succeed or none. [`UserRepository::begin_transaction`] opens a
+//! [`RepositoryTransaction`], a repository view whose writes are committed or rolled back
+//! together, and `transaction` (on `dyn UserRepository`) runs a closure inside one:
+//!
+//! ```rust,ignore
+//! let user = repo
+//!     .transaction(move |tx| {
+//!         Box::pin(async move {
+//!             let user = tx.add_user(user).await?;
+//!             tx.update_user(&user).await?;
+//!             Ok(user)
+//!         })
+//!     })
+//!     .await?;
+//! ```
+//!
+//! # Backend guarantees
+//!
+//! - SQL backends run the closure in a database transaction.
+//! - [`InMemoryUserRepo`](super::InMemoryUserRepo) applies writes immediately and undoes the
+//!   transaction's own writes on rollback. It provides no isolation from concurrent callers.
+//! - Transactions cannot be nested: beginning a transaction within one fails with
+//!   [`AuthError::NotImplemented`].
+//! - Repositories that do not override [`UserRepository::begin_transaction`] get a no-op
+//!   wrapper: writes are applied immediately and never rolled back.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use async_trait::async_trait;
+
+use super::reservation::ReservationGuard;
+use super::traits::{UserRepository, UserStream};
+use crate::core::credentials::StoredCredentials;
+use crate::core::oauth::store::OAuth2Provider;
+use crate::core::user::{User, UserId};
+use crate::error::AuthError;
+
+/// The future returned by the closure passed to `transaction`, borrowing the transaction.
+pub type TransactionFuture<'t, T> = Pin<Box<dyn Future<Output = Result<T, AuthError>> + Send + 't>>;
+
+/// An open transaction over a user repository.
+///
+/// Reads and writes go through the [`UserRepository`] methods of the transaction. Dropping a
+/// transaction without committing it rolls it back where the backend supports it.
+#[async_trait]
+pub trait RepositoryTransaction: UserRepository {
+    /// Makes the transaction's writes permanent.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the writes were committed.
+    /// * `Err(AuthError)` - If the commit failed, in which case the writes are discarded.
+    async fn commit(self: Box<Self>) -> Result<(), AuthError>;
+
+    /// Discards the transaction's writes.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the writes were discarded (or the backend cannot discard them).
+    /// * `Err(AuthError)` - If the rollback failed.
+    async fn rollback(self: Box<Self>) -> Result<(), AuthError>;
+}
+
+impl dyn UserRepository + Send + Sync {
+    /// Runs `f` within a repository transaction, committing if it succeeds and rolling back
+    /// if it fails.
+    ///
+    /// See the [module documentation](self) for the guarantees of each backend. The closure
+    /// receives the transaction as a repository and must return a boxed future, which can
+    /// only borrow the transaction: move any other data it needs into it.
+    ///
+    /// # Arguments
+    /// * `f` - The work to run, given the transaction.
+    ///
+    /// # Returns
+    /// * `Ok(T)` - The closure's result, once the transaction is committed.
+    /// * `Err(AuthError)` - The closure's error after rolling back, or a transaction error.
+    pub async fn transaction<T, F>(&self, f: F) -> Result<T, AuthError>
+    where
+        T: Send,
+        F: for<'t> FnOnce(&'t dyn UserRepository) -> TransactionFuture<'t, T> + Send,
+    {
+        let tx = self.begin_transaction().await?;
+        let result = f(&*tx).await;
+        match result {
+            Ok(value) => {
+                tx.commit().await?;
+                Ok(value)
+            }
+            Err(e) => {
+                if let Err(rollback_error) = tx.rollback().await {
+                    log::error!("Failed to roll back repository transaction: {rollback_error}");
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+/// The error returned when beginning a transaction within a transaction.
+pub(crate) fn nested_transaction_error() -> AuthError {
+    AuthError::NotImplemented("Nested repository transactions are not supported".to_string())
+}
+
+/// The transaction returned by the default [`UserRepository::begin_transaction`], delegating
+/// every operation to the repository without atomicity.
+pub(crate) struct NoopTransaction<'a, R: ?Sized> {
+    /// The repository operations are delegated to.
+    repo: &'a R,
+}
+
+impl<'a, R: ?Sized> NoopTransaction<'a, R> {
+    /// Wraps `repo`.
+    pub(crate) fn new(repo: &'a R) -> Self {
+        Self { repo }
+    }
+}
+
+#[async_trait]
+impl<R: UserRepository + ?Sized> UserRepository for NoopTransaction<'_, R> {
+    async fn begin_transaction(&self) -> Result<Box<dyn RepositoryTransaction + '_>, AuthError> {
+        Err(nested_transaction_error())
+    }
+
+    async fn add_user(&self, user: User) -> Result<User, AuthError> {
+        self.repo.add_user(user).await
+    }
+
+    async fn get_user_by_id(&self, id: &UserId) -> Option<User> {
+        self.repo.get_user_by_id(id).await
+    }
+
+    async fn find_user_by_id(&self, id: &UserId) -> Result<Option<User>, AuthError> {
+        self.repo.find_user_by_id(id).await
+    }
+
+    async fn get_user_by_identifier(&self, identifier: &str) -> Option<User> {
+        self.repo.get_user_by_identifier(identifier).await
+    }
+
+    async fn get_user_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Option<User> {
+        self.repo
+            .get_user_by_identifier_in_tenant(identifier, tenant_id)
+            .await
+    }
+
+    async fn find_user_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<User>, AuthError> {
+        self.repo
+            .find_user_by_identifier_in_tenant(identifier, tenant_id)
+            .await
+    }
+
+    async fn get_credentials_by_identifier(
+        &self,
+        identifier: &str,
+    ) -> Result<Option<StoredCredentials>, AuthError> {
+        self.repo.get_credentials_by_identifier(identifier).await
+    }
+
+    async fn get_credentials_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<StoredCredentials>, AuthError> {
+        self.repo
+            .get_credentials_by_identifier_in_tenant(identifier, tenant_id)
+            .await
+    }
+
+    async fn list_credentials(&self) -> Result<Vec<StoredCredentials>, AuthError> {
+        self.repo.list_credentials().await
+    }
+
+    fn stream_users(&self) -> UserStream<'_> {
+        self.repo.stream_users()
+    }
+
+    async fn reserve_identifier(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<ReservationGuard, AuthError> {
+        self.repo.reserve_identifier(identifier, tenant_id).await
+    }
+
+    async fn update_user(&self, user: &User) -> Result<(), AuthError> {
+        self.repo.update_user(user).await
+    }
+
+    async fn compare_and_update_user(
+        &self,
+        user: &User,
+        expected_version: u64,
+    ) -> Result<(), AuthError> {
+        self.repo
+            .compare_and_update_user(user, expected_version)
+            .await
+    }
+
+    async fn delete_user(&self, id: &UserId) -> Result<(), AuthError> {
+        self.repo.delete_user(id).await
+    }
+
+    async fn get_user_by_oauth_id(
+        &self,
+        provider: OAuth2Provider,
+        provider_user_id: &str,
+    ) -> Option<User> {
+        self.repo
+            .get_user_by_oauth_id(provider, provider_user_id)
+            .await
+    }
+
+    async fn find_user_by_oauth_id(
+        &self,
+        provider: OAuth2Provider,
+        provider_user_id: &str,
+    ) -> Result<Option<User>, AuthError> {
+        self.repo
+            .find_user_by_oauth_id(provider, provider_user_id)
+            .await
+    }
+}
+
+#[async_trait]
+impl<R: UserRepository + ?Sized> RepositoryTransaction for NoopTransaction<'_, R> {
+    async fn commit(self: Box<Self>) -> Result<(), AuthError> {
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), AuthError> {
+        Ok(())
+    }
+}
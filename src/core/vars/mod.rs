@@ -1,3 +1,68 @@
+use std::collections::HashMap;
+
+use crate::core::hash::Argon2Params;
+use crate::core::oauth::breaker::CircuitBreakerConfig;
+use crate::core::oauth::store::{OAuth2Config, OAuth2Provider};
+use crate::core::password::MalformedHashBehavior;
+use crate::core::policy::PasswordPolicy;
+use crate::core::rate_limit::RateLimit;
+use crate::core::token::refresh::RefreshStrategy;
+use crate::core::user::RoleSource;
+use crate::error::AuthError;
+
+/// Default access token lifetime (in seconds) used by [`AuthServiceVariables::from_env`].
+pub const DEFAULT_TOKEN_EXPIRATION: u64 = 3600;
+
+/// Default refresh token lifetime (in seconds) used by [`AuthServiceVariables::from_env`].
+pub const DEFAULT_REFRESH_TOKEN_EXPIRATION: u64 = 7 * 24 * 3600;
+
+/// Well-known placeholder secrets that must never be used in production.
+const PLACEHOLDER_SECRETS: &[&str] = &[
+    "",
+    "secret",
+    "changeme",
+    "change-me",
+    "change_me",
+    "default",
+    "mysecret",
+    "your-secret-key",
+    "super_secret_key",
+];
+
+/// Minimum password length below which a [`PasswordPolicy`] is reported as too weak.
+const MIN_POLICY_PASSWORD_LENGTH: usize = 8;
+
+/// A configuration problem found by [`AuthServiceVariables::validate`] or
+/// [`AuthService::validate_configuration`](crate::AuthService::validate_configuration).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// The misconfigured setting (e.g., `refresh_token_expiration` or `oauth_configs.google`).
+    pub field: String,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl ConfigIssue {
+    /// Creates an issue for `field`.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The misconfigured setting.
+    /// * `message` - A description of the problem.
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
 /// Configuration variables required for the authentication service.
 ///
 /// This struct holds the secret key and token expiration settings used by the authentication system.
@@ -7,6 +72,34 @@
 /// - `secret_key`: The cryptographic secret key used for signing and verifying tokens.
 /// - `token_expiration`: The duration (in seconds) for which an access token is valid.
 /// - `refresh_token_expiration`: The duration (in seconds) for which a refresh token is valid.
+/// - `argon2_params`: The Argon2 cost parameters used by the default password manager.
+/// - `oauth_configs`: The OAuth2 provider configurations used by the default OAuth2 manager.
+/// - `password_policy`: The policy enforced on new passwords, if any.
+/// - `disable_last_login_tracking`: Whether to skip recording `User::last_login_at` on login.
+/// - `require_oauth_link_verification`: Whether OAuth2 logins matching a password account by email need confirmation.
+/// - `email_otp_ttl`: The lifetime (in seconds) of email one-time passwords.
+/// - `email_otp_max_attempts`: The number of verification attempts allowed per email one-time password.
+/// - `password_reset_ttl`: The lifetime (in seconds) of password reset tokens.
+/// - `token_audiences`: The audiences accepted in tokens by the default token service.
+/// - `token_compression_threshold`: The payload size above which the default token service compresses tokens.
+/// - `refresh_strategy`: Whether the default token service rotates or reuses refresh tokens.
+/// - `verify_user_status_on_validation`: Whether token validation re-reads the user's status.
+/// - `role_source`: Where role checks read the roles of a token's user from by default.
+/// - `validation_concurrency`: The number of tokens `AuthService::validate_many` validates at once.
+/// - `password_hashing_timeout_ms`: The maximum duration (in milliseconds) of a password hash or verification.
+/// - `malformed_hash_behavior`: How the default password manager treats corrupt stored hashes.
+/// - `keep_session_on_password_change`: Whether `AuthService::change_password` keeps the caller's session alive.
+/// - `user_rate_limit`: The limit on emails sent to a single user, whatever the requesting IP.
+/// - `ip_rate_limit`: The limit on requests from a single IP address.
+/// - `availability_rate_limit`: The limit on identifier availability checks per client.
+/// - `link_confirmation_rate_limit`: The limit on account link confirmations per user.
+/// - `oauth_state_secret`: The secret signing stateless OAuth2 `state` parameters, if enabled.
+/// - `oauth_state_ttl`: The lifetime (in seconds) of signed OAuth2 `state` parameters.
+/// - `oauth_circuit_breaker`: When calls to an OAuth2 provider fail fast after consecutive failures, if enabled.
+/// - `oauth_token_expiry_margin`: How long (in seconds) before their expiration OAuth2 access tokens are refreshed.
+/// - `linked_providers_claim`: Whether access tokens list the user's linked OAuth2 providers.
+/// - `max_linked_providers`: The maximum number of OAuth2 providers linked to a single user.
+/// - `remember_me_refresh_token_expiration`: The refresh token lifetime (in seconds) of "remember me" logins.
 #[derive(Debug, Clone, Default)]
 pub struct AuthServiceVariables {
     /// The cryptographic secret key used for signing and verifying tokens.
@@ -17,4 +110,591 @@ pub struct AuthServiceVariables {
 
     /// The duration (in seconds) for which a refresh token is valid.
     pub refresh_token_expiration: u64,
+
+    /// The Argon2 cost parameters used by the default password manager.
+    pub argon2_params: Argon2Params,
+
+    /// The OAuth2 provider configurations used by the default OAuth2 manager.
+    pub oauth_configs: HashMap<OAuth2Provider, OAuth2Config>,
+
+    /// The policy enforced on new passwords (signup, password changes). `None` disables checks.
+    pub password_policy: Option<PasswordPolicy>,
+
+    /// Skips the extra user write that records `User::last_login_at` on every login.
+    pub disable_last_login_tracking: bool,
+
+    /// Requires the owner of a password account to confirm (`AuthService::confirm_link`) before
+    /// an OAuth2 account matching it by email is linked, instead of linking it silently.
+    pub require_oauth_link_verification: bool,
+
+    /// The lifetime (in seconds) of email one-time passwords. `None` uses
+    /// [`DEFAULT_OTP_TTL`](crate::core::otp::DEFAULT_OTP_TTL).
+    pub email_otp_ttl: Option<u64>,
+
+    /// The number of verification attempts allowed per email one-time password. `None` uses
+    /// [`DEFAULT_OTP_MAX_ATTEMPTS`](crate::core::otp::DEFAULT_OTP_MAX_ATTEMPTS).
+    pub email_otp_max_attempts: Option<u32>,
+
+    /// The lifetime (in seconds) of password reset tokens. `None` uses
+    /// [`DEFAULT_PASSWORD_RESET_TTL`](crate::core::otp::DEFAULT_PASSWORD_RESET_TTL).
+    pub password_reset_ttl: Option<u64>,
+
+    /// The audiences (`aud` claim) accepted by the default token service. Tokens issued
+    /// without an explicit audience carry the first one. Empty disables audience checks.
+    pub token_audiences: Vec<String>,
+
+    /// The size (in bytes) of the JSON payload above which the default token service
+    /// DEFLATE-compresses tokens (see
+    /// [`JwtTokenService::with_compression`](crate::core::token::jwt::JwtTokenService::with_compression)).
+    /// `None` (the default) never compresses. Other values require the `jwt-compression`
+    /// feature.
+    pub token_compression_threshold: Option<u64>,
+
+    /// Whether the default token service rotates or reuses refresh tokens on refresh, and
+    /// whether reused refresh tokens are rejected (see
+    /// [`RefreshStrategy`](crate::core::token::refresh::RefreshStrategy)). `None` (the
+    /// default) issues a new refresh token on every refresh without rejecting earlier ones.
+    pub refresh_strategy: Option<RefreshStrategy>,
+
+    /// Makes token validation and refreshes read the token's user from the repository and
+    /// reject suspended, deleted or missing users. Costs one user read per validation.
+    pub verify_user_status_on_validation: bool,
+
+    /// Where role checks such as `AuthService::user_has_role` read the roles of a token's
+    /// user from, unless a call picks a source. [`RoleSource::Token`] by default.
+    pub role_source: RoleSource,
+
+    /// The maximum number of tokens `AuthService::validate_many` validates at once, bounding
+    /// the concurrent reads of the session store and repository. `None` uses
+    /// [`DEFAULT_VALIDATION_CONCURRENCY`](crate::core::token::DEFAULT_VALIDATION_CONCURRENCY).
+    pub validation_concurrency: Option<u32>,
+
+    /// The maximum duration (in milliseconds) of a password hash or verification by the
+    /// default password manager, after which the operation fails with
+    /// [`AuthError::HashingTimeout`]. `None` waits for the operation to complete. Requires the
+    /// `tokio` dependency; without it, building the default password manager fails. See
+    /// `Argon2PasswordManager::with_timeout`.
+    pub password_hashing_timeout_ms: Option<u64>,
+
+    /// How the default password manager treats a stored hash that is not an Argon2 PHC
+    /// string. The default fails the login closed, as a wrong password would; see
+    /// [`MalformedHashBehavior`].
+    pub malformed_hash_behavior: MalformedHashBehavior,
+
+    /// Keeps the session of the access token passed to `AuthService::change_password` alive
+    /// when the other sessions of the user are revoked, so the user stays signed in on the
+    /// device they changed their password from.
+    pub keep_session_on_password_change: bool,
+
+    /// The limit on operations sending an email to a user (e.g., email one-time passwords),
+    /// keyed by the resolved user ID. `None` disables the limit.
+    pub user_rate_limit: Option<RateLimit>,
+
+    /// The limit enforced by `AuthService::check_ip_rate_limit`, keyed by IP address. Independent
+    /// of [`Self::user_rate_limit`]. `None` disables the limit.
+    pub ip_rate_limit: Option<RateLimit>,
+
+    /// The limit on `AuthService::is_identifier_available` checks, counted per client to slow
+    /// down user enumeration. `None` uses
+    /// [`DEFAULT_AVAILABILITY_RATE_LIMIT`](crate::core::rate_limit::DEFAULT_AVAILABILITY_RATE_LIMIT).
+    pub availability_rate_limit: Option<RateLimit>,
+
+    /// The limit on `AuthService::confirm_link` attempts, counted per user so that the proof
+    /// (a password or an access token) cannot be guessed. Attempts beyond it are rejected until
+    /// the window resets. `None` uses
+    /// [`DEFAULT_LINK_CONFIRMATION_RATE_LIMIT`](crate::core::rate_limit::DEFAULT_LINK_CONFIRMATION_RATE_LIMIT).
+    pub link_confirmation_rate_limit: Option<RateLimit>,
+
+    /// The secret with which the default OAuth2 manager signs `state` parameters, making them
+    /// verifiable without server-side storage (see
+    /// [`SignedState`](crate::core::oauth::state::SignedState)). Signed states are not tied
+    /// to the browser, whose nonce the caller still checks. `None` leaves tracking states to
+    /// the caller.
+    pub oauth_state_secret: Option<String>,
+
+    /// The lifetime (in seconds) of signed OAuth2 `state` parameters. `None` uses
+    /// [`DEFAULT_STATE_TTL`](crate::core::oauth::state::DEFAULT_STATE_TTL).
+    pub oauth_state_ttl: Option<u64>,
+
+    /// The circuit breaker of the default OAuth2 manager (see
+    /// [`CircuitBreaker`](crate::core::oauth::breaker::CircuitBreaker)). `None` lets every call
+    /// reach the providers.
+    pub oauth_circuit_breaker: Option<CircuitBreakerConfig>,
+
+    /// How long (in seconds) before its expiration `AuthService::fresh_oauth2_token` refreshes
+    /// an OAuth2 access token, to absorb clock skew and request latency. `None` uses
+    /// [`DEFAULT_OAUTH_EXPIRY_MARGIN`](crate::core::oauth::store::DEFAULT_OAUTH_EXPIRY_MARGIN).
+    pub oauth_token_expiry_margin: Option<u64>,
+
+    /// When `true`, access tokens issued for a loaded user (logins, signups, link confirmations
+    /// and enriched refreshes) carry a `linked_providers` claim listing the providers linked to
+    /// the user (e.g., `["github","google"]`). Disabled by default, since it grows every token.
+    pub linked_providers_claim: bool,
+
+    /// The maximum number of OAuth2 providers a single user can link, enforced by explicit
+    /// links, automatic links by email and link confirmations. Refreshing an already linked
+    /// provider is always allowed. `None` (the default) sets no limit.
+    pub max_linked_providers: Option<u32>,
+
+    /// The duration (in seconds) for which the refresh token of a login with `remember_me`
+    /// set is valid. Refreshes keep the extended lifetime; access tokens are unaffected.
+    /// `None` gives remembered logins the default [`Self::refresh_token_expiration`].
+    pub remember_me_refresh_token_expiration: Option<u64>,
+}
+
+impl AuthServiceVariables {
+    /// Checks the variables for inconsistent or unsafe values, without building any service.
+    ///
+    /// Reports, among others, secrets too short for HMAC signing or known placeholders,
+    /// refresh tokens living no longer than access tokens, zero lifetimes or attempt limits,
+    /// Argon2 parameters rejected by Argon2 and password policies accepting short passwords.
+    ///
+    /// # Returns
+    /// Every issue found, empty if the variables are coherent.
+    pub fn validate(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+
+        let min_secret_len = crate::core::token::jwt::MIN_HMAC_SECRET_LEN;
+        if PLACEHOLDER_SECRETS.contains(&self.secret_key.trim()) {
+            issues.push(ConfigIssue::new("secret_key", "is a placeholder value"));
+        } else if self.secret_key.len() < min_secret_len {
+            issues.push(ConfigIssue::new(
+                "secret_key",
+                format!(
+                    "is {} bytes long, shorter than the {min_secret_len} bytes required for HMAC signing",
+                    self.secret_key.len()
+                ),
+            ));
+        }
+
+        if self.token_expiration == 0 {
+            issues.push(ConfigIssue::new(
+                "token_expiration",
+                "is 0, so access tokens expire immediately",
+            ));
+        }
+        if self.refresh_token_expiration <= self.token_expiration {
+            issues.push(ConfigIssue::new(
+                "refresh_token_expiration",
+                format!(
+                    "refresh TTL ({}s) is not longer than access TTL ({}s)",
+                    self.refresh_token_expiration, self.token_expiration
+                ),
+            ));
+        }
+
+        if let Some(remembered) = self.remember_me_refresh_token_expiration
+            && remembered <= self.refresh_token_expiration
+        {
+            issues.push(ConfigIssue::new(
+                "remember_me_refresh_token_expiration",
+                format!(
+                    "remembered refresh TTL ({remembered}s) is not longer than refresh TTL ({}s)",
+                    self.refresh_token_expiration
+                ),
+            ));
+        }
+
+        if let Err(e) = crate::core::hash::Argon2Hasher::with_params(self.argon2_params) {
+            issues.push(ConfigIssue::new(
+                "argon2_params",
+                format!("are rejected by Argon2: {e}"),
+            ));
+        }
+
+        if let Some(policy) = &self.password_policy
+            && policy.min_length < MIN_POLICY_PASSWORD_LENGTH
+        {
+            issues.push(ConfigIssue::new(
+                "password_policy.min_length",
+                format!(
+                    "is {}, below the recommended minimum of {MIN_POLICY_PASSWORD_LENGTH}",
+                    policy.min_length
+                ),
+            ));
+        }
+
+        if let Some(policy) = &self.password_policy
+            && policy.min_strength_score.is_some_and(|score| score > 4)
+        {
+            issues.push(ConfigIssue::new(
+                "password_policy.min_strength_score",
+                "is above 4, the highest score, so every password is rejected",
+            ));
+        }
+
+        if self.email_otp_ttl == Some(0) {
+            issues.push(ConfigIssue::new(
+                "email_otp_ttl",
+                "is 0, so one-time passwords expire immediately",
+            ));
+        }
+        if self.email_otp_max_attempts == Some(0) {
+            issues.push(ConfigIssue::new(
+                "email_otp_max_attempts",
+                "is 0, so one-time passwords can never be verified",
+            ));
+        }
+        if self.password_reset_ttl == Some(0) {
+            issues.push(ConfigIssue::new(
+                "password_reset_ttl",
+                "is 0, so password reset tokens expire immediately",
+            ));
+        }
+        if self.password_hashing_timeout_ms == Some(0) {
+            issues.push(ConfigIssue::new(
+                "password_hashing_timeout_ms",
+                "is 0, so every password operation times out",
+            ));
+        }
+        if cfg!(not(feature = "tokio")) && self.password_hashing_timeout_ms.is_some() {
+            issues.push(ConfigIssue::new(
+                "password_hashing_timeout_ms",
+                "is set, but the timeout requires the tokio dependency (enabled by the postgres and axum features)",
+            ));
+        }
+        if let Some(secret) = &self.oauth_state_secret
+            && secret.len() < min_secret_len
+        {
+            issues.push(ConfigIssue::new(
+                "oauth_state_secret",
+                format!(
+                    "is {} bytes long, shorter than the {min_secret_len} bytes required for HMAC signing",
+                    secret.len()
+                ),
+            ));
+        }
+        if self.max_linked_providers == Some(0) {
+            issues.push(ConfigIssue::new(
+                "max_linked_providers",
+                "is 0, so OAuth2 accounts can never be linked",
+            ));
+        }
+        if self.validation_concurrency == Some(0) {
+            issues.push(ConfigIssue::new(
+                "validation_concurrency",
+                "is 0, so batch validations validate one token at a time",
+            ));
+        }
+        if self.oauth_state_ttl == Some(0) {
+            issues.push(ConfigIssue::new(
+                "oauth_state_ttl",
+                "is 0, so OAuth2 states expire immediately",
+            ));
+        }
+        if let Some(breaker) = self.oauth_circuit_breaker {
+            if breaker.failure_threshold == 0 {
+                issues.push(ConfigIssue::new(
+                    "oauth_circuit_breaker",
+                    "has a failure threshold of 0, so OAuth2 providers are never called",
+                ));
+            }
+            if breaker.cooldown_secs == 0 {
+                issues.push(ConfigIssue::new(
+                    "oauth_circuit_breaker",
+                    "has a cooldown of 0, so circuits never stay open",
+                ));
+            }
+        }
+
+        for (field, limit) in [
+            ("user_rate_limit", self.user_rate_limit),
+            ("ip_rate_limit", self.ip_rate_limit),
+            ("availability_rate_limit", self.availability_rate_limit),
+            (
+                "link_confirmation_rate_limit",
+                self.link_confirmation_rate_limit,
+            ),
+        ] {
+            if limit.is_some_and(|limit| limit.max_requests == 0) {
+                issues.push(ConfigIssue::new(
+                    format!("{field}.max_requests"),
+                    "is 0, so every request is rejected",
+                ));
+            }
+        }
+        if self
+            .token_audiences
+            .iter()
+            .any(|audience| audience.trim().is_empty())
+        {
+            issues.push(ConfigIssue::new(
+                "token_audiences",
+                "contains an empty audience",
+            ));
+        }
+
+        issues
+    }
+
+    /// Builds the configuration from `CRYPTIC_*` environment variables.
+    ///
+    /// # Environment
+    /// - `CRYPTIC_SECRET_KEY` (required): The token signing secret.
+    /// - `CRYPTIC_TOKEN_EXPIRATION`: Access token lifetime in seconds (default: 3600).
+    /// - `CRYPTIC_REFRESH_EXPIRATION`: Refresh token lifetime in seconds (default: 7 days).
+    /// - `CRYPTIC_REMEMBER_ME_REFRESH_EXPIRATION`: Refresh token lifetime in seconds of
+    ///   "remember me" logins (default: the refresh token lifetime).
+    /// - `CRYPTIC_ARGON2_MEMORY_KIB`, `CRYPTIC_ARGON2_ITERATIONS`, `CRYPTIC_ARGON2_PARALLELISM`:
+    ///   Argon2 cost parameters (default: the [`Argon2Params`] defaults).
+    /// - `CRYPTIC_APP_NAME`: Application name sent to OAuth2 providers (default: `cryptic`).
+    /// - `CRYPTIC_<PROVIDER>_CLIENT_ID`, `CRYPTIC_<PROVIDER>_CLIENT_SECRET`,
+    ///   `CRYPTIC_<PROVIDER>_REDIRECT_URI`, `CRYPTIC_<PROVIDER>_REDIRECT_FRONTEND_URI` and the
+    ///   optional comma-separated `CRYPTIC_<PROVIDER>_SCOPES`, comma-separated
+    ///   `CRYPTIC_<PROVIDER>_DEFAULT_SCOPES` (replacing the built-in default scopes when set,
+    ///   see [`OAuth2Config::default_scopes`]), boolean
+    ///   `CRYPTIC_<PROVIDER>_REQUIRE_VERIFIED_EMAIL` and comma-separated `field=path` pairs
+    ///   `CRYPTIC_<PROVIDER>_FIELD_MAP` (e.g. `email=upn,name=profile.display_name`, see
+    ///   [`OAuth2Config::field_map`]), where `<PROVIDER>` is one of
+    ///   `GOOGLE`, `GITHUB`, `DISCORD` or `MICROSOFT`. A provider is configured only when its
+    ///   client ID is set, in which case the other non-optional values become required.
+    /// - `CRYPTIC_DISABLE_LAST_LOGIN_TRACKING`: When set to `true` or `1`, logins do not record
+    ///   the last login timestamp.
+    /// - `CRYPTIC_REQUIRE_OAUTH_LINK_VERIFICATION`: When set to `true` or `1`, OAuth2 logins
+    ///   matching a password account by email must be confirmed by the account owner.
+    /// - `CRYPTIC_EMAIL_OTP_TTL`, `CRYPTIC_EMAIL_OTP_MAX_ATTEMPTS`: Lifetime in seconds and attempt
+    ///   limit of email one-time passwords (default: 5 minutes and 5 attempts).
+    /// - `CRYPTIC_PASSWORD_RESET_TTL`: Lifetime in seconds of password reset tokens (default: 30
+    ///   minutes).
+    /// - `CRYPTIC_TOKEN_AUDIENCES`: Comma-separated audiences accepted in tokens (default: none,
+    ///   which disables audience checks).
+    /// - `CRYPTIC_TOKEN_COMPRESSION_THRESHOLD`: Payload size in bytes above which tokens are
+    ///   compressed (default: none, which disables compression).
+    /// - `CRYPTIC_REFRESH_STRATEGY`, `CRYPTIC_REFRESH_GRACE_SECS`: `rotate`, `reuse` or
+    ///   `rotate_with_grace`, and the grace window in seconds of the latter (default: no
+    ///   strategy, 10 seconds).
+    /// - `CRYPTIC_VERIFY_USER_STATUS`: When set to `true` or `1`, token validation rejects tokens
+    ///   of suspended or deleted users.
+    /// - `CRYPTIC_RESOLVE_ROLES_FROM_DB`: When set to `true` or `1`, role checks read the user's
+    ///   current roles from the repository instead of the token's `roles` claim.
+    /// - `CRYPTIC_VALIDATION_CONCURRENCY`: Maximum number of tokens validated at once by batch
+    ///   validations (default: 8).
+    /// - `CRYPTIC_PASSWORD_HASHING_TIMEOUT_MS`: Maximum duration of a password hash or
+    ///   verification in milliseconds (default: none).
+    /// - `CRYPTIC_REJECT_MALFORMED_HASHES`: When set to `true` or `1`, verifying a password
+    ///   against a corrupt stored hash fails with an error instead of a mismatch.
+    /// - `CRYPTIC_KEEP_SESSION_ON_PASSWORD_CHANGE`: When set to `true` or `1`, password changes
+    ///   keep the current session alive while revoking the others.
+    /// - `CRYPTIC_USER_RATE_LIMIT_MAX`, `CRYPTIC_USER_RATE_LIMIT_WINDOW`: Number of emails sent
+    ///   to a single user per window, and window in seconds (default: limit disabled, 1 hour).
+    /// - `CRYPTIC_IP_RATE_LIMIT_MAX`, `CRYPTIC_IP_RATE_LIMIT_WINDOW`: Number of requests per IP
+    ///   address per window, and window in seconds (default: limit disabled, 1 hour).
+    /// - `CRYPTIC_AVAILABILITY_RATE_LIMIT_MAX`, `CRYPTIC_AVAILABILITY_RATE_LIMIT_WINDOW`: Number
+    ///   of identifier availability checks per window, and window in seconds (default: limit
+    ///   disabled, 1 hour).
+    /// - `CRYPTIC_LINK_CONFIRMATION_RATE_LIMIT_MAX`, `CRYPTIC_LINK_CONFIRMATION_RATE_LIMIT_WINDOW`:
+    ///   Number of account link confirmations per user per window, and window in seconds
+    ///   (default: 5 per 15 minutes).
+    /// - `CRYPTIC_OAUTH_STATE_SECRET`, `CRYPTIC_OAUTH_STATE_TTL`: Secret signing stateless OAuth2
+    ///   `state` parameters, and their lifetime in seconds (default: caller-managed states,
+    ///   10 minutes).
+    /// - `CRYPTIC_OAUTH_BREAKER_THRESHOLD`, `CRYPTIC_OAUTH_BREAKER_COOLDOWN`: Number of
+    ///   consecutive failures of an OAuth2 provider after which its calls fail fast, and for how
+    ///   long in seconds (default: breaker disabled, 30 seconds).
+    /// - `CRYPTIC_OAUTH_TOKEN_EXPIRY_MARGIN`: Seconds before their expiration OAuth2 access tokens
+    ///   are refreshed (default: 30).
+    /// - `CRYPTIC_LINKED_PROVIDERS_CLAIM`: When set to `true` or `1`, access tokens list the
+    ///   user's linked OAuth2 providers.
+    /// - `CRYPTIC_MAX_LINKED_PROVIDERS`: Maximum number of OAuth2 providers linked to a single
+    ///   user (default: unlimited).
+    /// - `CRYPTIC_ENV`: When set to `production`, placeholder secrets are rejected.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::ConfigError`] if a required value is missing, a value cannot be parsed,
+    /// or a placeholder secret is used in production mode.
+    pub fn from_env() -> Result<Self, AuthError> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Builds the configuration using `lookup` to resolve each variable.
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, AuthError> {
+        let required = |key: &str| {
+            lookup(key).ok_or_else(|| AuthError::ConfigError(format!("{key} is not set")))
+        };
+        let parsed = |key: &str| -> Result<Option<u64>, AuthError> {
+            lookup(key)
+                .map(|value| {
+                    value.trim().parse::<u64>().map_err(|e| {
+                        AuthError::ConfigError(format!("{key} is not a valid integer: {e}"))
+                    })
+                })
+                .transpose()
+        };
+        let parsed_u32 = |key: &str, default: u32| -> Result<u32, AuthError> {
+            match parsed(key)? {
+                Some(value) => u32::try_from(value)
+                    .map_err(|_| AuthError::ConfigError(format!("{key} is out of range"))),
+                None => Ok(default),
+            }
+        };
+
+        let flag = |key: &str| {
+            lookup(key)
+                .map(|value| {
+                    let value = value.trim();
+                    value == "1" || value.eq_ignore_ascii_case("true")
+                })
+                .unwrap_or(false)
+        };
+
+        let rate_limit = |prefix: &str| -> Result<Option<RateLimit>, AuthError> {
+            let max_key = format!("{prefix}_MAX");
+            if lookup(&max_key).is_none() {
+                return Ok(None);
+            }
+            Ok(Some(RateLimit::new(
+                parsed_u32(&max_key, 0)?,
+                parsed(&format!("{prefix}_WINDOW"))?
+                    .unwrap_or(crate::core::rate_limit::DEFAULT_RATE_LIMIT_WINDOW),
+            )))
+        };
+
+        let secret_key = required("CRYPTIC_SECRET_KEY")?;
+        let is_production = lookup("CRYPTIC_ENV")
+            .map(|env| env.eq_ignore_ascii_case("production"))
+            .unwrap_or(false);
+        if is_production && PLACEHOLDER_SECRETS.contains(&secret_key.trim()) {
+            return Err(AuthError::ConfigError(
+                "CRYPTIC_SECRET_KEY is a placeholder value and cannot be used in production"
+                    .to_string(),
+            ));
+        }
+
+        let defaults = Argon2Params::default();
+        let argon2_params = Argon2Params {
+            memory_kib: parsed_u32("CRYPTIC_ARGON2_MEMORY_KIB", defaults.memory_kib)?,
+            iterations: parsed_u32("CRYPTIC_ARGON2_ITERATIONS", defaults.iterations)?,
+            parallelism: parsed_u32("CRYPTIC_ARGON2_PARALLELISM", defaults.parallelism)?,
+        };
+
+        let list = |key: &str| -> Vec<String> {
+            lookup(key)
+                .map(|values| {
+                    values
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let app_name = lookup("CRYPTIC_APP_NAME").unwrap_or_else(|| "cryptic".to_string());
+        let mut oauth_configs = HashMap::new();
+        for &provider in OAuth2Provider::all() {
+            let prefix = format!("CRYPTIC_{}", provider.as_str().to_ascii_uppercase());
+            let Some(client_id) = lookup(&format!("{prefix}_CLIENT_ID")) else {
+                continue;
+            };
+            let default_scopes_key = format!("{prefix}_DEFAULT_SCOPES");
+            let default_scopes = lookup(&default_scopes_key).map(|_| list(&default_scopes_key));
+            let additional_scopes = list(&format!("{prefix}_SCOPES"));
+            let field_map_key = format!("{prefix}_FIELD_MAP");
+            let field_map = list(&field_map_key)
+                .into_iter()
+                .map(|entry| match entry.split_once('=') {
+                    Some((field, path)) if !field.trim().is_empty() && !path.trim().is_empty() => {
+                        Ok((field.trim().to_string(), path.trim().to_string()))
+                    }
+                    _ => Err(AuthError::ConfigError(format!(
+                        "{field_map_key} entry {entry:?} is not a field=path pair"
+                    ))),
+                })
+                .collect::<Result<HashMap<_, _>, _>>()?;
+            oauth_configs.insert(
+                provider,
+                OAuth2Config {
+                    app_name: app_name.clone(),
+                    client_id,
+                    client_secret: required(&format!("{prefix}_CLIENT_SECRET"))?,
+                    redirect_callback_uri: required(&format!("{prefix}_REDIRECT_URI"))?,
+                    redirect_frontend_uri: required(&format!("{prefix}_REDIRECT_FRONTEND_URI"))?,
+                    default_scopes,
+                    additional_scopes,
+                    require_verified_email: flag(&format!("{prefix}_REQUIRE_VERIFIED_EMAIL")),
+                    field_map,
+                },
+            );
+        }
+
+        Ok(Self {
+            secret_key,
+            token_expiration: parsed("CRYPTIC_TOKEN_EXPIRATION")?
+                .unwrap_or(DEFAULT_TOKEN_EXPIRATION),
+            refresh_token_expiration: parsed("CRYPTIC_REFRESH_EXPIRATION")?
+                .unwrap_or(DEFAULT_REFRESH_TOKEN_EXPIRATION),
+            argon2_params,
+            oauth_configs,
+            password_policy: None,
+            disable_last_login_tracking: flag("CRYPTIC_DISABLE_LAST_LOGIN_TRACKING"),
+            require_oauth_link_verification: flag("CRYPTIC_REQUIRE_OAUTH_LINK_VERIFICATION"),
+            email_otp_ttl: parsed("CRYPTIC_EMAIL_OTP_TTL")?,
+            password_reset_ttl: parsed("CRYPTIC_PASSWORD_RESET_TTL")?,
+            email_otp_max_attempts: lookup("CRYPTIC_EMAIL_OTP_MAX_ATTEMPTS")
+                .is_some()
+                .then(|| parsed_u32("CRYPTIC_EMAIL_OTP_MAX_ATTEMPTS", 0))
+                .transpose()?,
+            token_audiences: list("CRYPTIC_TOKEN_AUDIENCES"),
+            token_compression_threshold: parsed("CRYPTIC_TOKEN_COMPRESSION_THRESHOLD")?,
+            refresh_strategy: match lookup("CRYPTIC_REFRESH_STRATEGY")
+                .map(|value| value.trim().to_ascii_lowercase())
+                .as_deref()
+            {
+                None => None,
+                Some("rotate") => Some(RefreshStrategy::Rotate),
+                Some("reuse") => Some(RefreshStrategy::Reuse),
+                Some("rotate_with_grace") => Some(RefreshStrategy::RotateWithGrace(
+                    std::time::Duration::from_secs(
+                        parsed("CRYPTIC_REFRESH_GRACE_SECS")?
+                            .unwrap_or(crate::core::token::refresh::DEFAULT_REFRESH_GRACE_SECS),
+                    ),
+                )),
+                Some(other) => {
+                    return Err(AuthError::ConfigError(format!(
+                        "CRYPTIC_REFRESH_STRATEGY must be rotate, reuse or rotate_with_grace, \
+                         got {other:?}"
+                    )));
+                }
+            },
+            verify_user_status_on_validation: flag("CRYPTIC_VERIFY_USER_STATUS"),
+            role_source: if flag("CRYPTIC_RESOLVE_ROLES_FROM_DB") {
+                RoleSource::Repository
+            } else {
+                RoleSource::Token
+            },
+            validation_concurrency: lookup("CRYPTIC_VALIDATION_CONCURRENCY")
+                .is_some()
+                .then(|| parsed_u32("CRYPTIC_VALIDATION_CONCURRENCY", 0))
+                .transpose()?,
+            password_hashing_timeout_ms: parsed("CRYPTIC_PASSWORD_HASHING_TIMEOUT_MS")?,
+            malformed_hash_behavior: if flag("CRYPTIC_REJECT_MALFORMED_HASHES") {
+                MalformedHashBehavior::Reject
+            } else {
+                MalformedHashBehavior::Mismatch
+            },
+            keep_session_on_password_change: flag("CRYPTIC_KEEP_SESSION_ON_PASSWORD_CHANGE"),
+            user_rate_limit: rate_limit("CRYPTIC_USER_RATE_LIMIT")?,
+            ip_rate_limit: rate_limit("CRYPTIC_IP_RATE_LIMIT")?,
+            availability_rate_limit: rate_limit("CRYPTIC_AVAILABILITY_RATE_LIMIT")?,
+            link_confirmation_rate_limit: rate_limit("CRYPTIC_LINK_CONFIRMATION_RATE_LIMIT")?,
+            oauth_state_secret: lookup("CRYPTIC_OAUTH_STATE_SECRET"),
+            oauth_state_ttl: parsed("CRYPTIC_OAUTH_STATE_TTL")?,
+            oauth_circuit_breaker: match lookup("CRYPTIC_OAUTH_BREAKER_THRESHOLD") {
+                Some(_) => Some(CircuitBreakerConfig::new(
+                    parsed_u32("CRYPTIC_OAUTH_BREAKER_THRESHOLD", 0)?,
+                    parsed("CRYPTIC_OAUTH_BREAKER_COOLDOWN")?
+                        .unwrap_or(crate::core::oauth::breaker::DEFAULT_BREAKER_COOLDOWN),
+                )),
+                None => None,
+            },
+            oauth_token_expiry_margin: parsed("CRYPTIC_OAUTH_TOKEN_EXPIRY_MARGIN")?,
+            linked_providers_claim: flag("CRYPTIC_LINKED_PROVIDERS_CLAIM"),
+            remember_me_refresh_token_expiration: parsed("CRYPTIC_REMEMBER_ME_REFRESH_EXPIRATION")?,
+            max_linked_providers: lookup("CRYPTIC_MAX_LINKED_PROVIDERS")
+                .is_some()
+                .then(|| parsed_u32("CRYPTIC_MAX_LINKED_PROVIDERS", 0))
+                .transpose()?,
+        })
+    }
 }
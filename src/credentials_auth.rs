@@ -0,0 +1,224 @@
+//! Lightweight identifier/password authentication, without OAuth2.
+//!
+//! [`AuthService`](crate::AuthService) wires every feature of the crate: its default
+//! construction builds an OAuth2 manager, session, OTP, API key and rate limit stores, an audit
+//! log and more. Internal services that only sign users in with a password can use
+//! [`CredentialsAuth`] instead, composed of just a password manager, a user repository and a
+//! token service. It shares the traits of `AuthService`, so the same implementations (e.g., a
+//! Postgres repository or a JWT service with RS256 keys) plug into both. It builds without the
+//! default `oauth` feature (`default-features = false`), which drops the `oauth2` and `reqwest`
+//! dependencies.
+//!
+//! Everything else is left to the caller: there is no identifier normalization, password
+//! policy, session tracking (tokens cannot be revoked per session), rate limiting or audit log.
+
+use crate::core::credentials::PlainPassword;
+use crate::core::password::SecurePasswordManager;
+use crate::core::token::claims::{Claims, amr};
+use crate::core::token::{TokenOptions, TokenPair, TokenService};
+use crate::core::user::persistence::UserRepository;
+use crate::core::user::{LogIdKey, User};
+use crate::core::vars::AuthServiceVariables;
+use crate::error::AuthError;
+
+/// Credentials-only authentication: signup, login and access token validation.
+///
+/// # Example
+/// ```rust,ignore
+/// let auth = CredentialsAuth::from_vars(&AuthServiceVariables::from_env()?)?;
+/// let (user, tokens) = auth.signup("alice@example.com", "correct horse battery").await?;
+/// let claims = auth.validate(&tokens.access_token).await?;
+/// assert_eq!(claims.get_subject(), user.id.as_str());
+/// ```
+pub struct CredentialsAuth {
+    /// Hashes and verifies passwords.
+    pub password_manager: Box<dyn SecurePasswordManager + Send + Sync>,
+    /// Stores the users.
+    pub users: Box<dyn UserRepository + Send + Sync>,
+    /// Issues and validates tokens.
+    pub token_manager: Box<dyn TokenService + Send + Sync>,
+    /// The key of the user pseudonyms written to logs.
+    log_ids: LogIdKey,
+}
+
+impl CredentialsAuth {
+    /// Creates a [`CredentialsAuth`] from its three components.
+    ///
+    /// User pseudonyms in logs are keyed by a random key; see [`CredentialsAuth::from_vars`]
+    /// for pseudonyms stable across restarts.
+    ///
+    /// # Arguments
+    /// * `password_manager` - The password manager hashing and verifying passwords.
+    /// * `users` - The repository storing the users.
+    /// * `token_manager` - The token service issuing and validating tokens.
+    pub fn new(
+        password_manager: Box<dyn SecurePasswordManager + Send + Sync>,
+        users: Box<dyn UserRepository + Send + Sync>,
+        token_manager: Box<dyn TokenService + Send + Sync>,
+    ) -> Self {
+        Self {
+            password_manager,
+            users,
+            token_manager,
+            log_ids: LogIdKey::random(),
+        }
+    }
+
+    /// Creates a [`CredentialsAuth`] with the components `AuthService::new` uses by default:
+    /// Argon2, an in-memory repository and a JWT token service.
+    ///
+    /// # Arguments
+    /// * `vars` - The configuration, read as by `AuthService::new`. OAuth2 settings are ignored.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::ConfigError`] if the secret key is too short for the JWT token
+    /// service or if the Argon2 parameters are invalid.
+    pub fn from_vars(vars: &AuthServiceVariables) -> Result<Self, AuthError> {
+        Ok(Self {
+            log_ids: LogIdKey::derive(vars.secret_key.as_bytes()),
+            ..Self::new(
+                crate::auth_service::default_password_manager(vars)?,
+                Box::new(crate::core::user::persistence::InMemoryUserRepo::new()),
+                crate::auth_service::default_token_service(vars)?,
+            )
+        })
+    }
+
+    /// Registers a user with an identifier and a password, and logs them in.
+    ///
+    /// # Arguments
+    /// * `identifier` - The login identifier (e.g., a username or email address), stored as is.
+    /// * `password` - The plain password.
+    ///
+    /// # Returns
+    /// The created user and their tokens.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::SignupError`] if the identifier is taken or the user cannot be
+    /// stored, or an error if hashing or token generation fails.
+    pub async fn signup(
+        &self,
+        identifier: &str,
+        password: &str,
+    ) -> Result<(User, TokenPair), AuthError> {
+        let reservation = self
+            .users
+            .reserve_identifier(identifier, None)
+            .await
+            .map_err(|e| AuthError::SignupError(format!("signup: {e}")))?;
+        let user = User::with_plain_password(
+            self.password_manager.as_ref(),
+            uuid::Uuid::new_v4().to_string(),
+            identifier.to_string(),
+            PlainPassword::new(password.to_string()),
+        )
+        .await?;
+        let user = self
+            .users
+            .add_user(user)
+            .await
+            .map_err(|e| AuthError::SignupError(format!("signup: {e}")))?;
+        reservation.commit();
+
+        let tokens = self.issue_tokens(&user).await?;
+        Ok((user, tokens))
+    }
+
+    /// Logs a user in with their identifier and password.
+    ///
+    /// Outdated password hashes are upgraded on success, as with `AuthService::login`.
+    ///
+    /// # Arguments
+    /// * `identifier` - The login identifier, as given at signup.
+    /// * `password` - The plain password.
+    ///
+    /// # Returns
+    /// The user and new tokens.
+    ///
+    /// # Errors
+    /// Returns [`AuthError::InvalidCredentials`] if no user has this identifier or the password
+    /// is wrong, [`AuthError::AccountDisabled`] if the user is not active, or an error if the
+    /// repository cannot be queried or token generation fails.
+    pub async fn login(
+        &self,
+        identifier: &str,
+        password: &str,
+    ) -> Result<(User, TokenPair), AuthError> {
+        let credentials = self
+            .users
+            .get_credentials_by_identifier_in_tenant(identifier, None)
+            .await?
+            .ok_or(AuthError::InvalidCredentials)?;
+        if credentials.password_hash.is_empty() {
+            return Err(AuthError::InvalidCredentials);
+        }
+        let is_valid = self
+            .password_manager
+            .verify_password(password, &credentials.password_hash)
+            .await
+            .map_err(|e| match e {
+                AuthError::HashingTimeout => e,
+                e => AuthError::PasswordVerificationError(format!(
+                    "Password verification failed: {e}"
+                )),
+            })?;
+        if !is_valid {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        let mut user = self
+            .users
+            .find_user_by_id(&credentials.user_id)
+            .await?
+            .ok_or(AuthError::InvalidCredentials)?;
+        if !user.status.is_active() {
+            return Err(AuthError::AccountDisabled);
+        }
+        if self
+            .password_manager
+            .needs_rehash_for(&credentials.password_hash, user.hash_target.as_ref())
+        {
+            crate::auth_service::rehash_password(
+                self.password_manager.as_ref(),
+                self.users.as_ref(),
+                &self.log_ids,
+                &mut user,
+                password,
+            )
+            .await;
+        }
+
+        let tokens = self.issue_tokens(&user).await?;
+        Ok((user, tokens))
+    }
+
+    /// Validates an access token.
+    ///
+    /// Only the token itself is checked by the token service (e.g., its signature and
+    /// expiration); the user is not read from the repository.
+    ///
+    /// # Arguments
+    /// * `token` - The access token to validate.
+    ///
+    /// # Returns
+    /// The claims of the token.
+    ///
+    /// # Errors
+    /// Returns the errors of the token service, e.g. [`AuthError::TokenExpired`].
+    pub async fn validate(&self, token: &str) -> Result<Box<dyn Claims + Send + Sync>, AuthError> {
+        self.token_manager.validate_access_token(token).await
+    }
+
+    /// Issues the tokens of a password login.
+    async fn issue_tokens(&self, user: &User) -> Result<TokenPair, AuthError> {
+        let options = TokenOptions {
+            tenant_id: user.tenant_id.clone(),
+            auth_time: Some(chrono::Utc::now().timestamp().max(0) as usize),
+            amr: vec![amr::PASSWORD.to_string()],
+            ..Default::default()
+        };
+        self.token_manager
+            .generate_token_pair_with(user.id.as_str(), &options)
+            .await
+    }
+}
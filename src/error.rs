@@ -16,15 +16,30 @@ pub enum AuthError {
     #[error("User not found.")]
     UserNotFound,
 
+    /// Returned when a versioned update finds that the user changed since it was loaded
+    /// (see `UserRepository::compare_and_update_user`). The caller should reload the user
+    /// and reapply its changes.
+    #[error("User was modified concurrently.")]
+    ConcurrentModification,
+
     /// Returned when attempting to create a user that already exists.
     #[error("User already exists.")]
     UserAlreadyExists,
 
+    /// Returned by `AuthService::bootstrap` when an administrator already exists, or another
+    /// bootstrap is running.
+    #[error("An administrator already exists.")]
+    AlreadyBootstrapped,
+
     /// Returned when password hashing fails.
     /// Contains the underlying error message.
     #[error("Password hashing failed: {0}")]
     HashingError(String),
 
+    /// Returned when hashing or verifying a password takes longer than the configured timeout.
+    #[error("Password hashing timed out")]
+    HashingTimeout,
+
     /// Returned when password verification fails.
     /// Contains the underlying error message.
     #[error("Password verification failed: {0}")]
@@ -40,6 +55,13 @@ pub enum AuthError {
     #[error("Service unavailable: {0}")]
     ServiceUnavailable(String),
 
+    /// Returned when the user repository cannot be reached (e.g., a dropped database connection
+    /// or an exhausted pool). Unlike [`AuthError::UserNotFound`] or
+    /// [`AuthError::InvalidCredentials`], the request may succeed when retried.
+    /// Contains a description of the storage issue.
+    #[error("Storage unavailable: {0}")]
+    StorageUnavailable(String),
+
     /// Returned when a feature is not yet implemented.
     /// Contains a description of the missing feature.
     #[error("Feature not implemented yet: {0}")]
@@ -55,6 +77,16 @@ pub enum AuthError {
     #[error("Invalid password: {0}")]
     InvalidPassword(String),
 
+    /// Returned when a new password satisfies the rules of the password policy but is too
+    /// predictable (see [`PasswordPolicy::min_strength_score`](crate::core::policy::PasswordPolicy::min_strength_score)).
+    #[error("Password is too easy to guess (strength score {score}).")]
+    WeakPassword {
+        /// The estimated strength score, from 0 to 4.
+        score: u8,
+        /// Suggestions for a stronger password, to show to the user.
+        feedback: Vec<String>,
+    },
+
     /// Returned when a verification process fails (e.g., email or token verification).
     /// Contains a description of the verification error.
     #[error("Verification error: {0}")]
@@ -102,6 +134,18 @@ pub enum AuthError {
     #[error("OAuth provider error: {0}")]
     OAuthProvider(String),
 
+    /// Returned when the OAuth provider answered a token request with a standard error response
+    /// (RFC 6749, section 5.2), e.g. `invalid_grant` for a used or expired authorization code.
+    #[error("OAuth provider returned error `{code}`{}", description.as_deref().map(|d| format!(": {d}")).unwrap_or_default())]
+    OAuthProviderError {
+        /// The OAuth2 error code (`error`), e.g. `invalid_grant` or `access_denied`.
+        code: String,
+        /// The human-readable description (`error_description`), if any.
+        description: Option<String>,
+        /// A page documenting the error (`error_uri`), if any.
+        uri: Option<String>,
+    },
+
     /// Returned when the token exchange process fails during OAuth.
     #[error("OAuth token exchange failed: {0}")]
     OAuthTokenExchange(String),
@@ -114,6 +158,16 @@ pub enum AuthError {
     #[error("OAuth other error: {0}")]
     OAuthOther(String),
 
+    /// Returned when a provider configured to require verified emails does not report the
+    /// account's email as verified.
+    #[error("OAuth provider did not report a verified email")]
+    OAuthEmailNotVerified,
+
+    /// Returned when the OAuth2 `state` of a callback is not a valid, unexpired state signed
+    /// for the provider.
+    #[error("OAuth state is invalid or expired")]
+    OAuthStateMismatch,
+
     /// Returned when a user signup operation fails.
     /// Contains a description of the signup error.
     #[error("User signup error: {0}")]
@@ -124,9 +178,88 @@ pub enum AuthError {
     #[error("User login error: {0}")]
     LoginError(String),
 
+    /// Returned when an OAuth2 login matches an existing password account by email.
+    /// The account owner must prove ownership with `AuthService::confirm_link` before the
+    /// provider is linked.
+    #[error("Linking {provider} to user {user_id} requires verification")]
+    AccountLinkRequiresVerification {
+        /// The ID of the matched user.
+        user_id: crate::core::user::UserId,
+        /// The provider waiting to be linked.
+        provider: crate::core::oauth::store::OAuth2Provider,
+    },
+
+    /// Returned when an API key to revoke does not exist or belongs to another user.
+    #[error("API key not found")]
+    ApiKeyNotFound,
+
+    /// Returned when linking a provider identity that is already linked to another user.
+    #[error("OAuth account is already linked to user {other_user_id}")]
+    OAuthAccountAlreadyLinked {
+        /// The ID of the user the provider identity is linked to.
+        other_user_id: crate::core::user::UserId,
+    },
+
+    /// Returned when linking another OAuth2 provider would exceed
+    /// `AuthServiceVariables::max_linked_providers`.
+    #[error("A user cannot link more than {max} OAuth accounts")]
+    TooManyLinkedAccounts {
+        /// The maximum number of linked providers.
+        max: u32,
+    },
+
+    /// Returned when a signup or an OAuth2 account uses an email address at a domain denied by
+    /// the disposable email filter.
+    #[error("Disposable email addresses are not allowed")]
+    DisposableEmailNotAllowed,
+
+    /// Returned when unlinking an OAuth2 provider would leave a user without any way to log
+    /// in (no password and no other linked provider).
+    #[error("Cannot remove the last login method of a user")]
+    CannotRemoveLastCredential,
+
+    /// Returned when a single-use refresh token, or an access token consumed with
+    /// `AuthService::consume_one_time_access`, is presented again after being consumed.
+    #[error("Token has already been used")]
+    TokenReuseDetected,
+
+    /// Returned when a per-user or per-IP rate limit is reached.
+    #[error("Too many requests, retry in {retry_after_secs}s")]
+    RateLimited {
+        /// The number of seconds until the limit resets.
+        retry_after_secs: u64,
+    },
+
+    /// Returned when a suspended or deleted user tries to log in or use a token.
+    #[error("User account is disabled")]
+    AccountDisabled,
+
+    /// Returned when the challenge (CAPTCHA) token of a signup or login is missing or rejected
+    /// by the challenge verifier. Contains the reason.
+    #[error("Challenge verification failed: {0}")]
+    ChallengeFailed(String),
+
+    /// Returned when a token is valid but its authentication is too old or too weak for the
+    /// operation (see `AuthService::require_recent_auth` and `AuthService::require_amr`).
+    /// Contains the unmet requirement.
+    #[error("Step-up authentication required: {0}")]
+    StepUpRequired(String),
+
+    /// Returned when a token bound to a client fingerprint is presented by another client.
+    #[error("Token is bound to another client")]
+    TokenBindingMismatch,
+
+    /// Returned when a refresh token and the access token presented with it belong to
+    /// different users.
+    #[error("Refresh token and access token subjects do not match")]
+    SubjectMismatch,
+
     /// Returned when a database error occurs (only available with the `postgres` feature).
     /// Contains a description of the database error.
     #[cfg(feature = "postgres")]
     #[error("Database error: {0}")]
     DatabaseError(String),
 }
+
+/// Result type returned by fallible operations of this crate.
+pub type AuthResult<T> = Result<T, AuthError>;
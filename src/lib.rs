@@ -12,8 +12,14 @@
 //! - **Web Integration**: Axum-based web server integration (enable with `web` feature).
 //!
 //! ## Optional Features
+//! - `oauth` (default): Enables the OAuth2 provider client (`core::oauth::manager`) and its
+//!   `oauth2` and `reqwest` dependencies. Without it, OAuth2 logins fail with a configuration error.
 //! - `postgres`: Enables PostgreSQL-backed persistence.
 //! - `web`: Enables Axum web server integration for HTTP APIs.
+//! - `test-util`: Enables in-memory test helpers (`core::oauth::mock`, `test_util`).
+//! - `jwt-compression`: Enables DEFLATE compression of large JWT payloads
+//!   (`JwtTokenService::with_compression`); other tokens with a `zip` header are rejected.
+//! - `github-app`: Enables GitHub App installation tokens (`core::oauth::github_app`); implies `oauth`.
 //!
 //! ## Example
 //! ```rust
@@ -24,14 +30,20 @@
 //! ## Modules
 //! - [`auth_service`]: High-level authentication service API.
 //! - [`core`]: Core primitives (users, credentials, hashing, tokens, etc.).
+//! - [`credentials_auth`]: Lightweight identifier/password authentication, without OAuth2.
 //! - [`error`]: Error types for authentication operations.
+//! - [`prelude`]: Glob-importable re-exports of the commonly used types.
 //! - [`postgres`]: PostgreSQL backend (requires `postgres` feature).
 //! - [`web_axum`]: Axum web integration (requires `web` feature).
+//! - `test_util`: Deterministic test services and fixtures (requires `test-util` feature).
 //!
 //! ## Re-exports
 //! - [`AuthService`]: Main authentication service.
+//! - [`CredentialsAuth`]: Credentials-only authentication service.
 //! - [`CrypticUser`]: User type.
 //! - [`AuthError`]: Error type.
+//! - [`AuthResult`]: Result type alias using [`AuthError`].
+//! - [`User`], [`UserId`], [`TokenPair`], [`OAuth2Provider`]: Common domain types.
 //! - [`get_cryptic_axum_router`], [`start_server`]: Web server utilities (with `web` feature).
 //!
 //! ## License
@@ -45,11 +57,18 @@
 pub mod auth_service;
 /// Core primitives: users, credentials, hashing, tokens, etc.
 pub mod core;
+/// Lightweight identifier/password authentication, without OAuth2.
+pub mod credentials_auth;
 /// Error types for authentication operations.
 pub mod error;
 /// PostgreSQL backend (requires `postgres` feature).
 #[cfg(feature = "postgres")]
 pub mod postgres;
+/// Glob-importable re-exports of the commonly used types.
+pub mod prelude;
+/// Test helpers (requires `test-util` feature).
+#[cfg(feature = "test-util")]
+pub mod test_util;
 /// Axum web integration (requires `axum` feature).
 #[cfg(feature = "axum")]
 pub mod web_axum;
@@ -57,11 +76,21 @@ pub mod web_axum;
 /// Main authentication service.
 pub use auth_service::AuthService;
 /// Authentication method enums for unified login and signup.
-pub use auth_service::{LoginMethod, SignupMethod};
+pub use auth_service::{LoginMethod, SignupMethod, TenantAuthService};
+/// OAuth2 provider identifiers.
+pub use core::oauth::store::OAuth2Provider;
+/// Access and refresh token pair.
+pub use core::token::TokenPair;
 /// User type.
 pub use core::user::User as CrypticUser;
+/// User type and its identifier.
+pub use core::user::{User, UserId, UserStatus};
+/// Credentials-only authentication service.
+pub use credentials_auth::CredentialsAuth;
 /// Error type for authentication operations.
 pub use error::AuthError;
+/// Result type alias for authentication operations.
+pub use error::AuthResult;
 /// Returns an Axum router with authentication endpoints (with `axum` feature).
 #[cfg(feature = "axum")]
 pub use web_axum::get_cryptic_axum_router;
@@ -1,7 +1,8 @@
 //! Postgres-backed user repository implementation for the Cryptic authentication system.
 //!
 //! This module provides the [`PgUserRepo`] struct, which implements the user repository
-//! trait for storing and retrieving user and credential data in a PostgreSQL database.
+//! trait for storing and retrieving user and credential data in a PostgreSQL database, and
+//! [`PgApiKeyStore`], which persists API keys.
 //!
 //! # Features
 //!
@@ -17,10 +18,29 @@ use async_trait::async_trait;
 use uuid::Uuid;
 
 #[cfg(feature = "postgres")]
-use crate::{core::user::User, error::AuthError};
+use crate::{
+    core::{
+        credentials::StoredCredentials,
+        user::{
+            User, UserId,
+            persistence::{
+                RepositoryTransaction, UserStream, transaction::nested_transaction_error,
+            },
+        },
+    },
+    error::AuthError,
+};
 
 #[cfg(feature = "postgres")]
-use tokio::sync::Mutex;
+use futures_util::stream::{self, StreamExt};
+#[cfg(feature = "postgres")]
+use sqlx::TransactionManager;
+#[cfg(feature = "postgres")]
+use tokio::sync::{Mutex, MutexGuard};
+
+/// Number of users read per query by `PgUserRepo::stream_users`.
+#[cfg(feature = "postgres")]
+const STREAM_BATCH_SIZE: usize = 100;
 
 /// A PostgreSQL-backed implementation of the user repository for Cryptic.
 ///
@@ -198,13 +218,12 @@ impl PgUserRepo {
             ));
         }
 
-        // Check unique constraint on identifier
+        // Check unique index on identifier (partial indexes scope it to tenants)
         let _unique_identifier = sqlx::query(
-            r#"SELECT tc.constraint_name
-                FROM information_schema.table_constraints tc
-                JOIN information_schema.constraint_column_usage ccu
-                  ON tc.constraint_name = ccu.constraint_name
-                WHERE tc.table_name = 'cryptic_credentials' AND tc.constraint_type = 'UNIQUE' AND ccu.column_name = 'identifier'"#
+            r#"SELECT indexname
+                FROM pg_indexes
+                WHERE tablename = 'cryptic_credentials' AND indexdef LIKE 'CREATE UNIQUE INDEX%' AND indexdef LIKE '%identifier%'
+                LIMIT 1"#
         )
         .fetch_one(&mut *conn)
         .await
@@ -383,7 +402,8 @@ impl crate::core::user::persistence::traits::UserRepository for PgUserRepo {
     /// Adds a new user and their credentials to the database.
     ///
     /// Inserts a new user record into the `cryptic_users` table, along with associated credentials
-    /// and OAuth accounts if provided.
+    /// and OAuth accounts if provided. The inserts run in a single transaction, so a failure
+    /// (e.g., a duplicate identifier) stores nothing.
     ///
     /// # Arguments
     ///
@@ -397,100 +417,413 @@ impl crate::core::user::persistence::traits::UserRepository for PgUserRepo {
     ///
     /// Returns [`AuthError::DatabaseError`] if insertion fails or IDs are invalid.
     async fn add_user(&self, user: User) -> Result<User, crate::error::AuthError> {
-        // Convert String IDs to Uuid
-        let user_id =
-            Uuid::parse_str(&user.id).map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+        // Dropping the transaction on an error rolls the partial insert back
+        let transaction = self.begin_transaction().await?;
+        let user = transaction.add_user(user).await?;
+        transaction.commit().await?;
+        Ok(user)
+    }
+
+    /// Retrieves a user and their credentials by user ID.
+    ///
+    /// Looks up a user in the `cryptic_users` table by their UUID, and fetches associated credentials
+    /// and OAuth accounts.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The user ID as a string (UUID format).
+    ///
+    /// # Returns
+    ///
+    /// Returns [`Some(User)`] if found, or [`None`] if not found or ID is invalid.
+    async fn get_user_by_id(&self, id: &UserId) -> Option<User> {
+        let mut conn = self.conn.lock().await;
+        Self::get_user_by_id_on(&mut conn, id).await.ok().flatten()
+    }
+
+    /// Retrieves a user by their unique ID, reporting query failures.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The unique identifier of the user.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`Ok(Some(User))`] if found, [`Ok(None)`] if not found or ID is invalid,
+    /// [`AuthError::StorageUnavailable`] if the database cannot be reached, or
+    /// [`AuthError::DatabaseError`] on other failures.
+    async fn find_user_by_id(&self, id: &UserId) -> Result<Option<User>, AuthError> {
+        let mut conn = self.conn.lock().await;
+        Self::get_user_by_id_on(&mut conn, id).await
+    }
+
+    /// Retrieves a user without a tenant and their credentials by identifier (e.g., username or
+    /// email).
+    ///
+    /// Looks up the identifier among the `cryptic_credentials` rows without a tenant, then
+    /// fetches the full user record and associated data.
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier` - The unique identifier for the user.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`Some(User)`] if found, or [`None`] if not found.
+    async fn get_user_by_identifier(&self, identifier: &str) -> Option<User> {
+        let mut conn = self.conn.lock().await;
+        Self::get_user_by_identifier_on(&mut conn, identifier)
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Retrieves a user by identifier within a tenant.
+    ///
+    /// Looks up the identifier in the `cryptic_credentials` table, restricted to the given tenant
+    /// (`NULL` when `tenant_id` is `None`), then fetches the full user record.
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier` - The identifier for the user.
+    /// * `tenant_id` - The tenant to search in, or `None` for users without a tenant.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`Some(User)`] if found, or [`None`] if not found or the query failed.
+    async fn get_user_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Option<User> {
+        let mut conn = self.conn.lock().await;
+        Self::get_user_by_identifier_in_tenant_on(&mut conn, identifier, tenant_id)
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Retrieves a user by identifier within a tenant, reporting query failures.
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier` - The identifier for the user.
+    /// * `tenant_id` - The tenant to search in, or `None` for users without a tenant.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`Ok(Some(User))`] if found, [`Ok(None)`] if not found,
+    /// [`AuthError::StorageUnavailable`] if the database cannot be reached, or
+    /// [`AuthError::DatabaseError`] on other failures.
+    async fn find_user_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<User>, AuthError> {
+        let mut conn = self.conn.lock().await;
+        Self::get_user_by_identifier_in_tenant_on(&mut conn, identifier, tenant_id).await
+    }
+
+    /// Retrieves the credentials for an identifier, among users without a tenant.
+    ///
+    /// Reads only the `cryptic_credentials` table, without loading the user's OAuth accounts.
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier` - The unique identifier for the user.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`Ok(Some(StoredCredentials))`] if found, [`Ok(None)`] if not found, or
+    /// [`AuthError::DatabaseError`] on failure.
+    async fn get_credentials_by_identifier(
+        &self,
+        identifier: &str,
+    ) -> Result<Option<StoredCredentials>, crate::error::AuthError> {
+        let mut conn = self.conn.lock().await;
+        Self::get_credentials_by_identifier_on(&mut conn, identifier).await
+    }
+
+    /// Retrieves the credentials for an identifier within a tenant.
+    ///
+    /// Reads only the `cryptic_credentials` table, restricted to the given tenant (`NULL` when
+    /// `tenant_id` is `None`).
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier` - The identifier for the user.
+    /// * `tenant_id` - The tenant to search in, or `None` for users without a tenant.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`Ok(Some(StoredCredentials))`] if found, [`Ok(None)`] if not found, or
+    /// [`AuthError::DatabaseError`] on failure.
+    async fn get_credentials_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<StoredCredentials>, crate::error::AuthError> {
+        let mut conn = self.conn.lock().await;
+        Self::get_credentials_by_identifier_in_tenant_on(&mut conn, identifier, tenant_id).await
+    }
+
+    /// Retrieves the credentials of every user with a password.
+    ///
+    /// Reads only the `cryptic_credentials` table.
+    ///
+    /// # Returns
+    ///
+    /// Returns the credentials of all users, or [`AuthError::DatabaseError`] on failure.
+    async fn list_credentials(&self) -> Result<Vec<StoredCredentials>, crate::error::AuthError> {
+        let mut conn = self.conn.lock().await;
+        Self::list_credentials_on(&mut conn).await
+    }
+
+    /// Streams every user, reading `cryptic_users` in batches ordered by ID.
+    ///
+    /// Each batch is read with keyset pagination (`WHERE id > last_id`), so the connection is
+    /// only locked while a batch is read and the stream stays correct while users are added or
+    /// removed. Users added during the stream are yielded if their ID sorts after the current
+    /// position.
+    ///
+    /// # Returns
+    ///
+    /// A stream of users, ending with [`AuthError::DatabaseError`] if a batch cannot be read.
+    fn stream_users(&self) -> UserStream<'_> {
+        stream::unfold(Some(None), move |after| async move {
+            let after = after?;
+            let mut conn = self.conn.lock().await;
+            match Self::users_after_on(&mut conn, after, STREAM_BATCH_SIZE).await {
+                Ok(batch) if batch.is_empty() => None,
+                Ok(batch) => {
+                    let last = batch
+                        .last()
+                        .and_then(|user| Uuid::parse_str(user.id.as_str()).ok());
+                    let batch: Vec<_> = batch.into_iter().map(Ok).collect();
+                    Some((batch, last.map(Some)))
+                }
+                Err(e) => Some((vec![Err(e)], None)),
+            }
+        })
+        .flat_map(stream::iter)
+        .boxed()
+    }
 
+    /// Starts a database transaction on the repository's connection.
+    ///
+    /// The connection stays locked until the transaction is committed or rolled back, so other
+    /// operations on the repository wait for it. Dropping the transaction rolls it back.
+    ///
+    /// # Returns
+    ///
+    /// Returns the open [`RepositoryTransaction`], or [`AuthError::DatabaseError`] if `BEGIN`
+    /// fails.
+    async fn begin_transaction(
+        &self,
+    ) -> Result<Box<dyn RepositoryTransaction + '_>, crate::error::AuthError> {
         let mut conn = self.conn.lock().await;
+        sqlx::postgres::PgTransactionManager::begin(&mut conn, None)
+            .await
+            .map_err(database_error)?;
+        Ok(Box::new(PgTransaction {
+            conn: Mutex::new(conn),
+            open: true,
+        }))
+    }
+
+    /// Updates a user's credentials, OAuth accounts and metadata in the database.
+    ///
+    /// Updates the `updated_at` timestamp in the `cryptic_users` table, updates credentials
+    /// in the `cryptic_credentials` table if provided, and makes `cryptic_oauth_accounts` match
+    /// the user's linked accounts. The writes run in a single transaction, so a failure
+    /// leaves the stored user unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The [`User`] struct with updated credentials.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`Ok(())`] on success, or [`AuthError::DatabaseError`] on failure.
+    async fn update_user(&self, user: &User) -> Result<(), crate::error::AuthError> {
+        // Dropping the transaction on an error rolls the partial update back
+        let transaction = self.begin_transaction().await?;
+        transaction.update_user(user).await?;
+        transaction.commit().await
+    }
+
+    /// Updates a user only if its stored `version` is `expected_version`, bumping it.
+    ///
+    /// The version is compared and bumped by a single `UPDATE ... WHERE id = $1 AND
+    /// version = $2`, in the same transaction as the credential and OAuth account writes.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The [`User`] struct with updated fields.
+    /// * `expected_version` - The version of the user when it was loaded.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`Ok(())`] on success, [`AuthError::ConcurrentModification`] if the stored
+    /// version differs, [`AuthError::UserNotFound`] if the user does not exist, or
+    /// [`AuthError::DatabaseError`] on failure.
+    async fn compare_and_update_user(
+        &self,
+        user: &User,
+        expected_version: u64,
+    ) -> Result<(), crate::error::AuthError> {
+        let transaction = self.begin_transaction().await?;
+        transaction
+            .compare_and_update_user(user, expected_version)
+            .await?;
+        transaction.commit().await
+    }
+
+    /// Deletes a user and their credentials from the database by user ID.
+    ///
+    /// Removes the user record from the `cryptic_users` table, along with any associated credentials
+    /// and OAuth accounts (if foreign key constraints are set to cascade).
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The user ID as a string (UUID format).
+    ///
+    /// # Returns
+    ///
+    /// Returns [`Ok(())`] on success, or [`AuthError::DatabaseError`] on failure.
+    async fn delete_user(&self, id: &UserId) -> Result<(), crate::error::AuthError> {
+        let mut conn = self.conn.lock().await;
+        Self::delete_user_on(&mut conn, id).await
+    }
+
+    /// Retrieves a user by their OAuth provider and provider user ID.
+    ///
+    /// Looks up a user in the `cryptic_oauth_accounts` table by provider and provider user ID,
+    /// then fetches the full user record and associated data. Provider accounts are unique
+    /// across tenants, so the lookup is unambiguous; callers check the user's tenant.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - The OAuth2 provider.
+    /// * `provider_user_id` - The user ID from the OAuth provider.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`Some(User)`] if found, or [`None`] if not found or the query failed.
+    async fn get_user_by_oauth_id(
+        &self,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        provider_user_id: &str,
+    ) -> Option<User> {
+        let mut conn = self.conn.lock().await;
+        Self::get_user_by_oauth_id_on(&mut conn, provider, provider_user_id)
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Retrieves a user by OAuth provider and provider user ID, reporting query failures.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - The OAuth2 provider.
+    /// * `provider_user_id` - The user ID from the OAuth provider.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`Ok(Some(User))`] if found, [`Ok(None)`] if not found,
+    /// [`AuthError::StorageUnavailable`] if the database cannot be reached, or
+    /// [`AuthError::DatabaseError`] on other failures.
+    async fn find_user_by_oauth_id(
+        &self,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        provider_user_id: &str,
+    ) -> Result<Option<User>, AuthError> {
+        let mut conn = self.conn.lock().await;
+        Self::get_user_by_oauth_id_on(&mut conn, provider, provider_user_id).await
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl PgUserRepo {
+    /// Runs [`UserRepository::add_user`](crate::core::user::persistence::UserRepository::add_user) on `conn`.
+    async fn add_user_on(
+        conn: &mut sqlx::PgConnection,
+        user: User,
+    ) -> Result<User, crate::error::AuthError> {
+        // Convert String IDs to Uuid
+        let user_id = Uuid::parse_str(user.id.as_str())
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
 
         // Insert into cryptic_users with timestamps
-        sqlx::query!(
-            "INSERT INTO cryptic_users (id, created_at, updated_at) VALUES ($1, $2, $3)",
-            user_id,
-            user.created_at,
-            user.updated_at
+        sqlx::query(
+            "INSERT INTO cryptic_users (id, created_at, updated_at, tenant_id, last_login_at, status, hash_target, roles, version) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
         )
+        .bind(user_id)
+        .bind(user.created_at)
+        .bind(user.updated_at)
+        .bind(&user.tenant_id)
+        .bind(user.last_login_at)
+        .bind(user.status.as_str())
+        .bind(user.hash_target.map(|target| target.to_string()))
+        .bind(&user.roles)
+        .bind(user.version as i64)
         .execute(&mut *conn)
         .await
-        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+        .map_err(database_error)?;
 
         // Insert credentials if they exist
         if let Some(credentials) = &user.credentials {
             let cred_user_id = Uuid::parse_str(&credentials.user_id)
                 .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
 
-            sqlx::query!(
-                "INSERT INTO cryptic_credentials (user_id, identifier, password_hash) VALUES ($1, $2, $3)",
-                cred_user_id,
-                credentials.identifier,
-                credentials.password_hash
+            sqlx::query(
+                "INSERT INTO cryptic_credentials (user_id, identifier, password_hash, tenant_id) VALUES ($1, $2, $3, $4)",
             )
+            .bind(cred_user_id)
+            .bind(&credentials.identifier)
+            .bind(&credentials.password_hash)
+            .bind(&user.tenant_id)
             .execute(&mut *conn)
             .await
-            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+            .map_err(|e| match e {
+                sqlx::Error::Database(db) if db.is_unique_violation() => {
+                    AuthError::UserAlreadyExists
+                }
+                e => database_error(e),
+            })?;
         }
 
         // Insert OAuth accounts
         for (provider, oauth_info) in &user.oauth_accounts {
-            let provider_str = match provider {
-                crate::core::oauth::store::OAuth2Provider::Google => "google",
-                crate::core::oauth::store::OAuth2Provider::GitHub => "github",
-                crate::core::oauth::store::OAuth2Provider::Discord => "discord",
-                crate::core::oauth::store::OAuth2Provider::Microsoft => "microsoft",
-            };
-
-            let raw_data_json = oauth_info
-                .raw_data
-                .as_ref()
-                .map(|data| serde_json::to_value(data).unwrap_or(serde_json::Value::Null));
-
-            sqlx::query!(
-                r#"INSERT INTO cryptic_oauth_accounts
-                   (user_id, provider, provider_user_id, email, name, avatar_url, verified_email, locale, updated_at, raw_data)
-                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"#,
-                user_id,
-                provider_str,
-                oauth_info.provider_user_id,
-                oauth_info.email,
-                oauth_info.name,
-                oauth_info.avatar_url,
-                oauth_info.verified_email,
-                oauth_info.locale,
-                oauth_info.updated_at,
-                raw_data_json
-            )
-            .execute(&mut *conn)
-            .await
-            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+            upsert_oauth_account(&mut *conn, user_id, *provider, oauth_info).await?;
         }
 
         Ok(user)
     }
 
-    /// Retrieves a user and their credentials by user ID.
-    ///
-    /// Looks up a user in the `cryptic_users` table by their UUID, and fetches associated credentials
-    /// and OAuth accounts.
-    ///
-    /// # Arguments
-    ///
-    /// * `id` - The user ID as a string (UUID format).
-    ///
-    /// # Returns
+    /// Runs [`UserRepository::find_user_by_id`](crate::core::user::persistence::UserRepository::find_user_by_id) on `conn`.
     ///
-    /// Returns [`Some(User)`] if found, or [`None`] if not found or ID is invalid.
-    async fn get_user_by_id(&self, id: &str) -> Option<User> {
-        let uuid = Uuid::parse_str(id).ok()?;
-        let mut conn = self.conn.lock().await;
+    /// Query failures and rows that cannot be decoded are reported as errors.
+    async fn get_user_by_id_on(
+        conn: &mut sqlx::PgConnection,
+        id: &UserId,
+    ) -> Result<Option<User>, crate::error::AuthError> {
+        let Ok(uuid) = Uuid::parse_str(id.as_str()) else {
+            return Ok(None);
+        };
 
         // Get user basic info
-        let user_rec = sqlx::query!(
-            "SELECT id, created_at, updated_at FROM cryptic_users WHERE id = $1",
-            uuid
+        let Some(user_rec) = sqlx::query(
+            "SELECT id, created_at, updated_at, tenant_id, last_login_at, status, hash_target, roles, version FROM cryptic_users WHERE id = $1",
         )
-        .fetch_one(&mut *conn)
+        .bind(uuid)
+        .fetch_optional(&mut *conn)
         .await
-        .ok()?;
+        .map_err(database_error)?
+        else {
+            return Ok(None);
+        };
 
         // Get credentials (if any)
         let credentials = sqlx::query!(
@@ -499,7 +832,7 @@ impl crate::core::user::persistence::traits::UserRepository for PgUserRepo {
         )
         .fetch_optional(&mut *conn)
         .await
-        .ok()?
+        .map_err(database_error)?
         .map(|rec| crate::core::credentials::Credentials {
             user_id: rec.user_id.to_string(),
             identifier: rec.identifier,
@@ -507,188 +840,722 @@ impl crate::core::user::persistence::traits::UserRepository for PgUserRepo {
         });
 
         // Get OAuth accounts
-        let oauth_records = sqlx::query!(
-            r#"SELECT provider, provider_user_id, email, name, avatar_url, verified_email, locale, updated_at, raw_data
+        let oauth_records = sqlx::query(
+            r#"SELECT provider, provider_user_id, email, name, avatar_url, verified_email, locale, updated_at, raw_data, granted_scopes
                FROM cryptic_oauth_accounts WHERE user_id = $1"#,
-            uuid
         )
+        .bind(uuid)
         .fetch_all(&mut *conn)
         .await
-        .ok()?;
+        .map_err(database_error)?;
+
+        Self::user_from_rows(&user_rec, credentials, oauth_records).map(Some)
+    }
+
+    /// Assembles a user from the rows fetched by [`Self::get_user_by_id_on`].
+    ///
+    /// OAuth accounts of unknown providers are skipped; any other column that cannot be
+    /// decoded is reported as [`AuthError::DatabaseError`].
+    fn user_from_rows(
+        user_rec: &sqlx::postgres::PgRow,
+        credentials: Option<crate::core::credentials::Credentials>,
+        oauth_records: Vec<sqlx::postgres::PgRow>,
+    ) -> Result<User, AuthError> {
+        use sqlx::Row;
+        let user_id: Uuid = user_rec.try_get("id").map_err(column_error)?;
+        let status: String = user_rec.try_get("status").map_err(column_error)?;
 
         let mut oauth_accounts = std::collections::HashMap::new();
         for oauth_rec in oauth_records {
-            let provider = match oauth_rec.provider.as_str() {
-                "google" => crate::core::oauth::store::OAuth2Provider::Google,
-                "github" => crate::core::oauth::store::OAuth2Provider::GitHub,
-                "discord" => crate::core::oauth::store::OAuth2Provider::Discord,
-                "microsoft" => crate::core::oauth::store::OAuth2Provider::Microsoft,
-                _ => continue, // Skip unknown providers
+            let provider_str: String = oauth_rec.try_get("provider").map_err(column_error)?;
+            let Ok(provider) = provider_str.parse::<crate::core::oauth::store::OAuth2Provider>()
+            else {
+                continue; // Skip unknown providers
             };
 
             let oauth_info = crate::core::oauth::store::OAuth2UserInfo {
-                user_id: user_rec.id.to_string(),
+                user_id: user_id.to_string(),
                 provider,
-                provider_user_id: oauth_rec.provider_user_id,
-                email: oauth_rec.email,
-                name: oauth_rec.name,
-                avatar_url: oauth_rec.avatar_url,
-                verified_email: oauth_rec.verified_email,
-                locale: oauth_rec.locale,
-                updated_at: oauth_rec.updated_at,
-                raw_data: oauth_rec.raw_data,
+                provider_user_id: oauth_rec
+                    .try_get("provider_user_id")
+                    .map_err(column_error)?,
+                email: oauth_rec.try_get("email").map_err(column_error)?,
+                name: oauth_rec.try_get("name").map_err(column_error)?,
+                avatar_url: oauth_rec.try_get("avatar_url").map_err(column_error)?,
+                verified_email: oauth_rec.try_get("verified_email").map_err(column_error)?,
+                locale: oauth_rec.try_get("locale").map_err(column_error)?,
+                updated_at: oauth_rec.try_get("updated_at").map_err(column_error)?,
+                raw_data: oauth_rec.try_get("raw_data").map_err(column_error)?,
+                granted_scopes: oauth_rec.try_get("granted_scopes").map_err(column_error)?,
             };
 
             oauth_accounts.insert(provider, oauth_info);
         }
 
-        Some(User {
-            id: user_rec.id.to_string(),
+        let hash_target = user_rec
+            .try_get::<Option<String>, _>("hash_target")
+            .map_err(column_error)?
+            .map(|target| {
+                target.parse().map_err(|e| {
+                    AuthError::DatabaseError(format!("Invalid hash_target of user {user_id}: {e}"))
+                })
+            })
+            .transpose()?;
+        Ok(User {
+            id: UserId::from(user_id.to_string()),
             credentials,
             oauth_accounts,
-            created_at: user_rec.created_at,
-            updated_at: user_rec.updated_at,
+            created_at: user_rec.try_get("created_at").map_err(column_error)?,
+            updated_at: user_rec.try_get("updated_at").map_err(column_error)?,
+            tenant_id: user_rec.try_get("tenant_id").map_err(column_error)?,
+            last_login_at: user_rec.try_get("last_login_at").map_err(column_error)?,
+            status: status.parse().map_err(|e| {
+                AuthError::DatabaseError(format!("Invalid status of user {user_id}: {e}"))
+            })?,
+            hash_target,
+            roles: user_rec.try_get("roles").map_err(column_error)?,
+            version: user_rec
+                .try_get::<i64, _>("version")
+                .map_err(column_error)? as u64,
         })
     }
 
-    /// Retrieves a user and their credentials by identifier (e.g., username or email).
-    ///
-    /// Looks up a user by their unique identifier in the `cryptic_credentials` table, then fetches
-    /// the full user record and associated data.
-    ///
-    /// # Arguments
-    ///
-    /// * `identifier` - The unique identifier for the user.
-    ///
-    /// # Returns
+    /// Runs [`UserRepository::get_user_by_identifier`](crate::core::user::persistence::UserRepository::get_user_by_identifier) on `conn`.
     ///
-    /// Returns [`Some(User)`] if found, or [`None`] if not found.
-    async fn get_user_by_identifier(&self, identifier: &str) -> Option<User> {
-        let mut conn = self.conn.lock().await;
+    /// Only users without a tenant are matched, since identifiers are not unique across tenants.
+    async fn get_user_by_identifier_on(
+        conn: &mut sqlx::PgConnection,
+        identifier: &str,
+    ) -> Result<Option<User>, AuthError> {
+        Self::get_user_by_identifier_in_tenant_on(conn, identifier, None).await
+    }
+
+    /// Runs [`UserRepository::get_user_by_identifier_in_tenant`](crate::core::user::persistence::UserRepository::get_user_by_identifier_in_tenant) on `conn`.
+    async fn get_user_by_identifier_in_tenant_on(
+        conn: &mut sqlx::PgConnection,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<User>, AuthError> {
+        use sqlx::Row;
 
-        // Get user ID from credentials
-        let cred_rec = sqlx::query!(
-            "SELECT user_id FROM cryptic_credentials WHERE identifier = $1",
-            identifier
+        let Some(cred_rec) = sqlx::query(
+            "SELECT user_id FROM cryptic_credentials WHERE identifier = $1 AND tenant_id IS NOT DISTINCT FROM $2",
         )
-        .fetch_one(&mut *conn)
+        .bind(identifier)
+        .bind(tenant_id)
+        .fetch_optional(&mut *conn)
         .await
-        .ok()?;
+        .map_err(database_error)?
+        else {
+            return Ok(None);
+        };
+        let user_id: Uuid = cred_rec
+            .try_get("user_id")
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
 
-        // Use get_user_by_id to get the full user with all data
-        drop(conn); // Release the lock before calling get_user_by_id
-        self.get_user_by_id(&cred_rec.user_id.to_string()).await
+        Self::get_user_by_id_on(conn, &user_id.to_string().into()).await
     }
 
-    /// Updates a user's credentials and metadata in the database.
+    /// Runs [`UserRepository::get_credentials_by_identifier`](crate::core::user::persistence::UserRepository::get_credentials_by_identifier) on `conn`.
     ///
-    /// Updates the `updated_at` timestamp in the `cryptic_users` table, and updates credentials
-    /// in the `cryptic_credentials` table if provided.
-    ///
-    /// # Arguments
-    ///
-    /// * `user` - The [`User`] struct with updated credentials.
+    /// Only users without a tenant are matched, since identifiers are not unique across tenants.
+    async fn get_credentials_by_identifier_on(
+        conn: &mut sqlx::PgConnection,
+        identifier: &str,
+    ) -> Result<Option<StoredCredentials>, crate::error::AuthError> {
+        Self::get_credentials_by_identifier_in_tenant_on(conn, identifier, None).await
+    }
+
+    /// Runs [`UserRepository::get_credentials_by_identifier_in_tenant`](crate::core::user::persistence::UserRepository::get_credentials_by_identifier_in_tenant) on `conn`.
+    async fn get_credentials_by_identifier_in_tenant_on(
+        conn: &mut sqlx::PgConnection,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<StoredCredentials>, crate::error::AuthError> {
+        let rec = sqlx::query(
+            "SELECT user_id, password_hash FROM cryptic_credentials WHERE identifier = $1 AND tenant_id IS NOT DISTINCT FROM $2",
+        )
+        .bind(identifier)
+        .bind(tenant_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(database_error)?;
+        rec.map(|rec| stored_credentials_from_row(&rec)).transpose()
+    }
+
+    /// Runs [`UserRepository::list_credentials`](crate::core::user::persistence::UserRepository::list_credentials) on `conn`.
+    async fn list_credentials_on(
+        conn: &mut sqlx::PgConnection,
+    ) -> Result<Vec<StoredCredentials>, crate::error::AuthError> {
+        let rows = sqlx::query("SELECT user_id, password_hash FROM cryptic_credentials")
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(database_error)?;
+        rows.iter().map(stored_credentials_from_row).collect()
+    }
+
+    /// Reads up to `limit` users whose ID sorts after `after`, ordered by ID.
     ///
-    /// # Returns
+    /// The batch takes three queries: one per table, with the credentials and OAuth accounts
+    /// of all its users fetched at once.
+    async fn users_after_on(
+        conn: &mut sqlx::PgConnection,
+        after: Option<Uuid>,
+        limit: usize,
+    ) -> Result<Vec<User>, crate::error::AuthError> {
+        use sqlx::Row;
+        let rows = sqlx::query(
+            "SELECT id, created_at, updated_at, tenant_id, last_login_at, status, hash_target, roles, version FROM cryptic_users WHERE $1::uuid IS NULL OR id > $1 ORDER BY id LIMIT $2",
+        )
+        .bind(after)
+        .bind(limit as i64)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(database_error)?;
+        let ids = rows
+            .iter()
+            .map(|row| row.try_get::<Uuid, _>("id").map_err(column_error))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut credentials = std::collections::HashMap::new();
+        for rec in sqlx::query(
+            "SELECT user_id, identifier, password_hash FROM cryptic_credentials WHERE user_id = ANY($1)",
+        )
+        .bind(&ids)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(database_error)?
+        {
+            let user_id: Uuid = rec.try_get("user_id").map_err(column_error)?;
+            credentials.insert(
+                user_id,
+                crate::core::credentials::Credentials {
+                    user_id: user_id.to_string(),
+                    identifier: rec.try_get("identifier").map_err(column_error)?,
+                    password_hash: rec.try_get("password_hash").map_err(column_error)?,
+                },
+            );
+        }
+
+        let mut oauth_records = std::collections::HashMap::<Uuid, Vec<_>>::new();
+        for rec in sqlx::query(
+            r#"SELECT user_id, provider, provider_user_id, email, name, avatar_url, verified_email, locale, updated_at, raw_data, granted_scopes
+               FROM cryptic_oauth_accounts WHERE user_id = ANY($1)"#,
+        )
+        .bind(&ids)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(database_error)?
+        {
+            let user_id: Uuid = rec.try_get("user_id").map_err(column_error)?;
+            oauth_records.entry(user_id).or_default().push(rec);
+        }
+
+        rows.iter()
+            .zip(&ids)
+            .map(|(row, id)| {
+                Self::user_from_rows(
+                    row,
+                    credentials.remove(id),
+                    oauth_records.remove(id).unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    /// Runs [`UserRepository::update_user`](crate::core::user::persistence::UserRepository::update_user) on `conn`,
+    /// or [`UserRepository::compare_and_update_user`](crate::core::user::persistence::UserRepository::compare_and_update_user)
+    /// when `expected_version` is set.
     ///
-    /// Returns [`Ok(())`] on success, or [`AuthError::DatabaseError`] on failure.
-    async fn update_user(&self, user: &User) -> Result<(), crate::error::AuthError> {
+    /// The stored version is bumped by the `UPDATE` itself, which only matches the expected
+    /// version, so concurrent writers cannot both succeed.
+    async fn update_user_on(
+        conn: &mut sqlx::PgConnection,
+        user: &User,
+        expected_version: Option<u64>,
+    ) -> Result<(), crate::error::AuthError> {
         // Convert String user_id to Uuid
-        let user_id =
-            Uuid::parse_str(&user.id).map_err(|e| AuthError::DatabaseError(e.to_string()))?;
-
-        let mut conn = self.conn.lock().await;
+        let user_id = Uuid::parse_str(user.id.as_str())
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
 
-        // Update user's updated_at timestamp
-        sqlx::query!(
-            "UPDATE cryptic_users SET updated_at = $1 WHERE id = $2",
-            user.updated_at,
-            user_id
+        // Update user's metadata
+        let updated = sqlx::query(
+            "UPDATE cryptic_users SET updated_at = $1, tenant_id = $2, last_login_at = $3, status = $4, hash_target = $5, roles = $6, version = version + 1
+             WHERE id = $7 AND ($8::BIGINT IS NULL OR version = $8)",
         )
+        .bind(user.updated_at)
+        .bind(&user.tenant_id)
+        .bind(user.last_login_at)
+        .bind(user.status.as_str())
+        .bind(user.hash_target.map(|target| target.to_string()))
+        .bind(&user.roles)
+        .bind(user_id)
+        .bind(expected_version.map(|version| version as i64))
         .execute(&mut *conn)
         .await
-        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+        .map_err(database_error)?;
+        if expected_version.is_some() && updated.rows_affected() == 0 {
+            let exists = sqlx::query("SELECT 1 FROM cryptic_users WHERE id = $1")
+                .bind(user_id)
+                .fetch_optional(&mut *conn)
+                .await
+                .map_err(database_error)?
+                .is_some();
+            return Err(if exists {
+                AuthError::ConcurrentModification
+            } else {
+                AuthError::UserNotFound
+            });
+        }
 
-        // Update credentials if they exist
+        // Upsert credentials if they exist (users created through OAuth may gain a password later)
         if let Some(credentials) = &user.credentials {
             let cred_user_id = Uuid::parse_str(&credentials.user_id)
                 .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
 
-            sqlx::query!(
-                "UPDATE cryptic_credentials SET identifier = $1, password_hash = $2 WHERE user_id = $3",
-                credentials.identifier,
-                credentials.password_hash,
-                cred_user_id
+            sqlx::query(
+                "INSERT INTO cryptic_credentials (user_id, identifier, password_hash, tenant_id) VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (user_id) DO UPDATE SET identifier = EXCLUDED.identifier, password_hash = EXCLUDED.password_hash, tenant_id = EXCLUDED.tenant_id",
             )
+            .bind(cred_user_id)
+            .bind(&credentials.identifier)
+            .bind(&credentials.password_hash)
+            .bind(&user.tenant_id)
             .execute(&mut *conn)
             .await
-            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+            .map_err(database_error)?;
+        }
+
+        // Sync OAuth accounts: upsert the linked ones, remove the unlinked ones
+        let providers: Vec<&str> = user
+            .oauth_accounts
+            .keys()
+            .map(|provider| provider.as_str())
+            .collect();
+        sqlx::query(
+            "DELETE FROM cryptic_oauth_accounts WHERE user_id = $1 AND provider <> ALL($2)",
+        )
+        .bind(user_id)
+        .bind(&providers)
+        .execute(&mut *conn)
+        .await
+        .map_err(database_error)?;
+        for (provider, oauth_info) in &user.oauth_accounts {
+            upsert_oauth_account(&mut *conn, user_id, *provider, oauth_info).await?;
         }
 
         Ok(())
     }
 
-    /// Deletes a user and their credentials from the database by user ID.
-    ///
-    /// Removes the user record from the `cryptic_users` table, along with any associated credentials
-    /// and OAuth accounts (if foreign key constraints are set to cascade).
-    ///
-    /// # Arguments
-    ///
-    /// * `id` - The user ID as a string (UUID format).
-    ///
-    /// # Returns
-    ///
-    /// Returns [`Ok(())`] on success, or [`AuthError::DatabaseError`] on failure.
-    async fn delete_user(&self, id: &str) -> Result<(), crate::error::AuthError> {
-        let uuid = Uuid::parse_str(id).map_err(|e| AuthError::DatabaseError(e.to_string()))?;
-        let mut conn = self.conn.lock().await;
+    /// Runs [`UserRepository::delete_user`](crate::core::user::persistence::UserRepository::delete_user) on `conn`.
+    async fn delete_user_on(
+        conn: &mut sqlx::PgConnection,
+        id: &UserId,
+    ) -> Result<(), crate::error::AuthError> {
+        let uuid =
+            Uuid::parse_str(id.as_str()).map_err(|e| AuthError::DatabaseError(e.to_string()))?;
         sqlx::query!("DELETE FROM cryptic_users WHERE id = $1", uuid)
             .execute(&mut *conn)
             .await
-            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+            .map_err(database_error)?;
         Ok(())
     }
 
-    /// Retrieves a user by their OAuth provider and provider user ID.
-    ///
-    /// Looks up a user in the `cryptic_oauth_accounts` table by provider and provider user ID,
-    /// then fetches the full user record and associated data.
+    /// Runs [`UserRepository::get_user_by_oauth_id`](crate::core::user::persistence::UserRepository::get_user_by_oauth_id) on `conn`.
+    async fn get_user_by_oauth_id_on(
+        conn: &mut sqlx::PgConnection,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        provider_user_id: &str,
+    ) -> Result<Option<User>, AuthError> {
+        let provider_str = provider.as_str();
+
+        // Get user ID from OAuth accounts
+        let Some(oauth_rec) = sqlx::query!(
+            "SELECT user_id FROM cryptic_oauth_accounts WHERE provider = $1 AND provider_user_id = $2",
+            provider_str,
+            provider_user_id
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(database_error)?
+        else {
+            return Ok(None);
+        };
+
+        // Use get_user_by_id to get the full user with all data
+        Self::get_user_by_id_on(conn, &oauth_rec.user_id.to_string().into()).await
+    }
+}
+
+/// Reads a `user_id, password_hash` row of `cryptic_credentials`.
+#[cfg(feature = "postgres")]
+fn stored_credentials_from_row(
+    row: &sqlx::postgres::PgRow,
+) -> Result<StoredCredentials, AuthError> {
+    use sqlx::Row;
+    let user_id: Uuid = row
+        .try_get("user_id")
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+    let password_hash: String = row
+        .try_get("password_hash")
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+    Ok(StoredCredentials {
+        user_id: user_id.to_string().into(),
+        password_hash,
+    })
+}
+
+/// Inserts or updates the `cryptic_oauth_accounts` row of a linked OAuth2 account.
+#[cfg(feature = "postgres")]
+async fn upsert_oauth_account(
+    conn: &mut sqlx::PgConnection,
+    user_id: Uuid,
+    provider: crate::core::oauth::store::OAuth2Provider,
+    oauth_info: &crate::core::oauth::store::OAuth2UserInfo,
+) -> Result<(), AuthError> {
+    sqlx::query(
+        r#"INSERT INTO cryptic_oauth_accounts
+           (user_id, provider, provider_user_id, email, name, avatar_url, verified_email, locale, updated_at, raw_data, granted_scopes)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+           ON CONFLICT (user_id, provider) DO UPDATE SET
+             provider_user_id = EXCLUDED.provider_user_id, email = EXCLUDED.email, name = EXCLUDED.name,
+             avatar_url = EXCLUDED.avatar_url, verified_email = EXCLUDED.verified_email, locale = EXCLUDED.locale,
+             updated_at = EXCLUDED.updated_at, raw_data = EXCLUDED.raw_data, granted_scopes = EXCLUDED.granted_scopes"#,
+    )
+    .bind(user_id)
+    .bind(provider.as_str())
+    .bind(&oauth_info.provider_user_id)
+    .bind(&oauth_info.email)
+    .bind(&oauth_info.name)
+    .bind(&oauth_info.avatar_url)
+    .bind(oauth_info.verified_email)
+    .bind(&oauth_info.locale)
+    .bind(oauth_info.updated_at)
+    .bind(&oauth_info.raw_data)
+    .bind(&oauth_info.granted_scopes)
+    .execute(conn)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => AuthError::UserAlreadyExists,
+        e => database_error(e),
+    })?;
+    Ok(())
+}
+
+/// Maps a query error to an [`AuthError`].
+///
+/// Connection and pool failures become [`AuthError::StorageUnavailable`], so callers can retry
+/// or answer `503 Service Unavailable`; other failures become [`AuthError::DatabaseError`].
+#[cfg(feature = "postgres")]
+fn database_error(e: sqlx::Error) -> AuthError {
+    match e {
+        sqlx::Error::Io(_)
+        | sqlx::Error::Tls(_)
+        | sqlx::Error::PoolTimedOut
+        | sqlx::Error::PoolClosed
+        | sqlx::Error::WorkerCrashed => AuthError::StorageUnavailable(e.to_string()),
+        e => AuthError::DatabaseError(e.to_string()),
+    }
+}
+
+/// Maps an error decoding a column of a fetched row to [`AuthError::DatabaseError`].
+#[cfg(feature = "postgres")]
+fn column_error(e: sqlx::Error) -> AuthError {
+    AuthError::DatabaseError(e.to_string())
+}
+
+/// A database transaction over a [`PgUserRepo`], holding the repository's connection.
+#[cfg(feature = "postgres")]
+struct PgTransaction<'a> {
+    /// The repository's connection, on which the transaction was started.
+    conn: Mutex<MutexGuard<'a, sqlx::PgConnection>>,
+    /// Whether the transaction is still to be committed or rolled back.
+    open: bool,
+}
+
+#[cfg(feature = "postgres")]
+#[async_trait]
+impl crate::core::user::persistence::traits::UserRepository for PgTransaction<'_> {
+    async fn begin_transaction(
+        &self,
+    ) -> Result<Box<dyn RepositoryTransaction + '_>, crate::error::AuthError> {
+        Err(nested_transaction_error())
+    }
+
+    async fn add_user(&self, user: User) -> Result<User, crate::error::AuthError> {
+        let mut conn = self.conn.lock().await;
+        PgUserRepo::add_user_on(&mut conn, user).await
+    }
+
+    async fn get_user_by_id(&self, id: &UserId) -> Option<User> {
+        let mut conn = self.conn.lock().await;
+        PgUserRepo::get_user_by_id_on(&mut conn, id)
+            .await
+            .ok()
+            .flatten()
+    }
+
+    async fn find_user_by_id(&self, id: &UserId) -> Result<Option<User>, AuthError> {
+        let mut conn = self.conn.lock().await;
+        PgUserRepo::get_user_by_id_on(&mut conn, id).await
+    }
+
+    async fn get_user_by_identifier(&self, identifier: &str) -> Option<User> {
+        let mut conn = self.conn.lock().await;
+        PgUserRepo::get_user_by_identifier_on(&mut conn, identifier)
+            .await
+            .ok()
+            .flatten()
+    }
+
+    async fn get_user_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Option<User> {
+        let mut conn = self.conn.lock().await;
+        PgUserRepo::get_user_by_identifier_in_tenant_on(&mut conn, identifier, tenant_id)
+            .await
+            .ok()
+            .flatten()
+    }
+
+    async fn find_user_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<User>, AuthError> {
+        let mut conn = self.conn.lock().await;
+        PgUserRepo::get_user_by_identifier_in_tenant_on(&mut conn, identifier, tenant_id).await
+    }
+
+    async fn get_credentials_by_identifier(
+        &self,
+        identifier: &str,
+    ) -> Result<Option<StoredCredentials>, crate::error::AuthError> {
+        let mut conn = self.conn.lock().await;
+        PgUserRepo::get_credentials_by_identifier_on(&mut conn, identifier).await
+    }
+
+    async fn get_credentials_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<StoredCredentials>, crate::error::AuthError> {
+        let mut conn = self.conn.lock().await;
+        PgUserRepo::get_credentials_by_identifier_in_tenant_on(&mut conn, identifier, tenant_id)
+            .await
+    }
+
+    async fn list_credentials(&self) -> Result<Vec<StoredCredentials>, crate::error::AuthError> {
+        let mut conn = self.conn.lock().await;
+        PgUserRepo::list_credentials_on(&mut conn).await
+    }
+
+    async fn update_user(&self, user: &User) -> Result<(), crate::error::AuthError> {
+        let mut conn = self.conn.lock().await;
+        PgUserRepo::update_user_on(&mut conn, user, None).await
+    }
+
+    async fn compare_and_update_user(
+        &self,
+        user: &User,
+        expected_version: u64,
+    ) -> Result<(), crate::error::AuthError> {
+        let mut conn = self.conn.lock().await;
+        PgUserRepo::update_user_on(&mut conn, user, Some(expected_version)).await
+    }
+
+    async fn delete_user(&self, id: &UserId) -> Result<(), crate::error::AuthError> {
+        let mut conn = self.conn.lock().await;
+        PgUserRepo::delete_user_on(&mut conn, id).await
+    }
+
+    async fn get_user_by_oauth_id(
+        &self,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        provider_user_id: &str,
+    ) -> Option<User> {
+        let mut conn = self.conn.lock().await;
+        PgUserRepo::get_user_by_oauth_id_on(&mut conn, provider, provider_user_id)
+            .await
+            .ok()
+            .flatten()
+    }
+
+    async fn find_user_by_oauth_id(
+        &self,
+        provider: crate::core::oauth::store::OAuth2Provider,
+        provider_user_id: &str,
+    ) -> Result<Option<User>, AuthError> {
+        let mut conn = self.conn.lock().await;
+        PgUserRepo::get_user_by_oauth_id_on(&mut conn, provider, provider_user_id).await
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[async_trait]
+impl RepositoryTransaction for PgTransaction<'_> {
+    async fn commit(mut self: Box<Self>) -> Result<(), crate::error::AuthError> {
+        self.open = false;
+        let conn = self.conn.get_mut();
+        sqlx::postgres::PgTransactionManager::commit(conn)
+            .await
+            .map_err(database_error)
+    }
+
+    async fn rollback(mut self: Box<Self>) -> Result<(), crate::error::AuthError> {
+        self.open = false;
+        let conn = self.conn.get_mut();
+        sqlx::postgres::PgTransactionManager::rollback(conn)
+            .await
+            .map_err(database_error)
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl Drop for PgTransaction<'_> {
+    fn drop(&mut self) {
+        if self.open {
+            // Queues a ROLLBACK, sent before the connection's next query
+            sqlx::postgres::PgTransactionManager::start_rollback(self.conn.get_mut());
+        }
+    }
+}
+
+/// A PostgreSQL-backed [`ApiKeyStore`](crate::core::api_key::ApiKeyStore), storing records in
+/// the `cryptic_api_keys` table.
+///
+/// Like [`PgUserRepo`], it manages a single connection protected by a [`tokio::sync::Mutex`].
+/// Deleting a user cascades to their keys.
+#[derive(Debug)]
+#[cfg(feature = "postgres")]
+pub struct PgApiKeyStore {
+    /// The underlying PostgreSQL connection, protected by a mutex for safe concurrent access.
+    conn: Mutex<sqlx::PgConnection>,
+}
+
+#[cfg(feature = "postgres")]
+impl PgApiKeyStore {
+    /// Creates a new [`PgApiKeyStore`] instance from a PostgreSQL connection.
     ///
     /// # Arguments
     ///
-    /// * `provider` - The OAuth2 provider.
-    /// * `provider_user_id` - The user ID from the OAuth provider.
+    /// * `conn` - An established [`sqlx::PgConnection`] to a database with the
+    ///   `cryptic_api_keys` table.
     ///
     /// # Returns
     ///
-    /// Returns [`Some(User)`] if found, or [`None`] if not found.
-    async fn get_user_by_oauth_id(
+    /// Returns a new [`PgApiKeyStore`] instance wrapped in a mutex.
+    pub fn new(conn: sqlx::PgConnection) -> Self {
+        Self {
+            conn: Mutex::new(conn),
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[async_trait]
+impl crate::core::api_key::ApiKeyStore for PgApiKeyStore {
+    async fn insert(
         &self,
-        provider: crate::core::oauth::store::OAuth2Provider,
-        provider_user_id: &str,
-    ) -> Option<User> {
-        let provider_str = match provider {
-            crate::core::oauth::store::OAuth2Provider::Google => "google",
-            crate::core::oauth::store::OAuth2Provider::GitHub => "github",
-            crate::core::oauth::store::OAuth2Provider::Discord => "discord",
-            crate::core::oauth::store::OAuth2Provider::Microsoft => "microsoft",
-        };
+        record: crate::core::api_key::ApiKeyRecord,
+    ) -> Result<(), crate::error::AuthError> {
+        let user_id = Uuid::parse_str(&record.user_id)
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+        let mut conn = self.conn.lock().await;
+        sqlx::query(
+            "INSERT INTO cryptic_api_keys (id, user_id, label, secret_hash, created_at, last_used_at) VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(&record.id)
+        .bind(user_id)
+        .bind(&record.label)
+        .bind(&record.secret_hash)
+        .bind(record.created_at)
+        .bind(record.last_used_at)
+        .execute(&mut *conn)
+        .await
+        .map_err(database_error)?;
+        Ok(())
+    }
 
+    async fn get(
+        &self,
+        key_id: &str,
+    ) -> Result<Option<crate::core::api_key::ApiKeyRecord>, crate::error::AuthError> {
         let mut conn = self.conn.lock().await;
+        let row = sqlx::query(
+            "SELECT id, user_id, label, secret_hash, created_at, last_used_at FROM cryptic_api_keys WHERE id = $1",
+        )
+        .bind(key_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(database_error)?;
+        row.as_ref().map(api_key_record_from_row).transpose()
+    }
 
-        // Get user ID from OAuth accounts
-        let oauth_rec = sqlx::query!(
-            "SELECT user_id FROM cryptic_oauth_accounts WHERE provider = $1 AND provider_user_id = $2",
-            provider_str,
-            provider_user_id
+    async fn list_for_user(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<crate::core::api_key::ApiKeyRecord>, crate::error::AuthError> {
+        let Ok(user_id) = Uuid::parse_str(user_id) else {
+            return Ok(Vec::new());
+        };
+        let mut conn = self.conn.lock().await;
+        let rows = sqlx::query(
+            "SELECT id, user_id, label, secret_hash, created_at, last_used_at FROM cryptic_api_keys WHERE user_id = $1 ORDER BY created_at, id",
         )
-        .fetch_one(&mut *conn)
+        .bind(user_id)
+        .fetch_all(&mut *conn)
         .await
-        .ok()?;
+        .map_err(database_error)?;
+        rows.iter().map(api_key_record_from_row).collect()
+    }
 
-        // Use get_user_by_id to get the full user with all data
-        drop(conn); // Release the lock before calling get_user_by_id
-        self.get_user_by_id(&oauth_rec.user_id.to_string()).await
+    async fn remove(&self, user_id: &str, key_id: &str) -> Result<bool, crate::error::AuthError> {
+        let Ok(user_id) = Uuid::parse_str(user_id) else {
+            return Ok(false);
+        };
+        let mut conn = self.conn.lock().await;
+        let removed = sqlx::query("DELETE FROM cryptic_api_keys WHERE id = $1 AND user_id = $2")
+            .bind(key_id)
+            .bind(user_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(database_error)?;
+        Ok(removed.rows_affected() > 0)
     }
+
+    async fn touch(
+        &self,
+        key_id: &str,
+        used_at: chrono::NaiveDateTime,
+    ) -> Result<(), crate::error::AuthError> {
+        let mut conn = self.conn.lock().await;
+        sqlx::query("UPDATE cryptic_api_keys SET last_used_at = $1 WHERE id = $2")
+            .bind(used_at)
+            .bind(key_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(database_error)?;
+        Ok(())
+    }
+}
+
+/// Reads a row of `cryptic_api_keys`.
+#[cfg(feature = "postgres")]
+fn api_key_record_from_row(
+    row: &sqlx::postgres::PgRow,
+) -> Result<crate::core::api_key::ApiKeyRecord, AuthError> {
+    use sqlx::Row;
+    let column_error = |e: sqlx::Error| AuthError::DatabaseError(e.to_string());
+    let user_id: Uuid = row.try_get("user_id").map_err(column_error)?;
+    Ok(crate::core::api_key::ApiKeyRecord {
+        id: row.try_get("id").map_err(column_error)?,
+        user_id: user_id.to_string(),
+        label: row.try_get("label").map_err(column_error)?,
+        secret_hash: row.try_get("secret_hash").map_err(column_error)?,
+        created_at: row.try_get("created_at").map_err(column_error)?,
+        last_used_at: row.try_get("last_used_at").map_err(column_error)?,
+    })
 }
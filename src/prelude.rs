@@ -0,0 +1,18 @@
+//! Commonly used types, for glob import.
+//!
+//! ```rust,ignore
+//! use narangcia_cryptic::prelude::*;
+//!
+//! async fn sign_in(auth: &AuthService, identifier: String, password: String) -> AuthResult<TokenPair> {
+//!     let (_user, tokens) = auth
+//!         .login(LoginMethod::Credentials { identifier, password })
+//!         .await?;
+//!     Ok(tokens)
+//! }
+//! ```
+
+pub use crate::auth_service::{AuthService, LoginMethod, SignupMethod};
+pub use crate::core::oauth::store::OAuth2Provider;
+pub use crate::core::token::TokenPair;
+pub use crate::core::user::{User, UserId, UserStatus};
+pub use crate::error::{AuthError, AuthResult};
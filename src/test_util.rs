@@ -0,0 +1,101 @@
+//! Test helpers for applications built on cryptic.
+//!
+//! This module wires a deterministic, fully in-memory [`AuthService`] so downstream test suites
+//! don't have to repeat the same configuration boilerplate. It is only available with the
+//! `test-util` feature and must not be enabled in production builds.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use narangcia_cryptic::test_util::{seed_user, test_auth_service};
+//!
+//! let auth_service = test_auth_service();
+//! let user = seed_user(&auth_service, "alice@example.com", "Str0ng!Passw0rd").await?;
+//! ```
+
+use std::sync::Arc;
+
+use crate::auth_service::{AuthService, SignupMethod};
+use crate::core::hash::Argon2Params;
+use crate::core::oauth::mock::MockOAuth2Service;
+use crate::core::user::User;
+use crate::core::user::persistence::InMemoryUserRepo;
+use crate::core::vars::AuthServiceVariables;
+use crate::error::AuthError;
+
+/// The fixed secret used to sign tokens issued by [`test_auth_service`].
+pub const TEST_SECRET_KEY: &str = "cryptic-test-util-secret-key-not-for-production";
+
+/// Minimal Argon2 cost parameters keeping password hashing fast in tests.
+pub const TEST_ARGON2_PARAMS: Argon2Params = Argon2Params {
+    memory_kib: 1024,
+    iterations: 1,
+    parallelism: 1,
+};
+
+/// Returns the configuration used by [`test_auth_service`].
+///
+/// Tokens are signed with [`TEST_SECRET_KEY`], last for 15 minutes (access) and one day
+/// (refresh), and passwords are hashed with [`TEST_ARGON2_PARAMS`]. No password policy is set.
+pub fn test_vars() -> AuthServiceVariables {
+    AuthServiceVariables {
+        secret_key: TEST_SECRET_KEY.to_string(),
+        token_expiration: 15 * 60,
+        refresh_token_expiration: 24 * 3600,
+        argon2_params: TEST_ARGON2_PARAMS,
+        ..Default::default()
+    }
+}
+
+/// Creates an [`AuthService`] backed by an in-memory repository and an empty
+/// [`MockOAuth2Service`].
+///
+/// # Returns
+/// A deterministic `AuthService` configured with [`test_vars`].
+pub fn test_auth_service() -> AuthService {
+    test_auth_service_with_oauth(MockOAuth2Service::new())
+}
+
+/// Creates an [`AuthService`] like [`test_auth_service`], using `oauth` as the OAuth2 service.
+///
+/// # Arguments
+/// * `oauth` - The mock OAuth2 service, typically preloaded with authorization codes.
+///
+/// # Returns
+/// A deterministic `AuthService` configured with [`test_vars`].
+pub fn test_auth_service_with_oauth(oauth: MockOAuth2Service) -> AuthService {
+    AuthService::new(
+        Arc::new(test_vars()),
+        None,
+        Some(Box::new(InMemoryUserRepo::new())),
+        None,
+        Some(Box::new(oauth)),
+    )
+    .expect("the test configuration is valid")
+}
+
+/// Registers a user with credentials through `service`.
+///
+/// # Arguments
+/// * `service` - The service to register the user with.
+/// * `identifier` - The user's identifier (username, email, etc.).
+/// * `password` - The user's plain text password.
+///
+/// # Returns
+/// The created user.
+///
+/// # Errors
+/// Returns the error of [`AuthService::signup`], e.g. if the identifier is already taken.
+pub async fn seed_user(
+    service: &AuthService,
+    identifier: &str,
+    password: &str,
+) -> Result<User, AuthError> {
+    let (user, _) = service
+        .signup(SignupMethod::Credentials {
+            identifier: identifier.to_string(),
+            password: password.to_string(),
+        })
+        .await?;
+    Ok(user)
+}
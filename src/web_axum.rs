@@ -433,12 +433,13 @@ pub fn get_cryptic_axum_router(auth_service: Arc<AuthService>) -> Router {
 #[cfg(feature = "axum")]
 /// HTTP handler for the `/signup` endpoint.
 ///
-/// Accepts a JSON body with `username` and `password` fields, creates a new user,
-/// and returns the user ID and identifier on success. On error, returns a JSON error message.
+/// Accepts a JSON body with `username` and `password` fields (and an optional
+/// `challenge_token`, required when a challenge verifier is set), creates a new user, and
+/// returns the user ID and identifier on success. On error, returns a JSON error message.
 ///
 /// # Request JSON
 /// ```json
-/// { "username": "string", "password": "string" }
+/// { "username": "string", "password": "string", "challenge_token": "string" }
 /// ```
 ///
 /// # Response JSON
@@ -453,6 +454,8 @@ async fn signup_handler(
     struct SignupRequest {
         username: String,
         password: String,
+        #[serde(default)]
+        challenge_token: Option<String>,
     }
 
     let req: Result<SignupRequest, _> = serde_json::from_value(_body);
@@ -464,14 +467,20 @@ async fn signup_handler(
             );
             // Use the new unified signup method with credentials
             match _auth
-                .signup(crate::auth_service::SignupMethod::Credentials {
-                    identifier: signup.username,
-                    password: signup.password,
-                })
+                .signup_with_challenge(
+                    crate::auth_service::SignupMethod::Credentials {
+                        identifier: signup.username,
+                        password: signup.password,
+                    },
+                    signup.challenge_token.as_deref(),
+                )
                 .await
             {
                 Ok((user, _tokens)) => {
-                    log::info!("Signup successful for user_id: {id}", id = user.id);
+                    log::info!(
+                        "Signup successful for user: {id}",
+                        id = _auth.log_id(&user.id)
+                    );
                     serde_json::json!({
                         "id": user.id,
                         "identifier": user.credentials.as_ref().map(|c| &c.identifier).unwrap_or(&"".to_string())
@@ -508,12 +517,13 @@ async fn signup_handler(
 #[cfg(feature = "axum")]
 /// HTTP handler for the `/login` endpoint.
 ///
-/// Accepts a JSON body with `username` and `password` fields, authenticates the user,
-/// and returns user info and tokens on success. On error, returns a JSON error message.
+/// Accepts a JSON body with `username` and `password` fields (and an optional `remember_me`
+/// flag and `challenge_token`, required when a challenge verifier is set), authenticates the
+/// user, and returns user info and tokens on success. On error, returns a JSON error message.
 ///
 /// # Request JSON
 /// ```json
-/// { "username": "string", "password": "string" }
+/// { "username": "string", "password": "string", "remember_me": false, "challenge_token": "string" }
 /// ```
 ///
 /// # Response JSON
@@ -528,6 +538,10 @@ async fn login_handler(
     struct LoginRequest {
         username: String,
         password: String,
+        #[serde(default)]
+        remember_me: bool,
+        #[serde(default)]
+        challenge_token: Option<String>,
     }
 
     let req: Result<LoginRequest, _> = serde_json::from_value(_body);
@@ -538,14 +552,22 @@ async fn login_handler(
                 username = login.username
             );
             match _auth
-                .login(crate::auth_service::LoginMethod::Credentials {
-                    identifier: login.username,
-                    password: login.password,
-                })
+                .login_with_challenge_in_tenant(
+                    crate::auth_service::LoginMethod::Credentials {
+                        identifier: login.username,
+                        password: login.password,
+                    },
+                    login.challenge_token.as_deref(),
+                    None,
+                    login.remember_me,
+                )
                 .await
             {
                 Ok((user, tokens)) => {
-                    log::info!("Login successful for user_id: {id}", id = user.id);
+                    log::info!(
+                        "Login successful for user: {id}",
+                        id = _auth.log_id(&user.id)
+                    );
                     serde_json::json!({
                         "id": user.id,
                         "identifier": user.credentials.as_ref().map(|c| &c.identifier).unwrap_or(&"".to_string()),
@@ -745,12 +767,9 @@ async fn oauth_auth_handler(
 ) -> Response {
     log::info!("Received /oauth/{provider_str}/auth request with params: {params:?}");
     // Parse the provider from the path parameter
-    let provider = match provider_str.to_lowercase().as_str() {
-        "google" => crate::core::oauth::store::OAuth2Provider::Google,
-        "github" => crate::core::oauth::store::OAuth2Provider::GitHub,
-        "discord" => crate::core::oauth::store::OAuth2Provider::Discord,
-        "microsoft" => crate::core::oauth::store::OAuth2Provider::Microsoft,
-        _ => {
+    let provider = match provider_str.parse::<crate::core::oauth::store::OAuth2Provider>() {
+        Ok(provider) => provider,
+        Err(_) => {
             return (
                 axum::http::StatusCode::BAD_REQUEST,
                 serde_json::json!({
@@ -830,12 +849,9 @@ async fn oauth_callback_handler(
 ) -> Response {
     log::info!("Received /oauth/{provider_str}/callback request with params: {params:?}");
     // Parse the provider from the path parameter
-    let provider = match provider_str.to_lowercase().as_str() {
-        "google" => crate::core::oauth::store::OAuth2Provider::Google,
-        "github" => crate::core::oauth::store::OAuth2Provider::GitHub,
-        "discord" => crate::core::oauth::store::OAuth2Provider::Discord,
-        "microsoft" => crate::core::oauth::store::OAuth2Provider::Microsoft,
-        _ => {
+    let provider = match provider_str.parse::<crate::core::oauth::store::OAuth2Provider>() {
+        Ok(provider) => provider,
+        Err(_) => {
             return (
                 axum::http::StatusCode::BAD_REQUEST,
                 serde_json::json!({
@@ -903,8 +919,8 @@ async fn oauth_callback_handler(
     {
         Ok((user, tokens)) => {
             log::info!(
-                "OAuth2 callback login successful for user_id: {id}",
-                id = user.id
+                "OAuth2 callback login successful for user: {id}",
+                id = _auth.log_id(&user.id)
             );
 
             // Build the redirect URL with tokens in the fragment
@@ -913,7 +929,7 @@ async fn oauth_callback_handler(
                 frontend_uri,
                 url_encode(&tokens.access_token),
                 url_encode(&tokens.refresh_token),
-                url_encode(&user.id)
+                url_encode(user.id.as_str())
             );
 
             log::info!("Redirecting user to frontend: {redirect_url}");
@@ -972,12 +988,12 @@ async fn oauth_signup_handler(
                 provider = signup.provider
             );
             // Parse the provider
-            let provider = match signup.provider.to_lowercase().as_str() {
-                "google" => crate::core::oauth::store::OAuth2Provider::Google,
-                "github" => crate::core::oauth::store::OAuth2Provider::GitHub,
-                "discord" => crate::core::oauth::store::OAuth2Provider::Discord,
-                "microsoft" => crate::core::oauth::store::OAuth2Provider::Microsoft,
-                _ => {
+            let provider = match signup
+                .provider
+                .parse::<crate::core::oauth::store::OAuth2Provider>()
+            {
+                Ok(provider) => provider,
+                Err(_) => {
                     log::error!(
                         "Unsupported OAuth2 provider: {provider}",
                         provider = signup.provider
@@ -1003,7 +1019,10 @@ async fn oauth_signup_handler(
                 .await
             {
                 Ok((user, tokens)) => {
-                    log::info!("OAuth2 signup successful for user_id: {id}", id = user.id);
+                    log::info!(
+                        "OAuth2 signup successful for user: {id}",
+                        id = _auth.log_id(&user.id)
+                    );
                     serde_json::json!({
                         "id": user.id,
                         "access_token": tokens.access_token,
@@ -1073,12 +1092,12 @@ async fn oauth_login_handler(
                 provider = login.provider
             );
             // Parse the provider
-            let provider = match login.provider.to_lowercase().as_str() {
-                "google" => crate::core::oauth::store::OAuth2Provider::Google,
-                "github" => crate::core::oauth::store::OAuth2Provider::GitHub,
-                "discord" => crate::core::oauth::store::OAuth2Provider::Discord,
-                "microsoft" => crate::core::oauth::store::OAuth2Provider::Microsoft,
-                _ => {
+            let provider = match login
+                .provider
+                .parse::<crate::core::oauth::store::OAuth2Provider>()
+            {
+                Ok(provider) => provider,
+                Err(_) => {
                     log::error!(
                         "Unsupported OAuth2 provider: {provider}",
                         provider = login.provider
@@ -1104,7 +1123,10 @@ async fn oauth_login_handler(
                 .await
             {
                 Ok((user, tokens)) => {
-                    log::info!("OAuth2 login successful for user_id: {id}", id = user.id);
+                    log::info!(
+                        "OAuth2 login successful for user: {id}",
+                        id = _auth.log_id(&user.id)
+                    );
                     serde_json::json!({
                         "id": user.id,
                         "access_token": tokens.access_token,
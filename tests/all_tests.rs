@@ -58,6 +58,48 @@ fn test_argon2_hasher_hash_and_verify() {
     assert!(verify_fail.is_ok());
     assert!(!verify_fail.unwrap());
 }
+
+#[test]
+/// Tests that generated salts have the requested length and out-of-range lengths are rejected.
+fn test_generate_secure_salt_len() {
+    use narangcia_cryptic::core::hash::generate_secure_salt_len;
+
+    for len in [8, 16, 32, 48] {
+        let salt = generate_secure_salt_len(len).unwrap();
+        let mut buffer = [0u8; 64];
+        assert_eq!(salt.as_salt().decode_b64(&mut buffer).unwrap().len(), len);
+    }
+    assert!(generate_secure_salt_len(4).is_err());
+    assert!(generate_secure_salt_len(49).is_err());
+}
+
+#[test]
+/// Tests that hashes made with a non-default salt length encode that salt and still verify,
+/// including with a hasher configured with another salt length.
+fn test_argon2_hasher_with_salt_len() {
+    let long_salt = Argon2Hasher::with_params(TEST_ARGON2_PARAMS)
+        .unwrap()
+        .with_salt_len(32)
+        .unwrap();
+    let hash = long_salt.hash(b"password", None).unwrap();
+    let parsed = argon2::PasswordHash::new(&hash).unwrap();
+    let mut buffer = [0u8; 64];
+    assert_eq!(
+        parsed.salt.unwrap().decode_b64(&mut buffer).unwrap().len(),
+        32
+    );
+    assert!(long_salt.verify(b"password", &hash).unwrap());
+
+    let default_salt = Argon2Hasher::with_params(TEST_ARGON2_PARAMS).unwrap();
+    assert!(default_salt.verify(b"password", &hash).unwrap());
+    assert!(!default_salt.verify(b"wrong", &hash).unwrap());
+
+    assert!(Argon2Hasher::new().with_salt_len(7).is_err());
+    assert!(matches!(
+        narangcia_cryptic::core::password::Argon2PasswordManager::default().with_salt_len(64),
+        Err(narangcia_cryptic::AuthError::ConfigError(_))
+    ));
+}
 use narangcia_cryptic::AuthService;
 
 #[tokio::test]
@@ -211,10 +253,35 @@ async fn test_jwt_invalid_token() {
     assert!(result.is_err());
 }
 
+#[test]
+/// Tests that `JwtTokenService::try_new` rejects HMAC secrets shorter than 32 bytes.
+fn test_jwt_try_new_rejects_short_secret() {
+    let result = JwtTokenService::try_new("abcd", 60, 120);
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::ConfigError(_))
+    ));
+    assert!(JwtTokenService::try_new(&"k".repeat(32), 60, 120).is_ok());
+}
+
+#[test]
+/// Tests that `AuthService::new` refuses to build the default token service from a short secret.
+fn test_auth_service_new_rejects_short_secret() {
+    let vars = std::sync::Arc::new(AuthServiceVariables {
+        secret_key: "short".to_string(),
+        ..Default::default()
+    });
+    let result = AuthService::new(vars, None, None, None, None);
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::ConfigError(_))
+    ));
+}
+
 // --- User Persistence (InMemoryUserRepo) Integration Tests ---
 use narangcia_cryptic::core::credentials::{Credentials, PlainPassword};
-use narangcia_cryptic::core::user::User;
 use narangcia_cryptic::core::user::persistence::{InMemoryUserRepo, UserRepository};
+use narangcia_cryptic::core::user::{User, UserId};
 
 #[tokio::test]
 /// Tests adding and retrieving a user in `InMemoryUserRepo`.
@@ -235,7 +302,7 @@ async fn test_in_memory_user_repo_add_and_get_user() {
     .expect("Failed to create user");
     let added = repo.add_user(user.clone()).await.expect("Add user failed");
     assert_eq!(added.id, "id1");
-    let fetched = repo.get_user_by_id("id1").await;
+    let fetched = repo.get_user_by_id(&UserId::from("id1")).await;
     assert!(fetched.is_some());
     let fetched = fetched.unwrap();
     assert_eq!(fetched.id, "id1");
@@ -266,7 +333,7 @@ async fn test_in_memory_user_repo_update_user() {
     user.credentials.as_mut().unwrap().identifier = "user2_updated".to_string();
     let update_result = repo.update_user(&user).await;
     assert!(update_result.is_ok());
-    let fetched = repo.get_user_by_id("id2").await.unwrap();
+    let fetched = repo.get_user_by_id(&UserId::from("id2")).await.unwrap();
     assert_eq!(
         fetched.credentials.as_ref().unwrap().identifier,
         "user2_updated"
@@ -290,12 +357,12 @@ async fn test_in_memory_user_repo_delete_user() {
     .await
     .expect("Failed to create user");
     repo.add_user(user.clone()).await.expect("Add user failed");
-    let del_result = repo.delete_user("id3").await;
+    let del_result = repo.delete_user(&UserId::from("id3")).await;
     assert!(del_result.is_ok());
-    let fetched = repo.get_user_by_id("id3").await;
+    let fetched = repo.get_user_by_id(&UserId::from("id3")).await;
     assert!(fetched.is_none());
     // Deleting again should return UserNotFound
-    let del_again = repo.delete_user("id3").await;
+    let del_again = repo.delete_user(&UserId::from("id3")).await;
     assert!(del_again.is_err());
 }
 
@@ -321,6 +388,7 @@ fn test_auth_service_variables_custom() {
         secret_key: "mysecret".to_string(),
         token_expiration: 3600,
         refresh_token_expiration: 7200,
+        ..Default::default()
     };
     assert_eq!(vars.secret_key, "mysecret");
     assert_eq!(vars.token_expiration, 3600);
@@ -337,6 +405,7 @@ fn test_auth_service_variables_clone_and_debug() {
         secret_key: "clonekey".to_string(),
         token_expiration: 100,
         refresh_token_expiration: 200,
+        ..Default::default()
     };
     let cloned = vars.clone();
     assert_eq!(cloned.secret_key, "clonekey");
@@ -346,6 +415,132 @@ fn test_auth_service_variables_clone_and_debug() {
     assert!(debug_str.contains("clonekey"));
 }
 
+/// Serializes tests that mutate `CRYPTIC_*` environment variables.
+static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Runs `f` with the given environment variables set, removing them afterwards.
+fn with_env<T>(vars: &[(&str, &str)], f: impl FnOnce() -> T) -> T {
+    let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    // SAFETY: every test touching the process environment holds `ENV_LOCK`.
+    unsafe {
+        for (key, value) in vars {
+            std::env::set_var(key, value);
+        }
+    }
+    let result = f();
+    unsafe {
+        for (key, _) in vars {
+            std::env::remove_var(key);
+        }
+    }
+    result
+}
+
+#[test]
+/// Tests that `AuthServiceVariables::from_env` reads secrets, TTLs, Argon2 params and OAuth configs.
+fn test_auth_service_variables_from_env() {
+    let vars = with_env(
+        &[
+            (
+                "CRYPTIC_SECRET_KEY",
+                "an-env-provided-secret-that-is-long-enough",
+            ),
+            ("CRYPTIC_TOKEN_EXPIRATION", "900"),
+            ("CRYPTIC_REFRESH_EXPIRATION", "86400"),
+            ("CRYPTIC_ARGON2_MEMORY_KIB", "8192"),
+            ("CRYPTIC_ARGON2_ITERATIONS", "3"),
+            ("CRYPTIC_GITHUB_CLIENT_ID", "gh-id"),
+            ("CRYPTIC_GITHUB_CLIENT_SECRET", "gh-secret"),
+            (
+                "CRYPTIC_GITHUB_REDIRECT_URI",
+                "http://localhost/oauth/github/callback",
+            ),
+            (
+                "CRYPTIC_GITHUB_REDIRECT_FRONTEND_URI",
+                "http://localhost/done",
+            ),
+            ("CRYPTIC_GITHUB_SCOPES", "read:user, repo"),
+        ],
+        AuthServiceVariables::from_env,
+    )
+    .expect("from_env should succeed");
+    assert_eq!(
+        vars.secret_key,
+        "an-env-provided-secret-that-is-long-enough"
+    );
+    assert_eq!(vars.token_expiration, 900);
+    assert_eq!(vars.refresh_token_expiration, 86400);
+    assert_eq!(vars.argon2_params.memory_kib, 8192);
+    assert_eq!(vars.argon2_params.iterations, 3);
+    assert_eq!(vars.oauth_configs.len(), 1);
+    let github =
+        &vars.oauth_configs[&narangcia_cryptic::core::oauth::store::OAuth2Provider::GitHub];
+    assert_eq!(github.client_id, "gh-id");
+    assert_eq!(github.additional_scopes, vec!["read:user", "repo"]);
+}
+
+#[test]
+/// Tests that `AuthServiceVariables::from_env` rejects missing or invalid required values.
+fn test_auth_service_variables_from_env_missing_values() {
+    let missing_secret = with_env(&[], AuthServiceVariables::from_env);
+    assert!(matches!(
+        missing_secret,
+        Err(narangcia_cryptic::AuthError::ConfigError(_))
+    ));
+
+    let bad_ttl = with_env(
+        &[
+            (
+                "CRYPTIC_SECRET_KEY",
+                "an-env-provided-secret-that-is-long-enough",
+            ),
+            ("CRYPTIC_TOKEN_EXPIRATION", "soon"),
+        ],
+        AuthServiceVariables::from_env,
+    );
+    assert!(matches!(
+        bad_ttl,
+        Err(narangcia_cryptic::AuthError::ConfigError(_))
+    ));
+
+    let partial_oauth = with_env(
+        &[
+            (
+                "CRYPTIC_SECRET_KEY",
+                "an-env-provided-secret-that-is-long-enough",
+            ),
+            ("CRYPTIC_GOOGLE_CLIENT_ID", "google-id"),
+        ],
+        AuthServiceVariables::from_env,
+    );
+    assert!(matches!(
+        partial_oauth,
+        Err(narangcia_cryptic::AuthError::ConfigError(_))
+    ));
+}
+
+#[test]
+/// Tests that a placeholder secret is rejected in production mode but accepted otherwise.
+fn test_auth_service_variables_from_env_rejects_placeholder_in_production() {
+    let production = with_env(
+        &[
+            ("CRYPTIC_SECRET_KEY", "changeme"),
+            ("CRYPTIC_ENV", "production"),
+        ],
+        AuthServiceVariables::from_env,
+    );
+    assert!(matches!(
+        production,
+        Err(narangcia_cryptic::AuthError::ConfigError(_))
+    ));
+
+    let development = with_env(
+        &[("CRYPTIC_SECRET_KEY", "changeme")],
+        AuthServiceVariables::from_env,
+    );
+    assert!(development.is_ok());
+}
+
 // --- Credentials and PlainPassword Integration Tests ---
 use narangcia_cryptic::core::password::Argon2PasswordManager;
 
@@ -415,3 +610,8154 @@ fn test_plain_password_zeroize_on_drop() {
     // We can't guarantee the memory is zeroized (Rust doesn't let us read freed memory),
     // but this test ensures the ZeroizeOnDrop implementation is present and compiles.
 }
+
+// --- OAuth2Provider String Conversion Tests ---
+use narangcia_cryptic::core::oauth::store::OAuth2Provider;
+
+#[test]
+/// Tests that every provider round-trips through `Display`/`FromStr` and serde.
+fn test_oauth2_provider_string_round_trip() {
+    assert_eq!(OAuth2Provider::all().len(), 4);
+    for &provider in OAuth2Provider::all() {
+        let as_string = provider.to_string();
+        assert_eq!(as_string, provider.as_str());
+        assert_eq!(as_string.parse::<OAuth2Provider>().unwrap(), provider);
+        assert_eq!(
+            as_string.to_uppercase().parse::<OAuth2Provider>().unwrap(),
+            provider
+        );
+
+        let json = serde_json::to_string(&provider).unwrap();
+        assert_eq!(json, format!("\"{as_string}\""));
+        assert_eq!(
+            serde_json::from_str::<OAuth2Provider>(&json).unwrap(),
+            provider
+        );
+    }
+}
+
+#[test]
+/// Tests that parsing an unknown provider fails with `InvalidInput`.
+fn test_oauth2_provider_from_str_unknown() {
+    assert!(matches!(
+        "myspace".parse::<OAuth2Provider>(),
+        Err(narangcia_cryptic::AuthError::InvalidInput(_))
+    ));
+}
+
+// --- Refresh Token Validation Tests ---
+
+/// Secret long enough to satisfy `JwtTokenService::try_new`.
+const TEST_JWT_SECRET: &str = "integration-test-secret-0123456789abcdef";
+
+/// Encodes arbitrary claims with `TEST_JWT_SECRET` using HS256.
+fn encode_test_token(claims: &serde_json::Value) -> String {
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256),
+        claims,
+        &jsonwebtoken::EncodingKey::from_secret(TEST_JWT_SECRET.as_bytes()),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+/// Tests that a valid refresh token is accepted without issuing new tokens.
+async fn test_validate_refresh_token_valid() {
+    let jwt_service = JwtTokenService::new(TEST_JWT_SECRET, 60, 120);
+    let pair = jwt_service
+        .generate_token_pair("session_user")
+        .await
+        .unwrap();
+
+    let claims = jwt_service
+        .validate_refresh_token(&pair.refresh_token)
+        .await
+        .expect("Refresh token should be valid");
+    assert_eq!(claims.get_subject(), "session_user");
+    assert!(claims.get_expiration() > 0);
+}
+
+#[tokio::test]
+/// Tests that an expired refresh token is rejected with `TokenExpired`.
+async fn test_validate_refresh_token_expired() {
+    let jwt_service = JwtTokenService::new(TEST_JWT_SECRET, 60, 120);
+    let now = chrono::Utc::now().timestamp();
+    let expired = encode_test_token(&serde_json::json!({
+        "sub": "session_user",
+        "exp": now - 3600,
+        "iat": now - 7200,
+        "token_type": "refresh",
+    }));
+
+    let result = jwt_service.validate_refresh_token(&expired).await;
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::TokenExpired)
+    ));
+}
+
+#[tokio::test]
+/// Tests that an access token is rejected when validated as a refresh token.
+async fn test_validate_refresh_token_wrong_type() {
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    let pair = auth_service
+        .get_tokens("session_user".to_string())
+        .await
+        .unwrap();
+
+    let result = auth_service
+        .validate_refresh_token(&pair.access_token)
+        .await;
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+}
+
+// --- Composite Password Manager Migration Tests ---
+use narangcia_cryptic::core::hash::Argon2Params;
+use narangcia_cryptic::core::password::{CompositePasswordManager, SecurePasswordManager};
+
+/// Cheap Argon2 parameters to keep password hashing tests fast.
+const TEST_ARGON2_PARAMS: Argon2Params = Argon2Params {
+    memory_kib: 1024,
+    iterations: 1,
+    parallelism: 1,
+};
+
+#[tokio::test]
+/// Tests that the Argon2 password manager flags hashes produced with different parameters.
+async fn test_argon2_manager_needs_rehash_on_param_change() {
+    let cheap = Argon2PasswordManager::with_params(TEST_ARGON2_PARAMS).unwrap();
+    let hash = cheap.hash_password("password").await.unwrap();
+    assert!(cheap.identify(&hash));
+    assert!(!cheap.needs_rehash(&hash));
+
+    let stronger = Argon2PasswordManager::with_params(Argon2Params {
+        iterations: 2,
+        ..TEST_ARGON2_PARAMS
+    })
+    .unwrap();
+    assert!(stronger.needs_rehash(&hash));
+    assert!(!stronger.identify("$2b$04$invalidbcrypthash"));
+}
+
+#[tokio::test]
+/// Tests that the composite manager rejects hashes that no configured manager recognizes.
+async fn test_composite_manager_unrecognized_hash() {
+    let manager = CompositePasswordManager::new(
+        Box::new(Argon2PasswordManager::with_params(TEST_ARGON2_PARAMS).unwrap()),
+        Vec::new(),
+    );
+    let result = manager.verify_password("password", "plaintext").await;
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::VerificationError(_))
+    ));
+    assert!(manager.needs_rehash("plaintext"));
+}
+
+#[cfg(feature = "bcrypt")]
+#[tokio::test]
+/// Tests that a bcrypt-hashed user can log in through the composite manager and that
+/// the stored hash is transparently upgraded to Argon2id.
+async fn test_login_upgrades_bcrypt_hash() {
+    use narangcia_cryptic::core::password::BcryptPasswordManager;
+
+    let bcrypt_manager = BcryptPasswordManager::with_cost(4).unwrap();
+    let legacy_hash = bcrypt_manager.hash_password("legacy_pass").await.unwrap();
+    assert!(bcrypt_manager.identify(&legacy_hash));
+
+    let repo = InMemoryUserRepo::new();
+    repo.add_user(User::new(
+        "legacy_user".to_string(),
+        Credentials::new(
+            "legacy_user".to_string(),
+            "legacy@example.com".to_string(),
+            legacy_hash.clone(),
+        ),
+    ))
+    .await
+    .unwrap();
+
+    let composite = CompositePasswordManager::new(
+        Box::new(Argon2PasswordManager::with_params(TEST_ARGON2_PARAMS).unwrap()),
+        vec![Box::new(bcrypt_manager)],
+    );
+    assert!(composite.needs_rehash(&legacy_hash));
+
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            ..Default::default()
+        }),
+        Some(Box::new(composite)),
+        Some(Box::new(repo)),
+        None,
+        None,
+    )
+    .unwrap();
+
+    let login = || narangcia_cryptic::auth_service::LoginMethod::Credentials {
+        identifier: "legacy@example.com".to_string(),
+        password: "legacy_pass".to_string(),
+    };
+    auth_service.login(login()).await.unwrap();
+
+    let upgraded = auth_service
+        .persistent_users_manager
+        .get_user_by_identifier("legacy@example.com")
+        .await
+        .unwrap();
+    let upgraded_hash = upgraded.credentials.unwrap().password_hash;
+    assert!(upgraded_hash.starts_with("$argon2id$"));
+    assert!(!auth_service.password_manager.needs_rehash(&upgraded_hash));
+
+    // The upgraded hash still verifies on the next login
+    auth_service.login(login()).await.unwrap();
+}
+
+// --- OAuth2 Configuration Validation Tests ---
+#[cfg(feature = "oauth")]
+use narangcia_cryptic::core::oauth::manager::OAuth2Manager;
+use narangcia_cryptic::core::oauth::store::OAuth2Config;
+
+/// Builds an OAuth2 configuration with the given secret and callback URI.
+fn test_oauth_config(client_secret: &str, redirect_callback_uri: &str) -> OAuth2Config {
+    OAuth2Config {
+        app_name: "cryptic-tests".to_string(),
+        client_id: "client-id".to_string(),
+        client_secret: client_secret.to_string(),
+        redirect_callback_uri: redirect_callback_uri.to_string(),
+        redirect_frontend_uri: "https://app.example.com/auth".to_string(),
+        default_scopes: None,
+        additional_scopes: Vec::new(),
+        require_verified_email: false,
+        field_map: std::collections::HashMap::new(),
+    }
+}
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that `validate_configs` reports only the broken provider and that
+/// `AuthService::health_check` surfaces it.
+async fn test_oauth_validate_configs_reports_broken_provider() {
+    let mut configs = std::collections::HashMap::new();
+    configs.insert(
+        OAuth2Provider::Google,
+        test_oauth_config("secret", "https://api.example.com/oauth/google/callback"),
+    );
+    configs.insert(OAuth2Provider::GitHub, test_oauth_config("", "not a url"));
+
+    let problems = OAuth2Manager::new(configs.clone())
+        .validate_configs()
+        .unwrap_err();
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].0, OAuth2Provider::GitHub);
+    assert!(matches!(
+        problems[0].1,
+        narangcia_cryptic::AuthError::ConfigError(_)
+    ));
+
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            oauth_configs: configs,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    let result = auth_service.health_check().await;
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::ConfigError(msg)) if msg.contains("github")
+    ));
+}
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that a service with valid OAuth2 configurations passes the health check.
+async fn test_health_check_ok() {
+    let mut configs = std::collections::HashMap::new();
+    configs.insert(
+        OAuth2Provider::Discord,
+        test_oauth_config("secret", "https://api.example.com/oauth/discord/callback"),
+    );
+    assert!(
+        OAuth2Manager::new(configs.clone())
+            .validate_configs()
+            .is_ok()
+    );
+
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            oauth_configs: configs,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    assert!(auth_service.health_check().await.is_ok());
+}
+
+// --- JWT Header (kid, typ) Tests ---
+#[tokio::test]
+/// Tests that generated tokens carry the configured `kid` and `typ` header values.
+async fn test_jwt_header_contains_kid() {
+    let service = JwtTokenService::new(TEST_JWT_SECRET, 60, 120).with_kid("key-2024");
+    let pair = service.generate_token_pair("header_user").await.unwrap();
+
+    for token in [&pair.access_token, &pair.refresh_token] {
+        let header = jsonwebtoken::decode_header(token).unwrap();
+        assert_eq!(header.kid.as_deref(), Some("key-2024"));
+        assert_eq!(header.typ.as_deref(), Some("JWT"));
+    }
+    assert!(
+        service
+            .validate_access_token(&pair.access_token)
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+/// Tests that the `kid` header selects the verification key and unknown key IDs are rejected.
+async fn test_jwt_kid_selects_verification_key() {
+    const OLD_SECRET: &str = "previous-secret-key-rotated-out-of-signing";
+    let old_service = JwtTokenService::new(OLD_SECRET, 60, 120).with_kid("old");
+    let old_pair = old_service
+        .generate_token_pair("rotated_user")
+        .await
+        .unwrap();
+
+    let new_service = JwtTokenService::new(TEST_JWT_SECRET, 60, 120)
+        .with_kid("new")
+        .with_verification_key("old", OLD_SECRET);
+    let claims = new_service
+        .validate_access_token(&old_pair.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims.get_subject(), "rotated_user");
+
+    let unknown = JwtTokenService::new(OLD_SECRET, 60, 120).with_kid("unknown");
+    let unknown_pair = unknown.generate_token_pair("rotated_user").await.unwrap();
+    let result = new_service
+        .validate_access_token(&unknown_pair.access_token)
+        .await;
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+}
+
+// --- AuthService::set_password Tests ---
+use narangcia_cryptic::core::oauth::store::OAuth2UserInfo;
+use narangcia_cryptic::core::policy::PasswordPolicy;
+
+/// Builds an [`AuthService`] whose repository contains a single OAuth-only user.
+async fn auth_service_with_oauth_user(user_id: &str, email: &str) -> AuthService {
+    let repo = InMemoryUserRepo::new();
+    repo.add_user(User::from_oauth(
+        user_id.to_string(),
+        OAuth2UserInfo {
+            user_id: String::new(),
+            provider: OAuth2Provider::GitHub,
+            provider_user_id: "gh-42".to_string(),
+            email: Some(email.to_string()),
+            name: None,
+            avatar_url: None,
+            verified_email: Some(true),
+            locale: None,
+            updated_at: chrono::Utc::now().naive_utc(),
+            raw_data: None,
+            granted_scopes: Vec::new(),
+        },
+    ))
+    .await
+    .unwrap();
+
+    AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            password_policy: Some(PasswordPolicy::default()),
+            ..Default::default()
+        }),
+        None,
+        Some(Box::new(repo)),
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+/// Tests that an OAuth-only user can add a password and then log in with their email.
+async fn test_set_password_for_oauth_only_user() {
+    let auth_service = auth_service_with_oauth_user("oauth_user", "social@example.com").await;
+
+    auth_service
+        .set_password("oauth_user", "Str0ng!Passw0rd", false)
+        .await
+        .unwrap();
+
+    let (user, _) = auth_service
+        .login(narangcia_cryptic::auth_service::LoginMethod::Credentials {
+            identifier: "social@example.com".to_string(),
+            password: "Str0ng!Passw0rd".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(user.id, "oauth_user");
+    assert!(user.has_oauth_account(OAuth2Provider::GitHub));
+}
+
+#[tokio::test]
+/// Tests that `set_password` enforces the policy and refuses to replace existing
+/// credentials unless `overwrite` is set.
+async fn test_set_password_policy_and_overwrite() {
+    let auth_service = auth_service_with_oauth_user("oauth_user", "social@example.com").await;
+
+    let weak = auth_service.set_password("oauth_user", "weak", false).await;
+    assert!(matches!(
+        weak,
+        Err(narangcia_cryptic::AuthError::InvalidInput(_))
+    ));
+
+    auth_service
+        .set_password("oauth_user", "Str0ng!Passw0rd", false)
+        .await
+        .unwrap();
+    let again = auth_service
+        .set_password("oauth_user", "An0ther!Passw0rd", false)
+        .await;
+    assert!(matches!(
+        again,
+        Err(narangcia_cryptic::AuthError::InvalidInput(_))
+    ));
+
+    auth_service
+        .set_password("oauth_user", "An0ther!Passw0rd", true)
+        .await
+        .unwrap();
+    let login = auth_service
+        .login(narangcia_cryptic::auth_service::LoginMethod::Credentials {
+            identifier: "social@example.com".to_string(),
+            password: "An0ther!Passw0rd".to_string(),
+        })
+        .await;
+    assert!(login.is_ok());
+
+    let missing = auth_service
+        .set_password("missing_user", "Str0ng!Passw0rd", false)
+        .await;
+    assert!(matches!(
+        missing,
+        Err(narangcia_cryptic::AuthError::UserNotFound)
+    ));
+}
+
+#[tokio::test]
+/// Tests that changing a password revokes the refresh tokens of every prior session.
+async fn test_change_password_revokes_prior_sessions() {
+    let auth_service = tenant_test_auth_service();
+    let (signup, login) = credentials_methods("rotate@example.com", "old-password");
+    let (_, first) = auth_service.signup(signup).await.unwrap();
+    let (_, second) = auth_service.login(login).await.unwrap();
+
+    let wrong = auth_service
+        .change_password(&first.access_token, "not-the-password", "new-password")
+        .await;
+    assert!(matches!(
+        wrong,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+
+    auth_service
+        .change_password(&first.access_token, "old-password", "new-password")
+        .await
+        .unwrap();
+    for tokens in [&first, &second] {
+        assert!(
+            auth_service
+                .refresh_access_token(&tokens.refresh_token)
+                .await
+                .is_err()
+        );
+    }
+
+    let (_, new_login) = credentials_methods("rotate@example.com", "new-password");
+    let (_, fresh) = auth_service.login(new_login).await.unwrap();
+    assert!(
+        auth_service
+            .refresh_access_token(&fresh.refresh_token)
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+/// Tests that `keep_session_on_password_change` keeps only the caller's session alive.
+async fn test_change_password_keeps_current_session() {
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            keep_session_on_password_change: true,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    let (signup, login) = credentials_methods("keep@example.com", "old-password");
+    let (_, current) = auth_service.signup(signup).await.unwrap();
+    let (_, other) = auth_service.login(login).await.unwrap();
+
+    auth_service
+        .change_password(&current.access_token, "old-password", "new-password")
+        .await
+        .unwrap();
+    assert!(
+        auth_service
+            .validate_access_token(&current.access_token)
+            .await
+            .is_ok()
+    );
+    assert!(
+        auth_service
+            .refresh_access_token(&current.refresh_token)
+            .await
+            .is_ok()
+    );
+    assert!(
+        auth_service
+            .refresh_access_token(&other.refresh_token)
+            .await
+            .is_err()
+    );
+}
+
+/// A session store relying on the default `revoke_all_for_user_except`.
+struct AllOrNothingSessionStore(narangcia_cryptic::core::token::session::InMemorySessionStore);
+
+#[async_trait::async_trait]
+impl narangcia_cryptic::core::token::session::SessionStore for AllOrNothingSessionStore {
+    async fn record(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        expires_at: usize,
+    ) -> Result<(), narangcia_cryptic::AuthError> {
+        self.0.record(user_id, session_id, expires_at).await
+    }
+
+    async fn is_revoked(
+        &self,
+        user_id: &str,
+        session_id: Option<&str>,
+        issued_at: Option<usize>,
+    ) -> Result<bool, narangcia_cryptic::AuthError> {
+        self.0.is_revoked(user_id, session_id, issued_at).await
+    }
+
+    async fn revoke_all_for_user(
+        &self,
+        user_id: &str,
+    ) -> Result<u32, narangcia_cryptic::AuthError> {
+        self.0.revoke_all_for_user(user_id).await
+    }
+}
+
+#[tokio::test]
+/// Tests that `change_password` revokes every session when the store cannot keep one.
+async fn test_change_password_store_without_keep_revokes_all() {
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            keep_session_on_password_change: true,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap()
+    .with_session_store(Box::new(AllOrNothingSessionStore(
+        narangcia_cryptic::core::token::session::InMemorySessionStore::default(),
+    )));
+    let (signup, _) = credentials_methods("all-or-nothing@example.com", "old-password");
+    let (_, current) = auth_service.signup(signup).await.unwrap();
+
+    auth_service
+        .change_password(&current.access_token, "old-password", "new-password")
+        .await
+        .unwrap();
+    assert!(
+        auth_service
+            .refresh_access_token(&current.refresh_token)
+            .await
+            .is_err()
+    );
+}
+
+// --- Secure Random Generation Tests ---
+use narangcia_cryptic::core::rand::{secure_random_bytes, secure_random_string};
+
+#[test]
+/// Tests that `secure_random_bytes` returns the requested number of distinct random bytes.
+fn test_secure_random_bytes_length() {
+    assert!(secure_random_bytes(0).is_empty());
+    let first = secure_random_bytes(32);
+    let second = secure_random_bytes(32);
+    assert_eq!(first.len(), 32);
+    assert_ne!(first, second);
+}
+
+#[test]
+/// Tests that `secure_random_string` produces unpadded URL-safe base64 of the expected length.
+fn test_secure_random_string_length_and_charset() {
+    for (bytes, expected_len) in [(1, 2), (16, 22), (32, 43), (48, 64)] {
+        let token = secure_random_string(bytes);
+        assert_eq!(token.len(), expected_len);
+        assert!(
+            token
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        );
+    }
+    assert_ne!(secure_random_string(32), secure_random_string(32));
+}
+
+// --- Multi-Tenant Isolation Tests ---
+/// Builds an [`AuthService`] with a valid JWT secret and cheap hashing parameters.
+fn tenant_test_auth_service() -> AuthService {
+    AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+/// Builds credentials-based signup and login methods for the given identifier and password.
+fn credentials_methods(
+    identifier: &str,
+    password: &str,
+) -> (
+    narangcia_cryptic::SignupMethod,
+    narangcia_cryptic::LoginMethod,
+) {
+    (
+        narangcia_cryptic::SignupMethod::Credentials {
+            identifier: identifier.to_string(),
+            password: password.to_string(),
+        },
+        narangcia_cryptic::LoginMethod::Credentials {
+            identifier: identifier.to_string(),
+            password: password.to_string(),
+        },
+    )
+}
+
+#[tokio::test]
+/// Tests that the same identifier can be registered in two tenants, and that each tenant
+/// only sees its own user.
+async fn test_tenants_isolate_identifiers() {
+    let auth_service = tenant_test_auth_service();
+    let acme = auth_service.for_tenant("acme");
+    let globex = auth_service.for_tenant("globex");
+
+    let (signup, _) = credentials_methods("shared@example.com", "acme_pass");
+    let (acme_user, _) = acme.signup(signup).await.unwrap();
+    let (signup, _) = credentials_methods("shared@example.com", "globex_pass");
+    let (globex_user, _) = globex.signup(signup).await.unwrap();
+    assert_ne!(acme_user.id, globex_user.id);
+    assert_eq!(acme_user.tenant_id.as_deref(), Some("acme"));
+
+    // A duplicate within the same tenant is rejected
+    let (signup, _) = credentials_methods("shared@example.com", "other_pass");
+    assert!(acme.signup(signup).await.is_err());
+
+    // Each tenant logs in its own user, and rejects the other tenant's password
+    let (_, login) = credentials_methods("shared@example.com", "acme_pass");
+    let (user, _) = acme.login(login.clone()).await.unwrap();
+    assert_eq!(user.id, acme_user.id);
+    assert!(matches!(
+        globex.login(login.clone()).await,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+
+    // Users of a tenant are invisible to the unscoped service
+    assert!(matches!(
+        auth_service.login(login).await,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+}
+
+#[tokio::test]
+/// Tests that tokens embed the tenant claim, that it survives a refresh, and that the
+/// unscoped service and other tenants reject tokens issued for a tenant.
+async fn test_tenant_claim_in_tokens() {
+    let auth_service = tenant_test_auth_service();
+    let acme = auth_service.for_tenant("acme");
+    let globex = auth_service.for_tenant("globex");
+
+    let (signup, _) = credentials_methods("claims@example.com", "acme_pass");
+    let (user, tokens) = acme.signup(signup).await.unwrap();
+
+    let claims = acme
+        .validate_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims.get_tenant_id(), Some("acme"));
+    let (authenticated, _) = acme
+        .authenticate_request(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(authenticated.id, user.id);
+    assert!(matches!(
+        globex.validate_access_token(&tokens.access_token).await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    assert!(matches!(
+        auth_service
+            .validate_access_token(&tokens.access_token)
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    assert!(matches!(
+        auth_service
+            .authenticate_request(&tokens.access_token)
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+
+    // Unscoped lookups never match users of a tenant
+    assert!(
+        auth_service
+            .persistent_users_manager
+            .get_user_by_identifier("claims@example.com")
+            .await
+            .is_none()
+    );
+
+    let refreshed = auth_service
+        .refresh_access_token(&tokens.refresh_token)
+        .await
+        .unwrap();
+    let claims = acme
+        .validate_access_token(&refreshed.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims.get_tenant_id(), Some("acme"));
+
+    // Tokens issued without a tenant carry no tenant claim
+    let untenanted = auth_service
+        .get_tokens("plain_user".to_string())
+        .await
+        .unwrap();
+    assert!(
+        auth_service
+            .validate_access_token(&untenanted.access_token)
+            .await
+            .unwrap()
+            .get_tenant_id()
+            .is_none()
+    );
+    assert!(
+        acme.validate_access_token(&untenanted.access_token)
+            .await
+            .is_err()
+    );
+
+    // Tokens issued by user ID carry the tenant of the user
+    let by_id = auth_service.get_tokens(user.id.clone()).await.unwrap();
+    assert!(matches!(
+        auth_service
+            .validate_access_token(&by_id.access_token)
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    let claims = acme
+        .validate_access_token(&by_id.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims.get_tenant_id(), Some("acme"));
+}
+
+// --- Token Introspection Tests ---
+#[tokio::test]
+/// Tests that introspecting a valid access token reports it as active with its claims.
+async fn test_introspect_active_token() {
+    let auth_service = tenant_test_auth_service();
+    let tokens = auth_service
+        .get_tokens("introspected_user".to_string())
+        .await
+        .unwrap();
+
+    let result = auth_service.introspect(&tokens.access_token).await;
+    assert!(result.active);
+    assert_eq!(result.sub.as_deref(), Some("introspected_user"));
+    assert_eq!(result.token_type.as_deref(), Some("access"));
+    assert!(result.exp.unwrap() > result.iat.unwrap());
+}
+
+#[tokio::test]
+/// Tests that invalid and expired tokens are reported as inactive instead of failing.
+async fn test_introspect_inactive_tokens() {
+    let auth_service = tenant_test_auth_service();
+
+    let garbage = auth_service.introspect("not-a-token").await;
+    assert_eq!(
+        garbage,
+        narangcia_cryptic::core::token::IntrospectionResult::inactive()
+    );
+
+    let now = chrono::Utc::now().timestamp();
+    let expired = encode_test_token(&serde_json::json!({
+        "sub": "expired_user",
+        "exp": now - 3600,
+        "iat": now - 7200,
+        "token_type": "access",
+    }));
+    let result = auth_service.introspect(&expired).await;
+    assert!(!result.active);
+    assert!(result.sub.is_none());
+    assert_eq!(
+        serde_json::to_value(&result).unwrap(),
+        serde_json::json!({ "active": false })
+    );
+}
+
+// --- Last Login Tracking Tests ---
+#[tokio::test]
+/// Tests that `last_login_at` is recorded and advances on every successful login.
+async fn test_last_login_at_advances() {
+    let auth_service = tenant_test_auth_service();
+    let (signup, login) = credentials_methods("tracked@example.com", "tracked_pass");
+    let (user, _) = auth_service.signup(signup).await.unwrap();
+    assert!(user.last_login_at.is_none());
+
+    let (first, _) = auth_service.login(login.clone()).await.unwrap();
+    let first_login = first.last_login_at.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    let (second, _) = auth_service.login(login).await.unwrap();
+    let second_login = second.last_login_at.unwrap();
+    assert!(second_login > first_login);
+
+    let stored = auth_service
+        .persistent_users_manager
+        .get_user_by_id(&user.id)
+        .await
+        .unwrap();
+    assert_eq!(stored.last_login_at, Some(second_login));
+}
+
+#[tokio::test]
+/// Tests that disabling last login tracking skips the timestamp write.
+async fn test_last_login_tracking_disabled() {
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            argon2_params: TEST_ARGON2_PARAMS,
+            disable_last_login_tracking: true,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    let (signup, login) = credentials_methods("untracked@example.com", "untracked_pass");
+    let (user, _) = auth_service.signup(signup).await.unwrap();
+    let (logged_in, _) = auth_service.login(login).await.unwrap();
+    assert!(logged_in.last_login_at.is_none());
+    assert!(
+        auth_service
+            .persistent_users_manager
+            .get_user_by_id(&user.id)
+            .await
+            .unwrap()
+            .last_login_at
+            .is_none()
+    );
+}
+
+// --- Identifier Reservation Tests ---
+#[tokio::test]
+/// Tests that an identifier cannot be reserved twice and is released when the guard is dropped.
+async fn test_reserve_identifier_exclusive_and_released() {
+    let repo = InMemoryUserRepo::new();
+
+    let guard = repo
+        .reserve_identifier("taken@example.com", None)
+        .await
+        .unwrap();
+    assert!(matches!(
+        repo.reserve_identifier("taken@example.com", None).await,
+        Err(narangcia_cryptic::AuthError::UserAlreadyExists)
+    ));
+    // Reservations are scoped to a tenant
+    let other_tenant = repo
+        .reserve_identifier("taken@example.com", Some("acme"))
+        .await
+        .unwrap();
+
+    drop(guard);
+    drop(other_tenant);
+    let guard = repo
+        .reserve_identifier("taken@example.com", None)
+        .await
+        .unwrap();
+    guard.commit();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+/// Tests that concurrent signups for the same identifier result in exactly one user.
+async fn test_concurrent_signups_single_winner() {
+    let auth_service = std::sync::Arc::new(tenant_test_auth_service());
+
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            let auth_service = std::sync::Arc::clone(&auth_service);
+            tokio::spawn(async move {
+                let (signup, _) = credentials_methods("race@example.com", &format!("pass_{i}"));
+                auth_service.signup(signup).await
+            })
+        })
+        .collect();
+
+    let mut successes = 0;
+    for handle in handles {
+        match handle.await.unwrap() {
+            Ok(_) => successes += 1,
+            Err(e) => assert!(matches!(e, narangcia_cryptic::AuthError::SignupError(_))),
+        }
+    }
+    assert_eq!(successes, 1);
+
+    // The losing signups released their reservations, so a new identifier still works
+    let (signup, _) = credentials_methods("after_race@example.com", "pass");
+    assert!(auth_service.signup(signup).await.is_ok());
+}
+
+// --- Available OAuth2 Providers Tests ---
+#[test]
+/// Tests that only configured providers are reported as available.
+fn test_available_oauth_providers_partially_configured() {
+    let mut configs = std::collections::HashMap::new();
+    configs.insert(
+        OAuth2Provider::GitHub,
+        test_oauth_config("secret", "https://api.example.com/oauth/github/callback"),
+    );
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            oauth_configs: configs,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let providers = auth_service.available_oauth_providers();
+    assert_eq!(providers.len(), OAuth2Provider::all().len());
+    for info in &providers {
+        assert_eq!(info.available, info.provider == OAuth2Provider::GitHub);
+        assert_eq!(info.display_name, info.provider.display_name());
+        assert!(!info.default_scopes.is_empty());
+    }
+    let github = providers
+        .iter()
+        .find(|info| info.provider == OAuth2Provider::GitHub)
+        .unwrap();
+    assert_eq!(github.default_scopes, vec!["user:email".to_string()]);
+}
+
+// --- Single-Use Token Tests ---
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+/// Tests that a single-use token submitted twice concurrently is honored exactly once.
+async fn test_one_time_token_consumed_once_concurrently() {
+    let auth_service = std::sync::Arc::new(tenant_test_auth_service());
+    let expires_at = (chrono::Utc::now().timestamp() + 600) as usize;
+
+    let submit = || {
+        let auth_service = std::sync::Arc::clone(&auth_service);
+        tokio::spawn(async move {
+            auth_service
+                .consume_one_time_token("reset-jti-1", expires_at)
+                .await
+        })
+    };
+    let (first, second) = tokio::join!(submit(), submit());
+    let results = [first.unwrap(), second.unwrap()];
+
+    assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+    assert!(
+        results
+            .iter()
+            .any(|r| matches!(r, Err(narangcia_cryptic::AuthError::InvalidToken(_))))
+    );
+
+    // Other tokens are unaffected
+    assert!(
+        auth_service
+            .consume_one_time_token("reset-jti-2", expires_at)
+            .await
+            .is_ok()
+    );
+}
+
+// --- Mock OAuth2 Service Tests ---
+#[cfg(feature = "test-util")]
+use narangcia_cryptic::core::oauth::OAuth2Service;
+#[cfg(feature = "test-util")]
+use narangcia_cryptic::core::oauth::mock::{MockOAuth2Operation, MockOAuth2Service};
+
+#[cfg(feature = "test-util")]
+fn mock_oauth_user_info(provider_user_id: &str, email: &str) -> OAuth2UserInfo {
+    OAuth2UserInfo {
+        user_id: String::new(),
+        provider: OAuth2Provider::GitHub,
+        provider_user_id: provider_user_id.to_string(),
+        email: Some(email.to_string()),
+        name: Some("Mock User".to_string()),
+        avatar_url: None,
+        verified_email: Some(true),
+        locale: None,
+        updated_at: chrono::Utc::now().naive_utc(),
+        raw_data: None,
+        granted_scopes: Vec::new(),
+    }
+}
+
+#[cfg(feature = "test-util")]
+fn auth_service_with_mock_oauth(mock: MockOAuth2Service) -> AuthService {
+    AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        Some(Box::new(mock)),
+    )
+    .unwrap()
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests an OAuth2 signup followed by a login against the mock provider.
+async fn test_mock_oauth_signup_then_login() {
+    let mock = MockOAuth2Service::new()
+        .with_user(
+            OAuth2Provider::GitHub,
+            "code-1",
+            mock_oauth_user_info("gh-1", "mock@example.com"),
+        )
+        .with_user(
+            OAuth2Provider::GitHub,
+            "code-2",
+            mock_oauth_user_info("gh-1", "mock@example.com"),
+        );
+    let auth_service = auth_service_with_mock_oauth(mock);
+
+    let (user, _) = auth_service
+        .signup(narangcia_cryptic::auth_service::SignupMethod::OAuth2 {
+            provider: OAuth2Provider::GitHub,
+            code: "code-1".to_string(),
+            state: "state".to_string(),
+        })
+        .await
+        .unwrap();
+    let (logged_in, tokens) = auth_service
+        .login(narangcia_cryptic::auth_service::LoginMethod::OAuth2 {
+            provider: OAuth2Provider::GitHub,
+            code: "code-2".to_string(),
+            state: "state".to_string(),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(logged_in.id, user.id);
+    assert_eq!(
+        auth_service
+            .validate_access_token(&tokens.access_token)
+            .await
+            .unwrap()
+            .get_subject(),
+        user.id
+    );
+
+    // Unknown codes are rejected
+    let result = auth_service
+        .login(narangcia_cryptic::auth_service::LoginMethod::OAuth2 {
+            provider: OAuth2Provider::GitHub,
+            code: "unknown".to_string(),
+            state: "state".to_string(),
+        })
+        .await;
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::OAuthTokenExchange(_))
+    ));
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests that injected errors fire once and that expired mock tokens are rejected.
+async fn test_mock_oauth_error_injection_and_expiry() {
+    let mock = MockOAuth2Service::new()
+        .with_user(
+            OAuth2Provider::GitHub,
+            "code",
+            mock_oauth_user_info("gh-2", "expiry@example.com"),
+        )
+        .with_error(
+            MockOAuth2Operation::ExchangeCode,
+            narangcia_cryptic::AuthError::OAuthNetwork("provider down".to_string()),
+        );
+
+    let error = mock
+        .exchange_code_for_token(OAuth2Provider::GitHub, "code", "state")
+        .await;
+    assert!(matches!(
+        error,
+        Err(narangcia_cryptic::AuthError::OAuthNetwork(_))
+    ));
+    let token = mock
+        .exchange_code_for_token(OAuth2Provider::GitHub, "code", "state")
+        .await
+        .unwrap();
+    assert!(!token.is_expired_with_margin(
+        narangcia_cryptic::core::oauth::store::DEFAULT_OAUTH_EXPIRY_MARGIN
+    ));
+    assert_eq!(
+        mock.fetch_user_info(&token).await.unwrap().provider_user_id,
+        "gh-2"
+    );
+
+    let expired = MockOAuth2Service::new()
+        .with_user(
+            OAuth2Provider::GitHub,
+            "code",
+            mock_oauth_user_info("gh-2", "expiry@example.com"),
+        )
+        .with_token_lifetime(-60);
+    let token = expired
+        .exchange_code_for_token(OAuth2Provider::GitHub, "code", "state")
+        .await
+        .unwrap();
+    assert!(token.is_expired_with_margin(
+        narangcia_cryptic::core::oauth::store::DEFAULT_OAUTH_EXPIRY_MARGIN
+    ));
+    assert!(matches!(
+        expired.fetch_user_info(&token).await,
+        Err(narangcia_cryptic::AuthError::OAuthUserInfo(_))
+    ));
+}
+
+// --- Test Utility Tests ---
+#[cfg(feature = "test-util")]
+use narangcia_cryptic::test_util::{seed_user, test_auth_service, test_auth_service_with_oauth};
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests that a seeded user can log in on the test preset service.
+async fn test_util_seed_user_and_login() {
+    let auth_service = test_auth_service();
+    let user = seed_user(&auth_service, "seeded@example.com", "password123")
+        .await
+        .unwrap();
+
+    let (logged_in, tokens) = auth_service
+        .login(narangcia_cryptic::auth_service::LoginMethod::Credentials {
+            identifier: "seeded@example.com".to_string(),
+            password: "password123".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(logged_in.id, user.id);
+    assert!(
+        auth_service
+            .validate_access_token(&tokens.access_token)
+            .await
+            .is_ok()
+    );
+
+    // Seeding the same identifier twice fails
+    assert!(
+        seed_user(&auth_service, "seeded@example.com", "password123")
+            .await
+            .is_err()
+    );
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests that tokens from one test preset service validate on another one.
+async fn test_util_services_share_deterministic_secret() {
+    let issuer = test_auth_service_with_oauth(MockOAuth2Service::new().with_user(
+        OAuth2Provider::GitHub,
+        "code",
+        mock_oauth_user_info("gh-3", "preset@example.com"),
+    ));
+    let (_, tokens) = issuer
+        .signup(narangcia_cryptic::auth_service::SignupMethod::OAuth2 {
+            provider: OAuth2Provider::GitHub,
+            code: "code".to_string(),
+            state: "state".to_string(),
+        })
+        .await
+        .unwrap();
+
+    assert!(
+        test_auth_service()
+            .validate_access_token(&tokens.access_token)
+            .await
+            .is_ok()
+    );
+}
+
+// --- Email OTP Tests ---
+fn email_otp_auth_service(ttl: u64) -> AuthService {
+    AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            email_otp_ttl: Some(ttl),
+            email_otp_max_attempts: Some(3),
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+async fn signup_otp_user(
+    auth_service: &AuthService,
+    identifier: &str,
+) -> narangcia_cryptic::CrypticUser {
+    let (signup, _) = credentials_methods(identifier, "password123");
+    auth_service.signup(signup).await.unwrap().0
+}
+
+#[tokio::test]
+/// Tests that a correct email OTP logs the user in exactly once.
+async fn test_email_otp_correct_code() {
+    let auth_service = email_otp_auth_service(300);
+    let user = signup_otp_user(&auth_service, "otp@example.com").await;
+
+    let code = auth_service
+        .request_email_otp("otp@example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(code.len(), 6);
+    assert!(code.chars().all(|c| c.is_ascii_digit()));
+
+    let (logged_in, tokens) = auth_service
+        .login_with_email_otp("otp@example.com", &code)
+        .await
+        .unwrap();
+    assert_eq!(logged_in.id, user.id);
+    assert!(
+        auth_service
+            .validate_access_token(&tokens.access_token)
+            .await
+            .is_ok()
+    );
+
+    // The code cannot be reused
+    assert!(matches!(
+        auth_service
+            .login_with_email_otp("otp@example.com", &code)
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+
+    // Unknown identifiers get the same success, without a code
+    assert!(matches!(
+        auth_service.request_email_otp("unknown@example.com").await,
+        Ok(None)
+    ));
+}
+
+#[tokio::test]
+/// Tests that wrong email OTP codes are rejected and lock the user out after the attempt limit.
+async fn test_email_otp_wrong_code_lockout() {
+    let auth_service = email_otp_auth_service(300);
+    signup_otp_user(&auth_service, "wrong@example.com").await;
+
+    let code = auth_service
+        .request_email_otp("wrong@example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    let wrong = if code == "000000" { "111111" } else { "000000" };
+
+    for _ in 0..3 {
+        assert!(matches!(
+            auth_service
+                .login_with_email_otp("wrong@example.com", wrong)
+                .await,
+            Err(narangcia_cryptic::AuthError::InvalidCredentials)
+        ));
+    }
+
+    // Locked out: even the correct code and new codes are refused
+    assert!(matches!(
+        auth_service
+            .login_with_email_otp("wrong@example.com", &code)
+            .await,
+        Err(narangcia_cryptic::AuthError::LoginError(_))
+    ));
+    assert!(matches!(
+        auth_service.request_email_otp("wrong@example.com").await,
+        Err(narangcia_cryptic::AuthError::LoginError(_))
+    ));
+}
+
+#[tokio::test]
+/// Tests that an expired email OTP code is rejected.
+async fn test_email_otp_expired_code() {
+    let auth_service = email_otp_auth_service(1);
+    signup_otp_user(&auth_service, "expired@example.com").await;
+
+    let code = auth_service
+        .request_email_otp("expired@example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+
+    assert!(matches!(
+        auth_service
+            .login_with_email_otp("expired@example.com", &code)
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+}
+
+#[tokio::test]
+/// Tests that email OTPs are scoped to tenants and are not sent to inactive users.
+async fn test_email_otp_tenants_and_inactive_users() {
+    let auth_service = email_otp_auth_service(300);
+    let acme = auth_service.for_tenant("acme");
+    let (signup, _) = credentials_methods("tenant-otp@example.com", "password123");
+    let (user, _) = acme.signup(signup).await.unwrap();
+
+    // The root service only knows users without a tenant
+    assert!(matches!(
+        auth_service
+            .request_email_otp("tenant-otp@example.com")
+            .await,
+        Ok(None)
+    ));
+    let code = acme
+        .request_email_otp("tenant-otp@example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(
+        auth_service
+            .login_with_email_otp("tenant-otp@example.com", &code)
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+    let (logged_in, _) = acme
+        .login_with_email_otp("tenant-otp@example.com", &code)
+        .await
+        .unwrap();
+    assert_eq!(logged_in.id, user.id);
+    assert_eq!(logged_in.tenant_id.as_deref(), Some("acme"));
+
+    auth_service
+        .set_user_status(
+            user.id.clone(),
+            narangcia_cryptic::core::user::UserStatus::Suspended,
+        )
+        .await
+        .unwrap();
+    assert!(matches!(
+        acme.request_email_otp("tenant-otp@example.com").await,
+        Ok(None)
+    ));
+}
+
+// --- Argon2 Calibration Tests ---
+#[test]
+/// Tests that calibrated Argon2 params hash a password within a tolerance of the target duration.
+fn test_argon2_calibrate_hits_target() {
+    let target = std::time::Duration::from_millis(200);
+    let params = Argon2Params::calibrate(target);
+    let hasher = Argon2Hasher::with_params(params).unwrap();
+
+    // The fastest of a few runs filters out scheduling noise from concurrent tests
+    let elapsed = (0..3)
+        .map(|_| {
+            let start = std::time::Instant::now();
+            hasher.hash(b"calibration-test", None).unwrap();
+            start.elapsed()
+        })
+        .min()
+        .unwrap();
+
+    assert!(
+        elapsed >= target / 4 && elapsed <= target * 4,
+        "calibrated params {params:?} hashed in {elapsed:?}, expected about {target:?}"
+    );
+}
+
+// --- Argon2 Buffer Pool Tests ---
+#[test]
+/// Tests that a pooled hasher produces the same hashes as an unpooled one and that both verify each other's hashes.
+fn test_argon2_buffer_pool_matches_unpooled() {
+    let params = Argon2Params {
+        memory_kib: 1024,
+        iterations: 1,
+        parallelism: 1,
+    };
+    let plain = Argon2Hasher::with_params(params).unwrap();
+    let pooled = Argon2Hasher::with_params(params)
+        .unwrap()
+        .with_buffer_pool(2);
+    let salt = generate_secure_salt().unwrap();
+
+    // Repeated calls reuse the same buffer
+    for _ in 0..3 {
+        let pooled_hash = pooled.hash(b"pooled-password", Some(&salt)).unwrap();
+        assert_eq!(
+            pooled_hash,
+            plain.hash(b"pooled-password", Some(&salt)).unwrap()
+        );
+        assert!(pooled.verify(b"pooled-password", &pooled_hash).unwrap());
+        assert!(!pooled.verify(b"wrong-password", &pooled_hash).unwrap());
+        assert!(plain.verify(b"pooled-password", &pooled_hash).unwrap());
+    }
+
+    // Hashes made with other parameters still verify through the standard path
+    let default_hash = Argon2Hasher::new().hash(b"pooled-password", None).unwrap();
+    assert!(pooled.verify(b"pooled-password", &default_hash).unwrap());
+    assert!(!pooled.verify(b"wrong-password", &default_hash).unwrap());
+}
+
+// --- Argon2 Secret Key Tests ---
+#[test]
+/// Tests that hashes made with an Argon2 secret key only verify with the same key.
+fn test_argon2_secret_key_cross_verification_fails() {
+    let params = Argon2Params {
+        memory_kib: 1024,
+        iterations: 1,
+        parallelism: 1,
+    };
+    let keyed = Argon2Hasher::with_params(params)
+        .unwrap()
+        .with_secret(b"server-key-one".to_vec());
+    let other_key = Argon2Hasher::with_params(params)
+        .unwrap()
+        .with_secret(b"server-key-two".to_vec());
+    let unkeyed = Argon2Hasher::with_params(params).unwrap();
+    let salt = generate_secure_salt().unwrap();
+
+    let hash = keyed.hash(b"keyed-password", Some(&salt)).unwrap();
+    assert!(keyed.verify(b"keyed-password", &hash).unwrap());
+    assert!(!keyed.verify(b"wrong-password", &hash).unwrap());
+    assert!(!other_key.verify(b"keyed-password", &hash).unwrap());
+    assert!(!unkeyed.verify(b"keyed-password", &hash).unwrap());
+    assert_ne!(hash, unkeyed.hash(b"keyed-password", Some(&salt)).unwrap());
+
+    let unkeyed_hash = unkeyed.hash(b"keyed-password", None).unwrap();
+    assert!(!keyed.verify(b"keyed-password", &unkeyed_hash).unwrap());
+
+    // The pooled path uses the key as well
+    let pooled = Argon2Hasher::with_params(params)
+        .unwrap()
+        .with_secret(b"server-key-one".to_vec())
+        .with_buffer_pool(1);
+    assert_eq!(pooled.hash(b"keyed-password", Some(&salt)).unwrap(), hash);
+    assert!(pooled.verify(b"keyed-password", &hash).unwrap());
+}
+
+// --- OAuth2 User Info Parsing Tests ---
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that user IDs are found in alternate fields for every provider.
+async fn test_parse_user_info_alternate_id_fields() {
+    let manager = OAuth2Manager::default();
+    let cases = [
+        (
+            OAuth2Provider::Google,
+            serde_json::json!({ "id": "g-1" }),
+            "g-1",
+        ),
+        (
+            OAuth2Provider::Google,
+            serde_json::json!({ "sub": "g-2" }),
+            "g-2",
+        ),
+        (
+            OAuth2Provider::GitHub,
+            serde_json::json!({ "id": 42 }),
+            "42",
+        ),
+        (
+            OAuth2Provider::GitHub,
+            serde_json::json!({ "id": null, "node_id": "MDQ6VXNlcjQy" }),
+            "MDQ6VXNlcjQy",
+        ),
+        (
+            OAuth2Provider::Discord,
+            serde_json::json!({ "id": "d-1" }),
+            "d-1",
+        ),
+        (
+            OAuth2Provider::Discord,
+            serde_json::json!({ "user": { "id": "d-2" } }),
+            "d-2",
+        ),
+        (
+            OAuth2Provider::Microsoft,
+            serde_json::json!({ "id": "m-1" }),
+            "m-1",
+        ),
+        (
+            OAuth2Provider::Microsoft,
+            serde_json::json!({ "id": "", "oid": "m-2" }),
+            "m-2",
+        ),
+    ];
+
+    for (provider, body, expected) in cases {
+        let info = manager.parse_user_info(provider, body).await.unwrap();
+        assert_eq!(info.provider_user_id, expected);
+    }
+}
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that a user info response without any ID field fails with the raw response.
+async fn test_parse_user_info_missing_id_includes_response() {
+    let manager = OAuth2Manager::default();
+    let result = manager
+        .parse_user_info(
+            OAuth2Provider::Microsoft,
+            serde_json::json!({ "displayName": "No Id" }),
+        )
+        .await;
+
+    match result {
+        Err(narangcia_cryptic::AuthError::OAuthInvalidResponse(message)) => {
+            assert!(message.contains("oid"));
+            assert!(message.contains("No Id"));
+        }
+        other => panic!("expected OAuthInvalidResponse, got {other:?}"),
+    }
+}
+
+// --- OAuth2 Verified Email Requirement Tests ---
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests that providers requiring a verified email accept only verified accounts, treating a
+/// missing flag as unverified.
+async fn test_oauth_required_verified_email_per_provider() {
+    for &provider in OAuth2Provider::all() {
+        for (verified, accepted) in [(Some(true), true), (Some(false), false), (None, false)] {
+            let mut user_info = mock_oauth_user_info("account-1", "verified@example.com");
+            user_info.verified_email = verified;
+            let mock = MockOAuth2Service::new()
+                .with_user(provider, "code", user_info)
+                .with_required_verified_email(provider);
+            let auth_service = auth_service_with_mock_oauth(mock);
+
+            let result = auth_service
+                .login(narangcia_cryptic::auth_service::LoginMethod::OAuth2 {
+                    provider,
+                    code: "code".to_string(),
+                    state: "state".to_string(),
+                })
+                .await;
+            if accepted {
+                assert!(result.is_ok(), "{provider:?} rejected a verified email");
+            } else {
+                assert!(
+                    matches!(
+                        result,
+                        Err(narangcia_cryptic::AuthError::OAuthEmailNotVerified)
+                    ),
+                    "{provider:?} accepted verified_email = {verified:?}"
+                );
+            }
+        }
+    }
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests that the requirement only applies to the providers it is configured for.
+async fn test_oauth_verified_email_not_required_for_other_providers() {
+    let mut user_info = mock_oauth_user_info("gh-unverified", "unverified@example.com");
+    user_info.verified_email = None;
+    let mock = MockOAuth2Service::new()
+        .with_user(OAuth2Provider::GitHub, "code", user_info)
+        .with_required_verified_email(OAuth2Provider::Google);
+    let auth_service = auth_service_with_mock_oauth(mock);
+
+    assert!(
+        auth_service
+            .signup(narangcia_cryptic::auth_service::SignupMethod::OAuth2 {
+                provider: OAuth2Provider::GitHub,
+                code: "code".to_string(),
+                state: "state".to_string(),
+            })
+            .await
+            .is_ok()
+    );
+}
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that `OAuth2Manager` reads the requirement from each provider configuration and
+/// that providers without a verified flag report none.
+async fn test_oauth_manager_required_verified_email_config() {
+    use narangcia_cryptic::core::oauth::OAuth2Service;
+
+    let mut strict = test_oauth_config("secret", "https://api.example.com/oauth/github/callback");
+    strict.require_verified_email = true;
+    let mut configs = std::collections::HashMap::new();
+    configs.insert(OAuth2Provider::GitHub, strict);
+    configs.insert(
+        OAuth2Provider::Google,
+        test_oauth_config("secret", "https://api.example.com/oauth/google/callback"),
+    );
+    let manager = OAuth2Manager::new(configs);
+
+    assert!(manager.requires_verified_email(OAuth2Provider::GitHub));
+    assert!(!manager.requires_verified_email(OAuth2Provider::Google));
+    assert!(!manager.requires_verified_email(OAuth2Provider::Discord));
+
+    for provider in [OAuth2Provider::GitHub, OAuth2Provider::Microsoft] {
+        let info = manager
+            .parse_user_info(provider, serde_json::json!({ "id": "42" }))
+            .await
+            .unwrap();
+        assert_eq!(info.verified_email, None);
+    }
+}
+
+// --- OAuth2 Account Link Verification Tests ---
+#[cfg(feature = "test-util")]
+async fn link_verification_auth_service() -> (AuthService, narangcia_cryptic::CrypticUser) {
+    let mock = MockOAuth2Service::new()
+        .with_user(
+            OAuth2Provider::GitHub,
+            "code-1",
+            mock_oauth_user_info("gh-link", "owner@example.com"),
+        )
+        .with_user(
+            OAuth2Provider::GitHub,
+            "code-2",
+            mock_oauth_user_info("gh-link", "owner@example.com"),
+        );
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            require_oauth_link_verification: true,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        Some(Box::new(mock)),
+    )
+    .unwrap();
+    let (signup, _) = credentials_methods("owner@example.com", "password123");
+    let (user, _) = auth_service.signup(signup).await.unwrap();
+    (auth_service, user)
+}
+
+#[cfg(feature = "test-util")]
+fn github_login(code: &str) -> narangcia_cryptic::auth_service::LoginMethod {
+    narangcia_cryptic::auth_service::LoginMethod::OAuth2 {
+        provider: OAuth2Provider::GitHub,
+        code: code.to_string(),
+        state: "state".to_string(),
+    }
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests that an OAuth2 login matching a password account returns a challenge, confirmed by password.
+async fn test_oauth_link_challenge_confirmed_with_password() {
+    let (auth_service, user) = link_verification_auth_service().await;
+
+    let challenge = auth_service.login(github_login("code-1")).await;
+    match challenge {
+        Err(narangcia_cryptic::AuthError::AccountLinkRequiresVerification {
+            user_id,
+            provider,
+        }) => {
+            assert_eq!(user_id, user.id);
+            assert_eq!(provider, OAuth2Provider::GitHub);
+        }
+        other => panic!("expected a link challenge, got {other:?}"),
+    }
+    // Nothing is linked before confirmation
+    let stored = auth_service
+        .persistent_users_manager
+        .get_user_by_id(&user.id)
+        .await
+        .unwrap();
+    assert!(stored.oauth_accounts.is_empty());
+
+    // A wrong password is rejected
+    assert!(matches!(
+        auth_service
+            .confirm_link(user.id.as_str(), OAuth2Provider::GitHub, "wrong-password")
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+
+    let (linked, _) = auth_service
+        .confirm_link(user.id.as_str(), OAuth2Provider::GitHub, "password123")
+        .await
+        .unwrap();
+    assert!(linked.oauth_accounts.contains_key(&OAuth2Provider::GitHub));
+
+    // Later OAuth2 logins go straight through
+    let (logged_in, _) = auth_service.login(github_login("code-2")).await.unwrap();
+    assert_eq!(logged_in.id, user.id);
+
+    // The pending link was consumed
+    assert!(matches!(
+        auth_service
+            .confirm_link(user.id.as_str(), OAuth2Provider::GitHub, "password123")
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidInput(_))
+    ));
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests that a link challenge can be confirmed with an access token of the account owner.
+async fn test_oauth_link_challenge_confirmed_with_token() {
+    let (auth_service, user) = link_verification_auth_service().await;
+    let (_, tokens) = auth_service
+        .login(narangcia_cryptic::auth_service::LoginMethod::Credentials {
+            identifier: "owner@example.com".to_string(),
+            password: "password123".to_string(),
+        })
+        .await
+        .unwrap();
+
+    assert!(auth_service.login(github_login("code-1")).await.is_err());
+    let (linked, _) = auth_service
+        .confirm_link(
+            user.id.as_str(),
+            OAuth2Provider::GitHub,
+            &tokens.access_token,
+        )
+        .await
+        .unwrap();
+    assert!(linked.oauth_accounts.contains_key(&OAuth2Provider::GitHub));
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests that link confirmations lock the user out once the attempts are exhausted, and
+/// that the attempts are audited like logins.
+async fn test_oauth_link_confirmation_locks_out() {
+    let (auth_service, user) = link_verification_auth_service().await;
+    let events = RecordingAuditLog::default();
+    let auth_service = auth_service.with_audit_log(Box::new(events.clone()));
+    assert!(auth_service.login(github_login("code-1")).await.is_err());
+
+    let limit = narangcia_cryptic::core::rate_limit::DEFAULT_LINK_CONFIRMATION_RATE_LIMIT;
+    for _ in 0..limit.max_requests {
+        assert!(matches!(
+            auth_service
+                .confirm_link(user.id.as_str(), OAuth2Provider::GitHub, "wrong-password")
+                .await,
+            Err(narangcia_cryptic::AuthError::InvalidCredentials)
+        ));
+    }
+    // Even the right password is refused once locked out
+    assert!(matches!(
+        auth_service
+            .confirm_link(user.id.as_str(), OAuth2Provider::GitHub, "password123")
+            .await,
+        Err(narangcia_cryptic::AuthError::LoginError(_))
+    ));
+
+    let events = events.events();
+    assert!(events.iter().any(|event| matches!(
+        event,
+        narangcia_cryptic::core::audit::AuditEvent::LockedOut { user_id }
+            if *user_id == auth_service.log_id(&user.id)
+    )));
+    let failures = events
+        .iter()
+        .filter(|event| {
+            matches!(
+                event,
+                narangcia_cryptic::core::audit::AuditEvent::LoginFailed { method, reason, .. }
+                    if method == "oauth:github" && reason == "invalid_credentials"
+            )
+        })
+        .count();
+    assert_eq!(failures, limit.max_requests as usize);
+}
+
+// --- Session Revocation Tests ---
+#[tokio::test]
+/// Tests that revoking all sessions of a user invalidates every token, idempotently.
+async fn test_revoke_all_for_user() {
+    let auth_service = email_otp_auth_service(300);
+    let (signup, login) = credentials_methods("sessions@example.com", "password123");
+    let (user, first) = auth_service.signup(signup).await.unwrap();
+    let (_, second) = auth_service.login(login.clone()).await.unwrap();
+    let (_, third) = auth_service.login(login.clone()).await.unwrap();
+    let refreshed = auth_service
+        .refresh_access_token(&third.refresh_token)
+        .await
+        .unwrap();
+
+    let other_signup = credentials_methods("bystander@example.com", "password123").0;
+    let (_, other) = auth_service.signup(other_signup).await.unwrap();
+
+    assert_eq!(
+        auth_service
+            .revoke_all_for_user(user.id.as_str())
+            .await
+            .unwrap(),
+        3
+    );
+
+    for pair in [&first, &second, &third, &refreshed] {
+        assert!(matches!(
+            auth_service.validate_access_token(&pair.access_token).await,
+            Err(narangcia_cryptic::AuthError::InvalidToken(_))
+        ));
+        assert!(matches!(
+            auth_service.refresh_access_token(&pair.refresh_token).await,
+            Err(narangcia_cryptic::AuthError::InvalidToken(_))
+        ));
+    }
+    assert!(!auth_service.introspect(&first.access_token).await.active);
+
+    // Other users are unaffected
+    assert!(
+        auth_service
+            .validate_access_token(&other.access_token)
+            .await
+            .is_ok()
+    );
+
+    // Idempotent, and new logins start a fresh session
+    assert_eq!(
+        auth_service
+            .revoke_all_for_user(user.id.as_str())
+            .await
+            .unwrap(),
+        0
+    );
+    let (_, fresh) = auth_service.login(login).await.unwrap();
+    assert!(
+        auth_service
+            .validate_access_token(&fresh.access_token)
+            .await
+            .is_ok()
+    );
+}
+
+// --- User ID Tests ---
+#[test]
+/// Tests that user IDs convert from and to strings and serialize as plain strings.
+fn test_user_id_conversions_and_wire_format() {
+    let id = UserId::from("user-42");
+    assert_eq!(id.as_str(), "user-42");
+    assert_eq!(id.to_string(), "user-42");
+    assert_eq!(id, "user-42");
+    assert_eq!(UserId::from("user-42".to_string()), id);
+    assert_eq!(serde_json::to_string(&id).unwrap(), "\"user-42\"");
+    assert_eq!(serde_json::from_str::<UserId>("\"user-42\"").unwrap(), id);
+    assert_ne!(UserId::generate(), UserId::generate());
+}
+
+// --- Prelude Tests ---
+#[tokio::test]
+/// Tests that the prelude provides everything needed for a signup and login.
+async fn test_prelude_covers_common_flow() {
+    use narangcia_cryptic::prelude::*;
+
+    async fn sign_in(auth: &AuthService) -> AuthResult<(User, TokenPair)> {
+        auth.signup(SignupMethod::Credentials {
+            identifier: "prelude@example.com".to_string(),
+            password: "password123".to_string(),
+        })
+        .await?;
+        auth.login(LoginMethod::Credentials {
+            identifier: "prelude@example.com".to_string(),
+            password: "password123".to_string(),
+        })
+        .await
+    }
+
+    let auth = email_otp_auth_service(300);
+    let (user, tokens) = sign_in(&auth).await.unwrap();
+    let user_id: UserId = auth
+        .get_user_id_from_token(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(user_id, user.id);
+    assert_eq!(OAuth2Provider::GitHub.as_str(), "github");
+    assert!(matches!(
+        sign_in(&auth).await,
+        Err(AuthError::SignupError(_) | AuthError::UserAlreadyExists)
+    ));
+}
+
+// --- Credentials Lookup Tests ---
+#[tokio::test]
+/// Tests that credentials are looked up by identifier and tenant without the full user, and that logins still work through them.
+async fn test_get_credentials_by_identifier() {
+    let auth = email_otp_auth_service(300);
+    let (user, _) = auth
+        .signup(narangcia_cryptic::auth_service::SignupMethod::Credentials {
+            identifier: "creds@example.com".to_string(),
+            password: "password123".to_string(),
+        })
+        .await
+        .unwrap();
+    let repo = &auth.persistent_users_manager;
+
+    let credentials = repo
+        .get_credentials_by_identifier("creds@example.com")
+        .await
+        .unwrap()
+        .expect("credentials should be found");
+    assert_eq!(credentials.user_id, user.id);
+    assert_eq!(
+        credentials.password_hash,
+        user.credentials.as_ref().unwrap().password_hash
+    );
+    assert_eq!(
+        repo.get_credentials_by_identifier_in_tenant("creds@example.com", None)
+            .await
+            .unwrap(),
+        Some(credentials)
+    );
+    assert!(
+        repo.get_credentials_by_identifier_in_tenant("creds@example.com", Some("other"))
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        repo.get_credentials_by_identifier("missing@example.com")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let (logged_in, _) = auth
+        .login(narangcia_cryptic::auth_service::LoginMethod::Credentials {
+            identifier: "creds@example.com".to_string(),
+            password: "password123".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(logged_in.id, user.id);
+    assert!(matches!(
+        auth.login(narangcia_cryptic::auth_service::LoginMethod::Credentials {
+            identifier: "creds@example.com".to_string(),
+            password: "wrong-password".to_string(),
+        })
+        .await,
+        Err(narangcia_cryptic::error::AuthError::InvalidCredentials)
+    ));
+}
+
+// --- Subject Formatter Tests ---
+use narangcia_cryptic::core::token::subject::PrefixSubjectFormatter;
+
+#[tokio::test]
+/// Tests that a prefixing subject formatter shapes the `sub` claim and round-trips user IDs through validation and refresh.
+async fn test_subject_formatter_round_trip() {
+    let vars = std::sync::Arc::new(AuthServiceVariables {
+        secret_key: TEST_JWT_SECRET.to_string(),
+        token_expiration: 60,
+        refresh_token_expiration: 120,
+        argon2_params: TEST_ARGON2_PARAMS,
+        ..Default::default()
+    });
+    let prefixed = JwtTokenService::new(TEST_JWT_SECRET, 60, 120)
+        .with_subject_formatter(PrefixSubjectFormatter::new("user:"));
+    let auth = AuthService::new(vars, None, None, Some(Box::new(prefixed)), None).unwrap();
+    let (user, tokens) = auth
+        .signup(narangcia_cryptic::auth_service::SignupMethod::Credentials {
+            identifier: "subject@example.com".to_string(),
+            password: "password123".to_string(),
+        })
+        .await
+        .unwrap();
+
+    // The raw claim carries the formatted subject
+    let plain = JwtTokenService::new(TEST_JWT_SECRET, 60, 120);
+    let raw = plain
+        .validate_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(raw.get_subject(), format!("user:{}", user.id));
+
+    assert_eq!(
+        auth.get_user_id_from_token(&tokens.access_token)
+            .await
+            .unwrap(),
+        user.id
+    );
+    let refreshed = auth
+        .refresh_access_token(&tokens.refresh_token)
+        .await
+        .unwrap();
+    assert_eq!(
+        auth.get_user_id_from_token(&refreshed.access_token)
+            .await
+            .unwrap(),
+        user.id
+    );
+
+    // Tokens whose subject lacks the prefix are rejected
+    let bare = plain.generate_token_pair(user.id.as_str()).await.unwrap();
+    assert!(matches!(
+        auth.get_user_id_from_token(&bare.access_token).await,
+        Err(narangcia_cryptic::error::AuthError::InvalidToken(_))
+    ));
+}
+
+// --- Rehash Scan Tests ---
+#[tokio::test]
+/// Tests that `scan_for_rehash` reports outdated and unrecognized hashes without changing them, and that logins clear outdated ones.
+async fn test_scan_for_rehash_reports_outdated_hashes() {
+    let old_manager = Argon2PasswordManager::with_params(TEST_ARGON2_PARAMS).unwrap();
+    let current_params = Argon2Params {
+        iterations: 2,
+        ..TEST_ARGON2_PARAMS
+    };
+    let current_manager = Argon2PasswordManager::with_params(current_params).unwrap();
+
+    let repo = InMemoryUserRepo::new();
+    let hashes = [
+        (
+            "current",
+            current_manager.hash_password("pass-current").await.unwrap(),
+        ),
+        ("old", old_manager.hash_password("pass-old").await.unwrap()),
+        ("legacy", "plaintext-legacy".to_string()),
+    ];
+    for (id, hash) in &hashes {
+        repo.add_user(User::new(
+            id.to_string(),
+            Credentials::new(id.to_string(), format!("{id}@example.com"), hash.clone()),
+        ))
+        .await
+        .unwrap();
+    }
+
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            ..Default::default()
+        }),
+        Some(Box::new(current_manager)),
+        Some(Box::new(repo)),
+        None,
+        None,
+    )
+    .unwrap();
+
+    let report = auth_service.scan_for_rehash().await.unwrap();
+    assert_eq!(report.scanned, 3);
+    assert_eq!(report.needs_rehash, vec![UserId::from("old")]);
+    assert_eq!(report.unrecognized, vec![UserId::from("legacy")]);
+    assert!(!report.is_complete());
+
+    // Scanning leaves hashes untouched
+    let old_user = auth_service
+        .persistent_users_manager
+        .get_user_by_id(&UserId::from("old"))
+        .await
+        .unwrap();
+    assert_eq!(old_user.credentials.unwrap().password_hash, hashes[1].1);
+
+    auth_service
+        .login(narangcia_cryptic::auth_service::LoginMethod::Credentials {
+            identifier: "old@example.com".to_string(),
+            password: "pass-old".to_string(),
+        })
+        .await
+        .unwrap();
+    let report = auth_service.scan_for_rehash().await.unwrap();
+    assert!(report.needs_rehash.is_empty());
+    assert_eq!(report.unrecognized, vec![UserId::from("legacy")]);
+}
+
+// --- OAuth2 Granted Scopes Tests ---
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests that the scopes granted by the provider are returned with the linked account and persisted.
+async fn test_oauth_granted_scopes_propagate() {
+    let mock = MockOAuth2Service::new()
+        .with_user(
+            OAuth2Provider::GitHub,
+            "scoped-code",
+            mock_oauth_user_info("gh-scoped", "scoped@example.com"),
+        )
+        .with_granted_scope("read:user,user:email");
+    let auth_service = auth_service_with_mock_oauth(mock);
+
+    let (user, _) = auth_service
+        .login(narangcia_cryptic::auth_service::LoginMethod::OAuth2 {
+            provider: OAuth2Provider::GitHub,
+            code: "scoped-code".to_string(),
+            state: "state".to_string(),
+        })
+        .await
+        .unwrap();
+    let expected = vec!["read:user".to_string(), "user:email".to_string()];
+    assert_eq!(
+        user.oauth_accounts[&OAuth2Provider::GitHub].granted_scopes,
+        expected
+    );
+
+    let stored = auth_service
+        .persistent_users_manager
+        .get_user_by_id(&user.id)
+        .await
+        .unwrap();
+    assert_eq!(
+        stored.oauth_accounts[&OAuth2Provider::GitHub].granted_scopes,
+        expected
+    );
+}
+
+#[test]
+/// Tests that granted scopes are parsed from space- and comma-separated token scopes.
+fn test_oauth_token_granted_scopes_parsing() {
+    let mut token = narangcia_cryptic::core::oauth::store::OAuth2Token {
+        access_token: "token".to_string(),
+        refresh_token: None,
+        expires_at: None,
+        token_type: "Bearer".to_string(),
+        scope: Some("openid email  profile".to_string()),
+        provider: OAuth2Provider::Google,
+        created_at: chrono::Utc::now().naive_utc(),
+    };
+    assert_eq!(token.granted_scopes(), vec!["openid", "email", "profile"]);
+    token.scope = Some("repo, gist".to_string());
+    assert_eq!(token.granted_scopes(), vec!["repo", "gist"]);
+    token.scope = None;
+    assert!(token.granted_scopes().is_empty());
+}
+
+// --- Empty Password Verification Tests ---
+#[tokio::test]
+/// Tests that empty passwords or hashes verify as a mismatch by default and fail in strict mode.
+async fn test_argon2_manager_empty_input_behavior() {
+    use narangcia_cryptic::core::password::EmptyInputBehavior;
+
+    let lenient = Argon2PasswordManager::with_params(TEST_ARGON2_PARAMS).unwrap();
+    let hash = lenient.hash_password("password").await.unwrap();
+    assert!(!lenient.verify_password("", &hash).await.unwrap());
+    assert!(!lenient.verify_password("password", "").await.unwrap());
+
+    let strict = Argon2PasswordManager::with_params(TEST_ARGON2_PARAMS)
+        .unwrap()
+        .with_empty_input_behavior(EmptyInputBehavior::Reject);
+    assert!(matches!(
+        strict.verify_password("", &hash).await,
+        Err(narangcia_cryptic::AuthError::InvalidPassword(_))
+    ));
+    assert!(matches!(
+        strict.verify_password("password", "").await,
+        Err(narangcia_cryptic::AuthError::InvalidPassword(_))
+    ));
+    assert!(strict.verify_password("password", &hash).await.unwrap());
+    assert!(!strict.verify_password("wrong", &hash).await.unwrap());
+}
+
+// --- Repository Transaction Tests ---
+#[tokio::test]
+/// Tests that a failing transaction leaves no partial writes and a successful one commits.
+async fn test_repository_transaction_rollback_and_commit() {
+    let repo: Box<dyn UserRepository + Send + Sync> = Box::new(InMemoryUserRepo::new());
+    let user_with_identifier = |id: &str, identifier: &str| User {
+        id: UserId::from(id),
+        credentials: Some(Credentials {
+            user_id: id.to_string(),
+            identifier: identifier.to_string(),
+            password_hash: "hash".to_string(),
+        }),
+        ..User::default()
+    };
+
+    // A closure failing after its first write rolls the write back
+    let user = user_with_identifier("tx1", "tx1@example.com");
+    let result: Result<(), _> = repo
+        .transaction(move |tx| {
+            Box::pin(async move {
+                tx.add_user(user).await?;
+                Err(narangcia_cryptic::AuthError::ServiceUnavailable(
+                    "later step failed".to_string(),
+                ))
+            })
+        })
+        .await;
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::ServiceUnavailable(_))
+    ));
+    assert!(repo.get_user_by_id(&UserId::from("tx1")).await.is_none());
+    assert!(
+        repo.get_user_by_identifier("tx1@example.com")
+            .await
+            .is_none()
+    );
+
+    // A successful closure commits its writes
+    let user = user_with_identifier("tx2", "tx2@example.com");
+    repo.transaction(move |tx| Box::pin(async move { tx.add_user(user).await.map(drop) }))
+        .await
+        .unwrap();
+    assert!(repo.get_user_by_id(&UserId::from("tx2")).await.is_some());
+
+    // Updates and deletions are restored on rollback
+    let result: Result<(), _> = repo
+        .transaction(|tx| {
+            Box::pin(async move {
+                let mut user = tx.get_user_by_id(&UserId::from("tx2")).await.unwrap();
+                user.credentials.as_mut().unwrap().identifier = "renamed@example.com".to_string();
+                tx.update_user(&user).await?;
+                tx.delete_user(&user.id).await?;
+                Err(narangcia_cryptic::AuthError::ServiceUnavailable(
+                    "later step failed".to_string(),
+                ))
+            })
+        })
+        .await;
+    assert!(result.is_err());
+    let restored = repo.get_user_by_id(&UserId::from("tx2")).await.unwrap();
+    assert_eq!(restored.credentials.unwrap().identifier, "tx2@example.com");
+}
+
+// --- Token Audience Tests ---
+
+/// Builds an `AuthService` accepting the given token audiences.
+fn audience_auth_service(audiences: &[&str]) -> AuthService {
+    AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            token_audiences: audiences.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+/// Tests that tokens minted for one audience are rejected by services expecting another.
+async fn test_token_audience_crossing() {
+    let issuer = audience_auth_service(&[]);
+    let service_a = audience_auth_service(&["service-a"]);
+    let service_b = audience_auth_service(&["service-b"]);
+    let service_ab = audience_auth_service(&["service-b", "service-a"]);
+
+    let for_a = issuer
+        .generate_token_pair_for_audience("user_1", "service-a")
+        .await
+        .unwrap();
+    let claims = service_a
+        .validate_access_token(&for_a.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims.get_audience(), Some("service-a"));
+    assert!(matches!(
+        service_b.validate_access_token(&for_a.access_token).await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    // Validators may accept several audiences
+    assert!(
+        service_ab
+            .validate_access_token(&for_a.access_token)
+            .await
+            .is_ok()
+    );
+
+    // Tokens without an audience are rejected once an audience is expected
+    let unscoped = issuer.get_tokens("user_1").await.unwrap();
+    assert!(matches!(
+        service_a
+            .validate_access_token(&unscoped.access_token)
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+
+    // A service issues tokens for its first audience, and refreshing keeps the audience
+    let own = service_b.get_tokens("user_1").await.unwrap();
+    assert!(
+        service_b
+            .validate_access_token(&own.access_token)
+            .await
+            .is_ok()
+    );
+    assert!(
+        service_a
+            .validate_access_token(&own.access_token)
+            .await
+            .is_err()
+    );
+    let refreshed = service_ab
+        .refresh_access_token(&for_a.refresh_token)
+        .await
+        .unwrap();
+    assert!(
+        service_a
+            .validate_access_token(&refreshed.access_token)
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+/// Tests that tokens minted for an audience keep the tenant of the user.
+async fn test_token_audience_keeps_tenant() {
+    let service = audience_auth_service(&["service-a"]);
+    let tenant = service.for_tenant("acme");
+    let (signup, _) = credentials_methods("audience@example.com", "password");
+    let (user, _) = tenant.signup(signup).await.unwrap();
+
+    let tokens = service
+        .generate_token_pair_for_audience(user.id.clone(), "service-a")
+        .await
+        .unwrap();
+    let claims = tenant
+        .validate_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims.get_tenant_id(), Some("acme"));
+    assert_eq!(claims.get_audience(), Some("service-a"));
+}
+
+// --- Token Binding Tests ---
+#[tokio::test]
+/// Tests that bound tokens are only accepted from the client they were issued to.
+async fn test_token_binding_fingerprints() {
+    let auth_service = audience_auth_service(&[]);
+    let pair = auth_service
+        .generate_token_pair_bound("user_1", "device-a")
+        .await
+        .unwrap();
+
+    let claims = auth_service
+        .validate_access_token_bound(&pair.access_token, "device-a")
+        .await
+        .unwrap();
+    assert_eq!(claims.get_fingerprint(), Some("device-a"));
+    assert!(matches!(
+        auth_service
+            .validate_access_token_bound(&pair.access_token, "device-b")
+            .await,
+        Err(narangcia_cryptic::AuthError::TokenBindingMismatch)
+    ));
+
+    // Unbound tokens cannot pass a binding check
+    let unbound = auth_service.get_tokens("user_1").await.unwrap();
+    assert!(matches!(
+        auth_service
+            .validate_access_token_bound(&unbound.access_token, "device-a")
+            .await,
+        Err(narangcia_cryptic::AuthError::TokenBindingMismatch)
+    ));
+
+    // Refreshed tokens stay bound to the same client
+    let refreshed = auth_service
+        .refresh_access_token(&pair.refresh_token)
+        .await
+        .unwrap();
+    assert!(
+        auth_service
+            .validate_access_token_bound(&refreshed.access_token, "device-a")
+            .await
+            .is_ok()
+    );
+    assert!(
+        auth_service
+            .validate_access_token_bound(&refreshed.access_token, "device-b")
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+/// Tests that bound tokens of a tenant user are issued for the tenant.
+async fn test_token_binding_keeps_tenant() {
+    let service = audience_auth_service(&[]);
+    let tenant = service.for_tenant("acme");
+    let (signup, _) = credentials_methods("bound@example.com", "password");
+    let (user, _) = tenant.signup(signup).await.unwrap();
+
+    let pair = service
+        .generate_token_pair_bound(user.id.clone(), "device-a")
+        .await
+        .unwrap();
+    assert!(matches!(
+        service.validate_access_token(&pair.access_token).await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    let claims = tenant
+        .validate_access_token(&pair.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims.get_tenant_id(), Some("acme"));
+    assert_eq!(claims.get_fingerprint(), Some("device-a"));
+}
+
+// --- User Status Tests ---
+#[tokio::test]
+/// Tests that suspended users cannot log in and can be reactivated.
+async fn test_suspended_user_cannot_login() {
+    use narangcia_cryptic::UserStatus;
+
+    let auth_service = tenant_test_auth_service();
+    let (signup, login) = credentials_methods("suspended@example.com", "password");
+    let (user, _) = auth_service.signup(signup).await.unwrap();
+    assert_eq!(user.status, UserStatus::Active);
+
+    auth_service
+        .set_user_status(user.id.clone(), UserStatus::Suspended)
+        .await
+        .unwrap();
+    assert!(matches!(
+        auth_service.login(login.clone()).await,
+        Err(narangcia_cryptic::AuthError::AccountDisabled)
+    ));
+    // A wrong password is still reported as invalid credentials
+    let (_, wrong_login) = credentials_methods("suspended@example.com", "wrong");
+    assert!(matches!(
+        auth_service.login(wrong_login).await,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+
+    auth_service
+        .set_user_status(user.id, UserStatus::Active)
+        .await
+        .unwrap();
+    assert!(auth_service.login(login).await.is_ok());
+}
+
+#[tokio::test]
+/// Tests that token validation rejects suspended users only when statuses are verified.
+async fn test_token_validation_user_status_check() {
+    use narangcia_cryptic::UserStatus;
+
+    for verify in [false, true] {
+        let auth_service = AuthService::new(
+            std::sync::Arc::new(AuthServiceVariables {
+                secret_key: TEST_JWT_SECRET.to_string(),
+                token_expiration: 60,
+                refresh_token_expiration: 120,
+                argon2_params: TEST_ARGON2_PARAMS,
+                verify_user_status_on_validation: verify,
+                ..Default::default()
+            }),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let (signup, _) = credentials_methods("status@example.com", "password");
+        let (user, tokens) = auth_service.signup(signup).await.unwrap();
+        auth_service
+            .set_user_status(user.id, UserStatus::Suspended)
+            .await
+            .unwrap();
+
+        let validation = auth_service
+            .validate_access_token(&tokens.access_token)
+            .await;
+        let refresh = auth_service
+            .refresh_access_token(&tokens.refresh_token)
+            .await;
+        if verify {
+            assert!(matches!(
+                validation,
+                Err(narangcia_cryptic::AuthError::AccountDisabled)
+            ));
+            assert!(matches!(
+                refresh,
+                Err(narangcia_cryptic::AuthError::AccountDisabled)
+            ));
+        } else {
+            assert!(validation.is_ok());
+            assert!(refresh.is_ok());
+        }
+    }
+}
+
+// --- Validation Cache Tests ---
+
+/// Builds an `AuthService` verifying user statuses, with a validation cache of the given TTL.
+fn cached_validation_auth_service(ttl: std::time::Duration) -> AuthService {
+    AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            verify_user_status_on_validation: true,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap()
+    .with_validation_cache(narangcia_cryptic::core::token::cache::ValidationCache::new(
+        ttl,
+    ))
+}
+
+#[tokio::test]
+/// Tests that repeated validations within the TTL hit the cache instead of the repository.
+async fn test_validation_cache_hits_within_ttl() {
+    use narangcia_cryptic::UserStatus;
+
+    let auth_service = cached_validation_auth_service(std::time::Duration::from_secs(60));
+    let (signup, _) = credentials_methods("cached@example.com", "password");
+    let (mut user, tokens) = auth_service.signup(signup).await.unwrap();
+
+    let claims = auth_service
+        .validate_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+    let cache = auth_service.validation_cache.as_ref().unwrap();
+    let cached = cache.get(claims.get_token_id().unwrap()).unwrap();
+    assert_eq!(cached.user.unwrap().id, user.id);
+
+    // Suspending the user behind the service's back is not seen while the entry is cached
+    user.status = UserStatus::Suspended;
+    auth_service
+        .persistent_users_manager
+        .update_user(&user)
+        .await
+        .unwrap();
+    assert!(
+        auth_service
+            .validate_access_token(&tokens.access_token)
+            .await
+            .is_ok()
+    );
+    assert_eq!(cache.len(), 1);
+
+    // Status changes through the service invalidate the user's entries
+    auth_service
+        .set_user_status(user.id.clone(), UserStatus::Suspended)
+        .await
+        .unwrap();
+    assert!(cache.is_empty());
+    assert!(matches!(
+        auth_service
+            .validate_access_token(&tokens.access_token)
+            .await,
+        Err(narangcia_cryptic::AuthError::AccountDisabled)
+    ));
+
+    // So does revoking the user's sessions
+    auth_service
+        .set_user_status(user.id.clone(), UserStatus::Active)
+        .await
+        .unwrap();
+    assert!(
+        auth_service
+            .validate_access_token(&tokens.access_token)
+            .await
+            .is_ok()
+    );
+    auth_service
+        .revoke_all_for_user(user.id.as_str())
+        .await
+        .unwrap();
+    assert!(matches!(
+        auth_service
+            .validate_access_token(&tokens.access_token)
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+}
+
+#[tokio::test]
+/// Tests that cached validations are re-checked once the TTL has elapsed.
+async fn test_validation_cache_expires() {
+    use narangcia_cryptic::UserStatus;
+
+    let auth_service = cached_validation_auth_service(std::time::Duration::from_millis(50));
+    let (signup, _) = credentials_methods("expiring@example.com", "password");
+    let (mut user, tokens) = auth_service.signup(signup).await.unwrap();
+    assert!(
+        auth_service
+            .validate_access_token(&tokens.access_token)
+            .await
+            .is_ok()
+    );
+
+    user.status = UserStatus::Suspended;
+    auth_service
+        .persistent_users_manager
+        .update_user(&user)
+        .await
+        .unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert!(matches!(
+        auth_service
+            .validate_access_token(&tokens.access_token)
+            .await,
+        Err(narangcia_cryptic::AuthError::AccountDisabled)
+    ));
+}
+
+// --- Identifier Resolver Tests ---
+
+/// Normalizes French phone numbers to the E.164 format.
+struct PhoneNumberResolver;
+
+impl narangcia_cryptic::core::credentials::IdentifierResolver for PhoneNumberResolver {
+    fn resolve(&self, raw: &str) -> Result<String, narangcia_cryptic::AuthError> {
+        let compact: String = raw
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+            .collect();
+        let national = compact
+            .strip_prefix("+33")
+            .or_else(|| compact.strip_prefix('0'))
+            .filter(|digits| digits.len() == 9 && digits.chars().all(|c| c.is_ascii_digit()))
+            .ok_or(narangcia_cryptic::AuthError::InvalidCredentials)?;
+        Ok(format!("+33{national}"))
+    }
+}
+
+#[tokio::test]
+/// Tests that signups and logins resolve identifiers before looking users up.
+async fn test_identifier_resolver_normalizes_phone_numbers() {
+    let auth_service =
+        tenant_test_auth_service().with_identifier_resolver(Box::new(PhoneNumberResolver));
+
+    let (signup, _) = credentials_methods("06 12 34 56 78", "password");
+    let (user, _) = auth_service.signup(signup).await.unwrap();
+    assert_eq!(user.credentials.unwrap().identifier, "+33612345678");
+
+    for identifier in ["+33612345678", "06-12-34-56-78", "(0)6.12.34.56.78"] {
+        let (_, login) = credentials_methods(identifier, "password");
+        let (logged_in, _) = auth_service.login(login).await.unwrap();
+        assert_eq!(logged_in.id, user.id);
+    }
+
+    let (duplicate, _) = credentials_methods("+33 6 12 34 56 78", "password");
+    assert!(auth_service.signup(duplicate).await.is_err());
+
+    let (_, invalid) = credentials_methods("not a phone", "password");
+    assert!(matches!(
+        auth_service.login(invalid).await,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+}
+
+// --- User Stream Tests ---
+
+#[tokio::test]
+/// Tests that streaming users yields every user across batches and tenants.
+async fn test_stream_users_yields_every_user() {
+    use futures_util::StreamExt;
+
+    let auth_service = tenant_test_auth_service();
+    for i in 0..150 {
+        let id = narangcia_cryptic::UserId::generate();
+        let credentials = narangcia_cryptic::core::credentials::Credentials::new(
+            id.to_string(),
+            format!("streamed{i}@example.com"),
+            "hash".to_string(),
+        );
+        let user = narangcia_cryptic::User::new(id, credentials);
+        auth_service
+            .persistent_users_manager
+            .add_user(user)
+            .await
+            .unwrap();
+    }
+    let (signup, _) = credentials_methods("tenant-streamed@example.com", "password");
+    auth_service
+        .for_tenant("acme")
+        .signup(signup)
+        .await
+        .unwrap();
+
+    let users: Vec<_> = auth_service
+        .persistent_users_manager
+        .stream_users()
+        .collect()
+        .await;
+    assert_eq!(users.len(), 151);
+    assert!(users.iter().all(Result::is_ok));
+}
+
+// --- Password Hashing Timeout Tests ---
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+/// Tests that hashing with costly parameters fails fast once the timeout elapses.
+async fn test_password_hashing_timeout_fires() {
+    use narangcia_cryptic::core::password::{Argon2PasswordManager, SecurePasswordManager};
+
+    let heavy_params = Argon2Params {
+        memory_kib: 64 * 1024,
+        iterations: 4,
+        parallelism: 1,
+    };
+    let manager = Argon2PasswordManager::with_params(heavy_params)
+        .unwrap()
+        .with_timeout(std::time::Duration::from_millis(1));
+
+    let started = std::time::Instant::now();
+    assert!(matches!(
+        manager.hash_password("password").await,
+        Err(narangcia_cryptic::AuthError::HashingTimeout)
+    ));
+    assert!(started.elapsed() < std::time::Duration::from_millis(500));
+
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: heavy_params,
+            password_hashing_timeout_ms: Some(1),
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    let (signup, _) = credentials_methods("slow@example.com", "password");
+    assert!(matches!(
+        auth_service.signup(signup).await,
+        Err(narangcia_cryptic::AuthError::HashingTimeout)
+    ));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+/// Tests that password operations completing within the timeout succeed.
+async fn test_password_hashing_within_timeout() {
+    use narangcia_cryptic::core::password::{Argon2PasswordManager, SecurePasswordManager};
+
+    let manager = Argon2PasswordManager::with_params(TEST_ARGON2_PARAMS)
+        .unwrap()
+        .with_timeout(std::time::Duration::from_secs(30));
+    let hash = manager.hash_password("password").await.unwrap();
+    assert!(manager.verify_password("password", &hash).await.unwrap());
+    assert!(!manager.verify_password("wrong", &hash).await.unwrap());
+}
+
+#[cfg(not(feature = "tokio"))]
+#[test]
+/// Tests that a hashing timeout set without the tokio dependency is reported, not ignored.
+fn test_password_hashing_timeout_requires_tokio() {
+    let vars = AuthServiceVariables {
+        secret_key: TEST_JWT_SECRET.to_string(),
+        token_expiration: 60,
+        refresh_token_expiration: 120,
+        argon2_params: TEST_ARGON2_PARAMS,
+        password_hashing_timeout_ms: Some(1000),
+        ..Default::default()
+    };
+    assert!(
+        vars.validate()
+            .iter()
+            .any(|issue| issue.field == "password_hashing_timeout_ms")
+    );
+    assert!(matches!(
+        AuthService::new(std::sync::Arc::new(vars), None, None, None, None),
+        Err(narangcia_cryptic::AuthError::ConfigError(_))
+    ));
+}
+
+// --- Token Pair With Claims Tests ---
+
+#[tokio::test]
+/// Tests that the claims returned with a token pair match a subsequent decode.
+async fn test_generate_token_pair_with_claims_out_matches_decode() {
+    use narangcia_cryptic::core::token::claims::AccessTokenClaims;
+
+    let jwt_service = JwtTokenService::new(TEST_JWT_SECRET, 60, 120);
+    let (pair, claims) = jwt_service
+        .generate_token_pair_with_claims_out("claims_user", &Default::default())
+        .await
+        .unwrap();
+    let decoded = jwt_service
+        .validate_access_token(&pair.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims, AccessTokenClaims::from_claims(decoded.as_ref()));
+    assert_eq!(claims.sub, "claims_user");
+    assert!(claims.jti.is_some());
+    assert!(claims.exp > claims.iat);
+
+    let auth_service = tenant_test_auth_service();
+    let (pair, claims) = auth_service
+        .generate_token_pair_with_claims_out("service_user")
+        .await
+        .unwrap();
+    let decoded = auth_service
+        .validate_access_token(&pair.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims, AccessTokenClaims::from_claims(decoded.as_ref()));
+    assert!(claims.sid.is_some());
+    assert!(
+        auth_service
+            .refresh_access_token(&pair.refresh_token)
+            .await
+            .is_ok()
+    );
+
+    // Tokens of a tenant user stay scoped to the tenant
+    let (signup, _) = credentials_methods("claims-out@example.com", "password");
+    let tenant = auth_service.for_tenant("acme");
+    let (user, _) = tenant.signup(signup).await.unwrap();
+    let (pair, claims) = auth_service
+        .generate_token_pair_with_claims_out(user.id.clone())
+        .await
+        .unwrap();
+    assert_eq!(claims.tenant_id.as_deref(), Some("acme"));
+    assert!(
+        tenant
+            .validate_access_token(&pair.access_token)
+            .await
+            .is_ok()
+    );
+}
+
+// --- Checked Refresh Tests ---
+
+#[tokio::test]
+/// Tests that a checked refresh succeeds when both tokens belong to the user, even if the
+/// access token has expired.
+async fn test_refresh_access_token_checked_same_subject() {
+    let auth_service = tenant_test_auth_service();
+    let (signup, _) = credentials_methods("checked@example.com", "password");
+    let (user, tokens) = auth_service.signup(signup).await.unwrap();
+
+    let refreshed = auth_service
+        .refresh_access_token_checked(&tokens.refresh_token, &tokens.access_token)
+        .await
+        .unwrap();
+    let claims = auth_service
+        .validate_access_token(&refreshed.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims.get_subject(), user.id.as_str());
+
+    let now = chrono::Utc::now().timestamp();
+    let expired = encode_test_token(&serde_json::json!({
+        "sub": user.id.as_str(),
+        "exp": now - 3600,
+        "iat": now - 7200,
+        "token_type": "access",
+    }));
+    assert!(
+        auth_service
+            .refresh_access_token_checked(&tokens.refresh_token, &expired)
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+/// Tests that a checked refresh is rejected when the tokens belong to different users.
+async fn test_refresh_access_token_checked_mismatched_subjects() {
+    let auth_service = tenant_test_auth_service();
+    let (victim_signup, _) = credentials_methods("victim@example.com", "password");
+    let (attacker_signup, _) = credentials_methods("attacker@example.com", "password");
+    let (_, victim_tokens) = auth_service.signup(victim_signup).await.unwrap();
+    let (_, attacker_tokens) = auth_service.signup(attacker_signup).await.unwrap();
+
+    assert!(matches!(
+        auth_service
+            .refresh_access_token_checked(
+                &victim_tokens.refresh_token,
+                &attacker_tokens.access_token
+            )
+            .await,
+        Err(narangcia_cryptic::AuthError::SubjectMismatch)
+    ));
+    assert!(matches!(
+        auth_service
+            .refresh_access_token_checked(&victim_tokens.refresh_token, "not-a-token")
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+}
+
+// --- CSRF Double-Submit Tests ---
+
+#[test]
+/// Tests that generated CSRF pairs verify, including re-masked form values.
+fn test_csrf_pair_matches() {
+    use narangcia_cryptic::core::csrf::{generate_csrf_pair, mask_csrf_token, verify_csrf};
+
+    let (cookie_value, form_value) = generate_csrf_pair();
+    assert_ne!(cookie_value, form_value);
+    assert!(verify_csrf(&cookie_value, &form_value));
+
+    let remasked = mask_csrf_token(&cookie_value).unwrap();
+    assert_ne!(remasked, form_value);
+    assert!(verify_csrf(&cookie_value, &remasked));
+}
+
+#[test]
+/// Tests that tampered, swapped or malformed CSRF values are rejected.
+fn test_csrf_tampered_pairs_rejected() {
+    use narangcia_cryptic::core::csrf::{generate_csrf_pair, verify_csrf};
+
+    let (cookie_value, form_value) = generate_csrf_pair();
+    let (other_cookie, other_form) = generate_csrf_pair();
+    assert!(!verify_csrf(&cookie_value, &other_form));
+    assert!(!verify_csrf(&other_cookie, &form_value));
+
+    let mut tampered = form_value.clone().into_bytes();
+    let last = tampered.len() - 2;
+    tampered[last] = if tampered[last] == b'A' { b'B' } else { b'A' };
+    assert!(!verify_csrf(
+        &cookie_value,
+        &String::from_utf8(tampered).unwrap()
+    ));
+
+    assert!(!verify_csrf(&cookie_value, &cookie_value));
+    assert!(!verify_csrf(&cookie_value, ""));
+    assert!(!verify_csrf("", &form_value));
+    assert!(!verify_csrf(&cookie_value, "not base64!"));
+}
+
+// --- GitHub App Tests ---
+
+/// Starts a mock GitHub API answering installation token requests, returning its base URL
+/// and the number of requests it received.
+#[cfg(feature = "github-app")]
+async fn mock_github_api(
+    expires_in: chrono::Duration,
+) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else {
+                return;
+            };
+            let mut buffer = vec![0u8; 8192];
+            let read = socket.read(&mut buffer).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+            let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+
+            let authorized = request.lines().any(|line| {
+                line.to_ascii_lowercase()
+                    .starts_with("authorization: bearer ")
+            });
+            let (status, body) = if !request.starts_with("POST /app/installations/42/access_tokens")
+            {
+                ("404 Not Found", r#"{"message":"Not Found"}"#.to_string())
+            } else if !authorized {
+                (
+                    "401 Unauthorized",
+                    r#"{"message":"Bad credentials"}"#.to_string(),
+                )
+            } else {
+                let expires_at = (chrono::Utc::now() + expires_in).to_rfc3339();
+                (
+                    "201 Created",
+                    format!(r#"{{"token":"ghs_token_{n}","expires_at":"{expires_at}"}}"#),
+                )
+            };
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+            let _ = socket.shutdown().await;
+        }
+    });
+    (base, requests)
+}
+
+#[cfg(feature = "github-app")]
+#[tokio::test]
+/// Tests that installation tokens are fetched once and served from the cache until near expiry.
+async fn test_github_app_installation_token_is_cached() {
+    use narangcia_cryptic::core::oauth::github_app::GitHubAppTokenService;
+    use std::sync::atomic::Ordering;
+
+    let (base, requests) = mock_github_api(chrono::Duration::hours(1)).await;
+    let service = GitHubAppTokenService::new("12345", TEST_RSA_PRIVATE_KEY.as_bytes())
+        .unwrap()
+        .with_api_base(base);
+
+    let first = service.installation_token(42).await.unwrap();
+    assert_eq!(first.token, "ghs_token_1");
+    assert_eq!(first.installation_id, 42);
+    let second = service.installation_token(42).await.unwrap();
+    assert_eq!(second, first);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    service.invalidate(42);
+    let third = service.installation_token(42).await.unwrap();
+    assert_eq!(third.token, "ghs_token_2");
+}
+
+#[cfg(feature = "github-app")]
+#[tokio::test]
+/// Tests that tokens about to expire are refreshed and API errors are reported.
+async fn test_github_app_installation_token_refresh_and_errors() {
+    use narangcia_cryptic::core::oauth::github_app::GitHubAppTokenService;
+    use std::sync::atomic::Ordering;
+
+    let (base, requests) = mock_github_api(chrono::Duration::seconds(30)).await;
+    let service = GitHubAppTokenService::new("12345", TEST_RSA_PRIVATE_KEY.as_bytes())
+        .unwrap()
+        .with_api_base(base);
+
+    service.installation_token(42).await.unwrap();
+    let refreshed = service.installation_token(42).await.unwrap();
+    assert_eq!(refreshed.token, "ghs_token_2");
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+    assert!(!format!("{refreshed:?}").contains("ghs_token_2"));
+
+    assert!(matches!(
+        service.installation_token(7).await,
+        Err(narangcia_cryptic::AuthError::OAuthProvider(_))
+    ));
+    assert!(matches!(
+        GitHubAppTokenService::new("12345", b"not a key"),
+        Err(narangcia_cryptic::AuthError::ConfigError(_))
+    ));
+}
+
+// --- Configuration Validation Tests ---
+
+use narangcia_cryptic::core::vars::ConfigIssue;
+
+/// Returns the fields reported by `issues`.
+fn issue_fields(issues: &[ConfigIssue]) -> Vec<&str> {
+    issues.iter().map(|issue| issue.field.as_str()).collect()
+}
+
+#[test]
+/// Tests that coherent variables produce no issue.
+fn test_validate_variables_accepts_coherent_config() {
+    let vars = AuthServiceVariables {
+        secret_key: TEST_JWT_SECRET.to_string(),
+        token_expiration: 60,
+        refresh_token_expiration: 120,
+        argon2_params: TEST_ARGON2_PARAMS,
+        password_policy: Some(narangcia_cryptic::core::policy::PasswordPolicy::default()),
+        ..Default::default()
+    };
+    assert!(vars.validate().is_empty());
+}
+
+#[test]
+/// Tests that every problem of the variables is reported at once.
+fn test_validate_variables_reports_every_issue() {
+    let vars = AuthServiceVariables {
+        secret_key: "short".to_string(),
+        token_expiration: 600,
+        refresh_token_expiration: 300,
+        argon2_params: TEST_ARGON2_PARAMS,
+        password_policy: Some(narangcia_cryptic::core::policy::PasswordPolicy {
+            min_length: 4,
+            ..Default::default()
+        }),
+        email_otp_max_attempts: Some(0),
+        ..Default::default()
+    };
+    let issues = vars.validate();
+    assert_eq!(
+        issue_fields(&issues),
+        vec![
+            "secret_key",
+            "refresh_token_expiration",
+            "password_policy.min_length",
+            "email_otp_max_attempts",
+        ]
+    );
+    assert!(issues[1].to_string().contains("refresh TTL"));
+}
+
+#[test]
+/// Tests that placeholder secrets and zero lifetimes are reported.
+fn test_validate_variables_reports_placeholder_secret_and_zero_ttl() {
+    let vars = AuthServiceVariables {
+        secret_key: "changeme".to_string(),
+        token_expiration: 0,
+        refresh_token_expiration: 120,
+        argon2_params: TEST_ARGON2_PARAMS,
+        email_otp_ttl: Some(0),
+        ..Default::default()
+    };
+    let issues = vars.validate();
+    assert_eq!(
+        issue_fields(&issues),
+        vec!["secret_key", "token_expiration", "email_otp_ttl"]
+    );
+    assert!(issues[0].message.contains("placeholder"));
+}
+
+#[tokio::test]
+/// Tests that `AuthService::validate_configuration` combines variable and OAuth2 issues.
+async fn test_validate_configuration_reports_vars_and_oauth_issues() {
+    assert!(tenant_test_auth_service().validate_configuration().is_ok());
+
+    let mut configs = std::collections::HashMap::new();
+    configs.insert(OAuth2Provider::GitHub, test_oauth_config("", "not a url"));
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 120,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            oauth_configs: configs,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let issues = auth_service.validate_configuration().unwrap_err();
+    assert_eq!(
+        issue_fields(&issues),
+        vec!["refresh_token_expiration", "oauth_configs.github"]
+    );
+}
+
+// --- Claims Enrichment Tests ---
+
+use narangcia_cryptic::core::token::enricher::ClaimsEnricher;
+
+/// Adds the subscription tier of the user, and tries to override the subject.
+struct TierEnricher;
+
+#[async_trait::async_trait]
+impl ClaimsEnricher for TierEnricher {
+    async fn enrich(
+        &self,
+        user: &narangcia_cryptic::core::user::User,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, narangcia_cryptic::AuthError> {
+        let serde_json::Value::Object(claims) = serde_json::json!({
+            "tier": "pro",
+            "uid_len": user.id.as_str().len(),
+            "sub": "someone-else",
+        }) else {
+            unreachable!()
+        };
+        Ok(claims)
+    }
+}
+
+#[tokio::test]
+/// Tests that enriched claims appear in issued access tokens without overriding the subject.
+async fn test_claims_enricher_adds_custom_claims() {
+    let auth_service = tenant_test_auth_service().with_claims_enricher(Box::new(TierEnricher));
+    let (signup, login) = credentials_methods("enriched@example.com", "password");
+    let (user, _) = auth_service.signup(signup).await.unwrap();
+    let (_, tokens) = auth_service.login(login).await.unwrap();
+
+    let claims = auth_service
+        .validate_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims.get_subject(), user.id.as_str());
+    let custom = claims.get_custom_claims().unwrap();
+    assert_eq!(custom["tier"], "pro");
+    assert_eq!(custom["uid_len"], user.id.as_str().len());
+    assert!(!custom.contains_key("sub"));
+
+    let plain = auth_service.get_tokens(user.id.clone()).await.unwrap();
+    let plain_claims = auth_service
+        .validate_access_token(&plain.access_token)
+        .await
+        .unwrap();
+    assert!(plain_claims.get_custom_claims().unwrap().is_empty());
+}
+
+// --- OAuth2 Provider Error Tests ---
+
+#[cfg(feature = "oauth")]
+/// Starts a mock OAuth2 token endpoint answering every request with `status` and `body`,
+/// returning its URL.
+async fn mock_oauth_token_endpoint(status: &'static str, body: &'static str) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/token", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else {
+                return;
+            };
+            let mut buffer = vec![0u8; 8192];
+            let _ = socket.read(&mut buffer).await;
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+            let _ = socket.shutdown().await;
+        }
+    });
+    url
+}
+
+#[cfg(feature = "oauth")]
+/// Builds a manager for GitHub whose token endpoint answers with `status` and `body`.
+async fn oauth_manager_with_token_response(
+    status: &'static str,
+    body: &'static str,
+) -> OAuth2Manager {
+    let mut configs = std::collections::HashMap::new();
+    configs.insert(
+        OAuth2Provider::GitHub,
+        test_oauth_config("secret", "https://api.example.com/oauth/github/callback"),
+    );
+    OAuth2Manager::new(configs).with_token_url(
+        OAuth2Provider::GitHub,
+        mock_oauth_token_endpoint(status, body).await,
+    )
+}
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that a standard OAuth2 error response is exposed with its code and description.
+async fn test_exchange_code_maps_standard_error_response() {
+    use narangcia_cryptic::core::oauth::OAuth2Service;
+
+    let manager = oauth_manager_with_token_response(
+        "400 Bad Request",
+        r#"{"error":"invalid_grant","error_description":"Code already used","error_uri":"https://example.com/errors/invalid_grant"}"#,
+    )
+    .await;
+    let result = manager
+        .exchange_code_for_token(OAuth2Provider::GitHub, "used-code", "state")
+        .await;
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::OAuthProviderError { code, description, uri })
+            if code == "invalid_grant"
+                && description.as_deref() == Some("Code already used")
+                && uri.as_deref() == Some("https://example.com/errors/invalid_grant")
+    ));
+}
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that error bodies sent with a success status, as GitHub does, are also mapped.
+async fn test_exchange_code_maps_error_sent_with_success_status() {
+    use narangcia_cryptic::core::oauth::OAuth2Service;
+
+    let manager = oauth_manager_with_token_response(
+        "200 OK",
+        r#"{"error":"bad_verification_code","error_description":"The code passed is incorrect or expired."}"#,
+    )
+    .await;
+    let result = manager
+        .exchange_code_for_token(OAuth2Provider::GitHub, "expired-code", "state")
+        .await;
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::OAuthProviderError { code, uri: None, .. })
+            if code == "bad_verification_code"
+    ));
+}
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that responses without an OAuth2 error body still fail as token exchange errors.
+async fn test_exchange_code_keeps_unstructured_errors() {
+    use narangcia_cryptic::core::oauth::OAuth2Service;
+
+    let manager =
+        oauth_manager_with_token_response("500 Internal Server Error", "upstream unavailable")
+            .await;
+    let result = manager
+        .exchange_code_for_token(OAuth2Provider::GitHub, "code", "state")
+        .await;
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::OAuthTokenExchange(_))
+    ));
+}
+
+// --- API Key Tests ---
+
+#[tokio::test]
+/// Tests creating, listing and revoking API keys, and that revoked keys no longer verify.
+async fn test_api_key_create_list_revoke() {
+    let auth_service = tenant_test_auth_service();
+    let (signup, _) = credentials_methods("keys@example.com", "password");
+    let (user, _) = auth_service.signup(signup).await.unwrap();
+
+    let (ci_key, ci_info) = auth_service
+        .create_api_key(user.id.as_str(), "ci-deploy")
+        .await
+        .unwrap();
+    let (backup_key, _) = auth_service
+        .create_api_key(user.id.as_str(), "backup")
+        .await
+        .unwrap();
+    assert!(ci_key.starts_with("ck_"));
+    assert!(ci_info.last_used_at.is_none());
+
+    // Only a keyed SHA-256 tag of the key is stored
+    let record = auth_service
+        .api_keys
+        .get(&ci_info.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.secret_hash.len(), 43);
+    assert!(!ci_key.contains(&record.secret_hash));
+
+    let verified = auth_service.verify_api_key(&ci_key).await.unwrap();
+    assert_eq!(verified.id, user.id);
+
+    let keys = auth_service.list_api_keys(user.id.as_str()).await.unwrap();
+    assert_eq!(keys.len(), 2);
+    let listed = keys.iter().find(|key| key.id == ci_info.id).unwrap();
+    assert_eq!(listed.label, "ci-deploy");
+    assert!(listed.last_used_at.is_some());
+
+    auth_service
+        .revoke_api_key(user.id.as_str(), &ci_info.id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        auth_service.verify_api_key(&ci_key).await,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+    assert!(auth_service.verify_api_key(&backup_key).await.is_ok());
+    assert!(matches!(
+        auth_service
+            .revoke_api_key(user.id.as_str(), &ci_info.id)
+            .await,
+        Err(narangcia_cryptic::AuthError::ApiKeyNotFound)
+    ));
+    assert_eq!(
+        auth_service
+            .list_api_keys(user.id.as_str())
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+/// Tests that tampered keys and keys revoked by another user are handled safely.
+async fn test_api_key_rejects_tampered_and_foreign_revocation() {
+    let auth_service = tenant_test_auth_service();
+    let (owner_signup, _) = credentials_methods("owner@example.com", "password");
+    let (other_signup, _) = credentials_methods("other@example.com", "password");
+    let (owner, _) = auth_service.signup(owner_signup).await.unwrap();
+    let (other, _) = auth_service.signup(other_signup).await.unwrap();
+
+    let (key, info) = auth_service
+        .create_api_key(owner.id.as_str(), "laptop")
+        .await
+        .unwrap();
+    let tampered = format!("{key}x");
+    for candidate in [tampered.as_str(), "ck_missing.secret", "not-a-key"] {
+        assert!(matches!(
+            auth_service.verify_api_key(candidate).await,
+            Err(narangcia_cryptic::AuthError::InvalidCredentials)
+        ));
+    }
+
+    assert!(matches!(
+        auth_service
+            .revoke_api_key(other.id.as_str(), &info.id)
+            .await,
+        Err(narangcia_cryptic::AuthError::ApiKeyNotFound)
+    ));
+    assert!(auth_service.verify_api_key(&key).await.is_ok());
+    assert!(matches!(
+        auth_service.create_api_key("missing-user", "x").await,
+        Err(narangcia_cryptic::AuthError::UserNotFound)
+    ));
+}
+
+// --- Enriched Refresh Tests ---
+
+/// Adds the roles held for each user in a shared directory.
+struct RoleDirectoryEnricher {
+    roles: std::sync::Arc<std::sync::Mutex<std::collections::HashMap<String, Vec<String>>>>,
+}
+
+#[async_trait::async_trait]
+impl ClaimsEnricher for RoleDirectoryEnricher {
+    async fn enrich(
+        &self,
+        user: &narangcia_cryptic::core::user::User,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, narangcia_cryptic::AuthError> {
+        let roles = self
+            .roles
+            .lock()
+            .unwrap()
+            .get(user.id.as_str())
+            .cloned()
+            .unwrap_or_default();
+        let mut claims = serde_json::Map::new();
+        claims.insert("roles".to_string(), serde_json::json!(roles));
+        Ok(claims)
+    }
+}
+
+#[tokio::test]
+/// Tests that roles survive an enriched refresh, reflecting their current value, while a
+/// plain refresh drops them.
+async fn test_refresh_access_token_enriched_keeps_roles() {
+    let roles = std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new()));
+    let auth_service =
+        tenant_test_auth_service().with_claims_enricher(Box::new(RoleDirectoryEnricher {
+            roles: roles.clone(),
+        }));
+    let (signup, login) = credentials_methods("roles@example.com", "password");
+    let (user, _) = auth_service.signup(signup).await.unwrap();
+    roles
+        .lock()
+        .unwrap()
+        .insert(user.id.to_string(), vec!["admin".to_string()]);
+    let (_, tokens) = auth_service.login(login).await.unwrap();
+
+    let refreshed = auth_service
+        .refresh_access_token_enriched(&tokens.refresh_token)
+        .await
+        .unwrap();
+    let claims = auth_service
+        .validate_access_token(&refreshed.access_token)
+        .await
+        .unwrap();
+    assert_eq!(
+        claims.get_custom_claims().unwrap()["roles"],
+        serde_json::json!(["admin"])
+    );
+    assert!(claims.get_session_id().is_some());
+
+    roles.lock().unwrap().insert(
+        user.id.to_string(),
+        vec!["admin".to_string(), "billing".to_string()],
+    );
+    let refreshed = auth_service
+        .refresh_access_token_enriched(&refreshed.refresh_token)
+        .await
+        .unwrap();
+    let claims = auth_service
+        .validate_access_token(&refreshed.access_token)
+        .await
+        .unwrap();
+    assert_eq!(
+        claims.get_custom_claims().unwrap()["roles"],
+        serde_json::json!(["admin", "billing"])
+    );
+
+    let plain = auth_service
+        .refresh_access_token(&tokens.refresh_token)
+        .await
+        .unwrap();
+    let plain_claims = auth_service
+        .validate_access_token(&plain.access_token)
+        .await
+        .unwrap();
+    assert!(
+        !plain_claims
+            .get_custom_claims()
+            .unwrap()
+            .contains_key("roles")
+    );
+
+    auth_service
+        .revoke_all_for_user(user.id.as_str())
+        .await
+        .unwrap();
+    assert!(
+        auth_service
+            .refresh_access_token_enriched(&refreshed.refresh_token)
+            .await
+            .is_err()
+    );
+}
+
+// --- OAuth2 Link Ownership Tests ---
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests that a provider identity linked to one user cannot be linked to another.
+async fn test_link_oauth_account_rejects_identity_of_another_user() {
+    let mock = MockOAuth2Service::new()
+        .with_user(
+            OAuth2Provider::GitHub,
+            "alice-code",
+            mock_oauth_user_info("gh-42", "alice@example.com"),
+        )
+        .with_user(
+            OAuth2Provider::GitHub,
+            "bob-code",
+            mock_oauth_user_info("gh-42", "alice@example.com"),
+        );
+    let auth_service = auth_service_with_mock_oauth(mock);
+    let (alice_signup, _) = credentials_methods("alice", "password");
+    let (bob_signup, _) = credentials_methods("bob", "password");
+    let (alice, _) = auth_service.signup(alice_signup).await.unwrap();
+    let (bob, _) = auth_service.signup(bob_signup).await.unwrap();
+
+    auth_service
+        .link_oauth_account(
+            alice.id.as_str(),
+            OAuth2Provider::GitHub,
+            "alice-code",
+            "state",
+        )
+        .await
+        .unwrap();
+    let result = auth_service
+        .link_oauth_account(bob.id.as_str(), OAuth2Provider::GitHub, "bob-code", "state")
+        .await;
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::OAuthAccountAlreadyLinked { other_user_id })
+            if other_user_id == alice.id
+    ));
+    let bob = auth_service
+        .persistent_users_manager
+        .get_user_by_id(&bob.id)
+        .await
+        .unwrap();
+    assert!(bob.oauth_accounts.is_empty());
+
+    // Linking the identity again to its owner is allowed
+    assert!(
+        auth_service
+            .link_oauth_account(
+                alice.id.as_str(),
+                OAuth2Provider::GitHub,
+                "bob-code",
+                "state"
+            )
+            .await
+            .is_ok()
+    );
+}
+
+// --- Opaque Refresh Token Tests ---
+
+use narangcia_cryptic::core::token::opaque::{ConcurrentRefreshPolicy, OpaqueRefreshTokenService};
+
+/// Builds an opaque refresh token service issuing JWT access tokens, with `policy`.
+fn opaque_token_service(
+    policy: ConcurrentRefreshPolicy,
+) -> std::sync::Arc<OpaqueRefreshTokenService> {
+    std::sync::Arc::new(
+        OpaqueRefreshTokenService::new(
+            Box::new(JwtTokenService::new(TEST_JWT_SECRET, 60, 120)),
+            120,
+        )
+        .with_concurrent_refresh_policy(policy),
+    )
+}
+
+/// Refreshes `refresh_token` from `count` concurrent tasks.
+async fn concurrent_opaque_refreshes(
+    service: &std::sync::Arc<OpaqueRefreshTokenService>,
+    refresh_token: &str,
+    count: usize,
+) -> Vec<Result<narangcia_cryptic::core::token::TokenPair, narangcia_cryptic::AuthError>> {
+    let tasks: Vec<_> = (0..count)
+        .map(|_| {
+            let service = service.clone();
+            let refresh_token = refresh_token.to_string();
+            tokio::spawn(async move { service.refresh_access_token(&refresh_token).await })
+        })
+        .collect();
+    let mut results = Vec::new();
+    for task in tasks {
+        results.push(task.await.unwrap());
+    }
+    results
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+/// Tests that with the reject policy, exactly one of concurrent refreshes succeeds.
+async fn test_opaque_refresh_concurrent_reject() {
+    let service = opaque_token_service(ConcurrentRefreshPolicy::Reject);
+    let pair = service.generate_token_pair("user-1").await.unwrap();
+
+    let results = concurrent_opaque_refreshes(&service, &pair.refresh_token, 8).await;
+    let successors: Vec<_> = results.iter().filter_map(|r| r.as_ref().ok()).collect();
+    assert_eq!(successors.len(), 1);
+    assert!(
+        results
+            .iter()
+            .filter_map(|r| r.as_ref().err())
+            .all(|e| matches!(e, narangcia_cryptic::AuthError::TokenReuseDetected))
+    );
+
+    // The successor works once, and the consumed token stays rejected
+    let successor = successors[0].clone();
+    assert!(
+        service
+            .refresh_access_token(&successor.refresh_token)
+            .await
+            .is_ok()
+    );
+    assert!(matches!(
+        service.validate_refresh_token(&pair.refresh_token).await,
+        Err(narangcia_cryptic::AuthError::TokenReuseDetected)
+    ));
+    assert!(matches!(
+        service.refresh_access_token("unknown").await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+/// Tests that with a grace window, concurrent refreshes share the same successor, and that
+/// reuse after the window is rejected.
+async fn test_opaque_refresh_concurrent_grace() {
+    let service = opaque_token_service(ConcurrentRefreshPolicy::Grace(
+        std::time::Duration::from_millis(300),
+    ));
+    let pair = service.generate_token_pair("user-1").await.unwrap();
+
+    let results = concurrent_opaque_refreshes(&service, &pair.refresh_token, 8).await;
+    let first = results[0].as_ref().unwrap().clone();
+    assert!(results.iter().all(|r| r.as_ref().ok() == Some(&first)));
+    assert_eq!(
+        service
+            .validate_access_token(&first.access_token)
+            .await
+            .unwrap()
+            .get_subject(),
+        "user-1"
+    );
+
+    tokio::time::sleep(std::time::Duration::from_millis(400)).await;
+    assert!(matches!(
+        service.refresh_access_token(&pair.refresh_token).await,
+        Err(narangcia_cryptic::AuthError::TokenReuseDetected)
+    ));
+    assert!(
+        service
+            .refresh_access_token(&first.refresh_token)
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+/// Tests that enriched refreshes consume opaque refresh tokens, so they cannot be reused.
+async fn test_opaque_refresh_token_consumed_by_enriched_refresh() {
+    let token_service = OpaqueRefreshTokenService::new(
+        Box::new(JwtTokenService::new(TEST_JWT_SECRET, 60, 120)),
+        120,
+    );
+    let service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            ..Default::default()
+        }),
+        None,
+        None,
+        Some(Box::new(token_service)),
+        None,
+    )
+    .unwrap();
+    let (signup, _) = credentials_methods("opaque@example.com", "password");
+    let (_, tokens) = service.signup(signup).await.unwrap();
+
+    let refreshed = service
+        .refresh_access_token_enriched(&tokens.refresh_token)
+        .await
+        .unwrap();
+    assert_ne!(refreshed.refresh_token, tokens.refresh_token);
+    assert!(matches!(
+        service
+            .refresh_access_token_enriched(&tokens.refresh_token)
+            .await,
+        Err(narangcia_cryptic::AuthError::TokenReuseDetected)
+    ));
+    assert!(matches!(
+        service.refresh_access_token(&tokens.refresh_token).await,
+        Err(narangcia_cryptic::AuthError::TokenReuseDetected)
+    ));
+    assert!(
+        service
+            .refresh_access_token(&refreshed.refresh_token)
+            .await
+            .is_ok()
+    );
+}
+
+// --- Per-User Rate Limit Tests ---
+fn rate_limited_auth_service(
+    user_rate_limit: Option<narangcia_cryptic::core::rate_limit::RateLimit>,
+    ip_rate_limit: Option<narangcia_cryptic::core::rate_limit::RateLimit>,
+) -> AuthService {
+    AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            user_rate_limit,
+            ip_rate_limit,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+/// Tests that the per-user limit caps the emails sent to one user, whichever IP asks, and
+/// leaves other users alone.
+async fn test_per_user_rate_limit() {
+    use narangcia_cryptic::core::rate_limit::RateLimit;
+
+    let auth_service = rate_limited_auth_service(
+        Some(RateLimit::new(2, 3600)),
+        Some(RateLimit::new(100, 3600)),
+    );
+    signup_otp_user(&auth_service, "limited@example.com").await;
+    signup_otp_user(&auth_service, "other@example.com").await;
+
+    for ip in ["203.0.113.1", "203.0.113.2"] {
+        auth_service.check_ip_rate_limit(ip).await.unwrap();
+        auth_service
+            .request_email_otp("limited@example.com")
+            .await
+            .unwrap();
+    }
+
+    auth_service
+        .check_ip_rate_limit("203.0.113.3")
+        .await
+        .unwrap();
+    match auth_service.request_email_otp("limited@example.com").await {
+        Err(narangcia_cryptic::AuthError::RateLimited { retry_after_secs }) => {
+            assert!(retry_after_secs > 0 && retry_after_secs <= 3600)
+        }
+        other => panic!("expected RateLimited, got {other:?}"),
+    }
+
+    assert!(
+        auth_service
+            .request_email_otp("other@example.com")
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+/// Tests that the per-IP limit is counted independently of the per-user limit and that
+/// unset limits are not enforced.
+async fn test_per_ip_rate_limit_independent() {
+    use narangcia_cryptic::core::rate_limit::RateLimit;
+
+    let auth_service = rate_limited_auth_service(None, Some(RateLimit::new(1, 3600)));
+    signup_otp_user(&auth_service, "ip@example.com").await;
+
+    auth_service
+        .check_ip_rate_limit("198.51.100.7")
+        .await
+        .unwrap();
+    assert!(matches!(
+        auth_service.check_ip_rate_limit("198.51.100.7").await,
+        Err(narangcia_cryptic::AuthError::RateLimited { .. })
+    ));
+    auth_service
+        .check_ip_rate_limit("198.51.100.8")
+        .await
+        .unwrap();
+
+    for _ in 0..3 {
+        auth_service
+            .request_email_otp("ip@example.com")
+            .await
+            .unwrap();
+    }
+}
+
+// --- Authenticate Request Tests ---
+
+#[tokio::test]
+/// Tests that `authenticate_request` returns the token's user together with its claims.
+async fn test_authenticate_request_returns_user_and_claims() {
+    let auth_service = tenant_test_auth_service().with_claims_enricher(Box::new(TierEnricher));
+    let (signup, login) = credentials_methods("middleware@example.com", "password");
+    let (user, _) = auth_service.signup(signup).await.unwrap();
+    let (_, tokens) = auth_service.login(login).await.unwrap();
+
+    let (authenticated, claims) = auth_service
+        .authenticate_request(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(authenticated.id, user.id);
+    assert_eq!(authenticated.created_at, user.created_at);
+    assert_eq!(claims.get_subject(), user.id.as_str());
+    assert!(claims.get_token_id().is_some());
+    assert_eq!(claims.get_custom_claims().unwrap()["tier"], "pro");
+
+    auth_service
+        .persistent_users_manager
+        .delete_user(&user.id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        auth_service
+            .authenticate_request(&tokens.access_token)
+            .await,
+        Err(narangcia_cryptic::AuthError::UserNotFound)
+    ));
+}
+
+// --- Token Cookie Tests ---
+
+#[tokio::test]
+/// Tests the attributes of rendered token cookies, including Max-Age derived from the JWT expirations.
+async fn test_token_pair_set_cookie_attributes() {
+    use narangcia_cryptic::core::token::TokenPair;
+    use narangcia_cryptic::core::token::cookie::{CookieOptions, SameSite};
+
+    let auth_service = tenant_test_auth_service();
+    let tokens = auth_service.get_tokens("cookie-user").await.unwrap();
+
+    let headers = tokens.to_set_cookie_headers(&CookieOptions::default());
+    assert_eq!(headers.len(), 2);
+    let access: Vec<&str> = headers[0].split("; ").collect();
+    assert_eq!(access[0], format!("access_token={}", tokens.access_token));
+    assert!(access.contains(&"Path=/"));
+    assert!(access.contains(&"HttpOnly"));
+    assert!(access.contains(&"Secure"));
+    assert!(access.contains(&"SameSite=Lax"));
+    let max_age: u64 = access
+        .iter()
+        .find_map(|attr| attr.strip_prefix("Max-Age="))
+        .unwrap()
+        .parse()
+        .unwrap();
+    let expected = auth_service.vars.token_expiration;
+    assert!(max_age <= expected && max_age + 5 >= expected);
+    assert!(headers[1].starts_with(&format!("refresh_token={}; Path=/;", tokens.refresh_token)));
+
+    let options = CookieOptions {
+        access_cookie_name: "at".to_string(),
+        refresh_cookie_name: "rt".to_string(),
+        refresh_path: Some("/auth/refresh".to_string()),
+        domain: Some("example.com".to_string()),
+        secure: false,
+        same_site: SameSite::None,
+        access_max_age: Some(30),
+        ..Default::default()
+    };
+    let headers = tokens.to_set_cookie_headers(&options);
+    assert_eq!(
+        headers[0],
+        format!(
+            "at={}; Path=/; Domain=example.com; Max-Age=30; HttpOnly; Secure; SameSite=None",
+            tokens.access_token
+        )
+    );
+    assert!(headers[1].contains("; Path=/auth/refresh;"));
+
+    // Opaque tokens carry no expiration and render session cookies
+    let opaque = TokenPair {
+        access_token: "opaque-access".to_string(),
+        refresh_token: "opaque-refresh".to_string(),
+    };
+    let headers = opaque.to_set_cookie_headers(&CookieOptions {
+        same_site: SameSite::Strict,
+        http_only: false,
+        ..Default::default()
+    });
+    assert_eq!(
+        headers[1],
+        "refresh_token=opaque-refresh; Path=/; Secure; SameSite=Strict"
+    );
+}
+
+#[test]
+/// Tests that token pairs are read back from a Cookie request header.
+fn test_token_pair_from_cookie_header() {
+    use narangcia_cryptic::core::token::TokenPair;
+    use narangcia_cryptic::core::token::cookie::{CookieOptions, cookie_value};
+
+    let options = CookieOptions::default();
+    let pair =
+        TokenPair::from_cookie_header("theme=dark; refresh_token=r.t; access_token=a.t", &options)
+            .unwrap();
+    assert_eq!(pair.access_token, "a.t");
+    assert_eq!(pair.refresh_token, "r.t");
+
+    assert_eq!(
+        TokenPair::from_cookie_header("access_token=a.t; refresh_token=", &options),
+        None
+    );
+    assert_eq!(
+        cookie_value("x_access_token=1; access_token=2", "access_token"),
+        Some("2")
+    );
+}
+
+// --- Signed OAuth2 State Tests ---
+
+const TEST_STATE_SECRET: &str = "oauth-state-secret-for-tests-0123456789";
+
+/// Extracts the `state` query parameter of an authorization URL.
+fn state_from_auth_url(url: &str) -> String {
+    let url = url::Url::parse(url).unwrap();
+    url.query_pairs()
+        .find(|(key, _)| key == "state")
+        .map(|(_, value)| value.into_owned())
+        .unwrap()
+}
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that a signed state is accepted for its provider by another instance sharing the
+/// secret, and rejected once tampered with or presented to another provider.
+async fn test_signed_oauth_state_round_trip_and_tampering() {
+    use narangcia_cryptic::core::oauth::OAuth2Service;
+
+    use narangcia_cryptic::core::oauth::state::SignedState;
+
+    let signer = || {
+        SignedState::new(
+            TEST_STATE_SECRET.as_bytes(),
+            std::time::Duration::from_secs(600),
+        )
+        .unwrap()
+    };
+    let issuer = oauth_manager_with_token_response(
+        "200 OK",
+        r#"{"access_token":"gho_signed","token_type":"bearer"}"#,
+    )
+    .await
+    .with_signed_state(signer());
+    let url = issuer
+        .generate_auth_url(OAuth2Provider::GitHub, "caller-nonce", None)
+        .await
+        .unwrap();
+    let state = state_from_auth_url(&url);
+    assert!(state.starts_with("caller-nonce."));
+    assert_eq!(
+        signer().verify(OAuth2Provider::GitHub, &state).unwrap(),
+        "caller-nonce"
+    );
+
+    let verifier = oauth_manager_with_token_response(
+        "200 OK",
+        r#"{"access_token":"gho_signed","token_type":"bearer"}"#,
+    )
+    .await
+    .with_signed_state(signer());
+    let token = verifier
+        .exchange_code_for_token(OAuth2Provider::GitHub, "code", &state)
+        .await
+        .unwrap();
+    assert_eq!(token.access_token, "gho_signed");
+
+    let tampered = state.replacen("caller-nonce", "attacker-nonce", 1);
+    for bad in [tampered.as_str(), "caller-nonce", "", "a.b.c"] {
+        assert!(matches!(
+            verifier
+                .exchange_code_for_token(OAuth2Provider::GitHub, "code", bad)
+                .await,
+            Err(narangcia_cryptic::AuthError::OAuthStateMismatch)
+        ));
+    }
+    assert!(matches!(
+        signer().verify(OAuth2Provider::Google, &state),
+        Err(narangcia_cryptic::AuthError::OAuthStateMismatch)
+    ));
+    assert!(SignedState::new(b"short", std::time::Duration::from_secs(600)).is_err());
+}
+
+#[tokio::test]
+/// Tests that an expired signed state is rejected, and that the service enables signed
+/// states from its configuration.
+async fn test_signed_oauth_state_expired() {
+    let mut configs = std::collections::HashMap::new();
+    configs.insert(
+        OAuth2Provider::GitHub,
+        test_oauth_config("secret", "https://api.example.com/oauth/github/callback"),
+    );
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            oauth_configs: configs,
+            oauth_state_secret: Some(TEST_STATE_SECRET.to_string()),
+            oauth_state_ttl: Some(1),
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let url = auth_service
+        .generate_oauth2_auth_url(OAuth2Provider::GitHub, "", None)
+        .await
+        .unwrap();
+    let state = state_from_auth_url(&url);
+    assert_eq!(state.split('.').count(), 3);
+
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    assert!(matches!(
+        auth_service
+            .exchange_oauth2_code_for_token(OAuth2Provider::GitHub, "code", &state)
+            .await,
+        Err(narangcia_cryptic::AuthError::OAuthStateMismatch)
+    ));
+}
+
+// --- Authentication Time and Methods Tests ---
+
+#[tokio::test]
+/// Tests that password and email OTP logins record `auth_time` and the matching `amr`, and
+/// that refreshes keep them.
+async fn test_amr_for_password_and_otp_logins() {
+    let auth_service = email_otp_auth_service(300);
+    let (signup, login) = credentials_methods("amr@example.com", "password123");
+    let (_, signup_tokens) = auth_service.signup(signup).await.unwrap();
+    let (_, tokens) = auth_service.login(login).await.unwrap();
+
+    for access_token in [&signup_tokens.access_token, &tokens.access_token] {
+        let claims = auth_service
+            .validate_access_token(access_token)
+            .await
+            .unwrap();
+        assert_eq!(claims.get_amr(), ["pwd".to_string()]);
+    }
+    let claims = auth_service
+        .validate_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+    let auth_time = claims.get_auth_time().unwrap();
+    assert!(auth_time.abs_diff(claims.get_issued_at().unwrap()) <= 1);
+
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    let refreshed = auth_service
+        .refresh_access_token(&tokens.refresh_token)
+        .await
+        .unwrap();
+    let refreshed_claims = auth_service
+        .validate_access_token(&refreshed.access_token)
+        .await
+        .unwrap();
+    assert_eq!(refreshed_claims.get_auth_time(), Some(auth_time));
+    assert_eq!(refreshed_claims.get_amr(), ["pwd".to_string()]);
+    assert!(refreshed_claims.get_issued_at().unwrap() > auth_time);
+
+    let code = auth_service
+        .request_email_otp("amr@example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    let (_, otp_tokens) = auth_service
+        .login_with_email_otp("amr@example.com", &code)
+        .await
+        .unwrap();
+    let otp_claims = auth_service
+        .validate_access_token(&otp_tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(otp_claims.get_amr(), ["otp".to_string()]);
+    assert!(otp_claims.get_auth_time().is_some());
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests that OAuth2 logins record the provider in `amr`.
+async fn test_amr_for_oauth_login() {
+    let mock = MockOAuth2Service::new().with_user(
+        OAuth2Provider::GitHub,
+        "amr-code",
+        mock_oauth_user_info("gh-amr", "amr-oauth@example.com"),
+    );
+    let auth_service = auth_service_with_mock_oauth(mock);
+
+    let (_, tokens) = auth_service
+        .login(narangcia_cryptic::auth_service::LoginMethod::OAuth2 {
+            provider: OAuth2Provider::GitHub,
+            code: "amr-code".to_string(),
+            state: "state".to_string(),
+        })
+        .await
+        .unwrap();
+    let claims = auth_service
+        .validate_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims.get_amr(), ["oauth:github".to_string()]);
+    assert!(claims.get_auth_time().is_some());
+
+    // Tokens issued without a login carry neither claim
+    let plain = auth_service.get_tokens("no-login").await.unwrap();
+    let plain_claims = auth_service
+        .validate_access_token(&plain.access_token)
+        .await
+        .unwrap();
+    assert!(plain_claims.get_amr().is_empty());
+    assert_eq!(plain_claims.get_auth_time(), None);
+}
+
+// --- Step-Up Authentication Tests ---
+
+#[tokio::test]
+/// Tests that `require_recent_auth` accepts fresh logins and rejects old or login-less tokens.
+async fn test_require_recent_auth() {
+    let auth_service = tenant_test_auth_service();
+    let (signup, login) = credentials_methods("stepup@example.com", "password");
+    auth_service.signup(signup).await.unwrap();
+    let (_, tokens) = auth_service.login(login).await.unwrap();
+
+    let claims = auth_service
+        .require_recent_auth(&tokens.access_token, std::time::Duration::from_secs(300))
+        .await
+        .unwrap();
+    assert!(claims.get_auth_time().is_some());
+
+    tokio::time::sleep(std::time::Duration::from_millis(2100)).await;
+    assert!(matches!(
+        auth_service
+            .require_recent_auth(&tokens.access_token, std::time::Duration::from_secs(1))
+            .await,
+        Err(narangcia_cryptic::AuthError::StepUpRequired(_))
+    ));
+
+    let plain = auth_service.get_tokens("no-login").await.unwrap();
+    assert!(matches!(
+        auth_service
+            .require_recent_auth(&plain.access_token, std::time::Duration::from_secs(300))
+            .await,
+        Err(narangcia_cryptic::AuthError::StepUpRequired(_))
+    ));
+    assert!(matches!(
+        auth_service
+            .require_recent_auth("not-a-token", std::time::Duration::from_secs(300))
+            .await,
+        Err(e) if !matches!(e, narangcia_cryptic::AuthError::StepUpRequired(_))
+    ));
+}
+
+#[tokio::test]
+/// Tests that `require_amr` accepts tokens carrying every required method and names the
+/// missing ones otherwise.
+async fn test_require_amr() {
+    let auth_service = email_otp_auth_service(300);
+    let (signup, login) = credentials_methods("amr-stepup@example.com", "password123");
+    auth_service.signup(signup).await.unwrap();
+    let (_, password_tokens) = auth_service.login(login).await.unwrap();
+
+    assert!(
+        auth_service
+            .require_amr(&password_tokens.access_token, &["pwd"])
+            .await
+            .is_ok()
+    );
+    match auth_service
+        .require_amr(&password_tokens.access_token, &["pwd", "otp"])
+        .await
+    {
+        Err(narangcia_cryptic::AuthError::StepUpRequired(reason)) => {
+            assert!(reason.contains("otp") && !reason.contains("pwd"))
+        }
+        other => panic!("expected StepUpRequired, got {:?}", other.map(|_| ())),
+    }
+
+    let code = auth_service
+        .request_email_otp("amr-stepup@example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    let (_, otp_tokens) = auth_service
+        .login_with_email_otp("amr-stepup@example.com", &code)
+        .await
+        .unwrap();
+    assert!(
+        auth_service
+            .require_amr(&otp_tokens.access_token, &["otp"])
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+/// Tests that step-up checks only accept tokens issued for the tenant of the service.
+async fn test_step_up_rejects_cross_tenant_token() {
+    let auth_service = tenant_test_auth_service();
+    let acme = auth_service.for_tenant("acme");
+    let globex = auth_service.for_tenant("globex");
+    let (signup, login) = credentials_methods("stepup-tenant@example.com", "password");
+    acme.signup(signup).await.unwrap();
+    let (_, tokens) = acme.login(login).await.unwrap();
+    let token = &tokens.access_token;
+    let max_age = std::time::Duration::from_secs(300);
+
+    assert!(acme.require_recent_auth(token, max_age).await.is_ok());
+    assert!(acme.require_amr(token, &["pwd"]).await.is_ok());
+    assert!(matches!(
+        auth_service.require_recent_auth(token, max_age).await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    assert!(matches!(
+        auth_service.require_amr(token, &["pwd"]).await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    assert!(matches!(
+        globex.require_amr(token, &["pwd"]).await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+}
+
+// --- Username Generator Tests ---
+use narangcia_cryptic::core::credentials::UsernameGenerator;
+
+/// Builds an OAuth-only GitHub user with no email.
+fn no_email_oauth_user(user_id: &str) -> User {
+    let info = OAuth2UserInfo {
+        user_id: String::new(),
+        provider: OAuth2Provider::GitHub,
+        provider_user_id: format!("gh-{user_id}"),
+        email: None,
+        name: None,
+        avatar_url: None,
+        verified_email: None,
+        locale: None,
+        updated_at: chrono::Utc::now().naive_utc(),
+        raw_data: None,
+        granted_scopes: Vec::new(),
+    };
+    User::from_oauth(user_id.to_string(), info)
+}
+
+/// Proposes `taken` first, then `free-<attempt>`.
+struct CollidingUsernameGenerator;
+
+impl UsernameGenerator for CollidingUsernameGenerator {
+    fn generate(&self, _provider: OAuth2Provider, _info: &OAuth2UserInfo, attempt: u32) -> String {
+        if attempt == 0 {
+            "taken".to_string()
+        } else {
+            format!("free-{attempt}")
+        }
+    }
+}
+
+#[tokio::test]
+/// Tests that OAuth2 users without an email get distinct generated identifiers.
+async fn test_generated_usernames_are_unique() {
+    let repo = InMemoryUserRepo::new();
+    let ids: Vec<String> = (0..5).map(|i| format!("no-email-{i}")).collect();
+    for id in &ids {
+        repo.add_user(no_email_oauth_user(id)).await.unwrap();
+    }
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            ..Default::default()
+        }),
+        None,
+        Some(Box::new(repo)),
+        None,
+        None,
+    )
+    .unwrap();
+
+    let mut identifiers = std::collections::HashSet::new();
+    for id in &ids {
+        auth_service
+            .set_password(id.as_str(), "password123", false)
+            .await
+            .unwrap();
+        let user = auth_service
+            .persistent_users_manager
+            .get_user_by_id(&id.as_str().into())
+            .await
+            .unwrap();
+        let identifier = user.credentials.unwrap().identifier;
+        assert!(identifier.starts_with("github_"));
+        assert!(identifiers.insert(identifier));
+    }
+    assert_eq!(identifiers.len(), ids.len());
+}
+
+#[tokio::test]
+/// Tests that a generated identifier already used by another user is skipped.
+async fn test_generated_username_skips_taken_identifier() {
+    let auth_service =
+        tenant_test_auth_service().with_username_generator(Box::new(CollidingUsernameGenerator));
+    let (signup, _) = credentials_methods("taken", "password");
+    auth_service.signup(signup).await.unwrap();
+    auth_service
+        .persistent_users_manager
+        .add_user(no_email_oauth_user("colliding"))
+        .await
+        .unwrap();
+
+    auth_service
+        .set_password("colliding", "password123", false)
+        .await
+        .unwrap();
+    let user = auth_service
+        .persistent_users_manager
+        .get_user_by_identifier("free-1")
+        .await
+        .unwrap();
+    assert_eq!(user.id.as_str(), "colliding");
+}
+
+// --- Audit Log Tests ---
+use narangcia_cryptic::core::audit::{AuditEvent, AuditLog};
+
+/// Keeps recorded audit events in memory.
+#[derive(Clone, Default)]
+struct RecordingAuditLog(std::sync::Arc<std::sync::Mutex<Vec<AuditEvent>>>);
+
+impl RecordingAuditLog {
+    fn events(&self) -> Vec<AuditEvent> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl AuditLog for RecordingAuditLog {
+    fn record(&self, event: AuditEvent) {
+        self.0.lock().unwrap().push(event);
+    }
+}
+
+#[tokio::test]
+/// Tests that successful and failed password logins are audited without the password.
+async fn test_audit_log_records_logins() {
+    let audit_log = RecordingAuditLog::default();
+    let auth_service = tenant_test_auth_service().with_audit_log(Box::new(audit_log.clone()));
+    let (signup, login) = credentials_methods("audited@example.com", "password123");
+    let user = auth_service.signup(signup).await.unwrap().0;
+    auth_service.login(login).await.unwrap();
+    let (_, wrong_login) = credentials_methods("audited@example.com", "wrong-password");
+    assert!(auth_service.login(wrong_login).await.is_err());
+
+    let events = audit_log.events();
+    assert_eq!(
+        events,
+        vec![
+            AuditEvent::LoginSucceeded {
+                user_id: auth_service.log_id(&user.id),
+                method: "pwd".to_string(),
+            },
+            AuditEvent::LoginFailed {
+                identifier: Some("audited@example.com".to_string()),
+                method: "pwd".to_string(),
+                reason: "invalid_credentials".to_string(),
+            },
+        ]
+    );
+    let serialized = serde_json::to_string(&events[1]).unwrap();
+    assert!(serialized.contains("\"event\":\"login_failed\""));
+    assert!(!serialized.contains("wrong-password"));
+    // Users are identified by their pseudonym, never by their ID
+    let serialized = serde_json::to_string(&events[0]).unwrap();
+    assert!(!serialized.contains(user.id.as_str()));
+}
+
+#[tokio::test]
+/// Tests that exhausting the attempts of an email OTP records a lockout.
+async fn test_audit_log_records_otp_lockout() {
+    let audit_log = RecordingAuditLog::default();
+    let auth_service = email_otp_auth_service(300).with_audit_log(Box::new(audit_log.clone()));
+    let user = signup_otp_user(&auth_service, "locked@example.com").await;
+    auth_service
+        .request_email_otp("locked@example.com")
+        .await
+        .unwrap();
+    for _ in 0..4 {
+        assert!(
+            auth_service
+                .login_with_email_otp("locked@example.com", "not-a-code")
+                .await
+                .is_err()
+        );
+    }
+
+    let events = audit_log.events();
+    let lockouts = events
+        .iter()
+        .filter(|event| {
+            **event
+                == AuditEvent::LockedOut {
+                    user_id: auth_service.log_id(&user.id),
+                }
+        })
+        .count();
+    assert_eq!(lockouts, 1);
+    assert_eq!(
+        events.last(),
+        Some(&AuditEvent::LoginFailed {
+            identifier: Some("locked@example.com".to_string()),
+            method: "otp".to_string(),
+            reason: "locked_out".to_string(),
+        })
+    );
+}
+
+// --- Prepared Credentials Tests ---
+
+#[tokio::test]
+/// Tests that prepared credentials can be stored later and used to log in.
+async fn test_prepare_credentials_then_insert() {
+    let auth_service = tenant_test_auth_service();
+    let credentials = auth_service
+        .prepare_credentials("staged@example.com", "password123")
+        .await
+        .unwrap();
+    assert_eq!(credentials.identifier, "staged@example.com");
+    assert_ne!(credentials.password_hash, "password123");
+    assert!(
+        auth_service
+            .persistent_users_manager
+            .get_user_by_identifier("staged@example.com")
+            .await
+            .is_none()
+    );
+
+    let user_id = credentials.user_id.clone();
+    auth_service
+        .persistent_users_manager
+        .add_user(User::new(user_id.clone(), credentials))
+        .await
+        .unwrap();
+    let (_, login) = credentials_methods("staged@example.com", "password123");
+    let (user, _) = auth_service.login(login).await.unwrap();
+    assert_eq!(user.id.as_str(), user_id);
+}
+
+// --- Linked Providers Claim Tests ---
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests that the `linked_providers` claim reflects the providers linked at each login.
+async fn test_linked_providers_claim_follows_links() {
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            linked_providers_claim: true,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    let (signup, login) = credentials_methods("linked@example.com", "password123");
+    let (user, tokens) = auth_service.signup(signup).await.unwrap();
+    let claims = auth_service
+        .validate_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(
+        claims.get_custom_claims().unwrap()["linked_providers"],
+        serde_json::json!([])
+    );
+
+    let mut google = mock_oauth_user_info("google-linked", "linked@example.com");
+    google.provider = OAuth2Provider::Google;
+    let user = user
+        .link_oauth_account(mock_oauth_user_info("gh-linked", "linked@example.com"))
+        .link_oauth_account(google);
+    auth_service
+        .persistent_users_manager
+        .update_user(&user)
+        .await
+        .unwrap();
+
+    let (_, tokens) = auth_service.login(login).await.unwrap();
+    let claims = auth_service
+        .validate_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(
+        claims.get_custom_claims().unwrap()["linked_providers"],
+        serde_json::json!(["google", "github"])
+    );
+}
+
+// --- Linked Provider Limit Tests ---
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests that linking a provider beyond `max_linked_providers` is refused, explicitly and by email.
+async fn test_max_linked_providers_is_enforced() {
+    let mock = MockOAuth2Service::new()
+        .with_user(
+            OAuth2Provider::GitHub,
+            "gh-code",
+            mock_oauth_user_info("gh-capped", "capped@example.com"),
+        )
+        .with_user(
+            OAuth2Provider::GitHub,
+            "gh-code-again",
+            mock_oauth_user_info("gh-capped", "capped@example.com"),
+        )
+        .with_user(
+            OAuth2Provider::Google,
+            "google-code",
+            mock_oauth_user_info("google-capped", "capped@example.com"),
+        );
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            max_linked_providers: Some(1),
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        Some(Box::new(mock)),
+    )
+    .unwrap();
+    let (signup, _) = credentials_methods("capped@example.com", "password123");
+    let user = auth_service.signup(signup).await.unwrap().0;
+
+    auth_service
+        .link_oauth_account(user.id.as_str(), OAuth2Provider::GitHub, "gh-code", "state")
+        .await
+        .unwrap();
+    // Refreshing the already linked provider stays allowed
+    auth_service
+        .link_oauth_account(
+            user.id.as_str(),
+            OAuth2Provider::GitHub,
+            "gh-code-again",
+            "state",
+        )
+        .await
+        .unwrap();
+
+    let login = narangcia_cryptic::auth_service::LoginMethod::OAuth2 {
+        provider: OAuth2Provider::Google,
+        code: "google-code".to_string(),
+        state: "state".to_string(),
+    };
+    assert!(matches!(
+        auth_service.login(login).await,
+        Err(narangcia_cryptic::AuthError::TooManyLinkedAccounts { max: 1 })
+    ));
+    let stored = auth_service
+        .persistent_users_manager
+        .get_user_by_id(&user.id)
+        .await
+        .unwrap();
+    assert_eq!(stored.oauth_accounts.len(), 1);
+}
+
+// --- Unlink Guard Tests ---
+
+#[tokio::test]
+/// Tests that the only provider of a user without a password is only unlinked when forced.
+async fn test_unlink_last_provider_requires_force() {
+    let auth_service = tenant_test_auth_service();
+    auth_service
+        .persistent_users_manager
+        .add_user(no_email_oauth_user("only-github"))
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        auth_service
+            .unlink_oauth_account("only-github", OAuth2Provider::GitHub, false)
+            .await,
+        Err(narangcia_cryptic::AuthError::CannotRemoveLastCredential)
+    ));
+    let user = auth_service
+        .unlink_oauth_account("only-github", OAuth2Provider::GitHub, true)
+        .await
+        .unwrap();
+    assert!(user.oauth_accounts.is_empty());
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests that a provider can be unlinked when the user keeps a password to log in with.
+async fn test_unlink_provider_with_password_fallback() {
+    let auth_service = tenant_test_auth_service();
+    let (signup, login) = credentials_methods("fallback@example.com", "password123");
+    let user = auth_service.signup(signup).await.unwrap().0;
+    let user = user.link_oauth_account(mock_oauth_user_info("gh-fallback", "fallback@example.com"));
+    auth_service
+        .persistent_users_manager
+        .update_user(&user)
+        .await
+        .unwrap();
+
+    let user = auth_service
+        .unlink_oauth_account(user.id.as_str(), OAuth2Provider::GitHub, false)
+        .await
+        .unwrap();
+    assert!(user.oauth_accounts.is_empty());
+    assert!(auth_service.login(login).await.is_ok());
+}
+
+// --- Remember Me Tests ---
+
+#[tokio::test]
+/// Tests that "remember me" logins get the extended refresh lifetime, kept across refreshes.
+async fn test_remember_me_extends_refresh_token() {
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            remember_me_refresh_token_expiration: Some(3600),
+            argon2_params: TEST_ARGON2_PARAMS,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    let (signup, _) = credentials_methods("remember@example.com", "password123");
+    auth_service.signup(signup).await.unwrap();
+    let login = || narangcia_cryptic::auth_service::LoginMethod::Credentials {
+        identifier: "remember@example.com".to_string(),
+        password: "password123".to_string(),
+    };
+    let lifetimes = |claims: &dyn narangcia_cryptic::core::token::claims::Claims| {
+        claims.get_expiration() - claims.get_issued_at().unwrap()
+    };
+
+    let (_, normal) = auth_service.login(login()).await.unwrap();
+    let (_, remembered) = auth_service.login_remember_me(login()).await.unwrap();
+    let normal_claims = auth_service
+        .validate_refresh_token(&normal.refresh_token)
+        .await
+        .unwrap();
+    let remembered_claims = auth_service
+        .validate_refresh_token(&remembered.refresh_token)
+        .await
+        .unwrap();
+    assert_eq!(lifetimes(normal_claims.as_ref()), 120);
+    assert_eq!(lifetimes(remembered_claims.as_ref()), 3600);
+    assert_eq!(normal_claims.get_refresh_expiration(), None);
+    assert_eq!(remembered_claims.get_refresh_expiration(), Some(3600));
+
+    // Access tokens keep the default lifetime
+    let access_claims = auth_service
+        .validate_access_token(&remembered.access_token)
+        .await
+        .unwrap();
+    assert_eq!(lifetimes(access_claims.as_ref()), 60);
+
+    let refreshed = auth_service
+        .refresh_access_token(&remembered.refresh_token)
+        .await
+        .unwrap();
+    let refreshed_claims = auth_service
+        .validate_refresh_token(&refreshed.refresh_token)
+        .await
+        .unwrap();
+    assert_eq!(lifetimes(refreshed_claims.as_ref()), 3600);
+    let enriched = auth_service
+        .refresh_access_token_enriched(&refreshed.refresh_token)
+        .await
+        .unwrap();
+    let enriched_claims = auth_service
+        .validate_refresh_token(&enriched.refresh_token)
+        .await
+        .unwrap();
+    assert_eq!(lifetimes(enriched_claims.as_ref()), 3600);
+}
+
+// --- Offline Verifier Tests ---
+use narangcia_cryptic::core::token::offline::OfflineVerifier;
+
+/// A test-only RSA key pair signing RS256 tokens in tests.
+const TEST_RSA_PRIVATE_KEY: &str = include_str!("fixtures/keys/test_rsa_private.pem");
+/// The public half of [`TEST_RSA_PRIVATE_KEY`].
+const TEST_RSA_PUBLIC_KEY: &str = include_str!("fixtures/keys/test_rsa_public.pem");
+/// A test-only P-256 key pair signing ES256 tokens in tests.
+const TEST_EC_PRIVATE_KEY: &str = include_str!("fixtures/keys/test_ec_private.pem");
+/// The public half of [`TEST_EC_PRIVATE_KEY`].
+const TEST_EC_PUBLIC_KEY: &str = include_str!("fixtures/keys/test_ec_public.pem");
+
+/// The JWKS publishing the public keys of [`TEST_RSA_PUBLIC_KEY`] and [`TEST_EC_PUBLIC_KEY`].
+const TEST_JWKS: &str = r#"{"keys":[
+    {"kty":"RSA","kid":"rsa-1","alg":"RS256","use":"sig","n":"zRAq62cu8ZwtNHFZs4fyo4T2QBXbFSRnLNhQ5aFvcoAWtPk9WdQxPqRF11t0dDr3586ebC9EJJHoczWXz-GdVINyApcR2-0ItPLS-okrPqqppkNR3rOksXe-cVmCSieivxO1zVKqJp9AUxwVVFnt2eDAR5m2NKTyPfrumWMpq4ll8cd7ZZwPa0eb8Iavrv9aeJw6gQfgClVS9sxISjhR_2mCNAEo9dd-xvRFgiu-3OTBbX7hBnTvtevT4cvuEAzfX-lIFhR3LEy1sEEM9jWtT0s6iHbQ67NMef-85gNd3-2fQXiQzq1m0wDfGX7iEPuU79_81ne_5A_gWvcMudPG8w","e":"AQAB"},
+    {"kty":"EC","kid":"ec-1","crv":"P-256","x":"HDeNcivPxdqq3m2_fsApGk_oNg8HNH7H3f7H3ZNWP0E","y":"n0yrPh6_U8K8nSdnNHtn0wf8xjiiyODYRwc5n5BC2ik"},
+    {"kty":"oct","kid":"hmac-1","k":"c2VjcmV0"}
+]}"#;
+
+#[tokio::test]
+/// Tests that RS256 tokens are validated offline, enforcing issuer, audience and token type.
+async fn test_offline_verifier_validates_rs256_tokens() {
+    let issuer = JwtTokenService::from_rsa_pem(
+        TEST_RSA_PRIVATE_KEY.as_bytes(),
+        TEST_RSA_PUBLIC_KEY.as_bytes(),
+        60,
+        120,
+    )
+    .unwrap()
+    .with_kid("rsa-1")
+    .with_issuer("https://auth.example.com")
+    .with_audience("billing");
+    let tokens = issuer.generate_token_pair("user-rsa").await.unwrap();
+
+    let verifier = OfflineVerifier::from_jwks(TEST_JWKS)
+        .unwrap()
+        .with_issuer("https://auth.example.com")
+        .with_audience("billing");
+    let claims = verifier.validate(&tokens.access_token).unwrap();
+    assert_eq!(claims.sub, "user-rsa");
+    assert_eq!(claims.iss.as_deref(), Some("https://auth.example.com"));
+    assert!(matches!(
+        verifier.validate(&tokens.refresh_token),
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+
+    let other_issuer = OfflineVerifier::from_jwks(TEST_JWKS)
+        .unwrap()
+        .with_issuer("https://other.example.com");
+    assert!(other_issuer.validate(&tokens.access_token).is_err());
+    let other_audience = OfflineVerifier::from_jwks(TEST_JWKS)
+        .unwrap()
+        .with_audience("reports");
+    assert!(other_audience.validate(&tokens.access_token).is_err());
+
+    // HMAC tokens cannot be verified with published keys
+    let hmac_tokens = JwtTokenService::new(TEST_JWT_SECRET, 60, 120)
+        .generate_token_pair("user-rsa")
+        .await
+        .unwrap();
+    assert!(verifier.validate(&hmac_tokens.access_token).is_err());
+}
+
+#[tokio::test]
+/// Tests that ES256 tokens are validated offline, and that tampered ones are rejected.
+async fn test_offline_verifier_validates_es256_tokens() {
+    let verifier = OfflineVerifier::from_jwks(TEST_JWKS).unwrap();
+    let issuer = || {
+        JwtTokenService::from_ec_pem(
+            TEST_EC_PRIVATE_KEY.as_bytes(),
+            TEST_EC_PUBLIC_KEY.as_bytes(),
+            60,
+            120,
+        )
+        .unwrap()
+    };
+
+    // Tokens without a `kid` are tried against every key of their algorithm
+    let tokens = issuer().generate_token_pair("user-ec").await.unwrap();
+    assert_eq!(
+        verifier.validate(&tokens.access_token).unwrap().sub,
+        "user-ec"
+    );
+    // The signing service validates its own tokens with the public key
+    assert!(
+        issuer()
+            .validate_access_token(&tokens.access_token)
+            .await
+            .is_ok()
+    );
+
+    let mut tampered = tokens.access_token.clone();
+    tampered.replace_range(tampered.len() - 4.., "AAAA");
+    assert!(matches!(
+        verifier.validate(&tampered),
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+}
+
+// --- Storage Unavailable Tests ---
+
+/// An in-memory repository whose reads fail like a dropped connection while `down` is set,
+/// and whose updates always fail with `reject_updates`.
+struct FlakyUserRepo {
+    inner: InMemoryUserRepo,
+    down: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    reject_updates: bool,
+}
+
+impl FlakyUserRepo {
+    fn check(&self) -> Result<(), narangcia_cryptic::AuthError> {
+        if self.down.load(std::sync::atomic::Ordering::SeqCst) {
+            Err(narangcia_cryptic::AuthError::StorageUnavailable(
+                "connection reset".to_string(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl UserRepository for FlakyUserRepo {
+    async fn add_user(
+        &self,
+        user: narangcia_cryptic::core::user::User,
+    ) -> Result<narangcia_cryptic::core::user::User, narangcia_cryptic::AuthError> {
+        self.check()?;
+        self.inner.add_user(user).await
+    }
+
+    async fn get_user_by_id(
+        &self,
+        id: &narangcia_cryptic::core::user::UserId,
+    ) -> Option<narangcia_cryptic::core::user::User> {
+        self.find_user_by_id(id).await.ok().flatten()
+    }
+
+    async fn find_user_by_id(
+        &self,
+        id: &narangcia_cryptic::core::user::UserId,
+    ) -> Result<Option<narangcia_cryptic::core::user::User>, narangcia_cryptic::AuthError> {
+        self.check()?;
+        Ok(self.inner.get_user_by_id(id).await)
+    }
+
+    async fn get_user_by_identifier(
+        &self,
+        identifier: &str,
+    ) -> Option<narangcia_cryptic::core::user::User> {
+        self.check().ok()?;
+        self.inner.get_user_by_identifier(identifier).await
+    }
+
+    async fn find_user_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<narangcia_cryptic::core::user::User>, narangcia_cryptic::AuthError> {
+        self.check()?;
+        Ok(self
+            .inner
+            .get_user_by_identifier_in_tenant(identifier, tenant_id)
+            .await)
+    }
+
+    async fn get_credentials_by_identifier_in_tenant(
+        &self,
+        identifier: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<
+        Option<narangcia_cryptic::core::credentials::StoredCredentials>,
+        narangcia_cryptic::AuthError,
+    > {
+        self.check()?;
+        self.inner
+            .get_credentials_by_identifier_in_tenant(identifier, tenant_id)
+            .await
+    }
+
+    async fn update_user(
+        &self,
+        user: &narangcia_cryptic::core::user::User,
+    ) -> Result<(), narangcia_cryptic::AuthError> {
+        self.check()?;
+        if self.reject_updates {
+            return Err(narangcia_cryptic::AuthError::StorageUnavailable(
+                "read-only replica".to_string(),
+            ));
+        }
+        self.inner.update_user(user).await
+    }
+
+    async fn delete_user(
+        &self,
+        id: &narangcia_cryptic::core::user::UserId,
+    ) -> Result<(), narangcia_cryptic::AuthError> {
+        self.check()?;
+        self.inner.delete_user(id).await
+    }
+
+    async fn get_user_by_oauth_id(
+        &self,
+        provider: narangcia_cryptic::core::oauth::store::OAuth2Provider,
+        provider_user_id: &str,
+    ) -> Option<narangcia_cryptic::core::user::User> {
+        self.check().ok()?;
+        self.inner
+            .get_user_by_oauth_id(provider, provider_user_id)
+            .await
+    }
+}
+
+/// Builds a service over a [`FlakyUserRepo`], returning the flag taking the repository down.
+fn flaky_auth_service(
+    verify_user_status_on_validation: bool,
+) -> (AuthService, std::sync::Arc<std::sync::atomic::AtomicBool>) {
+    flaky_auth_service_with(AuthServiceVariables {
+        verify_user_status_on_validation,
+        ..Default::default()
+    })
+}
+
+/// Builds a service over a [`FlakyUserRepo`] with `vars`, adding a valid secret and cheap
+/// hashing parameters.
+fn flaky_auth_service_with(
+    vars: AuthServiceVariables,
+) -> (AuthService, std::sync::Arc<std::sync::atomic::AtomicBool>) {
+    let down = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            ..vars
+        }),
+        None,
+        Some(Box::new(FlakyUserRepo {
+            inner: InMemoryUserRepo::new(),
+            down: down.clone(),
+            reject_updates: false,
+        })),
+        None,
+        None,
+    )
+    .unwrap();
+    (auth_service, down)
+}
+
+#[tokio::test]
+/// Tests that logins report an unreachable repository instead of invalid credentials.
+async fn test_login_propagates_storage_unavailable() {
+    let (auth_service, down) = flaky_auth_service(false);
+    let (signup, login) = credentials_methods("flaky@example.com", "password123");
+    auth_service.signup(signup).await.unwrap();
+
+    down.store(true, std::sync::atomic::Ordering::SeqCst);
+    let result = auth_service.login(login.clone()).await;
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::StorageUnavailable(_))
+    ));
+
+    down.store(false, std::sync::atomic::Ordering::SeqCst);
+    assert!(auth_service.login(login).await.is_ok());
+}
+
+#[tokio::test]
+/// Tests that validations reading the user report an unreachable repository.
+async fn test_validate_propagates_storage_unavailable() {
+    let (auth_service, down) = flaky_auth_service(true);
+    let (signup, login) = credentials_methods("flaky-validate@example.com", "password123");
+    auth_service.signup(signup).await.unwrap();
+    let (_, tokens) = auth_service.login(login).await.unwrap();
+
+    down.store(true, std::sync::atomic::Ordering::SeqCst);
+    let result = auth_service
+        .validate_access_token(&tokens.access_token)
+        .await;
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::StorageUnavailable(_))
+    ));
+    let result = auth_service
+        .refresh_access_token(&tokens.refresh_token)
+        .await;
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::StorageUnavailable(_))
+    ));
+
+    down.store(false, std::sync::atomic::Ordering::SeqCst);
+    assert!(
+        auth_service
+            .validate_access_token(&tokens.access_token)
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+/// Tests that identifier lookups report an unreachable repository instead of an unknown user.
+async fn test_email_otp_propagates_storage_unavailable() {
+    let (auth_service, down) = flaky_auth_service_with(AuthServiceVariables {
+        email_otp_ttl: Some(300),
+        email_otp_max_attempts: Some(3),
+        ..Default::default()
+    });
+    signup_otp_user(&auth_service, "flaky-otp@example.com").await;
+
+    down.store(true, std::sync::atomic::Ordering::SeqCst);
+    let result = auth_service
+        .request_email_otp("flaky-otp@example.com")
+        .await;
+    assert!(matches!(
+        result,
+        Err(narangcia_cryptic::AuthError::StorageUnavailable(_))
+    ));
+
+    down.store(false, std::sync::atomic::Ordering::SeqCst);
+    assert!(
+        auth_service
+            .request_email_otp("flaky-otp@example.com")
+            .await
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[tokio::test]
+/// Tests that API key checks report an unreachable repository instead of invalid credentials.
+async fn test_api_key_propagates_storage_unavailable() {
+    let (auth_service, down) = flaky_auth_service(false);
+    let (signup, _) = credentials_methods("flaky-key@example.com", "password123");
+    let (user, _) = auth_service.signup(signup).await.unwrap();
+    let (api_key, _) = auth_service
+        .create_api_key(user.id.as_str(), "ci-deploy")
+        .await
+        .unwrap();
+
+    down.store(true, std::sync::atomic::Ordering::SeqCst);
+    assert!(matches!(
+        auth_service.verify_api_key(&api_key).await,
+        Err(narangcia_cryptic::AuthError::StorageUnavailable(_))
+    ));
+
+    down.store(false, std::sync::atomic::Ordering::SeqCst);
+    assert_eq!(
+        auth_service.verify_api_key(&api_key).await.unwrap().id,
+        user.id
+    );
+}
+
+#[tokio::test]
+/// Tests that OAuth2 logins of existing users succeed when the last login cannot be stored.
+async fn test_oauth_login_survives_failed_last_login_update() {
+    let service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            ..Default::default()
+        }),
+        None,
+        Some(Box::new(FlakyUserRepo {
+            inner: InMemoryUserRepo::new(),
+            down: Default::default(),
+            reject_updates: true,
+        })),
+        None,
+        None,
+    )
+    .unwrap();
+    let info = hand_built_google_user_info("google-read-only", "replica@example.com");
+    let (user, _) = service
+        .login_from_oauth_userinfo(info.clone())
+        .await
+        .unwrap();
+
+    let (again, tokens) = service.login_from_oauth_userinfo(info).await.unwrap();
+    assert_eq!(again.id, user.id);
+    assert_eq!(again.last_login_at, user.last_login_at);
+    assert!(!tokens.access_token.is_empty());
+}
+
+// --- Per-User Hash Target Tests ---
+
+#[tokio::test]
+/// Tests that logins rehash toward each user's target, and that scans compare against it.
+async fn test_per_user_hash_targets() {
+    let low = TEST_ARGON2_PARAMS;
+    let high = Argon2Params {
+        iterations: 2,
+        ..TEST_ARGON2_PARAMS
+    };
+    let low_manager = Argon2PasswordManager::with_params(low).unwrap();
+    let high_manager = Argon2PasswordManager::with_params(high).unwrap();
+
+    // Both users start with a low-cost hash; only the grandfathered one targets it
+    let repo = InMemoryUserRepo::new();
+    for (id, target) in [("grandfathered", Some(low)), ("regular", None)] {
+        let hash = low_manager.hash_password("password123").await.unwrap();
+        let mut user = User::new(
+            id.to_string(),
+            Credentials::new(id.to_string(), format!("{id}@example.com"), hash),
+        );
+        user.hash_target = target;
+        repo.add_user(user).await.unwrap();
+    }
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            ..Default::default()
+        }),
+        Some(Box::new(high_manager)),
+        Some(Box::new(repo)),
+        None,
+        None,
+    )
+    .unwrap();
+
+    let report = auth_service.scan_for_rehash().await.unwrap();
+    assert_eq!(report.needs_rehash, vec![UserId::from("regular")]);
+
+    let service = &auth_service;
+    let stored_hash = |id: &'static str| async move {
+        service
+            .persistent_users_manager
+            .get_user_by_id(&UserId::from(id))
+            .await
+            .unwrap()
+            .credentials
+            .unwrap()
+            .password_hash
+    };
+    let before = stored_hash("grandfathered").await;
+    for id in ["grandfathered", "regular"] {
+        auth_service
+            .login(narangcia_cryptic::auth_service::LoginMethod::Credentials {
+                identifier: format!("{id}@example.com"),
+                password: "password123".to_string(),
+            })
+            .await
+            .unwrap();
+    }
+    assert_eq!(stored_hash("grandfathered").await, before);
+    let regular = stored_hash("regular").await;
+    assert!(regular.contains(&high.to_string()));
+    assert!(
+        !auth_service
+            .password_manager
+            .needs_rehash_for(&regular, None)
+    );
+    assert!(
+        auth_service
+            .scan_for_rehash()
+            .await
+            .unwrap()
+            .needs_rehash
+            .is_empty()
+    );
+
+    // Raising the grandfathered user's target upgrades their hash on the next login
+    let mut user = auth_service
+        .persistent_users_manager
+        .get_user_by_id(&UserId::from("grandfathered"))
+        .await
+        .unwrap();
+    let raised = Argon2Params {
+        memory_kib: 2048,
+        ..TEST_ARGON2_PARAMS
+    };
+    user.hash_target = Some(raised);
+    auth_service
+        .persistent_users_manager
+        .update_user(&user)
+        .await
+        .unwrap();
+    assert_eq!(
+        auth_service.scan_for_rehash().await.unwrap().needs_rehash,
+        vec![UserId::from("grandfathered")]
+    );
+    auth_service
+        .login(narangcia_cryptic::auth_service::LoginMethod::Credentials {
+            identifier: "grandfathered@example.com".to_string(),
+            password: "password123".to_string(),
+        })
+        .await
+        .unwrap();
+    assert!(
+        stored_hash("grandfathered")
+            .await
+            .contains(&raised.to_string())
+    );
+
+    // A target above the service-wide parameters flags hashes that meet the latter
+    let mut user = auth_service
+        .persistent_users_manager
+        .get_user_by_id(&UserId::from("regular"))
+        .await
+        .unwrap();
+    user.hash_target = Some(Argon2Params {
+        iterations: 3,
+        ..high
+    });
+    auth_service
+        .persistent_users_manager
+        .update_user(&user)
+        .await
+        .unwrap();
+    assert_eq!(
+        auth_service.scan_for_rehash().await.unwrap().needs_rehash,
+        vec![UserId::from("regular")]
+    );
+}
+
+#[tokio::test]
+/// Tests that Argon2 parameters round-trip through their PHC representation.
+async fn test_argon2_params_phc_round_trip() {
+    let params = Argon2Params {
+        memory_kib: 19456,
+        iterations: 2,
+        parallelism: 1,
+    };
+    assert_eq!(params.to_string(), "m=19456,t=2,p=1");
+    assert_eq!("m=19456,t=2,p=1".parse::<Argon2Params>().unwrap(), params);
+    assert!("m=19456,t=2".parse::<Argon2Params>().is_err());
+    assert!("m=19456,t=2,p=x".parse::<Argon2Params>().is_err());
+}
+
+// --- Notifier Tests ---
+
+use narangcia_cryptic::core::notify::Notifier;
+
+/// Keeps sent notifications in memory as `<kind>:<user_id>` entries.
+#[derive(Clone, Default)]
+struct RecordingNotifier(std::sync::Arc<std::sync::Mutex<Vec<String>>>);
+
+impl RecordingNotifier {
+    fn sent(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn push(&self, entry: String) -> Result<(), narangcia_cryptic::AuthError> {
+        self.0.lock().unwrap().push(entry);
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Notifier for RecordingNotifier {
+    async fn notify_password_changed(
+        &self,
+        user: &User,
+    ) -> Result<(), narangcia_cryptic::AuthError> {
+        self.push(format!("password_changed:{}", user.id))
+    }
+
+    async fn notify_new_login(
+        &self,
+        user: &User,
+        method: &str,
+    ) -> Result<(), narangcia_cryptic::AuthError> {
+        self.push(format!("new_login:{}:{method}", user.id))
+    }
+
+    async fn notify_reset_requested(
+        &self,
+        user: &User,
+    ) -> Result<(), narangcia_cryptic::AuthError> {
+        self.push(format!("reset_requested:{}", user.id))
+    }
+}
+
+#[tokio::test]
+/// Tests that successful logins and password changes notify the user, and failed logins do not.
+async fn test_notifier_hooks_fire() {
+    let notifier = RecordingNotifier::default();
+    let auth_service = tenant_test_auth_service().with_notifier(Box::new(notifier.clone()));
+    let (signup, login) = credentials_methods("notified@example.com", "password123");
+    let user = auth_service.signup(signup).await.unwrap().0;
+    assert!(notifier.sent().is_empty());
+
+    let (_, tokens) = auth_service.login(login).await.unwrap();
+    let (_, wrong_login) = credentials_methods("notified@example.com", "wrong-password");
+    assert!(auth_service.login(wrong_login).await.is_err());
+    auth_service
+        .change_password(&tokens.access_token, "password123", "new-password456")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        notifier.sent(),
+        vec![
+            format!("new_login:{}:pwd", user.id),
+            format!("password_changed:{}", user.id),
+        ]
+    );
+}
+
+#[tokio::test]
+/// Tests that email one-time password logins notify the user with the `otp` method.
+async fn test_notifier_fires_on_email_otp_login() {
+    let notifier = RecordingNotifier::default();
+    let auth_service = email_otp_auth_service(300).with_notifier(Box::new(notifier.clone()));
+    let user = signup_otp_user(&auth_service, "otp-notified@example.com").await;
+    let code = auth_service
+        .request_email_otp("otp-notified@example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    auth_service
+        .login_with_email_otp("otp-notified@example.com", &code)
+        .await
+        .unwrap();
+    assert_eq!(notifier.sent(), vec![format!("new_login:{}:otp", user.id)]);
+}
+
+#[tokio::test]
+/// Tests that password reset requests notify known users only, and that reset tokens set the
+/// password once.
+async fn test_password_reset_notifies_and_sets_password() {
+    let notifier = RecordingNotifier::default();
+    let auth_service = tenant_test_auth_service().with_notifier(Box::new(notifier.clone()));
+    let (signup, login) = credentials_methods("reset@example.com", "password123");
+    let (user, tokens) = auth_service.signup(signup).await.unwrap();
+
+    assert!(
+        auth_service
+            .request_password_reset("nobody@example.com")
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(notifier.sent().is_empty());
+    let token = auth_service
+        .request_password_reset("reset@example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        notifier.sent(),
+        vec![format!("reset_requested:{}", user.id)]
+    );
+
+    assert!(matches!(
+        auth_service
+            .reset_password("reset@example.com", "not-the-token", "new-password456")
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    auth_service
+        .reset_password("reset@example.com", &token, "new-password456")
+        .await
+        .unwrap();
+    assert!(matches!(
+        auth_service
+            .reset_password("reset@example.com", &token, "other-password789")
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+
+    assert!(auth_service.login(login).await.is_err());
+    let (_, new_login) = credentials_methods("reset@example.com", "new-password456");
+    assert!(auth_service.login(new_login).await.is_ok());
+    assert!(
+        auth_service
+            .refresh_access_token(&tokens.refresh_token)
+            .await
+            .is_err()
+    );
+}
+
+// --- Identifier Availability Tests ---
+
+#[tokio::test]
+/// Tests that availability checks report taken and free identifiers, after normalization.
+async fn test_is_identifier_available() {
+    let auth_service =
+        tenant_test_auth_service().with_identifier_resolver(Box::new(PhoneNumberResolver));
+    let (signup, _) = credentials_methods("06 12 34 56 78", "password123");
+    auth_service.signup(signup).await.unwrap();
+
+    assert!(
+        !auth_service
+            .is_identifier_available("+33612345678", "203.0.113.1")
+            .await
+            .unwrap()
+    );
+    // Another spelling of the same number resolves to the taken identifier
+    assert!(
+        !auth_service
+            .is_identifier_available("06.12.34.56.78", "203.0.113.1")
+            .await
+            .unwrap()
+    );
+    assert!(
+        auth_service
+            .is_identifier_available("0698765432", "203.0.113.1")
+            .await
+            .unwrap()
+    );
+    assert!(matches!(
+        auth_service
+            .is_identifier_available("not-a-number", "203.0.113.1")
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+
+    // Identifiers are only taken within their tenant
+    let tenant = auth_service.for_tenant("acme");
+    assert!(
+        tenant
+            .is_identifier_available("0612345678", "203.0.113.1")
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+/// Tests that availability checks are rate limited per client, by default too.
+async fn test_is_identifier_available_rate_limited() {
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            availability_rate_limit: Some(narangcia_cryptic::core::rate_limit::RateLimit::new(
+                2, 60,
+            )),
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    for identifier in ["probe1@example.com", "probe2@example.com"] {
+        assert!(
+            auth_service
+                .is_identifier_available(identifier, "203.0.113.1")
+                .await
+                .unwrap()
+        );
+    }
+    assert!(matches!(
+        auth_service
+            .is_identifier_available("probe3@example.com", "203.0.113.1")
+            .await,
+        Err(narangcia_cryptic::AuthError::RateLimited { .. })
+    ));
+    // Other clients have their own budget
+    assert!(
+        auth_service
+            .is_identifier_available("probe3@example.com", "203.0.113.2")
+            .await
+            .unwrap()
+    );
+
+    let default_limited = tenant_test_auth_service();
+    let limit = narangcia_cryptic::core::rate_limit::DEFAULT_AVAILABILITY_RATE_LIMIT;
+    for _ in 0..limit.max_requests {
+        default_limited
+            .is_identifier_available("probe@example.com", "203.0.113.1")
+            .await
+            .unwrap();
+    }
+    assert!(matches!(
+        default_limited
+            .is_identifier_available("probe@example.com", "203.0.113.1")
+            .await,
+        Err(narangcia_cryptic::AuthError::RateLimited { .. })
+    ));
+}
+
+// --- Token Compression Tests ---
+
+/// Returns token options carrying many repetitive custom claims.
+#[cfg(feature = "jwt-compression")]
+fn large_claim_options() -> narangcia_cryptic::core::token::TokenOptions {
+    let mut options = narangcia_cryptic::core::token::TokenOptions::default();
+    let permissions: Vec<String> = (0..200)
+        .map(|i| format!("projects:project-{i}:read"))
+        .collect();
+    options
+        .custom_claims
+        .insert("permissions".to_string(), serde_json::json!(permissions));
+    options
+}
+
+/// Returns the decoded header of a JWT, as JSON.
+#[cfg(feature = "jwt-compression")]
+fn jwt_header_json(token: &str) -> serde_json::Value {
+    use base64::Engine;
+
+    let header = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(token.split('.').next().unwrap())
+        .unwrap();
+    serde_json::from_slice(&header).unwrap()
+}
+
+#[cfg(feature = "jwt-compression")]
+#[tokio::test]
+/// Tests that tokens with large claim sets are compressed, shrink, and validate back to the
+/// same claims.
+async fn test_jwt_compression_round_trip_shrinks_large_tokens() {
+    let options = large_claim_options();
+    let plain_service = JwtTokenService::new(TEST_JWT_SECRET, 60, 120);
+    let compressing_service = JwtTokenService::new(TEST_JWT_SECRET, 60, 120).with_compression(512);
+
+    let plain = plain_service
+        .generate_token_pair_with("big_user", &options)
+        .await
+        .unwrap();
+    let compressed = compressing_service
+        .generate_token_pair_with("big_user", &options)
+        .await
+        .unwrap();
+    assert!(jwt_header_json(&plain.access_token).get("zip").is_none());
+    assert_eq!(jwt_header_json(&compressed.access_token)["zip"], "DEF");
+    assert!(compressed.access_token.len() * 2 < plain.access_token.len());
+
+    let claims = compressing_service
+        .validate_access_token(&compressed.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims.get_subject(), "big_user");
+    assert_eq!(
+        claims.get_custom_claims().unwrap()["permissions"],
+        options.custom_claims["permissions"]
+    );
+    // Compressed tokens stay valid once compression is turned off
+    assert!(
+        plain_service
+            .validate_access_token(&compressed.access_token)
+            .await
+            .is_ok()
+    );
+    assert!(
+        compressing_service
+            .refresh_access_token(&compressed.refresh_token)
+            .await
+            .is_ok()
+    );
+}
+
+#[cfg(feature = "jwt-compression")]
+#[tokio::test]
+/// Tests that small tokens are left uncompressed and tampered compressed tokens are rejected.
+async fn test_jwt_compression_skips_small_tokens_and_checks_signature() {
+    use base64::Engine;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+    let service = JwtTokenService::new(TEST_JWT_SECRET, 60, 120).with_compression(512);
+    let small = service.generate_token_pair("small_user").await.unwrap();
+    assert!(jwt_header_json(&small.access_token).get("zip").is_none());
+    assert!(
+        service
+            .validate_access_token(&small.access_token)
+            .await
+            .is_ok()
+    );
+
+    let large = service
+        .generate_token_pair_with("big_user", &large_claim_options())
+        .await
+        .unwrap();
+    let segments: Vec<&str> = large.access_token.split('.').collect();
+    let forged_payload = miniz_oxide::deflate::compress_to_vec(
+        br#"{"sub":"admin","exp":9999999999,"iat":0,"token_type":"access"}"#,
+        6,
+    );
+    let forged = format!(
+        "{}.{}.{}",
+        segments[0],
+        URL_SAFE_NO_PAD.encode(forged_payload),
+        segments[2]
+    );
+    assert!(matches!(
+        service.validate_access_token(&forged).await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+
+    let other_service = JwtTokenService::new(&"x".repeat(32), 60, 120);
+    assert!(
+        other_service
+            .validate_access_token(&large.access_token)
+            .await
+            .is_err()
+    );
+}
+
+// --- Secret Key Rotation Tests ---
+
+use narangcia_cryptic::core::password::KeyRotatingPasswordManager;
+
+/// Returns a password manager hashing with the given Argon2 secret key and key ID.
+fn keyed_manager(key: &[u8], key_id: Option<&[u8]>) -> Argon2PasswordManager {
+    let manager = Argon2PasswordManager::with_params(TEST_ARGON2_PARAMS)
+        .unwrap()
+        .with_secret(key.to_vec());
+    match key_id {
+        Some(key_id) => manager.with_key_id(key_id).unwrap(),
+        None => manager,
+    }
+}
+
+#[tokio::test]
+/// Tests that a rotated key keeps verifying old hashes, flags them for rehash, and upgrades
+/// them on login.
+async fn test_key_rotation_verifies_old_hashes_and_rehashes_on_login() {
+    let old_hash = keyed_manager(b"old pepper", None)
+        .hash_password("rotated_pass")
+        .await
+        .unwrap();
+
+    let rotating = || {
+        KeyRotatingPasswordManager::new(keyed_manager(b"new pepper", Some(b"k2")))
+            .with_legacy(keyed_manager(b"old pepper", None))
+            .unwrap()
+    };
+    let manager = rotating();
+    assert!(manager.identify(&old_hash));
+    assert!(
+        manager
+            .verify_password("rotated_pass", &old_hash)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !manager
+            .verify_password("wrong_pass", &old_hash)
+            .await
+            .unwrap()
+    );
+    assert!(manager.needs_rehash(&old_hash));
+
+    let new_hash = manager.hash_password("rotated_pass").await.unwrap();
+    assert_eq!(Argon2Hasher::key_id_of(&new_hash), Some(b"k2".to_vec()));
+    assert!(!manager.needs_rehash(&new_hash));
+    assert!(
+        manager
+            .verify_password("rotated_pass", &new_hash)
+            .await
+            .unwrap()
+    );
+    // The old key alone cannot verify hashes of the new one
+    assert!(
+        !keyed_manager(b"old pepper", None)
+            .verify_password("rotated_pass", &new_hash)
+            .await
+            .unwrap()
+    );
+
+    assert!(matches!(
+        KeyRotatingPasswordManager::new(keyed_manager(b"new pepper", Some(b"k2")))
+            .with_legacy(keyed_manager(b"other pepper", Some(b"k2"))),
+        Err(narangcia_cryptic::AuthError::ConfigError(_))
+    ));
+
+    let repo = InMemoryUserRepo::new();
+    repo.add_user(User::new(
+        "rotated_user".to_string(),
+        Credentials::new(
+            "rotated_user".to_string(),
+            "rotated@example.com".to_string(),
+            old_hash.clone(),
+        ),
+    ))
+    .await
+    .unwrap();
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            ..Default::default()
+        }),
+        Some(Box::new(rotating())),
+        Some(Box::new(repo)),
+        None,
+        None,
+    )
+    .unwrap();
+    auth_service
+        .login(narangcia_cryptic::auth_service::LoginMethod::Credentials {
+            identifier: "rotated@example.com".to_string(),
+            password: "rotated_pass".to_string(),
+        })
+        .await
+        .unwrap();
+    let upgraded = auth_service
+        .persistent_users_manager
+        .get_user_by_identifier("rotated@example.com")
+        .await
+        .unwrap()
+        .credentials
+        .unwrap()
+        .password_hash;
+    assert_eq!(Argon2Hasher::key_id_of(&upgraded), Some(b"k2".to_vec()));
+}
+
+#[tokio::test]
+/// Tests that hashes of a previous key only verify during its grace window.
+async fn test_key_rotation_grace_window() {
+    let old_hash = keyed_manager(b"old pepper", Some(b"k1"))
+        .hash_password("grace_pass")
+        .await
+        .unwrap();
+
+    let in_window = KeyRotatingPasswordManager::new(keyed_manager(b"new pepper", Some(b"k2")))
+        .with_legacy_until(
+            keyed_manager(b"old pepper", Some(b"k1")),
+            chrono::Utc::now() + chrono::Duration::days(30),
+        )
+        .unwrap();
+    assert!(in_window.identify(&old_hash));
+    assert!(
+        in_window
+            .verify_password("grace_pass", &old_hash)
+            .await
+            .unwrap()
+    );
+
+    let retired = KeyRotatingPasswordManager::new(keyed_manager(b"new pepper", Some(b"k2")))
+        .with_legacy_until(
+            keyed_manager(b"old pepper", Some(b"k1")),
+            chrono::Utc::now() - chrono::Duration::seconds(1),
+        )
+        .unwrap();
+    assert!(!retired.identify(&old_hash));
+    assert!(matches!(
+        retired.verify_password("grace_pass", &old_hash).await,
+        Err(narangcia_cryptic::AuthError::VerificationError(_))
+    ));
+
+    // Hashes of unknown keys are never accepted
+    let unknown_hash = keyed_manager(b"stray pepper", Some(b"k9"))
+        .hash_password("grace_pass")
+        .await
+        .unwrap();
+    assert!(!in_window.identify(&unknown_hash));
+}
+
+// --- Bootstrap Tests ---
+
+#[tokio::test]
+/// Tests that bootstrapping twice creates a single administrator, whose tokens carry the
+/// `admin` role.
+async fn test_bootstrap_creates_single_admin() {
+    use futures_util::TryStreamExt;
+    use narangcia_cryptic::core::user::ADMIN_ROLE;
+
+    let auth_service = tenant_test_auth_service();
+    let admin = auth_service
+        .bootstrap("admin@example.com", "admin_password")
+        .await
+        .unwrap();
+    assert!(admin.has_role(ADMIN_ROLE));
+    assert!(matches!(
+        auth_service
+            .bootstrap("other-admin@example.com", "admin_password")
+            .await,
+        Err(narangcia_cryptic::AuthError::AlreadyBootstrapped)
+    ));
+
+    let users: Vec<User> = auth_service
+        .persistent_users_manager
+        .stream_users()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(users.len(), 1);
+
+    let (_, tokens) = auth_service
+        .login(narangcia_cryptic::auth_service::LoginMethod::Credentials {
+            identifier: "admin@example.com".to_string(),
+            password: "admin_password".to_string(),
+        })
+        .await
+        .unwrap();
+    let claims = auth_service
+        .validate_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(
+        claims.get_custom_claims().unwrap()["roles"],
+        serde_json::json!([ADMIN_ROLE])
+    );
+}
+
+#[tokio::test]
+/// Tests that bootstrapping never returns or promotes an existing user, and that a failed
+/// bootstrap can be retried.
+async fn test_bootstrap_populated_repository() {
+    use narangcia_cryptic::core::user::ADMIN_ROLE;
+
+    let auth_service = tenant_test_auth_service();
+    let (signup, _) = credentials_methods("existing@example.com", "password");
+    let (existing, _) = auth_service.signup(signup).await.unwrap();
+
+    assert!(matches!(
+        auth_service
+            .bootstrap("existing@example.com", "admin_password")
+            .await,
+        Err(narangcia_cryptic::AuthError::UserAlreadyExists)
+    ));
+    let stored = auth_service
+        .persistent_users_manager
+        .get_user_by_id(&existing.id)
+        .await
+        .unwrap();
+    assert!(stored.roles.is_empty());
+
+    let admin = auth_service
+        .bootstrap("admin@example.com", "admin_password")
+        .await
+        .unwrap();
+    assert!(admin.has_role(ADMIN_ROLE));
+    assert_ne!(admin.id, existing.id);
+}
+
+#[tokio::test]
+/// Tests that concurrent bootstraps create a single administrator.
+async fn test_bootstrap_concurrent_calls() {
+    use futures_util::TryStreamExt;
+
+    let auth_service = tenant_test_auth_service();
+    let (first, second) = tokio::join!(
+        auth_service.bootstrap("first-admin@example.com", "admin_password"),
+        auth_service.bootstrap("second-admin@example.com", "admin_password"),
+    );
+    assert_eq!([&first, &second].iter().filter(|r| r.is_ok()).count(), 1);
+    assert!(
+        [first, second]
+            .into_iter()
+            .any(|r| matches!(r, Err(narangcia_cryptic::AuthError::AlreadyBootstrapped)))
+    );
+    let users: Vec<User> = auth_service
+        .persistent_users_manager
+        .stream_users()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(users.len(), 1);
+}
+
+// --- Typed OAuth2 Scope Tests ---
+
+#[test]
+/// Tests that typed scopes map to the scope strings of their provider, and that default
+/// scopes are unchanged.
+fn test_typed_scopes_map_to_provider_strings() {
+    use narangcia_cryptic::core::oauth::scope::{
+        DiscordScope, GitHubScope, GoogleScope, MicrosoftScope,
+    };
+
+    assert_eq!(GoogleScope::OpenId.to_str(), "openid");
+    assert_eq!(
+        GoogleScope::CalendarReadonly.to_str(),
+        "https://www.googleapis.com/auth/calendar.readonly"
+    );
+    assert_eq!(GitHubScope::UserEmail.to_str(), "user:email");
+    assert_eq!(GitHubScope::ReadOrg.to_string(), "read:org");
+    assert_eq!(DiscordScope::GuildsJoin.to_str(), "guilds.join");
+    assert_eq!(String::from(MicrosoftScope::UserRead), "User.Read");
+    assert_eq!(MicrosoftScope::OfflineAccess.to_str(), "offline_access");
+
+    assert_eq!(
+        OAuth2Provider::Google.default_scopes(),
+        ["openid", "email", "profile"]
+    );
+    assert_eq!(OAuth2Provider::GitHub.default_scopes(), ["user:email"]);
+    assert_eq!(
+        OAuth2Provider::Discord.default_scopes(),
+        ["identify", "email"]
+    );
+    assert_eq!(
+        OAuth2Provider::Microsoft.default_scopes(),
+        ["openid", "email", "profile"]
+    );
+}
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that typed and raw extra scopes are both requested in the authorization URL.
+async fn test_generate_auth_url_accepts_typed_and_raw_scopes() {
+    use narangcia_cryptic::core::oauth::OAuth2Service;
+
+    use narangcia_cryptic::core::oauth::scope::GitHubScope;
+
+    let manager = oauth_manager_with_token_response("200 OK", "{}").await;
+    let url = manager
+        .generate_auth_url(
+            OAuth2Provider::GitHub,
+            "nonce",
+            Some(vec![
+                GitHubScope::ReadOrg.into(),
+                "admin:enterprise".to_string(),
+            ]),
+        )
+        .await
+        .unwrap();
+    let url = url::Url::parse(&url).unwrap();
+    let scope = url
+        .query_pairs()
+        .find(|(key, _)| key == "scope")
+        .map(|(_, value)| value.into_owned())
+        .unwrap();
+    let scopes: Vec<&str> = scope.split(' ').collect();
+    assert!(scopes.contains(&"read:org"));
+    assert!(scopes.contains(&"admin:enterprise"));
+    assert!(scopes.contains(&"user:email"));
+}
+
+// --- OAuth2 Token Expiry Margin Tests ---
+
+/// Returns a Google token expiring in `secs` seconds.
+fn oauth_token_expiring_in(secs: i64) -> narangcia_cryptic::core::oauth::store::OAuth2Token {
+    let now = chrono::Utc::now().naive_utc();
+    narangcia_cryptic::core::oauth::store::OAuth2Token {
+        access_token: "token".to_string(),
+        refresh_token: None,
+        expires_at: Some(now + chrono::Duration::seconds(secs)),
+        token_type: "Bearer".to_string(),
+        scope: None,
+        provider: OAuth2Provider::Google,
+        created_at: now,
+    }
+}
+
+#[test]
+/// Tests that tokens count as expired once they are within the safety margin of their
+/// expiration.
+fn test_oauth_token_expiry_margin_boundary() {
+    use narangcia_cryptic::core::oauth::store::DEFAULT_OAUTH_EXPIRY_MARGIN;
+
+    assert_eq!(DEFAULT_OAUTH_EXPIRY_MARGIN, 30);
+    let inside_margin = oauth_token_expiring_in(20);
+    assert!(inside_margin.is_expired_with_margin(DEFAULT_OAUTH_EXPIRY_MARGIN));
+    assert!(!inside_margin.is_expired_with_margin(0));
+    assert!(!inside_margin.is_expired_with_margin(10));
+
+    let outside_margin = oauth_token_expiring_in(45);
+    assert!(!outside_margin.is_expired_with_margin(DEFAULT_OAUTH_EXPIRY_MARGIN));
+    assert!(outside_margin.is_expired_with_margin(60));
+
+    assert!(oauth_token_expiring_in(-1).is_expired_with_margin(0));
+    let mut no_expiry = oauth_token_expiring_in(0);
+    no_expiry.expires_at = None;
+    assert!(!no_expiry.is_expired_with_margin(DEFAULT_OAUTH_EXPIRY_MARGIN));
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+/// Tests that tokens close to their expiration are refreshed proactively, according to the
+/// configured margin.
+async fn test_fresh_oauth2_token_refreshes_within_margin() {
+    let oauth = MockOAuth2Service::new()
+        .with_user(
+            OAuth2Provider::GitHub,
+            "code",
+            mock_oauth_user_info("gh-margin", "margin@example.com"),
+        )
+        .with_token_lifetime(20);
+    let auth_service = test_auth_service_with_oauth(oauth);
+    let token = auth_service
+        .exchange_oauth2_code_for_token(OAuth2Provider::GitHub, "code", "state")
+        .await
+        .unwrap();
+
+    let fresh = auth_service.fresh_oauth2_token(&token).await.unwrap();
+    assert_ne!(fresh.access_token, token.access_token);
+
+    let mut vars = narangcia_cryptic::test_util::test_vars();
+    vars.oauth_token_expiry_margin = Some(5);
+    let oauth = MockOAuth2Service::new()
+        .with_user(
+            OAuth2Provider::GitHub,
+            "code",
+            mock_oauth_user_info("gh-margin", "margin@example.com"),
+        )
+        .with_token_lifetime(20);
+    let lenient_service = AuthService::new(
+        std::sync::Arc::new(vars),
+        None,
+        None,
+        None,
+        Some(Box::new(oauth)),
+    )
+    .unwrap();
+    let token = lenient_service
+        .exchange_oauth2_code_for_token(OAuth2Provider::GitHub, "code", "state")
+        .await
+        .unwrap();
+    let kept = lenient_service.fresh_oauth2_token(&token).await.unwrap();
+    assert_eq!(kept.access_token, token.access_token);
+}
+
+// --- OAuth2 Service Configuration Tests ---
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that provider configurations are reachable through a `dyn OAuth2Service`.
+async fn test_oauth2_service_exposes_configs_through_trait_object() {
+    use narangcia_cryptic::core::oauth::OAuth2Service;
+
+    let manager: Box<dyn OAuth2Service + Send + Sync> =
+        Box::new(oauth_manager_with_token_response("200 OK", "{}").await);
+
+    let config = manager
+        .get_config(OAuth2Provider::GitHub)
+        .expect("GitHub should be configured");
+    assert_eq!(
+        config.redirect_callback_uri,
+        "https://api.example.com/oauth/github/callback"
+    );
+    assert!(manager.get_config(OAuth2Provider::Google).is_none());
+    assert_eq!(
+        manager.list_configured_providers(),
+        vec![OAuth2Provider::GitHub]
+    );
+    assert_eq!(
+        manager
+            .get_redirect_frontend_uri(OAuth2Provider::GitHub)
+            .await
+            .unwrap(),
+        config.redirect_frontend_uri
+    );
+    assert!(matches!(
+        manager
+            .get_redirect_frontend_uri(OAuth2Provider::Google)
+            .await,
+        Err(narangcia_cryptic::AuthError::ConfigError(_))
+    ));
+}
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that `AuthService` reports the providers configured in its OAuth2 service.
+async fn test_auth_service_lists_configured_oauth2_providers() {
+    let mut auth_service = tenant_test_auth_service();
+    auth_service.oauth2_manager = Box::new(oauth_manager_with_token_response("200 OK", "{}").await);
+
+    assert_eq!(
+        auth_service.configured_oauth2_providers(),
+        vec![OAuth2Provider::GitHub]
+    );
+    assert!(auth_service.oauth2_config(OAuth2Provider::GitHub).is_some());
+    assert!(
+        auth_service
+            .oauth2_config(OAuth2Provider::Discord)
+            .is_none()
+    );
+}
+
+// --- Store Pruning Tests ---
+
+#[tokio::test]
+/// Tests that the in-memory stores prune their expired entries and keep the live ones.
+async fn test_in_memory_stores_prune_expired_entries() {
+    use narangcia_cryptic::core::otp::{InMemoryOtpStore, OtpRecord, OtpStore};
+    use narangcia_cryptic::core::rate_limit::{InMemoryRateLimitStore, RateLimit, RateLimitStore};
+    use narangcia_cryptic::core::token::one_time::{InMemoryOneTimeTokenStore, OneTimeTokenStore};
+    use narangcia_cryptic::core::token::session::{InMemorySessionStore, SessionStore};
+
+    let now = chrono::Utc::now().timestamp() as usize;
+
+    let one_time = InMemoryOneTimeTokenStore::new();
+    // Every access prunes lazily, so only the last entry written can still be expired
+    assert!(one_time.consume("live", now + 3600).await.unwrap());
+    assert!(one_time.consume("expired", now - 10).await.unwrap());
+    assert_eq!(one_time.prune_expired().await.unwrap(), 1);
+    assert!(!one_time.consume("live", now + 3600).await.unwrap());
+
+    let sessions = InMemorySessionStore::new();
+    sessions.record("user", "live", now + 3600).await.unwrap();
+    sessions.record("other", "expired", now - 10).await.unwrap();
+    assert_eq!(sessions.prune_expired().await.unwrap(), 1);
+    assert_eq!(sessions.revoke_all_for_user("user").await.unwrap(), 1);
+
+    let otps = InMemoryOtpStore::new();
+    let record = |expires_at| OtpRecord {
+        code_hash: "hash".to_string(),
+        expires_at,
+        attempts: 0,
+    };
+    otps.put("live", record(now + 3600)).await.unwrap();
+    otps.put("expired", record(now - 10)).await.unwrap();
+    assert_eq!(otps.prune_expired().await.unwrap(), 1);
+    assert!(otps.get("live").await.unwrap().is_some());
+
+    let rate_limits = InMemoryRateLimitStore::new();
+    let limit = RateLimit {
+        max_requests: 5,
+        window_secs: 1,
+    };
+    rate_limits.hit("ip:203.0.113.7", limit).await.unwrap();
+    assert_eq!(rate_limits.prune_expired().await.unwrap(), 0);
+    tokio::time::sleep(std::time::Duration::from_millis(2100)).await;
+    assert_eq!(rate_limits.prune_expired().await.unwrap(), 1);
+}
+
+#[cfg(all(feature = "oauth", feature = "tokio"))]
+#[tokio::test]
+/// Tests that the maintenance task prunes the service's stores in the background.
+async fn test_spawn_maintenance_prunes_stores_periodically() {
+    let auth_service = std::sync::Arc::new(tenant_test_auth_service());
+    let now = chrono::Utc::now().timestamp() as usize;
+    assert!(
+        auth_service
+            .one_time_tokens
+            .consume("expired", now - 10)
+            .await
+            .unwrap()
+    );
+
+    let handle = auth_service
+        .clone()
+        .spawn_maintenance(std::time::Duration::from_millis(10));
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    handle.abort();
+
+    assert_eq!(auth_service.prune_expired().await.unwrap(), 0);
+}
+
+// --- OAuth2 User Info Field Mapping Tests ---
+
+#[cfg(feature = "oauth")]
+/// Builds a manager configuring Google with the given user info field overrides.
+fn oauth_manager_with_field_map(field_map: &[(&str, &str)]) -> OAuth2Manager {
+    let mut config = test_oauth_config("secret", "https://api.example.com/oauth/google/callback");
+    config.field_map = field_map
+        .iter()
+        .map(|(field, path)| (field.to_string(), path.to_string()))
+        .collect();
+    let mut configs = std::collections::HashMap::new();
+    configs.insert(OAuth2Provider::Google, config);
+    OAuth2Manager::new(configs)
+}
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that remapped fields are read from a nonstandard user info response.
+async fn test_parse_user_info_uses_field_map_for_missing_fields() {
+    let manager = oauth_manager_with_field_map(&[
+        ("id", "account.uid"),
+        ("email", "primary_mail"),
+        ("name", "account.display_name"),
+        ("avatar", "photo"),
+    ]);
+    let response = serde_json::json!({
+        "account": { "uid": 4242, "display_name": "Oddball User" },
+        "primary_mail": "oddball@example.com",
+        "photo": "https://cdn.example.com/oddball.png",
+    });
+
+    let info = manager
+        .parse_user_info(OAuth2Provider::Google, response)
+        .await
+        .expect("remapped fields should parse");
+    assert_eq!(info.provider_user_id, "4242");
+    assert_eq!(info.email.as_deref(), Some("oddball@example.com"));
+    assert_eq!(info.name.as_deref(), Some("Oddball User"));
+    assert_eq!(
+        info.avatar_url.as_deref(),
+        Some("https://cdn.example.com/oddball.png")
+    );
+}
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that standard fields take precedence over the field map, and that a missing field stays
+/// missing without an override.
+async fn test_parse_user_info_prefers_builtin_fields_over_field_map() {
+    let manager = oauth_manager_with_field_map(&[("email", "primary_mail")]);
+    let response = serde_json::json!({
+        "id": "google-1",
+        "email": "standard@example.com",
+        "primary_mail": "oddball@example.com",
+    });
+
+    let info = manager
+        .parse_user_info(OAuth2Provider::Google, response)
+        .await
+        .unwrap();
+    assert_eq!(info.provider_user_id, "google-1");
+    assert_eq!(info.email.as_deref(), Some("standard@example.com"));
+    assert_eq!(info.name, None);
+
+    let unmapped = oauth_manager_with_field_map(&[])
+        .parse_user_info(
+            OAuth2Provider::Google,
+            serde_json::json!({ "id": "google-2", "primary_mail": "oddball@example.com" }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(unmapped.email, None);
+}
+
+#[test]
+/// Tests that `AuthServiceVariables::from_env` reads and validates provider field maps.
+fn test_auth_service_variables_from_env_reads_field_map() {
+    let base = [
+        (
+            "CRYPTIC_SECRET_KEY",
+            "an-env-provided-secret-that-is-long-enough",
+        ),
+        ("CRYPTIC_GOOGLE_CLIENT_ID", "google-id"),
+        ("CRYPTIC_GOOGLE_CLIENT_SECRET", "google-secret"),
+        (
+            "CRYPTIC_GOOGLE_REDIRECT_URI",
+            "http://localhost/oauth/google/callback",
+        ),
+        (
+            "CRYPTIC_GOOGLE_REDIRECT_FRONTEND_URI",
+            "http://localhost/done",
+        ),
+    ];
+
+    let mut vars = base.to_vec();
+    vars.push(("CRYPTIC_GOOGLE_FIELD_MAP", "email=upn, name=profile.name"));
+    let parsed = with_env(&vars, AuthServiceVariables::from_env).unwrap();
+    let field_map = &parsed.oauth_configs[&OAuth2Provider::Google].field_map;
+    assert_eq!(field_map.len(), 2);
+    assert_eq!(field_map["email"], "upn");
+    assert_eq!(field_map["name"], "profile.name");
+
+    let mut vars = base.to_vec();
+    vars.push(("CRYPTIC_GOOGLE_FIELD_MAP", "email"));
+    assert!(matches!(
+        with_env(&vars, AuthServiceVariables::from_env),
+        Err(narangcia_cryptic::AuthError::ConfigError(_))
+    ));
+}
+
+// --- Optimistic Concurrency Tests ---
+
+#[tokio::test]
+/// Tests that the second of two edits made from the same loaded user is rejected.
+async fn test_compare_and_update_user_rejects_stale_version() {
+    let repo = InMemoryUserRepo::new();
+    let user = User::new(
+        "versioned-user",
+        Credentials::new(
+            "versioned-user".to_string(),
+            "versioned@example.com".to_string(),
+            "hash".to_string(),
+        ),
+    );
+    repo.add_user(user.clone()).await.unwrap();
+
+    let mut first_admin = repo.get_user_by_id(&user.id).await.unwrap();
+    let mut second_admin = repo.get_user_by_id(&user.id).await.unwrap();
+    assert_eq!(first_admin.version, second_admin.version);
+
+    first_admin.roles = vec!["editor".to_string()];
+    repo.compare_and_update_user(&first_admin, first_admin.version)
+        .await
+        .expect("the first edit should win");
+
+    second_admin.roles = vec!["viewer".to_string()];
+    assert!(matches!(
+        repo.compare_and_update_user(&second_admin, second_admin.version)
+            .await,
+        Err(narangcia_cryptic::AuthError::ConcurrentModification)
+    ));
+    let stored = repo.get_user_by_id(&user.id).await.unwrap();
+    assert_eq!(stored.roles, vec!["editor"]);
+    assert_eq!(stored.version, first_admin.version + 1);
+
+    // Reloading and reapplying the change succeeds
+    let mut reloaded = stored;
+    reloaded.roles.push("viewer".to_string());
+    repo.compare_and_update_user(&reloaded, reloaded.version)
+        .await
+        .unwrap();
+    assert_eq!(
+        repo.get_user_by_id(&user.id).await.unwrap().roles,
+        vec!["editor", "viewer"]
+    );
+}
+
+#[tokio::test]
+/// Tests that plain updates bump the version, so that versioned updates made concurrently with
+/// them are rejected as well.
+async fn test_update_user_bumps_version_for_optimistic_locks() {
+    let repo: Box<dyn UserRepository + Send + Sync> = Box::new(InMemoryUserRepo::new());
+    let user = User::new(
+        "bumped-user",
+        Credentials::new(
+            "bumped-user".to_string(),
+            "bumped@example.com".to_string(),
+            "hash".to_string(),
+        ),
+    );
+    repo.add_user(user.clone()).await.unwrap();
+    let loaded = repo.get_user_by_id(&user.id).await.unwrap();
+
+    repo.update_user(&loaded).await.unwrap();
+    assert_eq!(
+        repo.get_user_by_id(&user.id).await.unwrap().version,
+        loaded.version + 1
+    );
+    assert!(matches!(
+        repo.compare_and_update_user(&loaded, loaded.version).await,
+        Err(narangcia_cryptic::AuthError::ConcurrentModification)
+    ));
+}
+
+// --- Credentials-Only Authentication Tests ---
+
+/// Builds a `CredentialsAuth` with fast Argon2 parameters and an in-memory repository.
+fn test_credentials_auth() -> narangcia_cryptic::CredentialsAuth {
+    narangcia_cryptic::CredentialsAuth::new(
+        Box::new(Argon2PasswordManager::with_params(TEST_ARGON2_PARAMS).unwrap()),
+        Box::new(InMemoryUserRepo::new()),
+        Box::new(JwtTokenService::new(TEST_JWT_SECRET, 900, 3600)),
+    )
+}
+
+#[tokio::test]
+/// Tests the signup, login and validation flow of `CredentialsAuth`.
+async fn test_credentials_auth_signup_login_and_validate() {
+    let auth = test_credentials_auth();
+
+    let (user, signup_tokens) = auth
+        .signup("lean@example.com", "a-strong-password")
+        .await
+        .expect("signup should succeed");
+    let claims = auth.validate(&signup_tokens.access_token).await.unwrap();
+    assert_eq!(claims.get_subject(), user.id.as_str());
+
+    let (logged_in, login_tokens) = auth
+        .login("lean@example.com", "a-strong-password")
+        .await
+        .expect("login should succeed");
+    assert_eq!(logged_in.id, user.id);
+    let claims = auth.validate(&login_tokens.access_token).await.unwrap();
+    assert_eq!(claims.get_subject(), user.id.as_str());
+    assert!(auth.validate("not-a-token").await.is_err());
+}
+
+#[tokio::test]
+/// Tests that `CredentialsAuth` rejects duplicate signups, wrong passwords, unknown identifiers
+/// and disabled users.
+async fn test_credentials_auth_rejects_invalid_attempts() {
+    let auth = test_credentials_auth();
+    let (mut user, _) = auth
+        .signup("taken@example.com", "a-strong-password")
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        auth.signup("taken@example.com", "another-password").await,
+        Err(narangcia_cryptic::AuthError::SignupError(_))
+    ));
+    assert!(matches!(
+        auth.login("taken@example.com", "wrong-password").await,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+    assert!(matches!(
+        auth.login("nobody@example.com", "a-strong-password").await,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+
+    user.status = narangcia_cryptic::UserStatus::Suspended;
+    auth.users.update_user(&user).await.unwrap();
+    assert!(matches!(
+        auth.login("taken@example.com", "a-strong-password").await,
+        Err(narangcia_cryptic::AuthError::AccountDisabled)
+    ));
+}
+
+// --- Verification Material Tests ---
+
+use narangcia_cryptic::core::token::material::VerificationMaterial;
+
+#[test]
+/// Tests that HMAC services describe their secret by reference only, including behind an
+/// opaque refresh token service.
+fn test_verification_material_references_hmac_secret() {
+    let service = JwtTokenService::new(TEST_JWT_SECRET, 60, 120).with_kid("hmac-1");
+    let material = service.verification_material().unwrap();
+    assert_eq!(
+        material,
+        VerificationMaterial::HmacSecretRef {
+            key_id: Some("hmac-1".to_string()),
+        }
+    );
+    // Nothing derived from the secret is exported
+    assert_eq!(
+        JwtTokenService::new("another-secret-of-sufficient-length", 60, 120)
+            .with_kid("hmac-1")
+            .verification_material()
+            .unwrap(),
+        material
+    );
+
+    let opaque = OpaqueRefreshTokenService::new(Box::new(service), 120);
+    assert_eq!(opaque.verification_material().unwrap(), material);
+}
+
+#[tokio::test]
+/// Tests that RS256 and ES256 services export a JWKS verifying their access tokens offline.
+async fn test_verification_material_exports_public_jwks() {
+    let rsa = JwtTokenService::from_rsa_pem(
+        TEST_RSA_PRIVATE_KEY.as_bytes(),
+        TEST_RSA_PUBLIC_KEY.as_bytes(),
+        60,
+        120,
+    )
+    .unwrap()
+    .with_kid("rsa-1");
+    let ec = JwtTokenService::from_ec_pem(
+        TEST_EC_PRIVATE_KEY.as_bytes(),
+        TEST_EC_PUBLIC_KEY.as_bytes(),
+        60,
+        120,
+    )
+    .unwrap();
+
+    for service in [rsa, ec] {
+        let VerificationMaterial::PublicJwks(jwks) = service.verification_material().unwrap()
+        else {
+            panic!("expected a JWKS");
+        };
+        assert_eq!(jwks.keys.len(), 1);
+        let verifier = OfflineVerifier::from_jwks(&serde_json::to_string(&jwks).unwrap()).unwrap();
+        let tokens = service.generate_token_pair("user-jwks").await.unwrap();
+        assert_eq!(
+            verifier.validate(&tokens.access_token).unwrap().sub,
+            "user-jwks"
+        );
+    }
+}
+
+// --- Unknown Claims Tests ---
+
+use narangcia_cryptic::core::token::claims::UnknownClaimsPolicy;
+
+/// Encodes an access token carrying, besides the standard claims, `extra`.
+fn token_with_extra_claims(extra: serde_json::Value) -> String {
+    let mut claims = serde_json::json!({
+        "sub": "user-extra",
+        "exp": chrono::Utc::now().timestamp() + 60,
+        "iat": chrono::Utc::now().timestamp(),
+        "token_type": "access",
+    });
+    claims
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    encode_test_token(&claims)
+}
+
+#[tokio::test]
+/// Tests that unknown claims are accepted and kept by default.
+async fn test_unknown_claims_are_kept_when_lenient() {
+    let service = JwtTokenService::new(TEST_JWT_SECRET, 60, 120);
+    let token = token_with_extra_claims(serde_json::json!({
+        "department": "billing",
+        "nbf": chrono::Utc::now().timestamp(),
+    }));
+
+    let claims = service.validate_access_token(&token).await.unwrap();
+    assert_eq!(claims.get_subject(), "user-extra");
+    assert_eq!(
+        claims.get_custom("department"),
+        Some(&serde_json::json!("billing"))
+    );
+    assert!(claims.get_custom("nbf").is_some());
+    assert_eq!(claims.get_custom("missing"), None);
+}
+
+#[tokio::test]
+/// Tests that the strict policy rejects unsupported registered claims and keeps other unknown
+/// claims, both online and offline.
+async fn test_unknown_claims_strict_rejects_unsupported_registered_claims() {
+    let service = JwtTokenService::new(TEST_JWT_SECRET, 60, 120)
+        .with_unknown_claims(UnknownClaimsPolicy::Strict);
+    let custom = token_with_extra_claims(serde_json::json!({"department": "billing"}));
+    let claims = service.validate_access_token(&custom).await.unwrap();
+    assert_eq!(
+        claims.get_custom("department"),
+        Some(&serde_json::json!("billing"))
+    );
+
+    let delegated = token_with_extra_claims(serde_json::json!({"act": {"sub": "admin"}}));
+    assert!(matches!(
+        service.validate_access_token(&delegated).await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+
+    let issuer = JwtTokenService::from_rsa_pem(
+        TEST_RSA_PRIVATE_KEY.as_bytes(),
+        TEST_RSA_PUBLIC_KEY.as_bytes(),
+        60,
+        120,
+    )
+    .unwrap()
+    .with_kid("rsa-1");
+    let mut options = narangcia_cryptic::core::token::TokenOptions::default();
+    options
+        .custom_claims
+        .insert("azp".to_string(), serde_json::json!("other-client"));
+    let tokens = issuer
+        .generate_token_pair_with("user-offline", &options)
+        .await
+        .unwrap();
+    let lenient = OfflineVerifier::from_jwks(TEST_JWKS).unwrap();
+    assert!(lenient.validate(&tokens.access_token).is_ok());
+    let strict = OfflineVerifier::from_jwks(TEST_JWKS)
+        .unwrap()
+        .with_unknown_claims(UnknownClaimsPolicy::Strict);
+    assert!(matches!(
+        strict.validate(&tokens.access_token),
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+}
+
+// --- Log ID Tests ---
+
+#[cfg(feature = "oauth")]
+#[test]
+/// Tests that log IDs are stable per user and secret, distinct across users and do not reveal
+/// the ID.
+fn test_user_log_id_is_stable_pseudonym() {
+    use narangcia_cryptic::core::user::LogIdKey;
+
+    let key = LogIdKey::derive(TEST_JWT_SECRET.as_bytes());
+    let alice = User::new("alice@example.com", Credentials::default());
+    let bob = User::new("bob@example.com", Credentials::default());
+
+    assert_eq!(key.log_id(&alice.id), key.log_id(&alice.id));
+    assert_eq!(
+        key.log_id(&alice.id),
+        LogIdKey::derive(TEST_JWT_SECRET.as_bytes()).log_id(&alice.id)
+    );
+    assert_ne!(key.log_id(&alice.id), key.log_id(&bob.id));
+    assert_eq!(key.log_id(&alice.id).len(), 16);
+    assert!(!key.log_id(&alice.id).contains("alice"));
+
+    // The key is derived from the service's secret, not shared process-wide
+    assert_eq!(
+        tenant_test_auth_service().log_id(&alice.id),
+        key.log_id(&alice.id)
+    );
+    assert_ne!(
+        LogIdKey::derive(b"another-secret-0123456789abcdef0123456789").log_id(&alice.id),
+        key.log_id(&alice.id)
+    );
+    assert_ne!(LogIdKey::random().log_id(&alice.id), key.log_id(&alice.id));
+}
+
+// --- OAuth2 Circuit Breaker Tests ---
+
+#[cfg(feature = "oauth")]
+use narangcia_cryptic::core::oauth::breaker::CircuitBreakerConfig;
+
+#[cfg(feature = "oauth")]
+/// The status and body served by a switchable token endpoint.
+type TokenResponse = std::sync::Arc<std::sync::Mutex<(&'static str, &'static str)>>;
+
+#[cfg(feature = "oauth")]
+/// Builds a manager for GitHub with a circuit breaker, whose token endpoint answers with the
+/// current value of the returned response.
+async fn oauth_manager_with_breaker(
+    config: CircuitBreakerConfig,
+    status: &'static str,
+    body: &'static str,
+) -> (OAuth2Manager, TokenResponse) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let response: TokenResponse = std::sync::Arc::new(std::sync::Mutex::new((status, body)));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/token", listener.local_addr().unwrap());
+    let served = response.clone();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut buffer = vec![0u8; 8192];
+            let _ = socket.read(&mut buffer).await;
+            let (status, body) = *served.lock().unwrap();
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+            let _ = socket.shutdown().await;
+        }
+    });
+
+    let mut configs = std::collections::HashMap::new();
+    configs.insert(
+        OAuth2Provider::GitHub,
+        test_oauth_config("secret", "https://api.example.com/oauth/github/callback"),
+    );
+    let manager = OAuth2Manager::new(configs)
+        .with_token_url(OAuth2Provider::GitHub, url)
+        .with_circuit_breaker(config);
+    (manager, response)
+}
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that consecutive provider failures open the circuit, while standard OAuth2 errors do
+/// not count.
+async fn test_oauth_circuit_breaker_opens_after_consecutive_failures() {
+    use narangcia_cryptic::core::oauth::OAuth2Service;
+
+    let (manager, response) = oauth_manager_with_breaker(
+        CircuitBreakerConfig::new(2, 60),
+        "503 Service Unavailable",
+        "upstream unavailable",
+    )
+    .await;
+    let breaker = manager.circuit_breaker().unwrap();
+
+    for _ in 0..2 {
+        assert!(matches!(
+            manager
+                .exchange_code_for_token(OAuth2Provider::GitHub, "code", "state")
+                .await,
+            Err(narangcia_cryptic::AuthError::OAuthTokenExchange(_))
+        ));
+    }
+    assert!(breaker.is_open(OAuth2Provider::GitHub));
+    assert!(!breaker.is_open(OAuth2Provider::Google));
+
+    // The provider recovered, but the open circuit does not let calls through
+    *response.lock().unwrap() = (
+        "200 OK",
+        r#"{"access_token":"gho_ok","token_type":"bearer"}"#,
+    );
+    assert!(matches!(
+        manager
+            .exchange_code_for_token(OAuth2Provider::GitHub, "code", "state")
+            .await,
+        Err(narangcia_cryptic::AuthError::OAuthNetwork(_))
+    ));
+
+    let (manager, _) = oauth_manager_with_breaker(
+        CircuitBreakerConfig::new(1, 60),
+        "400 Bad Request",
+        r#"{"error":"invalid_grant"}"#,
+    )
+    .await;
+    for _ in 0..2 {
+        assert!(matches!(
+            manager
+                .exchange_code_for_token(OAuth2Provider::GitHub, "used-code", "state")
+                .await,
+            Err(narangcia_cryptic::AuthError::OAuthProviderError { .. })
+        ));
+    }
+    assert!(
+        !manager
+            .circuit_breaker()
+            .unwrap()
+            .is_open(OAuth2Provider::GitHub)
+    );
+}
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that a call after the cooldown closes the circuit on success, and reopens it at once
+/// on failure.
+async fn test_oauth_circuit_breaker_closes_after_cooldown() {
+    use narangcia_cryptic::core::oauth::OAuth2Service;
+
+    let (manager, response) = oauth_manager_with_breaker(
+        CircuitBreakerConfig::new(2, 1),
+        "503 Service Unavailable",
+        "upstream unavailable",
+    )
+    .await;
+    let breaker = manager.circuit_breaker().unwrap();
+    let exchange = || manager.exchange_code_for_token(OAuth2Provider::GitHub, "code", "state");
+
+    for _ in 0..2 {
+        assert!(exchange().await.is_err());
+    }
+    assert!(breaker.is_open(OAuth2Provider::GitHub));
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    assert!(!breaker.is_open(OAuth2Provider::GitHub));
+
+    // Still failing after the cooldown: a single failure reopens the circuit
+    assert!(matches!(
+        exchange().await,
+        Err(narangcia_cryptic::AuthError::OAuthTokenExchange(_))
+    ));
+    assert!(breaker.is_open(OAuth2Provider::GitHub));
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+
+    *response.lock().unwrap() = (
+        "200 OK",
+        r#"{"access_token":"gho_ok","token_type":"bearer"}"#,
+    );
+    assert_eq!(exchange().await.unwrap().access_token, "gho_ok");
+
+    // The success reset the count: the next failure alone does not open the circuit
+    *response.lock().unwrap() = ("503 Service Unavailable", "upstream unavailable");
+    assert!(exchange().await.is_err());
+    assert!(!breaker.is_open(OAuth2Provider::GitHub));
+}
+
+// --- OAuth2 User Info Login Tests ---
+
+/// Builds the user info of a Google account, as an application would after its own flow.
+fn hand_built_google_user_info(provider_user_id: &str, email: &str) -> OAuth2UserInfo {
+    OAuth2UserInfo {
+        user_id: String::new(),
+        provider: OAuth2Provider::Google,
+        provider_user_id: provider_user_id.to_string(),
+        email: Some(email.to_string()),
+        name: Some("Grace Hopper".to_string()),
+        avatar_url: None,
+        verified_email: Some(true),
+        locale: None,
+        updated_at: chrono::Utc::now().naive_utc(),
+        raw_data: None,
+        granted_scopes: vec!["openid".to_string(), "email".to_string()],
+    }
+}
+
+#[tokio::test]
+/// Tests that user info fetched by the application creates a user once, then logs them in.
+async fn test_login_from_oauth_userinfo_creates_then_logs_in() {
+    let service = tenant_test_auth_service();
+    let info = hand_built_google_user_info("google-42", "grace@example.com");
+
+    let (user, tokens) = service
+        .login_from_oauth_userinfo(info.clone())
+        .await
+        .unwrap();
+    assert!(user.has_oauth_account(OAuth2Provider::Google));
+    let claims = service
+        .token_manager
+        .validate_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims.get_subject(), user.id.as_str());
+    assert_eq!(claims.get_amr(), ["oauth:google"]);
+
+    let (again, _) = service.login_from_oauth_userinfo(info).await.unwrap();
+    assert_eq!(again.id, user.id);
+    assert_eq!(
+        service
+            .persistent_users_manager
+            .get_user_by_oauth_id(OAuth2Provider::Google, "google-42")
+            .await
+            .unwrap()
+            .id,
+        user.id
+    );
+}
+
+#[tokio::test]
+/// Tests that user info logins respect tenants and user status.
+async fn test_login_from_oauth_userinfo_checks_tenant_and_status() {
+    let service = tenant_test_auth_service();
+    let info = hand_built_google_user_info("google-acme", "ada@acme.example");
+    let (mut user, _) = service
+        .for_tenant("acme")
+        .login_from_oauth_userinfo(info.clone())
+        .await
+        .unwrap();
+    assert_eq!(user.tenant_id.as_deref(), Some("acme"));
+
+    assert!(matches!(
+        service.login_from_oauth_userinfo(info.clone()).await,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+
+    user.status = narangcia_cryptic::UserStatus::Suspended;
+    service
+        .persistent_users_manager
+        .update_user(&user)
+        .await
+        .unwrap();
+    assert!(matches!(
+        service
+            .for_tenant("acme")
+            .login_from_oauth_userinfo(info)
+            .await,
+        Err(narangcia_cryptic::AuthError::AccountDisabled)
+    ));
+}
+
+// --- Password Strength Tests ---
+
+use narangcia_cryptic::core::policy::strength::estimate_strength;
+
+#[test]
+/// Tests that predictable passwords score low even when they satisfy composition rules.
+fn test_estimate_strength_scores_predictable_passwords_low() {
+    for predictable in ["P@ssw0rd1", "Qwerty123!", "aaaaaaaa", "abcdef123"] {
+        let estimate = estimate_strength(predictable);
+        assert!(
+            estimate.score <= 1,
+            "{predictable} scored {}",
+            estimate.score
+        );
+        assert!(!estimate.feedback.is_empty());
+    }
+    let estimate = estimate_strength("P@ssw0rd1");
+    assert!(
+        estimate
+            .feedback
+            .iter()
+            .any(|message| message.contains("substitutions"))
+    );
+
+    for strong in ["correct-Horse-battery-9staple", "vX7#qL2!mZ9@kR4w"] {
+        let estimate = estimate_strength(strong);
+        assert_eq!(estimate.score, 4, "{strong} scored {}", estimate.score);
+        assert!(estimate.feedback.is_empty());
+    }
+}
+
+#[tokio::test]
+/// Tests that signups and password changes reject passwords below the minimum strength score.
+async fn test_password_policy_min_strength_score_rejects_weak_passwords() {
+    let service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            password_policy: Some(PasswordPolicy {
+                min_strength_score: Some(3),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    let signup = |password: &str| narangcia_cryptic::auth_service::SignupMethod::Credentials {
+        identifier: "strength@example.com".to_string(),
+        password: password.to_string(),
+    };
+
+    // Passes every composition rule, but is a common password with a sequence
+    assert!(matches!(
+        service.signup(signup("P@ssw0rd1234!")).await,
+        Err(narangcia_cryptic::AuthError::WeakPassword { score, feedback })
+            if score < 3 && !feedback.is_empty()
+    ));
+    let (_, tokens) = service
+        .signup(signup("correct-Horse-battery-9staple"))
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        service
+            .change_password(
+                &tokens.access_token,
+                "correct-Horse-battery-9staple",
+                "Password2024!"
+            )
+            .await,
+        Err(narangcia_cryptic::AuthError::WeakPassword { .. })
+    ));
+    service
+        .change_password(
+            &tokens.access_token,
+            "correct-Horse-battery-9staple",
+            "Violet-Kayak-73-drizzle",
+        )
+        .await
+        .unwrap();
+}
+
+// --- OAuth2 Non-JSON Response Tests ---
+
+#[cfg(feature = "oauth")]
+/// Serves `body` as an HTML page with `status`, like a provider behind a rate limiter or in
+/// maintenance, and returns the URL of the page.
+async fn mock_html_endpoint(status: &'static str, body: &'static str) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/page", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut buffer = vec![0u8; 8192];
+            let _ = socket.read(&mut buffer).await;
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+            let _ = socket.shutdown().await;
+        }
+    });
+    url
+}
+
+#[cfg(feature = "oauth")]
+/// An HTML maintenance page.
+const MAINTENANCE_PAGE: &str = "<!DOCTYPE html>\n<html>\n  <head><title>Down for maintenance</title></head>\n  <body>We'll be back soon.</body>\n</html>";
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that an HTML user info response is reported as a network error quoting the page.
+async fn test_oauth_user_info_html_response_is_network_error() {
+    use narangcia_cryptic::core::oauth::OAuth2Service;
+
+    let mut configs = std::collections::HashMap::new();
+    configs.insert(
+        OAuth2Provider::GitHub,
+        test_oauth_config("secret", "https://api.example.com/oauth/github/callback"),
+    );
+    let manager = OAuth2Manager::new(configs).with_user_info_url(
+        OAuth2Provider::GitHub,
+        mock_html_endpoint("200 OK", MAINTENANCE_PAGE).await,
+    );
+    let token = narangcia_cryptic::core::oauth::store::OAuth2Token {
+        access_token: "token".to_string(),
+        refresh_token: None,
+        expires_at: None,
+        token_type: "Bearer".to_string(),
+        scope: None,
+        provider: OAuth2Provider::GitHub,
+        created_at: chrono::Utc::now().naive_utc(),
+    };
+
+    match manager.fetch_user_info(&token).await {
+        Err(narangcia_cryptic::AuthError::OAuthNetwork(message)) => {
+            assert!(message.contains("200 OK"), "{message}");
+            assert!(message.contains("text/html"), "{message}");
+            assert!(
+                message.contains("<title>Down for maintenance</title>"),
+                "{message}"
+            );
+        }
+        other => panic!("expected a network error, got {other:?}"),
+    }
+}
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that an HTML error page from the token endpoint is reported as a network error
+/// quoting the page, instead of a token parsing failure.
+async fn test_oauth_token_exchange_html_response_is_network_error() {
+    use narangcia_cryptic::core::oauth::OAuth2Service;
+
+    let mut configs = std::collections::HashMap::new();
+    configs.insert(
+        OAuth2Provider::GitHub,
+        test_oauth_config("secret", "https://api.example.com/oauth/github/callback"),
+    );
+    let manager = OAuth2Manager::new(configs).with_token_url(
+        OAuth2Provider::GitHub,
+        mock_html_endpoint("429 Too Many Requests", MAINTENANCE_PAGE).await,
+    );
+
+    match manager
+        .exchange_code_for_token(OAuth2Provider::GitHub, "code", "state")
+        .await
+    {
+        Err(narangcia_cryptic::AuthError::OAuthNetwork(message)) => {
+            assert!(message.starts_with("Token exchange failed"), "{message}");
+            assert!(message.contains("We'll be back soon."), "{message}");
+        }
+        other => panic!("expected a network error, got {other:?}"),
+    }
+}
+
+// --- OAuth2 Default Scope Override Tests ---
+
+#[cfg(feature = "oauth")]
+#[tokio::test]
+/// Tests that overridden default scopes replace the built-in ones in the authorization URL,
+/// while additional scopes are still requested.
+async fn test_oauth_default_scopes_override_replaces_builtin_scopes() {
+    use narangcia_cryptic::core::oauth::OAuth2Service;
+
+    let mut config = test_oauth_config("secret", "https://api.example.com/oauth/google/callback");
+    config.default_scopes = Some(vec!["openid".to_string(), "email".to_string()]);
+    config.additional_scopes =
+        vec!["https://www.googleapis.com/auth/calendar.readonly".to_string()];
+    let mut configs = std::collections::HashMap::new();
+    configs.insert(OAuth2Provider::Google, config);
+    let manager = OAuth2Manager::new(configs);
+
+    let url = manager
+        .generate_auth_url(OAuth2Provider::Google, "state", None)
+        .await
+        .unwrap();
+    let url = url::Url::parse(&url).unwrap();
+    let scope = url
+        .query_pairs()
+        .find(|(key, _)| key == "scope")
+        .map(|(_, value)| value.into_owned())
+        .unwrap();
+    let scopes: Vec<&str> = scope.split(' ').collect();
+
+    assert!(scopes.contains(&"openid"), "{scope}");
+    assert!(scopes.contains(&"email"), "{scope}");
+    assert!(
+        scopes.contains(&"https://www.googleapis.com/auth/calendar.readonly"),
+        "{scope}"
+    );
+    assert!(!scopes.contains(&"profile"), "{scope}");
+}
+
+#[test]
+/// Tests that `AuthServiceVariables::from_env` overrides default scopes only when set.
+fn test_auth_service_variables_from_env_reads_default_scopes() {
+    let vars = [
+        ("CRYPTIC_SECRET_KEY", TEST_JWT_SECRET),
+        ("CRYPTIC_GOOGLE_CLIENT_ID", "google-id"),
+        ("CRYPTIC_GOOGLE_CLIENT_SECRET", "google-secret"),
+        (
+            "CRYPTIC_GOOGLE_REDIRECT_URI",
+            "https://api.example.com/oauth/google/callback",
+        ),
+        (
+            "CRYPTIC_GOOGLE_REDIRECT_FRONTEND_URI",
+            "https://app.example.com/auth",
+        ),
+        ("CRYPTIC_GOOGLE_DEFAULT_SCOPES", "openid, email"),
+        ("CRYPTIC_GITHUB_CLIENT_ID", "github-id"),
+        ("CRYPTIC_GITHUB_CLIENT_SECRET", "github-secret"),
+        (
+            "CRYPTIC_GITHUB_REDIRECT_URI",
+            "https://api.example.com/oauth/github/callback",
+        ),
+        (
+            "CRYPTIC_GITHUB_REDIRECT_FRONTEND_URI",
+            "https://app.example.com/auth",
+        ),
+    ];
+    let parsed = with_env(&vars, AuthServiceVariables::from_env).unwrap();
+
+    let google = &parsed.oauth_configs[&OAuth2Provider::Google];
+    assert_eq!(
+        google.default_scopes,
+        Some(vec!["openid".to_string(), "email".to_string()])
+    );
+    assert_eq!(
+        google.default_scopes_for(OAuth2Provider::Google),
+        ["openid", "email"]
+    );
+    let github = &parsed.oauth_configs[&OAuth2Provider::GitHub];
+    assert_eq!(github.default_scopes, None);
+    assert_eq!(
+        github.default_scopes_for(OAuth2Provider::GitHub),
+        OAuth2Provider::GitHub.default_scopes()
+    );
+}
+
+// --- Deferred Identifier Tests ---
+
+/// Builds the user info of a Discord account that shares no email.
+fn discord_user_info_without_email(provider_user_id: &str) -> OAuth2UserInfo {
+    OAuth2UserInfo {
+        user_id: String::new(),
+        provider: OAuth2Provider::Discord,
+        provider_user_id: provider_user_id.to_string(),
+        email: None,
+        name: Some("Wumpus".to_string()),
+        avatar_url: None,
+        verified_email: None,
+        locale: None,
+        updated_at: chrono::Utc::now().naive_utc(),
+        raw_data: None,
+        granted_scopes: vec!["identify".to_string()],
+    }
+}
+
+#[tokio::test]
+/// Tests that an OAuth2 user without email can pick an identifier later, and log in with it
+/// once they set a password.
+async fn test_complete_profile_sets_identifier_of_oauth_user() {
+    let service = tenant_test_auth_service();
+    let (user, _) = service
+        .login_from_oauth_userinfo(discord_user_info_without_email("discord-1"))
+        .await
+        .unwrap();
+    assert!(user.needs_identifier());
+
+    let completed = service
+        .complete_profile(user.id.as_str(), "wumpus")
+        .await
+        .unwrap();
+    assert!(!completed.needs_identifier());
+    let stored = service
+        .persistent_users_manager
+        .get_user_by_identifier_in_tenant("wumpus", None)
+        .await
+        .unwrap();
+    assert_eq!(stored.id, user.id);
+    assert!(stored.has_oauth_account(OAuth2Provider::Discord));
+
+    // No password yet: password logins fail like a wrong password
+    let login = |password: &str| {
+        service.login(narangcia_cryptic::auth_service::LoginMethod::Credentials {
+            identifier: "wumpus".to_string(),
+            password: password.to_string(),
+        })
+    };
+    assert!(matches!(
+        login("").await,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+    assert!(matches!(
+        service
+            .unlink_oauth_account(user.id.as_str(), OAuth2Provider::Discord, false)
+            .await,
+        Err(narangcia_cryptic::AuthError::CannotRemoveLastCredential)
+    ));
+
+    // Setting a password keeps the chosen identifier
+    service
+        .set_password(user.id.as_str(), "correct horse battery staple", false)
+        .await
+        .unwrap();
+    let (logged_in, _) = login("correct horse battery staple").await.unwrap();
+    assert_eq!(logged_in.id, user.id);
+}
+
+#[tokio::test]
+/// Tests that `complete_profile` rejects taken identifiers and users that already have one.
+async fn test_complete_profile_rejects_taken_and_existing_identifiers() {
+    let service = tenant_test_auth_service();
+    let (first, _) = service
+        .login_from_oauth_userinfo(discord_user_info_without_email("discord-1"))
+        .await
+        .unwrap();
+    let (second, _) = service
+        .login_from_oauth_userinfo(discord_user_info_without_email("discord-2"))
+        .await
+        .unwrap();
+    service
+        .complete_profile(first.id.as_str(), "wumpus")
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        service.complete_profile(second.id.as_str(), "wumpus").await,
+        Err(narangcia_cryptic::AuthError::UserAlreadyExists)
+    ));
+    assert!(matches!(
+        service.complete_profile(first.id.as_str(), "wumpus2").await,
+        Err(narangcia_cryptic::AuthError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        service.complete_profile("missing-user", "nobody").await,
+        Err(narangcia_cryptic::AuthError::UserNotFound)
+    ));
+    assert!(
+        service
+            .persistent_users_manager
+            .get_user_by_id(&second.id)
+            .await
+            .unwrap()
+            .needs_identifier()
+    );
+}
+
+// --- Claims Transformer Tests ---
+use narangcia_cryptic::core::token::claims::{AccessTokenClaims, Claims};
+use narangcia_cryptic::core::token::transformer::ClaimsTransformer;
+
+/// Renames the `internal_role` custom claim to `role`.
+struct RenameRoleTransformer;
+
+#[async_trait::async_trait]
+impl ClaimsTransformer for RenameRoleTransformer {
+    async fn transform(
+        &self,
+        claims: Box<dyn Claims + Send + Sync>,
+    ) -> Result<Box<dyn Claims + Send + Sync>, narangcia_cryptic::AuthError> {
+        let mut claims = AccessTokenClaims::from_claims(claims.as_ref());
+        if let Some(role) = claims.custom.remove("internal_role") {
+            claims.custom.insert("role".to_string(), role);
+        }
+        Ok(Box::new(claims))
+    }
+}
+
+#[tokio::test]
+/// Tests that validated claims go through the transformer, while the token keeps its claims.
+async fn test_claims_transformer_renames_claim_on_validation() {
+    let service =
+        tenant_test_auth_service().with_claims_transformer(Box::new(RenameRoleTransformer));
+    let mut custom = serde_json::Map::new();
+    custom.insert("internal_role".to_string(), serde_json::json!("staff"));
+    let tokens = service
+        .token_manager
+        .generate_token_pair_with(
+            "user-1",
+            &narangcia_cryptic::core::token::TokenOptions {
+                custom_claims: custom,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let claims = service
+        .validate_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims.get_subject(), "user-1");
+    assert_eq!(claims.get_custom("role"), Some(&serde_json::json!("staff")));
+    assert_eq!(claims.get_custom("internal_role"), None);
+
+    let raw = service
+        .token_manager
+        .validate_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(
+        raw.get_custom("internal_role"),
+        Some(&serde_json::json!("staff"))
+    );
+}
+
+// --- One-Time Access Token Tests ---
+
+#[tokio::test]
+/// Tests that a consumed access token is rejected when consumed again, but still validates
+/// normally.
+async fn test_consume_one_time_access_rejects_reuse() {
+    let service = tenant_test_auth_service();
+    let tokens = service.get_tokens("user-1").await.unwrap();
+    let other = service.get_tokens("user-1").await.unwrap();
+
+    let claims = service
+        .consume_one_time_access(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims.get_subject(), "user-1");
+    assert!(matches!(
+        service.consume_one_time_access(&tokens.access_token).await,
+        Err(narangcia_cryptic::AuthError::TokenReuseDetected)
+    ));
+
+    // Other tokens and plain validations are unaffected
+    service
+        .consume_one_time_access(&other.access_token)
+        .await
+        .unwrap();
+    service
+        .validate_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+/// Tests that a token of a tenant can only be consumed by the service of that tenant.
+async fn test_consume_one_time_access_checks_tenant() {
+    let service = tenant_test_auth_service();
+    let acme = service.for_tenant("acme");
+    let (signup, _) = credentials_methods("one-time@example.com", "password");
+    let (_, tokens) = acme.signup(signup).await.unwrap();
+
+    assert!(matches!(
+        service.consume_one_time_access(&tokens.access_token).await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    assert!(matches!(
+        service
+            .for_tenant("globex")
+            .consume_one_time_access(&tokens.access_token)
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+
+    // The rejected attempts did not consume the token
+    acme.consume_one_time_access(&tokens.access_token)
+        .await
+        .unwrap();
+    assert!(matches!(
+        acme.consume_one_time_access(&tokens.access_token).await,
+        Err(narangcia_cryptic::AuthError::TokenReuseDetected)
+    ));
+}
+
+// --- Malformed Password Hash Tests ---
+use narangcia_cryptic::core::password::MalformedHashBehavior;
+
+/// A truncated Argon2 PHC string, as left by a corrupted row.
+const CORRUPT_PHC_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$c2FsdHNhbHQ$!!corrupt!!";
+
+#[tokio::test]
+/// Tests that a corrupt stored hash is a mismatch by default, and an error when rejected.
+async fn test_argon2_verify_password_handles_malformed_hash() {
+    let manager = Argon2PasswordManager::with_params(TEST_ARGON2_PARAMS).unwrap();
+    assert!(
+        !manager
+            .verify_password("password123", CORRUPT_PHC_HASH)
+            .await
+            .unwrap()
+    );
+
+    let strict = Argon2PasswordManager::with_params(TEST_ARGON2_PARAMS)
+        .unwrap()
+        .with_malformed_hash_behavior(MalformedHashBehavior::Reject);
+    assert!(matches!(
+        strict
+            .verify_password("password123", CORRUPT_PHC_HASH)
+            .await,
+        Err(narangcia_cryptic::AuthError::VerificationError(_))
+    ));
+
+    // Well-formed hashes are unaffected
+    let hash = strict.hash_password("password123").await.unwrap();
+    assert!(strict.verify_password("password123", &hash).await.unwrap());
+}
+
+#[tokio::test]
+/// Tests that a login against a corrupt stored hash fails closed and is audited.
+async fn test_login_with_malformed_hash_fails_closed_and_is_audited() {
+    let audit_log = RecordingAuditLog::default();
+    let service = tenant_test_auth_service().with_audit_log(Box::new(audit_log.clone()));
+    let user = User::new(
+        "corrupt-user".to_string(),
+        Credentials::new(
+            "corrupt-user".to_string(),
+            "corrupt@example.com".to_string(),
+            CORRUPT_PHC_HASH.to_string(),
+        ),
+    );
+    service
+        .persistent_users_manager
+        .add_user(user)
+        .await
+        .unwrap();
+
+    let (_, login) = credentials_methods("corrupt@example.com", "password123");
+    assert!(matches!(
+        service.login(login).await,
+        Err(narangcia_cryptic::AuthError::InvalidCredentials)
+    ));
+    assert_eq!(
+        audit_log.events()[0],
+        AuditEvent::MalformedPasswordHash {
+            user_id: service.log_id(&"corrupt-user".into()),
+        }
+    );
+}
+
+#[test]
+/// Tests that `AuthServiceVariables::from_env` reads whether malformed hashes are rejected.
+fn test_auth_service_variables_from_env_reads_malformed_hash_behavior() {
+    let secret = ("CRYPTIC_SECRET_KEY", TEST_JWT_SECRET);
+    let parsed = with_env(&[secret], AuthServiceVariables::from_env).unwrap();
+    assert_eq!(
+        parsed.malformed_hash_behavior,
+        MalformedHashBehavior::Mismatch
+    );
+
+    let parsed = with_env(
+        &[secret, ("CRYPTIC_REJECT_MALFORMED_HASHES", "true")],
+        AuthServiceVariables::from_env,
+    )
+    .unwrap();
+    assert_eq!(
+        parsed.malformed_hash_behavior,
+        MalformedHashBehavior::Reject
+    );
+}
+
+// --- Signed URL Tests ---
+use narangcia_cryptic::core::signed_url::SignedUrl;
+
+#[test]
+/// Tests that a signed URL verifies and exposes its parameters, including those of the base.
+fn test_signed_url_verifies_valid_url() {
+    let signer = SignedUrl::new(TEST_JWT_SECRET.as_bytes()).unwrap();
+    let url = signer
+        .sign_url(
+            "https://files.example.com/export?format=csv",
+            &[("file", "annual report.csv"), ("user", "user-1")],
+            std::time::Duration::from_secs(300),
+        )
+        .unwrap();
+    assert!(url.starts_with("https://files.example.com/export?format=csv&file="));
+
+    let verified = signer.verify_url(&url).unwrap();
+    assert_eq!(verified.get("format"), Some("csv"));
+    assert_eq!(verified.get("file"), Some("annual report.csv"));
+    assert_eq!(verified.get("user"), Some("user-1"));
+    assert_eq!(verified.get("signature"), None);
+    assert!(verified.expires_at > chrono::Utc::now().timestamp());
+}
+
+#[test]
+/// Tests that tampered, unsigned and foreign URLs are rejected.
+fn test_signed_url_rejects_tampered_url() {
+    let signer = SignedUrl::new(TEST_JWT_SECRET.as_bytes()).unwrap();
+    let url = signer
+        .sign_url(
+            "https://files.example.com/export",
+            &[("file", "report.csv")],
+            std::time::Duration::from_secs(300),
+        )
+        .unwrap();
+    let invalid = |url: &str| {
+        matches!(
+            signer.verify_url(url),
+            Err(narangcia_cryptic::AuthError::InvalidToken(_))
+        )
+    };
+
+    assert!(invalid(&url.replace("report.csv", "secrets.csv")));
+    assert!(invalid(&url.replace("/export", "/admin")));
+    assert!(invalid(
+        &url.replace("files.example.com", "evil.example.com")
+    ));
+    assert!(invalid(&format!("{url}&extra=1")));
+    assert!(invalid("https://files.example.com/export?file=report.csv"));
+
+    let other = SignedUrl::new(b"another-secret-that-is-long-enough-for-hmac").unwrap();
+    assert!(matches!(
+        other.verify_url(&url),
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    assert!(matches!(
+        signer.sign_url(
+            "https://files.example.com/export",
+            &[("expires", "never")],
+            std::time::Duration::from_secs(300),
+        ),
+        Err(narangcia_cryptic::AuthError::InvalidInput(_))
+    ));
+}
+
+#[test]
+/// Tests that an expired URL is rejected as expired.
+fn test_signed_url_rejects_expired_url() {
+    let signer = SignedUrl::new(TEST_JWT_SECRET.as_bytes()).unwrap();
+    let url = signer
+        .sign_url(
+            "https://files.example.com/export",
+            &[("file", "report.csv")],
+            std::time::Duration::ZERO,
+        )
+        .unwrap();
+    assert!(matches!(
+        signer.verify_url(&url),
+        Err(narangcia_cryptic::AuthError::TokenExpired)
+    ));
+}
+
+// --- Refresh Strategy Tests ---
+use narangcia_cryptic::core::token::refresh::RefreshStrategy;
+
+/// Builds a test service whose default token service uses `strategy`.
+fn refresh_strategy_auth_service(strategy: RefreshStrategy) -> AuthService {
+    AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            refresh_strategy: Some(strategy),
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+/// Tests that the reuse strategy keeps returning the presented refresh token.
+async fn test_refresh_strategy_reuse_keeps_refresh_token() {
+    let auth_service = refresh_strategy_auth_service(RefreshStrategy::Reuse);
+    let (signup, _) = credentials_methods("reuse@example.com", "password");
+    let (user, tokens) = auth_service.signup(signup).await.unwrap();
+
+    for _ in 0..2 {
+        let refreshed = auth_service
+            .refresh_access_token(&tokens.refresh_token)
+            .await
+            .unwrap();
+        assert_eq!(refreshed.refresh_token, tokens.refresh_token);
+        let claims = auth_service
+            .validate_access_token(&refreshed.access_token)
+            .await
+            .unwrap();
+        assert_eq!(claims.get_subject(), user.id.as_str());
+    }
+    let enriched = auth_service
+        .refresh_access_token_enriched(&tokens.refresh_token)
+        .await
+        .unwrap();
+    assert_eq!(enriched.refresh_token, tokens.refresh_token);
+}
+
+#[tokio::test]
+/// Tests that the rotate strategy issues new refresh tokens, and that reusing one is rejected
+/// and revokes its session.
+async fn test_refresh_strategy_rotate_detects_reuse() {
+    let audit_log = RecordingAuditLog::default();
+    let auth_service = refresh_strategy_auth_service(RefreshStrategy::Rotate)
+        .with_audit_log(Box::new(audit_log.clone()));
+    let (signup, login) = credentials_methods("rotate@example.com", "password");
+    let (user, tokens) = auth_service.signup(signup).await.unwrap();
+
+    let refreshed = auth_service
+        .refresh_access_token(&tokens.refresh_token)
+        .await
+        .unwrap();
+    assert_ne!(refreshed.refresh_token, tokens.refresh_token);
+    assert!(matches!(
+        auth_service
+            .refresh_access_token(&tokens.refresh_token)
+            .await,
+        Err(narangcia_cryptic::AuthError::TokenReuseDetected)
+    ));
+    assert!(
+        audit_log
+            .events()
+            .contains(&AuditEvent::RefreshTokenReused {
+                user_id: Some(auth_service.log_id(&user.id)),
+            })
+    );
+    // The reuse may come from a thief, so the legitimate successor stops working as well
+    assert!(matches!(
+        auth_service
+            .refresh_access_token(&refreshed.refresh_token)
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    assert!(
+        auth_service
+            .validate_access_token(&refreshed.access_token)
+            .await
+            .is_err()
+    );
+
+    let (_, refreshed) = auth_service.login(login).await.unwrap();
+    let successor = auth_service
+        .refresh_access_token_enriched(&refreshed.refresh_token)
+        .await
+        .unwrap();
+    assert_ne!(successor.refresh_token, refreshed.refresh_token);
+    assert!(matches!(
+        auth_service
+            .refresh_access_token(&refreshed.refresh_token)
+            .await,
+        Err(narangcia_cryptic::AuthError::TokenReuseDetected)
+    ));
+}
+
+#[tokio::test]
+/// Tests that the grace strategy accepts reuse within the window only.
+async fn test_refresh_strategy_rotate_with_grace_window() {
+    let auth_service = refresh_strategy_auth_service(RefreshStrategy::RotateWithGrace(
+        std::time::Duration::from_secs(60),
+    ));
+    let (signup, _) = credentials_methods("grace@example.com", "password");
+    let (_, tokens) = auth_service.signup(signup).await.unwrap();
+    let first = auth_service
+        .refresh_access_token(&tokens.refresh_token)
+        .await
+        .unwrap();
+    let second = auth_service
+        .refresh_access_token(&tokens.refresh_token)
+        .await
+        .unwrap();
+    assert_ne!(first.refresh_token, tokens.refresh_token);
+    assert_ne!(second.refresh_token, tokens.refresh_token);
+
+    let auth_service =
+        refresh_strategy_auth_service(RefreshStrategy::RotateWithGrace(std::time::Duration::ZERO));
+    let (signup, _) = credentials_methods("no-grace@example.com", "password");
+    let (_, tokens) = auth_service.signup(signup).await.unwrap();
+    auth_service
+        .refresh_access_token(&tokens.refresh_token)
+        .await
+        .unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    assert!(matches!(
+        auth_service
+            .refresh_access_token(&tokens.refresh_token)
+            .await,
+        Err(narangcia_cryptic::AuthError::TokenReuseDetected)
+    ));
+}
+
+#[test]
+/// Tests that the refresh strategy is read from the environment.
+fn test_refresh_strategy_from_env() {
+    let vars = with_env(
+        &[
+            ("CRYPTIC_SECRET_KEY", TEST_JWT_SECRET),
+            ("CRYPTIC_REFRESH_STRATEGY", "rotate_with_grace"),
+            ("CRYPTIC_REFRESH_GRACE_SECS", "5"),
+        ],
+        AuthServiceVariables::from_env,
+    )
+    .unwrap();
+    assert_eq!(
+        vars.refresh_strategy,
+        Some(RefreshStrategy::RotateWithGrace(
+            std::time::Duration::from_secs(5)
+        ))
+    );
+
+    let invalid = with_env(
+        &[
+            ("CRYPTIC_SECRET_KEY", TEST_JWT_SECRET),
+            ("CRYPTIC_REFRESH_STRATEGY", "sometimes"),
+        ],
+        AuthServiceVariables::from_env,
+    );
+    assert!(matches!(
+        invalid,
+        Err(narangcia_cryptic::AuthError::ConfigError(_))
+    ));
+}
+
+// --- Role Source Tests ---
+use narangcia_cryptic::core::user::{ADMIN_ROLE, RoleSource};
+
+#[tokio::test]
+/// Tests that a role removed in the repository is immediately reflected when roles are read
+/// from it, while the token keeps the stale role.
+async fn test_user_has_role_from_repository_reflects_removal() {
+    let auth_service = tenant_test_auth_service();
+    let (signup, login) = credentials_methods("editor@example.com", "password");
+    let (mut user, _) = auth_service.signup(signup).await.unwrap();
+    user.roles = vec!["editor".to_string(), "viewer".to_string()];
+    auth_service
+        .persistent_users_manager
+        .update_user(&user)
+        .await
+        .unwrap();
+    let (_, tokens) = auth_service.login(login).await.unwrap();
+    let token = &tokens.access_token;
+    assert!(
+        auth_service
+            .user_has_role(token, "editor", None)
+            .await
+            .unwrap()
+    );
+
+    let mut stored = auth_service
+        .persistent_users_manager
+        .get_user_by_id(&user.id)
+        .await
+        .unwrap();
+    stored.roles = vec!["viewer".to_string()];
+    auth_service
+        .persistent_users_manager
+        .update_user(&stored)
+        .await
+        .unwrap();
+
+    assert!(
+        auth_service
+            .user_has_role(token, "editor", None)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !auth_service
+            .user_has_role(token, "editor", Some(RoleSource::Repository))
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        auth_service
+            .user_roles(token, Some(RoleSource::Repository))
+            .await
+            .unwrap(),
+        vec!["viewer"]
+    );
+
+    let repository_backed = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            role_source: RoleSource::Repository,
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    let (signup, login) = credentials_methods("former-admin@example.com", "password");
+    let (mut user, _) = repository_backed.signup(signup).await.unwrap();
+    user.roles = vec![ADMIN_ROLE.to_string()];
+    repository_backed
+        .persistent_users_manager
+        .update_user(&user)
+        .await
+        .unwrap();
+    let (_, tokens) = repository_backed.login(login).await.unwrap();
+    assert!(
+        repository_backed
+            .user_has_role(&tokens.access_token, ADMIN_ROLE, None)
+            .await
+            .unwrap()
+    );
+    let mut stored = repository_backed
+        .persistent_users_manager
+        .get_user_by_id(&user.id)
+        .await
+        .unwrap();
+    stored.roles.clear();
+    repository_backed
+        .persistent_users_manager
+        .update_user(&stored)
+        .await
+        .unwrap();
+    assert!(
+        !repository_backed
+            .user_has_role(&tokens.access_token, ADMIN_ROLE, None)
+            .await
+            .unwrap()
+    );
+    assert!(
+        repository_backed
+            .user_has_role(&tokens.access_token, ADMIN_ROLE, Some(RoleSource::Token))
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+/// Tests that roles are only read from tokens issued for the tenant of the service.
+async fn test_user_roles_reject_cross_tenant_token() {
+    let auth_service = tenant_test_auth_service();
+    let acme = auth_service.for_tenant("acme");
+    let globex = auth_service.for_tenant("globex");
+    let (signup, login) = credentials_methods("tenant-editor@example.com", "password");
+    let (mut user, _) = acme.signup(signup).await.unwrap();
+    user.roles = vec!["editor".to_string()];
+    auth_service
+        .persistent_users_manager
+        .update_user(&user)
+        .await
+        .unwrap();
+    let (_, tokens) = acme.login(login).await.unwrap();
+    let token = &tokens.access_token;
+
+    assert!(acme.user_has_role(token, "editor", None).await.unwrap());
+    assert_eq!(
+        acme.user_roles(token, Some(RoleSource::Repository))
+            .await
+            .unwrap(),
+        vec!["editor"]
+    );
+    assert!(matches!(
+        auth_service.user_roles(token, None).await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    assert!(matches!(
+        auth_service.user_has_role(token, "editor", None).await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    assert!(matches!(
+        globex
+            .user_has_role(token, "editor", Some(RoleSource::Repository))
+            .await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+}
+
+// --- Batch Validation Tests ---
+
+#[tokio::test]
+/// Tests that a batch of valid, malformed, forged and revoked tokens is validated in order.
+async fn test_validate_many_mixed_tokens() {
+    let auth_service = AuthService::new(
+        std::sync::Arc::new(AuthServiceVariables {
+            secret_key: TEST_JWT_SECRET.to_string(),
+            token_expiration: 60,
+            refresh_token_expiration: 120,
+            argon2_params: TEST_ARGON2_PARAMS,
+            validation_concurrency: Some(2),
+            ..Default::default()
+        }),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    let (alice_signup, _) = credentials_methods("batch-alice@example.com", "password");
+    let (bob_signup, _) = credentials_methods("batch-bob@example.com", "password");
+    let (alice, alice_tokens) = auth_service.signup(alice_signup).await.unwrap();
+    let (bob, bob_tokens) = auth_service.signup(bob_signup).await.unwrap();
+    let (revoked_signup, _) = credentials_methods("batch-revoked@example.com", "password");
+    let (revoked, revoked_tokens) = auth_service.signup(revoked_signup).await.unwrap();
+    auth_service
+        .revoke_all_for_user(revoked.id.as_str())
+        .await
+        .unwrap();
+
+    let tokens = vec![
+        alice_tokens.access_token.clone(),
+        "not-a-token".to_string(),
+        bob_tokens.access_token.clone(),
+        format!("{}x", alice_tokens.access_token),
+        revoked_tokens.access_token.clone(),
+        alice_tokens.access_token.clone(),
+    ];
+    let results = auth_service.validate_many(&tokens).await;
+    assert_eq!(results.len(), tokens.len());
+    assert_eq!(
+        results[0].as_ref().unwrap().get_subject(),
+        alice.id.as_str()
+    );
+    assert!(matches!(
+        results[1],
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    assert_eq!(results[2].as_ref().unwrap().get_subject(), bob.id.as_str());
+    assert!(results[3].is_err());
+    assert!(matches!(
+        results[4],
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    assert_eq!(
+        results[5].as_ref().unwrap().get_subject(),
+        alice.id.as_str()
+    );
+    assert!(auth_service.validate_many(&[]).await.is_empty());
+}
+
+// --- Disposable Email Tests ---
+use narangcia_cryptic::core::policy::DisposableEmailFilter;
+
+#[test]
+/// Tests that the filter matches denied domains and their subdomains, ignoring case.
+fn test_disposable_email_filter_matches_domains() {
+    let filter = DisposableEmailFilter::from_list(
+        "# Throwaway providers\nmailinator.com\n\n  Guerrillamail.com.\n",
+    );
+    assert_eq!(filter.len(), 2);
+    assert!(filter.is_disposable("someone@mailinator.com"));
+    assert!(filter.is_disposable("someone@MAILINATOR.com"));
+    assert!(filter.is_disposable("someone@eu.guerrillamail.com"));
+    assert!(!filter.is_disposable("someone@example.com"));
+    assert!(!filter.is_disposable("someone@notmailinator.com"));
+    assert!(!filter.is_disposable("mailinator.com"));
+    assert!(matches!(
+        filter.check("someone@mailinator.com"),
+        Err(narangcia_cryptic::AuthError::DisposableEmailNotAllowed)
+    ));
+    assert!(filter.check("someone@example.com").is_ok());
+}
+
+#[tokio::test]
+/// Tests that credentials signups and OAuth2 accounts with disposable emails are rejected,
+/// while other addresses are accepted.
+async fn test_signup_rejects_disposable_emails() {
+    let service = tenant_test_auth_service()
+        .with_disposable_email_filter(DisposableEmailFilter::new(["mailinator.com"]));
+
+    let (blocked, _) = credentials_methods("throwaway@mailinator.com", "password");
+    assert!(matches!(
+        service.signup(blocked).await,
+        Err(narangcia_cryptic::AuthError::DisposableEmailNotAllowed)
+    ));
+    let (allowed, _) = credentials_methods("ada@example.com", "password");
+    assert!(service.signup(allowed).await.is_ok());
+    let (username, _) = credentials_methods("mailinator.com", "password");
+    assert!(service.signup(username).await.is_ok());
+
+    let blocked_info = hand_built_google_user_info("google-throwaway", "x@mailinator.com");
+    assert!(matches!(
+        service.login_from_oauth_userinfo(blocked_info).await,
+        Err(narangcia_cryptic::AuthError::DisposableEmailNotAllowed)
+    ));
+    let allowed_info = hand_built_google_user_info("google-ada", "ada@example.com");
+    let (linked, _) = service
+        .login_from_oauth_userinfo(allowed_info)
+        .await
+        .unwrap();
+    assert!(linked.has_oauth_account(OAuth2Provider::Google));
+}
+
+// --- Challenge Verification Tests ---
+use narangcia_cryptic::core::challenge::ChallengeVerifier;
+
+/// Accepts a single challenge token and counts verifications.
+#[derive(Clone, Default)]
+struct StaticChallengeVerifier {
+    accepted: &'static str,
+    calls: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl ChallengeVerifier for StaticChallengeVerifier {
+    async fn verify(&self, token: &str) -> Result<(), narangcia_cryptic::AuthError> {
+        self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        if token == self.accepted {
+            Ok(())
+        } else {
+            Err(narangcia_cryptic::AuthError::ChallengeFailed(
+                "invalid-input-response".to_string(),
+            ))
+        }
+    }
+}
+
+#[tokio::test]
+/// Tests that signups and logins with a passing challenge go through.
+async fn test_challenge_verifier_accepts_solved_challenge() {
+    let verifier = StaticChallengeVerifier {
+        accepted: "solved",
+        ..Default::default()
+    };
+    let service = tenant_test_auth_service().with_challenge_verifier(Box::new(verifier.clone()));
+    let (signup, login) = credentials_methods("human@example.com", "password");
+
+    let (user, _) = service
+        .signup_with_challenge(signup, Some("solved"))
+        .await
+        .unwrap();
+    let (logged_in, _) = service
+        .login_with_challenge(login, Some("solved"))
+        .await
+        .unwrap();
+    assert_eq!(logged_in.id, user.id);
+    assert_eq!(verifier.calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+/// Tests that failed or missing challenges are rejected before the credentials are checked.
+async fn test_challenge_verifier_rejects_failed_challenge() {
+    let verifier = StaticChallengeVerifier {
+        accepted: "solved",
+        ..Default::default()
+    };
+    let service = tenant_test_auth_service().with_challenge_verifier(Box::new(verifier.clone()));
+    let (signup, _) = credentials_methods("bot@example.com", "password");
+    assert!(matches!(
+        service
+            .signup_with_challenge(signup.clone(), Some("forged"))
+            .await,
+        Err(narangcia_cryptic::AuthError::ChallengeFailed(_))
+    ));
+    assert!(matches!(
+        service.signup_with_challenge(signup.clone(), None).await,
+        Err(narangcia_cryptic::AuthError::ChallengeFailed(_))
+    ));
+    assert!(
+        service
+            .persistent_users_manager
+            .get_user_by_identifier("bot@example.com")
+            .await
+            .is_none()
+    );
+
+    service.signup(signup).await.unwrap();
+    let (_, wrong_login) = credentials_methods("bot@example.com", "wrong-password");
+    assert!(matches!(
+        service
+            .login_with_challenge(wrong_login, Some("forged"))
+            .await,
+        Err(narangcia_cryptic::AuthError::ChallengeFailed(_))
+    ));
+    assert_eq!(verifier.calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+/// Tests that the tenant challenge variants verify the challenge and stay in the tenant.
+async fn test_tenant_challenge_verifier() {
+    let verifier = StaticChallengeVerifier {
+        accepted: "solved",
+        ..Default::default()
+    };
+    let service = tenant_test_auth_service().with_challenge_verifier(Box::new(verifier.clone()));
+    let tenant = service.for_tenant("acme");
+    let (signup, login) = credentials_methods("tenant-human@example.com", "password");
+    assert!(matches!(
+        tenant.signup_with_challenge(signup.clone(), None).await,
+        Err(narangcia_cryptic::AuthError::ChallengeFailed(_))
+    ));
+
+    let (user, _) = tenant
+        .signup_with_challenge(signup, Some("solved"))
+        .await
+        .unwrap();
+    assert_eq!(user.tenant_id.as_deref(), Some("acme"));
+    let (logged_in, _) = tenant
+        .login_with_challenge(login.clone(), Some("solved"))
+        .await
+        .unwrap();
+    assert_eq!(logged_in.id, user.id);
+    assert!(matches!(
+        tenant.login_with_challenge(login, Some("forged")).await,
+        Err(narangcia_cryptic::AuthError::ChallengeFailed(_))
+    ));
+    assert_eq!(verifier.calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+}
+
+// --- Access Token Only Tests ---
+
+#[tokio::test]
+/// Tests that access-token-only issuance returns a valid access token and no refresh token.
+async fn test_access_token_only_issues_no_refresh_token() {
+    let events = RecordingAuditLog::default();
+    let service = tenant_test_auth_service().with_audit_log(Box::new(events.clone()));
+    let (signup, login) = credentials_methods("api-client@example.com", "password");
+    let (user, _) = service.signup(signup).await.unwrap();
+
+    let (logged_in, access_token) = service.login_access_token_only(login).await.unwrap();
+    assert_eq!(logged_in.id, user.id);
+    let claims = service.validate_access_token(&access_token).await.unwrap();
+    assert_eq!(claims.get_subject(), user.id.as_str());
+    assert_eq!(claims.get_token_type(), Some("access"));
+    assert!(
+        service
+            .token_manager
+            .validate_refresh_token(&access_token)
+            .await
+            .is_err()
+    );
+    assert!(service.refresh_access_token(&access_token).await.is_err());
+    assert!(events.events().iter().any(|event| matches!(
+        event,
+        narangcia_cryptic::core::audit::AuditEvent::LoginSucceeded { user_id, .. }
+            if *user_id == service.log_id(&user.id)
+    )));
+
+    let access_token = service
+        .get_access_token_only(user.id.clone())
+        .await
+        .unwrap();
+    let claims = service.validate_access_token(&access_token).await.unwrap();
+    assert_eq!(claims.get_subject(), user.id.as_str());
+}
+
+#[tokio::test]
+/// Tests that access-token-only issuance keeps the tenant of a tenant user.
+async fn test_access_token_only_keeps_tenant() {
+    let service = tenant_test_auth_service();
+    let tenant = service.for_tenant("acme");
+    let (signup, _) = credentials_methods("api-tenant@example.com", "password");
+    let (user, _) = tenant.signup(signup).await.unwrap();
+
+    let access_token = service
+        .get_access_token_only(user.id.clone())
+        .await
+        .unwrap();
+    assert!(matches!(
+        service.validate_access_token(&access_token).await,
+        Err(narangcia_cryptic::AuthError::InvalidToken(_))
+    ));
+    let claims = tenant.validate_access_token(&access_token).await.unwrap();
+    assert_eq!(claims.get_tenant_id(), Some("acme"));
+}